@@ -1,21 +1,100 @@
 use crate::app::{App, AppAction, AppActionComplete}; // <-- Add AppActionComplete
+use crate::backup::{self, BackupProgress, BackupStage, BackupStatus};
+use crate::boot;
+use crate::clock;
+use crate::connectivity;
+use crate::coredump;
+use crate::demo;
+use crate::dev;
+use crate::diagnostics;
+use crate::environment;
+use crate::faucet::{self, FaucetStatus};
+use crate::files;
+use crate::firewall;
+use crate::fleet;
+use crate::integrity;
+use crate::ip_config;
+use crate::leadership;
+use crate::modem;
+use crate::network_status;
+use crate::node_config::{self, ConfigStatus};
+use crate::offline_update;
+use crate::oplock::{self, Operation};
+use crate::peers;
+use crate::pools;
+use crate::port_check::{self, PortCheckConfig};
+use crate::power;
+use crate::processes;
+use crate::restore::{self, RestoreStatus};
+use crate::rtc;
 use crate::screens::WifiConnectionStatus;
-use crate::systemd;
+use crate::stake_pool;
+use crate::storage;
+use crate::systemd::{self, units};
+use crate::tasks::{TaskHandle, TaskKind};
+use crate::telemetry;
+use crate::thermal;
+use crate::ticker;
+use crate::topology;
+use crate::update_history::{self, ReleaseNotes};
+use crate::usb;
+use crate::vpn;
+use crate::webhook;
 use crate::wifi;
+use anyhow::Result;
+use std::future::Future;
 use std::time::Duration;
+use tracing::{info, warn};
 
-pub async fn handle_action(app: &mut App, effect: AppAction) {
+/// Runs a long operation as a task, listed on the tasks screen.
+fn spawn_task<T, Fut>(
+    app: &mut App,
+    kind: TaskKind,
+    task: impl FnOnce(TaskHandle) -> Fut,
+    complete: impl FnOnce(Result<T>) -> AppActionComplete + Send + 'static,
+) where
+    T: Send + 'static,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+{
+    let info = app.tasks.spawn(kind, app.action_tx.clone(), task, complete);
+    app.system_state.tasks.insert(0, info);
+}
+
+/// Starts `effect` in the background, its result comes back through
+/// `app.action_tx`. Never waits, not to hold up the frame being drawn.
+pub fn handle_action(app: &mut App, effect: AppAction) {
     match effect {
-        // TODO: These should be in background threads
         AppAction::CheckNetworkStatus => {
-            app.system_state.network_status = app.connectivity_cache.get().await;
+            if !app.connectivity_cache.start_check() {
+                return;
+            }
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(network_status::check_network_status_or_unknown)
+                    .await
+                {
+                    Ok(status) => {
+                        let _ = tx.send(AppActionComplete::NetworkStatus(status)).await;
+                    }
+                    Err(e) => warn!("Failed to check the network: {}", e),
+                }
+            });
+        }
+        AppAction::CheckAmaruStatus if dev::is_mocked() => {
+            app.system_state.amaru_status = dev::mock_node().service_info();
         }
         AppAction::CheckAmaruStatus => {
-            app.system_state.amaru_status = tokio::task::spawn_blocking(|| {
-                systemd::get_systemd_service_info("amaru").unwrap_or_default()
-            })
-            .await
-            .unwrap_or_default();
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let info = tokio::task::spawn_blocking(|| {
+                    systemd::get_systemd_service_info("amaru").unwrap_or_default()
+                })
+                .await
+                .unwrap_or_default();
+                let _ = tx.send(AppActionComplete::AmaruStatus(info)).await;
+            });
         }
         AppAction::ConnectToWifi(ssid, pw) => {
             app.system_state.wifi_connection_status = WifiConnectionStatus::Connecting;
@@ -39,6 +118,880 @@ pub async fn handle_action(app: &mut App, effect: AppAction) {
                     .await;
             });
         }
-        AppAction::Quit => {}
+        AppAction::RefreshSavedNetworks => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(wifi::list_saved_networks).await {
+                    Ok(Ok(networks)) => {
+                        let _ = tx.send(AppActionComplete::SavedNetworks(networks)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to list saved networks: {}", e),
+                    Err(e) => warn!("Failed to list saved networks: {}", e),
+                }
+            });
+        }
+        AppAction::SetNetworkOrder(names) => {
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = wifi::set_network_order(&names) {
+                    warn!("Failed to save network order: {}", e);
+                }
+            });
+        }
+        AppAction::SwitchNetwork(name) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result = tokio::task::spawn_blocking(move || {
+                    wifi::switch_network(&name)?;
+                    wifi::list_saved_networks()
+                })
+                .await;
+
+                let complete = match result {
+                    Ok(Ok(networks)) => AppActionComplete::SavedNetworks(networks),
+                    Ok(Err(e)) => AppActionComplete::NetworkSwitchFailed(e.to_string()),
+                    Err(e) => AppActionComplete::NetworkSwitchFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::CheckVpn => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(vpn::detect).await {
+                    Ok(Ok(status)) => {
+                        let _ = tx.send(AppActionComplete::Vpn(status)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to check the VPN: {}", e),
+                    Err(e) => warn!("Failed to check the VPN: {}", e),
+                }
+            });
+        }
+        AppAction::SetVpn(backend, up) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result = tokio::task::spawn_blocking(move || {
+                    info!("Setting {} up: {}", backend.name(), up);
+                    vpn::set_up(&backend, up)?;
+                    vpn::detect()
+                })
+                .await;
+
+                let complete = match result {
+                    Ok(Ok(status)) => AppActionComplete::Vpn(status),
+                    Ok(Err(e)) => AppActionComplete::VpnFailed(e.to_string()),
+                    Err(e) => AppActionComplete::VpnFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::CheckLinks => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(connectivity::default_route_links).await {
+                    Ok(Ok(links)) => {
+                        // Only worth the traffic while on the cellular link
+                        let primary_reachable = if links.first().is_some_and(|l| l.is_metered()) {
+                            match tokio::task::spawn_blocking(connectivity::primary_device).await {
+                                Ok(Ok(Some(device))) => {
+                                    Some(connectivity::probe_through(&device).await)
+                                }
+                                _ => None,
+                            }
+                        } else {
+                            None
+                        };
+                        let _ = tx
+                            .send(AppActionComplete::Links {
+                                links,
+                                primary_reachable,
+                            })
+                            .await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to check links: {}", e),
+                    Err(e) => warn!("Failed to check links: {}", e),
+                }
+            });
+        }
+        AppAction::CheckCaptivePortal => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match connectivity::detect_captive_portal().await {
+                    Ok(portal) => {
+                        let _ = tx.send(AppActionComplete::CaptivePortal(portal)).await;
+                    }
+                    // Most likely no connectivity at all, not a portal
+                    Err(e) => warn!("Failed to probe for a captive portal: {}", e),
+                }
+            });
+        }
+        AppAction::SetFailover(active) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result = tokio::task::spawn_blocking(move || modem::set_failover(active)).await;
+                let error = match result {
+                    Ok(Ok(())) => return,
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) => e.to_string(),
+                };
+                warn!("Failed to switch the LTE failover: {}", error);
+                if active {
+                    let _ = tx.send(AppActionComplete::FailoverFailed).await;
+                }
+            });
+        }
+        AppAction::CheckModemStatus => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(modem::check_modem_status).await {
+                    Ok(Ok(status)) => {
+                        let _ = tx.send(AppActionComplete::Modem(status)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to check modem status: {}", e),
+                    Err(e) => warn!("Failed to check modem status: {}", e),
+                }
+            });
+        }
+        AppAction::SetApn(apn) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result = tokio::task::spawn_blocking(move || {
+                    modem::configure_apn(&apn)?;
+                    modem::check_modem_status()
+                })
+                .await;
+
+                let complete = match result {
+                    Ok(Ok(status)) => AppActionComplete::Modem(status),
+                    Ok(Err(e)) => AppActionComplete::ApnFailed(e.to_string()),
+                    Err(e) => AppActionComplete::ApnFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::RefreshPeers if demo::is_active() => {
+            app.system_state.peers = demo::peers();
+        }
+        AppAction::RefreshPeers => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(peers::locate_peers).await {
+                    Ok(Ok(snapshot)) => {
+                        let _ = tx.send(AppActionComplete::Peers(snapshot)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to locate peers: {}", e),
+                    Err(e) => warn!("Failed to locate peers: {}", e),
+                }
+            });
+        }
+        AppAction::SampleLatency => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(peers::peer_latencies).await {
+                    Ok(Ok(samples)) => {
+                        let _ = tx.send(AppActionComplete::LatencySamples(samples)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to sample peer latencies: {}", e),
+                    Err(e) => warn!("Failed to sample peer latencies: {}", e),
+                }
+            });
+        }
+        AppAction::AnalyzeBoot => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(boot::analyze_boot).await {
+                    Ok(Ok(report)) => {
+                        let _ = tx.send(AppActionComplete::Boot(report)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to analyze boot: {}", e),
+                    Err(e) => warn!("Failed to analyze boot: {}", e),
+                }
+            });
+        }
+        AppAction::CheckUnits => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(units::check_all).await {
+                    Ok(units) => {
+                        let _ = tx.send(AppActionComplete::Units(units)).await;
+                    }
+                    Err(e) => warn!("Failed to check unit files: {}", e),
+                }
+            });
+        }
+        AppAction::VerifyBinaries => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(integrity::verify).await {
+                    Ok(Ok(modified)) => {
+                        let _ = tx.send(AppActionComplete::BinariesVerified(modified)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to verify binaries: {}", e),
+                    Err(e) => warn!("Failed to verify binaries: {}", e),
+                }
+            });
+        }
+        AppAction::SampleStorage => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(storage::sample).await {
+                    Ok(Ok(sample)) => {
+                        let _ = tx.send(AppActionComplete::Storage(sample)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to sample storage: {}", e),
+                    Err(e) => warn!("Failed to sample storage: {}", e),
+                }
+            });
+        }
+        AppAction::CheckPower => {
+            let Some(config) = app.ups_config.clone() else {
+                return;
+            };
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(move || power::read(&config)).await {
+                    Ok(Ok(status)) => {
+                        let _ = tx.send(AppActionComplete::Power(status)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to read the UPS: {}", e),
+                    Err(e) => warn!("Failed to read the UPS: {}", e),
+                }
+            });
+        }
+        AppAction::StopNodeOnLowBattery(percent) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result =
+                    match tokio::task::spawn_blocking(move || power::shut_down_node(percent)).await
+                    {
+                        Ok(Ok(())) => AppActionComplete::NodeStoppedOnLowBattery,
+                        Ok(Err(e)) => AppActionComplete::NodeStopFailed(e.to_string()),
+                        Err(e) => AppActionComplete::NodeStopFailed(e.to_string()),
+                    };
+                let _ = tx.send(result).await;
+            });
+        }
+        AppAction::CheckClock => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(clock::status).await {
+                    Ok(Ok(status)) => {
+                        if status.synchronized {
+                            match tokio::task::spawn_blocking(rtc::save_system_time).await {
+                                Ok(Ok(true)) => info!("RTC set from the clock"),
+                                Ok(Ok(false)) => {}
+                                Ok(Err(e)) => warn!("Failed to set the RTC: {}", e),
+                                Err(e) => warn!("Failed to set the RTC: {}", e),
+                            }
+                        }
+                        let _ = tx.send(AppActionComplete::Clock(status)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to check the clock: {}", e),
+                    Err(e) => warn!("Failed to check the clock: {}", e),
+                }
+            });
+        }
+        AppAction::ListTimezones => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(clock::list_timezones).await {
+                    Ok(Ok(timezones)) => {
+                        let _ = tx.send(AppActionComplete::Timezones(timezones)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to list time zones: {}", e),
+                    Err(e) => warn!("Failed to list time zones: {}", e),
+                }
+            });
+        }
+        AppAction::SetTimezone(timezone) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result = tokio::task::spawn_blocking(move || {
+                    clock::set_timezone(&timezone)?;
+                    clock::status()
+                })
+                .await;
+
+                let complete = match result {
+                    Ok(Ok(status)) => AppActionComplete::Clock(status),
+                    Ok(Err(e)) => AppActionComplete::TimezoneFailed(e.to_string()),
+                    Err(e) => AppActionComplete::TimezoneFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::ReadIpConfig => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let read = || ip_config::read(&ip_config::interface());
+                let settings = match tokio::task::spawn_blocking(read).await {
+                    Ok(result) => result.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                let _ = tx.send(AppActionComplete::IpConfig(settings)).await;
+            });
+        }
+        AppAction::ApplyIpConfig(settings) => {
+            app.system_state.ip_config.applying = true;
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result = tokio::task::spawn_blocking(move || {
+                    let interface = ip_config::interface();
+                    info!("Configuring {} with {:?}", interface, settings);
+                    ip_config::apply(&interface, &settings)?;
+                    ip_config::read(&interface)
+                })
+                .await;
+
+                let complete = match result {
+                    Ok(Ok(settings)) => AppActionComplete::IpConfig(Ok(settings)),
+                    Ok(Err(e)) => AppActionComplete::IpConfigFailed(e.to_string()),
+                    Err(e) => AppActionComplete::IpConfigFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::CheckFirewall => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let status = match tokio::task::spawn_blocking(firewall::status).await {
+                    Ok(result) => result.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                let _ = tx.send(AppActionComplete::Firewall(status)).await;
+            });
+        }
+        AppAction::ApplyFirewallPreset(preset) => {
+            app.system_state.firewall.applying = true;
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let result = tokio::task::spawn_blocking(move || {
+                    info!("Applying the firewall preset {:?}", preset);
+                    firewall::apply(preset)?;
+                    firewall::status()
+                })
+                .await;
+
+                let complete = match result {
+                    Ok(Ok(status)) => AppActionComplete::Firewall(Ok(status)),
+                    Ok(Err(e)) => AppActionComplete::FirewallFailed(e.to_string()),
+                    Err(e) => AppActionComplete::FirewallFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::CheckThermal => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(thermal::sample).await {
+                    Ok(status) => {
+                        let _ = tx.send(AppActionComplete::Thermal(status)).await;
+                    }
+                    Err(e) => warn!("Failed to check the temperature: {}", e),
+                }
+            });
+        }
+        AppAction::ReadEnvironment => {
+            let Some(sensor) = app.environment_sensor else {
+                return;
+            };
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(move || environment::read(sensor)).await {
+                    Ok(Ok(reading)) => {
+                        let _ = tx.send(AppActionComplete::Environment(reading)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to read the {:?} sensor: {}", sensor, e),
+                    Err(e) => warn!("Failed to read the {:?} sensor: {}", sensor, e),
+                }
+            });
+        }
+        AppAction::RefreshCoredumps => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(coredump::list).await {
+                    Ok(Ok(dumps)) => {
+                        let _ = tx.send(AppActionComplete::Coredumps(dumps)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to list core dumps: {}", e),
+                    Err(e) => warn!("Failed to list core dumps: {}", e),
+                }
+            });
+        }
+        AppAction::ExportCoredump(pid) => spawn_task(
+            app,
+            TaskKind::CoredumpExport,
+            move |_| async move { tokio::task::spawn_blocking(move || coredump::export(pid)).await? },
+            |result| match result {
+                Ok(name) => AppActionComplete::CoredumpExported(name),
+                Err(e) => AppActionComplete::CoredumpExportFailed(e.to_string()),
+            },
+        ),
+        AppAction::ExportDiagnostics => spawn_task(
+            app,
+            TaskKind::Diagnostics,
+            |_| diagnostics::export(),
+            |result| match result {
+                Ok(location) => AppActionComplete::DiagnosticsExported(location),
+                Err(e) => AppActionComplete::DiagnosticsExportFailed(e.to_string()),
+            },
+        ),
+        AppAction::StageOfflineUpdates => spawn_task(
+            app,
+            TaskKind::OfflineUpdate,
+            |_| async { tokio::task::spawn_blocking(offline_update::stage_from_usb).await? },
+            |result| match result {
+                Ok(staged) => AppActionComplete::OfflineUpdatesStaged(staged),
+                Err(e) => AppActionComplete::OfflineUpdatesFailed(e.to_string()),
+            },
+        ),
+        AppAction::DetectUsb => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match tokio::task::spawn_blocking(usb::is_present).await {
+                    Ok(present) => {
+                        let _ = tx.send(AppActionComplete::UsbDetected(present)).await;
+                    }
+                    Err(e) => warn!("Failed to detect USB drives: {}", e),
+                }
+            });
+        }
+        AppAction::BackUp => {
+            if app.system_state.backup.is_running() {
+                return;
+            }
+            // Keeps the screen from requesting the backup twice
+            app.system_state.backup =
+                BackupStatus::Running(BackupProgress::of(BackupStage::Waiting));
+            let tx = app.action_tx.clone();
+
+            spawn_task(
+                app,
+                TaskKind::Backup,
+                move |handle| async move {
+                    let back_up = move || {
+                        backup::back_up(
+                            |progress| {
+                                if let Some(ratio) = progress.ratio() {
+                                    handle.progress(ratio);
+                                }
+                                let _ =
+                                    tx.blocking_send(AppActionComplete::BackupProgress(progress));
+                            },
+                            || handle.is_cancelled(),
+                        )
+                    };
+                    tokio::task::spawn_blocking(back_up).await?
+                },
+                |result| match result {
+                    Ok(record) => AppActionComplete::BackedUp(record),
+                    Err(e) => AppActionComplete::BackupFailed(e.to_string()),
+                },
+            );
+        }
+        AppAction::RefreshSnapshots => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let mut snapshots = match tokio::task::spawn_blocking(restore::list_usb).await {
+                    Ok(Ok(snapshots)) => snapshots,
+                    Ok(Err(e)) => {
+                        warn!("Failed to list snapshots on the USB drive: {}", e);
+                        Vec::new()
+                    }
+                    Err(e) => {
+                        warn!("Failed to list snapshots on the USB drive: {}", e);
+                        Vec::new()
+                    }
+                };
+                match restore::list_remote().await {
+                    Ok(remote) => snapshots.extend(remote),
+                    Err(e) => warn!("Failed to fetch the snapshot index: {}", e),
+                }
+                let _ = tx.send(AppActionComplete::Snapshots(snapshots)).await;
+            });
+        }
+        AppAction::Restore(snapshot) => {
+            if app.system_state.restore.is_running() {
+                return;
+            }
+            // Keeps the screen from requesting the restore twice
+            app.system_state.restore =
+                RestoreStatus::Running(BackupProgress::of(BackupStage::Waiting));
+            let tx = app.action_tx.clone();
+
+            spawn_task(
+                app,
+                TaskKind::Restore,
+                move |handle| async move {
+                    let name = snapshot.name.clone();
+                    let fetch_tx = tx.clone();
+                    let fetch_handle = handle.clone();
+                    // Only the download can be cancelled, it resumes later
+                    let fetch = restore::fetch(&snapshot, move |progress| {
+                        if let Some(ratio) = progress.ratio() {
+                            fetch_handle.progress(ratio);
+                        }
+                        // Dropping updates is fine when the UI lags behind
+                        let _ = fetch_tx.try_send(AppActionComplete::RestoreProgress(progress));
+                    });
+                    handle.unless_cancelled(fetch).await?;
+                    let restore = move || {
+                        restore::restore(
+                            &snapshot,
+                            |progress| {
+                                if let Some(ratio) = progress.ratio() {
+                                    handle.progress(ratio);
+                                }
+                                let _ =
+                                    tx.blocking_send(AppActionComplete::RestoreProgress(progress));
+                            },
+                            || handle.is_cancelled(),
+                        )
+                    };
+                    tokio::task::spawn_blocking(restore).await??;
+                    Ok(name)
+                },
+                |result| match result {
+                    Ok(name) => AppActionComplete::Restored(name),
+                    Err(e) => AppActionComplete::RestoreFailed(e.to_string()),
+                },
+            );
+        }
+        AppAction::RefreshProcesses => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let sample = || processes::sample(processes::SAMPLE_INTERVAL);
+                match tokio::task::spawn_blocking(sample).await {
+                    Ok(Ok(processes)) => {
+                        let _ = tx.send(AppActionComplete::Processes(processes)).await;
+                    }
+                    Ok(Err(e)) => warn!("Failed to sample processes: {}", e),
+                    Err(e) => warn!("Failed to sample processes: {}", e),
+                }
+            });
+        }
+        AppAction::FetchProcessLogs(pid, unit) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let journal = move || processes::journal(pid, unit.as_deref());
+                let complete = match tokio::task::spawn_blocking(journal).await {
+                    Ok(Ok(lines)) => AppActionComplete::ProcessLogs(pid, lines),
+                    Ok(Err(e)) => AppActionComplete::ProcessLogsFailed(e.to_string()),
+                    Err(e) => AppActionComplete::ProcessLogsFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::Browse(path) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let complete = match tokio::task::spawn_blocking(move || files::browse(&path)).await
+                {
+                    Ok(Ok(listing)) => AppActionComplete::Listing(listing),
+                    Ok(Err(e)) => AppActionComplete::BrowseFailed(e.to_string()),
+                    Err(e) => AppActionComplete::BrowseFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::DeleteFile(path) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let delete = move || {
+                    files::delete(&path)?;
+                    // Shows the directory without the file
+                    let parent = path.parent().unwrap_or(&path);
+                    let listing = files::browse(parent)?;
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    anyhow::Ok((name, listing))
+                };
+                match tokio::task::spawn_blocking(delete).await {
+                    Ok(Ok((name, listing))) => {
+                        let _ = tx.send(AppActionComplete::FileDeleted(name)).await;
+                        let _ = tx.send(AppActionComplete::Listing(listing)).await;
+                    }
+                    Ok(Err(e)) => {
+                        let _ = tx
+                            .send(AppActionComplete::BrowseFailed(e.to_string()))
+                            .await;
+                    }
+                    Err(e) => warn!("Failed to delete file: {}", e),
+                }
+            });
+        }
+        AppAction::SaveNodeConfig(changes) => {
+            app.system_state.node_config_status = ConfigStatus::Saving;
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let save = move || node_config::save(&changes);
+                let complete = match tokio::task::spawn_blocking(save).await {
+                    Ok(Ok(())) => AppActionComplete::NodeConfigSaved,
+                    Ok(Err(e)) => AppActionComplete::NodeConfigFailed(e.to_string()),
+                    Err(e) => AppActionComplete::NodeConfigFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::RestartNode => {
+            app.system_state.node_config_status = ConfigStatus::Restarting;
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let restart = || {
+                    let _lock = oplock::try_acquire(Operation::NodeRestart)?;
+                    systemd::restart_service("amaru.service")
+                };
+                let complete = match tokio::task::spawn_blocking(restart).await {
+                    Ok(Ok(())) => AppActionComplete::NodeRestarted,
+                    Ok(Err(e)) => AppActionComplete::NodeRestartFailed(e.to_string()),
+                    Err(e) => AppActionComplete::NodeRestartFailed(e.to_string()),
+                };
+                let _ = tx.send(complete).await;
+            });
+        }
+        AppAction::Power(action) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                // Only returns on failure, unless restarting the UI
+                let failed = match tokio::task::spawn_blocking(move || power::run(action)).await {
+                    Ok(Ok(())) => None,
+                    Ok(Err(e)) => Some(e.to_string()),
+                    Err(e) => Some(e.to_string()),
+                };
+                if let Some(e) = failed {
+                    let _ = tx.send(AppActionComplete::PowerFailed(e)).await;
+                }
+            });
+        }
+        AppAction::TestPeer(address) => {
+            // Shows the peer as being tested again
+            app.system_state.peer_tests.remove(&address);
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let test_address = address.clone();
+                match tokio::task::spawn_blocking(move || topology::test_peer(&test_address)).await
+                {
+                    Ok(reachability) => {
+                        let _ = tx
+                            .send(AppActionComplete::PeerTested(address, reachability))
+                            .await;
+                    }
+                    Err(e) => warn!("Failed to test peer {}: {}", address, e),
+                }
+            });
+        }
+        AppAction::CheckPort => {
+            if app.system_state.port_check.checking {
+                return;
+            }
+            app.system_state.port_check.checking = true;
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let check = port_check::check(&PortCheckConfig::from_env()).await;
+                info!("Port {} check: {:?}", check.port, check.verdict);
+                let _ = tx.send(AppActionComplete::PortChecked(check)).await;
+            });
+        }
+        AppAction::RefreshPools if demo::is_active() => {
+            app.system_state.pools = demo::pools();
+        }
+        AppAction::CheckUpdates => {
+            info!("Checking for updates");
+            tokio::task::spawn_blocking(|| {
+                if let Err(e) = systemd::start_service("updater.service") {
+                    warn!("Failed to start the updater: {}", e);
+                }
+            });
+        }
+        AppAction::RefreshPools => {
+            let config = app.pools_config.clone();
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match pools::fetch_pools(&config).await {
+                    Ok(snapshot) => {
+                        if let Err(e) = pools::write_cache(&snapshot) {
+                            warn!("Failed to write pools cache: {}", e);
+                        }
+                        let _ = tx.send(AppActionComplete::Pools(snapshot)).await;
+                    }
+                    // Keep showing the cached data
+                    Err(e) => warn!("Failed to fetch watched pools: {}", e),
+                }
+            });
+        }
+        AppAction::RefreshStakePool => {
+            let config = app.stake_pool_config.clone();
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match stake_pool::fetch(&config).await {
+                    Ok(summary) => {
+                        if let Err(e) = stake_pool::write_cache(&summary) {
+                            warn!("Failed to write stake pool cache: {}", e);
+                        }
+                        let _ = tx.send(AppActionComplete::StakePool(summary)).await;
+                    }
+                    // Keep showing the cached figures
+                    Err(e) => warn!("Failed to fetch the stake pool: {}", e),
+                }
+            });
+        }
+        AppAction::RefreshTicker => {
+            let config = app.ticker_config.clone();
+            let snapshot = app.system_state.ticker.clone();
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let snapshot = ticker::refresh(&config, snapshot).await;
+                if let Err(e) = ticker::write_cache(&snapshot) {
+                    warn!("Failed to write ticker cache: {}", e);
+                }
+                let _ = tx.send(AppActionComplete::Ticker(snapshot)).await;
+            });
+        }
+        AppAction::RefreshFleet => {
+            let config = app.fleet_config.clone();
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                let results = fleet::fetch_fleet(&config).await;
+                let _ = tx.send(AppActionComplete::Fleet(results)).await;
+            });
+        }
+        AppAction::RefreshLeadership if demo::is_active() => {
+            app.system_state.leadership = demo::leadership();
+        }
+        AppAction::RefreshLeadership => {
+            let config = app.leadership_config.clone();
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match leadership::fetch(&config).await {
+                    Ok(schedule) => {
+                        let _ = tx.send(AppActionComplete::Leadership(schedule)).await;
+                    }
+                    Err(e) => warn!("Failed to read leadership schedule: {}", e),
+                }
+            });
+        }
+        AppAction::FetchReleaseNotes(app_name, version, repo) => {
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match update_history::release_notes(&repo, &version).await {
+                    Ok(body) => {
+                        let notes = ReleaseNotes {
+                            app: app_name,
+                            version,
+                            body,
+                        };
+                        let _ = tx.send(AppActionComplete::ReleaseNotes(notes)).await;
+                    }
+                    Err(e) => warn!("Failed to fetch the notes of {} {}: {}", repo, version, e),
+                }
+            });
+        }
+        AppAction::RequestFaucetFunds => {
+            app.system_state.faucet = FaucetStatus::Requesting;
+            let config = app.faucet_config.clone();
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                // Arrival is detected by the balance growing
+                let balance_before = match faucet::fetch_balance(&config).await {
+                    Ok(balance) => balance,
+                    Err(e) => {
+                        warn!("Failed to check faucet address balance: {}", e);
+                        0
+                    }
+                };
+
+                let final_status = match faucet::request_funds(&config).await {
+                    Ok(tx_id) => {
+                        let _ = tx
+                            .send(AppActionComplete::Faucet(FaucetStatus::WaitingForFunds {
+                                tx_id,
+                            }))
+                            .await;
+                        match faucet::wait_for_arrival(&config, balance_before).await {
+                            Ok(lovelace) => FaucetStatus::Received { lovelace },
+                            Err(e) => FaucetStatus::Failed(e.to_string()),
+                        }
+                    }
+                    Err(e) => FaucetStatus::Failed(e.to_string()),
+                };
+
+                let _ = tx.send(AppActionComplete::Faucet(final_status)).await;
+            });
+        }
+        AppAction::SendTelemetry(report) => {
+            let Some(endpoint) = app.system_state.telemetry.endpoint.clone() else {
+                return;
+            };
+            let tx = app.action_tx.clone();
+
+            tokio::spawn(async move {
+                match telemetry::send_report(&endpoint, &report).await {
+                    Ok(()) => {
+                        let _ = tx.send(AppActionComplete::TelemetrySent).await;
+                    }
+                    // Counters are kept until a report goes through
+                    Err(e) => warn!("Failed to send usage statistics: {}", e),
+                }
+            });
+        }
+        AppAction::PushWebhook(body) => {
+            let Some(webhook) = &app.webhook else {
+                return;
+            };
+            let (url, token) = (webhook.url.clone(), webhook.token.clone());
+            tokio::spawn(async move {
+                if let Err(e) = webhook::push(&url, token.as_deref(), body).await {
+                    warn!("Failed to push the state to the webhook: {}", e);
+                }
+            });
+        }
+        // Handled by the main loop
+        AppAction::Screenshot | AppAction::Restart | AppAction::Quit => {}
     }
 }
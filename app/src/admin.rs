@@ -0,0 +1,69 @@
+use std::env;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Tracks whether privileged actions are currently allowed. The session
+/// locks itself again after a period without input.
+#[derive(Debug, Clone)]
+pub struct AdminSession {
+    unlocked: bool,
+    last_activity: Instant,
+    idle_timeout: Duration,
+}
+
+impl Default for AdminSession {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDLE_TIMEOUT)
+    }
+}
+
+impl AdminSession {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            unlocked: false,
+            last_activity: Instant::now(),
+            idle_timeout,
+        }
+    }
+
+    /// Reads the idle timeout from `AMARU_PI_ADMIN_IDLE_SECS`.
+    pub fn from_env() -> Self {
+        let idle_timeout = env::var("AMARU_PI_ADMIN_IDLE_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_IDLE_TIMEOUT);
+        Self::new(idle_timeout)
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked
+    }
+
+    pub fn unlock(&mut self) {
+        info!("Admin session unlocked");
+        self.unlocked = true;
+        self.last_activity = Instant::now();
+    }
+
+    pub fn lock(&mut self) {
+        if self.unlocked {
+            info!("Admin session locked");
+        }
+        self.unlocked = false;
+    }
+
+    /// Records user activity, postponing the automatic lock.
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Locks the session once idle for too long. Call this every loop.
+    pub fn check_idle(&mut self) {
+        if self.unlocked && self.last_activity.elapsed() >= self.idle_timeout {
+            self.lock();
+        }
+    }
+}
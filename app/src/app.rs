@@ -1,42 +1,344 @@
-use crate::button::InputEvent;
+use crate::admin::AdminSession;
+use crate::backup::{BackupProgress, BackupRecord, BackupStatus};
+use crate::blanking::Blanking;
+use crate::boot::{BootHistory, BootReport};
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::chain::Network;
+use crate::clock::ClockStatus;
+use crate::connectivity::{ConnectivityEvent, ConnectivityManager, Link};
+use crate::control::{ControlCall, ControlRequest};
+use crate::coredump::Coredump;
+use crate::demo::DemoMode;
+use crate::density::Density;
+use crate::dev;
+use crate::environment::{EnvironmentReading, EnvironmentSensor};
+use crate::epoch::EpochTracker;
+use crate::faucet::{FaucetConfig, FaucetStatus};
+use crate::files::Listing;
+use crate::firewall::{FirewallPreset, FirewallState, FirewallStatus};
+use crate::fleet::{self, FleetConfig, FleetNode, RemoteStatus, StatusServer};
 use crate::frame::FrameState;
+use crate::i18n::{self, tr};
+use crate::influx::InfluxExporter;
+use crate::inputs::mapping::{Command, MappedInput};
+use crate::ip_config::{self, IpConfigState, IpSettings};
+use crate::kiosk;
+use crate::latency::LatencyHistory;
+use crate::leadership::{LeadershipConfig, LeadershipSchedule};
 use crate::modal::Modal;
+use crate::modem::ModemStatus;
+#[cfg(feature = "mqtt")]
+use crate::mqtt::Mqtt;
 use crate::network_status::NetworkStatusCache;
+use crate::node_config::{ConfigStatus, Field};
+use crate::notifications::{self, Notification, Notifier, Severity};
+use crate::peers::PeersSnapshot;
+use crate::pin::{Guarded, PinCheck, PinLock, PinPrompt};
+use crate::pools::{self, PoolsConfig, PoolsSnapshot};
+use crate::port_check::{PortCheck, PortCheckState};
+use crate::power::{PowerAction, PowerStatus, UpsConfig};
+use crate::processes::ProcessInfo;
+use crate::restore::{RestoreStatus, Snapshot};
 use crate::screen_flow::ScreenFlow;
-use crate::screens::{AppContext, ScreenAction, SystemState, WifiConnectionStatus};
-use crate::systemd::ServiceInfo;
-use crate::update::{UpdateManager, UpdateStatus};
+use crate::screens::{AppContext, Kind, ScreenAction, SystemState, WifiConnectionStatus};
+use crate::self_update::{self, Handoff};
+use crate::stake_pool::{self, PoolSummary, StakePoolConfig};
+use crate::status::status_json;
+use crate::storage::{StorageHistory, StorageSample};
+use crate::sync_eta::SyncEstimator;
+use crate::systemd::units::UnitStatus;
+use crate::systemd::{ActiveState, ServiceInfo};
+use crate::tasks::{self, TaskId, TaskState, Tasks};
+use crate::telemetry::{Telemetry, TelemetryReport};
+use crate::thermal::ThermalStatus;
+use crate::ticker::{self, TickerConfig, TickerSnapshot};
+use crate::topology::Reachability;
+use crate::update::{UpdateManager, UpdateStatus, read_state_file};
+use crate::update_history::{self, ReleaseNotes};
+use crate::update_source;
+use crate::util::{current_timestamp, format_ada, format_bytes};
+use crate::vpn::{VpnBackend, VpnStatus};
+use crate::webhook::Webhook;
+use crate::widgets::dialog::Dialog;
+use crate::wifi::{Connectivity, NetworkStatus, SavedNetwork};
+use anyhow::anyhow;
 use ratatui::prelude::*;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, VecDeque};
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+const PORTAL_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const LATENCY_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+/// Hashing the node binary takes a few seconds on a Pi
+const INTEGRITY_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Sizing the ledger walks thousands of files
+const STORAGE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Low enough to stop the node before the battery runs out
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Also how often the fan is adjusted
+const THERMAL_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// The air of an enclosure warms up slowly
+const ENVIRONMENT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// NTP takes a moment to synchronize after boot or a network change
+const CLOCK_SYNC_GRACE: Duration = Duration::from_secs(5 * 60);
 
 pub enum AppEvent {
     Tick,
     Input(InputEvent),
+    Command(Command),
+    /// The result of an action run in the background
+    ActionComplete(AppActionComplete),
+    /// From the control socket, answered once handled
+    Control(ControlRequest),
+}
+
+impl From<MappedInput> for AppEvent {
+    fn from(input: MappedInput) -> Self {
+        match input {
+            MappedInput::Event(event) => AppEvent::Input(event),
+            MappedInput::Command(command) => AppEvent::Command(command),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum AppAction {
     CheckNetworkStatus,
     CheckAmaruStatus,
+    CheckLinks,
+    CheckCaptivePortal,
+    SetFailover(bool),
     ConnectToWifi(String, String),
+    RefreshSavedNetworks,
+    SetNetworkOrder(Vec<String>),
+    SwitchNetwork(String),
+    CheckVpn,
+    SetVpn(VpnBackend, bool),
+    /// Runs the updater now, instead of waiting for its timer
+    CheckUpdates,
+    CheckModemStatus,
+    SetApn(String),
+    RefreshPeers,
+    SampleLatency,
+    AnalyzeBoot,
+    CheckUnits,
+    VerifyBinaries,
+    SampleStorage,
+    CheckThermal,
+    ReadEnvironment,
+    CheckPower,
+    /// At the given charge of the battery
+    StopNodeOnLowBattery(u8),
+    CheckClock,
+    ListTimezones,
+    SetTimezone(String),
+    ReadIpConfig,
+    ApplyIpConfig(IpSettings),
+    CheckFirewall,
+    /// Confirmed on the Firewall screen
+    ApplyFirewallPreset(FirewallPreset),
+    RefreshCoredumps,
+    ExportCoredump(u32),
+    ExportDiagnostics,
+    StageOfflineUpdates,
+    DetectUsb,
+    BackUp,
+    RefreshSnapshots,
+    Restore(Snapshot),
+    RefreshProcesses,
+    FetchProcessLogs(u32, Option<String>),
+    Browse(PathBuf),
+    DeleteFile(PathBuf),
+    SaveNodeConfig(Vec<(Field, String)>),
+    RestartNode,
+    /// Confirmed on the Power screen
+    Power(PowerAction),
+    TestPeer(String),
+    CheckPort,
+    RefreshPools,
+    RefreshStakePool,
+    RefreshTicker,
+    RefreshFleet,
+    RefreshLeadership,
+    /// Application, version and repository of a pending update
+    FetchReleaseNotes(String, String, String),
+    RequestFaucetFunds,
+    SendTelemetry(TelemetryReport),
+    /// The body to push to the webhook
+    PushWebhook(String),
+    /// Saves what the display shows once drawn
+    Screenshot,
+    /// Hands off to the newly installed amaru-pi
+    Restart,
     Quit,
 }
 
 #[derive(Debug)]
 pub enum AppActionComplete {
+    NetworkStatus(NetworkStatus),
+    AmaruStatus(ServiceInfo),
     WifiConnection(WifiConnectionStatus),
+    Links {
+        links: Vec<Link>,
+        /// Whether the internet answered through the primary link, probed
+        /// while the cellular link carries the traffic
+        primary_reachable: Option<bool>,
+    },
+    FailoverFailed,
+    CaptivePortal(Option<String>),
+    SavedNetworks(Vec<SavedNetwork>),
+    NetworkSwitchFailed(String),
+    Vpn(Option<VpnStatus>),
+    VpnFailed(String),
+    Modem(ModemStatus),
+    ApnFailed(String),
+    Peers(PeersSnapshot),
+    LatencySamples(Vec<(IpAddr, f64)>),
+    /// `None` while the boot isn't finished
+    Boot(Option<BootReport>),
+    Units(Vec<UnitStatus>),
+    /// Names of the binaries modified since installed
+    BinariesVerified(Vec<String>),
+    Storage(StorageSample),
+    Thermal(ThermalStatus),
+    Environment(EnvironmentReading),
+    Power(PowerStatus),
+    NodeStoppedOnLowBattery,
+    NodeStopFailed(String),
+    Clock(ClockStatus),
+    Timezones(Vec<String>),
+    ReleaseNotes(ReleaseNotes),
+    TimezoneFailed(String),
+    IpConfig(Result<IpSettings, String>),
+    IpConfigFailed(String),
+    Firewall(Result<FirewallStatus, String>),
+    FirewallFailed(String),
+    Coredumps(Vec<Coredump>),
+    /// Name of the dump on the USB drive
+    CoredumpExported(String),
+    CoredumpExportFailed(String),
+    /// Where the support bundle went: its link, its name on the USB drive or
+    /// its path
+    DiagnosticsExported(String),
+    DiagnosticsExportFailed(String),
+    /// As `<app> <version>`
+    OfflineUpdatesStaged(Vec<String>),
+    OfflineUpdatesFailed(String),
+    UsbDetected(bool),
+    BackupProgress(BackupProgress),
+    BackedUp(BackupRecord),
+    BackupFailed(String),
+    Snapshots(Vec<Snapshot>),
+    RestoreProgress(BackupProgress),
+    /// Name of the snapshot restored
+    Restored(String),
+    RestoreFailed(String),
+    Processes(Vec<ProcessInfo>),
+    ProcessLogs(u32, Vec<String>),
+    ProcessLogsFailed(String),
+    Listing(Listing),
+    BrowseFailed(String),
+    /// Name of the deleted file
+    FileDeleted(String),
+    NodeConfigSaved,
+    NodeConfigFailed(String),
+    NodeRestarted,
+    NodeRestartFailed(String),
+    PowerFailed(String),
+    PeerTested(String, Reachability),
+    PortChecked(PortCheck),
+    Pools(PoolsSnapshot),
+    StakePool(PoolSummary),
+    Ticker(TickerSnapshot),
+    /// Status of each node of the fleet, by name
+    Fleet(Vec<(String, anyhow::Result<RemoteStatus>)>),
+    Leadership(LeadershipSchedule),
+    Faucet(FaucetStatus),
+    TelemetrySent,
+    ScreenshotSaved(PathBuf),
+    ScreenshotFailed(String),
+    /// From 0 to 1
+    TaskProgress(TaskId, f64),
+    TaskEnded(TaskId, TaskState),
 }
 
 pub struct App {
     frame_state: FrameState,
     screen_flow: ScreenFlow,
     pub connectivity_cache: NetworkStatusCache,
+    connectivity: ConnectivityManager,
+    portal_last_check: Option<Instant>,
+    /// Whether the captive portal warning still has to be shown
+    portal_warning_pending: bool,
     amaru_status_last_check: Instant,
     amaru_status_interval: Duration,
+    /// Set while the node is failed, alerted on once
+    amaru_failed: bool,
+    pub pools_config: PoolsConfig,
+    pools_last_check: Option<Instant>,
+    pub stake_pool_config: StakePoolConfig,
+    stake_pool_last_check: Option<Instant>,
+    pub ticker_config: TickerConfig,
+    ticker_last_check: Option<Instant>,
+    pub fleet_config: FleetConfig,
+    fleet_last_check: Option<Instant>,
+    /// Serves the status of this node to the dashboards of other Pis
+    status_server: Option<StatusServer>,
+    pub leadership_config: LeadershipConfig,
+    leadership_last_check: Option<Instant>,
+    latency_last_sample: Option<Instant>,
+    integrity_last_check: Option<Instant>,
+    /// Modified binaries already alerted on
+    modified_binaries: Vec<String>,
+    storage_last_check: Option<Instant>,
+    /// Set while free space is low, alerted on once
+    low_disk_alerted: bool,
+    thermal_last_check: Option<Instant>,
+    /// Set while too hot or throttled, alerted on once
+    thermal_alerted: bool,
+    pub environment_sensor: Option<EnvironmentSensor>,
+    environment_last_check: Option<Instant>,
+    /// Set while the enclosure is too hot, alerted on once
+    environment_alerted: bool,
+    pub ups_config: Option<UpsConfig>,
+    power_last_check: Option<Instant>,
+    /// Set once the node is stopped on low battery, until it charges again
+    low_battery_handled: bool,
+    clock_last_check: Option<Instant>,
+    /// Since when the clock has been unsynchronized or drifting
+    clock_off_since: Option<Instant>,
+    /// Set while the clock is off, alerted on once
+    clock_alerted: bool,
+    /// Whether this boot made it to the boot history
+    boot_recorded: bool,
+    demo: DemoMode,
+    blanking: Blanking,
+    /// Run as a daemon, without display
+    headless: bool,
+    pub faucet_config: FaucetConfig,
+    telemetry_last_attempt: Option<Instant>,
+    pin_lock: PinLock,
     pub system_state: SystemState,
     modal: Modal,
     update_manager: UpdateManager,
+    /// Update of amaru-pi being tried out, since when
+    handoff: Option<(Handoff, Instant)>,
+    epoch_tracker: EpochTracker,
+    sync_estimator: SyncEstimator,
+    notifier: Notifier,
+    pub webhook: Option<Webhook>,
+    influx: Option<InfluxExporter>,
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<Mqtt>,
+    pending_notifications: VecDeque<Notification>,
+    /// Asked through the control socket, answered with the next screenshot
+    screenshot_requests: Vec<ControlRequest>,
+    pub tasks: Tasks,
     pub action_tx: mpsc::Sender<AppActionComplete>,
     action_rx: mpsc::Receiver<AppActionComplete>,
 }
@@ -46,21 +348,123 @@ impl Default for App {
         let default_interval = Duration::from_secs(5);
         let now = Instant::now();
         let connectivity_cache = NetworkStatusCache::new(default_interval);
+        #[cfg_attr(not(feature = "mqtt"), allow(unused_mut))]
+        let mut notifier = Notifier::from_env();
+        #[cfg(feature = "mqtt")]
+        let mqtt = Mqtt::from_env();
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &mqtt {
+            notifier.add_channel(Box::new(mqtt.channel()));
+        }
+        let fleet_config = FleetConfig::from_env();
         let system_state = SystemState {
             amaru_status: ServiceInfo::default(),
             network_status: connectivity_cache.last_result,
             wifi_connection_status: WifiConnectionStatus::default(),
+            pools: pools::read_cache().unwrap_or_default(),
+            stake_pool: stake_pool::read_cache().unwrap_or_default(),
+            ticker: ticker::read_cache().unwrap_or_default(),
+            faucet: FaucetStatus::default(),
+            telemetry: Telemetry::load(),
+            admin: AdminSession::from_env(),
+            saved_networks: Vec::new(),
+            vpn: None,
+            modem: ModemStatus::default(),
+            captive_portal: None,
+            peers: PeersSnapshot::default(),
+            latency: LatencyHistory::load(),
+            boot: BootHistory::load(),
+            units: Vec::new(),
+            coredumps: Vec::new(),
+            processes: Vec::new(),
+            process_logs: None,
+            browser: None,
+            node_config_status: ConfigStatus::Idle,
+            ip_config: IpConfigState::default(),
+            port_check: PortCheckState::default(),
+            firewall: FirewallState::default(),
+            peer_tests: BTreeMap::new(),
+            leadership: LeadershipSchedule::default(),
+            storage: StorageHistory::load(),
+            usb_drive: false,
+            backup: BackupStatus::Idle,
+            snapshots: Vec::new(),
+            restore: RestoreStatus::Idle,
+            thermal: ThermalStatus::default(),
+            environment: None,
+            sync_eta: None,
+            tip: None,
+            power: None,
+            clock: None,
+            timezones: Vec::new(),
+            release_notes: None,
+            tasks: Vec::new(),
+            paste_buffer: None,
+            fleet: fleet_config
+                .members
+                .iter()
+                .map(|member| FleetNode::new(&member.name))
+                .collect(),
         };
         let (action_tx, action_rx) = mpsc::channel(100);
         Self {
             frame_state: FrameState::default(),
             screen_flow: ScreenFlow::default(),
             connectivity_cache,
+            connectivity: ConnectivityManager::from_env(),
+            portal_last_check: None,
+            portal_warning_pending: false,
             amaru_status_last_check: now - default_interval,
             amaru_status_interval: default_interval,
+            amaru_failed: false,
+            pools_config: PoolsConfig::from_env(),
+            pools_last_check: None,
+            stake_pool_config: StakePoolConfig::from_env(),
+            stake_pool_last_check: None,
+            ticker_config: TickerConfig::from_env(),
+            ticker_last_check: None,
+            fleet_config,
+            fleet_last_check: None,
+            status_server: StatusServer::from_env(),
+            leadership_config: LeadershipConfig::from_env(),
+            leadership_last_check: None,
+            latency_last_sample: None,
+            integrity_last_check: None,
+            modified_binaries: Vec::new(),
+            storage_last_check: None,
+            low_disk_alerted: false,
+            thermal_last_check: None,
+            thermal_alerted: false,
+            environment_sensor: EnvironmentSensor::from_env(),
+            environment_last_check: None,
+            environment_alerted: false,
+            ups_config: UpsConfig::from_env(),
+            power_last_check: None,
+            low_battery_handled: false,
+            clock_last_check: None,
+            clock_off_since: None,
+            clock_alerted: false,
+            boot_recorded: false,
+            demo: DemoMode::from_env(),
+            blanking: Blanking::from_env(),
+            headless: false,
+            faucet_config: FaucetConfig::from_env(),
+            telemetry_last_attempt: None,
+            pin_lock: PinLock::from_env(),
             system_state,
             modal: Modal::default(),
             update_manager: UpdateManager::new(Duration::from_secs(5)),
+            handoff: None,
+            epoch_tracker: EpochTracker::new(Duration::from_secs(1)),
+            sync_estimator: SyncEstimator::new(Duration::from_secs(5)),
+            notifier,
+            webhook: Webhook::from_env(),
+            influx: InfluxExporter::from_env(),
+            #[cfg(feature = "mqtt")]
+            mqtt,
+            pending_notifications: VecDeque::new(),
+            screenshot_requests: Vec::new(),
+            tasks: Tasks::default(),
             action_tx,
             action_rx,
         }
@@ -68,30 +472,317 @@ impl Default for App {
 }
 
 impl App {
+    /// Results of the actions finished since last called, to pass to
+    /// `update`.
+    pub fn completed_actions(&mut self) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+        while let Ok(result) = self.action_rx.try_recv() {
+            events.push(AppEvent::ActionComplete(result));
+        }
+        events
+    }
+
     pub fn update(&mut self, msg: AppEvent) -> Vec<AppAction> {
         let mut actions = Vec::new();
+        let previous_screen_kind = self.screen_flow.current_screen_kind;
 
         match msg {
             AppEvent::Tick => {
                 self.frame_state.update();
+                self.system_state.admin.check_idle();
+                if !self.headless && self.blanking.check_idle() {
+                    self.on_display_blanked();
+                }
 
-                while let Ok(action_result) = self.action_rx.try_recv() {
-                    match action_result {
-                        AppActionComplete::WifiConnection(status) => {
-                            self.system_state.wifi_connection_status = status;
-                        }
-                    }
+                #[cfg(feature = "mqtt")]
+                if let Some(mqtt) = &mut self.mqtt {
+                    mqtt.publish_state(&self.system_state);
+                }
+                if let Some(body) = self
+                    .webhook
+                    .as_mut()
+                    .and_then(|webhook| webhook.due_body(&self.system_state))
+                {
+                    actions.push(AppAction::PushWebhook(body));
+                }
+                if let Some(influx) = &mut self.influx {
+                    influx.sample(&self.system_state);
                 }
+                if let Some(server) = &mut self.status_server {
+                    server.publish(&self.system_state);
+                }
+
+                let failed = matches!(
+                    self.system_state.amaru_status.active_state,
+                    ActiveState::Failed
+                );
+                if failed && !self.amaru_failed {
+                    self.notify(Notification::new(
+                        "Node stopped",
+                        vec!["amaru.service failed".to_string()],
+                        Severity::Critical,
+                    ));
+                }
+                self.amaru_failed = failed;
 
                 // Amaru status check
                 if self.amaru_status_last_check.elapsed() >= self.amaru_status_interval {
                     self.amaru_status_last_check = Instant::now();
                     actions.push(AppAction::CheckNetworkStatus);
+                    actions.push(AppAction::CheckLinks);
                     actions.push(AppAction::CheckAmaruStatus);
+
+                    // A portal only shows as peers being unreachable
+                    let status = self.system_state.network_status;
+                    if status.connectivity == Connectivity::Portal || !status.resolving {
+                        if self
+                            .portal_last_check
+                            .is_none_or(|last| last.elapsed() >= PORTAL_CHECK_INTERVAL)
+                        {
+                            self.portal_last_check = Some(Instant::now());
+                            actions.push(AppAction::CheckCaptivePortal);
+                        }
+                    } else {
+                        self.system_state.captive_portal = None;
+                    }
+                    // Only listed on their screen
+                    match self.screen_flow.current_screen_kind {
+                        Kind::Networks => {
+                            actions.push(AppAction::RefreshSavedNetworks);
+                            actions.push(AppAction::CheckVpn);
+                        }
+                        Kind::Lte => actions.push(AppAction::CheckModemStatus),
+                        Kind::PeersMap => actions.push(AppAction::RefreshPeers),
+                        Kind::Units => actions.push(AppAction::CheckUnits),
+                        Kind::Coredumps => actions.push(AppAction::RefreshCoredumps),
+                        Kind::Processes => actions.push(AppAction::RefreshProcesses),
+                        Kind::Backup => actions.push(AppAction::DetectUsb),
+                        Kind::Leadership => actions.push(AppAction::RefreshLeadership),
+                        Kind::Clock => actions.push(AppAction::CheckClock),
+                        _ => {}
+                    }
+
+                    // Only known once systemd reports the boot finished
+                    if !self.boot_recorded && self.screen_flow.is_enabled(Kind::Boot) {
+                        actions.push(AppAction::AnalyzeBoot);
+                    }
+
+                    // Not while the pool is about to lead a slot
+                    let slots_to_leadership = current_timestamp()
+                        .ok()
+                        .and_then(|now| self.system_state.leadership.slots_to_next(now));
+                    if let Some(app_names) =
+                        self.update_manager.apply_if_scheduled(slots_to_leadership)
+                    {
+                        self.notify(Notification::new(
+                            "Applying scheduled update",
+                            vec![app_names.join(", ")],
+                            Severity::Info,
+                        ));
+                    }
+                }
+
+                if self.update_manager.take_restart_request() {
+                    actions.push(AppAction::Restart);
+                }
+                // Still running after the health check, the update is kept
+                if self
+                    .handoff
+                    .as_ref()
+                    .is_some_and(|(_, since)| since.elapsed() >= self_update::HEALTH_CHECK)
+                    && let Some((handoff, _)) = self.handoff.take()
+                {
+                    match self_update::promote(&handoff) {
+                        Ok(()) => self.notify(Notification::new(
+                            "Updated amaru-pi",
+                            vec![handoff.version.clone()],
+                            Severity::Info,
+                        )),
+                        Err(e) => warn!("Failed to promote amaru-pi update: {}", e),
+                    }
+                }
+
+                // Watched pools refresh
+                if self.pools_config.is_enabled()
+                    && self
+                        .pools_last_check
+                        .is_none_or(|last| last.elapsed() >= self.pools_config.refresh_interval)
+                {
+                    self.pools_last_check = Some(Instant::now());
+                    actions.push(AppAction::RefreshPools);
+                }
+
+                if self.stake_pool_config.is_enabled()
+                    && self.stake_pool_last_check.is_none_or(|last| {
+                        last.elapsed() >= self.stake_pool_config.refresh_interval
+                    })
+                {
+                    self.stake_pool_last_check = Some(Instant::now());
+                    actions.push(AppAction::RefreshStakePool);
+                }
+
+                // Nothing is asked of third parties unless the screen is listed
+                if self.screen_flow.is_enabled(Kind::Ticker)
+                    && self
+                        .ticker_last_check
+                        .is_none_or(|last| last.elapsed() >= self.ticker_config.refresh_interval)
+                {
+                    self.ticker_last_check = Some(Instant::now());
+                    actions.push(AppAction::RefreshTicker);
+                }
+
+                if self.fleet_config.is_enabled()
+                    && self
+                        .fleet_last_check
+                        .is_none_or(|last| last.elapsed() >= self.fleet_config.refresh_interval)
+                {
+                    self.fleet_last_check = Some(Instant::now());
+                    actions.push(AppAction::RefreshFleet);
+                }
+
+                // Upcoming slots also hold updates back
+                if self.leadership_config.is_enabled()
+                    && self.leadership_last_check.is_none_or(|last| {
+                        last.elapsed() >= self.leadership_config.refresh_interval
+                    })
+                {
+                    self.leadership_last_check = Some(Instant::now());
+                    actions.push(AppAction::RefreshLeadership);
+                }
+
+                // Sampled all day long for the hourly heatmap
+                if self.screen_flow.is_enabled(Kind::Latency)
+                    && self
+                        .latency_last_sample
+                        .is_none_or(|last| last.elapsed() >= LATENCY_SAMPLE_INTERVAL)
+                {
+                    self.latency_last_sample = Some(Instant::now());
+                    actions.push(AppAction::SampleLatency);
+                }
+
+                // Catches tampering or a corrupt SD card
+                if self
+                    .integrity_last_check
+                    .is_none_or(|last| last.elapsed() >= INTEGRITY_CHECK_INTERVAL)
+                {
+                    self.integrity_last_check = Some(Instant::now());
+                    actions.push(AppAction::VerifyBinaries);
+                }
+
+                if self
+                    .storage_last_check
+                    .is_none_or(|last| last.elapsed() >= STORAGE_CHECK_INTERVAL)
+                {
+                    self.storage_last_check = Some(Instant::now());
+                    actions.push(AppAction::SampleStorage);
+                }
+
+                if self
+                    .thermal_last_check
+                    .is_none_or(|last| last.elapsed() >= THERMAL_CHECK_INTERVAL)
+                {
+                    self.thermal_last_check = Some(Instant::now());
+                    actions.push(AppAction::CheckThermal);
+                }
+
+                if self.environment_sensor.is_some()
+                    && self
+                        .environment_last_check
+                        .is_none_or(|last| last.elapsed() >= ENVIRONMENT_CHECK_INTERVAL)
+                {
+                    self.environment_last_check = Some(Instant::now());
+                    actions.push(AppAction::ReadEnvironment);
+                }
+
+                if self.ups_config.is_some()
+                    && self
+                        .power_last_check
+                        .is_none_or(|last| last.elapsed() >= POWER_CHECK_INTERVAL)
+                {
+                    self.power_last_check = Some(Instant::now());
+                    actions.push(AppAction::CheckPower);
+                }
+
+                // Slots are told from the clock, off by much the node misses them
+                if self
+                    .clock_last_check
+                    .is_none_or(|last| last.elapsed() >= CLOCK_CHECK_INTERVAL)
+                {
+                    self.clock_last_check = Some(Instant::now());
+                    actions.push(AppAction::CheckClock);
+                }
+
+                // Daily usage report, retried hourly on failure
+                if self.system_state.telemetry.is_report_due()
+                    && self
+                        .telemetry_last_attempt
+                        .is_none_or(|last| last.elapsed() >= Duration::from_secs(60 * 60))
+                {
+                    self.telemetry_last_attempt = Some(Instant::now());
+                    actions.push(AppAction::SendTelemetry(
+                        self.system_state.telemetry.report(),
+                    ));
+                }
+
+                if let Some(summary) = self.epoch_tracker.check_for_rollover() {
+                    self.notify((&summary).into());
+                }
+                if self.sync_estimator.check() {
+                    self.system_state.sync_eta = self.sync_estimator.eta();
+                    self.system_state.tip = self.sync_estimator.tip();
+                }
+
+                if self.demo.expire() {
+                    actions.extend(self.on_demo_toggled());
+                }
+                if let Some(kind) = self
+                    .demo
+                    .next_screen(self.screen_flow.current_screen_kind, |kind| {
+                        self.screen_flow.is_enabled(kind)
+                    })
+                {
+                    self.screen_flow.jump_to(kind);
+                }
+
+                // Neither modals nor screens without display
+                if self.headless {
+                    return actions;
                 }
 
-                // Update check if no modal is active
+                // A locked UI shows nothing but the PIN prompt
+                if self.pin_lock.locks_ui()
+                    && !self.system_state.admin.is_unlocked()
+                    && !matches!(self.modal, Modal::PinEntry(_))
+                {
+                    match std::mem::take(&mut self.modal) {
+                        Modal::Notification(notification) => {
+                            self.pending_notifications.push_front(notification);
+                        }
+                        Modal::CaptivePortal(_) => self.portal_warning_pending = true,
+                        _ => {}
+                    }
+                    self.modal = Modal::PinEntry(PinPrompt::new(Guarded::Ui, &self.pin_lock));
+                }
+
+                if !self.modal.is_active()
+                    && self.portal_warning_pending
+                    && let Some(url) = &self.system_state.captive_portal
+                {
+                    self.portal_warning_pending = false;
+                    self.modal = Modal::CaptivePortal(url.clone());
+                }
+
+                // Show pending notifications one at a time
+                if !self.modal.is_active()
+                    && let Some(notification) = self.pending_notifications.pop_front()
+                {
+                    self.modal = Modal::Notification(notification);
+                }
+
+                // Update check if no modal is active, updates can't be
+                // applied in kiosk mode
                 if !self.modal.is_active()
+                    && !kiosk::is_enabled()
                     && let UpdateStatus::UpdateReadyToNotify(app_names) =
                         self.update_manager.check_for_update()
                     && !app_names.is_empty()
@@ -100,14 +791,88 @@ impl App {
                 }
             }
             AppEvent::Input(event) => {
+                if self.blanking.touch() {
+                    return actions;
+                }
+                self.system_state.admin.touch();
+                self.demo.touch();
+
                 // If a modal is active, it handles the input
-                if self.modal.handle_input(event, &mut self.update_manager) {
+                let pin_lock = (!self.is_authorized()).then_some(&self.pin_lock);
+                if self
+                    .modal
+                    .handle_input(event, &mut self.update_manager, pin_lock)
+                {
                     // The modal handled it, don't process further
-                    return Vec::new();
+                    if let Modal::Confirm(dialog) = &self.modal
+                        && dialog.answer.is_some()
+                    {
+                        let chosen = dialog.chosen();
+                        self.modal = Modal::None;
+                        return chosen
+                            .map(|guarded| self.guard(guarded))
+                            .unwrap_or_default();
+                    }
+                    return self.check_pin_prompt();
                 }
 
-                // Modal not active or didn't handle, pass to screen flow
-                self.screen_flow.handle_input(event);
+                // Modal not active or didn't handle, pass to screen flow.
+                // Holding B is a hidden toggle, for booths.
+                if !self.screen_flow.handle_input(event)
+                    && event.id == ButtonId::B
+                    && event.press_type == ButtonPress::Long
+                {
+                    self.demo.toggle();
+                    actions.extend(self.on_demo_toggled());
+                }
+            }
+            AppEvent::ActionComplete(result) => actions.extend(self.on_action_complete(result)),
+            AppEvent::Control(request) => {
+                let result = match request.call {
+                    ControlCall::Status => {
+                        let mut status = status_json(&self.system_state, &Network::from_env());
+                        status["screen"] = json!(self.screen_flow.current_screen_kind.to_string());
+                        Some(Ok(status))
+                    }
+                    ControlCall::SwitchScreen(kind) if self.screen_flow.is_enabled(kind) => {
+                        self.screen_flow.jump_to(kind);
+                        Some(Ok(Value::Null))
+                    }
+                    ControlCall::SwitchScreen(kind) => {
+                        Some(Err(anyhow!("The {} screen isn't enabled", kind)))
+                    }
+                    ControlCall::CheckUpdates => {
+                        actions.push(AppAction::CheckUpdates);
+                        Some(Ok(Value::Null))
+                    }
+                    ControlCall::Press(event) => {
+                        // The inner update already ran the screens for it
+                        request.answer(Ok(Value::Null));
+                        actions.extend(self.update(AppEvent::Input(event)));
+                        return actions;
+                    }
+                    // Answered once the next frame is saved
+                    ControlCall::Screenshot => None,
+                };
+                match result {
+                    Some(result) => request.answer(result),
+                    None => {
+                        self.screenshot_requests.push(request);
+                        actions.push(AppAction::Screenshot);
+                    }
+                }
+            }
+            // Modals included
+            AppEvent::Command(Command::Screenshot) => actions.push(AppAction::Screenshot),
+            AppEvent::Command(command) => {
+                // Commands don't bypass an active modal
+                if !self.modal.is_active() {
+                    match command {
+                        Command::DevMenu if dev::is_enabled() => self.modal = Modal::DevMenu(0),
+                        Command::DevMenu => {}
+                        _ => self.screen_flow.handle_command(command),
+                    }
+                }
             }
         }
 
@@ -117,22 +882,983 @@ impl App {
         };
 
         // Let the current screen update and potentially return an action
-        let screen_action = self.screen_flow.update(ctx);
+        let mut screen_action = self.screen_flow.update(ctx);
+        if kiosk::is_enabled() && !kiosk::allows(&screen_action) {
+            info!("Kiosk mode, ignoring the screen action");
+            screen_action = ScreenAction::None;
+        }
         match screen_action {
             ScreenAction::ConnectToWifi(ssid, pw) => {
-                actions.push(AppAction::ConnectToWifi(ssid, pw))
+                actions.extend(self.guard(Guarded::ConnectToWifi(ssid, pw)))
             }
             ScreenAction::ResetWifiConnectionStatus => {
                 // Handle this sync action immediately
                 self.system_state.wifi_connection_status = WifiConnectionStatus::Idle;
             }
+            ScreenAction::RequestFaucetFunds => actions.push(AppAction::RequestFaucetFunds),
+            ScreenAction::SetTelemetry(enabled) => {
+                self.system_state.telemetry.set_enabled(enabled);
+            }
+            ScreenAction::AnnotateEvent(timestamp, note) => {
+                if let Err(e) = notifications::annotate(Some(timestamp), &note) {
+                    warn!("Failed to note the event: {}", e);
+                }
+            }
+            ScreenAction::SetNetworkOrder(names) => {
+                // Shown right away, saved in the background
+                let networks = &mut self.system_state.saved_networks;
+                networks.sort_by_key(|n| names.iter().position(|name| *name == n.name));
+                actions.push(AppAction::SetNetworkOrder(names));
+            }
+            ScreenAction::ApplyUpdate => self.confirm(Dialog::new(
+                tr("Apply update"),
+                vec![tr("amaru-pi restarts, and the node if updated").to_string()],
+                tr("Apply"),
+                Guarded::ApplyUpdate,
+            )),
+            ScreenAction::SetUpdateHold(name, hold) => {
+                actions.extend(self.guard(Guarded::SetUpdateHold(name, hold)))
+            }
+            ScreenAction::SetApn(apn) => actions.extend(self.guard(Guarded::SetApn(apn))),
+            ScreenAction::SetTimezone(timezone) => {
+                actions.extend(self.guard(Guarded::SetTimezone(timezone)))
+            }
+            ScreenAction::ApplyIpConfig(settings) => {
+                actions.extend(self.guard(Guarded::ApplyIpConfig(settings)))
+            }
+            ScreenAction::ExportCoredump(pid) => {
+                actions.extend(self.guard(Guarded::ExportCoredump(pid)))
+            }
+            ScreenAction::ExportDiagnostics => {
+                actions.extend(self.guard(Guarded::ExportDiagnostics))
+            }
+            ScreenAction::StageOfflineUpdates => {
+                actions.extend(self.guard(Guarded::StageOfflineUpdates))
+            }
+            ScreenAction::BackUp => actions.extend(self.guard(Guarded::BackUp)),
+            ScreenAction::RefreshSnapshots => actions.push(AppAction::RefreshSnapshots),
+            ScreenAction::Restore(snapshot) => self.confirm(Dialog::new(
+                tr("Restore ledger"),
+                vec![
+                    snapshot.name.clone(),
+                    tr("The node stops meanwhile").to_string(),
+                ],
+                tr("Restore"),
+                Guarded::Restore(snapshot),
+            )),
+            ScreenAction::ViewProcessLogs(pid, unit) => {
+                actions.push(AppAction::FetchProcessLogs(pid, unit));
+            }
+            ScreenAction::Browse(path) => actions.push(AppAction::Browse(path)),
+            ScreenAction::TestPeer(address) => actions.push(AppAction::TestPeer(address)),
+            ScreenAction::CheckPort => actions.push(AppAction::CheckPort),
+            ScreenAction::DeleteFile(path) => actions.extend(self.guard(Guarded::DeleteFile(path))),
+            ScreenAction::SaveNodeConfig(changes) => {
+                actions.extend(self.guard(Guarded::SaveNodeConfig(changes)))
+            }
+            ScreenAction::Confirm(dialog) => self.confirm(*dialog),
+            ScreenAction::CancelTask(id) => {
+                info!("Cancelling task {}", id);
+                self.tasks.cancel(id);
+            }
+            ScreenAction::Copy(text) => {
+                info!("Copied {} characters", text.chars().count());
+                self.system_state.paste_buffer = Some(text);
+            }
+            ScreenAction::SetLanguage(language) => {
+                if let Err(e) = i18n::set(language) {
+                    warn!("{}", e);
+                }
+            }
+            ScreenAction::RestartNode => self.confirm(Dialog::new(
+                tr("Restart node"),
+                vec![tr("It stops syncing until it is up again").to_string()],
+                tr("Restart"),
+                Guarded::RestartNode,
+            )),
+            ScreenAction::SwitchNetwork(name) => {
+                actions.extend(self.guard(Guarded::SwitchNetwork(name)))
+            }
+            ScreenAction::SetVpn(backend, up) => {
+                actions.extend(self.guard(Guarded::SetVpn(backend, up)))
+            }
             _ => {}
         }
 
+        let current_screen_kind = self.screen_flow.current_screen_kind;
+        if current_screen_kind != previous_screen_kind {
+            self.system_state
+                .telemetry
+                .record_screen_view(current_screen_kind);
+            match current_screen_kind {
+                Kind::Networks => {
+                    actions.push(AppAction::RefreshSavedNetworks);
+                    actions.push(AppAction::CheckVpn);
+                }
+                Kind::Lte => actions.push(AppAction::CheckModemStatus),
+                Kind::PeersMap => actions.push(AppAction::RefreshPeers),
+                Kind::Units => actions.push(AppAction::CheckUnits),
+                Kind::Coredumps => actions.push(AppAction::RefreshCoredumps),
+                Kind::Processes => actions.push(AppAction::RefreshProcesses),
+                Kind::Backup => actions.push(AppAction::DetectUsb),
+                Kind::Leadership => actions.push(AppAction::RefreshLeadership),
+                Kind::Updates => {
+                    if let Some(action) = self.release_notes_action() {
+                        actions.push(action);
+                    }
+                }
+                // Listing mounts the USB drive, only done on entry
+                Kind::Restore => actions.push(AppAction::RefreshSnapshots),
+                Kind::Clock => {
+                    actions.push(AppAction::CheckClock);
+                    actions.push(AppAction::ListTimezones);
+                }
+                Kind::IpConfig => actions.push(AppAction::ReadIpConfig),
+                Kind::Firewall => actions.push(AppAction::CheckFirewall),
+                _ => {}
+            }
+        }
+
         actions
     }
 
+    /// Alerts once the clock has stayed unsynchronized or drifting for a
+    /// while.
+    fn on_clock_status(&mut self, status: &ClockStatus) {
+        if !status.needs_attention() {
+            if self.clock_alerted {
+                info!("Clock synchronized again");
+            }
+            self.clock_off_since = None;
+            self.clock_alerted = false;
+            return;
+        }
+        let since = *self.clock_off_since.get_or_insert_with(Instant::now);
+        // NTP can't be waited for with a reset clock, the node is already
+        // rejecting blocks
+        if self.clock_alerted || (!status.implausible && since.elapsed() < CLOCK_SYNC_GRACE) {
+            return;
+        }
+        self.clock_alerted = true;
+        let mut lines = Vec::new();
+        if status.implausible {
+            lines.push("The clock was reset".to_string());
+        }
+        if !status.ntp {
+            lines.push("NTP is disabled".to_string());
+        } else if !status.synchronized {
+            lines.push("NTP hasn't synchronized".to_string());
+        }
+        if let Some(offset) = status.offset_ms.filter(|_| status.is_drifting()) {
+            lines.push(format!("Off by {:+.0} ms", offset));
+        }
+        lines.push("Slots may be missed".to_string());
+        warn!("Clock off: {}", lines.join(", "));
+        self.notify(Notification::new(
+            "Clock not synchronized",
+            lines,
+            Severity::Critical,
+        ));
+    }
+
+    /// Logs the UPS switching to battery and back, and stops the node when
+    /// the battery is about to run out.
+    fn on_power_status(
+        &mut self,
+        previous: Option<PowerStatus>,
+        status: PowerStatus,
+    ) -> Vec<AppAction> {
+        if let Some(previous) = previous {
+            if status.on_battery() && !previous.on_battery() {
+                info!("Power lost, on battery at {}%", status.percent);
+                self.notify(Notification::new(
+                    "On battery",
+                    vec![format!("{}% left", status.percent)],
+                    Severity::Warning,
+                ));
+            } else if previous.on_battery() && status.charging == Some(true) {
+                info!("Power restored, battery at {}%", status.percent);
+                self.notify(Notification::new(
+                    "Power restored",
+                    vec![format!("Battery at {}%", status.percent)],
+                    Severity::Info,
+                ));
+            }
+        }
+
+        let shutdown_percent = self
+            .ups_config
+            .as_ref()
+            .map_or(0, |config| config.shutdown_percent);
+        if status.charging == Some(true) {
+            self.low_battery_handled = false;
+        } else if status.on_battery()
+            && status.percent <= shutdown_percent
+            && !self.low_battery_handled
+        {
+            self.low_battery_handled = true;
+            self.notify(Notification::new(
+                "Battery low",
+                vec![
+                    format!("{}% left", status.percent),
+                    "Stopping the node".to_string(),
+                ],
+                Severity::Critical,
+            ));
+            return vec![AppAction::StopNodeOnLowBattery(status.percent)];
+        }
+        Vec::new()
+    }
+
+    /// Runs a confirmed power action, the PIN being entered.
+    fn power(&mut self, action: PowerAction) -> Vec<AppAction> {
+        if action != PowerAction::RestartUi {
+            self.notify(Notification::new(
+                action.label(),
+                vec!["Stopping the node first, don't unplug yet".to_string()],
+                Severity::Info,
+            ));
+        }
+        vec![AppAction::Power(action)]
+    }
+
+    /// Whether guarded actions can run without asking for the PIN.
+    fn is_authorized(&self) -> bool {
+        !self.pin_lock.is_enabled() || self.system_state.admin.is_unlocked()
+    }
+
+    /// Runs a guarded action, the PIN being entered or not needed.
+    fn run_guarded(&mut self, guarded: Guarded) -> Vec<AppAction> {
+        match guarded {
+            Guarded::Ui => Vec::new(),
+            Guarded::ApplyUpdate => {
+                self.update_manager.request_update().ok();
+                Vec::new()
+            }
+            Guarded::SetUpdateHold(name, hold) => {
+                self.set_update_hold(&name, hold);
+                Vec::new()
+            }
+            Guarded::ConnectToWifi(ssid, pw) => {
+                // Keeps the screen from requesting the connection twice
+                self.system_state.wifi_connection_status = WifiConnectionStatus::Connecting;
+                vec![AppAction::ConnectToWifi(ssid, pw)]
+            }
+            Guarded::SwitchNetwork(name) => vec![AppAction::SwitchNetwork(name)],
+            Guarded::SetVpn(backend, up) => vec![AppAction::SetVpn(backend, up)],
+            Guarded::SetApn(apn) => vec![AppAction::SetApn(apn)],
+            Guarded::SetTimezone(timezone) => vec![AppAction::SetTimezone(timezone)],
+            Guarded::ApplyIpConfig(settings) => vec![AppAction::ApplyIpConfig(settings)],
+            Guarded::ApplyFirewallPreset(preset) => vec![AppAction::ApplyFirewallPreset(preset)],
+            Guarded::ExportCoredump(pid) => vec![AppAction::ExportCoredump(pid)],
+            Guarded::ExportDiagnostics => vec![AppAction::ExportDiagnostics],
+            Guarded::StageOfflineUpdates => vec![AppAction::StageOfflineUpdates],
+            Guarded::DeleteFile(path) => vec![AppAction::DeleteFile(path)],
+            Guarded::SaveNodeConfig(changes) => vec![AppAction::SaveNodeConfig(changes)],
+            Guarded::RestartNode => vec![AppAction::RestartNode],
+            Guarded::Power(action) => self.power(action),
+            Guarded::BackUp => vec![AppAction::BackUp],
+            Guarded::Restore(snapshot) => vec![AppAction::Restore(snapshot)],
+        }
+    }
+
+    /// Runs a guarded action, asking for the PIN first when needed, unless
+    /// another modal is up.
+    fn guard(&mut self, guarded: Guarded) -> Vec<AppAction> {
+        if kiosk::is_enabled() && guarded != Guarded::Ui {
+            info!("Kiosk mode, not allowed to {}", guarded.label());
+            return Vec::new();
+        }
+        if self.is_authorized() {
+            return self.run_guarded(guarded);
+        }
+        if self.modal.is_active() {
+            debug!("A modal is up, not asking the PIN to {}", guarded.label());
+            return Vec::new();
+        }
+        self.modal = Modal::PinEntry(PinPrompt::new(guarded, &self.pin_lock));
+        Vec::new()
+    }
+
+    /// Shows a dialog, unless another modal is up.
+    fn confirm(&mut self, dialog: Dialog) {
+        if !self.modal.is_active() {
+            self.modal = Modal::Confirm(dialog);
+        }
+    }
+
+    /// Verifies a completed PIN prompt and runs the action it was guarding.
+    fn check_pin_prompt(&mut self) -> Vec<AppAction> {
+        let Modal::PinEntry(prompt) = &mut self.modal else {
+            return Vec::new();
+        };
+        if prompt.cancelled {
+            if let Guarded::ConnectToWifi(..) = prompt.guarded {
+                // Stops the screen from asking again
+                self.system_state.wifi_connection_status =
+                    WifiConnectionStatus::Failed("PIN required".to_string());
+            }
+            self.modal = Modal::None;
+            return Vec::new();
+        }
+        if !prompt.submitted {
+            return Vec::new();
+        }
+        match self.pin_lock.verify(&prompt.digits) {
+            PinCheck::Accepted => {
+                self.system_state.admin.unlock();
+                let guarded = prompt.guarded.clone();
+                self.modal = Modal::None;
+                self.run_guarded(guarded)
+            }
+            check => {
+                prompt.reject(check);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Picks up where the previous binary left off when it handed off to
+    /// this one.
+    pub fn resume_after_update(&mut self, handoff: Handoff) {
+        if let Some(kind) = handoff.screen_kind()
+            && self.screen_flow.is_enabled(kind)
+        {
+            self.screen_flow.jump_to(kind);
+        }
+        self.handoff = Some((handoff, Instant::now()));
+    }
+
+    pub fn set_density(&mut self, density: Density) {
+        self.frame_state.density = density;
+    }
+
+    pub fn system_state(&self) -> &SystemState {
+        &self.system_state
+    }
+
+    pub fn current_screen_kind(&self) -> Kind {
+        self.screen_flow.current_screen_kind
+    }
+
+    /// Whether the current screen shows a keyboard.
+    pub fn is_typing(&self) -> bool {
+        self.screen_flow.is_typing()
+    }
+
+    /// Without display, notifications aren't queued for it and no modal is
+    /// shown.
+    pub fn set_headless(&mut self) {
+        self.headless = true;
+    }
+
+    /// The requests the next screenshot answers.
+    pub fn take_screenshot_requests(&mut self) -> Vec<ControlRequest> {
+        std::mem::take(&mut self.screenshot_requests)
+    }
+
+    pub fn is_blanked(&self) -> bool {
+        self.blanking.is_blanked()
+    }
+
+    /// Privileged actions must not stay available on a blanked screen.
+    fn on_display_blanked(&mut self) {
+        self.system_state.admin.lock();
+    }
+
+    fn on_connectivity_event(&mut self, event: ConnectivityEvent) -> Option<AppAction> {
+        match event {
+            ConnectivityEvent::Switched { from, to } => {
+                let was_metered = from.is_some_and(|link| link.is_metered());
+                let paused = self.connectivity.pauses_on_metered();
+                match to {
+                    Some(link) if link.is_metered() && !was_metered => {
+                        let mut lines = vec![match from {
+                            Some(from) => format!("{} connection lost", from),
+                            None => "No other connection".to_string(),
+                        }];
+                        if paused {
+                            lines.push("Updates paused".to_string());
+                        }
+                        self.notify(Notification::new(
+                            "On cellular data",
+                            lines,
+                            Severity::Warning,
+                        ));
+                    }
+                    Some(link) if was_metered && !link.is_metered() => {
+                        let lines = if paused {
+                            vec!["Updates resumed".to_string()]
+                        } else {
+                            Vec::new()
+                        };
+                        self.notify(Notification::new(
+                            format!("Back on {}", link),
+                            lines,
+                            Severity::Info,
+                        ));
+                    }
+                    _ => {}
+                }
+                None
+            }
+            ConnectivityEvent::FailOver => Some(AppAction::SetFailover(true)),
+            ConnectivityEvent::FailBack => Some(AppAction::SetFailover(false)),
+        }
+    }
+
+    /// Fetches the notes of the next pending update, unless already there.
+    fn release_notes_action(&self) -> Option<AppAction> {
+        let state = read_state_file().ok()?;
+        let (name, app) = update_history::next_pending(&state)?;
+        if self
+            .system_state
+            .release_notes
+            .as_ref()
+            .is_some_and(|notes| notes.app == name && notes.version == app.pending_version)
+        {
+            return None;
+        }
+        let repo = update_source::github_repo(&name, &app.pending_source)?;
+        Some(AppAction::FetchReleaseNotes(
+            name,
+            app.pending_version,
+            repo,
+        ))
+    }
+
+    fn set_update_hold(&mut self, app_name: &str, hold: bool) {
+        if let Err(e) = self.update_manager.set_hold(app_name, hold) {
+            self.notify(Notification::new(
+                "Update hold not changed",
+                vec![e.to_string()],
+                Severity::Warning,
+            ));
+        }
+    }
+
+    /// Delivers the notification to all channels and queues it for display.
+    pub fn notify(&mut self, notification: Notification) {
+        self.notifier.notify(&notification);
+        if self.headless {
+            info!("{}: {}", notification.title, notification.lines.join(", "));
+        } else {
+            self.pending_notifications.push_back(notification);
+        }
+    }
+
+    /// Takes in the result of an action run in the background.
+    fn on_action_complete(&mut self, result: AppActionComplete) -> Vec<AppAction> {
+        let mut actions = Vec::new();
+        match result {
+            AppActionComplete::NetworkStatus(status) => {
+                self.connectivity_cache.last_result = status;
+                self.system_state.network_status = status;
+            }
+            AppActionComplete::AmaruStatus(info) => {
+                self.system_state.amaru_status = info;
+            }
+            AppActionComplete::WifiConnection(status) => {
+                if let WifiConnectionStatus::Failed(_) = status {
+                    self.system_state.telemetry.record_error("wifi");
+                }
+                self.system_state.wifi_connection_status = status;
+            }
+            AppActionComplete::Links {
+                links,
+                primary_reachable,
+            } => {
+                let connectivity = self.system_state.network_status.connectivity;
+                for event in self
+                    .connectivity
+                    .observe(&links, connectivity, primary_reachable)
+                {
+                    actions.extend(self.on_connectivity_event(event));
+                }
+            }
+            AppActionComplete::FailoverFailed => {
+                self.connectivity.failover_failed();
+                self.system_state.telemetry.record_error("lte");
+            }
+            AppActionComplete::CaptivePortal(portal) => {
+                if let Some(url) = &portal
+                    && self.system_state.captive_portal.as_ref() != Some(url)
+                {
+                    warn!("Traffic is intercepted by a captive portal: {}", url);
+                    self.notifier.notify(&Notification::new(
+                        "Captive portal",
+                        vec![url.clone()],
+                        Severity::Warning,
+                    ));
+                    self.portal_warning_pending = true;
+                }
+                self.system_state.captive_portal = portal;
+            }
+            AppActionComplete::SavedNetworks(networks) => {
+                self.system_state.saved_networks = networks;
+            }
+            AppActionComplete::NetworkSwitchFailed(e) => {
+                self.system_state.telemetry.record_error("wifi");
+                self.notify(Notification::new(
+                    "Network switch failed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::Vpn(status) => {
+                self.system_state.vpn = status;
+            }
+            AppActionComplete::VpnFailed(e) => {
+                self.notify(Notification::new(
+                    "VPN not changed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::Modem(status) => {
+                self.system_state.modem = status;
+            }
+            AppActionComplete::ApnFailed(e) => {
+                self.notify(Notification::new(
+                    "APN change failed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::Peers(snapshot) => {
+                self.system_state.peers = snapshot;
+            }
+            AppActionComplete::LatencySamples(samples) => {
+                self.system_state.latency.record(&samples);
+            }
+            AppActionComplete::Boot(Some(report)) => {
+                self.boot_recorded = true;
+                if let Some(regression) = self.system_state.boot.record(report) {
+                    let mut lines = vec![format!(
+                        "{:.1}s slower than usual",
+                        regression.slower_by_secs
+                    )];
+                    lines.extend(
+                        regression
+                            .culprits
+                            .iter()
+                            .take(2)
+                            .map(|(unit, _)| unit.clone()),
+                    );
+                    self.notify(Notification::new("Slow boot", lines, Severity::Warning));
+                }
+            }
+            AppActionComplete::Boot(None) => {}
+            AppActionComplete::Units(units) => {
+                self.system_state.units = units;
+            }
+            AppActionComplete::BinariesVerified(modified) => {
+                let new = modified
+                    .iter()
+                    .any(|name| !self.modified_binaries.contains(name));
+                if new {
+                    let mut lines = modified.clone();
+                    lines.push("changed since installed".to_string());
+                    self.notify(Notification::new(
+                        "Binary modified",
+                        lines,
+                        Severity::Critical,
+                    ));
+                }
+                self.modified_binaries = modified;
+            }
+            AppActionComplete::Storage(sample) => {
+                self.system_state.storage.record(sample);
+                if sample.is_low() && !self.low_disk_alerted {
+                    let mut lines = vec![format!(
+                        "{} free ({}%)",
+                        format_bytes(sample.free_bytes),
+                        sample.free_percent()
+                    )];
+                    if let Some(days) = self.system_state.storage.days_until_full() {
+                        lines.push(format!("Full in ~{:.0} days", days));
+                    }
+                    self.notify(Notification::new(
+                        "Low disk space",
+                        lines,
+                        Severity::Critical,
+                    ));
+                }
+                self.low_disk_alerted = sample.is_low();
+            }
+            AppActionComplete::Thermal(status) => {
+                if status.needs_attention() && !self.thermal_alerted {
+                    let mut lines = Vec::new();
+                    if let Some(celsius) = status.celsius {
+                        lines.push(format!("SoC at {:.0}°C", celsius));
+                    }
+                    if let Some(throttled) = status.throttled {
+                        lines.extend(throttled.now().iter().map(|s| s.to_string()));
+                    }
+                    self.notify(Notification::new("Overheating", lines, Severity::Critical));
+                }
+                self.thermal_alerted = status.needs_attention();
+                self.system_state.thermal = status;
+            }
+            AppActionComplete::Environment(reading) => {
+                if reading.is_hot() && !self.environment_alerted {
+                    self.notify(Notification::new(
+                        "Enclosure overheating",
+                        vec![format!("Enclosure at {:.0}°C", reading.celsius)],
+                        Severity::Critical,
+                    ));
+                }
+                self.environment_alerted = reading.is_hot();
+                self.system_state.environment = Some(reading);
+            }
+            AppActionComplete::Power(status) => {
+                let previous = self.system_state.power;
+                let status = status.following(previous);
+                actions.extend(self.on_power_status(previous, status));
+                self.system_state.power = Some(status);
+            }
+            AppActionComplete::NodeStoppedOnLowBattery => {
+                self.notify(Notification::new(
+                    "Node stopped",
+                    vec!["Restart it once charged".to_string()],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::NodeStopFailed(e) => {
+                self.notify(Notification::new(
+                    "Node not stopped",
+                    vec![e],
+                    Severity::Critical,
+                ));
+            }
+            AppActionComplete::Clock(status) => {
+                self.on_clock_status(&status);
+                self.system_state.clock = Some(status);
+            }
+            AppActionComplete::Timezones(timezones) => {
+                self.system_state.timezones = timezones;
+            }
+            AppActionComplete::ReleaseNotes(notes) => {
+                self.system_state.release_notes = Some(notes);
+            }
+            AppActionComplete::TimezoneFailed(e) => {
+                self.notify(Notification::new(
+                    "Time zone not changed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::IpConfig(settings) => {
+                if self.system_state.ip_config.applying && settings.is_ok() {
+                    self.notify(Notification::new(
+                        "IP address changed",
+                        vec![format!("{} reconfigured", ip_config::interface())],
+                        Severity::Info,
+                    ));
+                }
+                self.system_state.ip_config = IpConfigState {
+                    current: Some(settings),
+                    applying: false,
+                };
+            }
+            AppActionComplete::IpConfigFailed(e) => {
+                self.system_state.ip_config.applying = false;
+                self.notify(Notification::new(
+                    "IP address not changed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::Firewall(status) => {
+                if self.system_state.firewall.applying && status.is_ok() {
+                    self.notify(Notification::new(
+                        "Firewall changed",
+                        Vec::new(),
+                        Severity::Info,
+                    ));
+                }
+                self.system_state.firewall = FirewallState {
+                    current: Some(status),
+                    applying: false,
+                };
+            }
+            AppActionComplete::FirewallFailed(e) => {
+                self.system_state.firewall.applying = false;
+                self.notify(Notification::new(
+                    "Firewall not changed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::Coredumps(dumps) => {
+                self.system_state.coredumps = dumps;
+            }
+            AppActionComplete::CoredumpExported(name) => {
+                self.notify(Notification::new(
+                    "Crash exported",
+                    vec![name],
+                    Severity::Info,
+                ));
+            }
+            AppActionComplete::CoredumpExportFailed(e) => {
+                self.notify(Notification::new(
+                    "Export failed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::DiagnosticsExported(location) => {
+                self.notify(Notification::new(
+                    "Support bundle saved",
+                    vec![location],
+                    Severity::Info,
+                ));
+            }
+            AppActionComplete::DiagnosticsExportFailed(e) => {
+                self.notify(Notification::new(
+                    "Support bundle failed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::OfflineUpdatesStaged(staged) if staged.is_empty() => {
+                self.notify(Notification::new(
+                    "No update on USB",
+                    vec!["No newer verified archive".to_string()],
+                    Severity::Info,
+                ));
+            }
+            AppActionComplete::OfflineUpdatesStaged(staged) => {
+                self.notify(Notification::new(
+                    "Updates staged from USB",
+                    staged,
+                    Severity::Info,
+                ));
+            }
+            AppActionComplete::OfflineUpdatesFailed(e) => {
+                self.notify(Notification::new(
+                    "USB update failed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::UsbDetected(present) => {
+                self.system_state.usb_drive = present;
+            }
+            AppActionComplete::BackupProgress(progress) => {
+                self.system_state.backup = BackupStatus::Running(progress);
+            }
+            AppActionComplete::BackedUp(record) => {
+                self.notify(Notification::new(
+                    "Backup done",
+                    vec![record.name.clone(), format_bytes(record.size)],
+                    Severity::Info,
+                ));
+                self.system_state.backup = BackupStatus::Done(record);
+            }
+            AppActionComplete::BackupFailed(e) => {
+                self.notify(Notification::new(
+                    "Backup failed",
+                    vec![e.clone()],
+                    Severity::Warning,
+                ));
+                self.system_state.backup = BackupStatus::Failed(e);
+            }
+            AppActionComplete::Snapshots(snapshots) => {
+                self.system_state.snapshots = snapshots;
+            }
+            AppActionComplete::RestoreProgress(progress) => {
+                self.system_state.restore = RestoreStatus::Running(progress);
+            }
+            AppActionComplete::Restored(name) => {
+                self.notify(Notification::new(
+                    "Ledger restored",
+                    vec![name.clone()],
+                    Severity::Info,
+                ));
+                self.system_state.restore = RestoreStatus::Done(name);
+            }
+            AppActionComplete::RestoreFailed(e) => {
+                self.notify(Notification::new(
+                    "Restore failed",
+                    vec![e.clone()],
+                    Severity::Warning,
+                ));
+                self.system_state.restore = RestoreStatus::Failed(e);
+            }
+            AppActionComplete::Processes(processes) => {
+                self.system_state.processes = processes;
+            }
+            AppActionComplete::ProcessLogs(pid, lines) => {
+                self.system_state.process_logs = Some((pid, lines));
+            }
+            AppActionComplete::ProcessLogsFailed(e) => {
+                self.notify(Notification::new(
+                    "Logs unavailable",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::Listing(listing) => {
+                self.system_state.browser = Some(listing);
+            }
+            AppActionComplete::BrowseFailed(e) => {
+                self.notify(Notification::new("Can't open", vec![e], Severity::Warning));
+            }
+            AppActionComplete::FileDeleted(name) => {
+                self.notify(Notification::new(
+                    "File deleted",
+                    vec![name],
+                    Severity::Info,
+                ));
+            }
+            AppActionComplete::NodeConfigSaved => {
+                self.system_state.node_config_status = ConfigStatus::Saved;
+            }
+            AppActionComplete::NodeConfigFailed(e) => {
+                self.system_state.node_config_status = ConfigStatus::Idle;
+                self.notify(Notification::new(
+                    "Config not saved",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::NodeRestarted => {
+                self.system_state.node_config_status = ConfigStatus::Idle;
+                self.notify(Notification::new(
+                    "Node restarted",
+                    vec!["Running with the new config".to_string()],
+                    Severity::Info,
+                ));
+            }
+            AppActionComplete::PowerFailed(e) => {
+                self.notify(Notification::new(
+                    "Power off failed",
+                    vec![e],
+                    Severity::Critical,
+                ));
+            }
+            AppActionComplete::NodeRestartFailed(e) => {
+                self.system_state.node_config_status = ConfigStatus::Saved;
+                self.notify(Notification::new(
+                    "Restart failed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::PeerTested(address, reachability) => {
+                self.system_state.peer_tests.insert(address, reachability);
+            }
+            AppActionComplete::PortChecked(check) => {
+                self.system_state.port_check = PortCheckState {
+                    checking: false,
+                    last: Some(check),
+                };
+            }
+            AppActionComplete::Pools(snapshot) => {
+                self.system_state.pools = snapshot;
+            }
+            AppActionComplete::StakePool(summary) => {
+                self.system_state.stake_pool = summary;
+            }
+            AppActionComplete::Ticker(snapshot) => {
+                self.system_state.ticker = snapshot;
+            }
+            AppActionComplete::Fleet(results) => {
+                fleet::merge(&mut self.system_state.fleet, results)
+            }
+            AppActionComplete::Leadership(schedule) => {
+                self.system_state.leadership = schedule;
+            }
+            AppActionComplete::Faucet(status) => {
+                self.on_faucet_status(&status);
+                self.system_state.faucet = status;
+            }
+            AppActionComplete::TelemetrySent => {
+                self.system_state.telemetry.mark_reported();
+            }
+            AppActionComplete::ScreenshotSaved(path) => {
+                self.pending_notifications.push_back(Notification::new(
+                    "Screenshot saved",
+                    vec![path.display().to_string()],
+                    Severity::Info,
+                ));
+            }
+            AppActionComplete::ScreenshotFailed(e) => {
+                warn!("Failed to save screenshot: {}", e);
+                self.pending_notifications.push_back(Notification::new(
+                    "Screenshot failed",
+                    vec![e],
+                    Severity::Warning,
+                ));
+            }
+            AppActionComplete::TaskProgress(id, fraction) => {
+                if let Some(task) = self
+                    .system_state
+                    .tasks
+                    .iter_mut()
+                    .find(|task| task.id == id)
+                {
+                    task.progress = Some(fraction);
+                }
+            }
+            AppActionComplete::TaskEnded(id, state) => {
+                self.tasks.finished(id);
+                tasks::record_end(&mut self.system_state.tasks, id, state);
+            }
+        }
+        actions
+    }
+
+    /// Swaps the data shown between the synthetic and the node's.
+    fn on_demo_toggled(&mut self) -> Vec<AppAction> {
+        let (title, line) = if self.demo.is_on() {
+            ("Demo mode", "Synthetic data, hold B to leave")
+        } else {
+            self.system_state.pools = pools::read_cache().unwrap_or_default();
+            self.system_state.leadership = LeadershipSchedule::default();
+            ("Demo mode off", "Back to the node's data")
+        };
+        // Local only, not worth reaching the operator
+        self.pending_notifications.push_back(Notification::new(
+            title,
+            vec![line.to_string()],
+            Severity::Info,
+        ));
+        let mut actions = vec![AppAction::CheckAmaruStatus, AppAction::RefreshPeers];
+        if self.demo.is_on() || self.pools_config.is_enabled() {
+            actions.push(AppAction::RefreshPools);
+        }
+        if self.demo.is_on() || self.leadership_config.is_enabled() {
+            actions.push(AppAction::RefreshLeadership);
+        }
+        actions
+    }
+
+    /// Reports the outcome of a faucet request, which can take minutes.
+    fn on_faucet_status(&mut self, status: &FaucetStatus) {
+        match status {
+            FaucetStatus::Received { lovelace } => self.notify(Notification::new(
+                "Faucet funds received",
+                vec![format!("{} ADA arrived", format_ada(*lovelace))],
+                Severity::Info,
+            )),
+            FaucetStatus::Failed(e) => {
+                self.system_state.telemetry.record_error("faucet");
+                self.notify(Notification::new(
+                    "Faucet request failed",
+                    vec![e.clone()],
+                    Severity::Warning,
+                ));
+            }
+            _ => {}
+        }
+    }
+
     pub fn draw(&self, frame: &mut Frame) {
+        if self.blanking.is_blanked() {
+            return;
+        }
         let ctx = AppContext {
             frame: &self.frame_state,
             system: &self.system_state,
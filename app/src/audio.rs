@@ -0,0 +1,149 @@
+use crate::notifications::{Notification, NotificationChannel, Severity};
+use anyhow::Result;
+use std::env;
+use std::f64::consts::TAU;
+use std::fs;
+use std::process::{Command, Stdio};
+use std::thread;
+use tracing::warn;
+
+/// Written before each alert, played by `aplay`
+const TONE_PATH: &str = "/tmp/amaru_pi_alert.wav";
+const SAMPLE_RATE: u32 = 22_050;
+const BEEP_MS: u32 = 150;
+const PAUSE_MS: u32 = 100;
+/// Out of 1, loud enough in a rack without clipping
+const VOLUME: f64 = 0.6;
+
+/// How alerts are played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioMode {
+    /// Beeps, more and higher the more severe
+    Tone,
+    /// The title and lines, read by `espeak-ng`
+    Speech,
+}
+
+/// Plays notifications through the audio jack or HDMI of the Pi, for when
+/// the screen can't be seen.
+pub struct AudioChannel {
+    mode: AudioMode,
+    min_severity: Severity,
+}
+
+impl AudioChannel {
+    /// Reads `AMARU_PI_AUDIO`, `tone` or `speech`, off otherwise, and
+    /// `AMARU_PI_AUDIO_SEVERITY`, the least severe notification played,
+    /// `critical` by default.
+    pub fn from_env() -> Option<Self> {
+        let mode = match env::var("AMARU_PI_AUDIO")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "tone" => AudioMode::Tone,
+            "speech" => AudioMode::Speech,
+            _ => return None,
+        };
+        let min_severity = match env::var("AMARU_PI_AUDIO_SEVERITY")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "info" => Severity::Info,
+            "warning" => Severity::Warning,
+            _ => Severity::Critical,
+        };
+        Some(Self { mode, min_severity })
+    }
+}
+
+/// Beeps of a notification: how many, and how high.
+fn beeps(severity: Severity) -> (u32, f64) {
+    match severity {
+        Severity::Info => (1, 440.0),
+        Severity::Warning => (2, 660.0),
+        Severity::Critical => (3, 880.0),
+    }
+}
+
+/// A mono 16 bits WAV of `count` beeps.
+fn tone_wav(count: u32, frequency: f64) -> Vec<u8> {
+    let beep = (SAMPLE_RATE * BEEP_MS / 1000) as usize;
+    let pause = (SAMPLE_RATE * PAUSE_MS / 1000) as usize;
+    let mut samples: Vec<i16> = Vec::new();
+    for _ in 0..count {
+        samples.extend((0..beep).map(|i| {
+            let t = i as f64 / f64::from(SAMPLE_RATE);
+            (f64::sin(TAU * frequency * t) * VOLUME * f64::from(i16::MAX)) as i16
+        }));
+        samples.extend(std::iter::repeat_n(0, pause));
+    }
+
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, mono
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+/// Plays without waiting, the player is reaped in the background.
+fn play(mut command: Command) -> Result<()> {
+    let mut child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    thread::spawn(move || {
+        if let Err(e) = child.wait() {
+            warn!("Failed to play an alert: {}", e);
+        }
+    });
+    Ok(())
+}
+
+impl NotificationChannel for AudioChannel {
+    fn name(&self) -> &str {
+        "audio"
+    }
+
+    fn send(&mut self, notification: &Notification) -> Result<()> {
+        if notification.severity < self.min_severity {
+            return Ok(());
+        }
+        match self.mode {
+            AudioMode::Tone => {
+                let (count, frequency) = beeps(notification.severity);
+                fs::write(TONE_PATH, tone_wav(count, frequency))?;
+                let mut command = Command::new("aplay");
+                command.args(["-q", TONE_PATH]);
+                play(command)
+            }
+            AudioMode::Speech => {
+                let mut text = notification.title.clone();
+                for line in &notification.lines {
+                    text.push_str(". ");
+                    text.push_str(line);
+                }
+                let mut command = Command::new("espeak-ng");
+                command.arg(text);
+                play(command)
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use crate::backends::Backend;
 use crate::button::{ButtonId, InputEvent};
-use anyhow::Result;
+use crate::geometry::{self, Geometry};
+use crate::inputs::gpio::{GpioButtonsConfig, spawn_buttons};
+use crate::outputs::led::RgbLed;
+use anyhow::{Result, bail};
 use embedded_hal_bus::spi::{ExclusiveDevice, NoDelay};
 use mipidsi::interface::SpiInterface;
 use mipidsi::models::ST7789;
@@ -10,13 +13,11 @@ use mousefood::{EmbeddedBackend, EmbeddedBackendConfig};
 use rppal::gpio::{Gpio, OutputPin};
 use rppal::hal::Delay;
 use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
-use std::collections::HashMap;
 use std::sync::mpsc::Receiver;
 
-pub mod input;
-
-const W: i32 = 240;
-const H: i32 = 320;
+/// Largest panel of the ST7789, in portrait
+const MAX_W: u32 = 240;
+const MAX_H: u32 = 320;
 const BUTTON_A: u8 = 5;
 const BUTTON_B: u8 = 6;
 const BUTTON_X: u8 = 16;
@@ -42,30 +43,58 @@ impl embedded_hal::digital::ErrorType for NoCs {
 
 type EbSpi = SpiInterface<'static, ExclusiveDevice<Spi, NoCs, NoDelay>, OutputPin>;
 
-/// Initializes the display, GPIO, and the input handler thread.
+/// The ST7789 scans in portrait, landscape panels are turned a quarter.
+fn orientation(geometry: &Geometry) -> Orientation {
+    let base = if geometry.panel.width > geometry.panel.height {
+        geometry::Rotation::Deg270
+    } else {
+        geometry::Rotation::Deg0
+    };
+    let rotation = match base.then(geometry.rotation) {
+        geometry::Rotation::Deg0 => Rotation::Deg0,
+        geometry::Rotation::Deg90 => Rotation::Deg90,
+        geometry::Rotation::Deg180 => Rotation::Deg180,
+        geometry::Rotation::Deg270 => Rotation::Deg270,
+    };
+    Orientation {
+        rotation,
+        mirrored: false,
+    }
+}
+
+/// Initializes the display, GPIO, the input handler thread, the RGB LED and
+/// the backlight, left on. The HAT has no other button than A, B, X and Y.
 pub fn setup_hardware_and_input() -> Result<(
     Backend<Display<EbSpi, ST7789, NoResetPin>>,
     Receiver<InputEvent>,
+    RgbLed,
+    OutputPin,
 )> {
     println!("Setting up display_hat hardware and input");
+    let geometry = Geometry::from_env();
+    // Panels are landscape, the controller portrait
+    let (width, height) = (geometry.panel.height, geometry.panel.width);
+    if width > MAX_W || height > MAX_H {
+        bail!(
+            "A {}x{} panel can't be driven by the ST7789",
+            geometry.panel.width,
+            geometry.panel.height
+        );
+    }
     let gpio = Gpio::new()?;
     let dc = gpio.get(SPI_DC)?.into_output();
     let mut backlight = gpio.get(BACKLIGHT)?.into_output();
     backlight.set_high();
 
-    let mut pin_map = HashMap::new();
-    pin_map.insert(ButtonId::A, gpio.get(BUTTON_A)?.into_input_pullup());
-    pin_map.insert(ButtonId::B, gpio.get(BUTTON_B)?.into_input_pullup());
-    pin_map.insert(ButtonId::X, gpio.get(BUTTON_X)?.into_input_pullup());
-    pin_map.insert(ButtonId::Y, gpio.get(BUTTON_Y)?.into_input_pullup());
-    let input_event_receiver = input::InputHandler::spawn(pin_map)?;
+    let buttons_config = GpioButtonsConfig::from_env(&[
+        (ButtonId::A, BUTTON_A),
+        (ButtonId::B, BUTTON_B),
+        (ButtonId::X, BUTTON_X),
+        (ButtonId::Y, BUTTON_Y),
+    ]);
+    let input_event_receiver = spawn_buttons(&gpio, buttons_config)?;
 
-    let mut led_r = gpio.get(LED_R)?.into_output();
-    let mut led_g = gpio.get(LED_G)?.into_output();
-    let mut led_b = gpio.get(LED_B)?.into_output();
-    led_r.set_high();
-    led_g.set_high();
-    led_b.set_high();
+    let led = RgbLed::new(&gpio, (LED_R, LED_G, LED_B))?;
 
     // Initialize SPI and display
     let spi = Spi::new(Bus::Spi0, SlaveSelect::Ss1, 15_000_000_u32, Mode::Mode0)?;
@@ -74,11 +103,8 @@ pub fn setup_hardware_and_input() -> Result<(
     let di = SpiInterface::new(spi_device, dc, Box::leak(buffer));
     let mut delay = Delay::new();
     let display: Display<EbSpi, ST7789, NoResetPin> = Builder::new(ST7789, di)
-        .display_size(W as u16, H as u16)
-        .orientation(Orientation {
-            rotation: Rotation::Deg270,
-            mirrored: false,
-        })
+        .display_size(width as u16, height as u16)
+        .orientation(orientation(&geometry))
         .invert_colors(ColorInversion::Inverted)
         .init(&mut delay)
         .unwrap();
@@ -86,9 +112,10 @@ pub fn setup_hardware_and_input() -> Result<(
     let backend_config = EmbeddedBackendConfig {
         // Define how to display newly rendered widgets to the simulator window
         flush_callback: Box::new(move |_display| {}),
+        font_regular: geometry.scale.font(),
         ..Default::default()
     };
     let backend = EmbeddedBackend::new(Box::leak(Box::new(display)), backend_config);
 
-    Ok((backend, input_event_receiver))
+    Ok((backend, input_event_receiver, led, backlight))
 }
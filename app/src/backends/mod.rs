@@ -2,6 +2,8 @@ use mousefood::{EmbeddedBackend, prelude::Rgb565};
 
 #[cfg(feature = "display_hat")]
 pub mod display_hat;
+#[cfg(feature = "oled")]
+pub mod oled;
 #[cfg(feature = "simulator")]
 pub mod simulator;
 
@@ -0,0 +1,161 @@
+use anyhow::{Result, anyhow};
+use mousefood::embedded_graphics::Pixel;
+use mousefood::embedded_graphics::draw_target::DrawTarget;
+use mousefood::embedded_graphics::geometry::{OriginDimensions, Size};
+use mousefood::embedded_graphics::pixelcolor::BinaryColor;
+use rppal::i2c::I2c;
+use std::convert::Infallible;
+use std::env;
+
+const WIDTH: u32 = 128;
+const HEIGHT: u32 = 64;
+/// Rows of 8 pixels, a byte per column
+const PAGES: usize = (HEIGHT / 8) as usize;
+const I2C_BUS: u8 = 1;
+const DEFAULT_ADDRESS: u16 = 0x3c;
+/// Control bytes preceding commands and display data
+const COMMAND: u8 = 0x00;
+const DATA: u8 = 0x40;
+
+/// Controllers of 128x64 OLEDs. Both take the same commands, but for the
+/// charge pump, and the SH1106 has 132 columns of which the middle 128 show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    Ssd1306,
+    Sh1106,
+}
+
+impl Controller {
+    fn column_offset(self) -> u8 {
+        match self {
+            Controller::Ssd1306 => 0,
+            Controller::Sh1106 => 2,
+        }
+    }
+
+    fn init_commands(self) -> Vec<u8> {
+        let mut commands = vec![
+            0xae, // Display off
+            0xd5, 0x80, // Clock divide ratio
+            0xa8, 0x3f, // Multiplex ratio, 64 rows
+            0xd3, 0x00, // No display offset
+            0x40, // Start line 0
+        ];
+        match self {
+            // Charge pump on, page addressing
+            Controller::Ssd1306 => commands.extend([0x8d, 0x14, 0x20, 0x02]),
+            // DC-DC on
+            Controller::Sh1106 => commands.extend([0xad, 0x8b]),
+        }
+        commands.extend([
+            0xa1, // Columns right to left
+            0xc8, // Rows bottom to top
+            0xda, 0x12, // Alternative COM pins
+            0x81, 0xcf, // Contrast
+            0xd9, 0xf1, // Pre-charge period
+            0xdb, 0x40, // VCOMH deselect level
+            0xa4, // Show the RAM
+            0xa6, // Not inverted
+            0xaf, // Display on
+        ]);
+        commands
+    }
+}
+
+/// A 128x64 monochrome OLED on the I2C bus of the Pi, drawn in memory then
+/// sent on `flush`.
+pub struct Oled {
+    i2c: I2c,
+    controller: Controller,
+    buffer: [u8; WIDTH as usize * PAGES],
+}
+
+impl Oled {
+    /// Reads `AMARU_PI_OLED`, `ssd1306` or `sh1106`, and
+    /// `AMARU_PI_OLED_ADDRESS`, `0x3c` by default. `None` without an OLED.
+    pub fn from_env() -> Result<Option<Self>> {
+        let controller = match env::var("AMARU_PI_OLED")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return Ok(None),
+            "ssd1306" => Controller::Ssd1306,
+            "sh1106" => Controller::Sh1106,
+            other => return Err(anyhow!("Unknown OLED controller: {}", other)),
+        };
+        let address = match env::var("AMARU_PI_OLED_ADDRESS") {
+            Ok(value) => u16::from_str_radix(value.trim().trim_start_matches("0x"), 16)
+                .map_err(|_| anyhow!("Invalid AMARU_PI_OLED_ADDRESS: {}", value))?,
+            Err(_) => DEFAULT_ADDRESS,
+        };
+        Self::new(controller, address).map(Some)
+    }
+
+    pub fn new(controller: Controller, address: u16) -> Result<Self> {
+        let mut i2c = I2c::with_bus(I2C_BUS)?;
+        i2c.set_slave_address(address)?;
+        let mut oled = Self {
+            i2c,
+            controller,
+            buffer: [0; WIDTH as usize * PAGES],
+        };
+        oled.commands(&controller.init_commands())?;
+        Ok(oled)
+    }
+
+    fn commands(&mut self, commands: &[u8]) -> Result<()> {
+        let mut bytes = vec![COMMAND];
+        bytes.extend_from_slice(commands);
+        self.i2c.write(&bytes)?;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.fill(0);
+    }
+
+    /// Sends the buffer, a page at a time.
+    pub fn flush(&mut self) -> Result<()> {
+        let column = self.controller.column_offset();
+        for page in 0..PAGES {
+            self.commands(&[0xb0 | page as u8, column & 0x0f, 0x10 | column >> 4])?;
+            let mut bytes = vec![DATA];
+            bytes.extend_from_slice(&self.buffer[page * WIDTH as usize..][..WIDTH as usize]);
+            self.i2c.write(&bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Oled {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for Oled {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y))
+                && x < WIDTH
+                && y < HEIGHT
+            {
+                let index = (y / 8 * WIDTH + x) as usize;
+                let bit = 1 << (y % 8);
+                match color {
+                    BinaryColor::On => self.buffer[index] |= bit,
+                    BinaryColor::Off => self.buffer[index] &= !bit,
+                }
+            }
+        }
+        Ok(())
+    }
+}
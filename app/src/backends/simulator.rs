@@ -1,39 +1,216 @@
 use crate::backends::Backend;
-use crate::button::{ButtonId, ButtonPress, InputEvent};
-use embedded_graphics_simulator::sdl2::Keycode;
+use crate::button::{self, ButtonId, ButtonPress, ButtonTimings, InputEvent};
+use crate::geometry::Geometry;
+use crate::inputs::mapping::Command;
+use crate::screen_text;
+use anyhow::Result;
+use embedded_graphics_simulator::sdl2::{Keycode, Mod, MouseButton};
 use embedded_graphics_simulator::{OutputSettings, SimulatorDisplay, SimulatorEvent, Window};
-use mousefood::embedded_graphics::geometry::Size;
 use mousefood::{EmbeddedBackend, EmbeddedBackendConfig, prelude::Rgb565};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::sync::Mutex;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
 
-const DOUBLE_PRESS_TIMEOUT: Duration = Duration::from_millis(200);
+/// Panels wider than this are shown at their size, smaller ones doubled
+const MAX_SCALED_WIDTH: u32 = 480;
+/// Keys are pressed faster than buttons
+const DOUBLE_PRESS: Duration = Duration::from_millis(200);
+/// The screen text is written once a second
+const EXPECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Characters typed while typing, until taken by the UI
+static TYPED: Mutex<Vec<Command>> = Mutex::new(Vec::new());
+
+/// A step of a scenario, e.g. `{"press": "A", "type": "Double"}`,
+/// `{"wait_ms": 500}`, `{"expect": "Synced"}` or `{"capture": "tip"}`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Step {
+    Press {
+        press: ButtonId,
+        #[serde(rename = "type", default = "short_press")]
+        press_type: ButtonPress,
+    },
+    Wait {
+        wait_ms: u64,
+    },
+    /// Waits for the display to show the text
+    Expect {
+        expect: String,
+    },
+    /// Saves the display as `<name>.png`
+    Capture {
+        capture: String,
+    },
+}
+
+fn short_press() -> ButtonPress {
+    ButtonPress::Short
+}
+
+#[derive(Debug, Deserialize)]
+struct ScenarioFile {
+    steps: Vec<Step>,
+}
+
+/// Input played instead of the keyboard's, with checkpoints on what the
+/// display shows, for screen flows to be tested without hardware. Exits once
+/// played, with 1 if a checkpoint failed.
+struct Scenario {
+    steps: VecDeque<Step>,
+    capture_dir: PathBuf,
+    /// Until when the current step waits
+    deadline: Option<Instant>,
+}
+
+impl Scenario {
+    /// Reads the scenario at `AMARU_PI_SIM_SCENARIO`, if set. Captures go to
+    /// `AMARU_PI_SIM_CAPTURE_DIR`, the current directory by default.
+    fn from_env() -> Option<Self> {
+        let path = env::var("AMARU_PI_SIM_SCENARIO").ok()?;
+        let scenario = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| Ok(serde_json::from_str::<ScenarioFile>(&data)?));
+        let scenario = match scenario {
+            Ok(scenario) => scenario,
+            Err(e) => {
+                error!("Invalid scenario {}: {}", path, e);
+                exit(1);
+            }
+        };
+        info!("Playing {} steps of {}", scenario.steps.len(), path);
+        Some(Self {
+            steps: scenario.steps.into(),
+            capture_dir: env::var("AMARU_PI_SIM_CAPTURE_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_default(),
+            deadline: None,
+        })
+    }
+
+    /// Plays the next step once the current one is done. Called once a
+    /// frame, after it is drawn.
+    fn advance(&mut self, display: &SimulatorDisplay<Rgb565>, tx: &Sender<InputEvent>) {
+        let Some(step) = self.steps.front() else {
+            info!("Scenario passed");
+            exit(0);
+        };
+        let done = match step {
+            Step::Press { press, press_type } => {
+                tx.send(InputEvent {
+                    id: *press,
+                    press_type: *press_type,
+                })
+                .ok();
+                true
+            }
+            Step::Wait { wait_ms } => {
+                let deadline = *self
+                    .deadline
+                    .get_or_insert_with(|| Instant::now() + Duration::from_millis(*wait_ms));
+                Instant::now() >= deadline
+            }
+            Step::Expect { expect } => {
+                let deadline = *self
+                    .deadline
+                    .get_or_insert_with(|| Instant::now() + EXPECT_TIMEOUT);
+                let shown = screen_text::read().unwrap_or_default();
+                if !shown.contains(expect.as_str()) && Instant::now() >= deadline {
+                    error!("Scenario failed, expected {:?} on:\n{}", expect, shown);
+                    exit(1);
+                }
+                shown.contains(expect.as_str())
+            }
+            Step::Capture { capture } => {
+                let path = self.capture_dir.join(format!("{}.png", capture));
+                if let Err(e) = save_capture(display, &path) {
+                    warn!("Failed to capture {}: {}", path.display(), e);
+                }
+                true
+            }
+        };
+        if done {
+            self.steps.pop_front();
+            self.deadline = None;
+        }
+    }
+}
+
+fn save_capture(display: &SimulatorDisplay<Rgb565>, path: &Path) -> Result<()> {
+    display
+        .to_rgb_output_image(&OutputSettings::default())
+        .save_png(path)?;
+    Ok(())
+}
 type PendingPress = Option<(ButtonId, Instant)>;
+/// The key currently held down, and whether its long press was sent
+type HeldKey = Option<(ButtonId, bool)>;
+
+/// Characters typed in the simulator window, to be handled as commands. Tab
+/// switches between typing and pressing buttons with the letter keys.
+pub fn take_typed() -> Vec<Command> {
+    TYPED
+        .lock()
+        .map(|mut typed| std::mem::take(&mut *typed))
+        .unwrap_or_default()
+}
 
 /// Creates the simulator backend and returns it along with a channel receiver
 /// for input events generated by the simulator window.
+///
+/// A/B/X/Y are pressed with their keys, with the arrows (Right, Left, Up and
+/// Down), with Return and Escape for A and B, or with the mouse: left click
+/// for A, right click for B and the wheel for X and Y.
 pub fn setup_simulator_and_input() -> (Backend<SimulatorDisplay<Rgb565>>, Receiver<InputEvent>) {
     println!("Setting up simulator hardware and input");
+    // Rotated, the window shows the panel as mounted
+    let geometry = Geometry::from_env();
+    let size = geometry.size();
     let mut simulator_window = Window::new(
         "Simulator",
         &OutputSettings {
-            scale: 2,
+            scale: if size.width > MAX_SCALED_WIDTH { 1 } else { 2 },
             ..Default::default()
         },
     );
-    let display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    // Long presses come from the key repeat of the OS, only the double
+    // press window applies
+    button::configure_timings(ButtonTimings {
+        double_press: DOUBLE_PRESS,
+        ..ButtonTimings::default()
+    });
+    let display = SimulatorDisplay::<Rgb565>::new(size);
     let (tx, rx) = mpsc::channel();
     let mut pending_press: PendingPress = None;
+    let mut held_key: HeldKey = None;
+    let mut typing = false;
+    let mut scenario = Scenario::from_env();
 
     let backend_config: EmbeddedBackendConfig<SimulatorDisplay<Rgb565>, _> =
         EmbeddedBackendConfig {
             flush_callback: Box::new(move |display| {
                 simulator_window.update(display);
+                if let Some(scenario) = &mut scenario {
+                    scenario.advance(display, &tx);
+                }
 
-                handle_pending_press_timeout(&mut pending_press, &tx);
-                process_simulator_events(&mut simulator_window, &mut pending_press, &tx);
+                handle_pending_press_timeout(&mut pending_press, held_key, &tx);
+                process_simulator_events(
+                    &mut simulator_window,
+                    &mut pending_press,
+                    &mut held_key,
+                    &mut typing,
+                    &tx,
+                );
             }),
+            font_regular: geometry.scale.font(),
             ..Default::default()
         };
 
@@ -43,13 +220,22 @@ pub fn setup_simulator_and_input() -> (Backend<SimulatorDisplay<Rgb565>>, Receiv
 
 /// Checks if a pending press has timed out and sends a `Short` press event if
 /// it has.
-fn handle_pending_press_timeout(pending_press: &mut PendingPress, tx: &Sender<InputEvent>) {
+fn handle_pending_press_timeout(
+    pending_press: &mut PendingPress,
+    held_key: HeldKey,
+    tx: &Sender<InputEvent>,
+) {
     let Some((id, instant)) = pending_press else {
         // No pending press
         return;
     };
 
-    if instant.elapsed() <= DOUBLE_PRESS_TIMEOUT {
+    if held_key == Some((*id, false)) {
+        // Still held, this may become a long press
+        return;
+    }
+
+    if instant.elapsed() <= button::timings().double_press {
         // Still within the pending press timeout
         return;
     }
@@ -67,16 +253,63 @@ fn handle_pending_press_timeout(pending_press: &mut PendingPress, tx: &Sender<In
 fn process_simulator_events(
     window: &mut Window,
     pending_press: &mut PendingPress,
+    held_key: &mut HeldKey,
+    typing: &mut bool,
     tx: &Sender<InputEvent>,
 ) {
     for event in window.events() {
         match event {
+            SimulatorEvent::KeyDown {
+                keycode: Keycode::Tab,
+                repeat: false,
+                ..
+            } => {
+                *typing = !*typing;
+                info!("Typing {}", if *typing { "on" } else { "off" });
+            }
+            // Held keys repeat their character, as in text fields
+            SimulatorEvent::KeyDown {
+                keycode, keymod, ..
+            } if *typing && let Some(command) = typed_command(keycode, keymod) => {
+                if let Ok(mut typed) = TYPED.lock() {
+                    typed.push(command);
+                }
+            }
             SimulatorEvent::KeyDown {
                 keycode,
                 repeat: false,
                 ..
             } => {
-                handle_keydown_event(keycode, pending_press, tx);
+                if let Some(id) = button_for_keycode(keycode) {
+                    *held_key = Some((id, false));
+                    handle_press(id, pending_press, tx);
+                }
+            }
+            SimulatorEvent::KeyDown {
+                keycode,
+                repeat: true,
+                ..
+            } => {
+                handle_key_repeat(keycode, pending_press, held_key, tx);
+            }
+            SimulatorEvent::KeyUp { .. } | SimulatorEvent::MouseButtonUp { .. } => {
+                *held_key = None;
+            }
+            SimulatorEvent::MouseButtonDown { mouse_btn, .. } => {
+                let id = match mouse_btn {
+                    MouseButton::Right => ButtonId::B,
+                    _ => ButtonId::A,
+                };
+                *held_key = Some((id, false));
+                handle_press(id, pending_press, tx);
+            }
+            SimulatorEvent::MouseWheel { scroll_delta, .. } if scroll_delta.y != 0 => {
+                let id = if scroll_delta.y > 0 {
+                    ButtonId::X
+                } else {
+                    ButtonId::Y
+                };
+                handle_press(id, pending_press, tx);
             }
             SimulatorEvent::Quit => {
                 println!("simulator window closed");
@@ -87,25 +320,8 @@ fn process_simulator_events(
     }
 }
 
-/// Handles the logic for a key press, including double presses.
-fn handle_keydown_event(
-    keycode: Keycode,
-    pending_press: &mut PendingPress,
-    tx: &Sender<InputEvent>,
-) {
-    let button_id = match keycode {
-        Keycode::A => Some(ButtonId::A),
-        Keycode::B => Some(ButtonId::B),
-        Keycode::X => Some(ButtonId::X),
-        Keycode::Y => Some(ButtonId::Y),
-        _ => None,
-    };
-
-    let Some(id) = button_id else {
-        // Not a button we recognize
-        return;
-    };
-
+/// Handles the logic for a button press, including double presses.
+fn handle_press(id: ButtonId, pending_press: &mut PendingPress, tx: &Sender<InputEvent>) {
     let Some((pending_id, _)) = *pending_press else {
         // There's no pending press, this is the first (maybe) press of a double press
         *pending_press = Some((id, Instant::now()));
@@ -130,3 +346,60 @@ fn handle_keydown_event(
         *pending_press = Some((id, Instant::now())); // New press is now pending
     }
 }
+
+/// Turns the key repeat of a held key into a `Long` press followed by
+/// `Repeat` presses.
+fn handle_key_repeat(
+    keycode: Keycode,
+    pending_press: &mut PendingPress,
+    held_key: &mut HeldKey,
+    tx: &Sender<InputEvent>,
+) {
+    let Some(id) = button_for_keycode(keycode) else {
+        return;
+    };
+
+    let press_type = if *held_key == Some((id, true)) {
+        ButtonPress::Repeat
+    } else {
+        if matches!(*pending_press, Some((pending_id, _)) if pending_id == id) {
+            // The initial press wasn't a short press after all
+            *pending_press = None;
+        }
+        *held_key = Some((id, true));
+        ButtonPress::Long
+    };
+    tx.send(InputEvent { id, press_type }).ok();
+}
+
+fn button_for_keycode(keycode: Keycode) -> Option<ButtonId> {
+    match keycode {
+        Keycode::A | Keycode::Right | Keycode::Return => Some(ButtonId::A),
+        Keycode::B | Keycode::Left | Keycode::Escape => Some(ButtonId::B),
+        Keycode::X | Keycode::Up => Some(ButtonId::X),
+        Keycode::Y | Keycode::Down => Some(ButtonId::Y),
+        _ => None,
+    }
+}
+
+/// The character typed by a key, upper case with shift. Keys without one,
+/// like the arrows, still press buttons while typing.
+fn typed_command(keycode: Keycode, keymod: Mod) -> Option<Command> {
+    match keycode {
+        Keycode::Backspace => return Some(Command::Erase),
+        Keycode::Space => return Some(Command::Type(' ')),
+        _ => {}
+    }
+    // Named after the character they type, e.g. `A` or `1`
+    let name = keycode.name();
+    let mut chars = name.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    let shift = keymod.intersects(Mod::LSHIFTMOD) || keymod.intersects(Mod::RSHIFTMOD);
+    Some(Command::Type(if shift {
+        c.to_ascii_uppercase()
+    } else {
+        c.to_ascii_lowercase()
+    }))
+}
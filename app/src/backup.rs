@@ -0,0 +1,305 @@
+use crate::integrity;
+use crate::node_config::{Field, NodeConfig};
+use crate::oplock::{self, Operation};
+use crate::storage;
+use crate::systemd::{self, ActiveState};
+use crate::usb;
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// The last backup made, to tell how old it is
+const STATE_FILE_PATH: &str = "/home/pi/.amaru_backup.json";
+/// Directory of the backups on the drive
+pub const BACKUP_DIR: &str = "amaru-backups";
+pub const NODE_SERVICE: &str = "amaru.service";
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Steps of a backup, or of a restore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupStage {
+    /// For another operation to finish
+    Waiting,
+    Downloading,
+    StoppingNode,
+    Archiving,
+    Verifying,
+    Extracting,
+    StartingNode,
+}
+
+impl BackupStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BackupStage::Waiting => "Waiting for another operation",
+            BackupStage::Downloading => "Downloading the snapshot",
+            BackupStage::StoppingNode => "Stopping the node",
+            BackupStage::Archiving => "Archiving the ledger",
+            BackupStage::Verifying => "Verifying the archive",
+            BackupStage::Extracting => "Extracting the ledger",
+            BackupStage::StartingNode => "Starting the node",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupProgress {
+    pub stage: BackupStage,
+    /// Bytes archived, downloaded or extracted, of `total`
+    pub done: u64,
+    pub total: u64,
+}
+
+impl BackupProgress {
+    pub fn of(stage: BackupStage) -> Self {
+        Self {
+            stage,
+            done: 0,
+            total: 0,
+        }
+    }
+
+    pub fn ratio(&self) -> Option<f64> {
+        (self.total > 0).then(|| (self.done as f64 / self.total as f64).min(1.0))
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum BackupStatus {
+    #[default]
+    Idle,
+    Running(BackupProgress),
+    Done(BackupRecord),
+    Failed(String),
+}
+
+impl BackupStatus {
+    pub fn is_running(&self) -> bool {
+        matches!(self, BackupStatus::Running(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupRecord {
+    /// File name of the archive on the drive
+    pub name: String,
+    /// UNIX time the backup was made
+    pub timestamp: u64,
+    /// Of the archive, in bytes
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// The last backup made from this device.
+pub fn last_backup() -> Option<BackupRecord> {
+    let data = fs::read_to_string(STATE_FILE_PATH).ok()?;
+    serde_json::from_str(&data)
+        .inspect_err(|e| warn!("Failed to read {}: {}", STATE_FILE_PATH, e))
+        .ok()
+}
+
+fn save_record(record: &BackupRecord) -> Result<()> {
+    fs::write(STATE_FILE_PATH, serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// E.g. `ledger-preprod-1760000000.tar.gz`. FAT drives don't take the colon
+/// of `testnet:42`.
+pub fn archive_name(network: &str, timestamp: u64) -> String {
+    format!("ledger-{}-{}.tar.gz", network.replace(':', "_"), timestamp)
+}
+
+/// Pipes `tar` of the ledger through `gzip` into `dest`, reporting the bytes
+/// read by `tar`. Stops once `cancelled`.
+fn archive(
+    ledger_dir: &Path,
+    dest: &Path,
+    mut on_progress: impl FnMut(u64),
+    cancelled: impl Fn() -> bool,
+) -> Result<()> {
+    let parent = ledger_dir
+        .parent()
+        .ok_or_else(|| anyhow!("Unexpected ledger path {}", ledger_dir.display()))?;
+    let name = ledger_dir
+        .file_name()
+        .ok_or_else(|| anyhow!("Unexpected ledger path {}", ledger_dir.display()))?;
+    let mut tar = Command::new("tar")
+        .arg("-c")
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    // The ledger compresses poorly, a fast level is about as small
+    let mut gzip = Command::new("gzip")
+        .arg("-1")
+        .arg("-c")
+        .stdin(Stdio::piped())
+        .stdout(File::create(dest)?)
+        .spawn()?;
+
+    let mut tar_out = tar.stdout.take().ok_or_else(|| anyhow!("No tar output"))?;
+    let mut gzip_in = gzip.stdin.take().ok_or_else(|| anyhow!("No gzip input"))?;
+    let mut pump = || -> Result<()> {
+        let mut buf = vec![0; 64 * 1024];
+        let mut done = 0;
+        let mut last_progress = Instant::now();
+        loop {
+            let read = tar_out.read(&mut buf)?;
+            if read == 0 {
+                return Ok(());
+            }
+            gzip_in.write_all(&buf[..read])?;
+            done += read as u64;
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                on_progress(done);
+                if cancelled() {
+                    return Err(anyhow!("Cancelled"));
+                }
+            }
+        }
+    };
+    let pumped = pump();
+    drop(gzip_in);
+    if pumped.is_err() {
+        tar.kill().ok();
+    }
+
+    let tar_status = tar.wait()?;
+    let gzip_status = gzip.wait()?;
+    pumped?;
+    if !tar_status.success() {
+        return Err(anyhow!("tar exited with {}", tar_status));
+    }
+    if !gzip_status.success() {
+        return Err(anyhow!("gzip exited with {}", gzip_status));
+    }
+    File::open(dest)?.sync_all()?;
+    Ok(())
+}
+
+/// Reads the whole archive back, which checks its CRC and structure.
+fn verify(path: &Path) -> Result<()> {
+    let output = Command::new("tar").arg("-tzf").arg(path).output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Archive is corrupt: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Archives, verifies and moves the archive in place, the node stopped.
+fn archive_ledger(
+    ledger_dir: &Path,
+    dir: &Path,
+    name: &str,
+    total: u64,
+    progress: &impl Fn(BackupProgress),
+    cancelled: &impl Fn() -> bool,
+) -> Result<BackupRecord> {
+    let dest = dir.join(name);
+    let part = dir.join(format!("{}.part", name));
+    let result = archive(
+        ledger_dir,
+        &part,
+        |done| {
+            progress(BackupProgress {
+                stage: BackupStage::Archiving,
+                done,
+                total,
+            })
+        },
+        cancelled,
+    )
+    .and_then(|()| {
+        progress(BackupProgress::of(BackupStage::Verifying));
+        verify(&part)
+    });
+    if let Err(e) = result {
+        fs::remove_file(&part).ok();
+        return Err(e);
+    }
+
+    let sha256 = integrity::sha256_file(&part)?;
+    fs::rename(&part, &dest)?;
+    // In sha256sum format, to check it by hand too
+    let checksum_path = dir.join(format!("{}.sha256", name));
+    fs::write(&checksum_path, format!("{}  {}\n", sha256, name))?;
+    File::open(&checksum_path)?.sync_all()?;
+    Ok(BackupRecord {
+        name: name.to_string(),
+        timestamp: current_timestamp()?,
+        size: fs::metadata(&dest)?.len(),
+        sha256,
+    })
+}
+
+/// Copies the ledger to the USB drive as a compressed archive, with its
+/// checksum. The node is stopped meanwhile, for the ledger to be consistent,
+/// then started again if it was running. Stops while archiving once
+/// `cancelled`, leaving nothing on the drive.
+pub fn back_up(
+    progress: impl Fn(BackupProgress),
+    cancelled: impl Fn() -> bool,
+) -> Result<BackupRecord> {
+    progress(BackupProgress::of(BackupStage::Waiting));
+    let _lock = oplock::acquire(Operation::Backup, &cancelled)?;
+
+    let config = NodeConfig::load().ok();
+    let network = config
+        .as_ref()
+        .and_then(|config| config.get(Field::Network))
+        .unwrap_or_else(|| "mainnet".to_string());
+    let ledger_dir = storage::db_dir(config.as_ref(), Field::LedgerDir, "ledger");
+    if !ledger_dir.is_dir() {
+        return Err(anyhow!("No ledger at {}", ledger_dir.display()));
+    }
+    let total = storage::dir_size(&ledger_dir);
+
+    let drive = usb::open()?;
+    let dir = drive.path().join(BACKUP_DIR);
+    fs::create_dir_all(&dir)?;
+    // Compressed, the archive is smaller than the ledger
+    let (_, free) = storage::filesystem_space(&dir)?;
+    if free < total {
+        return Err(anyhow!("Not enough space on the USB drive"));
+    }
+
+    if cancelled() {
+        return Err(anyhow!("Cancelled"));
+    }
+    let was_running = systemd::get_systemd_service_info(NODE_SERVICE)
+        .is_ok_and(|info| matches!(info.active_state, ActiveState::Active));
+    if was_running {
+        progress(BackupProgress::of(BackupStage::StoppingNode));
+        systemd::stop_service(NODE_SERVICE)?;
+    }
+    let name = archive_name(&network, current_timestamp()?);
+    let result = archive_ledger(&ledger_dir, &dir, &name, total, &progress, &cancelled);
+    let started = if was_running {
+        progress(BackupProgress::of(BackupStage::StartingNode));
+        systemd::start_service(NODE_SERVICE)
+    } else {
+        Ok(())
+    };
+
+    let record = result?;
+    info!(
+        "Backed up the ledger to {}",
+        dir.join(&record.name).display()
+    );
+    if let Err(e) = save_record(&record) {
+        warn!("Failed to write {}: {}", STATE_FILE_PATH, e);
+    }
+    started.map_err(|e| anyhow!("Backed up, but the node didn't start: {}", e))?;
+    Ok(record)
+}
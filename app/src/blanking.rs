@@ -0,0 +1,62 @@
+use std::env;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Turns the screen off after a period without input. The press waking it
+/// does nothing else.
+#[derive(Debug, Clone)]
+pub struct Blanking {
+    /// Never blanks when `None`
+    after: Option<Duration>,
+    last_activity: Instant,
+    blanked: bool,
+}
+
+impl Blanking {
+    pub fn new(after: Option<Duration>) -> Self {
+        Self {
+            after,
+            last_activity: Instant::now(),
+            blanked: false,
+        }
+    }
+
+    /// Reads the idle period from `AMARU_PI_BLANK_AFTER_SECS`, unset to keep
+    /// the screen on.
+    pub fn from_env() -> Self {
+        let after = env::var("AMARU_PI_BLANK_AFTER_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs);
+        Self::new(after)
+    }
+
+    pub fn is_blanked(&self) -> bool {
+        self.blanked
+    }
+
+    /// Records user activity. Returns whether it woke the screen.
+    pub fn touch(&mut self) -> bool {
+        self.last_activity = Instant::now();
+        let woke = self.blanked;
+        if woke {
+            info!("Screen woken");
+        }
+        self.blanked = false;
+        woke
+    }
+
+    /// Blanks once idle for too long. Returns whether it just did. Call this
+    /// every loop.
+    pub fn check_idle(&mut self) -> bool {
+        let idle = self
+            .after
+            .is_some_and(|after| self.last_activity.elapsed() >= after);
+        if idle && !self.blanked {
+            info!("Screen blanked");
+            self.blanked = true;
+            return true;
+        }
+        false
+    }
+}
@@ -0,0 +1,137 @@
+use crate::chain::Network;
+use crate::logs::JournalReader;
+use crate::util::{current_timestamp, parse_utc_millis};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::env;
+
+const DEFAULT_BLOCKS_KEPT: usize = 50;
+/// Messages amaru logs when adopting a block, old and new
+const ADOPTION_MESSAGES: [&str; 4] = [
+    "new tip",
+    "track_peers.caught_up.new_tip",
+    "tip_changed",
+    "diffusion.forward_chain.new_tip",
+];
+
+/// A block adopted by the node. Fields the node doesn't trace are `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub slot: u64,
+    pub hash: String,
+    pub height: Option<u64>,
+    /// In bytes
+    pub size: Option<u64>,
+    pub tx_count: Option<u64>,
+    /// UNIX time in milliseconds, when the node logged it or else when read
+    pub adopted_at: Option<u64>,
+    /// From the start of the slot to its adoption
+    pub delay_ms: Option<u64>,
+}
+
+fn field_u64(fields: &Value, names: &[&str]) -> Option<u64> {
+    names.iter().find_map(|name| fields.get(name)?.as_u64())
+}
+
+/// The block adopted, if the line logs one.
+fn extract_block(line: &str, network: Network) -> Option<BlockHeader> {
+    let entry: Value = serde_json::from_str(&line[line.find('{')?..]).ok()?;
+    let fields = entry.get("fields")?;
+    let message = fields.get("message")?.as_str()?;
+    if !ADOPTION_MESSAGES.contains(&message) {
+        return None;
+    }
+    // Format is "SLOT.HASH"
+    let point = fields
+        .get("point")
+        .or_else(|| fields.get("tip"))?
+        .as_str()?;
+    let (slot, hash) = point.split_once('.')?;
+    let slot = slot.parse().ok()?;
+    let adopted_at = entry
+        .get("timestamp")
+        .and_then(Value::as_str)
+        .and_then(parse_utc_millis);
+    Some(BlockHeader {
+        slot,
+        hash: hash.to_string(),
+        height: field_u64(fields, &["height", "block_height"]),
+        size: field_u64(fields, &["size", "block_size"]),
+        tx_count: field_u64(fields, &["tx_count", "txs"]),
+        adopted_at,
+        delay_ms: adopted_at.map(|at| at.saturating_sub(network.slot_time(slot) * 1_000)),
+    })
+}
+
+/// Follows the node journal and keeps the last blocks it adopted.
+pub struct BlockTracker {
+    network: Network,
+    reader: JournalReader,
+    /// Newest first
+    blocks: VecDeque<BlockHeader>,
+    capacity: usize,
+}
+
+impl Default for BlockTracker {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl BlockTracker {
+    /// Reads `AMARU_PI_BLOCKS_KEPT`.
+    pub fn from_env() -> Self {
+        let capacity = env::var("AMARU_PI_BLOCKS_KEPT")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .filter(|capacity| *capacity > 0)
+            .unwrap_or(DEFAULT_BLOCKS_KEPT);
+        Self {
+            network: Network::from_env(),
+            reader: JournalReader::new("amaru.service"),
+            blocks: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn blocks(&self) -> &VecDeque<BlockHeader> {
+        &self.blocks
+    }
+
+    /// Reads the blocks adopted since last poll.
+    pub fn poll(&mut self) {
+        let lines = self.reader.next_lines().unwrap_or_default();
+        for line in lines {
+            if let Some(block) = extract_block(&line, self.network) {
+                self.adopt(block);
+            }
+        }
+    }
+
+    fn adopt(&mut self, mut block: BlockHeader) {
+        // Logged again by another stage of the node
+        if let Some(known) = self
+            .blocks
+            .iter_mut()
+            .find(|known| known.hash == block.hash)
+        {
+            known.height = known.height.or(block.height);
+            known.size = known.size.or(block.size);
+            known.tx_count = known.tx_count.or(block.tx_count);
+            return;
+        }
+        // A rollback drops the blocks after the new tip
+        while self
+            .blocks
+            .front()
+            .is_some_and(|newest| newest.slot >= block.slot)
+        {
+            self.blocks.pop_front();
+        }
+        if block.adopted_at.is_none() {
+            block.adopted_at = current_timestamp().ok().map(|now| now * 1_000);
+        }
+        self.blocks.push_front(block);
+        self.blocks.truncate(self.capacity);
+    }
+}
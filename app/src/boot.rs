@@ -0,0 +1,219 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+
+const STATE_FILE_PATH: &str = "/home/pi/.amaru_boot_history.json";
+/// Boots kept for the trend
+const MAX_BOOTS: usize = 30;
+/// Boots the latest one is compared to
+const BASELINE_BOOTS: usize = 10;
+/// Units quicker than this are left out of the history
+#[cfg(feature = "display_hat")]
+const MIN_UNIT_SECS: f64 = 0.1;
+/// A unit this much slower than on the previous boot, or new, is a suspect
+const CULPRIT_SECS: f64 = 1.0;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootReport {
+    /// From `/proc/sys/kernel/random/boot_id`, to record each boot once
+    pub boot_id: String,
+    pub kernel_secs: f64,
+    /// Includes the initrd, if any
+    pub userspace_secs: f64,
+    pub total_secs: f64,
+    /// Startup time of each unit, slowest first
+    #[serde(default)]
+    pub units: Vec<(String, f64)>,
+}
+
+/// A boot noticeably slower than the previous ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootRegression {
+    pub slower_by_secs: f64,
+    /// Units new to this boot or at least a second slower than on the
+    /// previous one, slowest first
+    pub culprits: Vec<(String, f64)>,
+}
+
+/// Parses a systemd timespan, e.g. `1min 2.345s` or `512ms`.
+#[cfg(feature = "display_hat")]
+fn parse_timespan(s: &str) -> Option<f64> {
+    let mut secs = 0.0;
+    for part in s.split_whitespace() {
+        let (value, unit_secs) = if let Some(v) = part.strip_suffix("ms") {
+            (v, 0.001)
+        } else if let Some(v) = part.strip_suffix("us").or(part.strip_suffix("µs")) {
+            (v, 0.000_001)
+        } else if let Some(v) = part.strip_suffix("min") {
+            (v, 60.0)
+        } else if let Some(v) = part.strip_suffix('h') {
+            (v, 3600.0)
+        } else if let Some(v) = part.strip_suffix('s') {
+            (v, 1.0)
+        } else {
+            return None;
+        };
+        secs += value.parse::<f64>().ok()? * unit_secs;
+    }
+    Some(secs)
+}
+
+/// Parses the first line of `systemd-analyze time`, e.g. `Startup finished in
+/// 3.012s (kernel) + 1.1s (initrd) + 9.874s (userspace) = 13.986s`. Returns
+/// the kernel, userspace and total times.
+#[cfg(feature = "display_hat")]
+fn parse_time(output: &str) -> Option<(f64, f64, f64)> {
+    let line = output
+        .lines()
+        .next()?
+        .strip_prefix("Startup finished in ")?;
+    let (phases, total) = line.split_once(" = ")?;
+    let (mut kernel, mut userspace) = (0.0, 0.0);
+    for phase in phases.split(" + ") {
+        let (time, label) = phase.rsplit_once(" (")?;
+        match label.trim_end_matches(')') {
+            "kernel" => kernel = parse_timespan(time)?,
+            "initrd" | "userspace" => userspace += parse_timespan(time)?,
+            // Firmware and loader aren't reported on a Pi
+            _ => {}
+        }
+    }
+    Some((kernel, userspace, parse_timespan(total)?))
+}
+
+/// Parses `systemd-analyze blame`, e.g. `1min 2.345s apt-daily.service`.
+#[cfg(feature = "display_hat")]
+fn parse_blame(output: &str) -> Vec<(String, f64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (time, unit) = line.trim().rsplit_once(' ')?;
+            Some((unit.to_string(), parse_timespan(time)?))
+        })
+        .filter(|(_, secs)| *secs >= MIN_UNIT_SECS)
+        .collect()
+}
+
+/// How long the current boot took, `None` while it isn't finished.
+#[cfg(feature = "display_hat")]
+pub fn analyze_boot() -> Result<Option<BootReport>> {
+    let time = match run_and_capture("systemd-analyze", ["time"].to_vec()) {
+        Ok(time) => time,
+        Err(e) if e.to_string().contains("not yet finished") => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let (kernel_secs, userspace_secs, total_secs) = parse_time(&time)
+        .ok_or_else(|| anyhow::anyhow!("Unexpected systemd-analyze output: {}", time))?;
+    let blame = run_and_capture("systemd-analyze", ["blame", "--no-pager"].to_vec())?;
+    Ok(Some(BootReport {
+        boot_id: fs::read_to_string("/proc/sys/kernel/random/boot_id")?
+            .trim()
+            .to_string(),
+        kernel_secs,
+        userspace_secs,
+        total_secs,
+        units: parse_blame(&blame),
+    }))
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn analyze_boot() -> Result<Option<BootReport>> {
+    Ok(None)
+}
+
+/// Boot times of the last boots, to notice a new service slowing down the
+/// recovery from a power cut.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootHistory {
+    /// Oldest first
+    #[serde(default)]
+    pub boots: Vec<BootReport>,
+}
+
+impl BootHistory {
+    pub fn load() -> Self {
+        read_state().unwrap_or_else(|e| {
+            warn!("Failed to read boot history: {}", e);
+            BootHistory::default()
+        })
+    }
+
+    pub fn latest(&self) -> Option<&BootReport> {
+        self.boots.last()
+    }
+
+    /// Adds the report unless its boot was already recorded. Returns the
+    /// regression if the boot was slower than usual.
+    pub fn record(&mut self, report: BootReport) -> Option<BootRegression> {
+        if self.boots.iter().any(|boot| boot.boot_id == report.boot_id) {
+            return None;
+        }
+        self.boots.push(report);
+        if self.boots.len() > MAX_BOOTS {
+            self.boots.remove(0);
+        }
+        if let Err(e) = write_state(self) {
+            warn!("Failed to write boot history: {}", e);
+        }
+        self.regression()
+    }
+
+    /// Compares the latest boot to the median of the previous ones, slower
+    /// by a fifth and at least two seconds being a regression.
+    pub fn regression(&self) -> Option<BootRegression> {
+        let (latest, previous) = self.boots.split_last()?;
+        let mut baseline: Vec<f64> = previous
+            .iter()
+            .rev()
+            .take(BASELINE_BOOTS)
+            .map(|boot| boot.total_secs)
+            .collect();
+        if baseline.is_empty() {
+            return None;
+        }
+        baseline.sort_by(f64::total_cmp);
+        let median = baseline[baseline.len() / 2];
+        let slower_by_secs = latest.total_secs - median;
+        if slower_by_secs < (median * 0.2).max(2.0) {
+            return None;
+        }
+
+        let before = &previous.last()?.units;
+        let culprits = latest
+            .units
+            .iter()
+            .filter(|(unit, secs)| {
+                let was = before
+                    .iter()
+                    .find(|(name, _)| name == unit)
+                    .map(|(_, secs)| *secs);
+                was.is_none_or(|was| secs - was >= CULPRIT_SECS)
+            })
+            .filter(|(_, secs)| *secs >= CULPRIT_SECS)
+            .cloned()
+            .collect();
+        Some(BootRegression {
+            slower_by_secs,
+            culprits,
+        })
+    }
+}
+
+fn read_state() -> Result<BootHistory> {
+    let path = Path::new(STATE_FILE_PATH);
+    if !path.exists() {
+        return Ok(BootHistory::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_state(history: &BootHistory) -> Result<()> {
+    fs::write(STATE_FILE_PATH, serde_json::to_string(history)?)?;
+    Ok(())
+}
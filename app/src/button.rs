@@ -1,11 +1,28 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::RwLock;
 use std::time::{Duration, Instant};
+use tracing::warn;
 
-const DEBOUNCE: Duration = Duration::from_millis(50);
-const LONG_PRESS: Duration = Duration::from_millis(1000);
-const DOUBLE_PRESS: Duration = Duration::from_millis(400);
+const DEFAULT_TIMINGS: ButtonTimings = ButtonTimings {
+    debounce: Duration::from_millis(50),
+    long_press: Duration::from_millis(1000),
+    double_press: Duration::from_millis(400),
+    repeat: Duration::from_millis(150),
+};
+/// Saved by the calibration screen, overriding the configured timings
+const TIMINGS_FILE_PATH: &str = "/home/pi/.amaru_button_timings.json";
+
+/// The configured timings and those presses are classified with, changed
+/// live by the calibration screen.
+static TIMINGS: RwLock<(ButtonTimings, ButtonTimings)> =
+    RwLock::new((DEFAULT_TIMINGS, DEFAULT_TIMINGS));
 
 /// Display HAT Mini button names
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ButtonId {
     A,
     B,
@@ -13,12 +30,28 @@ pub enum ButtonId {
     Y,
 }
 
+impl FromStr for ButtonId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "a" => Ok(ButtonId::A),
+            "b" => Ok(ButtonId::B),
+            "x" => Ok(ButtonId::X),
+            "y" => Ok(ButtonId::Y),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Type of button press
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ButtonPress {
     Short,
     Long,
     Double,
+    /// Emitted periodically while a button is held past the long press
+    Repeat,
 }
 
 /// The button pressed and the type of press
@@ -28,35 +61,127 @@ pub struct InputEvent {
     pub press_type: ButtonPress,
 }
 
+/// The timing windows used to classify presses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonTimings {
+    pub debounce: Duration,
+    pub long_press: Duration,
+    pub double_press: Duration,
+    /// Interval between auto-repeat events while held
+    pub repeat: Duration,
+}
+
+impl Default for ButtonTimings {
+    fn default() -> Self {
+        DEFAULT_TIMINGS
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTimings {
+    debounce_ms: u64,
+    long_press_ms: u64,
+    double_press_ms: u64,
+    repeat_ms: u64,
+}
+
+fn read_saved() -> Result<Option<ButtonTimings>> {
+    let path = Path::new(TIMINGS_FILE_PATH);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let saved: SavedTimings = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(Some(ButtonTimings {
+        debounce: Duration::from_millis(saved.debounce_ms),
+        long_press: Duration::from_millis(saved.long_press_ms),
+        double_press: Duration::from_millis(saved.double_press_ms),
+        repeat: Duration::from_millis(saved.repeat_ms),
+    }))
+}
+
+/// Sets the timings from the config, unless the calibration screen saved
+/// some. Returns the timings in use.
+pub fn configure_timings(configured: ButtonTimings) -> ButtonTimings {
+    let live = read_saved()
+        .unwrap_or_else(|e| {
+            warn!("Failed to read {}: {}", TIMINGS_FILE_PATH, e);
+            None
+        })
+        .unwrap_or(configured);
+    *TIMINGS.write().unwrap() = (configured, live);
+    live
+}
+
+/// The timings presses are classified with.
+pub fn timings() -> ButtonTimings {
+    TIMINGS.read().unwrap().1
+}
+
+/// Classifies presses with `timings` from now on, also after a restart.
+pub fn calibrate(timings: ButtonTimings) -> Result<()> {
+    TIMINGS.write().unwrap().1 = timings;
+    let saved = SavedTimings {
+        debounce_ms: timings.debounce.as_millis() as u64,
+        long_press_ms: timings.long_press.as_millis() as u64,
+        double_press_ms: timings.double_press.as_millis() as u64,
+        repeat_ms: timings.repeat.as_millis() as u64,
+    };
+    fs::write(TIMINGS_FILE_PATH, serde_json::to_string(&saved)?)?;
+    Ok(())
+}
+
+/// Goes back to the configured timings. Returns them.
+pub fn reset_timings() -> Result<ButtonTimings> {
+    let mut timings = TIMINGS.write().unwrap();
+    timings.1 = timings.0;
+    if Path::new(TIMINGS_FILE_PATH).exists() {
+        fs::remove_file(TIMINGS_FILE_PATH)?;
+    }
+    Ok(timings.0)
+}
+
 pub struct Button {
+    timings: ButtonTimings,
     pressed: bool,
     last_change: Instant,
     press_start: Option<Instant>,
     long_triggered: bool,
+    last_repeat: Option<Instant>,
     last_release: Option<Instant>,
     pending_short: bool,
 }
 
 impl Default for Button {
     fn default() -> Self {
+        Self::new(ButtonTimings::default())
+    }
+}
+
+impl Button {
+    pub fn new(timings: ButtonTimings) -> Self {
         Self {
+            timings,
             pressed: false,
             last_change: Instant::now(),
             press_start: None,
             long_triggered: false,
+            last_repeat: None,
             last_release: None,
             pending_short: false,
         }
     }
-}
 
-impl Button {
+    /// Applies timings changed since, e.g. by the calibration screen.
+    pub fn set_timings(&mut self, timings: ButtonTimings) {
+        self.timings = timings;
+    }
+
     /// Call this every loop with current pin state
     pub fn update(&mut self, is_low: bool) -> Option<ButtonPress> {
         let now = Instant::now();
 
         // Debounce
-        if now.duration_since(self.last_change) < DEBOUNCE {
+        if now.duration_since(self.last_change) < self.timings.debounce {
             return None;
         }
         let mut event = None;
@@ -73,11 +198,11 @@ impl Button {
             self.last_change = now;
             if let Some(start) = self.press_start
                 && !self.long_triggered
-                && now.duration_since(start) >= DEBOUNCE
+                && now.duration_since(start) >= self.timings.debounce
             {
                 // candidate short press
                 if let Some(last) = self.last_release
-                    && now.duration_since(last) <= DOUBLE_PRESS
+                    && now.duration_since(last) <= self.timings.double_press
                 {
                     // It's a double press
                     self.pending_short = false;
@@ -96,16 +221,25 @@ impl Button {
         if self.pressed
             && !self.long_triggered
             && let Some(start) = self.press_start
-            && now.duration_since(start) >= LONG_PRESS
+            && now.duration_since(start) >= self.timings.long_press
         {
             self.long_triggered = true;
+            self.last_repeat = Some(now);
             self.pending_short = false; // cancel short
             event = Some(ButtonPress::Long);
+        } else if self.pressed
+            && self.long_triggered
+            && let Some(last) = self.last_repeat
+            && now.duration_since(last) >= self.timings.repeat
+        {
+            // Auto-repeat while still held
+            self.last_repeat = Some(now);
+            event = Some(ButtonPress::Repeat);
         }
         // Resolve pending short if timeout expired
         if self.pending_short
             && let Some(last) = self.last_release
-            && now.duration_since(last) > DOUBLE_PRESS
+            && now.duration_since(last) > self.timings.double_press
         {
             self.pending_short = false;
             event = Some(ButtonPress::Short);
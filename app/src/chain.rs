@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::str::FromStr;
+
+/// Byron epochs are 21600 slots long (20s slots, 5 days).
+const BYRON_EPOCH_LENGTH: u64 = 21_600;
+/// Shelley-based epochs are 432000 slots long (1s slots, 5 days).
+const SHELLEY_EPOCH_LENGTH: u64 = 432_000;
+
+/// Ledger eras, oldest first. Syncing through them goes at different paces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Era {
+    Byron,
+    Shelley,
+    Allegra,
+    Mary,
+    Alonzo,
+    Babbage,
+    Conway,
+}
+
+/// The cardano network the node is following.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Preprod,
+    Preview,
+}
+
+impl FromStr for Network {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "preprod" => Ok(Network::Preprod),
+            "preview" => Ok(Network::Preview),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Network {
+    /// Reads the network from `AMARU_NETWORK`, defaulting to mainnet.
+    pub fn from_env() -> Self {
+        env::var("AMARU_NETWORK")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// The first slot and epoch of the Shelley era.
+    fn shelley_start(&self) -> (u64, u64) {
+        match self {
+            Network::Mainnet => (4_492_800, 208),
+            Network::Preprod => (86_400, 4),
+            Network::Preview => (0, 0),
+        }
+    }
+
+    /// The first epoch of each era, oldest first. Eras a network started in
+    /// start at 0.
+    fn era_starts(&self) -> [(Era, u64); 7] {
+        let epochs = match self {
+            Network::Mainnet => [0, 208, 236, 251, 290, 365, 507],
+            Network::Preprod => [0, 4, 5, 6, 7, 12, 163],
+            Network::Preview => [0, 0, 0, 0, 0, 3, 646],
+        };
+        let eras = [
+            Era::Byron,
+            Era::Shelley,
+            Era::Allegra,
+            Era::Mary,
+            Era::Alonzo,
+            Era::Babbage,
+            Era::Conway,
+        ];
+        std::array::from_fn(|i| (eras[i], epochs[i]))
+    }
+
+    /// Returns the era the given absolute slot belongs to.
+    pub fn era_at(&self, slot: u64) -> Era {
+        let epoch = self.epoch_for_slot(slot);
+        self.era_starts()
+            .iter()
+            .rev()
+            .find(|(_, start)| epoch >= *start)
+            .map_or(Era::Byron, |(era, _)| *era)
+    }
+
+    /// Splits the slots from `from` up to `to` by era, oldest first.
+    pub fn slots_by_era(&self, from: u64, to: u64) -> Vec<(Era, u64)> {
+        let starts = self.era_starts();
+        starts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (era, epoch))| {
+                let start = self.first_slot_of_epoch(*epoch).max(from);
+                let end = starts
+                    .get(i + 1)
+                    .map_or(u64::MAX, |(_, next)| self.first_slot_of_epoch(*next))
+                    .min(to);
+                (end > start).then(|| (*era, end - start))
+            })
+            .collect()
+    }
+
+    fn epoch_length(&self) -> u64 {
+        match self {
+            Network::Preview => 86_400,
+            _ => SHELLEY_EPOCH_LENGTH,
+        }
+    }
+
+    /// UNIX time of the first Shelley slot.
+    fn shelley_start_time(&self) -> u64 {
+        match self {
+            Network::Mainnet => 1_596_059_091,
+            Network::Preprod => 1_655_769_600,
+            Network::Preview => 1_666_656_000,
+        }
+    }
+
+    /// Returns the slot in progress at the given UNIX time. Only valid from
+    /// the Shelley era on.
+    pub fn slot_at(&self, timestamp: u64) -> u64 {
+        let (shelley_slot, _) = self.shelley_start();
+        shelley_slot + timestamp.saturating_sub(self.shelley_start_time())
+    }
+
+    /// Returns the UNIX time the given slot starts at. Only valid from the
+    /// Shelley era on.
+    pub fn slot_time(&self, slot: u64) -> u64 {
+        let (shelley_slot, _) = self.shelley_start();
+        self.shelley_start_time() + slot.saturating_sub(shelley_slot)
+    }
+
+    /// Returns the epoch the given absolute slot belongs to.
+    pub fn epoch_for_slot(&self, slot: u64) -> u64 {
+        let (shelley_slot, shelley_epoch) = self.shelley_start();
+        if slot < shelley_slot {
+            slot / BYRON_EPOCH_LENGTH
+        } else {
+            shelley_epoch + (slot - shelley_slot) / self.epoch_length()
+        }
+    }
+
+    /// Returns the first slot of the given epoch.
+    pub fn first_slot_of_epoch(&self, epoch: u64) -> u64 {
+        let (shelley_slot, shelley_epoch) = self.shelley_start();
+        if epoch < shelley_epoch {
+            epoch * BYRON_EPOCH_LENGTH
+        } else {
+            shelley_slot + (epoch - shelley_epoch) * self.epoch_length()
+        }
+    }
+}
+
+/// Base URL of the Koios compatible API used for public chain data, from
+/// `AMARU_PI_KOIOS_URL` or the public instance for the configured network.
+pub fn koios_url() -> String {
+    env::var("AMARU_PI_KOIOS_URL").unwrap_or_else(|_| {
+        match Network::from_env() {
+            Network::Mainnet => "https://api.koios.rest/api/v1",
+            Network::Preprod => "https://preprod.koios.rest/api/v1",
+            Network::Preview => "https://preview.koios.rest/api/v1",
+        }
+        .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epochs_around_the_shelley_hard_fork() {
+        let mainnet = Network::Mainnet;
+        assert_eq!(mainnet.epoch_for_slot(0), 0);
+        assert_eq!(mainnet.epoch_for_slot(4_492_799), 207);
+        assert_eq!(mainnet.epoch_for_slot(4_492_800), 208);
+        assert_eq!(mainnet.first_slot_of_epoch(207), 4_471_200);
+        assert_eq!(mainnet.first_slot_of_epoch(209), 4_924_800);
+        assert_eq!(Network::Preview.first_slot_of_epoch(3), 259_200);
+    }
+
+    #[test]
+    fn epochs_and_first_slots_agree() {
+        for network in [Network::Mainnet, Network::Preprod, Network::Preview] {
+            for epoch in [0, 3, 4, 5, 207, 208, 209, 507, 600] {
+                let slot = network.first_slot_of_epoch(epoch);
+                assert_eq!(network.epoch_for_slot(slot), epoch, "{:?}", network);
+                if slot > 0 {
+                    assert_eq!(network.epoch_for_slot(slot - 1), epoch - 1, "{:?}", network);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eras_start_at_their_hard_fork() {
+        let mainnet = Network::Mainnet;
+        assert_eq!(mainnet.era_at(4_492_799), Era::Byron);
+        assert_eq!(mainnet.era_at(4_492_800), Era::Shelley);
+        assert_eq!(mainnet.era_at(72_316_799), Era::Alonzo);
+        assert_eq!(mainnet.era_at(72_316_800), Era::Babbage);
+        assert_eq!(mainnet.era_at(133_660_800), Era::Conway);
+        assert_eq!(Network::Preprod.era_at(68_774_400), Era::Conway);
+        assert_eq!(Network::Preview.era_at(0), Era::Alonzo);
+    }
+
+    #[test]
+    fn slots_by_era_add_up() {
+        let mainnet = Network::Mainnet;
+        let slots = mainnet.slots_by_era(4_000_000, 5_000_000);
+        assert_eq!(slots, vec![(Era::Byron, 492_800), (Era::Shelley, 507_200)]);
+        let (from, to) = (1_000, 140_000_000);
+        let total: u64 = mainnet.slots_by_era(from, to).iter().map(|(_, n)| n).sum();
+        assert_eq!(total, to - from);
+        assert!(mainnet.slots_by_era(10, 10).is_empty());
+    }
+
+    #[test]
+    fn slot_times() {
+        let mainnet = Network::Mainnet;
+        assert_eq!(mainnet.slot_time(4_492_800), 1_596_059_091);
+        assert_eq!(mainnet.slot_at(mainnet.slot_time(100_000_000)), 100_000_000);
+    }
+}
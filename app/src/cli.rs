@@ -1,12 +1,31 @@
-use crate::{tui, wifi};
+use crate::geometry::UiScale;
+use crate::logging::LogFormat;
+use crate::platform::Platform;
+use crate::systemd::units::{self, Drift};
+use crate::util::format_local_time;
+use crate::{
+    control, daemon, diagnostics, download, github, i18n, logging, migrations, notifications,
+    offline_update, screen_text, secrets, self_update, tui, update, update_source, wifi,
+};
 use clap::{Parser, Subcommand};
-use std::{error::Error, time::Duration};
+use serde_json::Value;
+use std::{error::Error, io, path::PathBuf, time::Duration};
+use tokio::sync::mpsc;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 #[command(propagate_version = true)]
 #[command(arg_required_else_help = false)]
 struct Cli {
+    /// How logs are written, `json` for Loki or ELK
+    #[arg(
+        long,
+        global = true,
+        env = "AMARU_PI_LOG_FORMAT",
+        value_enum,
+        default_value_t
+    )]
+    log_format: LogFormat,
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -14,10 +33,127 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     Ui,
+    /// Runs the background checks, updates and alerts without any display,
+    /// for Pis without a HAT
+    Daemon,
     Conf {
         #[command(subcommand)]
         conf_cmd: ConfCommands,
     },
+    /// Downloads a file, resuming an interrupted download of it. Progress is
+    /// shown on the Info screen
+    Download {
+        url: String,
+        dest: PathBuf,
+        /// Expected SHA-256 of the file, as hex
+        #[arg(long)]
+        sha256: Option<String>,
+    },
+    /// Unit files installed by amaru-pi
+    Units {
+        #[command(subcommand)]
+        units_cmd: UnitsCommands,
+    },
+    /// Notifications sent, with the notes left on them
+    Events {
+        #[command(subcommand)]
+        events_cmd: EventsCommands,
+    },
+    /// Changes what amaru-pi logs without restarting it, e.g. `info` or
+    /// `amaru_pi=trace`, until the Pi reboots. `default` goes back to
+    /// AMARU_PI_LOGS_LEVEL. Prints the filter in use without one
+    LogLevel {
+        filter: Option<String>,
+    },
+    /// Collects logs, versions, state files and the state of the system into
+    /// a tar.gz to attach to bug reports, and prints its path
+    Diagnostics {
+        /// Also uploads it to AMARU_PI_DIAGNOSTICS_UPLOAD_URL and prints the
+        /// link
+        #[arg(long)]
+        upload: bool,
+    },
+    /// Updates of the managed applications
+    Updates {
+        #[command(subcommand)]
+        updates_cmd: UpdatesCommands,
+    },
+    /// Prints the latest release of a GitHub repository, e.g.
+    /// `jeluard/amaru-pi`, as JSON. Uses AMARU_PI_GITHUB_TOKEN and answers
+    /// unchanged releases from a cache, to stay under the rate limit
+    LatestRelease {
+        repo: String,
+    },
+    /// Prints the target triple of the system, e.g.
+    /// `armv7-unknown-linux-gnueabihf`, which picks the release assets
+    Platform {
+        /// Prints the architecture only, as assets name it
+        #[arg(long)]
+        arch: bool,
+        /// Prints the asset of a binary for the system, among the names of
+        /// the assets of a release read from stdin, one per line
+        #[arg(long, value_name = "BINARY")]
+        select: Option<String>,
+    },
+    /// Queries the running UI
+    Ctl {
+        #[command(subcommand)]
+        ctl_cmd: CtlCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CtlCommands {
+    /// Prints what the display shows, as plain text
+    Read,
+    /// Saves what the display shows as a PNG, and prints its path
+    Screenshot,
+}
+
+#[derive(Subcommand, Debug)]
+enum UpdatesCommands {
+    /// Lists the applications with their versions, holds and pins
+    List,
+    /// Keeps an application at its current version
+    Hold { app: String },
+    /// Never updates an application past a version, e.g. `v1.4.2`
+    Pin { app: String, version: String },
+    /// Lets an application update again, its hold and pin removed
+    Release { app: String },
+    /// Prints the latest release of an application from its source, as JSON
+    /// with the archive for the system and the patch from `--from`
+    Latest {
+        app: String,
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Stages the release archives (`amaru*-aarch64*.tar.gz`) of a directory,
+    /// verified against their checksums, the USB drive by default
+    Install { dir: Option<PathBuf> },
+}
+
+#[derive(Subcommand, Debug)]
+enum EventsCommands {
+    /// Lists the notifications sent, oldest first
+    List,
+    /// Notes a notification, e.g. "power cut, UPS held". An empty note
+    /// removes it
+    Note {
+        note: String,
+        /// UNIX time the notification was sent at, the latest one by default
+        #[arg(long)]
+        at: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum UnitsCommands {
+    /// Shows how the installed unit files differ from their templates
+    Diff,
+    /// Reinstalls the unit files that differ from their templates
+    Repair,
+    /// Prints a unit file as amaru-pi installs it
+    Show { name: String },
 }
 
 #[derive(Subcommand, Debug)]
@@ -26,28 +162,237 @@ enum ConfCommands {
         #[command(subcommand)]
         wifi_cmd: WifiCommands,
     },
+    Secrets {
+        #[command(subcommand)]
+        secrets_cmd: SecretsCommands,
+    },
+    /// Prints the language of the screens, or sets it, e.g. `fr`. Picked up
+    /// by the UI once restarted
+    Language { code: Option<String> },
+    /// Prints how large text is drawn, or sets it, `small`, `normal` or
+    /// `large`. Picked up by the UI once restarted
+    UiScale { scale: Option<String> },
 }
 
 #[derive(Subcommand, Debug)]
 enum WifiCommands {
-    SetConnection { ssid: String, password: String },
+    SetConnection {
+        ssid: String,
+        password: String,
+    },
+    /// Saves a network without joining it, an open one without password
+    AddNetwork {
+        ssid: String,
+        password: Option<String>,
+        /// Higher priorities are preferred when several networks are in range
+        #[arg(long, default_value_t = 0)]
+        priority: i32,
+    },
+    /// Lists the saved networks, highest priority first
+    ListNetworks,
+    /// Orders the saved networks, first one preferred
+    SetOrder {
+        names: Vec<String>,
+    },
+    /// Joins a saved network now
+    Switch {
+        name: String,
+    },
     CheckConnectivity,
     Up,
     Down,
 }
 
+#[derive(Subcommand, Debug)]
+enum SecretsCommands {
+    /// Stores a secret, read from stdin when no value is given
+    Set {
+        name: String,
+        value: Option<String>,
+    },
+    Remove {
+        name: String,
+    },
+    /// Lists the names of the stored secrets
+    List,
+}
+
 pub async fn handle() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
+    logging::init(cli.log_format);
+    let command = cli.command.unwrap_or(Commands::Ui);
 
-    match cli.command.unwrap_or(Commands::Ui) {
+    // Downloads, release checks and asset selection run from the updater
+    // script, which migrations rewrite, and queries of the UI leave it to the UI
+    if !matches!(
+        command,
+        Commands::Download { .. }
+            | Commands::LatestRelease { .. }
+            | Commands::Updates {
+                updates_cmd: UpdatesCommands::Latest { .. }
+            }
+            | Commands::Platform { .. }
+            | Commands::Ctl { .. }
+            | Commands::LogLevel { .. }
+    ) {
+        migrations::run_all();
+    }
+
+    match command {
         Commands::Ui => {
-            tui::run().await?;
+            tui::run(self_update::resume()).await?;
+        }
+        Commands::Daemon => {
+            daemon::run(self_update::resume()).await?;
+        }
+        Commands::Download { url, dest, sha256 } => {
+            let (tx, rx) = mpsc::channel(16);
+            let reporter = tokio::spawn(download::report_progress(rx));
+            let result = download::download(&url, &dest, sha256.as_deref(), tx).await;
+            let _ = reporter.await;
+            result?;
+        }
+        Commands::Diagnostics { upload } => {
+            let bundle = diagnostics::collect()?;
+            println!("{}", bundle.display());
+            if upload {
+                let url =
+                    diagnostics::upload_url().ok_or("AMARU_PI_DIAGNOSTICS_UPLOAD_URL isn't set")?;
+                println!("{}", diagnostics::upload(&url, &bundle).await?);
+            }
+        }
+        Commands::LogLevel { filter } => match filter {
+            Some(filter) => logging::set_filter(&filter)?,
+            None => println!("{}", logging::current_filter()),
+        },
+        Commands::LatestRelease { repo } => println!("{}", github::latest_release(&repo).await?),
+        Commands::Platform { arch, select } => {
+            let platform = Platform::current();
+            if let Some(binary) = select {
+                let names: Vec<String> = io::stdin().lines().collect::<Result<_, _>>()?;
+                let names: Vec<&str> = names.iter().map(|name| name.trim()).collect();
+                let asset = platform
+                    .select(&binary, &names)
+                    .ok_or_else(|| format!("No {} asset for {}", binary, platform))?;
+                println!("{}", asset);
+            } else if arch {
+                println!("{}", platform.arch.name());
+            } else {
+                println!("{}", platform);
+            }
         }
+        Commands::Ctl { ctl_cmd } => match ctl_cmd {
+            CtlCommands::Read => print!("{}", screen_text::read()?),
+            CtlCommands::Screenshot => {
+                let path = control::call("screenshot", Value::Null).await?;
+                println!("{}", path.as_str().unwrap_or_default());
+            }
+        },
+        Commands::Updates { updates_cmd } => match updates_cmd {
+            UpdatesCommands::List => {
+                let state = update::read_state_file()?;
+                let mut apps: Vec<_> = state.applications.iter().collect();
+                apps.sort();
+                for (name, app) in apps {
+                    let mut line = format!("{} {}", name, app.current_version);
+                    if !app.pending_version.is_empty() {
+                        line.push_str(&format!(", {} pending", app.pending_version));
+                    }
+                    if app.hold {
+                        line.push_str(", held");
+                    }
+                    if !app.pinned_version.is_empty() {
+                        line.push_str(&format!(", pinned to {}", app.pinned_version));
+                    }
+                    println!("{}", line);
+                }
+            }
+            UpdatesCommands::Hold { app } => update::set_policy(&app, |state| state.hold = true)?,
+            UpdatesCommands::Pin { app, version } => {
+                update::set_policy(&app, |state| state.pinned_version = version)?
+            }
+            UpdatesCommands::Release { app } => update::set_policy(&app, |state| {
+                state.hold = false;
+                state.pinned_version.clear();
+            })?,
+            UpdatesCommands::Latest { app, from } => {
+                let release = update_source::latest_release(&app, from.as_deref()).await?;
+                println!("{}", serde_json::to_string(&release)?);
+            }
+            UpdatesCommands::Install { dir } => {
+                let staged = match dir {
+                    Some(dir) => offline_update::stage_from(&dir)?,
+                    None => offline_update::stage_from_usb()?,
+                };
+                if staged.is_empty() {
+                    println!("No newer verified archive found");
+                }
+                for update in staged {
+                    println!("Staged {}", update);
+                }
+            }
+        },
+        Commands::Events { events_cmd } => match events_cmd {
+            EventsCommands::List => {
+                for event in notifications::read_history()? {
+                    let time = format_local_time(event.timestamp).unwrap_or_default();
+                    println!(
+                        "{} {} {:?} {}",
+                        event.timestamp, time, event.severity, event.title
+                    );
+                    for line in &event.lines {
+                        println!("    {}", line);
+                    }
+                    if let Some(note) = &event.note {
+                        println!("    Note: {}", note);
+                    }
+                }
+            }
+            EventsCommands::Note { note, at } => {
+                let event = notifications::annotate(at, &note)?;
+                println!("Noted {} ({})", event.title, event.timestamp);
+            }
+        },
+        Commands::Units { units_cmd } => match units_cmd {
+            UnitsCommands::Diff => {
+                for status in units::check_all() {
+                    match status.drift {
+                        None => println!("  {}", status.name),
+                        Some(Drift::Missing) => println!("! {} is missing", status.name),
+                        Some(Drift::Modified(lines)) => {
+                            println!("~ {}", status.name);
+                            for line in lines {
+                                println!("    {}", line);
+                            }
+                        }
+                    }
+                }
+            }
+            UnitsCommands::Repair => {
+                for name in units::repair()? {
+                    println!("Reinstalled {}", name);
+                }
+            }
+            UnitsCommands::Show { name } => print!("{}", units::render(&name)?),
+        },
         Commands::Conf { conf_cmd } => match conf_cmd {
             ConfCommands::Wifi { wifi_cmd } => match wifi_cmd {
                 WifiCommands::SetConnection { ssid, password } => {
                     wifi::set_connection(&ssid, &password)?
                 }
+                WifiCommands::AddNetwork {
+                    ssid,
+                    password,
+                    priority,
+                } => wifi::add_network(&ssid, &password.unwrap_or_default(), priority)?,
+                WifiCommands::ListNetworks => {
+                    for network in wifi::list_saved_networks()? {
+                        let active = if network.active { "*" } else { " " };
+                        println!("{} {:>4} {}", active, network.priority, network.name);
+                    }
+                }
+                WifiCommands::SetOrder { names } => wifi::set_network_order(&names)?,
+                WifiCommands::Switch { name } => wifi::switch_network(&name)?,
                 WifiCommands::CheckConnectivity => {
                     let network_status = wifi::check_network_status()?;
                     println!("{:?}", network_status);
@@ -55,6 +400,45 @@ pub async fn handle() -> Result<(), Box<dyn Error>> {
                 WifiCommands::Up => wifi::up_connection(Duration::from_secs(30))?,
                 WifiCommands::Down => wifi::down_connection(Duration::from_secs(30))?,
             },
+            ConfCommands::Language { code: None } => println!("{}", i18n::current().code()),
+            ConfCommands::Language { code: Some(code) } => {
+                let language = i18n::Language::parse(&code).ok_or_else(|| {
+                    let codes: Vec<_> = i18n::Language::ALL.iter().map(|l| l.code()).collect();
+                    format!("Unknown language {}, one of {}", code, codes.join(", "))
+                })?;
+                i18n::set(language)?
+            }
+            ConfCommands::UiScale { scale: None } => println!("{}", UiScale::from_env()),
+            ConfCommands::UiScale { scale: Some(scale) } => {
+                let scale: UiScale = scale.parse().map_err(|_| {
+                    format!("Unknown UI scale {}, one of small, normal, large", scale)
+                })?;
+                scale.save()?
+            }
+            ConfCommands::Secrets { secrets_cmd } => match secrets_cmd {
+                SecretsCommands::Set { name, value } => {
+                    // Keeps the value out of the shell history
+                    let value = match value {
+                        Some(value) => value,
+                        None => {
+                            let mut line = String::new();
+                            io::stdin().read_line(&mut line)?;
+                            line.trim_end_matches(['\r', '\n']).to_string()
+                        }
+                    };
+                    secrets::set(&name, &value)?
+                }
+                SecretsCommands::Remove { name } => {
+                    if !secrets::remove(&name)? {
+                        println!("No secret named {}", name);
+                    }
+                }
+                SecretsCommands::List => {
+                    for name in secrets::names()? {
+                        println!("{}", name);
+                    }
+                }
+            },
         },
     }
 
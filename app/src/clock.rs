@@ -0,0 +1,169 @@
+#[cfg(feature = "display_hat")]
+use crate::rtc;
+#[cfg(feature = "display_hat")]
+use crate::util::current_timestamp;
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+use anyhow::Result;
+use std::env;
+
+const DEFAULT_MAX_OFFSET_MS: f64 = 500.0;
+
+/// State of the system clock, as reported by `timedatectl`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ClockStatus {
+    /// Whether systemd-timesyncd is enabled
+    pub ntp: bool,
+    pub synchronized: bool,
+    pub timezone: String,
+    /// NTP server polled last, with its name
+    pub server: Option<String>,
+    /// From the server's time at the last poll, in milliseconds
+    pub offset_ms: Option<f64>,
+    /// Set before amaru-pi was built, e.g. reset by a power loss
+    pub implausible: bool,
+    /// Of the RTC from the system clock, in seconds, when there is one
+    pub rtc_offset_secs: Option<i64>,
+}
+
+impl ClockStatus {
+    /// Off by more than `AMARU_PI_CLOCK_MAX_OFFSET_MS`, which makes blocks
+    /// look early or late to peers.
+    pub fn is_drifting(&self) -> bool {
+        let max_offset_ms = env::var("AMARU_PI_CLOCK_MAX_OFFSET_MS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_OFFSET_MS);
+        self.offset_ms
+            .is_some_and(|offset| offset.abs() > max_offset_ms)
+    }
+
+    pub fn needs_attention(&self) -> bool {
+        self.implausible || !self.synchronized || self.is_drifting()
+    }
+}
+
+/// Reads the `KEY=value` lines of `timedatectl show`.
+fn parse_show(output: &str, status: &mut ClockStatus) {
+    for line in output.lines() {
+        match line.split_once('=') {
+            Some(("Timezone", value)) => status.timezone = value.to_string(),
+            Some(("NTP", value)) => status.ntp = value == "yes",
+            Some(("NTPSynchronized", value)) => status.synchronized = value == "yes",
+            _ => {}
+        }
+    }
+}
+
+/// Parses an offset of `timedatectl timesync-status`, e.g. `-1.058ms`.
+fn parse_offset(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (number, factor) = if let Some(number) = value.strip_suffix("us") {
+        (number, 0.001)
+    } else if let Some(number) = value.strip_suffix("ms") {
+        (number, 1.0)
+    } else {
+        (value.strip_suffix('s')?, 1000.0)
+    };
+    number
+        .trim_start_matches('+')
+        .parse::<f64>()
+        .ok()
+        .map(|number| number * factor)
+}
+
+/// Reads the `Key: value` lines of `timedatectl timesync-status`.
+fn parse_timesync(output: &str, status: &mut ClockStatus) {
+    for line in output.lines() {
+        match line.trim().split_once(": ") {
+            Some(("Server", value)) => status.server = Some(value.trim().to_string()),
+            Some(("Offset", value)) => status.offset_ms = parse_offset(value),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "display_hat")]
+pub fn status() -> Result<ClockStatus> {
+    let mut status = ClockStatus::default();
+    parse_show(&run_and_capture("timedatectl", vec!["show"])?, &mut status);
+    // Fails when timesyncd isn't the NTP client, e.g. with chrony
+    if let Ok(output) = run_and_capture("timedatectl", vec!["timesync-status"]) {
+        parse_timesync(&output, &mut status);
+    }
+    let now = current_timestamp()?;
+    status.implausible = !rtc::is_plausible(now);
+    status.rtc_offset_secs = rtc::read().map(|rtc| rtc as i64 - now as i64);
+    Ok(status)
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn status() -> Result<ClockStatus> {
+    let mut status = ClockStatus::default();
+    parse_show("Timezone=UTC\nNTP=yes\nNTPSynchronized=yes", &mut status);
+    parse_timesync(
+        "Server: 162.159.200.1 (pool.ntp.org)\nOffset: -1.058ms",
+        &mut status,
+    );
+    Ok(status)
+}
+
+#[cfg(feature = "display_hat")]
+pub fn list_timezones() -> Result<Vec<String>> {
+    let output = run_and_capture("timedatectl", vec!["list-timezones"])?;
+    Ok(output.lines().map(str::to_string).collect())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn list_timezones() -> Result<Vec<String>> {
+    Ok([
+        "America/New_York",
+        "Asia/Tokyo",
+        "Europe/London",
+        "Europe/Paris",
+        "UTC",
+    ]
+    .map(str::to_string)
+    .to_vec())
+}
+
+#[cfg(feature = "display_hat")]
+pub fn set_timezone(timezone: &str) -> Result<()> {
+    run_and_capture("timedatectl", vec!["set-timezone", timezone])?;
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn set_timezone(_timezone: &str) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_in_milliseconds() {
+        assert_eq!(parse_offset("+1.500ms"), Some(1.5));
+        assert_eq!(parse_offset("-250us"), Some(-0.25));
+        assert_eq!(parse_offset(" +2s "), Some(2000.0));
+        assert_eq!(parse_offset("12"), None);
+        assert_eq!(parse_offset("fast"), None);
+    }
+
+    #[test]
+    fn timesync_status() {
+        let mut status = ClockStatus::default();
+        parse_timesync(
+            "       Server: 162.159.200.1 (time.cloudflare.com)\n\
+             Poll interval: 34min 8s (min: 32s; max 34min 8s)\n\
+                    Offset: -3.211ms\n",
+            &mut status,
+        );
+        assert_eq!(
+            status.server.as_deref(),
+            Some("162.159.200.1 (time.cloudflare.com)")
+        );
+        assert_eq!(status.offset_ms, Some(-3.211));
+    }
+}
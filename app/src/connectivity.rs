@@ -0,0 +1,435 @@
+use crate::wifi::Connectivity;
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+use reqwest::StatusCode;
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Present while the device is on a metered link. The updater scripts skip
+/// downloads while it exists. Only root can write under /run.
+const METERED_FLAG_PATH: &str = "/run/amaru-pi/metered";
+const DEFAULT_FAILOVER_DELAY_SECS: u64 = 15;
+const DEFAULT_FAILBACK_DELAY_SECS: u64 = 120;
+/// Answers 204 with no body, unless something intercepts the request
+const DEFAULT_PORTAL_PROBE_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Link {
+    Ethernet,
+    Wifi,
+    Lte,
+}
+
+impl Link {
+    /// Interfaces that aren't uplinks (docker, VPNs...) are ignored.
+    #[cfg(feature = "display_hat")]
+    fn from_device(device: &str) -> Option<Self> {
+        if device.starts_with("wlan") {
+            Some(Link::Wifi)
+        } else if device.starts_with("eth") || device.starts_with("en") {
+            Some(Link::Ethernet)
+        } else if device.starts_with("wwan") || device.starts_with("ppp") {
+            Some(Link::Lte)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_metered(&self) -> bool {
+        *self == Link::Lte
+    }
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Link::Ethernet => write!(f, "Ethernet"),
+            Link::Wifi => write!(f, "WiFi"),
+            Link::Lte => write!(f, "LTE"),
+        }
+    }
+}
+
+/// Links with a default route, the one in use (lowest metric) first.
+#[cfg(feature = "display_hat")]
+pub fn default_route_links() -> anyhow::Result<Vec<Link>> {
+    Ok(default_routes()?
+        .into_iter()
+        .map(|(link, _)| link)
+        .collect())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn default_route_links() -> anyhow::Result<Vec<Link>> {
+    Ok(vec![Link::Ethernet])
+}
+
+/// The device of the preferred link that isn't metered, while it has a
+/// default route.
+#[cfg(feature = "display_hat")]
+pub fn primary_device() -> anyhow::Result<Option<String>> {
+    Ok(default_routes()?
+        .into_iter()
+        .find(|(link, _)| !link.is_metered())
+        .map(|(_, device)| device))
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn primary_device() -> anyhow::Result<Option<String>> {
+    Ok(None)
+}
+
+/// Uplinks with a default route and their device, by metric.
+#[cfg(feature = "display_hat")]
+fn default_routes() -> anyhow::Result<Vec<(Link, String)>> {
+    let stdout = run_and_capture("ip", ["-o", "route", "show", "default"].to_vec())?;
+    // e.g. `default via 192.168.1.1 dev wlan0 proto dhcp src 192.168.1.20 metric 600`
+    let mut routes: Vec<(u32, Link, String)> = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let mut device = None;
+            let mut metric = 0;
+            while let Some(word) = words.next() {
+                match word {
+                    "dev" => device = words.next(),
+                    "metric" => metric = words.next().and_then(|m| m.parse().ok()).unwrap_or(0),
+                    _ => {}
+                }
+            }
+            let device = device?;
+            Some((metric, Link::from_device(device)?, device.to_string()))
+        })
+        .collect();
+    routes.sort_by_key(|(metric, _, _)| *metric);
+    Ok(routes
+        .into_iter()
+        .map(|(_, link, device)| (link, device))
+        .collect())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConnectivityEvent {
+    /// The link traffic goes through changed
+    Switched {
+        from: Option<Link>,
+        to: Option<Link>,
+    },
+    /// The primary link is down, the cellular link should take over
+    FailOver,
+    /// The primary link is back, the cellular link can be released
+    FailBack,
+}
+
+/// Follows which link the device uses and decides when to fail over to the
+/// cellular link. Failing back waits for the primary link to be stable for a
+/// while: every switch changes the node's address and drops its peer
+/// connections, so flapping between links costs more than staying on LTE.
+#[derive(Debug)]
+pub struct ConnectivityManager {
+    /// Whether to bring up the cellular link when the primary one fails,
+    /// from `AMARU_PI_LTE_FAILOVER`
+    failover: bool,
+    /// Whether to pause downloads on a metered link, from
+    /// `AMARU_PI_PAUSE_ON_METERED`
+    pause_on_metered: bool,
+    failover_delay: Duration,
+    failback_delay: Duration,
+    active: Option<Link>,
+    failed_over: bool,
+    /// Since when the primary link has been in the state calling for a switch
+    switch_pending_since: Option<Instant>,
+}
+
+fn env_secs(name: &str, default: u64) -> Duration {
+    env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(default))
+}
+
+fn env_bool(name: &str, default: bool) -> bool {
+    env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+impl ConnectivityManager {
+    pub fn from_env() -> Self {
+        Self {
+            failover: env_bool("AMARU_PI_LTE_FAILOVER", false),
+            pause_on_metered: env_bool("AMARU_PI_PAUSE_ON_METERED", true),
+            failover_delay: env_secs("AMARU_PI_FAILOVER_DELAY_SECS", DEFAULT_FAILOVER_DELAY_SECS),
+            failback_delay: env_secs("AMARU_PI_FAILBACK_DELAY_SECS", DEFAULT_FAILBACK_DELAY_SECS),
+            active: None,
+            failed_over: false,
+            switch_pending_since: None,
+        }
+    }
+
+    pub fn pauses_on_metered(&self) -> bool {
+        self.pause_on_metered
+    }
+
+    /// Takes the links with a default route, in order of preference, the
+    /// connectivity NetworkManager reports and whether the internet answered
+    /// through the primary link, `None` if it wasn't probed.
+    pub fn observe(
+        &mut self,
+        links: &[Link],
+        connectivity: Connectivity,
+        primary_reachable: Option<bool>,
+    ) -> Vec<ConnectivityEvent> {
+        let mut events = Vec::new();
+        let active = links.first().copied();
+        if active != self.active {
+            info!("Active link changed from {:?} to {:?}", self.active, active);
+            events.push(ConnectivityEvent::Switched {
+                from: self.active,
+                to: active,
+            });
+            self.active = active;
+            self.update_metered_flag();
+        }
+
+        if !self.failover {
+            return events;
+        }
+
+        // Once failed over the cellular link is preferred, so the primary
+        // link is judged by a probe through its interface: a route alone
+        // comes back with the cable or the access point, not the internet
+        let primary_up = if self.failed_over {
+            links.iter().any(|link| !link.is_metered()) && primary_reachable == Some(true)
+        } else {
+            active.is_some_and(|link| !link.is_metered())
+                && matches!(connectivity, Connectivity::Full | Connectivity::Unknown)
+        };
+        if primary_up != self.failed_over {
+            self.switch_pending_since = None;
+            return events;
+        }
+
+        let since = *self.switch_pending_since.get_or_insert_with(Instant::now);
+        let delay = if self.failed_over {
+            self.failback_delay
+        } else {
+            self.failover_delay
+        };
+        if since.elapsed() >= delay {
+            self.switch_pending_since = None;
+            self.failed_over = !self.failed_over;
+            events.push(if self.failed_over {
+                ConnectivityEvent::FailOver
+            } else {
+                ConnectivityEvent::FailBack
+            });
+        }
+        events
+    }
+
+    /// The cellular link couldn't be brought up, e.g. no modem is plugged in.
+    /// Failing over is retried once the delay elapses again.
+    pub fn failover_failed(&mut self) {
+        self.failed_over = false;
+    }
+
+    fn update_metered_flag(&self) {
+        let metered = self.pause_on_metered && self.active.is_some_and(|link| link.is_metered());
+        let path = Path::new(METERED_FLAG_PATH);
+        let result = if metered {
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(path, b""))
+        } else if path.exists() {
+            fs::remove_file(path)
+        } else {
+            Ok(())
+        };
+        if let Err(e) = result {
+            warn!("Failed to update the metered link flag: {}", e);
+        }
+    }
+}
+
+fn probe_url() -> String {
+    env::var("AMARU_PI_PORTAL_PROBE_URL")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PORTAL_PROBE_URL.to_string())
+}
+
+/// Whether the probe answers through `device` rather than the default route.
+pub async fn probe_through(device: &str) -> bool {
+    let client = match reqwest::Client::builder()
+        .interface(device)
+        .redirect(Policy::none())
+        .timeout(Duration::from_secs(10))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Failed to probe through {}: {}", device, e);
+            return false;
+        }
+    };
+    match client.get(probe_url()).send().await {
+        Ok(response) => response.status() == StatusCode::NO_CONTENT,
+        Err(e) => {
+            info!("The internet doesn't answer through {}: {}", device, e);
+            false
+        }
+    }
+}
+
+/// Probes plain HTTP for a captive portal (hotel or office WiFi) intercepting
+/// requests until someone signs in. Returns the page to open to sign in. The
+/// probe URL can be changed with `AMARU_PI_PORTAL_PROBE_URL`.
+pub async fn detect_captive_portal() -> anyhow::Result<Option<String>> {
+    let probe_url = probe_url();
+    let client = reqwest::Client::builder()
+        .redirect(Policy::none())
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let response = client.get(&probe_url).send().await?;
+
+    if response.status() == StatusCode::NO_CONTENT {
+        return Ok(None);
+    }
+    if response.status().is_redirection()
+        && let Some(location) = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+    {
+        // Relative redirects are resolved against the probe
+        let portal_url = response
+            .url()
+            .join(location)
+            .map(String::from)
+            .unwrap_or_else(|_| location.to_string());
+        return Ok(Some(portal_url));
+    }
+    // The login page was served in place of the probe, opening it again
+    // from a phone on the same network shows it
+    Ok(Some(probe_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Switches as soon as asked, never touching the metered flag
+    fn manager(failover: bool) -> ConnectivityManager {
+        ConnectivityManager {
+            failover,
+            pause_on_metered: false,
+            failover_delay: Duration::ZERO,
+            failback_delay: Duration::ZERO,
+            active: None,
+            failed_over: false,
+            switch_pending_since: None,
+        }
+    }
+
+    #[test]
+    fn reports_switches_without_failover() {
+        let mut manager = manager(false);
+        assert_eq!(
+            manager.observe(&[Link::Wifi], Connectivity::Full, None),
+            vec![ConnectivityEvent::Switched {
+                from: None,
+                to: Some(Link::Wifi),
+            }]
+        );
+        assert!(
+            manager
+                .observe(&[Link::Wifi], Connectivity::Full, None)
+                .is_empty()
+        );
+        assert!(
+            manager
+                .observe(&[Link::Wifi], Connectivity::None, None)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn fails_over_and_back_once_the_primary_link_answers() {
+        let mut manager = manager(true);
+        manager.observe(&[Link::Ethernet], Connectivity::Full, None);
+        assert!(
+            manager
+                .observe(&[Link::Ethernet], Connectivity::Full, None)
+                .is_empty()
+        );
+
+        assert_eq!(
+            manager.observe(&[Link::Ethernet], Connectivity::None, None),
+            vec![ConnectivityEvent::FailOver]
+        );
+        let links = [Link::Lte, Link::Ethernet];
+        let events = manager.observe(&links, Connectivity::Full, None);
+        assert_eq!(
+            events,
+            vec![ConnectivityEvent::Switched {
+                from: Some(Link::Ethernet),
+                to: Some(Link::Lte),
+            }]
+        );
+        // A route back isn't enough, the probe must answer
+        assert!(
+            manager
+                .observe(&links, Connectivity::Full, Some(false))
+                .is_empty()
+        );
+        assert_eq!(
+            manager.observe(&links, Connectivity::Full, Some(true)),
+            vec![ConnectivityEvent::FailBack]
+        );
+    }
+
+    #[test]
+    fn fails_over_again_after_failing_to() {
+        let mut manager = manager(true);
+        assert_eq!(
+            manager.observe(&[], Connectivity::None, None),
+            vec![ConnectivityEvent::FailOver]
+        );
+        manager.failover_failed();
+        assert_eq!(
+            manager.observe(&[], Connectivity::None, None),
+            vec![ConnectivityEvent::FailOver]
+        );
+    }
+
+    #[test]
+    fn waits_for_the_delay() {
+        let mut manager = ConnectivityManager {
+            failover_delay: Duration::from_secs(60),
+            ..manager(true)
+        };
+        manager.observe(&[Link::Wifi], Connectivity::Full, None);
+        assert!(
+            manager
+                .observe(&[Link::Wifi], Connectivity::None, None)
+                .is_empty()
+        );
+        assert!(manager.switch_pending_since.is_some());
+        // Back before the delay, nothing pending anymore
+        assert!(
+            manager
+                .observe(&[Link::Wifi], Connectivity::Full, None)
+                .is_empty()
+        );
+        assert!(manager.switch_pending_since.is_none());
+    }
+}
@@ -0,0 +1,332 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::kiosk;
+use crate::screens::Kind;
+use anyhow::{Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::env;
+use std::fs::{self, Permissions};
+use std::os::unix::fs::{PermissionsExt, chown};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+
+const DEFAULT_PATH: &str = "/run/amaru-pi.sock";
+const GROUP_FILE_PATH: &str = "/etc/group";
+/// Requests waiting for the next frame
+const QUEUE_CAPACITY: usize = 16;
+/// Longest `call` waits for the app to answer
+const CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// Refused by the app, e.g. a screen not enabled
+const CALL_FAILED: i64 = -32000;
+
+/// What a client of the control socket asks the app.
+#[derive(Debug, Clone, Copy)]
+pub enum ControlCall {
+    /// The state of the node and the Pi, with the screen shown
+    Status,
+    SwitchScreen(Kind),
+    /// Runs the updater now
+    CheckUpdates,
+    /// As if a button was pressed
+    Press(InputEvent),
+    /// Saves the next frame as a PNG, answered with its path
+    Screenshot,
+}
+
+/// A call waiting for the app to answer it.
+#[derive(Debug)]
+pub struct ControlRequest {
+    pub call: ControlCall,
+    reply: oneshot::Sender<Result<Value, String>>,
+}
+
+impl ControlRequest {
+    pub fn answer(self, result: Result<Value>) {
+        // The client may be gone already
+        let _ = self.reply.send(result.map_err(|e| e.to_string()));
+    }
+}
+
+/// Lets shell scripts and amaru-doctor drive the app through a Unix socket,
+/// a JSON-RPC 2.0 request a line and its response a line, e.g.
+/// `{"jsonrpc": "2.0", "id": 1, "method": "press", "params": {"button": "A"}}`.
+/// Methods are `status`, `switch_screen` (`{"screen": "tip"}`),
+/// `check_updates`, `press` (`{"button": "A", "type": "Double"}`) and
+/// `screenshot`.
+pub struct ControlSocket {
+    rx: mpsc::Receiver<ControlRequest>,
+}
+
+impl ControlSocket {
+    /// Listens on `AMARU_PI_CONTROL_SOCKET`, `/run/amaru-pi.sock` by
+    /// default. Set empty, or in kiosk mode, there is no socket.
+    pub fn from_env() -> Option<Self> {
+        let path = socket_path()?;
+        if kiosk::is_enabled() {
+            warn!("No control socket in kiosk mode");
+            return None;
+        }
+        // Left by a previous run
+        let _ = fs::remove_file(&path);
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("Failed to listen on {}: {}", path, e);
+                return None;
+            }
+        };
+        // Pressing the buttons confirms dialogs: root only, and the group of
+        // `AMARU_PI_CONTROL_GROUP` when set
+        let group = env::var("AMARU_PI_CONTROL_GROUP")
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .and_then(|name| {
+                let gid = group_id(&name);
+                if gid.is_none() {
+                    warn!("No group {}, {} is only for root", name, path);
+                }
+                gid
+            });
+        let mode = match group.map(|gid| chown(&path, None, Some(gid))) {
+            Some(Ok(())) => 0o660,
+            Some(Err(e)) => {
+                warn!("Failed to give {} to its group: {}", path, e);
+                0o600
+            }
+            None => 0o600,
+        };
+        if let Err(e) = fs::set_permissions(&path, Permissions::from_mode(mode)) {
+            warn!("Failed to restrict {}: {}", path, e);
+        }
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(accept(listener, tx));
+        info!("Listening for control requests on {}", path);
+        Some(Self { rx })
+    }
+
+    /// The requests received since last asked.
+    pub fn requests(&mut self) -> Vec<ControlRequest> {
+        std::iter::from_fn(|| self.rx.try_recv().ok()).collect()
+    }
+}
+
+fn socket_path() -> Option<String> {
+    let path = env::var("AMARU_PI_CONTROL_SOCKET").unwrap_or_else(|_| DEFAULT_PATH.to_string());
+    (!path.trim().is_empty()).then_some(path)
+}
+
+/// Calls a method of the running app through its socket, e.g. for
+/// `amaru-pi ctl`. Returns the result of the call.
+pub async fn call(method: &str, params: Value) -> Result<Value> {
+    let path = socket_path().ok_or_else(|| anyhow!("The control socket is disabled"))?;
+    let stream = UnixStream::connect(&path)
+        .await
+        .map_err(|e| anyhow!("Failed to reach the app on {}: {}", path, e))?;
+    let (read, mut write) = stream.into_split();
+    let mut request =
+        json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}).to_string();
+    request.push('\n');
+    write.write_all(request.as_bytes()).await?;
+    let line = tokio::time::timeout(CALL_TIMEOUT, BufReader::new(read).lines().next_line())
+        .await
+        .map_err(|_| anyhow!("The app didn't answer"))??
+        .ok_or_else(|| anyhow!("The app closed the connection"))?;
+    let mut response: Value = serde_json::from_str(&line)?;
+    if let Some(message) = response["error"]["message"].as_str() {
+        bail!("{}", message);
+    }
+    Ok(response["result"].take())
+}
+
+/// The id of a group, from lines like `amaru:x:1001:pi`.
+fn group_id(name: &str) -> Option<u32> {
+    fs::read_to_string(GROUP_FILE_PATH)
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            (fields.next()? == name).then_some(())?;
+            fields.nth(1)?.parse().ok()
+        })
+}
+
+async fn accept(listener: UnixListener, tx: mpsc::Sender<ControlRequest>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(serve(stream, tx.clone()));
+            }
+            Err(e) => warn!("Failed to accept a control connection: {}", e),
+        }
+    }
+}
+
+/// Answers the requests of a client until it disconnects.
+async fn serve(stream: UnixStream, tx: mpsc::Sender<ControlRequest>) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut response = respond(&line, &tx).await.to_string();
+        response.push('\n');
+        if write.write_all(response.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScreenParams {
+    screen: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PressParams {
+    button: ButtonId,
+    /// A short press when not given
+    #[serde(rename = "type")]
+    press_type: Option<ButtonPress>,
+}
+
+async fn respond(line: &str, tx: &mpsc::Sender<ControlRequest>) -> Value {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(request) => request,
+        Err(e) => return error(Value::Null, PARSE_ERROR, e.to_string()),
+    };
+    let request: RpcRequest = match serde_json::from_value(request) {
+        Ok(request) => request,
+        Err(e) => return error(Value::Null, INVALID_REQUEST, e.to_string()),
+    };
+    let call = match parse_call(&request.method, request.params) {
+        Ok(call) => call,
+        Err((code, message)) => return error(request.id, code, message),
+    };
+
+    let (reply, answer) = oneshot::channel();
+    if tx.send(ControlRequest { call, reply }).await.is_err() {
+        return error(request.id, INTERNAL_ERROR, "The app stopped".to_string());
+    }
+    match answer.await {
+        Ok(Ok(result)) => json!({"jsonrpc": "2.0", "id": request.id, "result": result}),
+        Ok(Err(e)) => error(request.id, CALL_FAILED, e),
+        Err(_) => error(request.id, INTERNAL_ERROR, "The app stopped".to_string()),
+    }
+}
+
+fn parse_call(method: &str, params: Value) -> Result<ControlCall, (i64, String)> {
+    let invalid = |e: serde_json::Error| (INVALID_PARAMS, e.to_string());
+    match method {
+        "status" => Ok(ControlCall::Status),
+        "check_updates" => Ok(ControlCall::CheckUpdates),
+        "screenshot" => Ok(ControlCall::Screenshot),
+        "switch_screen" => {
+            let params: ScreenParams = serde_json::from_value(params).map_err(invalid)?;
+            params
+                .screen
+                .parse()
+                .map(ControlCall::SwitchScreen)
+                .map_err(|_| (INVALID_PARAMS, format!("Unknown screen {}", params.screen)))
+        }
+        "press" => {
+            let params: PressParams = serde_json::from_value(params).map_err(invalid)?;
+            Ok(ControlCall::Press(InputEvent {
+                id: params.button,
+                press_type: params.press_type.unwrap_or(ButtonPress::Short),
+            }))
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("Unknown method {}", method))),
+    }
+}
+
+fn error(id: Value, code: i64, message: String) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_without_params() {
+        assert!(matches!(
+            parse_call("status", Value::Null),
+            Ok(ControlCall::Status)
+        ));
+        assert!(matches!(
+            parse_call("check_updates", Value::Null),
+            Ok(ControlCall::CheckUpdates)
+        ));
+        assert!(matches!(
+            parse_call("screenshot", Value::Null),
+            Ok(ControlCall::Screenshot)
+        ));
+    }
+
+    #[test]
+    fn switch_screen() {
+        assert!(matches!(
+            parse_call("switch_screen", json!({"screen": "clock"})),
+            Ok(ControlCall::SwitchScreen(Kind::Clock))
+        ));
+        assert!(matches!(
+            parse_call("switch_screen", json!({"screen": "nowhere"})),
+            Err((INVALID_PARAMS, _))
+        ));
+        assert!(matches!(
+            parse_call("switch_screen", Value::Null),
+            Err((INVALID_PARAMS, _))
+        ));
+    }
+
+    #[test]
+    fn press_is_short_by_default() {
+        assert!(matches!(
+            parse_call("press", json!({"button": "A"})),
+            Ok(ControlCall::Press(InputEvent {
+                id: ButtonId::A,
+                press_type: ButtonPress::Short,
+            }))
+        ));
+        assert!(matches!(
+            parse_call("press", json!({"button": "X", "type": "Long"})),
+            Ok(ControlCall::Press(InputEvent {
+                id: ButtonId::X,
+                press_type: ButtonPress::Long,
+            }))
+        ));
+        assert!(matches!(
+            parse_call("press", json!({"button": "Z"})),
+            Err((INVALID_PARAMS, _))
+        ));
+    }
+
+    #[test]
+    fn unknown_method() {
+        assert!(matches!(
+            parse_call("reboot", Value::Null),
+            Err((METHOD_NOT_FOUND, _))
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::notifications;
+use crate::usb;
+use anyhow::{Result, anyhow};
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+#[cfg(feature = "display_hat")]
+use serde::Deserialize;
+#[cfg(feature = "display_hat")]
+use std::cmp::Reverse;
+
+const CONFIG_PATH: &str = "/etc/systemd/coredump.conf.d/amaru.conf";
+/// Disk space all core dumps may use, older ones are removed past it
+const DEFAULT_MAX_USE: &str = "1G";
+/// Core dumps are skipped rather than filling the disk the node needs
+const KEEP_FREE: &str = "4G";
+/// Directory created on the USB drive
+const EXPORT_DIR: &str = "amaru-coredumps";
+
+/// A crash of the node captured by systemd-coredump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coredump {
+    pub pid: u32,
+    /// Seconds since the UNIX epoch
+    pub timestamp: u64,
+    pub signal: i32,
+    /// Compressed size on disk
+    pub size: Option<u64>,
+    /// Whether the dump is still stored, systemd-coredump removes old ones
+    pub present: bool,
+}
+
+impl Coredump {
+    pub fn signal_name(&self) -> String {
+        match self.signal {
+            4 => "SIGILL".to_string(),
+            6 => "SIGABRT".to_string(),
+            7 => "SIGBUS".to_string(),
+            8 => "SIGFPE".to_string(),
+            11 => "SIGSEGV".to_string(),
+            signal => format!("signal {}", signal),
+        }
+    }
+}
+
+/// From `AMARU_PI_COREDUMP_MAX_USE`, e.g. `500M`.
+pub fn max_use() -> String {
+    env::var("AMARU_PI_COREDUMP_MAX_USE")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_MAX_USE.to_string())
+}
+
+/// Writes the systemd-coredump settings, read for each new dump. Returns
+/// whether they changed.
+pub fn configure() -> Result<bool> {
+    let config = format!(
+        "[Coredump]\nStorage=external\nCompress=yes\nMaxUse={}\nKeepFree={}\n",
+        max_use(),
+        KEEP_FREE
+    );
+    if fs::read_to_string(CONFIG_PATH).is_ok_and(|current| current == config) {
+        return Ok(false);
+    }
+    if let Some(dir) = Path::new(CONFIG_PATH).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(CONFIG_PATH, config)?;
+    Ok(true)
+}
+
+/// One entry of `coredumpctl list --json=short`.
+#[cfg(feature = "display_hat")]
+#[derive(Debug, Deserialize)]
+struct Entry {
+    /// Microseconds since the UNIX epoch
+    time: u64,
+    pid: u32,
+    sig: i32,
+    corefile: String,
+    size: Option<u64>,
+}
+
+/// Parses `coredumpctl list --json=short`, newest first.
+#[cfg(feature = "display_hat")]
+fn parse_list(output: &str) -> Result<Vec<Coredump>> {
+    let entries: Vec<Entry> = serde_json::from_str(output)?;
+    let mut dumps: Vec<Coredump> = entries
+        .into_iter()
+        .map(|entry| Coredump {
+            pid: entry.pid,
+            timestamp: entry.time / 1_000_000,
+            signal: entry.sig,
+            size: entry.size,
+            present: entry.corefile == "present",
+        })
+        .collect();
+    dumps.sort_by_key(|dump| Reverse(dump.timestamp));
+    Ok(dumps)
+}
+
+/// Core dumps of the node, newest first.
+#[cfg(feature = "display_hat")]
+pub fn list() -> Result<Vec<Coredump>> {
+    match run_and_capture(
+        "coredumpctl",
+        ["list", "--json=short", "--no-pager", "amaru"].to_vec(),
+    ) {
+        Ok(output) => parse_list(&output),
+        Err(e) if e.to_string().contains("No coredumps found") => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn list() -> Result<Vec<Coredump>> {
+    Ok(Vec::new())
+}
+
+/// Details of a dump, with the backtrace, as `coredumpctl info` prints them.
+#[cfg(feature = "display_hat")]
+fn info(pid: u32) -> Result<String> {
+    run_and_capture(
+        "coredumpctl",
+        ["info", "--no-pager", &pid.to_string()].to_vec(),
+    )
+}
+
+#[cfg(not(feature = "display_hat"))]
+fn info(_pid: u32) -> Result<String> {
+    Err(anyhow!("Core dumps are only available on the device"))
+}
+
+/// File the dump is stored in, from the `Storage:` line of `coredumpctl info`.
+fn storage_path(info: &str) -> Option<PathBuf> {
+    let storage = info
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Storage:"))?;
+    let path = storage.trim().split(" (").next()?;
+    Some(PathBuf::from(path))
+}
+
+/// Copies a dump, still compressed, and its details to the USB drive for an
+/// upstream bug report. Returns the name of the dump on the drive.
+pub fn export(pid: u32) -> Result<String> {
+    let info = info(pid)?;
+    let storage = storage_path(&info).ok_or_else(|| anyhow!("Core dump {} isn't stored", pid))?;
+    let name = storage
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Unexpected core dump path {}", storage.display()))?;
+
+    let drive = usb::open()?;
+    let dir = drive.path().join(EXPORT_DIR);
+    fs::create_dir_all(&dir)?;
+    let dest = dir.join(&name);
+    fs::copy(&storage, &dest)?;
+    File::open(&dest)?.sync_all()?;
+    let info_path = dir.join(format!("{}.txt", name));
+    fs::write(&info_path, info)?;
+    File::open(&info_path)?.sync_all()?;
+    // What happened around the crash, with the operator's notes
+    if let Err(e) = notifications::export_history(&dir) {
+        warn!("Failed to export the event log: {}", e);
+    }
+    info!("Exported core dump {} to {}", pid, dest.display());
+    Ok(name)
+}
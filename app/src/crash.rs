@@ -0,0 +1,169 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use std::backtrace::Backtrace;
+use std::env;
+use std::fs;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::panic::{self, PanicHookInfo};
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+/// Report of the last panic, overwritten by the next one
+pub const REPORT_PATH: &str = "/home/pi/.amaru_pi_crash.txt";
+/// The crash screen restarts amaru-pi by itself after this long, so that an
+/// unattended device recovers
+const DEFAULT_RESTART_SECS: u64 = 60;
+
+/// Set by the panic hook, taken by the UI loop once it caught the panic
+static LAST_PANIC: Mutex<Option<CrashReport>> = Mutex::new(None);
+
+/// What is known about a panic.
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub message: String,
+    /// `file:line:column` the panic was raised at
+    pub location: Option<String>,
+    pub thread: String,
+    /// Seconds since the UNIX epoch
+    pub timestamp: u64,
+    pub backtrace: String,
+}
+
+impl CrashReport {
+    fn from_hook(info: &PanicHookInfo) -> Self {
+        let message = info
+            .payload_as_str()
+            .unwrap_or("Unknown panic payload")
+            .to_string();
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            message,
+            location,
+            thread: thread::current().name().unwrap_or("unnamed").to_string(),
+            timestamp,
+            backtrace: Backtrace::force_capture().to_string(),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        format!(
+            "amaru-pi {} panicked\ntimestamp: {}\nthread: {}\nlocation: {}\nmessage: {}\n\n{}",
+            env!("CARGO_PKG_VERSION"),
+            self.timestamp,
+            self.thread,
+            self.location.as_deref().unwrap_or("unknown"),
+            self.message,
+            self.backtrace
+        )
+    }
+}
+
+/// Records every panic to `REPORT_PATH` before the default hook prints it.
+/// Panics of the input thread are recorded too, though only those of the UI
+/// loop end up on the crash screen.
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = CrashReport::from_hook(info);
+        error!(
+            "Panicked at {}: {}",
+            report.location.as_deref().unwrap_or("unknown location"),
+            report.message
+        );
+        if let Err(e) = fs::write(REPORT_PATH, report.to_text()) {
+            warn!("Failed to write crash report to {}: {}", REPORT_PATH, e);
+        }
+        if let Ok(mut last) = LAST_PANIC.lock() {
+            *last = Some(report);
+        }
+        default_hook(info);
+    }));
+}
+
+/// The panic just caught, as recorded by the hook.
+pub fn take_report() -> CrashReport {
+    LAST_PANIC
+        .lock()
+        .ok()
+        .and_then(|mut last| last.take())
+        .unwrap_or_else(|| CrashReport {
+            message: "Unknown panic".to_string(),
+            location: None,
+            thread: "main".to_string(),
+            timestamp: 0,
+            backtrace: String::new(),
+        })
+}
+
+/// From `AMARU_PI_CRASH_RESTART_SECS`, 0 waiting for a button press.
+pub fn restart_delay() -> Option<Duration> {
+    let secs = env::var("AMARU_PI_CRASH_RESTART_SECS")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_RESTART_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Whether the crash screen's restart button was pressed.
+pub fn is_restart_request(event: &InputEvent) -> bool {
+    event.id == ButtonId::A && event.press_type == ButtonPress::Short
+}
+
+/// Replaces the process with a fresh one running the same command. Only
+/// returns if it couldn't be started.
+pub fn restart() -> io::Error {
+    let exe = match env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return e,
+    };
+    info!("Restarting {} after a crash", exe.display());
+    Command::new(exe).args(env::args_os().skip(1)).exec()
+}
+
+/// Drawn in place of the screens once the UI loop panicked, as the state of
+/// the app can't be trusted anymore.
+pub fn render(frame: &mut Frame, report: &CrashReport, restart_in: Option<Duration>) {
+    let area = frame.area();
+    let block = Block::default()
+        .title(" Something went wrong ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(report.message.as_str()).red(),
+        Line::from(""),
+    ];
+    if let Some(location) = &report.location {
+        lines.push(Line::from(location.as_str()).gray());
+    }
+    lines.push(Line::from(format!("Report saved to {}", REPORT_PATH)).gray());
+    lines.push(Line::from(""));
+    lines.push(match restart_in {
+        Some(remaining) => Line::from(format!(
+            "A: Restart now | Restarting in {}s",
+            remaining.as_secs()
+        )),
+        None => Line::from("A: Restart"),
+    });
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .centered()
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+}
@@ -0,0 +1,125 @@
+use crate::actions::handle_action;
+use crate::app::{App, AppAction, AppEvent};
+#[cfg(feature = "oled")]
+use crate::backends::oled::Oled;
+use crate::control::ControlSocket;
+use crate::crash;
+#[cfg(feature = "oled")]
+use crate::glance::Glance;
+#[cfg(feature = "led_matrix")]
+use crate::outputs::matrix::{self, LedMatrix};
+use crate::rtc;
+use crate::self_update::{self, Handoff};
+use crate::supervisor;
+use crate::watchdog::Watchdog;
+use anyhow::Result;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+use tokio::signal::unix::{SignalKind, signal};
+use tracing::info;
+#[cfg(any(feature = "oled", feature = "led_matrix"))]
+use tracing::warn;
+
+/// Background checks are timed in seconds, no need to tick as often as the
+/// display refreshes
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Shows the glance of the node on the OLED, when there is one. The OLED is
+/// given up on after an error, e.g. once unplugged.
+#[cfg(feature = "oled")]
+fn show_glance(oled: &mut Option<Oled>, glance: &mut Glance, app: &App) {
+    let Some(display) = oled else {
+        return;
+    };
+    glance.tick();
+    display.clear();
+    let _ = glance.draw(display, app.system_state());
+    if let Err(e) = display.flush() {
+        warn!("Failed to draw on the OLED, leaving it: {}", e);
+        *oled = None;
+    }
+}
+
+/// Runs the background checks, updates and alerts of the UI without any
+/// display, for Pis without a HAT. Notifications only reach the configured
+/// channels and the event history. Built with `oled`, a tiny OLED shows a
+/// glance of the node, and with `led_matrix` an LED matrix its sync.
+pub async fn run(handoff: Option<Handoff>) -> Result<()> {
+    crash::install_hook();
+    rtc::restore_system_time();
+    supervisor::start_from_env();
+    let mut app = App::default();
+    app.set_headless();
+    if let Some(handoff) = handoff {
+        app.resume_after_update(handoff);
+    }
+    let mut watchdog = Watchdog::from_env();
+    let mut control = ControlSocket::from_env();
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    #[cfg(feature = "oled")]
+    let mut oled = Oled::from_env().unwrap_or_else(|e| {
+        warn!("Failed to set up the OLED: {}", e);
+        None
+    });
+    #[cfg(feature = "oled")]
+    let mut glance = Glance::from_env();
+    #[cfg(feature = "led_matrix")]
+    let mut led_matrix = LedMatrix::from_env().unwrap_or_else(|e| {
+        warn!("Failed to set up the LED matrix: {}", e);
+        None
+    });
+    let mut ticks = tokio::time::interval(TICK_INTERVAL);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    info!("Running without display");
+
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = terminate.recv() => break,
+            _ = interrupt.recv() => break,
+        }
+
+        let mut events = vec![AppEvent::Tick];
+        events.extend(app.completed_actions());
+        if let Some(control) = &mut control {
+            events.extend(control.requests().into_iter().map(AppEvent::Control));
+        }
+        let updated = panic::catch_unwind(AssertUnwindSafe(|| {
+            events
+                .into_iter()
+                .flat_map(|event| app.update(event))
+                .collect::<Vec<_>>()
+        }));
+        let actions = match updated {
+            Ok(actions) => actions,
+            Err(_) => {
+                // Logged by the panic hook, there is no crash screen to show
+                drop(watchdog);
+                if let Some(delay) = crash::restart_delay() {
+                    tokio::time::sleep(delay).await;
+                }
+                // Only returns if amaru-pi couldn't be started again
+                return Err(crash::restart().into());
+            }
+        };
+        for action in actions {
+            match action {
+                AppAction::Quit => return Ok(()),
+                AppAction::Restart => {
+                    drop(watchdog);
+                    // Only returns if the new binary couldn't be started
+                    return Err(self_update::restart(app.current_screen_kind()));
+                }
+                action => handle_action(&mut app, action),
+            }
+        }
+        #[cfg(feature = "oled")]
+        show_glance(&mut oled, &mut glance, &app);
+        #[cfg(feature = "led_matrix")]
+        matrix::show_status(&mut led_matrix, app.system_state());
+        watchdog.ping();
+    }
+    info!("Stopping");
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use crate::chain::Network;
+use crate::leadership::{LeaderSlot, LeadershipSchedule, SlotOutcome};
+use crate::peers::{PeerLocation, PeersSnapshot};
+use crate::pools::{PoolInfo, PoolsSnapshot};
+use crate::screens::Kind;
+use crate::util::current_timestamp;
+use std::env;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const DEFAULT_DURATION: Duration = Duration::from_secs(4 * 60 * 60);
+const DEFAULT_CYCLE: Duration = Duration::from_secs(20);
+/// Cycling pauses this long after a visitor presses a button
+const VISITOR_PAUSE: Duration = Duration::from_secs(60);
+/// Screens whose data is all synthetic during a demo
+const DEFAULT_SCREENS: [Kind; 5] = [
+    Kind::Tip,
+    Kind::PeersMap,
+    Kind::Blocks,
+    Kind::Leadership,
+    Kind::Pools,
+];
+/// Where the synthetic peers are, by country and coordinates
+const PEER_CITIES: [(&str, f64, f64); 12] = [
+    ("FR", 48.86, 2.35),
+    ("DE", 50.11, 8.68),
+    ("GB", 51.51, -0.13),
+    ("US", 40.71, -74.01),
+    ("US", 37.77, -122.42),
+    ("CA", 43.65, -79.38),
+    ("BR", -23.55, -46.63),
+    ("JP", 35.68, 139.69),
+    ("SG", 1.35, 103.82),
+    ("AU", -33.87, 151.21),
+    ("ZA", -33.92, 18.42),
+    ("KE", -1.29, 36.82),
+];
+const POOLS: [(&str, u64, f64); 5] = [
+    ("AMARU", 42_000_000, 0.54),
+    ("RPI", 12_500_000, 0.16),
+    ("PRAOS", 71_000_000, 0.91),
+    ("HYDRA", 33_700_000, 0.43),
+    ("LOVE", 5_200_000, 0.07),
+];
+/// Slots of the synthetic schedule, relative to now, with their outcome
+const LEADER_SLOTS: [(i64, SlotOutcome); 6] = [
+    (-61_200, SlotOutcome::Minted),
+    (-38_400, SlotOutcome::Minted),
+    (-17_900, SlotOutcome::NotMinted),
+    (-2_100, SlotOutcome::Minted),
+    (1_260, SlotOutcome::Upcoming),
+    (29_800, SlotOutcome::Upcoming),
+];
+
+/// Read by the data sources, which the demo replaces.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether a demo is running, the node data then being synthetic.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Shows synthetic data and cycles through the screens showing it off, for
+/// booths without a synced node. Ends by itself after a while, so that a
+/// device left in demo mode goes back to showing its node.
+#[derive(Debug)]
+pub struct DemoMode {
+    started: Option<Instant>,
+    duration: Duration,
+    cycle: Duration,
+    screens: Vec<Kind>,
+    last_switch: Instant,
+    last_input: Option<Instant>,
+}
+
+impl DemoMode {
+    /// Reads `AMARU_PI_DEMO_MINUTES`, how long a demo lasts,
+    /// `AMARU_PI_DEMO_CYCLE_SECS`, how long each screen is shown, and
+    /// `AMARU_PI_DEMO_SCREENS`, the comma separated screens shown.
+    pub fn from_env() -> Self {
+        let duration = env::var("AMARU_PI_DEMO_MINUTES")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(|minutes: u64| Duration::from_secs(minutes * 60))
+            .unwrap_or(DEFAULT_DURATION);
+        let cycle = env::var("AMARU_PI_DEMO_CYCLE_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_CYCLE);
+        let screens: Vec<Kind> = env::var("AMARU_PI_DEMO_SCREENS")
+            .map(|var| var.split(',').filter_map(|s| s.parse().ok()).collect())
+            .unwrap_or_default();
+        Self {
+            started: None,
+            duration,
+            cycle,
+            screens: if screens.is_empty() {
+                DEFAULT_SCREENS.to_vec()
+            } else {
+                screens
+            },
+            last_switch: Instant::now(),
+            last_input: None,
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Starts or stops the demo. Returns whether it is now on.
+    pub fn toggle(&mut self) -> bool {
+        self.started = match self.started {
+            Some(_) => None,
+            None => Some(Instant::now()),
+        };
+        self.last_switch = Instant::now();
+        ACTIVE.store(self.is_on(), Ordering::Relaxed);
+        self.is_on()
+    }
+
+    /// A visitor is using the buttons, the cycle waits for them.
+    pub fn touch(&mut self) {
+        self.last_input = Some(Instant::now());
+    }
+
+    /// Ends the demo once it has lasted its time. Returns whether it did.
+    pub fn expire(&mut self) -> bool {
+        if self
+            .started
+            .is_some_and(|started| started.elapsed() >= self.duration)
+        {
+            self.toggle();
+            return true;
+        }
+        false
+    }
+
+    /// The screen to show after `current`, when it is time to move on.
+    pub fn next_screen(&mut self, current: Kind, enabled: impl Fn(Kind) -> bool) -> Option<Kind> {
+        if !self.is_on()
+            || self.last_switch.elapsed() < self.cycle
+            || self
+                .last_input
+                .is_some_and(|last| last.elapsed() < VISITOR_PAUSE)
+        {
+            return None;
+        }
+        self.last_switch = Instant::now();
+        let start = self
+            .screens
+            .iter()
+            .position(|kind| *kind == current)
+            .map_or(0, |i| i + 1);
+        (0..self.screens.len())
+            .map(|i| self.screens[(start + i) % self.screens.len()])
+            .find(|kind| enabled(*kind))
+    }
+}
+
+pub fn peers() -> PeersSnapshot {
+    let located = PEER_CITIES
+        .iter()
+        .enumerate()
+        .map(|(i, (country, latitude, longitude))| PeerLocation {
+            // Documentation range, never a real peer
+            addr: IpAddr::V4(Ipv4Addr::new(203, 0, 113, 10 + i as u8)),
+            country: country.to_string(),
+            latitude: *latitude,
+            longitude: *longitude,
+        })
+        .collect();
+    PeersSnapshot {
+        located,
+        unlocated: 2,
+    }
+}
+
+pub fn pools() -> PoolsSnapshot {
+    let pools = POOLS
+        .iter()
+        .enumerate()
+        .map(|(i, (ticker, ada, saturation))| PoolInfo {
+            pool_id: format!("pool1demo{:02}", i),
+            ticker: Some(ticker.to_string()),
+            live_stake: ada * 1_000_000,
+            active_stake: ada * 990_000,
+            saturation: *saturation,
+            blocks: ada / 1_500_000,
+        })
+        .collect();
+    PoolsSnapshot {
+        pools,
+        fetched_at: current_timestamp().unwrap_or_default(),
+    }
+}
+
+/// A schedule with a few minted slots and the next one minutes away.
+pub fn leadership() -> LeadershipSchedule {
+    let network = Network::from_env();
+    let now = current_timestamp().unwrap_or_default();
+    let slot_now = network.slot_at(now);
+    let slots = LEADER_SLOTS
+        .iter()
+        .map(|(offset, outcome)| {
+            let slot = slot_now.saturating_add_signed(*offset);
+            LeaderSlot {
+                slot,
+                time: network.slot_time(slot),
+                outcome: *outcome,
+            }
+        })
+        .collect();
+    LeadershipSchedule {
+        epoch: Some(network.epoch_for_slot(slot_now)),
+        slots,
+    }
+}
@@ -0,0 +1,47 @@
+use ratatui::layout::Size;
+use std::env;
+
+/// Displays at least this large, in cells, are comfortable when automatic
+const COMFORTABLE_MIN_WIDTH: u16 = 80;
+const COMFORTABLE_MIN_HEIGHT: u16 = 30;
+
+/// How tightly screens are laid out. Compact fits the small displays of the
+/// HATs, comfortable adds spacing and columns on HDMI or terminal backends.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Density {
+    #[default]
+    Compact,
+    Comfortable,
+}
+
+impl Density {
+    /// Reads `AMARU_PI_DENSITY`, `compact`, `comfortable` or `auto` to pick
+    /// from the size of the display.
+    pub fn from_env(size: Size) -> Self {
+        match env::var("AMARU_PI_DENSITY")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "compact" => Density::Compact,
+            "comfortable" => Density::Comfortable,
+            _ if size.width >= COMFORTABLE_MIN_WIDTH && size.height >= COMFORTABLE_MIN_HEIGHT => {
+                Density::Comfortable
+            }
+            _ => Density::Compact,
+        }
+    }
+
+    pub fn is_comfortable(&self) -> bool {
+        *self == Density::Comfortable
+    }
+
+    /// Cells around the screen body and between table rows.
+    pub fn spacing(&self) -> u16 {
+        match self {
+            Density::Compact => 0,
+            Density::Comfortable => 1,
+        }
+    }
+}
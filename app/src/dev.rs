@@ -0,0 +1,214 @@
+use crate::chain::Network;
+use crate::demo;
+use crate::systemd::{ActiveState, EnabledState, ServiceInfo};
+use crate::util::current_timestamp;
+use serde_json::json;
+use std::collections::HashSet;
+use std::env;
+use std::sync::{LazyLock, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Number of journal lines kept by the mock node
+const JOURNAL_MAX_LINES: usize = 500;
+/// Average number of slots between two blocks
+const SLOTS_PER_BLOCK: u64 = 20;
+/// How often faults repeat their warnings in the journal
+const FAULT_WARNING_INTERVAL: Duration = Duration::from_secs(10);
+const HEALTHY_PEERS: u32 = 8;
+
+static DEV_MODE: OnceLock<bool> = OnceLock::new();
+static MOCK_NODE: LazyLock<Mutex<MockNode>> = LazyLock::new(|| Mutex::new(MockNode::new()));
+
+/// Whether dev mode is enabled through `AMARU_PI_DEV_MODE`. In dev mode the
+/// node data comes from a built-in mock node instead of amaru.
+pub fn is_enabled() -> bool {
+    *DEV_MODE.get_or_init(|| {
+        env::var("AMARU_PI_DEV_MODE")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(false)
+    })
+}
+
+/// Whether the node data comes from the mock node, in dev mode or during a
+/// demo.
+pub fn is_mocked() -> bool {
+    is_enabled() || demo::is_active()
+}
+
+/// The mock node shared by all node data consumers.
+pub fn mock_node() -> MutexGuard<'static, MockNode> {
+    MOCK_NODE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A fault that can be injected into the mock node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// The tip stops advancing
+    TipStall,
+    /// All peers disconnect, the node falls behind
+    PeerLoss,
+    /// The disk is close to full
+    DiskPressure,
+}
+
+impl Fault {
+    pub const ALL: [Fault; 3] = [Fault::TipStall, Fault::PeerLoss, Fault::DiskPressure];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Fault::TipStall => "Tip stall",
+            Fault::PeerLoss => "Peer loss",
+            Fault::DiskPressure => "Disk pressure",
+        }
+    }
+}
+
+/// Simulates a node following the chain and writes what amaru would log.
+pub struct MockNode {
+    slot: u64,
+    faults: HashSet<Fault>,
+    /// Lines logged so far, the first one being at index `journal_offset`
+    journal: Vec<String>,
+    journal_offset: usize,
+    last_step: Instant,
+    last_fault_warning: Instant,
+}
+
+impl MockNode {
+    fn new() -> Self {
+        Self {
+            slot: Network::from_env().slot_at(current_timestamp().unwrap_or(0)),
+            faults: HashSet::new(),
+            journal: Vec::new(),
+            journal_offset: 0,
+            last_step: Instant::now(),
+            last_fault_warning: Instant::now(),
+        }
+    }
+
+    pub fn is_active(&self, fault: Fault) -> bool {
+        self.faults.contains(&fault)
+    }
+
+    pub fn toggle(&mut self, fault: Fault) {
+        if self.faults.insert(fault) {
+            self.warn_about(fault);
+        } else {
+            self.faults.remove(&fault);
+            self.log("INFO", &format!("fault cleared: {}", fault.label()));
+        }
+    }
+
+    pub fn peers(&self) -> u32 {
+        if self.is_active(Fault::PeerLoss) {
+            0
+        } else {
+            HEALTHY_PEERS
+        }
+    }
+
+    /// Share of the disk in use, in percent.
+    pub fn disk_usage(&self) -> u8 {
+        if self.is_active(Fault::DiskPressure) {
+            97
+        } else {
+            42
+        }
+    }
+
+    pub fn service_info(&self) -> ServiceInfo {
+        ServiceInfo {
+            name: "amaru.service".to_string(),
+            description: "Mock amaru node".to_string(),
+            active_state: ActiveState::Active,
+            sub_state: "running".to_string(),
+            enabled_state: EnabledState::Enabled,
+            main_pid: Some(std::process::id()),
+        }
+    }
+
+    /// Returns the lines logged after `cursor` and moves it forward.
+    pub fn lines_since(&mut self, cursor: &mut usize) -> Vec<String> {
+        self.step();
+        let start = (*cursor).max(self.journal_offset) - self.journal_offset;
+        *cursor = self.journal_offset + self.journal.len();
+        self.journal[start..].to_vec()
+    }
+
+    /// Advances the chain by the wall clock time elapsed since last step.
+    fn step(&mut self) {
+        let elapsed = self.last_step.elapsed().as_secs();
+        if elapsed == 0 {
+            return;
+        }
+        self.last_step += Duration::from_secs(elapsed);
+
+        if !self.is_active(Fault::TipStall) {
+            // Catch up at most a minute worth of slots to keep the journal small
+            for _ in 0..elapsed.min(60) {
+                self.slot += 1;
+                if is_block_slot(self.slot) {
+                    self.log_tip();
+                }
+            }
+            self.slot += elapsed.saturating_sub(60);
+        }
+
+        if self.last_fault_warning.elapsed() >= FAULT_WARNING_INTERVAL {
+            self.last_fault_warning = Instant::now();
+            let faults: Vec<_> = self.faults.iter().copied().collect();
+            for fault in faults {
+                self.warn_about(fault);
+            }
+        }
+    }
+
+    fn log_tip(&mut self) {
+        let point = format!("{}.{:016x}", self.slot, self.slot.wrapping_mul(0x9e37_79b9));
+        // Without peers the node only hears about the chain with a delay
+        let message = if self.is_active(Fault::PeerLoss) {
+            "diffusion.forward_chain.new_tip"
+        } else {
+            "track_peers.caught_up.new_tip"
+        };
+        let line = json!({
+            "level": "INFO",
+            "fields": { "message": message, "point": point },
+            "target": "amaru::consensus",
+        });
+        self.push(line.to_string());
+    }
+
+    fn warn_about(&mut self, fault: Fault) {
+        let message = match fault {
+            Fault::TipStall => format!("no new block since slot {}", self.slot),
+            Fault::PeerLoss => "all upstream peers disconnected".to_string(),
+            Fault::DiskPressure => format!("low disk space: {}% used", self.disk_usage()),
+        };
+        self.log("WARN", &message);
+    }
+
+    fn log(&mut self, level: &str, message: &str) {
+        let line = json!({
+            "level": level,
+            "fields": { "message": message },
+            "target": "amaru::mock",
+        });
+        self.push(line.to_string());
+    }
+
+    fn push(&mut self, line: String) {
+        self.journal.push(line);
+        let overflow = self.journal.len().saturating_sub(JOURNAL_MAX_LINES);
+        self.journal.drain(..overflow);
+        self.journal_offset += overflow;
+    }
+}
+
+/// Spreads blocks pseudo-randomly, one every `SLOTS_PER_BLOCK` on average.
+fn is_block_slot(slot: u64) -> bool {
+    slot.wrapping_mul(2_654_435_761)
+        .rotate_right(16)
+        .is_multiple_of(SLOTS_PER_BLOCK)
+}
@@ -0,0 +1,231 @@
+use crate::status::hostname;
+use crate::util::current_timestamp;
+use crate::{crash, logging, notifications, usb};
+use anyhow::{Result, anyhow};
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Where bundles are kept on the Pi
+const BUNDLE_DIR: &str = "/home/pi/diagnostics";
+/// Directory on the USB drive bundles are copied to
+const EXPORT_DIR: &str = "amaru-diagnostics";
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+const JOURNAL_LINES: &str = "2000";
+const ENV_FILE_PATH: &str = "/home/pi/amaru.env";
+/// Settings of the env file whose values are left out
+const REDACTED_KEYS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD"];
+
+/// Persisted state worth a look. The secrets, the PIN and what was typed on
+/// the keyboard stay on the Pi.
+const STATE_FILES: &[&str] = &[
+    "/home/pi/.amaru_update_state.json",
+    "/home/pi/.amaru_update_history.json",
+    "/home/pi/.amaru_pi_handoff.json",
+    "/home/pi/.amaru_boot_history.json",
+    "/home/pi/.amaru_storage_history.json",
+    "/home/pi/.amaru_peer_latency.json",
+    "/home/pi/.amaru_sync_rates.json",
+    "/home/pi/.amaru_telemetry.json",
+    "/home/pi/.amaru_backup.json",
+    "/home/pi/.amaru_button_timings.json",
+    crash::REPORT_PATH,
+    "/etc/os-release",
+];
+
+/// Commands whose output is saved: file name, program and arguments.
+const COMMANDS: &[(&str, &str, &[&str])] = &[
+    ("uname.txt", "uname", &["-a"]),
+    ("amaru-version.txt", "/home/pi/bin/amaru", &["--version"]),
+    (
+        "systemd.txt",
+        "systemctl",
+        &["status", "--no-pager", "amaru.service", "amaru-pi.service"],
+    ),
+    (
+        "amaru.journal.txt",
+        "journalctl",
+        &["-u", "amaru.service", "-n", JOURNAL_LINES, "--no-pager"],
+    ),
+    (
+        "amaru-pi.journal.txt",
+        "journalctl",
+        &["-u", "amaru-pi.service", "-n", JOURNAL_LINES, "--no-pager"],
+    ),
+    ("df.txt", "df", &["-h"]),
+    ("free.txt", "free", &["-m"]),
+    ("uptime.txt", "uptime", &[]),
+    ("temperature.txt", "vcgencmd", &["measure_temp"]),
+    ("throttled.txt", "vcgencmd", &["get_throttled"]),
+    ("ip.txt", "ip", &["addr"]),
+];
+
+/// Saves the output of a command, or why there is none. Commands such as
+/// `systemctl status` exit with an error while still telling something.
+fn save_output(dir: &Path, name: &str, program: &str, args: &[&str]) -> Result<()> {
+    let content = match Command::new(program).args(args).output() {
+        Ok(output) => format!(
+            "$ {} {}\n{}{}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ),
+        Err(e) => format!("$ {} {}\nFailed to run: {}\n", program, args.join(" "), e),
+    };
+    fs::write(dir.join(name), content)?;
+    Ok(())
+}
+
+/// The env file of the node, values of keys and tokens replaced.
+fn redacted_env(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| match line.split_once('=') {
+            Some((key, _))
+                if REDACTED_KEYS
+                    .iter()
+                    .any(|secret| key.to_uppercase().contains(secret)) =>
+            {
+                format!("{}=<redacted>\n", key)
+            }
+            _ => format!("{}\n", line),
+        })
+        .collect()
+}
+
+/// Copies what exists of `files` into `dir`.
+fn copy_files(dir: &Path, files: &[PathBuf]) {
+    for file in files.iter().filter(|file| file.exists()) {
+        let Some(name) = file.file_name() else {
+            continue;
+        };
+        // Dotfiles would be hidden once extracted
+        let name = name.to_string_lossy();
+        let name = name.trim_start_matches('.');
+        if let Err(e) = fs::copy(file, dir.join(name)) {
+            warn!("Failed to add {} to the bundle: {}", file.display(), e);
+        }
+    }
+}
+
+/// Gathers logs, versions, state files, service statuses and the state of
+/// the system into `amaru-pi-diagnostics-<host>-<time>.tar.gz`, to attach to
+/// bug reports. Returns its path.
+pub fn collect() -> Result<PathBuf> {
+    let stem = format!(
+        "amaru-pi-diagnostics-{}-{}",
+        hostname(),
+        current_timestamp()?
+    );
+    let staging = env::temp_dir().join(&stem);
+    fs::create_dir_all(&staging)?;
+    let bundled = fill(&staging).and_then(|()| {
+        fs::create_dir_all(BUNDLE_DIR)?;
+        let dest = Path::new(BUNDLE_DIR).join(format!("{}.tar.gz", stem));
+        let output = Command::new("tar")
+            .arg("-czf")
+            .arg(&dest)
+            .arg("-C")
+            .arg(env::temp_dir())
+            .arg(&stem)
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "tar failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(dest)
+    });
+    let _ = fs::remove_dir_all(&staging);
+    let dest = bundled?;
+    info!("Saved a support bundle to {}", dest.display());
+    Ok(dest)
+}
+
+fn fill(dir: &Path) -> Result<()> {
+    fs::write(
+        dir.join("versions.txt"),
+        format!("amaru-pi {}\n", env!("CARGO_PKG_VERSION")),
+    )?;
+    for (name, program, args) in COMMANDS {
+        save_output(dir, name, program, args)?;
+    }
+    let state: Vec<PathBuf> = STATE_FILES.iter().map(PathBuf::from).collect();
+    copy_files(dir, &state);
+    if let Ok(content) = fs::read_to_string(ENV_FILE_PATH) {
+        fs::write(dir.join("amaru.env"), redacted_env(&content))?;
+    }
+    if let Err(e) = notifications::export_history(dir) {
+        warn!("Failed to add the event log to the bundle: {}", e);
+    }
+    let logs = dir.join("logs");
+    fs::create_dir_all(&logs)?;
+    copy_files(&logs, &logging::log_files());
+    Ok(())
+}
+
+/// Copies a bundle to the USB drive, returns its name there.
+pub fn copy_to_usb(bundle: &Path) -> Result<String> {
+    let name = bundle
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Unexpected bundle path {}", bundle.display()))?;
+    let drive = usb::open()?;
+    let dir = drive.path().join(EXPORT_DIR);
+    fs::create_dir_all(&dir)?;
+    let dest = dir.join(&name);
+    fs::copy(bundle, &dest)?;
+    File::open(&dest)?.sync_all()?;
+    Ok(name)
+}
+
+/// Reads `AMARU_PI_DIAGNOSTICS_UPLOAD_URL`, a paste service taking the file
+/// as the body of a PUT, such as `https://transfer.sh`. Nothing is uploaded
+/// without it.
+pub fn upload_url() -> Option<String> {
+    env::var("AMARU_PI_DIAGNOSTICS_UPLOAD_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Uploads a bundle, returns the link to share.
+pub async fn upload(url: &str, bundle: &Path) -> Result<String> {
+    let name = bundle
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Unexpected bundle path {}", bundle.display()))?;
+    let body = tokio::fs::read(bundle).await?;
+    let link = reqwest::Client::new()
+        .put(format!("{}/{}", url, name))
+        .timeout(UPLOAD_TIMEOUT)
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    Ok(link.trim().to_string())
+}
+
+/// Collects a bundle and uploads it when a service is set, else copies it to
+/// the USB drive if one is plugged in. Returns where it went.
+pub async fn export() -> Result<String> {
+    let bundle = tokio::task::spawn_blocking(collect).await??;
+    if let Some(url) = upload_url() {
+        return upload(&url, &bundle).await;
+    }
+    match tokio::task::spawn_blocking(move || copy_to_usb(&bundle).map_err(|e| (e, bundle))).await?
+    {
+        Ok(name) => Ok(name),
+        Err((e, bundle)) => {
+            warn!("Support bundle not copied to USB: {}", e);
+            Ok(bundle.display().to_string())
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use crate::integrity;
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Written by `amaru-pi download`, which the updater runs in its own
+/// process, for the UI to show
+const PROGRESS_FILE_PATH: &str = "/tmp/amaru_download_progress.json";
+/// Progress not updated for this long is from a download that was killed
+const PROGRESS_STALE_SECS: u64 = 30;
+const PROGRESS_WRITE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_ATTEMPTS: u32 = 5;
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A connection sending nothing for this long is considered dead
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    /// Name of the file being downloaded
+    pub name: String,
+    pub downloaded: u64,
+    /// Unknown when the server doesn't announce a length
+    pub total: Option<u64>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+impl DownloadProgress {
+    pub fn ratio(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded as f64 / total as f64).min(1.0))
+    }
+}
+
+/// Downloads `url` to `dest`, resuming from what an interrupted attempt left
+/// in `<dest>.part`, even across runs. Retries with exponential backoff and
+/// checks the length announced by the server and the SHA-256 when given
+/// before moving the file in place.
+pub async fn download(
+    url: &str,
+    dest: &Path,
+    sha256: Option<&str>,
+    progress: mpsc::Sender<DownloadProgress>,
+) -> Result<()> {
+    let part = part_path(dest);
+    let name = dest
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let client = reqwest::Client::builder()
+        .read_timeout(READ_TIMEOUT)
+        .build()?;
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let result = match fetch(&client, url, &part, &name, &progress).await {
+            Ok(()) => verify(&part, sha256),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => {
+                fs::rename(&part, dest)?;
+                return Ok(());
+            }
+            Err(e) if attempt >= MAX_ATTEMPTS => return Err(e),
+            Err(e) => {
+                let backoff = Duration::from_secs(2u64.pow(attempt)).min(MAX_BACKOFF);
+                warn!(
+                    "Download attempt {} failed, retrying in {}s: {}",
+                    attempt,
+                    backoff.as_secs(),
+                    e
+                );
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+}
+
+fn part_path(dest: &Path) -> PathBuf {
+    let mut path = OsString::from(dest.as_os_str());
+    path.push(".part");
+    PathBuf::from(path)
+}
+
+/// Total length from e.g. `bytes 1000-1999/2000` or `bytes */2000`
+fn content_range_total(response: &Response) -> Option<u64> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    name: &str,
+    progress: &mpsc::Sender<DownloadProgress>,
+) -> Result<()> {
+    let mut downloaded = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    let mut request = client.get(url);
+    if downloaded > 0 {
+        request = request.header(RANGE, format!("bytes={}-", downloaded));
+    }
+    let mut response = request.send().await?;
+
+    let total = match response.status() {
+        StatusCode::PARTIAL_CONTENT => content_range_total(&response)
+            .or_else(|| response.content_length().map(|length| length + downloaded)),
+        // The previous attempt got everything, or the part is from another file
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            if content_range_total(&response) == Some(downloaded) {
+                return Ok(());
+            }
+            fs::remove_file(part)?;
+            return Err(anyhow!("Partial download doesn't match, starting over"));
+        }
+        // Servers without range support send everything again
+        status if status.is_success() => {
+            downloaded = 0;
+            response.content_length()
+        }
+        status => return Err(anyhow!("Unexpected status {}", status)),
+    };
+
+    let mut file = OpenOptions::new().create(true).append(true).open(part)?;
+    if downloaded == 0 {
+        file.set_len(0)?;
+    }
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+        // Dropping updates is fine when the reader lags behind
+        let _ = progress.try_send(DownloadProgress {
+            name: name.to_string(),
+            downloaded,
+            total,
+            updated_at: current_timestamp().unwrap_or(0),
+        });
+    }
+    file.sync_all()?;
+
+    match total {
+        Some(total) if downloaded != total => Err(anyhow!(
+            "Incomplete download, got {} of {} bytes",
+            downloaded,
+            total
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A corrupt file is removed so that the next attempt starts over.
+fn verify(part: &Path, sha256: Option<&str>) -> Result<()> {
+    let Some(expected) = sha256 else {
+        return Ok(());
+    };
+    let actual = integrity::sha256_file(part)?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        fs::remove_file(part)?;
+        return Err(anyhow!(
+            "Checksum mismatch, expected {} got {}",
+            expected,
+            actual
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the progress received to the progress file, at most once a second,
+/// until the download is over.
+pub async fn report_progress(mut rx: mpsc::Receiver<DownloadProgress>) {
+    let mut last_write: Option<Instant> = None;
+    while let Some(progress) = rx.recv().await {
+        if last_write.is_some_and(|last| last.elapsed() < PROGRESS_WRITE_INTERVAL) {
+            continue;
+        }
+        last_write = Some(Instant::now());
+        if let Err(e) = write_progress(&progress) {
+            warn!("Failed to write download progress: {}", e);
+        }
+    }
+    if let Err(e) = fs::remove_file(PROGRESS_FILE_PATH)
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        warn!("Failed to remove download progress: {}", e);
+    }
+}
+
+fn write_progress(progress: &DownloadProgress) -> Result<()> {
+    // Write then rename so the UI never reads a half written file
+    let tmp_path = format!("{}.tmp", PROGRESS_FILE_PATH);
+    fs::write(&tmp_path, serde_json::to_string(progress)?)?;
+    fs::rename(&tmp_path, PROGRESS_FILE_PATH)?;
+    Ok(())
+}
+
+/// The download in progress, if any.
+pub fn read_progress() -> Option<DownloadProgress> {
+    let data = fs::read_to_string(PROGRESS_FILE_PATH).ok()?;
+    let progress: DownloadProgress = serde_json::from_str(&data).ok()?;
+    let now = current_timestamp().ok()?;
+    (now.saturating_sub(progress.updated_at) < PROGRESS_STALE_SECS).then_some(progress)
+}
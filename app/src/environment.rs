@@ -0,0 +1,220 @@
+use anyhow::{Result, anyhow};
+use std::env;
+use tracing::warn;
+
+#[cfg(feature = "gpio")]
+const HTS221_ADDRESS: u16 = 0x5f;
+#[cfg(feature = "gpio")]
+const LPS25H_ADDRESS: u16 = 0x5c;
+/// Set on the register of the ST sensors to read several in a row
+#[cfg(feature = "gpio")]
+const AUTO_INCREMENT: u8 = 0x80;
+#[cfg(feature = "gpio")]
+const BME280_CHIP_ID: u8 = 0x60;
+const DEFAULT_BME280_ADDRESS: u16 = 0x76;
+
+/// The sensor telling the temperature around the Pi, e.g. in its enclosure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvironmentSensor {
+    /// Its HTS221 for the temperature and humidity, its LPS25H for the
+    /// pressure
+    SenseHat,
+    Bme280(u16),
+}
+
+impl EnvironmentSensor {
+    /// Reads `AMARU_PI_ENV_SENSOR`, `sense_hat`, `bme280` or
+    /// `bme280:<address>`, e.g. `bme280:0x77`. `None` without a sensor.
+    pub fn from_env() -> Option<Self> {
+        let var = env::var("AMARU_PI_ENV_SENSOR").ok()?;
+        let sensor = Self::parse(&var);
+        if sensor.is_none() {
+            warn!("Ignoring invalid AMARU_PI_ENV_SENSOR {}", var);
+        }
+        sensor
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().split_once(':') {
+            None if s.trim() == "sense_hat" => Some(EnvironmentSensor::SenseHat),
+            None if s.trim() == "bme280" => Some(EnvironmentSensor::Bme280(DEFAULT_BME280_ADDRESS)),
+            Some(("bme280", address)) => u16::from_str_radix(address.trim_start_matches("0x"), 16)
+                .ok()
+                .map(EnvironmentSensor::Bme280),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct EnvironmentReading {
+    pub celsius: f32,
+    /// Relative, in percent
+    pub humidity: Option<f32>,
+    pub hpa: Option<f32>,
+}
+
+impl EnvironmentReading {
+    /// At or above `AMARU_PI_ENCLOSURE_WARN_CELSIUS`, never without it.
+    pub fn is_hot(&self) -> bool {
+        env::var("AMARU_PI_ENCLOSURE_WARN_CELSIUS")
+            .ok()
+            .and_then(|s| s.trim().parse::<f32>().ok())
+            .is_some_and(|warn_celsius| self.celsius >= warn_celsius)
+    }
+}
+
+#[cfg(feature = "gpio")]
+fn read_registers(i2c: &mut rppal::i2c::I2c, register: u8, buffer: &mut [u8]) -> Result<()> {
+    i2c.write_read(&[register], buffer)?;
+    Ok(())
+}
+
+#[cfg(feature = "gpio")]
+fn le_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+#[cfg(feature = "gpio")]
+fn le_i16(bytes: &[u8], at: usize) -> i16 {
+    i16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+/// The temperature and humidity, interpolated between the two points the
+/// HTS221 was calibrated at.
+#[cfg(feature = "gpio")]
+fn read_hts221(i2c: &mut rppal::i2c::I2c) -> Result<(f32, f32)> {
+    i2c.set_slave_address(HTS221_ADDRESS)?;
+    // Powered up, 1 Hz, registers updated once both bytes are read
+    i2c.write(&[0x20, 0x85])?;
+    let mut calibration = [0; 16];
+    read_registers(i2c, 0x30 | AUTO_INCREMENT, &mut calibration)?;
+    let mut output = [0; 4];
+    read_registers(i2c, 0x28 | AUTO_INCREMENT, &mut output)?;
+
+    let word = |bytes: &[u8], at| f32::from(le_i16(bytes, at));
+    let (h0, h1) = (
+        f32::from(calibration[0]) / 2.0,
+        f32::from(calibration[1]) / 2.0,
+    );
+    let msb = calibration[5];
+    let t0 = f32::from(u16::from(msb & 0x3) << 8 | u16::from(calibration[2])) / 8.0;
+    let t1 = f32::from(u16::from(msb >> 2 & 0x3) << 8 | u16::from(calibration[3])) / 8.0;
+    let (h0_out, h1_out) = (word(&calibration, 6), word(&calibration, 10));
+    let (t0_out, t1_out) = (word(&calibration, 12), word(&calibration, 14));
+
+    let humidity = h0 + (word(&output, 0) - h0_out) * (h1 - h0) / (h1_out - h0_out);
+    let celsius = t0 + (word(&output, 2) - t0_out) * (t1 - t0) / (t1_out - t0_out);
+    if !humidity.is_finite() || !celsius.is_finite() {
+        return Err(anyhow!("The HTS221 isn't calibrated"));
+    }
+    Ok((celsius, humidity.clamp(0.0, 100.0)))
+}
+
+#[cfg(feature = "gpio")]
+fn read_lps25h(i2c: &mut rppal::i2c::I2c) -> Result<f32> {
+    i2c.set_slave_address(LPS25H_ADDRESS)?;
+    // Powered up, 1 Hz
+    i2c.write(&[0x20, 0x90])?;
+    let mut output = [0; 3];
+    read_registers(i2c, 0x28 | AUTO_INCREMENT, &mut output)?;
+    let raw = u32::from(output[0]) | u32::from(output[1]) << 8 | u32::from(output[2]) << 16;
+    Ok(raw as f32 / 4096.0)
+}
+
+/// Takes a single measurement, compensated as in the datasheet.
+#[cfg(feature = "gpio")]
+fn read_bme280(i2c: &mut rppal::i2c::I2c, address: u16) -> Result<EnvironmentReading> {
+    i2c.set_slave_address(address)?;
+    let mut id = [0];
+    read_registers(i2c, 0xd0, &mut id)?;
+    if id[0] != BME280_CHIP_ID {
+        return Err(anyhow!("No BME280 at {:#x}, chip {:#x}", address, id[0]));
+    }
+    // Humidity, temperature and pressure oversampled once, in forced mode
+    i2c.write(&[0xf2, 0x01])?;
+    i2c.write(&[0xf4, 0x25])?;
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    let mut tp = [0; 26];
+    read_registers(i2c, 0x88, &mut tp)?;
+    let mut h = [0; 7];
+    read_registers(i2c, 0xe1, &mut h)?;
+    let mut raw = [0; 8];
+    read_registers(i2c, 0xf7, &mut raw)?;
+
+    let adc = |at: usize| {
+        f64::from(
+            u32::from(raw[at]) << 12 | u32::from(raw[at + 1]) << 4 | u32::from(raw[at + 2]) >> 4,
+        )
+    };
+    let (adc_p, adc_t) = (adc(0), adc(3));
+    let adc_h = f64::from(u16::from(raw[6]) << 8 | u16::from(raw[7]));
+
+    let t1 = f64::from(le_u16(&tp, 0));
+    let (t2, t3) = (f64::from(le_i16(&tp, 2)), f64::from(le_i16(&tp, 4)));
+    let var1 = (adc_t / 16384.0 - t1 / 1024.0) * t2;
+    let var2 = (adc_t / 131072.0 - t1 / 8192.0).powi(2) * t3;
+    let t_fine = var1 + var2;
+    let celsius = t_fine / 5120.0;
+
+    let p1 = f64::from(le_u16(&tp, 6));
+    let p: Vec<f64> = (8..24)
+        .step_by(2)
+        .map(|at| f64::from(le_i16(&tp, at)))
+        .collect();
+    let (p2, p3, p4, p5, p6, p7, p8, p9) = (p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7]);
+    let mut var1 = t_fine / 2.0 - 64000.0;
+    let mut var2 = var1 * var1 * p6 / 32768.0;
+    var2 += var1 * p5 * 2.0;
+    var2 = var2 / 4.0 + p4 * 65536.0;
+    var1 = (p3 * var1 * var1 / 524288.0 + p2 * var1) / 524288.0;
+    var1 = (1.0 + var1 / 32768.0) * p1;
+    let hpa = (var1 != 0.0).then(|| {
+        let pa = (1048576.0 - adc_p - var2 / 4096.0) * 6250.0 / var1;
+        let (var1, var2) = (p9 * pa * pa / 2147483648.0, pa * p8 / 32768.0);
+        (pa + (var1 + var2 + p7) / 16.0) / 100.0
+    });
+
+    let h1 = f64::from(tp[25]);
+    let (h2, h3) = (f64::from(le_i16(&h, 0)), f64::from(h[2]));
+    let h4 = f64::from(i16::from(h[3] as i8) << 4 | i16::from(h[4] & 0xf));
+    let h5 = f64::from(i16::from(h[5] as i8) << 4 | i16::from(h[4] >> 4));
+    let h6 = f64::from(h[6] as i8);
+    let mut humidity = t_fine - 76800.0;
+    humidity = (adc_h - (h4 * 64.0 + h5 / 16384.0 * humidity))
+        * (h2 / 65536.0 * (1.0 + h6 / 67108864.0 * humidity * (1.0 + h3 / 67108864.0 * humidity)));
+    humidity *= 1.0 - h1 * humidity / 524288.0;
+
+    Ok(EnvironmentReading {
+        celsius: celsius as f32,
+        humidity: Some(humidity.clamp(0.0, 100.0) as f32),
+        hpa: hpa.map(|hpa| hpa as f32),
+    })
+}
+
+#[cfg(feature = "gpio")]
+pub fn read(sensor: EnvironmentSensor) -> Result<EnvironmentReading> {
+    let mut i2c = rppal::i2c::I2c::new()?;
+    match sensor {
+        EnvironmentSensor::SenseHat => {
+            let (celsius, humidity) = read_hts221(&mut i2c)?;
+            let hpa = read_lps25h(&mut i2c)
+                .inspect_err(|e| warn!("Failed to read the pressure: {}", e))
+                .ok();
+            Ok(EnvironmentReading {
+                celsius,
+                humidity: Some(humidity),
+                hpa,
+            })
+        }
+        EnvironmentSensor::Bme280(address) => read_bme280(&mut i2c, address),
+    }
+}
+
+#[cfg(not(feature = "gpio"))]
+pub fn read(_sensor: EnvironmentSensor) -> Result<EnvironmentReading> {
+    Err(anyhow!(
+        "Environmental sensors can only be read on the device"
+    ))
+}
@@ -0,0 +1,142 @@
+use crate::chain::Network;
+use crate::logs::{JournalReader, extract_new_tip, extract_tip_changed};
+use crate::notifications::{Notification, Severity};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// What happened during a completed epoch, as observed by this device.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EpochSummary {
+    pub epoch: u64,
+    pub first_slot: u64,
+    pub last_slot: u64,
+    /// Number of tip updates seen during the epoch
+    pub tips_observed: u64,
+    /// Number of tip updates seen while the node was caught up
+    pub synced_tips: u64,
+    /// Not reported by the node yet
+    pub blocks_made: Option<u64>,
+    /// Not reported by the node yet
+    pub rewards_lovelace: Option<u64>,
+}
+
+impl EpochSummary {
+    fn new(epoch: u64, slot: u64) -> Self {
+        Self {
+            epoch,
+            first_slot: slot,
+            last_slot: slot,
+            ..Default::default()
+        }
+    }
+
+    fn observe(&mut self, slot: u64, synced: bool) {
+        self.last_slot = self.last_slot.max(slot);
+        self.tips_observed += 1;
+        if synced {
+            self.synced_tips += 1;
+        }
+    }
+
+    /// Share of tip updates received while caught up, in percent.
+    pub fn sync_health(&self) -> u64 {
+        (self.synced_tips * 100)
+            .checked_div(self.tips_observed)
+            .unwrap_or(0)
+    }
+}
+
+impl From<&EpochSummary> for Notification {
+    fn from(summary: &EpochSummary) -> Self {
+        let or_na = |v: Option<u64>| v.map_or_else(|| "n/a".to_string(), |v| v.to_string());
+        let severity = if summary.sync_health() >= 90 {
+            Severity::Info
+        } else {
+            Severity::Warning
+        };
+        Notification::new(
+            format!("Epoch {} complete", summary.epoch),
+            vec![
+                format!("Slots {} - {}", summary.first_slot, summary.last_slot),
+                format!(
+                    "Tips: {} ({}% synced)",
+                    summary.tips_observed,
+                    summary.sync_health()
+                ),
+                format!("Blocks made: {}", or_na(summary.blocks_made)),
+                format!("Rewards: {}", or_na(summary.rewards_lovelace)),
+            ],
+            severity,
+        )
+    }
+}
+
+/// Follows the node tip and detects epoch boundaries.
+pub struct EpochTracker {
+    network: Network,
+    reader: JournalReader,
+    current: Option<EpochSummary>,
+    last_check: Instant,
+    interval: Duration,
+}
+
+impl EpochTracker {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            network: Network::from_env(),
+            reader: JournalReader::new("amaru.service"),
+            current: None,
+            last_check: Instant::now(),
+            interval,
+        }
+    }
+
+    /// Reads the latest tips and returns the summary of the previous epoch
+    /// if a boundary has been crossed.
+    ///
+    /// Boundaries are only reported while the node is caught up, so that an
+    /// initial sync doesn't generate one summary per replayed epoch.
+    pub fn check_for_rollover(&mut self) -> Option<EpochSummary> {
+        if self.last_check.elapsed() < self.interval {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        let lines = self.reader.next_lines().unwrap_or_default();
+        let mut completed = None;
+        for line in lines {
+            let tip = extract_new_tip(&line)
+                .map(|slot| (slot, true))
+                .or_else(|| extract_tip_changed(&line).map(|slot| (slot, false)));
+            let Some((slot, synced)) = tip else {
+                continue;
+            };
+            if let Some(summary) = self.observe(slot, synced)
+                && synced
+            {
+                completed = Some(summary);
+            }
+        }
+        completed
+    }
+
+    fn observe(&mut self, slot: u64, synced: bool) -> Option<EpochSummary> {
+        let epoch = self.network.epoch_for_slot(slot);
+        match &mut self.current {
+            Some(current) if current.epoch == epoch => {
+                current.observe(slot, synced);
+                None
+            }
+            Some(current) if current.epoch > epoch => {
+                // Rollback across the boundary, keep tracking the newer epoch
+                None
+            }
+            _ => {
+                debug!("Entering epoch {} at slot {}", epoch, slot);
+                let mut next = EpochSummary::new(epoch, slot);
+                next.observe(slot, synced);
+                self.current.replace(next)
+            }
+        }
+    }
+}
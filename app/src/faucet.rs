@@ -0,0 +1,154 @@
+use crate::chain::{Network, koios_url};
+use crate::secrets;
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::env;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How often the address balance is checked while waiting for funds
+const ARRIVAL_POLL_INTERVAL: Duration = Duration::from_secs(20);
+/// Give up waiting for funds after this long
+const ARRIVAL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum FaucetStatus {
+    #[default]
+    Idle,
+    Requesting,
+    WaitingForFunds {
+        tx_id: Option<String>,
+    },
+    Received {
+        lovelace: u64,
+    },
+    Failed(String),
+}
+
+impl FaucetStatus {
+    pub fn is_in_progress(&self) -> bool {
+        matches!(
+            self,
+            FaucetStatus::Requesting | FaucetStatus::WaitingForFunds { .. }
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FaucetConfig {
+    pub network: Network,
+    /// Address funds are sent to
+    pub address: Option<String>,
+    /// Key lifting the faucet rate limits, if any
+    pub api_key: Option<String>,
+    pub faucet_url: String,
+    /// Base URL of a Koios compatible API, used to track arrival
+    pub api_url: String,
+}
+
+impl FaucetConfig {
+    /// Reads `AMARU_PI_FAUCET_ADDRESS`, `AMARU_PI_FAUCET_API_KEY` and
+    /// `AMARU_PI_FAUCET_URL`. The faucet URL defaults to the public faucet of
+    /// the configured network. The API key is preferably kept in the secrets
+    /// store, under the same name.
+    pub fn from_env() -> Self {
+        let network = Network::from_env();
+        let non_empty = |name: &str| env::var(name).ok().filter(|s| !s.trim().is_empty());
+        let faucet_url = non_empty("AMARU_PI_FAUCET_URL").unwrap_or_else(|| {
+            match network {
+                Network::Preview => "https://faucet.preview.world.dev.cardano.org",
+                _ => "https://faucet.preprod.world.dev.cardano.org",
+            }
+            .to_string()
+        });
+        Self {
+            network,
+            address: non_empty("AMARU_PI_FAUCET_ADDRESS"),
+            api_key: secrets::get_or_env("AMARU_PI_FAUCET_API_KEY"),
+            faucet_url,
+            api_url: koios_url(),
+        }
+    }
+
+    /// The faucet only makes sense on test networks.
+    pub fn is_available(&self) -> bool {
+        self.network != Network::Mainnet && self.address.is_some()
+    }
+
+    fn address(&self) -> Result<&str> {
+        self.address
+            .as_deref()
+            .ok_or_else(|| anyhow!("No faucet address configured"))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FaucetResponse {
+    txid: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KoiosAddressInfo {
+    balance: Option<String>,
+}
+
+/// Asks the faucet to send funds to the configured address. Returns the id
+/// of the transaction when the faucet reports it.
+pub async fn request_funds(config: &FaucetConfig) -> Result<Option<String>> {
+    if config.network == Network::Mainnet {
+        return Err(anyhow!("No faucet on mainnet"));
+    }
+    let url = format!(
+        "{}/send-money/{}",
+        config.faucet_url.trim_end_matches('/'),
+        config.address()?
+    );
+    let mut request = reqwest::Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(30));
+    if let Some(api_key) = &config.api_key {
+        request = request.query(&[("api_key", api_key)]);
+    }
+    let response: FaucetResponse = request.send().await?.error_for_status()?.json().await?;
+    Ok(response.txid)
+}
+
+/// Current balance of the configured address, in lovelace.
+pub async fn fetch_balance(config: &FaucetConfig) -> Result<u64> {
+    let url = format!("{}/address_info", config.api_url.trim_end_matches('/'));
+    let infos: Vec<KoiosAddressInfo> = reqwest::Client::new()
+        .post(url)
+        .timeout(Duration::from_secs(30))
+        .json(&serde_json::json!({ "_addresses": [config.address()?] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    // An address that never received funds is not returned at all
+    Ok(infos
+        .into_iter()
+        .next()
+        .and_then(|info| info.balance)
+        .and_then(|balance| balance.parse().ok())
+        .unwrap_or(0))
+}
+
+/// Polls the address until its balance grows past `balance_before` and
+/// returns the amount received.
+pub async fn wait_for_arrival(config: &FaucetConfig, balance_before: u64) -> Result<u64> {
+    let start = Instant::now();
+    while start.elapsed() < ARRIVAL_TIMEOUT {
+        tokio::time::sleep(ARRIVAL_POLL_INTERVAL).await;
+        match fetch_balance(config).await {
+            Ok(balance) if balance > balance_before => return Ok(balance - balance_before),
+            Ok(_) => {}
+            // Transient API errors shouldn't abort the wait
+            Err(e) => warn!("Failed to check faucet address balance: {}", e),
+        }
+    }
+    Err(anyhow!(
+        "Funds not received after {} minutes",
+        ARRIVAL_TIMEOUT.as_secs() / 60
+    ))
+}
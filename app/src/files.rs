@@ -0,0 +1,197 @@
+use anyhow::{Result, anyhow};
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::info;
+
+/// The node runs from there, its chain and ledger databases included
+const DEFAULT_DATA_DIR: &str = "/home/pi/bin";
+/// `amaru.env` and the state files of amaru-pi
+const DEFAULT_CONFIG_DIR: &str = "/home/pi";
+/// Only the end of a file is read, whatever its size
+const TAIL_BYTES: u64 = 16 * 1024;
+const TAIL_LINES: usize = 30;
+/// Never listed nor read, anyone holding the device could see them
+const PRIVATE: &[&str] = &[".ssh", ".gnupg", ".netrc", ".git-credentials"];
+
+/// A directory the browser can't leave.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Root {
+    pub label: &'static str,
+    pub path: PathBuf,
+}
+
+/// From `AMARU_PI_DATA_DIR` and `AMARU_PI_CONFIG_DIR`.
+pub fn roots() -> Vec<Root> {
+    let dir = |var: &str, default: &str| {
+        env::var(var)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(default))
+    };
+    vec![
+        Root {
+            label: "Data",
+            path: dir("AMARU_PI_DATA_DIR", DEFAULT_DATA_DIR),
+        },
+        Root {
+            label: "Config",
+            path: dir("AMARU_PI_CONFIG_DIR", DEFAULT_CONFIG_DIR),
+        },
+    ]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Seconds since the UNIX epoch
+    pub modified: u64,
+    pub deletable: bool,
+}
+
+/// What the browser shows for a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Listing {
+    /// Directories first, then by name
+    Dir(PathBuf, Vec<Entry>),
+    /// Last lines of a file, `None` if it isn't text
+    File(PathBuf, Option<Vec<String>>),
+}
+
+impl Listing {
+    pub fn path(&self) -> &Path {
+        match self {
+            Listing::Dir(path, _) | Listing::File(path, _) => path,
+        }
+    }
+}
+
+/// Rotated logs, e.g. `startup.log.1`, `startup.log.2.gz` or
+/// `startup.log-20250101`, which are safe to delete to free up space.
+pub fn is_deletable(name: &str) -> bool {
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    let Some((_, suffix)) = name.rsplit_once(".log") else {
+        return false;
+    };
+    let rotation = suffix
+        .strip_prefix('.')
+        .or_else(|| suffix.strip_prefix('-'));
+    rotation.is_some_and(|r| !r.is_empty() && r.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Whether a file or directory holds secrets: `amaru.env` and its backups
+/// (the PIN and API tokens), the state files of amaru-pi (its secrets, device
+/// key and PIN lockout among them) and shell histories.
+fn is_private(name: &str) -> bool {
+    PRIVATE.contains(&name)
+        || name.starts_with("amaru.env")
+        || name.starts_with(".amaru_")
+        || (name.starts_with('.') && name.ends_with("history"))
+}
+
+/// `path` once symlinks are resolved, if it is within one of the roots.
+fn resolve(path: &Path) -> Result<PathBuf> {
+    let resolved = fs::canonicalize(path)?;
+    let private = resolved
+        .components()
+        .any(|c| is_private(&c.as_os_str().to_string_lossy()));
+    if private {
+        return Err(anyhow!("{} is private", path.display()));
+    }
+    let within = roots()
+        .iter()
+        .any(|root| fs::canonicalize(&root.path).is_ok_and(|root| resolved.starts_with(root)));
+    if !within {
+        return Err(anyhow!(
+            "{} is outside of the browsed directories",
+            path.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+fn list(dir: &Path) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // Files may be removed while being listed, by the node compacting
+        // its databases for instance
+        let Ok(metadata) = fs::metadata(entry.path()) else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if is_private(&name) {
+            continue;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        entries.push(Entry {
+            deletable: metadata.is_file()
+                && !entry.file_type().is_ok_and(|t| t.is_symlink())
+                && is_deletable(&name),
+            name,
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            modified,
+        });
+    }
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// Last lines of a text file, `None` for binary files.
+fn tail(path: &Path) -> Result<Option<Vec<String>>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.take(TAIL_BYTES).read_to_end(&mut bytes)?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines: Vec<&str> = text.lines().collect();
+    // The first line is likely cut
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(TAIL_LINES);
+    Ok(Some(lines[skip..].iter().map(|s| s.to_string()).collect()))
+}
+
+/// Lists a directory, or reads the end of a file, within the roots.
+pub fn browse(path: &Path) -> Result<Listing> {
+    let resolved = resolve(path)?;
+    if resolved.is_dir() {
+        Ok(Listing::Dir(path.to_path_buf(), list(&resolved)?))
+    } else {
+        Ok(Listing::File(path.to_path_buf(), tail(&resolved)?))
+    }
+}
+
+/// Deletes a rotated log within the roots.
+pub fn delete(path: &Path) -> Result<()> {
+    let resolved = resolve(path)?;
+    let name = resolved
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // Not a symlink, whose target could be anything
+    let is_file = fs::symlink_metadata(path)?.is_file();
+    if !is_file || !is_deletable(&name) {
+        return Err(anyhow!("{} can't be deleted", name));
+    }
+    fs::remove_file(&resolved)?;
+    info!("Deleted {}", resolved.display());
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use crate::port_check::listen_port;
+use crate::vpn;
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+use anyhow::{Result, anyhow};
+use std::env;
+
+/// Run as root with amaru-pi, so installed where only root can change it
+#[cfg(feature = "display_hat")]
+const HELPER: &str = "/usr/local/sbin/amaru-pi-firewall";
+const SSH_PORT: u16 = 22;
+const TAILSCALE_INTERFACE: &str = "tailscale0";
+
+/// What the Firewall screen offers to apply, once confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirewallPreset {
+    /// Lets other relays connect to the node
+    AllowNodePort,
+    /// The status and remote control of amaru-pi only answer the LAN
+    LanOnlyApi,
+    LockDownSsh,
+}
+
+impl FirewallPreset {
+    pub const ALL: [FirewallPreset; 3] = [
+        FirewallPreset::AllowNodePort,
+        FirewallPreset::LanOnlyApi,
+        FirewallPreset::LockDownSsh,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FirewallPreset::AllowNodePort => "Allow node port",
+            FirewallPreset::LanOnlyApi => "LAN-only API",
+            FirewallPreset::LockDownSsh => "Lock down SSH to LAN",
+        }
+    }
+
+    /// Shown to confirm.
+    pub fn description(&self) -> &'static str {
+        match self {
+            FirewallPreset::AllowNodePort => "Peers on the internet can connect to the node",
+            FirewallPreset::LanOnlyApi => "The status and remote ports only answer the LAN",
+            FirewallPreset::LockDownSsh => "SSH only answers the LAN",
+        }
+    }
+
+    /// The arguments of the helper. The node port stays open when ufw gets
+    /// enabled, and the VPNs keep the LAN-only ports.
+    fn args(&self) -> Result<Vec<String>> {
+        let options = vec![
+            "--node-port".to_string(),
+            listen_port().to_string(),
+            "--vpn".to_string(),
+            TAILSCALE_INTERFACE.to_string(),
+            "--vpn".to_string(),
+            vpn::wireguard_interface(),
+        ];
+        Ok([options, self.command()?].concat())
+    }
+
+    fn command(&self) -> Result<Vec<String>> {
+        match self {
+            FirewallPreset::AllowNodePort => {
+                Ok(vec!["allow-port".to_string(), listen_port().to_string()])
+            }
+            FirewallPreset::LanOnlyApi => {
+                let ports: Vec<String> = ["AMARU_PI_STATUS_PORT", "AMARU_PI_REMOTE_PORT"]
+                    .iter()
+                    .filter_map(|var| env::var(var).ok()?.trim().parse::<u16>().ok())
+                    .map(|port| port.to_string())
+                    .collect();
+                if ports.is_empty() {
+                    return Err(anyhow!("Neither the status nor the remote port is set"));
+                }
+                Ok([vec!["lan-only".to_string()], ports].concat())
+            }
+            FirewallPreset::LockDownSsh => Ok(vec!["lan-only".to_string(), SSH_PORT.to_string()]),
+        }
+    }
+}
+
+/// The firewall as reported by the helper.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FirewallStatus {
+    /// `ufw` or `nftables`, `None` without either
+    pub backend: Option<String>,
+    pub active: bool,
+    /// e.g. `3001/tcp ALLOW IN Anywhere`
+    pub rules: Vec<String>,
+}
+
+/// The firewall as last read.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FirewallState {
+    /// `None` until read, or why it couldn't be
+    pub current: Option<Result<FirewallStatus, String>>,
+    pub applying: bool,
+}
+
+/// `backend ufw`, `active yes` and a `rule ...` line per rule.
+#[cfg(feature = "display_hat")]
+fn parse_status(stdout: &str) -> FirewallStatus {
+    let mut status = FirewallStatus::default();
+    for (key, value) in stdout.lines().filter_map(|line| line.split_once(' ')) {
+        match key {
+            "backend" if value != "none" => status.backend = Some(value.to_string()),
+            "active" => status.active = value == "yes",
+            "rule" => status.rules.push(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    status
+}
+
+#[cfg(feature = "display_hat")]
+pub fn status() -> Result<FirewallStatus> {
+    Ok(parse_status(&run_and_capture(HELPER, ["status"].to_vec())?))
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn status() -> Result<FirewallStatus> {
+    Ok(FirewallStatus::default())
+}
+
+#[cfg(feature = "display_hat")]
+pub fn apply(preset: FirewallPreset) -> Result<()> {
+    let args = preset.args()?;
+    run_and_capture(HELPER, args.iter().map(String::as_str).collect())?;
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn apply(preset: FirewallPreset) -> Result<()> {
+    preset.args().map(|_| ())
+}
@@ -0,0 +1,226 @@
+use crate::chain::Network;
+use crate::screens::SystemState;
+use crate::secrets;
+use crate::status::status_json;
+use crate::web_auth::{self, WebAuth};
+use anyhow::Result;
+use axum::Router;
+use axum::extract::State;
+use axum::middleware;
+use axum::response::Json;
+use axum::routing::get;
+use serde::Deserialize;
+use serde_json::Value;
+use std::env;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+const DEFAULT_REFRESH_SECS: u64 = 30;
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the status served is brought up to date
+const PUBLISH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A remote node, e.g. a headless relay running amaru-pi with
+/// `AMARU_PI_STATUS_PORT` set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FleetMember {
+    pub name: String,
+    /// Of its status, e.g. `http://relay1.local:8090/status`
+    pub url: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct FleetConfig {
+    pub members: Vec<FleetMember>,
+    pub refresh_interval: Duration,
+    /// Sent to the members requiring one, the fleet sharing its token
+    pub token: Option<String>,
+}
+
+impl FleetConfig {
+    /// Reads `AMARU_PI_FLEET`, comma separated `name=url` or bare URLs named
+    /// after their host, `AMARU_PI_FLEET_REFRESH_SECS` and the
+    /// `AMARU_PI_API_TOKEN` secret.
+    pub fn from_env() -> Self {
+        let members = env::var("AMARU_PI_FLEET")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|member| match member.split_once('=') {
+                Some((name, url)) => FleetMember {
+                    name: name.trim().to_string(),
+                    url: url.trim().to_string(),
+                },
+                None => FleetMember {
+                    name: host(member).to_string(),
+                    url: member.to_string(),
+                },
+            })
+            .collect();
+        let refresh_interval = env::var("AMARU_PI_FLEET_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_SECS));
+        Self {
+            members,
+            refresh_interval,
+            token: secrets::get_or_env("AMARU_PI_API_TOKEN").filter(|t| !t.trim().is_empty()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.members.is_empty()
+    }
+}
+
+/// `relay1.local` of `http://relay1.local:8090/status`.
+fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or(rest);
+    authority.split(':').next().unwrap_or(authority)
+}
+
+/// What the dashboard shows of the status of a node, as served by
+/// `StatusServer`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct RemoteStatus {
+    /// State of its service, e.g. `active`
+    pub node: Option<String>,
+    /// In percent
+    pub sync: Option<f64>,
+    pub tip: Option<u64>,
+}
+
+/// A node of the fleet, as last checked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FleetNode {
+    pub name: String,
+    /// Last fetched, kept while it can't be reached
+    pub status: Option<RemoteStatus>,
+    pub updated: Option<Instant>,
+    /// Of the last check, if it failed
+    pub error: Option<String>,
+}
+
+impl FleetNode {
+    /// Until checked.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: None,
+            updated: None,
+            error: None,
+        }
+    }
+}
+
+/// Fetches the status of every node at once, in the configured order.
+pub async fn fetch_fleet(config: &FleetConfig) -> Vec<(String, Result<RemoteStatus>)> {
+    let fetches: Vec<_> = config
+        .members
+        .iter()
+        .map(|member| tokio::spawn(fetch_status(member.url.clone(), config.token.clone())))
+        .collect();
+    let mut results = Vec::with_capacity(fetches.len());
+    for (member, fetch) in config.members.iter().zip(fetches) {
+        let result = fetch.await.map_err(anyhow::Error::from).and_then(|r| r);
+        results.push((member.name.clone(), result));
+    }
+    results
+}
+
+async fn fetch_status(url: String, token: Option<String>) -> Result<RemoteStatus> {
+    let mut request = reqwest::Client::new().get(url).timeout(TIMEOUT);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    Ok(request.send().await?.error_for_status()?.json().await?)
+}
+
+/// Records the results of a check, keeping the last status of the nodes that
+/// couldn't be reached.
+pub fn merge(nodes: &mut Vec<FleetNode>, results: Vec<(String, Result<RemoteStatus>)>) {
+    let mut previous = std::mem::take(nodes);
+    for (name, result) in results {
+        let mut node = match previous.iter().position(|node| node.name == name) {
+            Some(i) => previous.swap_remove(i),
+            None => FleetNode::new(&name),
+        };
+        match result {
+            Ok(status) => {
+                node.status = Some(status);
+                node.updated = Some(Instant::now());
+                node.error = None;
+            }
+            Err(e) => {
+                warn!("Failed to check {}: {}", node.name, e);
+                node.error = Some(e.to_string());
+            }
+        }
+        nodes.push(node);
+    }
+}
+
+/// Serves the status of this node at `/status`, the JSON pushed to the
+/// webhook, for the dashboard of another Pi.
+pub struct StatusServer {
+    status: watch::Sender<Value>,
+    network: Network,
+    last_published: Option<Instant>,
+}
+
+impl StatusServer {
+    /// Listens on `AMARU_PI_STATUS_PORT`, when set, for the clients
+    /// `WebAuth` allows.
+    pub fn from_env() -> Option<Self> {
+        let port: u16 = env::var("AMARU_PI_STATUS_PORT").ok()?.trim().parse().ok()?;
+        let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+        let listener = std::net::TcpListener::bind(address)
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                tokio::net::TcpListener::from_std(listener)
+            })
+            .inspect_err(|e| warn!("Failed to listen on {}: {}", address, e))
+            .ok()?;
+        let (tx, rx) = watch::channel(Value::Null);
+        let router = Router::new()
+            .route(
+                "/status",
+                get(|State(status): State<watch::Receiver<Value>>| async move {
+                    Json(status.borrow().clone())
+                }),
+            )
+            .with_state(rx)
+            .layer(middleware::from_fn_with_state(
+                WebAuth::from_env(),
+                web_auth::require,
+            ));
+        tokio::spawn(async move {
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(e) = axum::serve(listener, service).await {
+                warn!("The status server stopped: {}", e);
+            }
+        });
+        info!("Serving the status on {}", address);
+        Some(Self {
+            status: tx,
+            network: Network::from_env(),
+            last_published: None,
+        })
+    }
+
+    pub fn publish(&mut self, system: &SystemState) {
+        if self
+            .last_published
+            .is_some_and(|last| last.elapsed() < PUBLISH_INTERVAL)
+        {
+            return;
+        }
+        self.last_published = Some(Instant::now());
+        self.status.send_replace(status_json(system, &self.network));
+    }
+}
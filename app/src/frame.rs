@@ -1,3 +1,4 @@
+use crate::density::Density;
 use std::time::{Duration, Instant};
 
 pub struct FrameState {
@@ -6,6 +7,8 @@ pub struct FrameState {
     pub last_loop: Instant,
     pub elapsed_since_startup: Duration,
     pub elapsed_since_last_frame: Duration,
+    /// Set once the size of the display is known
+    pub density: Density,
 }
 
 impl Default for FrameState {
@@ -17,6 +20,7 @@ impl Default for FrameState {
             last_loop: now,
             elapsed_since_startup: Duration::ZERO,
             elapsed_since_last_frame: Duration::ZERO,
+            density: Density::default(),
         }
     }
 }
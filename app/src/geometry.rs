@@ -0,0 +1,207 @@
+use anyhow::{Result, anyhow};
+use mousefood::embedded_graphics::geometry::Size;
+use mousefood::embedded_graphics::mono_font::MonoFont;
+use mousefood::fonts;
+use ratatui::layout;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Panels laid out for, in landscape
+pub const SUPPORTED_SIZES: [Size; 4] = [
+    Size::new(320, 240),
+    Size::new(240, 240),
+    Size::new(480, 320),
+    Size::new(800, 480),
+];
+/// Set with `amaru-pi conf ui-scale`, over `AMARU_PI_UI_SCALE`
+const UI_SCALE_FILE_PATH: &str = "/home/pi/.amaru_ui_scale";
+
+/// How large text is drawn. Large fits fewer cells, for panels read from
+/// across a room; screens lay themselves out in what is left.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UiScale {
+    Small,
+    #[default]
+    Normal,
+    Large,
+}
+
+impl FromStr for UiScale {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "small" => Ok(UiScale::Small),
+            "normal" => Ok(UiScale::Normal),
+            "large" => Ok(UiScale::Large),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for UiScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            UiScale::Small => "small",
+            UiScale::Normal => "normal",
+            UiScale::Large => "large",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl UiScale {
+    /// The one saved with `amaru-pi conf ui-scale`, else `AMARU_PI_UI_SCALE`,
+    /// `small`, `normal` or `large`.
+    pub fn from_env() -> Self {
+        if let Ok(saved) = fs::read_to_string(UI_SCALE_FILE_PATH)
+            && let Ok(scale) = saved.parse()
+        {
+            return scale;
+        }
+        match env::var("AMARU_PI_UI_SCALE") {
+            Ok(value) => value.parse().unwrap_or_else(|_| {
+                warn!("Invalid AMARU_PI_UI_SCALE: {}", value);
+                UiScale::default()
+            }),
+            Err(_) => UiScale::default(),
+        }
+    }
+
+    /// Saves `self`, used by the UI once restarted.
+    pub fn save(&self) -> Result<()> {
+        fs::write(UI_SCALE_FILE_PATH, self.to_string())
+            .map_err(|e| anyhow!("Failed to save the UI scale: {}", e))
+    }
+
+    /// Normal is the default font of mousefood.
+    pub fn font(&self) -> MonoFont<'static> {
+        match self {
+            UiScale::Small => fonts::MONO_5X8,
+            UiScale::Normal => fonts::MONO_6X10,
+            UiScale::Large => fonts::MONO_10X20,
+        }
+    }
+
+    /// Of a character of [`Self::font`].
+    pub fn cell_size(&self) -> Size {
+        match self {
+            UiScale::Small => Size::new(5, 8),
+            UiScale::Normal => Size::new(6, 10),
+            UiScale::Large => Size::new(10, 20),
+        }
+    }
+}
+
+/// Clockwise, from the landscape orientation of the panel.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl FromStr for Rotation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "0" => Ok(Rotation::Deg0),
+            "90" => Ok(Rotation::Deg90),
+            "180" => Ok(Rotation::Deg180),
+            "270" => Ok(Rotation::Deg270),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Rotation {
+    /// Adds another rotation to this one.
+    pub fn then(self, other: Rotation) -> Rotation {
+        let quarters = (self as u8 + other as u8) % 4;
+        [
+            Rotation::Deg0,
+            Rotation::Deg90,
+            Rotation::Deg180,
+            Rotation::Deg270,
+        ][quarters as usize]
+    }
+
+    pub fn is_portrait(self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+}
+
+/// The size of the panel and how it is mounted, configured at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    /// In landscape
+    pub panel: Size,
+    pub rotation: Rotation,
+    pub scale: UiScale,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            panel: SUPPORTED_SIZES[0],
+            rotation: Rotation::Deg0,
+            scale: UiScale::Normal,
+        }
+    }
+}
+
+fn parse_size(s: &str) -> Option<Size> {
+    let (width, height) = s.trim().split_once('x')?;
+    let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+    // Landscape whichever way it is written
+    Some(Size::new(u32::max(width, height), u32::min(width, height)))
+}
+
+impl Geometry {
+    /// Reads `AMARU_PI_DISPLAY_SIZE`, one of [`SUPPORTED_SIZES`] such as
+    /// `480x320`, `AMARU_PI_DISPLAY_ROTATION`, `0`, `90`, `180` or `270`,
+    /// and the [`UiScale`].
+    pub fn from_env() -> Self {
+        let mut geometry = Geometry {
+            scale: UiScale::from_env(),
+            ..Geometry::default()
+        };
+        if let Ok(value) = env::var("AMARU_PI_DISPLAY_SIZE") {
+            match parse_size(&value).filter(|size| SUPPORTED_SIZES.contains(size)) {
+                Some(panel) => geometry.panel = panel,
+                None => warn!("Unsupported AMARU_PI_DISPLAY_SIZE: {}", value),
+            }
+        }
+        if let Ok(value) = env::var("AMARU_PI_DISPLAY_ROTATION") {
+            match value.parse() {
+                Ok(rotation) => geometry.rotation = rotation,
+                Err(_) => warn!("Invalid AMARU_PI_DISPLAY_ROTATION: {}", value),
+            }
+        }
+        geometry
+    }
+
+    /// The size screens are drawn at, once rotated.
+    pub fn size(&self) -> Size {
+        if self.rotation.is_portrait() {
+            Size::new(self.panel.height, self.panel.width)
+        } else {
+            self.panel
+        }
+    }
+
+    /// The size screens are laid out in.
+    pub fn cells(&self) -> layout::Size {
+        let (size, cell) = (self.size(), self.scale.cell_size());
+        layout::Size::new(
+            (size.width / cell.width) as u16,
+            (size.height / cell.height) as u16,
+        )
+    }
+}
@@ -0,0 +1,147 @@
+use crate::secrets;
+use crate::util::{current_timestamp, format_local_time};
+use anyhow::{Result, anyhow};
+use reqwest::StatusCode;
+use reqwest::header::{ACCEPT, ETAG, HeaderMap, IF_NONE_MATCH, USER_AGENT};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+const API_URL: &str = "https://api.github.com";
+const TIMEOUT: Duration = Duration::from_secs(15);
+/// Responses by path with their ETag, so that unchanged releases are served
+/// by a `304 Not Modified`, which doesn't count against the rate limit
+const CACHE_DIR: &str = "/home/pi/.amaru_github_cache";
+/// Written after each request, by the updater too, for the Updates screen
+const RATE_LIMIT_PATH: &str = "/tmp/amaru_github_rate_limit.json";
+
+/// The rate limit of the GitHub API, as of the last request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Requests per hour, 60 without a token
+    pub limit: u64,
+    pub remaining: u64,
+    /// UNIX time the limit resets at
+    pub reset: u64,
+    pub authenticated: bool,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap, authenticated: bool) -> Option<Self> {
+        let value = |name: &str| headers.get(name)?.to_str().ok()?.parse::<u64>().ok();
+        Some(Self {
+            limit: value("x-ratelimit-limit")?,
+            remaining: value("x-ratelimit-remaining")?,
+            reset: value("x-ratelimit-reset")?,
+            authenticated,
+        })
+    }
+
+    /// Whether requests are refused until the reset.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0 && current_timestamp().is_ok_and(|now| now < self.reset)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    etag: String,
+    body: String,
+}
+
+/// Reads `AMARU_PI_GITHUB_TOKEN`, from the secrets or the environment. A
+/// token without any scope raises the limit to 5000 requests an hour.
+fn token() -> Option<String> {
+    secrets::get_or_env("AMARU_PI_GITHUB_TOKEN")
+}
+
+pub fn read_rate_limit() -> Option<RateLimit> {
+    let data = fs::read_to_string(RATE_LIMIT_PATH).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn write_rate_limit(rate_limit: &RateLimit) -> Result<()> {
+    fs::write(RATE_LIMIT_PATH, serde_json::to_string(rate_limit)?)?;
+    Ok(())
+}
+
+fn cache_path(path: &str) -> PathBuf {
+    PathBuf::from(CACHE_DIR).join(format!("{}.json", path.replace('/', "_")))
+}
+
+fn read_cache(path: &str) -> Option<CachedResponse> {
+    let data = fs::read_to_string(cache_path(path)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn write_cache(path: &str, cached: &CachedResponse) -> Result<()> {
+    fs::create_dir_all(CACHE_DIR)?;
+    fs::write(cache_path(path), serde_json::to_string(cached)?)?;
+    Ok(())
+}
+
+/// GETs `path` of the API, e.g. `repos/jeluard/amaru-pi/releases/latest`,
+/// with the token when there is one. Answered from the cache when unchanged,
+/// or while the rate limit is exhausted.
+pub async fn get(path: &str) -> Result<String> {
+    let cached = read_cache(path);
+    if let Some(rate_limit) = read_rate_limit()
+        && rate_limit.is_exhausted()
+    {
+        return cached.map(|cached| cached.body).ok_or_else(|| {
+            anyhow!(
+                "GitHub rate limit exhausted until {}",
+                format_local_time(rate_limit.reset).unwrap_or_default()
+            )
+        });
+    }
+
+    let token = token();
+    let mut request = reqwest::Client::new()
+        .get(format!("{}/{}", API_URL, path))
+        .header(USER_AGENT, "amaru-pi")
+        .header(ACCEPT, "application/vnd.github+json")
+        .timeout(TIMEOUT);
+    if let Some(token) = &token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(cached) = &cached {
+        request = request.header(IF_NONE_MATCH, &cached.etag);
+    }
+    let response = request.send().await?;
+    if let Some(rate_limit) = RateLimit::from_headers(response.headers(), token.is_some())
+        && let Err(e) = write_rate_limit(&rate_limit)
+    {
+        warn!("Failed to save the GitHub rate limit: {}", e);
+    }
+
+    if response.status() == StatusCode::NOT_MODIFIED
+        && let Some(cached) = cached
+    {
+        return Ok(cached.body);
+    }
+    let response = response.error_for_status()?;
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+    let body = response.text().await?;
+    if let Some(etag) = etag {
+        let cached = CachedResponse {
+            etag,
+            body: body.clone(),
+        };
+        if let Err(e) = write_cache(path, &cached) {
+            warn!("Failed to cache {}: {}", path, e);
+        }
+    }
+    Ok(body)
+}
+
+/// The latest release of `repo`, as the JSON the API returns.
+pub async fn latest_release(repo: &str) -> Result<String> {
+    get(&format!("repos/{}/releases/latest", repo)).await
+}
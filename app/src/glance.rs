@@ -0,0 +1,138 @@
+use crate::chain::Network;
+use crate::screens::SystemState;
+use crate::sync_eta::sync_percent;
+use mousefood::embedded_graphics::Drawable;
+use mousefood::embedded_graphics::draw_target::DrawTarget;
+use mousefood::embedded_graphics::geometry::{Point, Size};
+use mousefood::embedded_graphics::mono_font::MonoTextStyle;
+use mousefood::embedded_graphics::mono_font::ascii::FONT_6X10;
+use mousefood::embedded_graphics::mono_font::iso_8859_1::FONT_10X20;
+use mousefood::embedded_graphics::pixelcolor::BinaryColor;
+use mousefood::embedded_graphics::primitives::{Circle, Primitive, PrimitiveStyle};
+use mousefood::embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use std::env;
+use std::time::{Duration, Instant};
+
+const DEFAULT_PAGE_INTERVAL: Duration = Duration::from_secs(5);
+const PAGES: [Page; 3] = [Page::Sync, Page::Tip, Page::Temperature];
+const DOT_DIAMETER: u32 = 4;
+const DOT_SPACING: i32 = 8;
+
+/// What a glance shows, one at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Sync,
+    Tip,
+    Temperature,
+}
+
+/// The essentials of the node, for displays too small for the screens:
+/// the sync progress, the tip and the temperature, in turns.
+pub struct Glance {
+    page: usize,
+    since: Instant,
+    interval: Duration,
+    network: Network,
+}
+
+impl Default for Glance {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            since: Instant::now(),
+            interval: DEFAULT_PAGE_INTERVAL,
+            network: Network::from_env(),
+        }
+    }
+}
+
+impl Glance {
+    /// Reads `AMARU_PI_GLANCE_INTERVAL_SECS`, how long each page shows.
+    pub fn from_env() -> Self {
+        let interval = env::var("AMARU_PI_GLANCE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_PAGE_INTERVAL);
+        Self {
+            interval,
+            ..Self::default()
+        }
+    }
+
+    /// Moves to the next page once the current one showed long enough.
+    pub fn tick(&mut self) {
+        if self.since.elapsed() >= self.interval {
+            self.page = (self.page + 1) % PAGES.len();
+            self.since = Instant::now();
+        }
+    }
+
+    /// The label, value and detail of the current page.
+    fn lines(&self, system: &SystemState) -> (&'static str, String, String) {
+        match PAGES[self.page] {
+            Page::Sync => {
+                let percent = system.tip.and_then(|tip| sync_percent(&self.network, tip));
+                let eta = system.sync_eta.map(|eta| eta.label()).unwrap_or_default();
+                let value = percent.map_or_else(|| "-".to_string(), |p| format!("{:.1}%", p));
+                ("Sync", value, eta)
+            }
+            Page::Tip => {
+                let value = system
+                    .tip
+                    .map_or_else(|| "-".to_string(), |(slot, _)| format!("#{}", slot));
+                ("Tip", value, String::new())
+            }
+            Page::Temperature => {
+                let value = system
+                    .thermal
+                    .celsius
+                    .map_or_else(|| "-".to_string(), |c| format!("{:.1}°C", c));
+                let fan = system
+                    .thermal
+                    .fan_duty
+                    .map(|duty| format!("Fan {}%", duty))
+                    .unwrap_or_default();
+                ("Temperature", value, fan)
+            }
+        }
+    }
+
+    /// Draws the current page, the display is expected to be cleared.
+    pub fn draw<D>(&self, target: &mut D, system: &SystemState) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let Size { width, height } = target.bounding_box().size;
+        let center = width as i32 / 2;
+        let centered = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Top)
+            .build();
+        let small = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+        let large = MonoTextStyle::new(&FONT_10X20, BinaryColor::On);
+
+        let (label, value, detail) = self.lines(system);
+        Text::with_text_style(label, Point::new(center, 0), small, centered).draw(target)?;
+        Text::with_text_style(&value, Point::new(center, 16), large, centered).draw(target)?;
+        Text::with_text_style(&detail, Point::new(center, 40), small, centered).draw(target)?;
+
+        // Which page this is
+        let first = center - DOT_SPACING * (PAGES.len() as i32 - 1) / 2;
+        for i in 0..PAGES.len() {
+            let style = if i == self.page {
+                PrimitiveStyle::with_fill(BinaryColor::On)
+            } else {
+                PrimitiveStyle::with_stroke(BinaryColor::On, 1)
+            };
+            let top_left = Point::new(
+                first + DOT_SPACING * i as i32 - DOT_DIAMETER as i32 / 2,
+                height as i32 - DOT_DIAMETER as i32 - 1,
+            );
+            Circle::new(top_left, DOT_DIAMETER)
+                .into_styled(style)
+                .draw(target)?;
+        }
+        Ok(())
+    }
+}
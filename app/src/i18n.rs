@@ -0,0 +1,660 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing::warn;
+
+/// Set from the Language screen, over `AMARU_PI_LANGUAGE`
+const LANGUAGE_FILE_PATH: &str = "/home/pi/.amaru_language";
+/// Until read from the file or the environment
+const UNSET: u8 = u8::MAX;
+
+static CURRENT: AtomicU8 = AtomicU8::new(UNSET);
+
+/// What the screens are shown in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+    Spanish,
+}
+
+impl fmt::Display for Language {
+    /// In the language itself, for it to be found when not understood.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Language::English => "English",
+            Language::French => "Français",
+            Language::Spanish => "Español",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::English, Language::French, Language::Spanish];
+
+    /// ISO 639-1, e.g. `fr`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+            Language::Spanish => "es",
+        }
+    }
+
+    /// Parses a code, or a locale such as `fr_FR.UTF-8`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let code = s.trim().get(..2)?.to_lowercase();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|language| language.code() == code)
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::French => FRENCH,
+            Language::Spanish => SPANISH,
+        }
+    }
+}
+
+/// The language saved from the Language screen, else `AMARU_PI_LANGUAGE`,
+/// English by default.
+pub fn current() -> Language {
+    let index = CURRENT.load(Ordering::Relaxed);
+    if let Some(language) = Language::ALL.get(usize::from(index)) {
+        return *language;
+    }
+    let language = fs::read_to_string(LANGUAGE_FILE_PATH)
+        .ok()
+        .and_then(|code| Language::parse(&code))
+        .or_else(|| {
+            let var = env::var("AMARU_PI_LANGUAGE").ok()?;
+            let language = Language::parse(&var);
+            if language.is_none() {
+                warn!("Unknown AMARU_PI_LANGUAGE {}", var);
+            }
+            language
+        })
+        .unwrap_or_default();
+    remember(language);
+    language
+}
+
+fn remember(language: Language) {
+    let index = Language::ALL
+        .iter()
+        .position(|l| *l == language)
+        .unwrap_or(0);
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+/// Switches the screens to `language` and saves it.
+pub fn set(language: Language) -> Result<()> {
+    remember(language);
+    fs::write(LANGUAGE_FILE_PATH, language.code())
+        .map_err(|e| anyhow!("Failed to save the language: {}", e))
+}
+
+/// `text` in the current language, as is when not translated. The English
+/// text is the key, so that untranslated screens still read well.
+pub fn tr(text: &'static str) -> &'static str {
+    static CATALOGS: OnceLock<Vec<HashMap<&'static str, &'static str>>> = OnceLock::new();
+    let catalogs = CATALOGS.get_or_init(|| {
+        Language::ALL
+            .iter()
+            .map(|language| language.catalog().iter().copied().collect())
+            .collect()
+    });
+    let index = Language::ALL
+        .iter()
+        .position(|language| *language == current())
+        .unwrap_or(0);
+    catalogs[index].get(text).copied().unwrap_or(text)
+}
+
+const FRENCH: &[(&str, &str)] = &[
+    (
+        "A (double): Activate/Toggle",
+        "A (double) : Activer/Basculer",
+    ),
+    ("A (double): Back up now", "A (double) : Sauvegarder"),
+    ("A (double): Set APN", "A (double) : Définir l'APN"),
+    (
+        "A (double): Type | B (double): Backspace",
+        "A (double) : Taper | B (double) : Effacer",
+    ),
+    ("A (double): Update now", "A (double) : Mettre à jour"),
+    ("A A: Time zone", "A A : Fuseau horaire"),
+    (
+        "A system update is available",
+        "Une mise à jour système est disponible",
+    ),
+    ("A/B/X/Y: Move Cursor", "A/B/X/Y : Déplacer le curseur"),
+    ("A/X: Change Field", "A/X : Changer de champ"),
+    ("A: Apply | B: Cancel", "A : Appliquer | B : Annuler"),
+    ("A: Back", "A : Retour"),
+    ("A: Back | Hold A: Copy", "A : Retour | A long : Copier"),
+    (
+        "An update is available for:",
+        "Une mise à jour est disponible pour :",
+    ),
+    ("Binary file", "Fichier binaire"),
+    ("Bootstrapping", "Amorçage"),
+    ("Checking the clock...", "Vérification de l'horloge..."),
+    ("Checking...", "Vérification..."),
+    (
+        "Checks that peers can connect to this node",
+        "Vérifie que les pairs peuvent joindre ce nœud",
+    ),
+    ("Countries", "Pays"),
+    ("Data sent daily:", "Données envoyées chaque jour :"),
+    (
+        "Do you want to restart and apply it?",
+        "Redémarrer pour l'appliquer ?",
+    ),
+    (
+        "Double press A to toggle",
+        "Double appui sur A pour basculer",
+    ),
+    ("Empty", "Vide"),
+    (
+        "Enter Wi-Fi credentials for the Pi to connect.",
+        "Saisissez les identifiants Wi-Fi du Pi.",
+    ),
+    (
+        "Enter the APN of your carrier",
+        "Saisissez l'APN de votre opérateur",
+    ),
+    (
+        "Faucet unavailable on mainnet",
+        "Faucet indisponible sur mainnet",
+    ),
+    ("Hold A: From USB", "Maintenir A : Depuis l'USB"),
+    (
+        "Hold X: Accents | ←/→: Move in text",
+        "Maintenir X : Accents | ←/→ : Se déplacer",
+    ),
+    ("Hold X: Refresh", "Maintenir X : Actualiser"),
+    ("IP address of", "Adresse IP de"),
+    ("Firewall", "Pare-feu"),
+    ("Allow node port", "Ouvrir le port du nœud"),
+    ("LAN-only API", "API limitée au LAN"),
+    ("Lock down SSH to LAN", "SSH limité au LAN"),
+    (
+        "Peers on the internet can connect to the node",
+        "Les pairs d'internet peuvent se connecter au nœud",
+    ),
+    (
+        "The status and remote ports only answer the LAN",
+        "Les ports d'état et de contrôle ne répondent qu'au LAN",
+    ),
+    ("SSH only answers the LAN", "SSH ne répond qu'au LAN"),
+    ("Other rules are kept", "Les autres règles sont gardées"),
+    ("No firewall installed", "Aucun pare-feu installé"),
+    ("active", "actif"),
+    ("inactive", "inactif"),
+    ("No rule", "Aucune règle"),
+    ("Applying...", "Application..."),
+    (
+        "A A: Apply | Hold X: Discard",
+        "A A : Appliquer | Maintenir X : Abandonner",
+    ),
+    ("X: Next | A: Edit", "X : Suivant | A : Modifier"),
+    (
+        "With its prefix, e.g. 192.168.1.50/24",
+        "Avec son préfixe, ex. 192.168.1.50/24",
+    ),
+    (
+        "The router, e.g. 192.168.1.1",
+        "Le routeur, ex. 192.168.1.1",
+    ),
+    (
+        "Comma separated, e.g. 1.1.1.1,9.9.9.9",
+        "Séparés par des virgules, ex. 1.1.1.1,9.9.9.9",
+    ),
+    ("IPv6 only", "IPv6 uniquement"),
+    ("Last minted", "Derniers forgés"),
+    ("Loading...", "Chargement..."),
+    ("Locations unknown", "Emplacements inconnus"),
+    ("NTP disabled", "NTP désactivé"),
+    ("Never updated", "Jamais mis à jour"),
+    ("Next in ", "Prochain dans "),
+    ("No LTE modem detected", "Aucun modem LTE détecté"),
+    ("No block adopted yet", "Aucun bloc adopté"),
+    ("No block minted yet", "Aucun bloc forgé"),
+    ("No crash captured", "Aucun plantage capturé"),
+    ("No event yet", "Aucun événement"),
+    ("No faucet address", "Aucune adresse de faucet"),
+    ("No latency samples yet", "Aucune mesure de latence"),
+    ("No logs", "Aucun journal"),
+    ("No more slots this epoch", "Plus de slots cette époque"),
+    ("No pool configured", "Aucun pool configuré"),
+    ("No saved networks", "Aucun réseau enregistré"),
+    ("No slots scheduled", "Aucun slot prévu"),
+    ("No snapshot found", "Aucun instantané trouvé"),
+    ("No task yet", "Aucune tâche"),
+    ("No watched pools", "Aucun pool suivi"),
+    ("Node config", "Configuration du nœud"),
+    ("Not connected", "Non connecté"),
+    ("Not resolving", "Pas de résolution DNS"),
+    ("Not synchronized", "Non synchronisée"),
+    ("Peers", "Pairs"),
+    ("Plug in a USB drive", "Branchez une clé USB"),
+    (
+        "Plug in a USB drive with a backup",
+        "Branchez une clé USB avec une sauvegarde",
+    ),
+    ("Plug in a USB modem", "Branchez un modem USB"),
+    ("Press A to request funds", "A pour demander des fonds"),
+    ("Hold A to copy the address", "A long pour copier l'adresse"),
+    ("No node in the fleet", "Aucun nœud dans la flotte"),
+    (
+        "Press a button to test",
+        "Appuyez sur un bouton pour tester",
+    ),
+    (
+        "Press any button to dismiss.",
+        "Appuyez sur un bouton pour fermer.",
+    ),
+    ("Replaces the node's ledger", "Remplace le ledger du nœud"),
+    ("Relay reachability", "Accessibilité du relais"),
+    ("Reachability unknown", "Accessibilité inconnue"),
+    ("Requesting funds...", "Demande de fonds..."),
+    ("The node isn't listening", "Le nœud n'écoute pas"),
+    ("Your relay is reachable", "Votre relais est accessible"),
+    (
+        "Your relay isn't reachable",
+        "Votre relais n'est pas accessible",
+    ),
+    ("A: Check", "A : Vérifier"),
+    ("Sampling...", "Mesure..."),
+    (
+        "Scan to sign in, any button to dismiss",
+        "Scannez pour vous connecter, un bouton pour fermer",
+    ),
+    ("Sign in needed", "Connexion requise"),
+    ("Slowest units", "Unités les plus lentes"),
+    ("Still booting", "Démarrage en cours"),
+    ("Synchronized", "Synchronisée"),
+    ("The clock was reset", "L'horloge a été réinitialisée"),
+    (
+        "Blocks can't be validated",
+        "Les blocs ne peuvent être validés",
+    ),
+    ("RTC in sync", "RTC synchronisée"),
+    (
+        "The node stops meanwhile",
+        "Le nœud est arrêté pendant ce temps",
+    ),
+    (
+        "This network needs a sign in, peers can't be reached",
+        "Ce réseau demande une connexion, les pairs sont injoignables",
+    ),
+    ("Time zone", "Fuseau horaire"),
+    ("Unit files", "Fichiers d'unités"),
+    ("Up to date", "À jour"),
+    (
+        "Updates are available for:",
+        "Des mises à jour sont disponibles pour :",
+    ),
+    (
+        "Usage statistics disabled",
+        "Statistiques d'usage désactivées",
+    ),
+    ("Waiting for funds...", "En attente des fonds..."),
+    ("Waiting for the node", "En attente du nœud"),
+    ("WiFi portal", "Portail WiFi"),
+    ("X (hold): Hold, release", "X (maintenu) : Bloquer, libérer"),
+    ("X X: Defaults", "X X : Par défaut"),
+    (
+        "X/Y: Move | Hold: Region",
+        "X/Y : Parcourir | Maintenir : Région",
+    ),
+    ("X: Back | A: Refresh", "X : Retour | A : Actualiser"),
+    (
+        "X: Next | A (double): Restore",
+        "X : Suivant | A (double) : Restaurer",
+    ),
+    (
+        "X: Next | A A: Export to USB",
+        "X : Suivant | A A : Exporter sur USB",
+    ),
+    ("X: Next | A A: Cancel", "X : Suivant | A A : Annuler"),
+    ("X: Next | A A: Note", "X : Suivant | A A : Annoter"),
+    ("X: Next | A: Logs", "X : Suivant | A : Journal"),
+    (
+        "X: Next | A: Longer | Hold A: Shorter",
+        "X : Suivant | A : Plus long | Maintenir A : Plus court",
+    ),
+    ("X: Next | A: Select", "X : Suivant | A : Choisir"),
+    (
+        "X: Next | Hold X: Move up | A A: Switch",
+        "X : Suivant | Maintenir X : Monter | A A : Basculer",
+    ),
+    ("Hold A: VPN up", "Maintenir A : Activer le VPN"),
+    ("Hold A: VPN down", "Maintenir A : Couper le VPN"),
+    ("connected", "connecté"),
+    ("down", "coupé"),
+    (
+        "X: Scroll | A A: Apply | Hold A: USB",
+        "X : Défiler | A A : Appliquer | Maintenir A : USB",
+    ),
+    ("[A] Yes, restart now", "[A] Oui, redémarrer"),
+    (
+        "[B] No, remind me in 48 hours",
+        "[B] Non, rappel dans 48 heures",
+    ),
+    ("[B] Cancel", "[B] Annuler"),
+    ("[Y] Cancel", "[Y] Annuler"),
+    (
+        "[X/Y] Digit  [A] Enter  [Hold A] Done  [B] Delete",
+        "[X/Y] Chiffre  [A] Valider  [A long] Terminer  [B] Effacer",
+    ),
+    (
+        "[X] Next  [A] Toggle  [B] Close",
+        "[X] Suivant  [A] Basculer  [B] Fermer",
+    ),
+    ("Shut down", "Éteindre"),
+    ("Reboot", "Redémarrer"),
+    ("Restart UI", "Redémarrer l'interface"),
+    ("Shut down the Pi?", "Éteindre le Pi ?"),
+    ("Reboot the Pi?", "Redémarrer le Pi ?"),
+    ("Restart amaru-pi?", "Redémarrer amaru-pi ?"),
+    ("The node keeps running", "Le nœud continue de tourner"),
+    ("The node is stopped first", "Le nœud est arrêté d'abord"),
+    ("Restart node", "Redémarrer le nœud"),
+    (
+        "It stops syncing until it is up again",
+        "La synchronisation s'arrête jusqu'au redémarrage",
+    ),
+    ("Restart", "Redémarrer"),
+    ("Apply update", "Appliquer la mise à jour"),
+    (
+        "amaru-pi restarts, and the node if updated",
+        "amaru-pi redémarre, et le nœud s'il est mis à jour",
+    ),
+    ("Apply", "Appliquer"),
+    ("Restore ledger", "Restaurer le ledger"),
+    ("Restore", "Restaurer"),
+    ("Language", "Langue"),
+    ("Power", "Alimentation"),
+    ("X: Next", "X : Suivant"),
+    ("Backup", "Sauvegarde"),
+    ("Support bundle", "Diagnostic"),
+    ("USB update", "Mise à jour USB"),
+    ("Crash export", "Export de plantage"),
+    ("Running", "En cours"),
+    ("Done", "Terminée"),
+    ("Failed", "Échouée"),
+    ("Cancelled", "Annulée"),
+];
+
+const SPANISH: &[(&str, &str)] = &[
+    ("A (double): Activate/Toggle", "A (doble): Activar/Alternar"),
+    ("A (double): Back up now", "A (doble): Respaldar ahora"),
+    ("A (double): Set APN", "A (doble): Configurar APN"),
+    (
+        "A (double): Type | B (double): Backspace",
+        "A (doble): Escribir | B (doble): Borrar",
+    ),
+    ("A (double): Update now", "A (doble): Actualizar ahora"),
+    ("A A: Time zone", "A A: Zona horaria"),
+    (
+        "A system update is available",
+        "Hay una actualización del sistema",
+    ),
+    ("A/B/X/Y: Move Cursor", "A/B/X/Y: Mover el cursor"),
+    ("A/X: Change Field", "A/X: Cambiar de campo"),
+    ("A: Apply | B: Cancel", "A: Aplicar | B: Cancelar"),
+    ("A: Back", "A: Volver"),
+    ("A: Back | Hold A: Copy", "A: Volver | Mantén A: Copiar"),
+    ("An update is available for:", "Hay una actualización para:"),
+    ("Binary file", "Archivo binario"),
+    ("Bootstrapping", "Arrancando"),
+    ("Checking the clock...", "Comprobando el reloj..."),
+    ("Checking...", "Comprobando..."),
+    (
+        "Checks that peers can connect to this node",
+        "Comprueba que los pares pueden conectar con este nodo",
+    ),
+    ("Countries", "Países"),
+    ("Data sent daily:", "Datos enviados a diario:"),
+    (
+        "Do you want to restart and apply it?",
+        "¿Reiniciar para aplicarla?",
+    ),
+    (
+        "Double press A to toggle",
+        "Doble pulsación de A para alternar",
+    ),
+    ("Empty", "Vacío"),
+    (
+        "Enter Wi-Fi credentials for the Pi to connect.",
+        "Introduce las credenciales Wi-Fi del Pi.",
+    ),
+    (
+        "Enter the APN of your carrier",
+        "Introduce el APN de tu operador",
+    ),
+    (
+        "Faucet unavailable on mainnet",
+        "Faucet no disponible en mainnet",
+    ),
+    ("Hold A: From USB", "Mantener A: Desde USB"),
+    (
+        "Hold X: Accents | ←/→: Move in text",
+        "Mantener X: Acentos | ←/→: Moverse",
+    ),
+    ("Hold X: Refresh", "Mantener X: Actualizar"),
+    ("IP address of", "Dirección IP de"),
+    ("Firewall", "Cortafuegos"),
+    ("Allow node port", "Abrir el puerto del nodo"),
+    ("LAN-only API", "API solo en la LAN"),
+    ("Lock down SSH to LAN", "SSH solo en la LAN"),
+    (
+        "Peers on the internet can connect to the node",
+        "Los pares de internet pueden conectarse al nodo",
+    ),
+    (
+        "The status and remote ports only answer the LAN",
+        "Los puertos de estado y control solo responden a la LAN",
+    ),
+    ("SSH only answers the LAN", "SSH solo responde a la LAN"),
+    ("Other rules are kept", "Las demás reglas se mantienen"),
+    ("No firewall installed", "Ningún cortafuegos instalado"),
+    ("active", "activo"),
+    ("inactive", "inactivo"),
+    ("No rule", "Ninguna regla"),
+    ("Applying...", "Aplicando..."),
+    (
+        "A A: Apply | Hold X: Discard",
+        "A A: Aplicar | Mantener X: Descartar",
+    ),
+    ("X: Next | A: Edit", "X: Siguiente | A: Editar"),
+    (
+        "With its prefix, e.g. 192.168.1.50/24",
+        "Con su prefijo, p. ej. 192.168.1.50/24",
+    ),
+    (
+        "The router, e.g. 192.168.1.1",
+        "El router, p. ej. 192.168.1.1",
+    ),
+    (
+        "Comma separated, e.g. 1.1.1.1,9.9.9.9",
+        "Separados por comas, p. ej. 1.1.1.1,9.9.9.9",
+    ),
+    ("IPv6 only", "Solo IPv6"),
+    ("Last minted", "Últimos forjados"),
+    ("Loading...", "Cargando..."),
+    ("Locations unknown", "Ubicaciones desconocidas"),
+    ("NTP disabled", "NTP desactivado"),
+    ("Never updated", "Nunca actualizado"),
+    ("Next in ", "Siguiente en "),
+    ("No LTE modem detected", "Ningún módem LTE detectado"),
+    ("No block adopted yet", "Ningún bloque adoptado"),
+    ("No block minted yet", "Ningún bloque forjado"),
+    ("No crash captured", "Ningún fallo capturado"),
+    ("No event yet", "Ningún evento"),
+    ("No faucet address", "Sin dirección de faucet"),
+    ("No latency samples yet", "Sin medidas de latencia"),
+    ("No logs", "Sin registros"),
+    ("No more slots this epoch", "No quedan slots en esta época"),
+    ("No pool configured", "Ningún pool configurado"),
+    ("No saved networks", "Ninguna red guardada"),
+    ("No slots scheduled", "Ningún slot previsto"),
+    ("No snapshot found", "Ninguna instantánea encontrada"),
+    ("No task yet", "Ninguna tarea"),
+    ("No watched pools", "Ningún pool vigilado"),
+    ("Node config", "Configuración del nodo"),
+    ("Not connected", "Sin conexión"),
+    ("Not resolving", "Sin resolución DNS"),
+    ("Not synchronized", "No sincronizado"),
+    ("Peers", "Pares"),
+    ("Plug in a USB drive", "Conecta una memoria USB"),
+    (
+        "Plug in a USB drive with a backup",
+        "Conecta una memoria USB con una copia",
+    ),
+    ("Plug in a USB modem", "Conecta un módem USB"),
+    ("Press A to request funds", "Pulsa A para pedir fondos"),
+    (
+        "Hold A to copy the address",
+        "Mantén A para copiar la dirección",
+    ),
+    ("No node in the fleet", "Ningún nodo en la flota"),
+    ("Press a button to test", "Pulsa un botón para probar"),
+    (
+        "Press any button to dismiss.",
+        "Pulsa cualquier botón para cerrar.",
+    ),
+    ("Replaces the node's ledger", "Reemplaza el ledger del nodo"),
+    ("Relay reachability", "Accesibilidad del relé"),
+    ("Reachability unknown", "Accesibilidad desconocida"),
+    ("Requesting funds...", "Pidiendo fondos..."),
+    ("The node isn't listening", "El nodo no escucha"),
+    ("Your relay is reachable", "Tu relé es accesible"),
+    ("Your relay isn't reachable", "Tu relé no es accesible"),
+    ("A: Check", "A: Comprobar"),
+    ("Sampling...", "Midiendo..."),
+    (
+        "Scan to sign in, any button to dismiss",
+        "Escanea para iniciar sesión, un botón para cerrar",
+    ),
+    ("Sign in needed", "Inicio de sesión requerido"),
+    ("Slowest units", "Unidades más lentas"),
+    ("Still booting", "Arrancando todavía"),
+    ("Synchronized", "Sincronizado"),
+    ("The clock was reset", "El reloj se reinició"),
+    (
+        "Blocks can't be validated",
+        "Los bloques no se pueden validar",
+    ),
+    ("RTC in sync", "RTC sincronizado"),
+    (
+        "The node stops meanwhile",
+        "El nodo se detiene mientras tanto",
+    ),
+    (
+        "This network needs a sign in, peers can't be reached",
+        "Esta red pide iniciar sesión, los pares no son accesibles",
+    ),
+    ("Time zone", "Zona horaria"),
+    ("Unit files", "Archivos de unidades"),
+    ("Up to date", "Al día"),
+    ("Updates are available for:", "Hay actualizaciones para:"),
+    (
+        "Usage statistics disabled",
+        "Estadísticas de uso desactivadas",
+    ),
+    ("Waiting for funds...", "Esperando los fondos..."),
+    ("Waiting for the node", "Esperando al nodo"),
+    ("WiFi portal", "Portal WiFi"),
+    ("X (hold): Hold, release", "X (mantener): Retener, liberar"),
+    ("X X: Defaults", "X X: Valores por defecto"),
+    ("X/Y: Move | Hold: Region", "X/Y: Mover | Mantener: Región"),
+    ("X: Back | A: Refresh", "X: Volver | A: Actualizar"),
+    (
+        "X: Next | A (double): Restore",
+        "X: Siguiente | A (doble): Restaurar",
+    ),
+    (
+        "X: Next | A A: Export to USB",
+        "X: Siguiente | A A: Exportar a USB",
+    ),
+    ("X: Next | A A: Cancel", "X: Siguiente | A A: Cancelar"),
+    ("X: Next | A A: Note", "X: Siguiente | A A: Anotar"),
+    ("X: Next | A: Logs", "X: Siguiente | A: Registros"),
+    (
+        "X: Next | A: Longer | Hold A: Shorter",
+        "X: Siguiente | A: Más largo | Mantener A: Más corto",
+    ),
+    ("X: Next | A: Select", "X: Siguiente | A: Elegir"),
+    (
+        "X: Next | Hold X: Move up | A A: Switch",
+        "X: Siguiente | Mantener X: Subir | A A: Cambiar",
+    ),
+    ("Hold A: VPN up", "Mantener A: Activar la VPN"),
+    ("Hold A: VPN down", "Mantener A: Cortar la VPN"),
+    ("connected", "conectada"),
+    ("down", "cortada"),
+    (
+        "X: Scroll | A A: Apply | Hold A: USB",
+        "X: Desplazar | A A: Aplicar | Mantener A: USB",
+    ),
+    ("[A] Yes, restart now", "[A] Sí, reiniciar ahora"),
+    (
+        "[B] No, remind me in 48 hours",
+        "[B] No, recordar en 48 horas",
+    ),
+    ("[B] Cancel", "[B] Cancelar"),
+    ("[Y] Cancel", "[Y] Cancelar"),
+    (
+        "[X/Y] Digit  [A] Enter  [Hold A] Done  [B] Delete",
+        "[X/Y] Dígito  [A] Validar  [Mantén A] Listo  [B] Borrar",
+    ),
+    (
+        "[X] Next  [A] Toggle  [B] Close",
+        "[X] Siguiente  [A] Alternar  [B] Cerrar",
+    ),
+    ("Shut down", "Apagar"),
+    ("Reboot", "Reiniciar"),
+    ("Restart UI", "Reiniciar la interfaz"),
+    ("Shut down the Pi?", "¿Apagar el Pi?"),
+    ("Reboot the Pi?", "¿Reiniciar el Pi?"),
+    ("Restart amaru-pi?", "¿Reiniciar amaru-pi?"),
+    ("The node keeps running", "El nodo sigue funcionando"),
+    ("The node is stopped first", "El nodo se detiene primero"),
+    ("Restart node", "Reiniciar el nodo"),
+    (
+        "It stops syncing until it is up again",
+        "Deja de sincronizar hasta que vuelva a arrancar",
+    ),
+    ("Restart", "Reiniciar"),
+    ("Apply update", "Aplicar la actualización"),
+    (
+        "amaru-pi restarts, and the node if updated",
+        "amaru-pi se reinicia, y el nodo si se actualiza",
+    ),
+    ("Apply", "Aplicar"),
+    ("Restore ledger", "Restaurar el ledger"),
+    ("Restore", "Restaurar"),
+    ("Language", "Idioma"),
+    ("Power", "Energía"),
+    ("X: Next", "X: Siguiente"),
+    ("Backup", "Copia de seguridad"),
+    ("Support bundle", "Diagnóstico"),
+    ("USB update", "Actualización USB"),
+    ("Crash export", "Exportación de fallo"),
+    ("Running", "En curso"),
+    ("Done", "Terminada"),
+    ("Failed", "Fallida"),
+    ("Cancelled", "Cancelada"),
+];
@@ -0,0 +1,282 @@
+use crate::chain::Network;
+use crate::screens::SystemState;
+use crate::secrets;
+use crate::status::hostname;
+use crate::sync_eta::sync_percent;
+use crate::systemd::ActiveState;
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use std::collections::VecDeque;
+use std::env;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+const MEASUREMENT: &str = "amaru_pi";
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Lines kept while InfluxDB is unreachable, a few hours at the default
+/// interval
+const MAX_PENDING_LINES: usize = 2000;
+/// Lines written at once
+const BATCH_LINES: usize = 500;
+/// UDP datagrams are kept under the usual MTU
+const MAX_DATAGRAM: usize = 1400;
+
+/// Where the lines go.
+#[derive(Debug, Clone)]
+enum Target {
+    /// The write endpoint, e.g. `http://influx:8086/api/v2/write?org=o&bucket=b`
+    /// or `http://influx:8086/write?db=amaru`, with its token
+    Http { url: String, token: Option<String> },
+    /// A line protocol listener, e.g. `influx:8089`
+    Udp(String),
+}
+
+/// A field of a line, in line protocol.
+enum FieldValue {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+/// Escapes tag values, which can't hold spaces, commas or equal signs.
+fn escape_tag(value: &str) -> String {
+    value
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+/// A line of the measurement at `timestamp`, `None` without any field.
+fn line(host: &str, fields: &[(&str, Option<FieldValue>)], timestamp: u64) -> Option<String> {
+    let fields: Vec<String> = fields
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = match value.as_ref()? {
+                FieldValue::Integer(i) => format!("{}i", i),
+                FieldValue::Float(f) => f.to_string(),
+                FieldValue::Boolean(b) => b.to_string(),
+            };
+            Some(format!("{}={}", key, value))
+        })
+        .collect();
+    if fields.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{},host={} {} {}",
+        MEASUREMENT,
+        escape_tag(host),
+        fields.join(","),
+        timestamp * 1_000_000_000
+    ))
+}
+
+/// Samples the metrics of the node at an interval and exports them to
+/// InfluxDB, so that graphs of the node live off the device. Samples are
+/// kept, in memory, while InfluxDB can't be reached.
+pub struct InfluxExporter {
+    tx: mpsc::Sender<String>,
+    interval: Duration,
+    host: String,
+    network: Network,
+    last_sample: Option<Instant>,
+}
+
+impl InfluxExporter {
+    /// Reads `AMARU_PI_INFLUX_URL`, with `AMARU_PI_INFLUX_TOKEN` from the
+    /// secrets or the environment, or
+    /// `AMARU_PI_INFLUX_UDP`, and `AMARU_PI_INFLUX_INTERVAL_SECS`. `None`
+    /// unless either target is set.
+    pub fn from_env() -> Option<Self> {
+        let target = match (
+            env::var("AMARU_PI_INFLUX_URL"),
+            env::var("AMARU_PI_INFLUX_UDP"),
+        ) {
+            (Ok(url), _) if !url.trim().is_empty() => Target::Http {
+                url: url.trim().to_string(),
+                token: secrets::get_or_env("AMARU_PI_INFLUX_TOKEN"),
+            },
+            (_, Ok(address)) if !address.trim().is_empty() => {
+                Target::Udp(address.trim().to_string())
+            }
+            _ => return None,
+        };
+        let interval = env::var("AMARU_PI_INFLUX_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_INTERVAL);
+        let (tx, rx) = mpsc::channel(MAX_PENDING_LINES);
+        tokio::spawn(export(target, rx));
+        Some(Self {
+            tx,
+            interval,
+            host: hostname(),
+            network: Network::from_env(),
+            last_sample: None,
+        })
+    }
+
+    /// Samples the metrics once the interval elapsed.
+    pub fn sample(&mut self, system: &SystemState) {
+        if self
+            .last_sample
+            .is_some_and(|last| last.elapsed() < self.interval)
+        {
+            return;
+        }
+        self.last_sample = Some(Instant::now());
+        let Ok(now) = current_timestamp() else {
+            return;
+        };
+        let storage = system.storage.latest.as_ref();
+        let fields = [
+            (
+                "node_active",
+                Some(FieldValue::Boolean(matches!(
+                    system.amaru_status.active_state,
+                    ActiveState::Active
+                ))),
+            ),
+            (
+                "tip",
+                system.tip.map(|(slot, _)| FieldValue::Integer(slot as i64)),
+            ),
+            (
+                "sync",
+                system
+                    .tip
+                    .and_then(|tip| sync_percent(&self.network, tip))
+                    .map(FieldValue::Float),
+            ),
+            (
+                "temperature",
+                system
+                    .thermal
+                    .celsius
+                    .map(|c| FieldValue::Float(f64::from(c))),
+            ),
+            (
+                "fan",
+                system
+                    .thermal
+                    .fan_duty
+                    .map(|duty| FieldValue::Integer(i64::from(duty))),
+            ),
+            (
+                "enclosure_temperature",
+                system
+                    .environment
+                    .map(|reading| FieldValue::Float(f64::from(reading.celsius))),
+            ),
+            (
+                "humidity",
+                system
+                    .environment
+                    .and_then(|reading| reading.humidity)
+                    .map(|humidity| FieldValue::Float(f64::from(humidity))),
+            ),
+            (
+                "pressure",
+                system
+                    .environment
+                    .and_then(|reading| reading.hpa)
+                    .map(|hpa| FieldValue::Float(f64::from(hpa))),
+            ),
+            (
+                "chain_bytes",
+                storage.map(|s| FieldValue::Integer(s.chain_bytes as i64)),
+            ),
+            (
+                "ledger_bytes",
+                storage.map(|s| FieldValue::Integer(s.ledger_bytes as i64)),
+            ),
+            (
+                "free_bytes",
+                storage.map(|s| FieldValue::Integer(s.free_bytes as i64)),
+            ),
+            (
+                "battery",
+                system
+                    .power
+                    .as_ref()
+                    .map(|power| FieldValue::Integer(i64::from(power.percent))),
+            ),
+        ];
+        if let Some(line) = line(&self.host, &fields, now)
+            && self.tx.try_send(line).is_err()
+        {
+            warn!("Dropping a metrics sample, InfluxDB is behind");
+        }
+    }
+}
+
+async fn write_http(url: &str, token: Option<&str>, body: String) -> Result<()> {
+    let mut request = reqwest::Client::new().post(url).timeout(TIMEOUT).body(body);
+    if let Some(token) = token {
+        request = request.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+async fn write_udp(address: &str, lines: &[String]) -> Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(address).await?;
+    let mut datagram = String::new();
+    for line in lines {
+        if !datagram.is_empty() && datagram.len() + line.len() + 1 > MAX_DATAGRAM {
+            socket.send(datagram.as_bytes()).await?;
+            datagram.clear();
+        }
+        datagram.push_str(line);
+        datagram.push('\n');
+    }
+    if !datagram.is_empty() {
+        socket.send(datagram.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Writes the lines as they come, and those pending first. Failed writes are
+/// retried with the next sample, the oldest lines dropped past the limit.
+async fn export(target: Target, mut rx: mpsc::Receiver<String>) {
+    let mut pending: VecDeque<String> = VecDeque::new();
+    let mut failing = false;
+    while let Some(line) = rx.recv().await {
+        pending.push_back(line);
+        while let Ok(line) = rx.try_recv() {
+            pending.push_back(line);
+        }
+        let overflow = pending.len().saturating_sub(MAX_PENDING_LINES);
+        pending.drain(..overflow);
+
+        while !pending.is_empty() {
+            let count = pending.len().min(BATCH_LINES);
+            let batch: Vec<String> = pending.iter().take(count).cloned().collect();
+            let result = match &target {
+                Target::Http { url, token } => {
+                    write_http(url, token.as_deref(), batch.join("\n")).await
+                }
+                Target::Udp(address) => write_udp(address, &batch).await,
+            };
+            match result {
+                Ok(()) => {
+                    pending.drain(..count);
+                    failing = false;
+                }
+                Err(e) => {
+                    // Only once until it works again
+                    if !failing {
+                        warn!("Failed to export metrics to InfluxDB: {}", anyhow!(e));
+                    }
+                    failing = true;
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::button::{self, Button, ButtonId, ButtonTimings, InputEvent};
+use crate::watchdog::INPUT_HEARTBEAT;
+use anyhow::{Result, anyhow};
+use rppal::gpio::{Gpio, InputPin};
+use std::{
+    collections::HashMap,
+    env,
+    sync::mpsc::{self},
+    thread,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Pins and timings of buttons wired directly to GPIO.
+#[derive(Debug, Clone)]
+pub struct GpioButtonsConfig {
+    /// BCM pin number of each button
+    pub pins: HashMap<ButtonId, u8>,
+    /// Whether a pressed button pulls the pin low (pull-up wiring)
+    pub active_low: bool,
+    pub timings: ButtonTimings,
+}
+
+impl GpioButtonsConfig {
+    /// Builds the configuration from environment variables, falling back to
+    /// the provided defaults:
+    /// - `AMARU_PI_GPIO_BUTTONS`: pin mapping, e.g. `A=5,B=6,X=16,Y=24`
+    /// - `AMARU_PI_GPIO_ACTIVE_LOW`: `true` (default) or `false`
+    /// - `AMARU_PI_GPIO_DEBOUNCE_MS`, `AMARU_PI_GPIO_LONG_PRESS_MS`,
+    ///   `AMARU_PI_GPIO_DOUBLE_PRESS_MS`, `AMARU_PI_GPIO_REPEAT_MS`: press
+    ///   classification windows, unless calibrated on the buttons screen
+    pub fn from_env(default_pins: &[(ButtonId, u8)]) -> Self {
+        let pins = env::var("AMARU_PI_GPIO_BUTTONS")
+            .ok()
+            .map(|var| parse_pins(&var))
+            .filter(|pins| !pins.is_empty())
+            .unwrap_or_else(|| default_pins.iter().copied().collect());
+        let active_low = env::var("AMARU_PI_GPIO_ACTIVE_LOW")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(true);
+        let defaults = ButtonTimings::default();
+        let timings = ButtonTimings {
+            debounce: duration_from_env("AMARU_PI_GPIO_DEBOUNCE_MS").unwrap_or(defaults.debounce),
+            long_press: duration_from_env("AMARU_PI_GPIO_LONG_PRESS_MS")
+                .unwrap_or(defaults.long_press),
+            double_press: duration_from_env("AMARU_PI_GPIO_DOUBLE_PRESS_MS")
+                .unwrap_or(defaults.double_press),
+            repeat: duration_from_env("AMARU_PI_GPIO_REPEAT_MS").unwrap_or(defaults.repeat),
+        };
+        Self {
+            pins,
+            active_low,
+            timings,
+        }
+    }
+}
+
+fn duration_from_env(name: &str) -> Option<Duration> {
+    env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .map(Duration::from_millis)
+}
+
+/// Parses a `ID=PIN` comma separated list, ignoring invalid entries.
+fn parse_pins(var: &str) -> HashMap<ButtonId, u8> {
+    var.split(',')
+        .filter_map(|entry| {
+            let (id, pin) = entry.split_once('=')?;
+            match (id.parse::<ButtonId>(), pin.trim().parse::<u8>()) {
+                (Ok(id), Ok(pin)) => Some((id, pin)),
+                _ => {
+                    warn!("Ignoring invalid GPIO button mapping: {}", entry);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Claims the configured pins and spawns a dedicated thread polling them.
+/// Classified presses are sent back through the returned channel.
+pub fn spawn_buttons(gpio: &Gpio, config: GpioButtonsConfig) -> Result<mpsc::Receiver<InputEvent>> {
+    if config.pins.is_empty() {
+        return Err(anyhow!("no GPIO button configured"));
+    }
+    info!("Using GPIO buttons {:?}", config.pins);
+
+    let mut pins: HashMap<ButtonId, InputPin> = HashMap::new();
+    for (id, pin) in &config.pins {
+        let pin = gpio.get(*pin)?;
+        let input = if config.active_low {
+            pin.into_input_pullup()
+        } else {
+            pin.into_input_pulldown()
+        };
+        pins.insert(*id, input);
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let timings = button::configure_timings(config.timings);
+    let mut buttons: HashMap<ButtonId, Button> =
+        pins.keys().map(|id| (*id, Button::new(timings))).collect();
+    let active_low = config.active_low;
+
+    thread::spawn(move || {
+        loop {
+            INPUT_HEARTBEAT.beat();
+            let timings = button::timings();
+            for (id, button_state) in &mut buttons {
+                button_state.set_timings(timings);
+                let pin = &pins[id];
+                let is_pressed = if active_low {
+                    pin.is_low()
+                } else {
+                    pin.is_high()
+                };
+
+                if let Some(press_type) = button_state.update(is_pressed) {
+                    let event = InputEvent {
+                        id: *id,
+                        press_type,
+                    };
+                    if tx.send(event).is_err() {
+                        return; // Main thread has disconnected
+                    }
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+
+    Ok(rx)
+}
@@ -0,0 +1,254 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::dev;
+use crate::keyboard::completion;
+use std::{
+    collections::HashMap,
+    env,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// Maximum delay between the two presses of a chord
+const CHORD_WINDOW: Duration = Duration::from_millis(300);
+
+/// App level commands that can be bound to chords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    NextScreen,
+    PreviousScreen,
+    Home,
+    /// Opens the hidden developer menu, in dev mode only
+    DevMenu,
+    /// Highlights the next keyboard suggestion
+    NextSuggestion,
+    /// Types the highlighted keyboard suggestion
+    AcceptSuggestion,
+    /// Saves what the display shows as a PNG
+    Screenshot,
+    /// Types a character in the open keyboard, from the simulator
+    Type(char),
+    /// Deletes the character before the cursor of the open keyboard
+    Erase,
+}
+
+impl Command {
+    /// Whether the command is handled by an open keyboard.
+    pub fn is_for_keyboard(self) -> bool {
+        matches!(
+            self,
+            Command::NextSuggestion | Command::AcceptSuggestion | Command::Type(_) | Command::Erase
+        )
+    }
+}
+
+impl FromStr for Command {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "next" | "next-screen" => Ok(Command::NextScreen),
+            "previous" | "previous-screen" => Ok(Command::PreviousScreen),
+            "home" => Ok(Command::Home),
+            "dev-menu" => Ok(Command::DevMenu),
+            "next-suggestion" => Ok(Command::NextSuggestion),
+            "accept-suggestion" => Ok(Command::AcceptSuggestion),
+            "screenshot" => Ok(Command::Screenshot),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The result of mapping a raw device event.
+#[derive(Debug, Clone, Copy)]
+pub enum MappedInput {
+    Event(InputEvent),
+    Command(Command),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Chord {
+    first: ButtonId,
+    second: ButtonId,
+    command: Command,
+}
+
+impl Chord {
+    fn matches(&self, a: ButtonId, b: ButtonId) -> bool {
+        (self.first == a && self.second == b) || (self.first == b && self.second == a)
+    }
+
+    fn involves(&self, id: ButtonId) -> bool {
+        self.first == id || self.second == id
+    }
+}
+
+/// Sits between the device and the app: remaps physical buttons to logical
+/// ones and turns chords (two buttons pressed together) into commands.
+#[derive(Debug, Default)]
+pub struct InputMapper {
+    remap: HashMap<ButtonId, ButtonId>,
+    chords: Vec<Chord>,
+    pending: Option<(InputEvent, Instant)>,
+    /// Whether a keyboard is shown, keyboard chords only apply then
+    typing: bool,
+}
+
+impl InputMapper {
+    /// Builds the mapper from environment variables:
+    /// - `AMARU_PI_BUTTON_MAP`: physical to logical mapping, e.g. `A=X,X=A`
+    /// - `AMARU_PI_CHORDS`: chords bound to commands, e.g. `A+X=home`
+    ///
+    /// Unless bound to something else, X+Y opens the developer menu and B+Y
+    /// takes a screenshot in dev mode, and A+X / A+B pick and type keyboard
+    /// suggestions when enabled, while a keyboard is shown.
+    pub fn from_env() -> Self {
+        let remap = env::var("AMARU_PI_BUTTON_MAP")
+            .map(|var| parse_remap(&var))
+            .unwrap_or_default();
+        let mut chords = env::var("AMARU_PI_CHORDS")
+            .map(|var| parse_chords(&var))
+            .unwrap_or_default();
+        if dev::is_enabled() {
+            add_default_chord(&mut chords, ButtonId::X, ButtonId::Y, Command::DevMenu);
+            add_default_chord(&mut chords, ButtonId::B, ButtonId::Y, Command::Screenshot);
+        }
+        if completion::is_enabled() {
+            add_default_chord(
+                &mut chords,
+                ButtonId::A,
+                ButtonId::X,
+                Command::NextSuggestion,
+            );
+            add_default_chord(
+                &mut chords,
+                ButtonId::A,
+                ButtonId::B,
+                Command::AcceptSuggestion,
+            );
+        }
+        Self {
+            remap,
+            chords,
+            pending: None,
+            typing: false,
+        }
+    }
+
+    /// Tells whether a keyboard is shown, so A alone is not held back for a
+    /// chord elsewhere.
+    pub fn set_typing(&mut self, typing: bool) {
+        self.typing = typing;
+    }
+
+    /// Maps a raw device event. Chord candidates are held back until either
+    /// their partner arrives or `flush` releases them.
+    pub fn map(&mut self, event: InputEvent) -> Vec<MappedInput> {
+        let mut mapped = Vec::new();
+        let is_chord_candidate = event.press_type == ButtonPress::Short
+            && self.active_chords().any(|c| c.involves(event.id));
+
+        if !is_chord_candidate {
+            mapped.extend(self.take_pending());
+            mapped.push(MappedInput::Event(self.remapped(event)));
+            return mapped;
+        }
+
+        if let Some((pending, at)) = self.pending
+            && at.elapsed() <= CHORD_WINDOW
+            && let Some(command) = self.chord_command(pending.id, event.id)
+        {
+            self.pending = None;
+            mapped.push(MappedInput::Command(command));
+            return mapped;
+        }
+
+        mapped.extend(self.take_pending());
+        self.pending = Some((event, Instant::now()));
+        mapped
+    }
+
+    /// Releases a held event once the chord window has passed. Call this
+    /// every loop.
+    pub fn flush(&mut self) -> Option<MappedInput> {
+        match self.pending {
+            Some((_, at)) if at.elapsed() > CHORD_WINDOW => self.take_pending(),
+            _ => None,
+        }
+    }
+
+    fn active_chords(&self) -> impl Iterator<Item = &Chord> {
+        self.chords
+            .iter()
+            .filter(|c| self.typing || !c.command.is_for_keyboard())
+    }
+
+    fn chord_command(&self, first: ButtonId, second: ButtonId) -> Option<Command> {
+        self.active_chords()
+            .find(|c| c.matches(first, second))
+            .map(|c| c.command)
+    }
+
+    fn take_pending(&mut self) -> Option<MappedInput> {
+        let (event, _) = self.pending.take()?;
+        Some(MappedInput::Event(self.remapped(event)))
+    }
+
+    fn remapped(&self, event: InputEvent) -> InputEvent {
+        InputEvent {
+            id: self.remap.get(&event.id).copied().unwrap_or(event.id),
+            ..event
+        }
+    }
+}
+
+fn add_default_chord(chords: &mut Vec<Chord>, first: ButtonId, second: ButtonId, command: Command) {
+    if !chords.iter().any(|c| c.matches(first, second)) {
+        chords.push(Chord {
+            first,
+            second,
+            command,
+        });
+    }
+}
+
+fn parse_remap(var: &str) -> HashMap<ButtonId, ButtonId> {
+    var.split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let (from, to) = entry.split_once('=')?;
+            match (from.parse::<ButtonId>(), to.parse::<ButtonId>()) {
+                (Ok(from), Ok(to)) => Some((from, to)),
+                _ => {
+                    warn!("Ignoring invalid button mapping: {}", entry);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn parse_chords(var: &str) -> Vec<Chord> {
+    var.split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let (buttons, command) = entry.split_once('=')?;
+            let (first, second) = buttons.split_once('+')?;
+            match (
+                first.parse::<ButtonId>(),
+                second.parse::<ButtonId>(),
+                command.parse::<Command>(),
+            ) {
+                (Ok(first), Ok(second), Ok(command)) if first != second => Some(Chord {
+                    first,
+                    second,
+                    command,
+                }),
+                _ => {
+                    warn!("Ignoring invalid chord: {}", entry);
+                    None
+                }
+            }
+        })
+        .collect()
+}
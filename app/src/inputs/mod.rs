@@ -0,0 +1,4 @@
+#[cfg(feature = "gpio")]
+pub mod gpio;
+pub mod mapping;
+pub mod recording;
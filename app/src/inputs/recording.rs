@@ -0,0 +1,130 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File};
+use std::io::{LineWriter, Write};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// A press, e.g. `{"at_ms": 1200, "press": "A", "type": "Double"}`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct RecordedInput {
+    /// Since the recording started
+    at_ms: u64,
+    press: ButtonId,
+    #[serde(rename = "type")]
+    press_type: ButtonPress,
+}
+
+/// Writes the presses to a file as they come, a JSON object a line, for
+/// them to be replayed.
+pub struct InputRecorder {
+    file: LineWriter<File>,
+    started: Instant,
+}
+
+impl InputRecorder {
+    /// Records to `AMARU_PI_RECORD_INPUT`, when set. The file is replaced.
+    pub fn from_env() -> Option<Self> {
+        let path = env::var("AMARU_PI_RECORD_INPUT").ok()?;
+        match File::create(&path) {
+            Ok(file) => {
+                info!("Recording the input to {}", path);
+                Some(Self {
+                    file: LineWriter::new(file),
+                    started: Instant::now(),
+                })
+            }
+            Err(e) => {
+                warn!("Failed to record the input to {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn record(&mut self, event: &InputEvent) {
+        let input = RecordedInput {
+            at_ms: self.started.elapsed().as_millis() as u64,
+            press: event.id,
+            press_type: event.press_type,
+        };
+        let written = serde_json::to_string(&input)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| Ok(writeln!(self.file, "{}", line)?));
+        if let Err(e) = written {
+            warn!("Failed to record {:?}: {}", event, e);
+        }
+    }
+}
+
+/// Plays recorded presses at the pace they were made, along with the
+/// buttons, to reproduce a bug or drive a soak test.
+pub struct InputReplay {
+    inputs: Vec<RecordedInput>,
+    next: usize,
+    started: Instant,
+    /// Starts over once played
+    looped: bool,
+}
+
+impl InputReplay {
+    /// Replays `AMARU_PI_REPLAY_INPUT`, when set, over and over with
+    /// `AMARU_PI_REPLAY_LOOP=1`.
+    pub fn from_env() -> Option<Self> {
+        let path = env::var("AMARU_PI_REPLAY_INPUT").ok()?;
+        let inputs = match load(&path) {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                warn!("Failed to read the input to replay {}: {}", path, e);
+                return None;
+            }
+        };
+        let looped = env::var("AMARU_PI_REPLAY_LOOP")
+            .is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes"));
+        info!("Replaying {} presses of {}", inputs.len(), path);
+        Some(Self {
+            inputs,
+            next: 0,
+            started: Instant::now(),
+            looped,
+        })
+    }
+
+    /// The presses due by now.
+    pub fn due(&mut self) -> Vec<InputEvent> {
+        if self.next == self.inputs.len() {
+            if !self.looped || self.inputs.is_empty() {
+                return Vec::new();
+            }
+            info!("Replaying the input again");
+            self.next = 0;
+            self.started = Instant::now();
+        }
+        let elapsed = self.started.elapsed();
+        let due: Vec<_> = self.inputs[self.next..]
+            .iter()
+            .take_while(|input| Duration::from_millis(input.at_ms) <= elapsed)
+            .map(|input| InputEvent {
+                id: input.press,
+                press_type: input.press_type,
+            })
+            .collect();
+        self.next += due.len();
+        if self.next == self.inputs.len() && !self.looped {
+            info!("Input replayed");
+        }
+        due
+    }
+}
+
+fn load(path: &str) -> Result<Vec<RecordedInput>> {
+    let mut inputs = fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str::<RecordedInput>)
+        .collect::<Result<Vec<_>, _>>()?;
+    // Files edited by hand may be out of order
+    inputs.sort_by_key(|input| input.at_ms);
+    Ok(inputs)
+}
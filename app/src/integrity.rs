@@ -0,0 +1,91 @@
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// In `sha256sum` format, so that `sha256sum -c` checks it by hand too.
+/// Written by the activation script and amaru-pi when installing a binary.
+const CHECKSUMS_FILE_PATH: &str = "/home/pi/.amaru_checksums";
+const BIN_DIR: &str = "/home/pi/bin";
+const BINARIES: [&str; 3] = ["amaru", "amaru-pi", "amaru-doctor"];
+
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Checksums by installed path.
+fn read_checksums() -> Result<BTreeMap<PathBuf, String>> {
+    let data = match fs::read_to_string(CHECKSUMS_FILE_PATH) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(data
+        .lines()
+        .filter_map(|line| {
+            let (sha256, path) = line.split_once("  ")?;
+            Some((PathBuf::from(path), sha256.to_string()))
+        })
+        .collect())
+}
+
+fn write_checksums(checksums: &BTreeMap<PathBuf, String>) -> Result<()> {
+    let data: String = checksums
+        .iter()
+        .map(|(path, sha256)| format!("{}  {}\n", sha256, path.display()))
+        .collect();
+    let tmp_path = format!("{}.tmp", CHECKSUMS_FILE_PATH);
+    fs::write(&tmp_path, data)?;
+    File::open(&tmp_path)?.sync_all()?;
+    fs::rename(&tmp_path, CHECKSUMS_FILE_PATH)?;
+    Ok(())
+}
+
+/// Records the checksum of a binary just installed at `path`, before it is
+/// switched to.
+pub fn record(path: &Path) -> Result<()> {
+    let mut checksums = read_checksums()?;
+    checksums.insert(path.to_path_buf(), sha256_file(path)?);
+    write_checksums(&checksums)
+}
+
+/// Hashes the binaries currently installed and returns the names of those
+/// that changed since they were installed. A binary without a recorded
+/// checksum, installed before they were, is trusted and recorded.
+pub fn verify() -> Result<Vec<String>> {
+    let mut checksums = read_checksums()?;
+    let mut recorded = false;
+    let mut modified = Vec::new();
+    for name in BINARIES {
+        // Resolves the symlink to the active slot
+        let Ok(path) = fs::canonicalize(Path::new(BIN_DIR).join(name)) else {
+            continue;
+        };
+        let actual = sha256_file(&path)?;
+        match checksums.get(&path) {
+            Some(expected) if *expected == actual => {}
+            Some(_) => {
+                warn!("{} was modified since it was installed", path.display());
+                modified.push(name.to_string());
+            }
+            None => {
+                info!("Recording the checksum of {}", path.display());
+                checksums.insert(path, actual);
+                recorded = true;
+            }
+        }
+    }
+    if recorded {
+        write_checksums(&checksums)?;
+    }
+    Ok(modified)
+}
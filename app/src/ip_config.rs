@@ -0,0 +1,342 @@
+#[cfg(feature = "display_hat")]
+use crate::systemd;
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+use std::env;
+#[cfg(feature = "display_hat")]
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+#[cfg(feature = "display_hat")]
+use std::path::Path;
+
+const DEFAULT_INTERFACE: &str = "eth0";
+#[cfg(feature = "display_hat")]
+const DHCPCD_CONF_PATH: &str = "/etc/dhcpcd.conf";
+
+/// The interface the IP screen configures, from `AMARU_PI_IP_INTERFACE`.
+pub fn interface() -> String {
+    env::var("AMARU_PI_IP_INTERFACE")
+        .map(|s| s.trim().to_string())
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_INTERFACE.to_string())
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IpMethod {
+    #[default]
+    Dhcp,
+    Static,
+}
+
+/// IPv4 addressing of an interface. Only the method matters with DHCP.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IpSettings {
+    pub method: IpMethod,
+    /// With its prefix, e.g. `192.168.1.50/24`
+    pub address: String,
+    pub gateway: String,
+    /// Comma separated
+    pub dns: String,
+}
+
+/// The addressing of the configured interface, as last read.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IpConfigState {
+    /// `None` until read, or why it couldn't be
+    pub current: Option<Result<IpSettings, String>>,
+    pub applying: bool,
+}
+
+/// A setting the IP screen can edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpField {
+    Method,
+    Address,
+    Gateway,
+    Dns,
+}
+
+impl IpField {
+    pub const ALL: [IpField; 4] = [
+        IpField::Method,
+        IpField::Address,
+        IpField::Gateway,
+        IpField::Dns,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IpField::Method => "Method",
+            IpField::Address => "Address",
+            IpField::Gateway => "Gateway",
+            IpField::Dns => "DNS",
+        }
+    }
+
+    pub fn get(&self, settings: &IpSettings) -> String {
+        match self {
+            IpField::Method => match settings.method {
+                IpMethod::Dhcp => "DHCP".to_string(),
+                IpMethod::Static => "Static".to_string(),
+            },
+            IpField::Address => settings.address.clone(),
+            IpField::Gateway => settings.gateway.clone(),
+            IpField::Dns => settings.dns.clone(),
+        }
+    }
+
+    pub fn set(&self, settings: &mut IpSettings, value: String) {
+        match self {
+            IpField::Method => {}
+            IpField::Address => settings.address = value,
+            IpField::Gateway => settings.gateway = value,
+            IpField::Dns => settings.dns = value,
+        }
+    }
+
+    /// Returns the message to show when `value` is invalid.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match self {
+            IpField::Method => Ok(()),
+            IpField::Address => parse_cidr(value).map(|_| ()),
+            IpField::Gateway => value
+                .parse::<Ipv4Addr>()
+                .map(|_| ())
+                .map_err(|_| "Not an IPv4 address".to_string()),
+            IpField::Dns => dns_servers(value)
+                .map(|_| ())
+                .map_err(|server| format!("Not an IP address: {}", server)),
+        }
+    }
+}
+
+/// `192.168.1.50/24` as its address and prefix length.
+fn parse_cidr(value: &str) -> Result<(Ipv4Addr, u8), String> {
+    let (address, prefix) = value
+        .split_once('/')
+        .ok_or_else(|| "Missing the prefix, e.g. /24".to_string())?;
+    let address = address
+        .parse::<Ipv4Addr>()
+        .map_err(|_| "Not an IPv4 address".to_string())?;
+    let prefix = prefix
+        .parse::<u8>()
+        .ok()
+        .filter(|prefix| (1..=32).contains(prefix))
+        .ok_or_else(|| "The prefix must be 1 to 32".to_string())?;
+    Ok((address, prefix))
+}
+
+/// The servers of a comma separated list, or the first one that isn't an
+/// address.
+fn dns_servers(value: &str) -> Result<Vec<IpAddr>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| s.to_string()))
+        .collect()
+}
+
+impl IpSettings {
+    /// Checks the fields together, the gateway having to be on the subnet.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.method == IpMethod::Dhcp {
+            return Ok(());
+        }
+        for field in IpField::ALL {
+            field
+                .validate(&field.get(self))
+                .map_err(|e| format!("{}: {}", field.label(), e))?;
+        }
+        let (address, prefix) = parse_cidr(self.address.trim())?;
+        let gateway: Ipv4Addr = self.gateway.trim().parse().map_err(|_| "Bad gateway")?;
+        let mask = u32::MAX << (32 - u32::from(prefix));
+        if u32::from(address) & mask != u32::from(gateway) & mask {
+            return Err("The gateway is not on the subnet".to_string());
+        }
+        if address == gateway {
+            return Err("The address is the gateway's".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Reads the addressing of `interface` from NetworkManager, or dhcpcd
+/// where NetworkManager doesn't run.
+#[cfg(feature = "display_hat")]
+pub fn read(interface: &str) -> anyhow::Result<IpSettings> {
+    match nm_connection(interface)? {
+        Some(connection) => nm_read(&connection),
+        None => Ok(dhcpcd_read(
+            &fs::read_to_string(DHCPCD_CONF_PATH)?,
+            interface,
+        )),
+    }
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn read(_interface: &str) -> anyhow::Result<IpSettings> {
+    Ok(IpSettings::default())
+}
+
+/// Applies `settings` to `interface`, which drops its current address.
+#[cfg(feature = "display_hat")]
+pub fn apply(interface: &str, settings: &IpSettings) -> anyhow::Result<()> {
+    settings.validate().map_err(anyhow::Error::msg)?;
+    match nm_connection(interface)? {
+        Some(connection) => nm_apply(&connection, settings),
+        None => {
+            let path = Path::new(DHCPCD_CONF_PATH);
+            let conf = dhcpcd_write(&fs::read_to_string(path)?, interface, settings);
+            let temp = path.with_extension("conf.tmp");
+            fs::write(&temp, conf)?;
+            fs::rename(&temp, path)?;
+            systemd::restart_service("dhcpcd.service")
+        }
+    }
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn apply(_interface: &str, settings: &IpSettings) -> anyhow::Result<()> {
+    settings.validate().map_err(anyhow::Error::msg)
+}
+
+/// The NetworkManager connection of `interface`, `None` when NetworkManager
+/// doesn't run.
+#[cfg(feature = "display_hat")]
+fn nm_connection(interface: &str) -> anyhow::Result<Option<String>> {
+    let running = run_and_capture("nmcli", ["-t", "-f", "RUNNING", "general"].to_vec());
+    if running.is_err() {
+        return Ok(None);
+    }
+    let connection = run_and_capture(
+        "nmcli",
+        ["-g", "GENERAL.CONNECTION", "device", "show", interface].to_vec(),
+    )?;
+    if connection.is_empty() {
+        anyhow::bail!("No connection on {}", interface);
+    }
+    Ok(Some(connection))
+}
+
+#[cfg(feature = "display_hat")]
+fn nm_read(connection: &str) -> anyhow::Result<IpSettings> {
+    let stdout = run_and_capture(
+        "nmcli",
+        [
+            "-t",
+            "-f",
+            "ipv4.method,ipv4.addresses,ipv4.gateway,ipv4.dns",
+            "connection",
+            "show",
+            connection,
+        ]
+        .to_vec(),
+    )?;
+    let mut settings = IpSettings::default();
+    // e.g. `ipv4.method:manual`
+    for (key, value) in stdout.lines().filter_map(|line| line.split_once(':')) {
+        match key {
+            "ipv4.method" if value == "manual" => settings.method = IpMethod::Static,
+            "ipv4.addresses" => settings.address = value.to_string(),
+            "ipv4.gateway" => settings.gateway = value.to_string(),
+            "ipv4.dns" => settings.dns = value.to_string(),
+            _ => {}
+        }
+    }
+    Ok(settings)
+}
+
+#[cfg(feature = "display_hat")]
+fn nm_apply(connection: &str, settings: &IpSettings) -> anyhow::Result<()> {
+    let dns = settings.dns.replace(' ', "");
+    let args = match settings.method {
+        IpMethod::Static => [
+            ("ipv4.method", "manual"),
+            ("ipv4.addresses", settings.address.trim()),
+            ("ipv4.gateway", settings.gateway.trim()),
+            ("ipv4.dns", dns.as_str()),
+        ],
+        // Addresses must go first for NetworkManager to accept auto
+        IpMethod::Dhcp => [
+            ("ipv4.addresses", ""),
+            ("ipv4.gateway", ""),
+            ("ipv4.dns", ""),
+            ("ipv4.method", "auto"),
+        ],
+    };
+    let mut command = vec!["con", "modify", connection];
+    for (key, value) in args {
+        command.extend([key, value]);
+    }
+    run_and_capture("nmcli", command)?;
+    run_and_capture("nmcli", ["con", "up", connection].to_vec())?;
+    Ok(())
+}
+
+/// Whether a line of `dhcpcd.conf` starts the block of another interface.
+#[cfg(feature = "display_hat")]
+fn starts_block(line: &str) -> bool {
+    line.trim_start().starts_with("interface ") || line.trim_start().starts_with("profile ")
+}
+
+#[cfg(feature = "display_hat")]
+fn dhcpcd_read(conf: &str, interface: &str) -> IpSettings {
+    let mut settings = IpSettings::default();
+    let mut in_block = false;
+    for line in conf.lines().map(str::trim) {
+        if starts_block(line) {
+            in_block = line.split_whitespace().nth(1) == Some(interface);
+            continue;
+        }
+        let Some(option) = line.strip_prefix("static ").filter(|_| in_block) else {
+            continue;
+        };
+        match option.split_once('=') {
+            Some(("ip_address", value)) => {
+                settings.method = IpMethod::Static;
+                settings.address = value.trim().to_string();
+            }
+            Some(("routers", value)) => settings.gateway = value.trim().to_string(),
+            Some(("domain_name_servers", value)) => {
+                settings.dns = value.split_whitespace().collect::<Vec<_>>().join(",")
+            }
+            _ => {}
+        }
+    }
+    settings
+}
+
+/// `conf` without the block of `interface`, then a new one for a static
+/// address.
+#[cfg(feature = "display_hat")]
+fn dhcpcd_write(conf: &str, interface: &str, settings: &IpSettings) -> String {
+    let mut in_block = false;
+    let mut lines: Vec<String> = Vec::new();
+    for line in conf.lines() {
+        if starts_block(line) {
+            in_block = line.split_whitespace().nth(1) == Some(interface);
+        }
+        if !in_block {
+            lines.push(line.to_string());
+        }
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    if settings.method == IpMethod::Static {
+        let dns = dns_servers(&settings.dns).unwrap_or_default();
+        lines.push(String::new());
+        lines.push(format!("interface {}", interface));
+        lines.push(format!("static ip_address={}", settings.address.trim()));
+        lines.push(format!("static routers={}", settings.gateway.trim()));
+        if !dns.is_empty() {
+            let dns: Vec<String> = dns.iter().map(IpAddr::to_string).collect();
+            lines.push(format!("static domain_name_servers={}", dns.join(" ")));
+        }
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use std::env;
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+
+const HISTORY_FILE_PATH: &str = "/home/pi/.amaru_keyboard_history.json";
+const HISTORY_MAX_WORDS: usize = 100;
+/// Shorter words aren't worth remembering
+const MIN_WORD_LENGTH: usize = 3;
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Words commonly typed on the device: network names, hosts and domains.
+const DICTIONARY: &[&str] = &[
+    "amaru",
+    "cardano",
+    "cardanofoundation",
+    "mainnet",
+    "preprod",
+    "preview",
+    "backbone",
+    "relay",
+    "pragma",
+    "iohk",
+    "local",
+    "localhost",
+    "raspberrypi",
+    "router",
+    "network",
+    "wireless",
+    "hotspot",
+    "home",
+    "guest",
+    "office",
+    "android",
+    "iphone",
+    "freebox",
+    "livebox",
+    "netgear",
+    "linksys",
+    "tplink",
+    "xfinitywifi",
+    "eduroam",
+];
+
+/// Whether the suggestion strip is enabled through
+/// `AMARU_PI_KEYBOARD_SUGGESTIONS`.
+pub fn is_enabled() -> bool {
+    env::var("AMARU_PI_KEYBOARD_SUGGESTIONS")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(false)
+}
+
+/// Suggests completions of the word being typed, from the words previously
+/// typed on the device and a small built-in dictionary.
+#[derive(Debug, Default)]
+pub struct Completer {
+    /// Most recent first
+    history: Vec<String>,
+}
+
+impl Completer {
+    pub fn load() -> Self {
+        let history = read_history().unwrap_or_else(|e| {
+            warn!("Failed to read keyboard history: {}", e);
+            Vec::new()
+        });
+        Self { history }
+    }
+
+    pub fn suggestions(&self, prefix: &str) -> Vec<String> {
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        let prefix = prefix.to_lowercase();
+        let mut suggestions: Vec<String> = Vec::new();
+        let candidates = self
+            .history
+            .iter()
+            .map(String::as_str)
+            .chain(DICTIONARY.iter().copied());
+        for word in candidates {
+            if suggestions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+            let lower = word.to_lowercase();
+            if lower.starts_with(&prefix)
+                && lower != prefix
+                && !suggestions.iter().any(|s| s.to_lowercase() == lower)
+            {
+                suggestions.push(word.to_string());
+            }
+        }
+        suggestions
+    }
+
+    /// Moves the word to the front of the history.
+    pub fn remember(&mut self, word: &str) {
+        if word.chars().count() < MIN_WORD_LENGTH {
+            return;
+        }
+        self.history.retain(|w| w != word);
+        self.history.insert(0, word.to_string());
+        self.history.truncate(HISTORY_MAX_WORDS);
+        if let Err(e) = write_history(&self.history) {
+            warn!("Failed to write keyboard history: {}", e);
+        }
+    }
+}
+
+fn read_history() -> Result<Vec<String>> {
+    let path = Path::new(HISTORY_FILE_PATH);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_history(history: &[String]) -> Result<()> {
+    fs::write(HISTORY_FILE_PATH, serde_json::to_string_pretty(history)?)?;
+    Ok(())
+}
@@ -1,82 +1,178 @@
-use super::{KeyboardAction, KeyboardMode, KeyboardWidget};
+use super::{AlternatesPopup, KeyboardAction, KeyboardContext, KeyboardMode, KeyboardWidget};
 use crate::button::{ButtonId, ButtonPress, InputEvent};
-use crate::keyboard::layout::KEYBOARD_LAYOUT;
+use crate::inputs::mapping::Command;
+use crate::keyboard::layout::{CURSOR_LEFT_KEY, CURSOR_RIGHT_KEY, PASTE_KEY, SWITCH_KEY};
+use crate::widgets::grid::{self, Edge};
 
 impl KeyboardWidget {
     /// Handles button presses and returns an optional action.
     pub fn handle_input(&mut self, event: InputEvent) -> Option<KeyboardAction> {
-        let max_row = KEYBOARD_LAYOUT.len() - 1;
+        let action = self.handle_button(event);
+        if let Some(action) = &action {
+            self.track_word(action);
+        }
+        action
+    }
 
-        match (event.id, event.press_type) {
-            // In the keyboard, A/B/X/Y are for nav, AA for key press, BB for backspace
-            (ButtonId::A, ButtonPress::Short) => {
-                let max_col = KEYBOARD_LAYOUT[self.cursor.0].len() - 1;
-                if self.cursor.1 < max_col {
-                    self.cursor.1 += 1;
-                } else {
-                    self.cursor.1 = 0;
-                }
+    /// Handles the suggestion strip commands, and characters typed on a
+    /// real keyboard. Accepting a suggestion types the rest of the word.
+    pub fn handle_command(&mut self, command: Command) -> Option<KeyboardAction> {
+        let suggestions = self.suggestions();
+        match command {
+            Command::Type(c) => {
+                let action = match c {
+                    ' ' => KeyboardAction::Space,
+                    c if self.context == KeyboardContext::Hex && !c.is_ascii_hexdigit() => {
+                        return None;
+                    }
+                    c => KeyboardAction::KeyPress(c.to_string()),
+                };
+                self.track_word(&action);
+                Some(action)
             }
-            (ButtonId::B, ButtonPress::Short) => {
-                if self.cursor.1 > 0 {
-                    self.cursor.1 -= 1;
-                } else {
-                    // The cursor is at col 0, wrap around
-                    let max_col = KEYBOARD_LAYOUT[self.cursor.0].len() - 1;
-                    self.cursor.1 = max_col;
-                }
+            Command::Erase => {
+                self.track_word(&KeyboardAction::Backspace);
+                Some(KeyboardAction::Backspace)
             }
-            (ButtonId::X, ButtonPress::Short) => {
-                if self.cursor.0 > 0 {
-                    self.cursor.0 -= 1;
-                    self.clamp_cursor_col();
-                }
+            Command::NextSuggestion if !suggestions.is_empty() => {
+                self.suggestion = (self.suggestion + 1) % suggestions.len();
+                None
             }
-            (ButtonId::Y, ButtonPress::Short) => {
-                if self.cursor.0 < max_row {
-                    self.cursor.0 += 1;
-                    self.clamp_cursor_col();
-                }
+            Command::AcceptSuggestion => {
+                let word = suggestions.get(self.suggestion)?;
+                let suffix: String = word
+                    .chars()
+                    .skip(self.current_word.chars().count())
+                    .collect();
+                self.current_word = word.clone();
+                self.suggestion = 0;
+                Some(KeyboardAction::KeyPress(suffix))
             }
-            (ButtonId::A, ButtonPress::Double) => return self.press_key(),
-            (ButtonId::B, ButtonPress::Double) => return Some(KeyboardAction::Backspace),
-            (ButtonId::X, ButtonPress::Double) => {
-                if self.cursor.0 > 1 {
-                    self.cursor.0 -= 2;
-                    self.clamp_cursor_col();
-                }
+            _ => None,
+        }
+    }
+
+    /// Follows the word being typed to offer completions.
+    fn track_word(&mut self, action: &KeyboardAction) {
+        match action {
+            KeyboardAction::KeyPress(chars) => self.current_word.push_str(chars),
+            KeyboardAction::Backspace => {
+                self.current_word.pop();
             }
-            (ButtonId::Y, ButtonPress::Double) => {
-                if self.cursor.0 < max_row - 1 {
-                    self.cursor.0 += 2;
-                    self.clamp_cursor_col();
+            KeyboardAction::Space | KeyboardAction::Exit => {
+                if let Some(completer) = &mut self.completer
+                    && self.context != KeyboardContext::Password
+                {
+                    completer.remember(&self.current_word);
                 }
+                self.current_word.clear();
+            }
+            // The position in the word is unknown after moving the cursor
+            KeyboardAction::CursorLeft | KeyboardAction::CursorRight => self.current_word.clear(),
+        }
+        self.suggestion = 0;
+    }
+
+    fn handle_button(&mut self, event: InputEvent) -> Option<KeyboardAction> {
+        if self.alternates.is_some() {
+            return self.handle_alternates_input(event);
+        }
+        match (event.id, event.press_type) {
+            // In the keyboard, A/B/X/Y are for nav, AA for key press, BB for backspace.
+            // Holding A/B moves the cursor continuously, holding Y deletes
+            // continuously and a long X opens the alternate characters.
+            (ButtonId::A, ButtonPress::Short | ButtonPress::Long | ButtonPress::Repeat) => {
+                self.move_cursor(0, 1);
             }
+            (ButtonId::B, ButtonPress::Short | ButtonPress::Long | ButtonPress::Repeat) => {
+                self.move_cursor(0, -1);
+            }
+            (ButtonId::X, ButtonPress::Short) => self.move_cursor(-1, 0),
+            (ButtonId::Y, ButtonPress::Short) => self.move_cursor(1, 0),
+            (ButtonId::A, ButtonPress::Double) => return self.press_key(),
+            (ButtonId::B, ButtonPress::Double)
+            | (ButtonId::Y, ButtonPress::Long | ButtonPress::Repeat) => {
+                return Some(KeyboardAction::Backspace);
+            }
+            (ButtonId::X, ButtonPress::Long) => self.open_alternates(),
+            (ButtonId::X, ButtonPress::Double) => self.move_cursor(-2, 0),
+            (ButtonId::Y, ButtonPress::Double) => self.move_cursor(2, 0),
             _ => { /* Ignore other presses */ }
         }
         None
     }
 
+    /// In the alternates popup, A/B select, AA types the character and any
+    /// other press closes the popup.
+    fn handle_alternates_input(&mut self, event: InputEvent) -> Option<KeyboardAction> {
+        let popup = self.alternates.as_mut()?;
+        let count = popup.chars.len();
+        match (event.id, event.press_type) {
+            (ButtonId::A, ButtonPress::Short | ButtonPress::Long | ButtonPress::Repeat) => {
+                popup.selected = grid::step(popup.selected, count, 1, Edge::Wrap);
+            }
+            (ButtonId::B, ButtonPress::Short | ButtonPress::Long | ButtonPress::Repeat) => {
+                popup.selected = grid::step(popup.selected, count, -1, Edge::Wrap);
+            }
+            (ButtonId::A, ButtonPress::Double) => {
+                let key = popup.chars[popup.selected];
+                self.alternates = None;
+                return Some(self.type_key(key));
+            }
+            _ => self.alternates = None,
+        }
+        None
+    }
+
+    fn open_alternates(&mut self) {
+        let (row, col) = self.cursor;
+        if let Some(chars) = self.alternate_chars.get(self.rows()[row][col]) {
+            self.alternates = Some(AlternatesPopup { chars, selected: 0 });
+        }
+    }
+
     /// Checks if the cursor is at the far-right key of the current row.
     pub fn is_cursor_at_right_edge(&self) -> bool {
         let (row, col) = self.cursor;
-        let max_col = KEYBOARD_LAYOUT[row].len() - 1;
+        let max_col = self.rows()[row].len() - 1;
         col == max_col
     }
 
-    fn clamp_cursor_col(&mut self) {
-        let max_col = KEYBOARD_LAYOUT[self.cursor.0].len() - 1;
-        if self.cursor.1 > max_col {
-            self.cursor.1 = max_col;
+    /// The row of the cursor among the rows, and its key among the keys of
+    /// the row, then the highlighted suggestion when there are any.
+    pub fn cursors(&self) -> Vec<(usize, usize)> {
+        let (row, col) = self.cursor;
+        let rows = self.rows();
+        let mut cursors = vec![(row, rows.len())];
+        if let Some(keys) = rows.get(row) {
+            cursors.push((col, keys.len()));
         }
+        let suggestions = self.suggestions().len();
+        if suggestions > 0 {
+            cursors.push((self.suggestion, suggestions));
+        }
+        cursors
+    }
+
+    fn move_cursor(&mut self, rows: isize, columns: isize) {
+        self.cursor = self
+            .navigation
+            .step(self.cursor, self.rows(), rows, columns);
     }
 
     fn press_key(&mut self) -> Option<KeyboardAction> {
         let (row, col) = self.cursor;
-        let key = KEYBOARD_LAYOUT[row][col];
+        let key = self.rows()[row][col];
 
         match key {
             "Done" => Some(KeyboardAction::Exit),
+            CURSOR_LEFT_KEY => Some(KeyboardAction::CursorLeft),
+            CURSOR_RIGHT_KEY => Some(KeyboardAction::CursorRight),
+            PASTE_KEY => self.paste(),
+            SWITCH_KEY => {
+                self.switch_layout();
+                None
+            }
             "shift" => {
                 self.mode = match self.mode {
                     KeyboardMode::Shift => KeyboardMode::Normal,
@@ -92,16 +188,41 @@ impl KeyboardWidget {
                 None
             }
             "[ space ]" => Some(KeyboardAction::Space),
-            _ => {
-                let is_shifted = matches!(self.mode, KeyboardMode::Shift | KeyboardMode::CapsLock);
-                let key_str = self.get_key_display_string(key, is_shifted);
+            _ => Some(self.type_key(key)),
+        }
+    }
 
-                if matches!(self.mode, KeyboardMode::Shift) {
-                    // Reset shift
-                    self.mode = KeyboardMode::Normal;
-                }
-                Some(KeyboardAction::KeyPress(key_str))
-            }
+    /// Types the text last copied, unless it has characters the context
+    /// doesn't take.
+    fn paste(&self) -> Option<KeyboardAction> {
+        let text = self.paste_buffer.as_ref().filter(|text| !text.is_empty())?;
+        if self.context == KeyboardContext::Hex && !text.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(KeyboardAction::KeyPress(text.clone()))
+    }
+
+    /// Moves to the next layout, keeping the cursor on the switch key so
+    /// that layouts can be cycled quickly.
+    fn switch_layout(&mut self) {
+        self.layout = (self.layout + 1) % self.layouts.len();
+        self.mode = KeyboardMode::Normal;
+        self.cursor = self
+            .rows()
+            .iter()
+            .enumerate()
+            .find_map(|(row, keys)| Some((row, keys.iter().position(|k| *k == SWITCH_KEY)?)))
+            .unwrap_or((0, 0));
+    }
+
+    fn type_key(&mut self, key: &str) -> KeyboardAction {
+        let is_shifted = matches!(self.mode, KeyboardMode::Shift | KeyboardMode::CapsLock);
+        let key_str = self.get_key_display_string(key, is_shifted);
+
+        if matches!(self.mode, KeyboardMode::Shift) {
+            // Reset shift
+            self.mode = KeyboardMode::Normal;
         }
+        KeyboardAction::KeyPress(key_str)
     }
 }
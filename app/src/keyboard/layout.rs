@@ -1,6 +1,62 @@
 use std::collections::HashMap;
 
-pub const KEYBOARD_LAYOUT: &[&[&str]] = &[
+/// Key switching to the next layout
+pub const SWITCH_KEY: &str = "switch";
+/// Keys moving the text cursor
+pub const CURSOR_LEFT_KEY: &str = "←";
+pub const CURSOR_RIGHT_KEY: &str = "→";
+/// Key typing the text last copied
+pub const PASTE_KEY: &str = "paste";
+
+/// A page of keys shown by the on-screen keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Numeric,
+    Symbols,
+    /// Only hexadecimal digits, e.g. for hashes and keys
+    Hex,
+}
+
+impl KeyboardLayout {
+    pub fn rows(&self) -> &'static [&'static [&'static str]] {
+        match self {
+            KeyboardLayout::Qwerty => QWERTY_LAYOUT,
+            KeyboardLayout::Numeric => NUMERIC_LAYOUT,
+            KeyboardLayout::Symbols => SYMBOLS_LAYOUT,
+            KeyboardLayout::Hex => HEX_LAYOUT,
+        }
+    }
+
+    /// Left padding of each row, staggered like a physical keyboard.
+    pub fn indent(&self, row: usize) -> &'static str {
+        match (self, row) {
+            (KeyboardLayout::Qwerty, 0) => "       ",
+            (KeyboardLayout::Qwerty, 1) => "        ",
+            (KeyboardLayout::Qwerty, 2 | 3) => "   ",
+            (KeyboardLayout::Qwerty, 4) => "      ",
+            (KeyboardLayout::Numeric, 0..=2) => "                   ",
+            (KeyboardLayout::Numeric, 3) => "            ",
+            (KeyboardLayout::Symbols, 0..=2) => "          ",
+            (KeyboardLayout::Symbols, 3) => "   ",
+            (KeyboardLayout::Hex, 0 | 1) => "              ",
+            (KeyboardLayout::Hex, 2) => "                    ",
+            _ => "",
+        }
+    }
+
+    /// Short name shown on the switch key leading to this layout.
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyboardLayout::Qwerty => "abc",
+            KeyboardLayout::Numeric => "123",
+            KeyboardLayout::Symbols => "#+=",
+            KeyboardLayout::Hex => "hex",
+        }
+    }
+}
+
+const QWERTY_LAYOUT: &[&[&str]] = &[
     &["1", "2", "3", "4", "5", "6", "7", "8", "9", "0", "-", "="],
     &[
         "q", "w", "e", "r", "t", "y", "u", "i", "o", "p", "[", "]", "\\",
@@ -9,7 +65,42 @@ pub const KEYBOARD_LAYOUT: &[&[&str]] = &[
         "caps", "a", "s", "d", "f", "g", "h", "j", "k", "l", ";", "'",
     ],
     &["shift", "z", "x", "c", "v", "b", "n", "m", ",", ".", "/"],
-    &["[ space ]", "Done"],
+    &[
+        SWITCH_KEY,
+        CURSOR_LEFT_KEY,
+        "[ space ]",
+        CURSOR_RIGHT_KEY,
+        "Done",
+    ],
+];
+
+const NUMERIC_LAYOUT: &[&[&str]] = &[
+    &["1", "2", "3"],
+    &["4", "5", "6"],
+    &["7", "8", "9"],
+    &[SWITCH_KEY, "0", ".", "Done"],
+];
+
+const SYMBOLS_LAYOUT: &[&[&str]] = &[
+    &["!", "@", "#", "$", "%", "^", "&", "*", "(", ")"],
+    &["-", "_", "=", "+", "[", "]", "{", "}", "\\", "|"],
+    &[";", ":", "'", "\"", ",", ".", "<", ">", "/", "?"],
+    &[
+        SWITCH_KEY,
+        "`",
+        "~",
+        PASTE_KEY,
+        CURSOR_LEFT_KEY,
+        "[ space ]",
+        CURSOR_RIGHT_KEY,
+        "Done",
+    ],
+];
+
+const HEX_LAYOUT: &[&[&str]] = &[
+    &["0", "1", "2", "3", "4", "5", "6", "7"],
+    &["8", "9", "a", "b", "c", "d", "e", "f"],
+    &[PASTE_KEY, CURSOR_LEFT_KEY, "Done", CURSOR_RIGHT_KEY],
 ];
 
 pub fn get_shifted_symbols() -> HashMap<&'static str, &'static str> {
@@ -39,3 +130,22 @@ pub fn get_shifted_symbols() -> HashMap<&'static str, &'static str> {
     .cloned()
     .collect()
 }
+
+/// Characters offered when long-pressing a key
+pub fn get_alternate_chars() -> HashMap<&'static str, &'static [&'static str]> {
+    let alternates: [(&str, &[&str]); 12] = [
+        ("a", &["à", "á", "â", "ä", "å", "æ"]),
+        ("c", &["ç"]),
+        ("e", &["è", "é", "ê", "ë"]),
+        ("i", &["ì", "í", "î", "ï"]),
+        ("n", &["ñ"]),
+        ("o", &["ò", "ó", "ô", "ö", "ø", "œ"]),
+        ("s", &["ß"]),
+        ("u", &["ù", "ú", "û", "ü"]),
+        ("y", &["ý", "ÿ"]),
+        ("'", &["`", "\""]),
+        ("-", &["_", "~"]),
+        ("=", &["+", "~"]),
+    ];
+    alternates.into_iter().collect()
+}
@@ -1,5 +1,9 @@
+use crate::widgets::grid::{Edge, GridNavigation};
+use completion::Completer;
+use layout::KeyboardLayout;
 use std::collections::HashMap;
 
+pub mod completion;
 mod input;
 mod layout;
 mod render;
@@ -9,6 +13,8 @@ pub enum KeyboardAction {
     KeyPress(String),
     Space,
     Backspace,
+    CursorLeft,
+    CursorRight,
     Exit,
 }
 
@@ -16,15 +22,46 @@ pub enum KeyboardAction {
 pub enum KeyboardContext {
     Normal,
     Password,
+    /// Hexadecimal values only
+    Hex,
 }
 
+/// Layouts cycled through with the switch key
+const DEFAULT_LAYOUTS: &[KeyboardLayout] = &[
+    KeyboardLayout::Qwerty,
+    KeyboardLayout::Numeric,
+    KeyboardLayout::Symbols,
+];
+
 pub struct KeyboardWidget {
     cursor: (usize, usize),
+    /// Whether the cursor wraps around the rows and the keys of a row
+    navigation: GridNavigation,
+    layouts: &'static [KeyboardLayout],
+    /// Index of the current layout in `layouts`
+    layout: usize,
     mode: KeyboardMode,
     shifted_symbols: HashMap<&'static str, &'static str>,
+    alternate_chars: HashMap<&'static str, &'static [&'static str]>,
+    /// Alternate characters popup opened by a long press
+    alternates: Option<AlternatesPopup>,
+    /// Word completion, when the suggestion strip is enabled
+    completer: Option<Completer>,
+    /// The word being typed, as far as the keyboard can tell
+    current_word: String,
+    /// Index of the highlighted suggestion
+    suggestion: usize,
+    /// Typed by the paste key
+    paste_buffer: Option<String>,
     context: KeyboardContext,
 }
 
+#[derive(Debug)]
+pub(super) struct AlternatesPopup {
+    chars: &'static [&'static str],
+    selected: usize,
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub(super) enum KeyboardMode {
     Normal,
@@ -36,8 +73,17 @@ impl Default for KeyboardWidget {
     fn default() -> Self {
         Self {
             cursor: (0, 0),
+            navigation: GridNavigation::new(Edge::Stop, Edge::Wrap),
+            layouts: DEFAULT_LAYOUTS,
+            layout: 0,
             mode: KeyboardMode::Normal,
             shifted_symbols: layout::get_shifted_symbols(),
+            alternate_chars: layout::get_alternate_chars(),
+            alternates: None,
+            completer: completion::is_enabled().then(Completer::load),
+            current_word: String::new(),
+            suggestion: 0,
+            paste_buffer: None,
             context: KeyboardContext::Normal,
         }
     }
@@ -47,5 +93,48 @@ impl KeyboardWidget {
     pub fn set_context(&mut self, context: KeyboardContext) {
         self.context = context;
         self.cursor = (0, 0);
+        self.alternates = None;
+        self.current_word.clear();
+        self.suggestion = 0;
+        self.layouts = match context {
+            KeyboardContext::Hex => &[KeyboardLayout::Hex],
+            _ => DEFAULT_LAYOUTS,
+        };
+        self.layout = 0;
+    }
+
+    /// Mirrors the text last copied, from the system state.
+    pub fn set_paste_buffer(&mut self, text: Option<&str>) {
+        if self.paste_buffer.as_deref() != text {
+            self.paste_buffer = text.map(str::to_string);
+        }
+    }
+
+    pub fn set_navigation(&mut self, navigation: GridNavigation) {
+        self.navigation = navigation;
+    }
+
+    pub(super) fn current_layout(&self) -> KeyboardLayout {
+        self.layouts[self.layout]
+    }
+
+    /// The layout the switch key leads to.
+    pub(super) fn next_layout(&self) -> KeyboardLayout {
+        self.layouts[(self.layout + 1) % self.layouts.len()]
+    }
+
+    /// Completions of the current word. Nothing is suggested, nor
+    /// remembered, while typing passwords.
+    pub(super) fn suggestions(&self) -> Vec<String> {
+        match &self.completer {
+            Some(completer) if self.context != KeyboardContext::Password => {
+                completer.suggestions(&self.current_word)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub(super) fn rows(&self) -> &'static [&'static [&'static str]] {
+        self.current_layout().rows()
     }
 }
@@ -1,23 +1,86 @@
-use super::{KeyboardMode, KeyboardWidget};
-use crate::keyboard::layout::KEYBOARD_LAYOUT;
+use super::{AlternatesPopup, KeyboardMode, KeyboardWidget};
+use crate::keyboard::layout::SWITCH_KEY;
 use ratatui::{
     Frame,
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
 
 impl KeyboardWidget {
     pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let area = if self.completer.is_some() {
+            let [strip_area, keyboard_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+            self.render_suggestions(frame, strip_area);
+            keyboard_area
+        } else {
+            area
+        };
         let rows = self.build_rows();
         let keyboard_widget =
             Paragraph::new(rows).block(Block::default().borders(Borders::ALL).title("Keyboard"));
         frame.render_widget(keyboard_widget, area);
+
+        if let Some(popup) = &self.alternates {
+            self.render_alternates(frame, area, popup);
+        }
+    }
+
+    fn render_suggestions(&self, frame: &mut Frame, area: Rect) {
+        let spans: Vec<Span> = self
+            .suggestions()
+            .into_iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let style = if i == self.suggestion {
+                    Style::default().bg(Color::Cyan).fg(Color::Black)
+                } else {
+                    Style::default().fg(Color::Gray)
+                };
+                Span::styled(format!(" {} ", word), style)
+            })
+            .collect();
+        frame.render_widget(Line::from(spans).alignment(Alignment::Center), area);
+    }
+
+    fn render_alternates(&self, frame: &mut Frame, area: Rect, popup: &AlternatesPopup) {
+        let is_shifted = matches!(self.mode, KeyboardMode::Shift | KeyboardMode::CapsLock);
+        let spans: Vec<Span> = popup
+            .chars
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let style = if i == popup.selected {
+                    Style::default().bg(Color::Yellow).fg(Color::Black)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Span::styled(
+                    format!(" {} ", self.get_key_display_string(key, is_shifted)),
+                    style,
+                )
+            })
+            .collect();
+
+        let width = (popup.chars.len() as u16 * 3 + 2).min(area.width);
+        let height = 3.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let paragraph = Paragraph::new(Line::from(spans))
+            .block(Block::default().borders(Borders::ALL))
+            .alignment(Alignment::Center);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(paragraph, popup_area);
     }
 
     fn build_rows(&self) -> Vec<Line<'_>> {
-        KEYBOARD_LAYOUT
+        self.rows()
             .iter()
             .enumerate()
             .map(|(row_idx, row)| self.build_row_line(row_idx, row))
@@ -31,14 +94,7 @@ impl KeyboardWidget {
             .map(|(col_idx, key)| self.build_key_span(row_idx, col_idx, key))
             .collect();
 
-        let indent = match row_idx {
-            0 => "       ",
-            1 => "        ",
-            2 => "   ",
-            3 => "   ",
-            4 => "            ",
-            _ => "",
-        };
+        let indent = self.current_layout().indent(row_idx);
         spans.insert(0, Span::raw(indent));
 
         Line::from(spans).alignment(Alignment::Left)
@@ -46,7 +102,11 @@ impl KeyboardWidget {
 
     fn build_key_span(&self, row_idx: usize, col_idx: usize, key: &str) -> Span<'_> {
         let is_shifted = matches!(self.mode, KeyboardMode::Shift | KeyboardMode::CapsLock);
-        let key_str = self.get_key_display_string(key, is_shifted);
+        let key_str = if key == SWITCH_KEY {
+            self.next_layout().label().to_string()
+        } else {
+            self.get_key_display_string(key, is_shifted)
+        };
         let style = self.get_key_style(row_idx, col_idx, key);
         Span::styled(format!(" {} ", key_str), style)
     }
@@ -62,7 +122,7 @@ impl KeyboardWidget {
         }
         // It's not a symbol, it's a char
 
-        let is_letter = key.len() == 1 && key.chars().next().unwrap().is_alphabetic();
+        let is_letter = key.chars().count() == 1 && key.chars().next().unwrap().is_alphabetic();
         if is_letter {
             return key.to_uppercase();
         }
@@ -0,0 +1,61 @@
+use crate::screens::{Kind, ScreenAction};
+use std::env;
+use std::sync::OnceLock;
+
+static KIOSK: OnceLock<bool> = OnceLock::new();
+
+/// Whether kiosk mode is enabled through `AMARU_PI_KIOSK`, for devices shown
+/// in public: nothing can be changed from the buttons, the screens changing
+/// things are hidden, and neither the remote nor the control socket runs.
+/// Scheduled updates still apply.
+pub fn is_enabled() -> bool {
+    *KIOSK.get_or_init(|| {
+        env::var("AMARU_PI_KIOSK").is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"))
+    })
+}
+
+/// Whether the screen is hidden in kiosk mode, as it mostly changes the node
+/// or the Pi.
+pub fn hides(kind: Kind) -> bool {
+    matches!(
+        kind,
+        Kind::Backup
+            | Kind::Buttons
+            | Kind::Clock
+            | Kind::Config
+            | Kind::Coredumps
+            | Kind::Events
+            | Kind::Faucet
+            | Kind::Files
+            | Kind::Firewall
+            | Kind::IpConfig
+            | Kind::Language
+            | Kind::Lte
+            | Kind::Networks
+            | Kind::Power
+            | Kind::Processes
+            | Kind::Restore
+            | Kind::Tasks
+            | Kind::Telemetry
+            | Kind::Topology
+            | Kind::Units
+            | Kind::Updates
+            | Kind::WiFiSettings
+    )
+}
+
+/// Whether the action only reads, which kiosk mode allows.
+pub fn allows(action: &ScreenAction) -> bool {
+    matches!(
+        action,
+        ScreenAction::None
+            | ScreenAction::NextScreen
+            | ScreenAction::ResetWifiConnectionStatus
+            | ScreenAction::RefreshSnapshots
+            | ScreenAction::ViewProcessLogs(..)
+            | ScreenAction::Browse(_)
+            | ScreenAction::TestPeer(_)
+            | ScreenAction::CheckPort
+            | ScreenAction::Copy(_)
+    )
+}
@@ -0,0 +1,94 @@
+use crate::util::{current_timestamp, local_minute_of_day};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use tracing::warn;
+
+const STATE_FILE_PATH: &str = "/home/pi/.amaru_peer_latency.json";
+/// Peers not seen for a week are forgotten
+const RETENTION_SECS: u64 = 7 * 24 * 60 * 60;
+/// Weight of a new sample in the hourly average, so that the heatmap follows
+/// a relay degrading within a few days
+const SAMPLE_WEIGHT: f64 = 0.1;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerLatency {
+    /// Average round trip time in milliseconds, by local hour of the day
+    pub hours: [Option<f64>; 24],
+    pub last_seen: u64,
+}
+
+/// Round trip times to the node's peers by hour of the day, to spot relays
+/// degrading at certain times.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyHistory {
+    #[serde(default)]
+    pub peers: BTreeMap<String, PeerLatency>,
+}
+
+impl LatencyHistory {
+    pub fn load() -> Self {
+        read_state().unwrap_or_else(|e| {
+            warn!("Failed to read latency history: {}", e);
+            LatencyHistory::default()
+        })
+    }
+
+    /// Adds round trip times, in milliseconds, measured now.
+    pub fn record(&mut self, samples: &[(IpAddr, f64)]) {
+        let hour = match local_minute_of_day() {
+            Ok(minute_of_day) => usize::from(minute_of_day / 60),
+            Err(e) => {
+                warn!("Failed to read local time: {}", e);
+                return;
+            }
+        };
+        let now = current_timestamp().unwrap_or(0);
+        for (addr, rtt) in samples {
+            let peer = self.peers.entry(addr.to_string()).or_default();
+            let average = &mut peer.hours[hour];
+            *average = Some(match *average {
+                Some(average) => average + SAMPLE_WEIGHT * (rtt - average),
+                None => *rtt,
+            });
+            peer.last_seen = now;
+        }
+        self.peers
+            .retain(|_, peer| now.saturating_sub(peer.last_seen) < RETENTION_SECS);
+        self.save();
+    }
+
+    /// Most recently seen first.
+    pub fn recent_peers(&self) -> Vec<(&str, &PeerLatency)> {
+        let mut peers: Vec<_> = self
+            .peers
+            .iter()
+            .map(|(addr, peer)| (addr.as_str(), peer))
+            .collect();
+        peers.sort_by(|a, b| b.1.last_seen.cmp(&a.1.last_seen).then(a.0.cmp(b.0)));
+        peers
+    }
+
+    fn save(&self) {
+        if let Err(e) = write_state(self) {
+            warn!("Failed to write latency history: {}", e);
+        }
+    }
+}
+
+fn read_state() -> Result<LatencyHistory> {
+    let path = Path::new(STATE_FILE_PATH);
+    if !path.exists() {
+        return Ok(LatencyHistory::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_state(history: &LatencyHistory) -> Result<()> {
+    fs::write(STATE_FILE_PATH, serde_json::to_string(history)?)?;
+    Ok(())
+}
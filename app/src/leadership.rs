@@ -0,0 +1,210 @@
+use crate::chain::{Network, koios_url};
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+/// Computing the schedule takes the pool's VRF key and the stake snapshot,
+/// so it is left to `cardano-cli query leadership-schedule` or `cncli
+/// leaderlog`, whose output is read from there.
+const DEFAULT_SCHEDULE_PATH: &str = "/home/pi/leader_schedule.json";
+const DEFAULT_REFRESH_SECS: u64 = 10 * 60; // 10 minutes
+/// Blocks take a while to be indexed, until then a slot isn't reported missed
+const CONFIRMATION_DELAY_SECS: u64 = 5 * 60;
+
+#[derive(Clone, Debug)]
+pub struct LeadershipConfig {
+    pub schedule_path: PathBuf,
+    /// Bech32 id of the pool, to confirm minted blocks
+    pub pool_id: Option<String>,
+    /// Base URL of a Koios compatible API
+    pub api_url: String,
+    pub network: Network,
+    pub refresh_interval: Duration,
+}
+
+impl LeadershipConfig {
+    /// Reads `AMARU_PI_LEADER_SCHEDULE`, `AMARU_PI_POOL_ID` and
+    /// `AMARU_PI_LEADERSHIP_REFRESH_SECS`.
+    pub fn from_env() -> Self {
+        let schedule_path = env::var("AMARU_PI_LEADER_SCHEDULE")
+            .unwrap_or_else(|_| DEFAULT_SCHEDULE_PATH.to_string())
+            .into();
+        let pool_id = env::var("AMARU_PI_POOL_ID")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let refresh_interval = env::var("AMARU_PI_LEADERSHIP_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_SECS));
+        Self {
+            schedule_path,
+            pool_id,
+            api_url: koios_url(),
+            network: Network::from_env(),
+            refresh_interval,
+        }
+    }
+
+    /// Only block producers have a schedule.
+    pub fn is_enabled(&self) -> bool {
+        self.pool_id.is_some() || self.schedule_path.exists()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotOutcome {
+    Upcoming,
+    Minted,
+    /// Lost a slot battle, or the node wasn't up
+    NotMinted,
+    /// Not known yet, or no pool id to check with
+    Unconfirmed,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaderSlot {
+    pub slot: u64,
+    /// UNIX time the slot starts at
+    pub time: u64,
+    pub outcome: SlotOutcome,
+}
+
+/// The slots this pool leads in the current epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LeadershipSchedule {
+    /// `None` until first read
+    pub epoch: Option<u64>,
+    pub slots: Vec<LeaderSlot>,
+}
+
+impl LeadershipSchedule {
+    pub fn next_slot(&self, now: u64) -> Option<&LeaderSlot> {
+        self.slots.iter().find(|slot| slot.time >= now)
+    }
+
+    /// Distance to the next slot led, slots lasting a second.
+    pub fn slots_to_next(&self, now: u64) -> Option<u64> {
+        self.next_slot(now).map(|slot| slot.time - now)
+    }
+
+    pub fn minted(&self) -> usize {
+        self.slots
+            .iter()
+            .filter(|slot| slot.outcome == SlotOutcome::Minted)
+            .count()
+    }
+}
+
+/// Slots of a `cardano-cli` schedule, a list of `{"slotNumber": ..}`, or of a
+/// `cncli` leaderlog, listing `{"slot": ..}` under `assignedSlots`.
+fn parse_schedule(data: &str) -> Result<Vec<u64>> {
+    let value: Value = serde_json::from_str(data)?;
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        other => other
+            .get("assignedSlots")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("Unknown leadership schedule format"))?,
+    };
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            entry
+                .get("slotNumber")
+                .or_else(|| entry.get("slot"))?
+                .as_u64()
+        })
+        .collect())
+}
+
+fn read_schedule(config: &LeadershipConfig) -> Result<Vec<u64>> {
+    match fs::read_to_string(&config.schedule_path) {
+        Ok(data) => parse_schedule(&data),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Subset of the Koios `pool_blocks` response.
+#[derive(Debug, Deserialize)]
+struct KoiosBlock {
+    abs_slot: u64,
+}
+
+/// Slots the pool minted a block in during `epoch`.
+async fn fetch_minted(
+    config: &LeadershipConfig,
+    pool_id: &str,
+    epoch: u64,
+) -> Result<BTreeSet<u64>> {
+    let url = format!("{}/pool_blocks", config.api_url.trim_end_matches('/'));
+    let blocks: Vec<KoiosBlock> = reqwest::Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(30))
+        .query(&[("_pool_bech32", pool_id), ("_epoch_no", &epoch.to_string())])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(blocks.into_iter().map(|block| block.abs_slot).collect())
+}
+
+/// Reads the schedule for the current epoch and checks which of its past
+/// slots were minted.
+pub async fn fetch(config: &LeadershipConfig) -> Result<LeadershipSchedule> {
+    let network = config.network;
+    let now = current_timestamp()?;
+    let epoch = network.epoch_for_slot(network.slot_at(now));
+    let mut slots = read_schedule(config)?;
+    // A schedule of the previous epoch is not worth showing
+    slots.retain(|slot| network.epoch_for_slot(*slot) == epoch);
+    slots.sort_unstable();
+    slots.dedup();
+
+    let past = slots
+        .first()
+        .is_some_and(|slot| network.slot_time(*slot) < now);
+    let minted = match &config.pool_id {
+        Some(pool_id) if past => match fetch_minted(config, pool_id, epoch).await {
+            Ok(minted) => Some(minted),
+            Err(e) => {
+                warn!("Failed to fetch minted blocks: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    let slots = slots
+        .into_iter()
+        .map(|slot| {
+            let time = network.slot_time(slot);
+            let outcome = match &minted {
+                _ if time >= now => SlotOutcome::Upcoming,
+                Some(minted) if minted.contains(&slot) => SlotOutcome::Minted,
+                Some(_) if time + CONFIRMATION_DELAY_SECS < now => SlotOutcome::NotMinted,
+                _ => SlotOutcome::Unconfirmed,
+            };
+            LeaderSlot {
+                slot,
+                time,
+                outcome,
+            }
+        })
+        .collect();
+    Ok(LeadershipSchedule {
+        epoch: Some(epoch),
+        slots,
+    })
+}
@@ -1,19 +1,97 @@
 pub mod actions;
+pub mod admin;
 pub mod app;
+pub mod audio;
 pub mod backends;
+pub mod backup;
+pub mod blanking;
+pub mod blocks;
+pub mod boot;
 pub mod button;
+pub mod chain;
 pub mod cli;
+pub mod clock;
+pub mod connectivity;
+pub mod control;
+pub mod coredump;
+pub mod crash;
+pub mod daemon;
+pub mod demo;
+pub mod density;
+pub mod dev;
+pub mod diagnostics;
+pub mod download;
+pub mod environment;
+pub mod epoch;
+pub mod faucet;
+pub mod files;
+pub mod firewall;
+pub mod fleet;
 pub mod frame;
+pub mod geometry;
+pub mod github;
+pub mod glance;
+pub mod i18n;
+pub mod influx;
+pub mod inputs;
+pub mod integrity;
+pub mod ip_config;
 pub mod keyboard;
+pub mod kiosk;
+pub mod latency;
+pub mod leadership;
+pub mod logging;
 pub mod logs;
 pub mod migrations;
 pub mod modal;
+pub mod modem;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
 pub mod network_status;
+pub mod node_config;
+pub mod notifications;
+pub mod offline_update;
+pub mod oplock;
+pub mod outputs;
+pub mod peers;
+pub mod pin;
+pub mod platform;
+pub mod pools;
+pub mod port_check;
+pub mod power;
+pub mod processes;
+pub mod remote;
+pub mod restore;
+pub mod rtc;
 pub mod screen_flow;
+pub mod screen_text;
 pub mod screens;
+pub mod screenshot;
+pub mod secrets;
+pub mod self_update;
+#[cfg(feature = "test-util")]
+pub mod snapshot;
+pub mod stake_pool;
+pub mod status;
+pub mod storage;
+pub mod supervisor;
+pub mod sync_eta;
 pub mod systemd;
+pub mod tasks;
+pub mod telemetry;
+pub mod thermal;
+pub mod ticker;
 pub mod top_bar;
+pub mod topology;
 pub mod tui;
 pub mod update;
+pub mod update_history;
+pub mod update_source;
+pub mod usb;
 pub mod util;
+pub mod vpn;
+pub mod watchdog;
+pub mod web_auth;
+pub mod webhook;
+pub mod widgets;
 pub mod wifi;
@@ -0,0 +1,352 @@
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use std::env;
+use std::fmt as std_fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber, info, warn};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry, fmt, reload};
+
+const DEFAULT_DIR: &str = "/home/pi/logs";
+const FILE_NAME: &str = "amaru-pi.log";
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// Rotated files kept besides the current one
+const DEFAULT_KEEP: usize = 5;
+const DEFAULT_FILTER: &str = "debug";
+/// Filter set at runtime, by `amaru-pi log-level` or the Logs screen. Gone
+/// after a reboot, back to `AMARU_PI_LOGS_LEVEL`
+const LEVEL_PATH: &str = "/tmp/amaru_pi_log_level";
+const LEVEL_POLL: Duration = Duration::from_secs(2);
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+/// Levels cycled through on the Logs screen, least verbose first
+pub const LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// Filter last applied
+static APPLIED: Mutex<String> = Mutex::new(String::new());
+/// Screen shown by the UI, added to JSON logs
+static SCREEN: Mutex<Option<String>> = Mutex::new(None);
+
+/// How log lines are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// For people, as the journal shows them
+    #[default]
+    Text,
+    /// A JSON object per line, for Loki or ELK
+    Json,
+}
+
+/// Notes the screen shown, for the logs.
+pub fn set_screen(screen: &str) {
+    if let Ok(mut shown) = SCREEN.lock() {
+        *shown = Some(screen.to_string());
+    }
+}
+
+/// Fields of an event, the message as `event`.
+struct JsonFields(Map<String, Value>);
+
+impl JsonFields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let name = match field.name() {
+            "message" => "event",
+            name => name,
+        };
+        self.0.insert(name.to_string(), value);
+    }
+}
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std_fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+}
+
+/// Writes events as JSON objects with their `timestamp`, in milliseconds,
+/// `level`, `subsystem`, the module they come from, `screen`, in the UI, and
+/// `event`, the message, besides their own fields.
+struct JsonLayer<W> {
+    writer: W,
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut fields = JsonFields(Map::new());
+        event.record(&mut fields);
+        let mut line = fields.0;
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|since| since.as_millis() as u64)
+            .unwrap_or_default();
+        line.insert("timestamp".to_string(), timestamp.into());
+        line.insert("level".to_string(), metadata.level().as_str().into());
+        let target = metadata.target();
+        let subsystem = target.strip_prefix("amaru_pi::").unwrap_or(target);
+        line.insert("subsystem".to_string(), subsystem.into());
+        if let Ok(screen) = SCREEN.lock()
+            && let Some(screen) = screen.as_ref()
+        {
+            line.insert("screen".to_string(), screen.as_str().into());
+        }
+        let mut writer = self.writer.make_writer();
+        let _ = writeln!(writer, "{}", Value::Object(line));
+    }
+}
+
+/// A log file rotated once a day (UTC) or once it grows past a size, as
+/// `amaru-pi.log.1` to `amaru-pi.log.<keep>`, the oldest dropped.
+struct RotatingFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    size: u64,
+    day: u64,
+}
+
+fn today() -> u64 {
+    current_timestamp().unwrap_or_default() / SECS_PER_DAY
+}
+
+impl RotatingFile {
+    fn open(dir: PathBuf, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(FILE_NAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        // A file left from a previous day is rotated on the first write
+        let day = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or_else(today, |since| since.as_secs() / SECS_PER_DAY);
+        Ok(Self {
+            dir,
+            max_bytes,
+            keep,
+            file,
+            size: metadata.len(),
+            day,
+        })
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("{}.{}", FILE_NAME, index))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let _ = fs::remove_file(self.rotated(self.keep));
+        for index in (1..self.keep).rev() {
+            let _ = fs::rename(self.rotated(index), self.rotated(index + 1));
+        }
+        let path = self.dir.join(FILE_NAME);
+        if self.keep > 0 {
+            fs::rename(&path, self.rotated(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)?;
+        self.size = 0;
+        self.day = today();
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && (self.size + buf.len() as u64 > self.max_bytes || today() != self.day) {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Reads `AMARU_PI_LOG_DIR`, `off` to only log to stderr.
+fn log_dir() -> Option<PathBuf> {
+    let dir = env::var("AMARU_PI_LOG_DIR").unwrap_or_else(|_| DEFAULT_DIR.to_string());
+    match dir.trim() {
+        "" | "off" => None,
+        dir => Some(PathBuf::from(dir)),
+    }
+}
+
+/// The log files written, the current one first.
+pub fn log_files() -> Vec<PathBuf> {
+    let Some(dir) = log_dir() else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with(FILE_NAME))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Reads `AMARU_PI_LOG_MAX_BYTES` and `AMARU_PI_LOG_FILES`, how many rotated
+/// files are kept.
+fn log_file() -> Option<RotatingFile> {
+    let dir = log_dir()?;
+    let max_bytes = env::var("AMARU_PI_LOG_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_BYTES);
+    let keep = env::var("AMARU_PI_LOG_FILES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_KEEP);
+    match RotatingFile::open(dir.clone(), max_bytes, keep) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Not logging to {}: {}", dir.display(), e);
+            None
+        }
+    }
+}
+
+/// The filter set at runtime, if any.
+fn runtime_filter() -> Option<String> {
+    fs::read_to_string(LEVEL_PATH)
+        .ok()
+        .map(|filter| filter.trim().to_string())
+        .filter(|filter| !filter.is_empty())
+}
+
+/// The filter in use: set at runtime, else `AMARU_PI_LOGS_LEVEL`.
+pub fn current_filter() -> String {
+    runtime_filter()
+        .or_else(|| env::var("AMARU_PI_LOGS_LEVEL").ok())
+        .unwrap_or_else(|| DEFAULT_FILTER.to_string())
+}
+
+fn filter() -> EnvFilter {
+    EnvFilter::try_new(current_filter()).unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER))
+}
+
+/// Logs to stderr, for the journal, and to a rotating file under
+/// `/home/pi/logs`, both in `format`. The filter follows the one set at
+/// runtime.
+pub fn init(format: LogFormat) {
+    let (filter, handle) = reload::Layer::new(filter());
+    let file = log_file().map(Mutex::new);
+    let registry = tracing_subscriber::registry().with(filter);
+    match format {
+        LogFormat::Text => registry
+            .with(fmt::layer().with_writer(io::stderr))
+            .with(file.map(|file| fmt::layer().with_ansi(false).with_writer(file)))
+            .init(),
+        LogFormat::Json => registry
+            .with(JsonLayer { writer: io::stderr })
+            .with(file.map(|writer| JsonLayer { writer }))
+            .init(),
+    }
+    let _ = FILTER.set(handle);
+    if let Ok(mut applied) = APPLIED.lock() {
+        *applied = current_filter();
+    }
+    thread::spawn(watch_filter);
+}
+
+/// Applies the filters set by other processes, such as the CLI.
+fn watch_filter() {
+    loop {
+        thread::sleep(LEVEL_POLL);
+        apply();
+    }
+}
+
+fn apply() {
+    let Some(handle) = FILTER.get() else {
+        return;
+    };
+    let current = current_filter();
+    let Ok(mut applied) = APPLIED.lock() else {
+        return;
+    };
+    if *applied == current {
+        return;
+    }
+    match handle.reload(filter()) {
+        Ok(()) => info!("Logging {}", current),
+        Err(e) => warn!("Failed to change the log filter: {}", e),
+    }
+    *applied = current;
+}
+
+/// Changes what is logged until the Pi reboots, e.g. `info` or
+/// `amaru_pi=trace`. `default` goes back to `AMARU_PI_LOGS_LEVEL`.
+pub fn set_filter(filter: &str) -> Result<()> {
+    let filter = filter.trim();
+    if filter == "default" {
+        match fs::remove_file(LEVEL_PATH) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    } else {
+        EnvFilter::try_new(filter).map_err(|e| anyhow!("Invalid filter {}: {}", filter, e))?;
+        fs::write(LEVEL_PATH, filter)?;
+    }
+    apply();
+    Ok(())
+}
+
+/// The next of the levels, after the one in use.
+pub fn next_level() -> &'static str {
+    let current = current_filter();
+    let index = LEVELS
+        .iter()
+        .position(|level| current.eq_ignore_ascii_case(level));
+    LEVELS[index.map_or(0, |i| (i + 1) % LEVELS.len())]
+}
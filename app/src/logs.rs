@@ -1,3 +1,5 @@
+use crate::dev;
+use crate::supervisor;
 use LogLevel::*;
 use serde::{Deserialize, Serialize};
 #[cfg(not(feature = "display_hat"))]
@@ -150,29 +152,56 @@ pub struct JournalReader {
     service: String,
     #[cfg(feature = "display_hat")]
     last_cursor: Option<String>,
+    /// Position in the mock node journal, in dev mode, or in the output of
+    /// the supervised node
+    mock_cursor: usize,
+    /// Reads the output of the node captured by the supervisor
+    supervised: bool,
 }
 
 impl JournalReader {
     #[cfg(not(feature = "display_hat"))]
-    pub fn new(_service: impl Into<String>) -> Self {
-        Self {}
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            mock_cursor: 0,
+            supervised: supervisor::for_service(&service.into()).is_some(),
+        }
     }
 
     #[cfg(feature = "display_hat")]
     pub fn new(service: impl Into<String>) -> Self {
+        let service = service.into();
         Self {
-            service: service.into(),
+            supervised: supervisor::for_service(&service).is_some(),
+            service,
             last_cursor: None,
+            mock_cursor: 0,
         }
     }
 
     #[cfg(not(feature = "display_hat"))]
     pub fn next_lines(&mut self) -> anyhow::Result<Vec<String>> {
+        if dev::is_mocked() {
+            return Ok(dev::mock_node().lines_since(&mut self.mock_cursor));
+        }
+        if self.supervised
+            && let Some(supervisor) = supervisor::get()
+        {
+            return Ok(supervisor.lines_since(&mut self.mock_cursor));
+        }
         Ok(vec![serde_json::to_string(&random_log_entry()).unwrap()])
     }
 
     #[cfg(feature = "display_hat")]
     pub fn next_lines(&mut self) -> anyhow::Result<Vec<String>> {
+        if dev::is_mocked() {
+            return Ok(dev::mock_node().lines_since(&mut self.mock_cursor));
+        }
+        if self.supervised
+            && let Some(supervisor) = supervisor::get()
+        {
+            return Ok(supervisor.lines_since(&mut self.mock_cursor));
+        }
         let mut cmd = Command::new("journalctl");
         cmd.arg("-u")
             .arg(&self.service)
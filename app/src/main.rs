@@ -1,16 +1,7 @@
-use amaru_pi::{cli, migrations};
-use std::{error::Error, io};
-use tracing_subscriber::EnvFilter;
+use amaru_pi::cli;
+use std::error::Error;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_env("AMARU_PI_LOGS_LEVEL")
-                .unwrap_or_else(|_| EnvFilter::new("debug")),
-        )
-        .with_writer(io::stderr)
-        .init();
-    migrations::run_all();
     cli::handle().await
 }
@@ -0,0 +1,19 @@
+use tracing::{debug, info};
+
+use crate::coredump;
+use crate::rtc;
+
+pub fn run() -> anyhow::Result<()> {
+    debug!("Checking core dump settings...");
+
+    if coredump::configure()? {
+        info!("Core dumps of amaru now kept up to {}", coredump::max_use());
+    }
+
+    debug!("Checking the RTC...");
+    if rtc::configure()? {
+        info!("RTC overlay added, the RTC is used from the next reboot");
+    }
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
 pub mod m2025_12;
+pub mod m2026_10;
 
-const MIGRATIONS: &[(&str, fn() -> Result<(), anyhow::Error>)] = &[("2025_12", m2025_12::run)];
+const MIGRATIONS: &[(&str, fn() -> Result<(), anyhow::Error>)] =
+    &[("2025_12", m2025_12::run), ("2026_10", m2026_10::run)];
 
 pub fn run_all() {
     println!("Starting Migrations...");
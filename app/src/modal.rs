@@ -1,27 +1,57 @@
 use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::dev::{self, Fault};
+use crate::i18n::tr;
+use crate::notifications::{Notification, Severity};
+use crate::pin::{Guarded, PinLock, PinPrompt};
 use crate::update::UpdateManager;
 use crate::util::centered_rect;
+use crate::widgets::dialog::Dialog;
+use crate::widgets::grid::{self, Edge};
+use qrcode::QrCode;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use tui_qrcode::{Colors, QrCodeWidget};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub enum Modal {
     #[default]
     None,
     UpdatePopup(Vec<String>),
+    Notification(Notification),
+    /// Fault injection menu, with the index of the selected fault
+    DevMenu(usize),
+    PinEntry(PinPrompt),
+    /// Sign in page of a captive portal, shown as a QR code
+    CaptivePortal(String),
+    /// Asks before a destructive action, answered for the app to run it
+    Confirm(Dialog),
 }
 
 impl Modal {
     /// Returns `true` if the input was handled, `false` otherwise.
-    pub fn handle_input(&mut self, event: InputEvent, update_manager: &mut UpdateManager) -> bool {
+    /// `pin_lock` is set when guarded actions need the PIN first.
+    pub fn handle_input(
+        &mut self,
+        event: InputEvent,
+        update_manager: &mut UpdateManager,
+        pin_lock: Option<&PinLock>,
+    ) -> bool {
         match self {
             Modal::None => false, // Not handled
             Modal::UpdatePopup(_) => {
                 match (event.id, event.press_type) {
                     (ButtonId::A, ButtonPress::Short) => {
                         println!("Received update request");
-                        UpdateManager::request_update().ok();
-                        *self = Modal::None; // Close the modal
+                        match pin_lock {
+                            Some(pin_lock) => {
+                                *self =
+                                    Modal::PinEntry(PinPrompt::new(Guarded::ApplyUpdate, pin_lock));
+                            }
+                            None => {
+                                update_manager.request_update().ok();
+                                *self = Modal::None; // Close the modal
+                            }
+                        }
                     }
                     (ButtonId::B, ButtonPress::Short) => {
                         println!("Received snooze request");
@@ -32,6 +62,38 @@ impl Modal {
                 }
                 true // Handled
             }
+            Modal::Notification(_) | Modal::CaptivePortal(_) => {
+                // Any short press dismisses the notification
+                if event.press_type == ButtonPress::Short {
+                    *self = Modal::None;
+                }
+                true // Handled
+            }
+            Modal::DevMenu(selected) => {
+                match (event.id, event.press_type) {
+                    (ButtonId::X, ButtonPress::Short) => {
+                        *selected = grid::step(*selected, Fault::ALL.len(), 1, Edge::Wrap);
+                    }
+                    (ButtonId::A, ButtonPress::Short) => {
+                        dev::mock_node().toggle(Fault::ALL[*selected]);
+                    }
+                    (ButtonId::B, ButtonPress::Short) => {
+                        *self = Modal::None; // Close the modal
+                    }
+                    _ => {}
+                }
+                true // Handled
+            }
+            Modal::PinEntry(prompt) => {
+                // Checked by the app once complete
+                prompt.handle_input(event);
+                true // Handled
+            }
+            Modal::Confirm(dialog) => {
+                // Taken by the app once answered
+                dialog.handle_input(event);
+                true // Handled
+            }
         }
     }
 
@@ -41,6 +103,19 @@ impl Modal {
             Modal::UpdatePopup(app_names) => {
                 render_update_popup(frame, app_names);
             }
+            Modal::Notification(notification) => {
+                render_notification_popup(frame, notification);
+            }
+            Modal::DevMenu(selected) => {
+                render_dev_menu(frame, *selected);
+            }
+            Modal::PinEntry(prompt) => {
+                render_pin_prompt(frame, prompt);
+            }
+            Modal::CaptivePortal(url) => {
+                render_captive_portal(frame, url);
+            }
+            Modal::Confirm(dialog) => dialog.render(frame),
         }
     }
 
@@ -53,9 +128,9 @@ fn render_update_popup(frame: &mut Frame, app_names: &[String]) {
     let mut text: Vec<Line> = Vec::new();
 
     if app_names.is_empty() {
-        text.push(Line::from("A system update is available").alignment(Alignment::Center));
+        text.push(Line::from(tr("A system update is available")).alignment(Alignment::Center));
     } else if app_names.len() == 1 {
-        text.push(Line::from("An update is available for:").alignment(Alignment::Center));
+        text.push(Line::from(tr("An update is available for:")).alignment(Alignment::Center));
         text.push(
             Line::from(Span::styled(
                 app_names[0].clone(),
@@ -66,7 +141,7 @@ fn render_update_popup(frame: &mut Frame, app_names: &[String]) {
             .alignment(Alignment::Center),
         );
     } else {
-        text.push(Line::from("Updates are available for:").alignment(Alignment::Center));
+        text.push(Line::from(tr("Updates are available for:")).alignment(Alignment::Center));
         let app_list = app_names.join(", ");
         text.push(
             Line::from(Span::styled(
@@ -80,14 +155,14 @@ fn render_update_popup(frame: &mut Frame, app_names: &[String]) {
     }
 
     text.push(Line::from(""));
-    text.push(Line::from("Do you want to restart and apply it?").alignment(Alignment::Center));
+    text.push(Line::from(tr("Do you want to restart and apply it?")).alignment(Alignment::Center));
     text.push(Line::from(""));
     text.push(Line::from(vec![Span::styled(
-        "[A] Yes, restart now",
+        tr("[A] Yes, restart now"),
         Style::default().fg(Color::Green),
     )]));
     text.push(Line::from(vec![Span::styled(
-        "[B] No, remind me in 48 hours",
+        tr("[B] No, remind me in 48 hours"),
         Style::default().fg(Color::Yellow),
     )]));
 
@@ -106,3 +181,168 @@ fn render_update_popup(frame: &mut Frame, app_names: &[String]) {
     frame.render_widget(Clear, area);
     frame.render_widget(paragraph, area);
 }
+
+fn render_notification_popup(frame: &mut Frame, notification: &Notification) {
+    let color = match notification.severity {
+        Severity::Info => Color::Cyan,
+        Severity::Warning => Color::Yellow,
+        Severity::Critical => Color::Red,
+    };
+
+    let mut text: Vec<Line> = vec![Line::from("")];
+    text.extend(
+        notification
+            .lines
+            .iter()
+            .map(|line| Line::from(line.as_str()).alignment(Alignment::Center)),
+    );
+    text.push(Line::from(""));
+    text.push(Line::from(tr("Press any button to dismiss.")).alignment(Alignment::Center));
+
+    let block = Block::default()
+        .title(format!(" {} ", notification.title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title_alignment(Alignment::Center);
+
+    let area = centered_rect(80, 60, frame.area());
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(ratatui::widgets::Wrap { trim: true });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn render_dev_menu(frame: &mut Frame, selected: usize) {
+    let node = dev::mock_node();
+    let mut text: Vec<Line> = vec![Line::from("")];
+    for (i, fault) in Fault::ALL.iter().enumerate() {
+        let checkbox = if node.is_active(*fault) { "[x]" } else { "[ ]" };
+        let style = if i == selected {
+            Style::default().fg(Color::Black).bg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+        text.push(Line::from(Span::styled(
+            format!(" {} {} ", checkbox, fault.label()),
+            style,
+        )));
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(format!(
+        "Peers: {}  Disk: {}%",
+        node.peers(),
+        node.disk_usage()
+    )));
+    text.push(Line::from(""));
+    text.push(Line::from(tr("[X] Next  [A] Toggle  [B] Close")).alignment(Alignment::Center));
+
+    let block = Block::default()
+        .title(" Fault Injection ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta))
+        .title_alignment(Alignment::Center);
+
+    let area = centered_rect(80, 60, frame.area());
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(ratatui::widgets::Wrap { trim: true });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn render_pin_prompt(frame: &mut Frame, prompt: &PinPrompt) {
+    let mut text: Vec<Line> = vec![
+        Line::from(""),
+        Line::from(format!("Enter PIN to {}", prompt.guarded.label())),
+        Line::from(""),
+    ];
+
+    // Entered digits are hidden, the one being picked is shown
+    let mut spans: Vec<Span> = prompt.digits.chars().map(|_| Span::raw("* ")).collect();
+    if !prompt.is_full() {
+        spans.push(Span::styled(
+            prompt.current.to_string(),
+            Style::default().fg(Color::Black).bg(Color::Cyan),
+        ));
+    }
+    text.push(Line::from(spans));
+    text.push(Line::from(""));
+
+    if let Some(remaining) = prompt.lockout_remaining() {
+        text.push(
+            Line::from(format!("Locked, retry in {}s", remaining.as_secs() + 1)).fg(Color::Red),
+        );
+    } else if let Some(error) = &prompt.error {
+        text.push(Line::from(error.as_str()).fg(Color::Red));
+    } else {
+        text.push(Line::from(""));
+    }
+    text.push(Line::from(""));
+    text.push(
+        Line::from(tr("[X/Y] Digit  [A] Enter  [Hold A] Done  [B] Delete"))
+            .alignment(Alignment::Center),
+    );
+
+    let block = Block::default()
+        .title(" PIN Required ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title_alignment(Alignment::Center);
+
+    let area = centered_rect(80, 60, frame.area());
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(ratatui::widgets::Wrap { trim: true });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+fn render_captive_portal(frame: &mut Frame, url: &str) {
+    let block = Block::default()
+        .title(" WiFi Sign In ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title_alignment(Alignment::Center);
+
+    let area = centered_rect(90, 90, frame.area());
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [help_area, qr_area, url_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(0),
+        Constraint::Length(3),
+    ])
+    .areas(inner);
+
+    frame.render_widget(
+        Paragraph::new(tr("This network needs a sign in, peers can't be reached"))
+            .alignment(Alignment::Center)
+            .wrap(ratatui::widgets::Wrap { trim: true }),
+        help_area,
+    );
+    // Can only fail for URLs too long to encode
+    if let Ok(qr_code) = QrCode::new(url) {
+        frame.render_widget(QrCodeWidget::new(qr_code).colors(Colors::Inverted), qr_area);
+    }
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(url).fg(Color::Cyan),
+            Line::from(tr("Scan to sign in, any button to dismiss")),
+        ])
+        .alignment(Alignment::Center)
+        .wrap(ratatui::widgets::Wrap { trim: true }),
+        url_area,
+    );
+}
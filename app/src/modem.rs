@@ -0,0 +1,171 @@
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+#[cfg(feature = "display_hat")]
+use serde_json::Value;
+
+/// NetworkManager connection used for the cellular data link
+#[cfg(feature = "display_hat")]
+const CONNECTION_NAME: &str = "lte";
+/// Higher than WiFi and ethernet, so cellular data is only used when
+/// nothing else is available
+#[cfg(feature = "display_hat")]
+const ROUTE_METRIC: &str = "1000";
+/// Lower than every other link, used while failed over so traffic leaves a
+/// primary link that is up but not reaching the internet
+#[cfg(feature = "display_hat")]
+const FAILOVER_ROUTE_METRIC: &str = "50";
+
+/// State of a USB LTE modem, as reported by ModemManager.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ModemStatus {
+    pub present: bool,
+    pub model: String,
+    /// e.g. `registered` or `connected`
+    pub state: String,
+    pub operator: String,
+    /// e.g. `lte`
+    pub access_tech: String,
+    /// 0-100
+    pub signal: u8,
+    pub apn: String,
+    /// Data used since the bearer was connected
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl ModemStatus {
+    pub fn is_connected(&self) -> bool {
+        self.state == "connected"
+    }
+}
+
+/// mmcli reports numbers as strings
+#[cfg(feature = "display_hat")]
+fn number(value: &Value) -> u64 {
+    value.as_str().and_then(|s| s.parse().ok()).unwrap_or(0)
+}
+
+#[cfg(feature = "display_hat")]
+fn text(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+/// Trailing index of a ModemManager object path, as expected by mmcli.
+#[cfg(feature = "display_hat")]
+fn object_index(path: &Value) -> Option<&str> {
+    path.as_str()?.rsplit('/').next()
+}
+
+#[cfg(feature = "display_hat")]
+pub fn check_modem_status() -> anyhow::Result<ModemStatus> {
+    let list: Value = serde_json::from_str(&run_and_capture("mmcli", ["-L", "-J"].to_vec())?)?;
+    let Some(index) = list["modem-list"].get(0).and_then(object_index) else {
+        return Ok(ModemStatus::default());
+    };
+
+    let modem: Value =
+        serde_json::from_str(&run_and_capture("mmcli", ["-m", index, "-J"].to_vec())?)?;
+    let generic = &modem["modem"]["generic"];
+    let mut status = ModemStatus {
+        present: true,
+        model: text(&generic["model"]),
+        state: text(&generic["state"]),
+        operator: text(&modem["modem"]["3gpp"]["operator-name"]),
+        access_tech: generic["access-technologies"]
+            .as_array()
+            .map(|techs| techs.iter().map(text).collect::<Vec<_>>().join(","))
+            .unwrap_or_default(),
+        signal: number(&generic["signal-quality"]["value"]).min(100) as u8,
+        ..ModemStatus::default()
+    };
+
+    if let Some(bearer) = generic["bearers"].get(0).and_then(object_index) {
+        let bearer: Value =
+            serde_json::from_str(&run_and_capture("mmcli", ["-b", bearer, "-J"].to_vec())?)?;
+        let bearer = &bearer["bearer"];
+        status.apn = text(&bearer["properties"]["apn"]);
+        status.rx_bytes = number(&bearer["stats"]["rx-bytes"]);
+        status.tx_bytes = number(&bearer["stats"]["tx-bytes"]);
+    }
+
+    Ok(status)
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn check_modem_status() -> anyhow::Result<ModemStatus> {
+    Ok(ModemStatus::default())
+}
+
+/// (Re)creates the cellular connection with the given APN. It connects on
+/// its own, but routes are only preferred when WiFi and ethernet are down.
+#[cfg(feature = "display_hat")]
+pub fn configure_apn(apn: &str) -> anyhow::Result<()> {
+    // Ignore failure, the connection may not exist yet
+    let _ = run_and_capture("nmcli", ["con", "delete", CONNECTION_NAME].to_vec());
+
+    run_and_capture(
+        "nmcli",
+        [
+            "con",
+            "add",
+            "type",
+            "gsm",
+            "ifname",
+            "*",
+            "con-name",
+            CONNECTION_NAME,
+            "apn",
+            apn,
+            "ipv4.route-metric",
+            ROUTE_METRIC,
+            "ipv6.route-metric",
+            ROUTE_METRIC,
+        ]
+        .to_vec(),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn configure_apn(_apn: &str) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "display_hat")]
+fn set_route_metric(metric: &str) -> anyhow::Result<()> {
+    run_and_capture(
+        "nmcli",
+        [
+            "con",
+            "modify",
+            CONNECTION_NAME,
+            "ipv4.route-metric",
+            metric,
+            "ipv6.route-metric",
+            metric,
+        ]
+        .to_vec(),
+    )?;
+    Ok(())
+}
+
+/// Brings the cellular connection up ahead of every other link, or takes it
+/// back down once the primary link has recovered.
+#[cfg(feature = "display_hat")]
+pub fn set_failover(active: bool) -> anyhow::Result<()> {
+    if active {
+        set_route_metric(FAILOVER_ROUTE_METRIC)?;
+        run_and_capture("nmcli", ["con", "up", CONNECTION_NAME].to_vec())?;
+    } else {
+        // Ignore failure, the connection may already be down
+        let _ = run_and_capture("nmcli", ["con", "down", CONNECTION_NAME].to_vec());
+        set_route_metric(ROUTE_METRIC)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn set_failover(_active: bool) -> anyhow::Result<()> {
+    Ok(())
+}
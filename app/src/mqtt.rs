@@ -0,0 +1,256 @@
+use crate::chain::Network;
+use crate::notifications::{Notification, NotificationChannel};
+use crate::screens::SystemState;
+use crate::secrets;
+use crate::status::{hostname, status_json};
+use anyhow::Result;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use serde_json::{Value, json};
+use std::env;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_DISCOVERY_PREFIX: &str = "homeassistant";
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+/// Publications queued while the broker is unreachable
+const QUEUE_CAPACITY: usize = 32;
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Sensors announced to Home Assistant: key in the state, name, unit and
+/// device class.
+const SENSORS: &[(&str, &str, Option<&str>, Option<&str>)] = &[
+    ("node", "Node", None, None),
+    ("sync", "Sync", Some("%"), None),
+    ("tip", "Tip", None, None),
+    (
+        "temperature",
+        "Temperature",
+        Some("°C"),
+        Some("temperature"),
+    ),
+    ("fan", "Fan", Some("%"), None),
+    ("disk_free", "Disk free", Some("%"), None),
+    ("battery", "Battery", Some("%"), Some("battery")),
+];
+
+/// Broker and topics, from the environment.
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    host: String,
+    port: u16,
+    credentials: Option<(String, String)>,
+    /// Of the topics of this node, e.g. `amaru-pi/<hostname>`
+    prefix: String,
+    discovery_prefix: String,
+    interval: Duration,
+    node_id: String,
+}
+
+impl MqttConfig {
+    /// Reads `AMARU_PI_MQTT_HOST`, without which nothing is published, and
+    /// `AMARU_PI_MQTT_PORT`, `AMARU_PI_MQTT_USER`, `AMARU_PI_MQTT_PASSWORD`,
+    /// preferably kept in the secrets store,
+    /// `AMARU_PI_MQTT_PREFIX`, `AMARU_PI_MQTT_DISCOVERY_PREFIX` and
+    /// `AMARU_PI_MQTT_INTERVAL_SECS`.
+    pub fn from_env() -> Option<Self> {
+        let host = env::var("AMARU_PI_MQTT_HOST")
+            .ok()
+            .filter(|host| !host.trim().is_empty())?;
+        let node_id = hostname();
+        let credentials = match (
+            env::var("AMARU_PI_MQTT_USER"),
+            secrets::get_or_env("AMARU_PI_MQTT_PASSWORD"),
+        ) {
+            (Ok(user), Some(password)) => Some((user, password)),
+            _ => None,
+        };
+        Some(Self {
+            host: host.trim().to_string(),
+            port: env::var("AMARU_PI_MQTT_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_PORT),
+            credentials,
+            prefix: env::var("AMARU_PI_MQTT_PREFIX")
+                .unwrap_or_else(|_| format!("amaru-pi/{}", node_id)),
+            discovery_prefix: env::var("AMARU_PI_MQTT_DISCOVERY_PREFIX")
+                .unwrap_or_else(|_| DEFAULT_DISCOVERY_PREFIX.to_string()),
+            interval: env::var("AMARU_PI_MQTT_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_INTERVAL),
+            node_id,
+        })
+    }
+
+    fn topic(&self, name: &str) -> String {
+        format!("{}/{}", self.prefix, name)
+    }
+
+    /// Home Assistant discovery messages, a topic and payload per sensor.
+    fn discovery(&self) -> Vec<(String, Value)> {
+        let device = json!({
+            "identifiers": [format!("amaru_pi_{}", self.node_id)],
+            "name": format!("Amaru {}", self.node_id),
+            "manufacturer": "Amaru",
+            "model": "amaru-pi",
+            "sw_version": env!("CARGO_PKG_VERSION"),
+        });
+        let mut messages: Vec<(String, Value)> = SENSORS
+            .iter()
+            .map(|(key, name, unit, class)| {
+                let mut config = json!({
+                    "name": name,
+                    "unique_id": format!("amaru_pi_{}_{}", self.node_id, key),
+                    "state_topic": self.topic("state"),
+                    "value_template": format!("{{{{ value_json.{} }}}}", key),
+                    "availability_topic": self.topic("availability"),
+                    "device": device,
+                });
+                if let Some(unit) = unit {
+                    config["unit_of_measurement"] = json!(unit);
+                }
+                if let Some(class) = class {
+                    config["device_class"] = json!(class);
+                }
+                let topic = format!(
+                    "{}/sensor/amaru_pi_{}/{}/config",
+                    self.discovery_prefix, self.node_id, key
+                );
+                (topic, config)
+            })
+            .collect();
+        messages.push((
+            format!(
+                "{}/sensor/amaru_pi_{}/alert/config",
+                self.discovery_prefix, self.node_id
+            ),
+            json!({
+                "name": "Last alert",
+                "unique_id": format!("amaru_pi_{}_alert", self.node_id),
+                "state_topic": self.topic("events"),
+                "value_template": "{{ value_json.title }}",
+                "json_attributes_topic": self.topic("events"),
+                "availability_topic": self.topic("availability"),
+                "device": device,
+            }),
+        ));
+        messages
+    }
+}
+
+/// Publishes the status of the node, and its notifications, to an MQTT
+/// broker. The node shows as a device in Home Assistant.
+pub struct Mqtt {
+    client: AsyncClient,
+    config: MqttConfig,
+    network: Network,
+    last_published: Option<Instant>,
+}
+
+impl Mqtt {
+    /// Connects in the background, `None` unless configured.
+    pub fn from_env() -> Option<Self> {
+        let config = MqttConfig::from_env()?;
+        let mut options = MqttOptions::new(
+            format!("amaru-pi-{}", config.node_id),
+            config.host.clone(),
+            config.port,
+        );
+        options.set_keep_alive(KEEP_ALIVE);
+        options.set_last_will(LastWill::new(
+            config.topic("availability"),
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let Some((user, password)) = &config.credentials {
+            options.set_credentials(user, password);
+        }
+        let (client, mut event_loop) = AsyncClient::new(options, QUEUE_CAPACITY);
+
+        let announcer = client.clone();
+        let discovery = config.discovery();
+        let availability = config.topic("availability");
+        tokio::spawn(async move {
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        info!("Connected to the MQTT broker");
+                        // Announced again on each connection, brokers may
+                        // have restarted without persistence
+                        for (topic, payload) in &discovery {
+                            let _ = announcer
+                                .publish(topic, QoS::AtLeastOnce, true, payload.to_string())
+                                .await;
+                        }
+                        let _ = announcer
+                            .publish(&availability, QoS::AtLeastOnce, true, "online")
+                            .await;
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("MQTT connection failed: {}", e);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        Some(Self {
+            client,
+            config,
+            network: Network::from_env(),
+            last_published: None,
+        })
+    }
+
+    /// Delivers notifications to the events topic.
+    pub fn channel(&self) -> MqttChannel {
+        MqttChannel {
+            client: self.client.clone(),
+            topic: self.config.topic("events"),
+        }
+    }
+
+    /// Publishes the state once the interval elapsed, retained so that it
+    /// shows as soon as Home Assistant starts.
+    pub fn publish_state(&mut self, system: &SystemState) {
+        if self
+            .last_published
+            .is_some_and(|last| last.elapsed() < self.config.interval)
+        {
+            return;
+        }
+        self.last_published = Some(Instant::now());
+        let payload = status_json(system, &self.network).to_string();
+        if let Err(e) =
+            self.client
+                .try_publish(self.config.topic("state"), QoS::AtMostOnce, true, payload)
+        {
+            warn!("Failed to publish the state over MQTT: {}", e);
+        }
+    }
+}
+
+/// Publishes notifications as JSON, not retained.
+pub struct MqttChannel {
+    client: AsyncClient,
+    topic: String,
+}
+
+impl NotificationChannel for MqttChannel {
+    fn name(&self) -> &str {
+        "mqtt"
+    }
+
+    fn send(&mut self, notification: &Notification) -> Result<()> {
+        let payload = serde_json::to_string(notification)?;
+        self.client
+            .try_publish(&self.topic, QoS::AtLeastOnce, false, payload)?;
+        Ok(())
+    }
+}
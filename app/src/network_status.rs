@@ -1,11 +1,13 @@
 use crate::wifi::{Connectivity, NetworkState, NetworkStatus, check_network_status};
 use std::time::{Duration, Instant};
 
-fn check_network_status_or_unknown() -> NetworkStatus {
+pub fn check_network_status_or_unknown() -> NetworkStatus {
     check_network_status().unwrap_or(NetworkStatus {
         state: NetworkState::Unknown,
         connectivity: Connectivity::Unknown,
         resolving: false,
+        ipv4: None,
+        ipv6: None,
     })
 }
 pub struct NetworkStatusCache {
@@ -23,11 +25,13 @@ impl NetworkStatusCache {
         }
     }
 
-    pub async fn get(&mut self) -> NetworkStatus {
-        if self.last_check.elapsed() >= self.interval {
-            self.last_result = check_network_status_or_unknown();
-            self.last_check = Instant::now();
+    /// Whether the last check is old enough for another, noting that one
+    /// starts when so.
+    pub fn start_check(&mut self) -> bool {
+        if self.last_check.elapsed() < self.interval {
+            return false;
         }
-        self.last_result
+        self.last_check = Instant::now();
+        true
     }
 }
@@ -0,0 +1,200 @@
+use anyhow::{Result, anyhow};
+use std::fs::{self, File};
+use std::os::unix::fs::{MetadataExt, chown};
+use std::path::Path;
+use tracing::info;
+
+/// Read by both amaru and amaru-pi through their units' `EnvironmentFile`
+const ENV_FILE_PATH: &str = "/home/pi/amaru.env";
+/// The file as it was before the last save
+const BACKUP_FILE_PATH: &str = "/home/pi/amaru.env.bak";
+
+pub const NETWORKS: [&str; 3] = ["mainnet", "preprod", "preview"];
+
+/// Where the config screen is at once changes are submitted.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ConfigStatus {
+    #[default]
+    Idle,
+    Saving,
+    /// Saved, the node still runs with the previous config
+    Saved,
+    Restarting,
+}
+
+/// A setting of the node the config screen can edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Network,
+    ListenAddress,
+    Peers,
+    ChainDir,
+    LedgerDir,
+}
+
+impl Field {
+    pub const ALL: [Field; 5] = [
+        Field::Network,
+        Field::ListenAddress,
+        Field::Peers,
+        Field::ChainDir,
+        Field::LedgerDir,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Field::Network => "Network",
+            Field::ListenAddress => "Listen address",
+            Field::Peers => "Peers",
+            Field::ChainDir => "Chain dir",
+            Field::LedgerDir => "Ledger dir",
+        }
+    }
+
+    /// The variable amaru reads the setting from.
+    fn var(&self) -> &'static str {
+        match self {
+            Field::Network => "AMARU_NETWORK",
+            Field::ListenAddress => "AMARU_LISTEN_ADDRESS",
+            Field::Peers => "AMARU_PEER_ADDRESS",
+            Field::ChainDir => "AMARU_CHAIN_DIR",
+            Field::LedgerDir => "AMARU_LEDGER_DIR",
+        }
+    }
+
+    /// Returns the message to show when `value` is invalid.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match self {
+            Field::Network => {
+                let testnet = value
+                    .strip_prefix("testnet:")
+                    .is_some_and(|magic| magic.parse::<u32>().is_ok());
+                if NETWORKS.contains(&value) || testnet {
+                    Ok(())
+                } else {
+                    Err("Unknown network".to_string())
+                }
+            }
+            Field::ListenAddress => validate_address(value),
+            Field::Peers => {
+                if value.is_empty() {
+                    return Err("At least one peer required".to_string());
+                }
+                value
+                    .split(',')
+                    .try_for_each(|peer| validate_address(peer.trim()))
+            }
+            Field::ChainDir | Field::LedgerDir => {
+                if Path::new(value).is_absolute() {
+                    Ok(())
+                } else {
+                    Err("Absolute path required".to_string())
+                }
+            }
+        }
+    }
+}
+
+/// `host:port`, the host being a name or an IP address.
+pub fn validate_address(address: &str) -> Result<(), String> {
+    let Some((host, port)) = address.rsplit_once(':') else {
+        return Err(format!("{} has no port", address));
+    };
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return Err(format!("Invalid host in {}", address));
+    }
+    match port.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(()),
+        _ => Err(format!("Invalid port in {}", address)),
+    }
+}
+
+/// The env file of the node, kept line by line so that comments and the
+/// variables of amaru-pi survive edits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeConfig {
+    lines: Vec<String>,
+}
+
+impl NodeConfig {
+    pub fn load() -> Result<Self> {
+        let data = fs::read_to_string(ENV_FILE_PATH)?;
+        Ok(Self {
+            lines: data.lines().map(|line| line.to_string()).collect(),
+        })
+    }
+
+    /// `None` when not set, amaru then uses its default.
+    pub fn get(&self, field: Field) -> Option<String> {
+        self.lines.iter().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == field.var()).then(|| unquote(value.trim()).to_string())
+        })
+    }
+
+    pub fn set(&mut self, field: Field, value: &str) {
+        let value = value.trim();
+        let value = if value.contains(char::is_whitespace) {
+            format!("\"{}\"", value)
+        } else {
+            value.to_string()
+        };
+        let entry = format!("{}={}", field.var(), value);
+        let existing = self.lines.iter_mut().find(|line| {
+            line.split_once('=')
+                .is_some_and(|(key, _)| key.trim() == field.var())
+        });
+        match existing {
+            Some(line) => *line = entry,
+            None => self.lines.push(entry),
+        }
+    }
+
+    fn to_file(&self) -> String {
+        let mut data = self.lines.join("\n");
+        data.push('\n');
+        data
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Applies `changes` to the env file as it currently is, keeping the previous
+/// version as a backup. The node only picks them up once restarted.
+pub fn save(changes: &[(Field, String)]) -> Result<()> {
+    for (field, value) in changes {
+        field
+            .validate(value)
+            .map_err(|e| anyhow!("{}: {}", field.label(), e))?;
+    }
+    let mut config = NodeConfig::load()?;
+    for (field, value) in changes {
+        config.set(*field, value);
+    }
+
+    fs::copy(ENV_FILE_PATH, BACKUP_FILE_PATH)?;
+    let tmp_path = format!("{}.tmp", ENV_FILE_PATH);
+    fs::write(&tmp_path, config.to_file())?;
+    // Still owned by pi, though written by root
+    let metadata = fs::metadata(ENV_FILE_PATH)?;
+    fs::set_permissions(&tmp_path, metadata.permissions())?;
+    chown(&tmp_path, Some(metadata.uid()), Some(metadata.gid()))?;
+    File::open(&tmp_path)?.sync_all()?;
+    fs::rename(&tmp_path, ENV_FILE_PATH)?;
+    info!(
+        "Saved {} to {}",
+        changes
+            .iter()
+            .map(|(field, _)| field.var())
+            .collect::<Vec<_>>()
+            .join(", "),
+        ENV_FILE_PATH
+    );
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use crate::audio::AudioChannel;
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use tracing::warn;
+
+const HISTORY_FILE_PATH: &str = "/home/pi/.amaru_notifications.json";
+const HISTORY_MAX_ENTRIES: usize = 200;
+
+/// Ordered from the least severe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+/// A message meant to reach the operator, on-screen and through any other
+/// configured channel.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    pub title: String,
+    #[serde(default)]
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default)]
+    pub timestamp: u64,
+    /// Added by the operator afterwards, e.g. "power cut, UPS held"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Notification {
+    pub fn new(title: impl Into<String>, lines: Vec<String>, severity: Severity) -> Self {
+        Self {
+            title: title.into(),
+            lines,
+            severity,
+            timestamp: current_timestamp().unwrap_or(0),
+            note: None,
+        }
+    }
+}
+
+/// A destination notifications are delivered to.
+pub trait NotificationChannel {
+    fn name(&self) -> &str;
+
+    fn send(&mut self, notification: &Notification) -> Result<()>;
+}
+
+/// Persists notifications to the history file so they can be reviewed later.
+pub struct HistoryChannel;
+
+impl NotificationChannel for HistoryChannel {
+    fn name(&self) -> &str {
+        "history"
+    }
+
+    fn send(&mut self, notification: &Notification) -> Result<()> {
+        let mut history = read_history().unwrap_or_default();
+        history.push(notification.clone());
+        let overflow = history.len().saturating_sub(HISTORY_MAX_ENTRIES);
+        history.drain(..overflow);
+        write_history(&history)
+    }
+}
+
+/// Fans notifications out to all registered channels.
+pub struct Notifier {
+    channels: Vec<Box<dyn NotificationChannel + Send>>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self {
+            channels: vec![Box::new(HistoryChannel)],
+        }
+    }
+}
+
+impl Notifier {
+    /// The history, and the channels configured in the environment.
+    pub fn from_env() -> Self {
+        let mut notifier = Self::default();
+        if let Some(audio) = AudioChannel::from_env() {
+            notifier.add_channel(Box::new(audio));
+        }
+        notifier
+    }
+
+    pub fn add_channel(&mut self, channel: Box<dyn NotificationChannel + Send>) {
+        self.channels.push(channel);
+    }
+
+    /// Delivers the notification to every channel. A failing channel doesn't
+    /// prevent delivery to the others.
+    pub fn notify(&mut self, notification: &Notification) {
+        for channel in &mut self.channels {
+            if let Err(e) = channel.send(notification) {
+                warn!("Failed to send notification to {}: {}", channel.name(), e);
+            }
+        }
+    }
+}
+
+/// Reads the notification history, oldest first.
+pub fn read_history() -> Result<Vec<Notification>> {
+    let path = Path::new(HISTORY_FILE_PATH);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_history(history: &[Notification]) -> Result<()> {
+    fs::write(HISTORY_FILE_PATH, serde_json::to_string_pretty(history)?)?;
+    Ok(())
+}
+
+/// Copies the history, notes included, into `dir` as `events.json`.
+pub fn export_history(dir: &Path) -> Result<()> {
+    let dest = dir.join("events.json");
+    fs::write(&dest, serde_json::to_string_pretty(&read_history()?)?)?;
+    File::open(&dest)?.sync_all()?;
+    Ok(())
+}
+
+/// Notes the latest notification sent at `timestamp`, or the latest one
+/// without it. An empty note removes the previous one.
+pub fn annotate(timestamp: Option<u64>, note: &str) -> Result<Notification> {
+    let mut history = read_history()?;
+    let notification = history
+        .iter_mut()
+        .rev()
+        .find(|notification| timestamp.is_none_or(|timestamp| notification.timestamp == timestamp))
+        .ok_or_else(|| anyhow!("No such notification"))?;
+    let note = note.trim();
+    notification.note = (!note.is_empty()).then(|| note.to_string());
+    let annotated = notification.clone();
+    write_history(&history)?;
+    Ok(annotated)
+}
@@ -0,0 +1,247 @@
+use crate::integrity::sha256_file;
+use crate::platform::{self, Platform};
+use crate::update::{compare_versions, read_state_file, write_state_file};
+use crate::usb;
+use anyhow::{Result, anyhow};
+use std::cmp::Ordering;
+use std::fs::{self, OpenOptions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{info, warn};
+
+/// Source of the updates staged from archives, instead of a repository
+pub const SOURCE: &str = "usb";
+/// Directory of the USB drive searched besides its root
+const UPDATES_DIR: &str = "amaru-updates";
+/// Checksums of the archives, as published with the releases
+const CHECKSUMS_FILE: &str = "checksums.txt";
+/// Taken by the updater script too, so that staging never runs twice at once.
+/// Created writable by pi by `/etc/tmpfiles.d/amaru-pi.conf`, as root can't
+/// open a file of pi in /tmp.
+const LOCK_FILE_PATH: &str = "/run/amaru-pi/update-check.lock";
+const STAGING_DIR: &str = "/tmp";
+/// Applications archives are installed for, longest names first so that
+/// `amaru-pi-v1` isn't taken for `amaru`
+const APPLICATIONS: &[&str] = &["amaru-doctor", "amaru-pi", "amaru"];
+
+/// A release archive, as `<app>-<version>-<target>.tar.gz`, e.g.
+/// `amaru-pi-v1.2.0-aarch64-unknown-linux-gnu.tar.gz`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archive {
+    pub app: String,
+    pub version: String,
+    pub path: PathBuf,
+}
+
+/// The application and version an archive is for, from its name. Archives
+/// for other platforms are left out.
+fn parse_name(name: &str) -> Option<(String, String)> {
+    if !name.starts_with("amaru")
+        || !name.ends_with(".tar.gz")
+        || !Platform::current().matches(name)
+    {
+        return None;
+    }
+    let (stem, _) = platform::split_asset(name)?;
+    APPLICATIONS.iter().find_map(|app| {
+        let version = stem.strip_prefix(app)?.strip_prefix('-')?;
+        (!version.is_empty()).then(|| (app.to_string(), version.to_string()))
+    })
+}
+
+/// Archives in `dir` and its `amaru-updates` directory, latest version of
+/// each application first.
+pub fn scan(dir: &Path) -> Vec<Archive> {
+    let mut archives: Vec<Archive> = [dir.to_path_buf(), dir.join(UPDATES_DIR)]
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| {
+            let (app, version) = parse_name(&entry.file_name().to_string_lossy())?;
+            Some(Archive {
+                app,
+                version,
+                path: entry.path(),
+            })
+        })
+        .collect();
+    archives.sort_by(|a, b| {
+        a.app
+            .cmp(&b.app)
+            .then_with(|| compare_versions(&b.version, &a.version))
+    });
+    archives
+}
+
+/// The SHA-256 of `name` in checksums as `sha256sum` writes them.
+pub fn find_checksum(checksums: &str, name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let sha256 = parts.next()?.to_lowercase();
+        let file = parts.next()?.trim_start_matches('*');
+        (file == name).then_some(sha256)
+    })
+}
+
+/// The SHA-256 of a file from the `checksums.txt` next to it, or its own
+/// `<file>.sha256`.
+pub fn listed_checksum(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let dir = path.parent().unwrap_or(Path::new("."));
+    [
+        dir.join(CHECKSUMS_FILE),
+        dir.join(format!("{}.sha256", name)),
+    ]
+    .iter()
+    .filter_map(|path| fs::read_to_string(path).ok())
+    .find_map(|content| find_checksum(&content, &name))
+}
+
+/// Checks an archive against its listed checksum. Archives without one
+/// aren't trusted.
+pub fn verify(archive: &Archive) -> Result<()> {
+    let name = archive.path.display();
+    let expected =
+        listed_checksum(&archive.path).ok_or_else(|| anyhow!("No checksum for {}", name))?;
+    let actual = sha256_file(&archive.path)?;
+    if actual != expected {
+        return Err(anyhow!("Checksum mismatch for {}", name));
+    }
+    Ok(())
+}
+
+/// Extracts the binary of an archive to `/tmp/<app>.new`, as the updater
+/// does, and returns its path.
+fn extract(archive: &Archive) -> Result<PathBuf> {
+    let dir = Path::new(STAGING_DIR).join(format!("{}.offline", archive.app));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir)?;
+    let output = Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive.path)
+        .arg("-C")
+        .arg(&dir)
+        .output()?;
+    let staged = Path::new(STAGING_DIR).join(format!("{}.new", archive.app));
+    let extracted = dir.join(&archive.app);
+    let result = if !output.status.success() {
+        Err(anyhow!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    } else if !extracted.is_file() {
+        Err(anyhow!("No {} in the archive", archive.app))
+    } else {
+        fs::rename(&extracted, &staged)
+            .and_then(|()| fs::set_permissions(&staged, fs::Permissions::from_mode(0o755)))
+            .map_err(Into::into)
+    };
+    let _ = fs::remove_dir_all(&dir);
+    result.map(|()| staged)
+}
+
+/// Verifies and stages the latest archive of each application in `dir` that
+/// is newer than the version installed, as a pending update applied like
+/// those downloaded. Held and pinned applications are left as they are.
+/// Returns the updates staged, as `<app> <version>`.
+pub fn stage_from(dir: &Path) -> Result<Vec<String>> {
+    let lock = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(LOCK_FILE_PATH)?;
+    lock.try_lock()
+        .map_err(|_| anyhow!("Another update process is running"))?;
+
+    let mut state = read_state_file()?;
+    let mut staged = Vec::new();
+    let mut seen = Vec::new();
+    for archive in scan(dir) {
+        if seen.contains(&archive.app) {
+            continue;
+        }
+        seen.push(archive.app.clone());
+        let Some(app_state) = state.applications.get_mut(&archive.app) else {
+            info!("{} isn't managed, skipping its archive", archive.app);
+            continue;
+        };
+        if compare_versions(&archive.version, &app_state.current_version) != Ordering::Greater {
+            info!("{} {} is already installed", archive.app, archive.version);
+            continue;
+        }
+        if app_state.is_held_back(&archive.version) {
+            info!(
+                "{} is held back, {} not staged",
+                archive.app, archive.version
+            );
+            continue;
+        }
+        if let Err(e) = verify(&archive) {
+            warn!("Not staging {}: {}", archive.path.display(), e);
+            continue;
+        }
+        let path = extract(&archive)?;
+        app_state.pending_version = archive.version.clone();
+        app_state.pending_source = SOURCE.to_string();
+        app_state.staged_path = path.display().to_string();
+        app_state.available_version = String::new();
+        info!(
+            "{} {} staged from {}",
+            archive.app,
+            archive.version,
+            archive.path.display()
+        );
+        staged.push(format!("{} {}", archive.app, archive.version));
+    }
+    if !staged.is_empty() {
+        write_state_file(&state)?;
+    }
+    Ok(staged)
+}
+
+/// Stages the updates found on the USB drive, see `stage_from`.
+pub fn stage_from_usb() -> Result<Vec<String>> {
+    let drive = usb::open()?;
+    stage_from(drive.path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(stem: &str) -> String {
+        format!("{}-{}.tar.gz", stem, Platform::current())
+    }
+
+    #[test]
+    fn names_of_the_applications() {
+        let parsed = |stem: &str| parse_name(&archive(stem));
+        assert_eq!(
+            parsed("amaru-v0.3.0"),
+            Some(("amaru".to_string(), "v0.3.0".to_string()))
+        );
+        assert_eq!(
+            parsed("amaru-pi-v1.2.0"),
+            Some(("amaru-pi".to_string(), "v1.2.0".to_string()))
+        );
+        assert_eq!(
+            parsed("amaru-doctor-v0.1.0"),
+            Some(("amaru-doctor".to_string(), "v0.1.0".to_string()))
+        );
+        assert_eq!(parsed("amaru"), None);
+        assert_eq!(parsed("cardano-node-v10.1.0"), None);
+    }
+
+    #[test]
+    fn only_archives_of_this_platform() {
+        assert_eq!(
+            parse_name("amaru-v0.3.0-x86_64-unknown-linux-gnu.tar.gz"),
+            None
+        );
+        assert_eq!(
+            parse_name(&archive("amaru-v0.3.0").replace(".tar.gz", ".zip")),
+            None
+        );
+    }
+}
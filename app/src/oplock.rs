@@ -0,0 +1,167 @@
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Only root can write there, unlike /tmp where anyone could take the lock
+const RUN_DIR: &str = "/run/amaru-pi";
+/// flock'd by whoever runs an operation, the activation script included
+const LOCK_FILE_PATH: &str = "/run/amaru-pi/operation.lock";
+/// Tells who holds the lock, written once it is taken
+const HOLDER_FILE_PATH: &str = "/run/amaru-pi/operation.holder";
+/// Longest an operation waits for the one running
+const MAX_WAIT: Duration = Duration::from_secs(2 * 60 * 60);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Operations that must not run at the same time, as they stop the node or
+/// swap the files it runs from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    UpdateActivation,
+    NodeRestart,
+    Backup,
+    Restore,
+    PowerOff,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Operation::UpdateActivation => "Update activation",
+            Operation::NodeRestart => "Node restart",
+            Operation::Backup => "Ledger backup",
+            Operation::Restore => "Ledger restore",
+            Operation::PowerOff => "Power off",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Holder {
+    pub operation: Operation,
+    pub pid: u32,
+    /// UNIX time the lock was taken
+    pub since: u64,
+}
+
+/// Operations of this process waiting for the lock, in order.
+static QUEUE: Mutex<Vec<Operation>> = Mutex::new(Vec::new());
+
+/// A panic while holding it leaves the queue as consistent as before.
+fn queue_lock() -> MutexGuard<'static, Vec<Operation>> {
+    QUEUE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Held until dropped.
+#[derive(Debug)]
+pub struct OperationGuard {
+    _file: File,
+    operation: Operation,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        // The lock itself is released when the file is closed
+        if let Err(e) = fs::remove_file(HOLDER_FILE_PATH) {
+            warn!("Failed to remove {}: {}", HOLDER_FILE_PATH, e);
+        }
+        info!("{} done", self.operation);
+    }
+}
+
+fn open_lock_file() -> Result<File> {
+    fs::create_dir_all(RUN_DIR)?;
+    Ok(OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(LOCK_FILE_PATH)?)
+}
+
+fn take(file: File, operation: Operation) -> Result<OperationGuard> {
+    let holder = Holder {
+        operation,
+        pid: std::process::id(),
+        since: current_timestamp()?,
+    };
+    fs::write(HOLDER_FILE_PATH, serde_json::to_string(&holder)?)?;
+    info!("{} started", operation);
+    Ok(OperationGuard {
+        _file: file,
+        operation,
+    })
+}
+
+/// Takes the lock, failing if another operation holds it.
+pub fn try_acquire(operation: Operation) -> Result<OperationGuard> {
+    let file = open_lock_file()?;
+    match file.try_lock() {
+        Ok(()) => take(file, operation),
+        Err(TryLockError::WouldBlock) => Err(match holder() {
+            Some(holder) => anyhow!("{} in progress", holder.operation),
+            None => anyhow!("Another operation is in progress"),
+        }),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+/// Waits for the lock, in line behind the operations already waiting. Gives
+/// up once `cancelled` or after `MAX_WAIT`.
+pub fn acquire(operation: Operation, cancelled: impl Fn() -> bool) -> Result<OperationGuard> {
+    let file = open_lock_file()?;
+    queue_lock().push(operation);
+    let locked = wait_for(&file, cancelled);
+    let mut queue = queue_lock();
+    if let Some(i) = queue.iter().position(|queued| *queued == operation) {
+        queue.remove(i);
+    }
+    drop(queue);
+    locked?;
+    take(file, operation)
+}
+
+fn wait_for(file: &File, cancelled: impl Fn() -> bool) -> Result<()> {
+    let started = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(()),
+            Err(TryLockError::WouldBlock) => {}
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+        if cancelled() {
+            return Err(anyhow!("Cancelled"));
+        }
+        if started.elapsed() >= MAX_WAIT {
+            return Err(match holder() {
+                Some(holder) => anyhow!("Timed out waiting for {}", holder.operation),
+                None => anyhow!("Timed out waiting for another operation"),
+            });
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// The operation running, if any.
+pub fn holder() -> Option<Holder> {
+    // A holder file left by a crash is stale once the lock is free
+    let file = open_lock_file().ok()?;
+    if file.try_lock().is_ok() {
+        return None;
+    }
+    let path = Path::new(HOLDER_FILE_PATH);
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Operations of this process waiting for the one running.
+pub fn queue() -> Vec<Operation> {
+    queue_lock().clone()
+}
@@ -0,0 +1,68 @@
+use super::LedColor;
+use anyhow::Result;
+use rppal::gpio::{Gpio, OutputPin};
+use std::env;
+use tracing::warn;
+
+/// Software PWM, fast enough not to flicker
+const PWM_FREQUENCY: f64 = 200.0;
+/// Percent, these LEDs are bright in a dark room
+const DEFAULT_BRIGHTNESS: u8 = 20;
+
+/// An RGB LED wired to three GPIO pins, lit when they are pulled low as on
+/// the Display HAT Mini.
+pub struct RgbLed {
+    pins: [OutputPin; 3],
+    /// From 0 to 1
+    brightness: f64,
+    enabled: bool,
+    color: Option<LedColor>,
+}
+
+impl RgbLed {
+    /// Reads `AMARU_PI_LED`, `off` to keep the LED dark, and
+    /// `AMARU_PI_LED_BRIGHTNESS`, in percent.
+    pub fn new(gpio: &Gpio, (r, g, b): (u8, u8, u8)) -> Result<Self> {
+        let enabled = env::var("AMARU_PI_LED").map_or(true, |v| v.trim() != "off");
+        let brightness = env::var("AMARU_PI_LED_BRIGHTNESS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u8>().ok())
+            .unwrap_or(DEFAULT_BRIGHTNESS)
+            .min(100);
+        let mut pins = [
+            gpio.get(r)?.into_output(),
+            gpio.get(g)?.into_output(),
+            gpio.get(b)?.into_output(),
+        ];
+        for pin in &mut pins {
+            pin.set_high();
+        }
+        Ok(Self {
+            pins,
+            brightness: f64::from(brightness) / 100.0,
+            enabled,
+            color: None,
+        })
+    }
+
+    /// Lights the LED, unless it already is of that color.
+    pub fn show(&mut self, color: LedColor) {
+        let color = if self.enabled { color } else { LedColor::OFF };
+        if self.color == Some(color) {
+            return;
+        }
+        self.color = Some(color);
+        for (pin, level) in self.pins.iter_mut().zip([color.r, color.g, color.b]) {
+            let duty = f64::from(level) / 255.0 * self.brightness;
+            let result = if duty == 0.0 {
+                pin.clear_pwm().map(|_| pin.set_high())
+            } else {
+                // Active low
+                pin.set_pwm_frequency(PWM_FREQUENCY, 1.0 - duty)
+            };
+            if let Err(e) = result {
+                warn!("Failed to light the LED: {}", e);
+            }
+        }
+    }
+}
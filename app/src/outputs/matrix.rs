@@ -0,0 +1,180 @@
+use super::LedColor;
+use crate::chain::Network;
+use crate::screens::SystemState;
+use crate::sync_eta::sync_percent;
+use anyhow::{Result, anyhow};
+use rppal::i2c::I2c;
+use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+use std::env;
+use tracing::warn;
+
+/// Percent, the matrices are blinding at full brightness
+const DEFAULT_BRIGHTNESS: u8 = 20;
+const SENSE_HAT_ADDRESS: u16 = 0x46;
+const I2C_BUS: u8 = 1;
+const UNICORN_HD_CLOCK_HZ: u32 = 9_000_000;
+/// Precedes the pixels sent to the Unicorn HAT HD
+const UNICORN_HD_START: u8 = 0x72;
+
+/// The LED matrix HATs that can be driven.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixKind {
+    /// 8x8, on the I2C bus
+    SenseHat,
+    /// Unicorn HAT HD, 16x16 on the SPI bus
+    UnicornHd,
+}
+
+impl MatrixKind {
+    fn size(self) -> (usize, usize) {
+        match self {
+            MatrixKind::SenseHat => (8, 8),
+            MatrixKind::UnicornHd => (16, 16),
+        }
+    }
+}
+
+enum Device {
+    SenseHat(I2c),
+    UnicornHd(Spi),
+}
+
+/// An LED matrix showing the sync progress as a bar of pixels filling up
+/// row by row, yellow while syncing and green once synced. It turns red
+/// when the node failed or the Pi is offline.
+pub struct LedMatrix {
+    device: Device,
+    kind: MatrixKind,
+    /// From 0 to 1
+    brightness: f64,
+    network: Network,
+    shown: Vec<LedColor>,
+}
+
+impl LedMatrix {
+    /// Reads `AMARU_PI_LED_MATRIX`, `sense_hat` or `unicorn_hd`, and
+    /// `AMARU_PI_LED_MATRIX_BRIGHTNESS`, in percent. `None` without a matrix.
+    pub fn from_env() -> Result<Option<Self>> {
+        let kind = match env::var("AMARU_PI_LED_MATRIX")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return Ok(None),
+            "sense_hat" => MatrixKind::SenseHat,
+            "unicorn_hd" => MatrixKind::UnicornHd,
+            other => return Err(anyhow!("Unknown LED matrix: {}", other)),
+        };
+        let brightness = env::var("AMARU_PI_LED_MATRIX_BRIGHTNESS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u8>().ok())
+            .unwrap_or(DEFAULT_BRIGHTNESS)
+            .min(100);
+        Self::new(kind, brightness).map(Some)
+    }
+
+    pub fn new(kind: MatrixKind, brightness: u8) -> Result<Self> {
+        let device = match kind {
+            MatrixKind::SenseHat => {
+                let mut i2c = I2c::with_bus(I2C_BUS)?;
+                i2c.set_slave_address(SENSE_HAT_ADDRESS)?;
+                Device::SenseHat(i2c)
+            }
+            MatrixKind::UnicornHd => Device::UnicornHd(Spi::new(
+                Bus::Spi0,
+                SlaveSelect::Ss0,
+                UNICORN_HD_CLOCK_HZ,
+                Mode::Mode0,
+            )?),
+        };
+        Ok(Self {
+            device,
+            kind,
+            brightness: f64::from(brightness) / 100.0,
+            network: Network::from_env(),
+            shown: Vec::new(),
+        })
+    }
+
+    /// Shows the state of the node, unless already shown.
+    pub fn show(&mut self, system: &SystemState) -> Result<()> {
+        let pixels = self.pixels(system);
+        self.display(pixels)
+    }
+
+    /// Fills the whole matrix with `color`, e.g. on a crash.
+    pub fn fill(&mut self, color: LedColor) -> Result<()> {
+        let (width, height) = self.kind.size();
+        self.display(vec![color; width * height])
+    }
+
+    fn display(&mut self, pixels: Vec<LedColor>) -> Result<()> {
+        if pixels == self.shown {
+            return Ok(());
+        }
+        self.write(&pixels)?;
+        self.shown = pixels;
+        Ok(())
+    }
+
+    /// Row by row from the top left. Until the tip is known, only the first
+    /// pixel is lit, in the color of the status.
+    fn pixels(&self, system: &SystemState) -> Vec<LedColor> {
+        let (width, height) = self.kind.size();
+        let count = width * height;
+        let color = super::status_color(system);
+        if color == LedColor::RED {
+            return vec![color; count];
+        }
+        let lit = match system.tip.and_then(|tip| sync_percent(&self.network, tip)) {
+            Some(percent) => ((percent / 100.0 * count as f64).round() as usize).clamp(1, count),
+            None => 1,
+        };
+        (0..count)
+            .map(|i| if i < lit { color } else { LedColor::OFF })
+            .collect()
+    }
+
+    fn dim(&self, channel: u8) -> u8 {
+        (f64::from(channel) * self.brightness).round() as u8
+    }
+
+    fn write(&mut self, pixels: &[LedColor]) -> Result<()> {
+        let (width, _) = self.kind.size();
+        let dimmed: Vec<[u8; 3]> = pixels
+            .iter()
+            .map(|p| [self.dim(p.r), self.dim(p.g), self.dim(p.b)])
+            .collect();
+        match &mut self.device {
+            Device::SenseHat(i2c) => {
+                // From register 0, a row of red, of green then of blue for
+                // each row, 5 bits per channel
+                let mut bytes = vec![0x00];
+                for row in dimmed.chunks(width) {
+                    for channel in 0..3 {
+                        bytes.extend(row.iter().map(|rgb| rgb[channel] >> 3));
+                    }
+                }
+                i2c.write(&bytes)?;
+            }
+            Device::UnicornHd(spi) => {
+                let mut bytes = vec![UNICORN_HD_START];
+                bytes.extend(dimmed.iter().flatten());
+                spi.write(&bytes)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shows the state of the node on the matrix, when there is one. The matrix
+/// is given up on after an error, e.g. once unplugged.
+pub fn show_status(matrix: &mut Option<LedMatrix>, system: &SystemState) {
+    if let Some(device) = matrix
+        && let Err(e) = device.show(system)
+    {
+        warn!("Failed to draw on the LED matrix, leaving it: {}", e);
+        *matrix = None;
+    }
+}
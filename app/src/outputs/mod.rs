@@ -0,0 +1,44 @@
+#[cfg(feature = "gpio")]
+pub mod led;
+#[cfg(feature = "led_matrix")]
+pub mod matrix;
+
+use crate::screens::SystemState;
+use crate::systemd::ActiveState;
+use crate::wifi::Connectivity;
+
+/// Color of a status LED, each channel from 0 to 255.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl LedColor {
+    pub const OFF: LedColor = LedColor::new(0, 0, 0);
+    pub const RED: LedColor = LedColor::new(255, 0, 0);
+    pub const YELLOW: LedColor = LedColor::new(255, 160, 0);
+    pub const GREEN: LedColor = LedColor::new(0, 255, 0);
+    pub const BLUE: LedColor = LedColor::new(0, 0, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// The health of the node at a glance: red when the node failed or the Pi is
+/// offline, yellow while syncing, green once synced and blue until known.
+pub fn status_color(system: &SystemState) -> LedColor {
+    let offline = system.network_status.connectivity == Connectivity::None;
+    match system.amaru_status.active_state {
+        ActiveState::Failed => LedColor::RED,
+        _ if offline => LedColor::RED,
+        ActiveState::Active => match system.tip {
+            Some((_, true)) => LedColor::GREEN,
+            Some((_, false)) => LedColor::YELLOW,
+            None => LedColor::BLUE,
+        },
+        _ => LedColor::BLUE,
+    }
+}
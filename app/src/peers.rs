@@ -0,0 +1,163 @@
+use anyhow::Result;
+use maxminddb::{Reader, geoip2};
+use std::env;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use tracing::warn;
+
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+
+/// A GeoLite2 or DB-IP City database in MaxMind format, never queried online
+const DEFAULT_GEOIP_DB_PATH: &str = "/home/pi/geoip.mmdb";
+
+/// Loaded once, the database doesn't change while running.
+static GEOIP_DB: OnceLock<Option<Reader<Vec<u8>>>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerLocation {
+    pub addr: IpAddr,
+    /// ISO code, e.g. `FR`
+    pub country: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PeersSnapshot {
+    pub located: Vec<PeerLocation>,
+    /// Peers missing from the database, or on a private network
+    pub unlocated: usize,
+}
+
+/// Remote address of a connection of the node, from a line of `ss -tnpH`.
+#[cfg(feature = "display_hat")]
+fn parse_node_peer(line: &str) -> Option<IpAddr> {
+    // e.g. `0 0 192.168.1.20:51234 [2001:db8::1]:3001 users:(("amaru",pid=812,fd=21))`
+    if !line.contains("((\"amaru\",") {
+        return None;
+    }
+    let (host, _port) = line.split_whitespace().nth(3)?.rsplit_once(':')?;
+    let addr = host
+        .trim_matches(['[', ']'])
+        .parse::<IpAddr>()
+        .ok()?
+        .to_canonical();
+    (!addr.is_loopback()).then_some(addr)
+}
+
+/// Established TCP connections of the node, with the round trip time the
+/// kernel measured for each, in milliseconds.
+#[cfg(feature = "display_hat")]
+fn node_connections() -> Result<Vec<(IpAddr, Option<f64>)>> {
+    let stdout = run_and_capture("ss", ["-tinpH", "state", "established"].to_vec())?;
+    let mut connections = Vec::new();
+    let mut current = None;
+    for line in stdout.lines() {
+        if line.starts_with(char::is_whitespace) {
+            // e.g. `\t cubic wscale:7,7 rto:204 rtt:12.5/3.1 ato:40 ...`
+            if let Some(addr) = current.take() {
+                let rtt = line
+                    .split_whitespace()
+                    .find_map(|word| word.strip_prefix("rtt:"))
+                    .and_then(|rtt| rtt.split('/').next()?.parse().ok());
+                connections.push((addr, rtt));
+            }
+        } else {
+            connections.extend(current.take().map(|addr| (addr, None)));
+            current = parse_node_peer(line);
+        }
+    }
+    connections.extend(current.map(|addr| (addr, None)));
+    Ok(connections)
+}
+
+/// Remote addresses of the established TCP connections of the node.
+#[cfg(feature = "display_hat")]
+pub fn connected_peers() -> Result<Vec<IpAddr>> {
+    let mut peers: Vec<IpAddr> = node_connections()?
+        .into_iter()
+        .map(|(addr, _)| addr)
+        .collect();
+    peers.sort();
+    peers.dedup();
+    Ok(peers)
+}
+
+/// Round trip time to each connected peer, in milliseconds.
+#[cfg(feature = "display_hat")]
+pub fn peer_latencies() -> Result<Vec<(IpAddr, f64)>> {
+    Ok(node_connections()?
+        .into_iter()
+        .filter_map(|(addr, rtt)| Some((addr, rtt?)))
+        .collect())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn peer_latencies() -> Result<Vec<(IpAddr, f64)>> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn connected_peers() -> Result<Vec<IpAddr>> {
+    Ok(Vec::new())
+}
+
+fn geoip_db() -> Option<&'static Reader<Vec<u8>>> {
+    GEOIP_DB
+        .get_or_init(|| {
+            let path =
+                env::var("AMARU_PI_GEOIP_DB").unwrap_or_else(|_| DEFAULT_GEOIP_DB_PATH.to_string());
+            Reader::open_readfile(&path)
+                .inspect_err(|e| warn!("Failed to open GeoIP database {}: {}", path, e))
+                .ok()
+        })
+        .as_ref()
+}
+
+fn locate(db: &Reader<Vec<u8>>, addr: IpAddr) -> Option<PeerLocation> {
+    let city: geoip2::City = db.lookup(addr).ok()?;
+    let location = city.location?;
+    Some(PeerLocation {
+        addr,
+        country: city
+            .country
+            .and_then(|country| country.iso_code)
+            .unwrap_or("??")
+            .to_string(),
+        latitude: location.latitude?,
+        longitude: location.longitude?,
+    })
+}
+
+/// Connected peers with their approximate location, all unlocated without a
+/// GeoIP database.
+pub fn locate_peers() -> Result<PeersSnapshot> {
+    let peers = connected_peers()?;
+    let Some(db) = geoip_db() else {
+        return Ok(PeersSnapshot {
+            located: Vec::new(),
+            unlocated: peers.len(),
+        });
+    };
+    let located: Vec<PeerLocation> = peers.iter().filter_map(|addr| locate(db, *addr)).collect();
+    Ok(PeersSnapshot {
+        unlocated: peers.len() - located.len(),
+        located,
+    })
+}
+
+impl PeersSnapshot {
+    /// Countries by number of peers, most first.
+    pub fn countries(&self) -> Vec<(&str, usize)> {
+        let mut countries: Vec<(&str, usize)> = Vec::new();
+        for peer in &self.located {
+            match countries.iter_mut().find(|(c, _)| *c == peer.country) {
+                Some((_, count)) => *count += 1,
+                None => countries.push((&peer.country, 1)),
+            }
+        }
+        countries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        countries
+    }
+}
@@ -0,0 +1,361 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::firewall::FirewallPreset;
+use crate::ip_config::IpSettings;
+use crate::node_config::Field;
+use crate::power::PowerAction;
+use crate::restore::Snapshot;
+use crate::secrets;
+use crate::util::current_timestamp;
+use crate::vpn::VpnBackend;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, OpenOptions, Permissions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+const STATE_FILE_PATH: &str = "/home/pi/.amaru_pin_state.json";
+/// The failures tell how close a guess came to a lockout
+const STATE_FILE_MODE: u32 = 0o600;
+const MIN_PIN_LENGTH: usize = 4;
+const MAX_PIN_LENGTH: usize = 8;
+/// Wrong attempts allowed before being locked out
+const FREE_ATTEMPTS: u32 = 3;
+/// First lockout, doubled after each further wrong attempt
+const BASE_LOCKOUT: Duration = Duration::from_secs(30);
+const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+
+/// What a PIN prompt stands between the user and.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Guarded {
+    /// The whole UI
+    Ui,
+    ApplyUpdate,
+    SetUpdateHold(String, bool),
+    ConnectToWifi(String, String),
+    SwitchNetwork(String),
+    SetVpn(VpnBackend, bool),
+    SetApn(String),
+    SetTimezone(String),
+    ApplyIpConfig(IpSettings),
+    ApplyFirewallPreset(FirewallPreset),
+    /// Core dumps hold the node's memory, keys included
+    ExportCoredump(u32),
+    /// Support bundles hold the logs and the state of the node
+    ExportDiagnostics,
+    StageOfflineUpdates,
+    DeleteFile(PathBuf),
+    SaveNodeConfig(Vec<(Field, String)>),
+    RestartNode,
+    BackUp,
+    Restore(Snapshot),
+    Power(PowerAction),
+}
+
+impl Guarded {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Guarded::Ui => "unlock",
+            Guarded::ApplyUpdate => "apply the update",
+            Guarded::SetUpdateHold(_, true) => "hold the updates",
+            Guarded::SetUpdateHold(_, false) => "release the updates",
+            Guarded::ConnectToWifi(..) => "change WiFi",
+            Guarded::SwitchNetwork(_) => "switch network",
+            Guarded::SetVpn(_, true) => "bring the VPN up",
+            Guarded::SetVpn(_, false) => "take the VPN down",
+            Guarded::SetApn(_) => "change the APN",
+            Guarded::SetTimezone(_) => "change the time zone",
+            Guarded::ApplyIpConfig(_) => "change the IP address",
+            Guarded::ApplyFirewallPreset(_) => "change the firewall",
+            Guarded::ExportCoredump(_) => "export the crash",
+            Guarded::ExportDiagnostics => "export a support bundle",
+            Guarded::StageOfflineUpdates => "install from USB",
+            Guarded::DeleteFile(_) => "delete the file",
+            Guarded::SaveNodeConfig(_) => "change the node config",
+            Guarded::RestartNode => "restart the node",
+            Guarded::BackUp => "back up the ledger",
+            Guarded::Restore(_) => "restore the ledger",
+            Guarded::Power(PowerAction::Shutdown) => "shut down",
+            Guarded::Power(PowerAction::Reboot) => "reboot",
+            Guarded::Power(PowerAction::RestartUi) => "restart the UI",
+        }
+    }
+}
+
+pub enum PinCheck {
+    Accepted,
+    Rejected { attempts_left: u32 },
+    LockedOut(Duration),
+}
+
+/// Failures survive restarts, so power cycling doesn't reset the backoff.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PinState {
+    #[serde(default)]
+    failures: u32,
+    #[serde(default)]
+    locked_until: u64,
+}
+
+/// Physical access control: destructive actions, and optionally the whole
+/// UI, require a PIN entered with the buttons.
+#[derive(Debug)]
+pub struct PinLock {
+    pin: Option<String>,
+    lock_ui: bool,
+    state: PinState,
+}
+
+impl PinLock {
+    /// The PIN is read from `AMARU_PI_PIN`, preferably kept in the secrets
+    /// store. `AMARU_PI_PIN_LOCK_UI=true` also requires it to use the UI.
+    pub fn from_env() -> Self {
+        let pin = secrets::get_or_env("AMARU_PI_PIN")
+            .map(|pin| pin.trim().to_string())
+            .filter(|pin| {
+                let valid = (MIN_PIN_LENGTH..=MAX_PIN_LENGTH).contains(&pin.len())
+                    && pin.chars().all(|c| c.is_ascii_digit());
+                if !valid {
+                    warn!(
+                        "Ignoring PIN, it must be {} to {} digits",
+                        MIN_PIN_LENGTH, MAX_PIN_LENGTH
+                    );
+                }
+                valid
+            });
+        let lock_ui = env::var("AMARU_PI_PIN_LOCK_UI")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(false);
+        Self {
+            lock_ui: lock_ui && pin.is_some(),
+            pin,
+            state: read_state().unwrap_or_else(|e| {
+                warn!("Failed to read PIN state: {}", e);
+                PinState::default()
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.pin.is_some()
+    }
+
+    pub fn locks_ui(&self) -> bool {
+        self.lock_ui
+    }
+
+    pub fn lockout_remaining(&self) -> Option<Duration> {
+        let now = current_timestamp().unwrap_or(0);
+        (self.state.locked_until > now).then(|| Duration::from_secs(self.state.locked_until - now))
+    }
+
+    pub fn verify(&mut self, attempt: &str) -> PinCheck {
+        let Some(pin) = &self.pin else {
+            return PinCheck::Accepted;
+        };
+        if let Some(remaining) = self.lockout_remaining() {
+            return PinCheck::LockedOut(remaining);
+        }
+        if constant_time_eq(pin.as_bytes(), attempt.as_bytes()) {
+            if self.state.failures > 0 {
+                self.state = PinState::default();
+                self.save();
+            }
+            return PinCheck::Accepted;
+        }
+
+        self.state.failures += 1;
+        warn!("Wrong PIN entered ({} failures)", self.state.failures);
+        let check = match lockout(self.state.failures) {
+            Some(lockout) => {
+                self.state.locked_until = current_timestamp().unwrap_or(0) + lockout.as_secs();
+                PinCheck::LockedOut(lockout)
+            }
+            None => PinCheck::Rejected {
+                attempts_left: FREE_ATTEMPTS - self.state.failures,
+            },
+        };
+        self.save();
+        check
+    }
+
+    fn save(&self) {
+        if let Err(e) = write_state(&self.state) {
+            warn!("Failed to write PIN state: {}", e);
+        }
+    }
+}
+
+/// How long `failures` wrong attempts in a row lock the PIN out, if at all.
+fn lockout(failures: u32) -> Option<Duration> {
+    let extra = failures.checked_sub(FREE_ATTEMPTS)?;
+    Some(
+        BASE_LOCKOUT
+            .saturating_mul(2u32.saturating_pow(extra))
+            .min(MAX_LOCKOUT),
+    )
+}
+
+/// Doesn't leak how many leading digits were right through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn read_state() -> Result<PinState> {
+    let path = Path::new(STATE_FILE_PATH);
+    if !path.exists() {
+        return Ok(PinState::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_state(state: &PinState) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(STATE_FILE_MODE)
+        .open(STATE_FILE_PATH)?;
+    // The mode only applies to new files
+    file.set_permissions(Permissions::from_mode(STATE_FILE_MODE))?;
+    file.write_all(serde_json::to_string_pretty(state)?.as_bytes())?;
+    Ok(())
+}
+
+/// The digits being entered in a PIN prompt. X and Y pick the next digit, A
+/// enters it, holding A submits them and B deletes the last one, or cancels
+/// when there is none. Nothing tells how long the PIN is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinPrompt {
+    pub guarded: Guarded,
+    pub digits: String,
+    /// Digit currently picked with X and Y
+    pub current: u8,
+    pub submitted: bool,
+    pub error: Option<String>,
+    pub locked_until: Option<Instant>,
+    pub cancelled: bool,
+}
+
+impl PinPrompt {
+    pub fn new(guarded: Guarded, lock: &PinLock) -> Self {
+        Self {
+            guarded,
+            digits: String::new(),
+            current: 0,
+            submitted: false,
+            error: None,
+            locked_until: lock.lockout_remaining().map(|d| Instant::now() + d),
+            cancelled: false,
+        }
+    }
+
+    /// Whether no more digits fit, any PIN being at most that long.
+    pub fn is_full(&self) -> bool {
+        self.digits.len() >= MAX_PIN_LENGTH
+    }
+
+    pub fn lockout_remaining(&self) -> Option<Duration> {
+        self.locked_until
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    pub fn reject(&mut self, check: PinCheck) {
+        self.digits.clear();
+        self.current = 0;
+        self.submitted = false;
+        match check {
+            PinCheck::Accepted => {}
+            PinCheck::Rejected { attempts_left } => {
+                self.error = Some(format!("Wrong PIN, {} attempts left", attempts_left));
+            }
+            PinCheck::LockedOut(lockout) => {
+                self.error = Some("Too many attempts".to_string());
+                self.locked_until = Some(Instant::now() + lockout);
+            }
+        }
+    }
+
+    pub fn handle_input(&mut self, event: InputEvent) {
+        let locked = self.lockout_remaining().is_some();
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short | ButtonPress::Repeat) => {
+                self.current = (self.current + 1) % 10;
+            }
+            (ButtonId::Y, ButtonPress::Short | ButtonPress::Repeat) => {
+                self.current = (self.current + 9) % 10;
+            }
+            (ButtonId::A, ButtonPress::Short) if !locked && !self.is_full() => {
+                self.digits.push(char::from(b'0' + self.current));
+                self.error = None;
+            }
+            (ButtonId::A, ButtonPress::Long) if !locked && !self.digits.is_empty() => {
+                self.submitted = true;
+            }
+            // The UI lock can't be dismissed
+            (ButtonId::B, ButtonPress::Short) if self.digits.is_empty() => {
+                self.cancelled = self.guarded != Guarded::Ui;
+            }
+            (ButtonId::B, ButtonPress::Short) => {
+                self.digits.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(locked_until: u64) -> PinLock {
+        PinLock {
+            pin: Some("1234".to_string()),
+            lock_ui: false,
+            state: PinState {
+                failures: 0,
+                locked_until,
+            },
+        }
+    }
+
+    #[test]
+    fn lockout_doubles_after_the_free_attempts() {
+        assert_eq!(lockout(0), None);
+        assert_eq!(lockout(FREE_ATTEMPTS - 1), None);
+        assert_eq!(lockout(FREE_ATTEMPTS), Some(BASE_LOCKOUT));
+        assert_eq!(lockout(FREE_ATTEMPTS + 1), Some(BASE_LOCKOUT * 2));
+        assert_eq!(lockout(FREE_ATTEMPTS + 2), Some(BASE_LOCKOUT * 4));
+        assert_eq!(lockout(FREE_ATTEMPTS + 10), Some(MAX_LOCKOUT));
+        assert_eq!(lockout(u32::MAX), Some(MAX_LOCKOUT));
+    }
+
+    #[test]
+    fn locked_out_even_with_the_right_pin() {
+        let until = current_timestamp().unwrap() + 60;
+        let mut lock = lock(until);
+        assert!(matches!(lock.verify("1234"), PinCheck::LockedOut(_)));
+        assert_eq!(lock.state.locked_until, until);
+    }
+
+    #[test]
+    fn right_pin_accepted_once_the_lockout_is_over() {
+        let mut lock = lock(1);
+        assert!(lock.lockout_remaining().is_none());
+        assert!(matches!(lock.verify("1234"), PinCheck::Accepted));
+    }
+
+    #[test]
+    fn compares_whole_pins() {
+        assert!(constant_time_eq(b"1234", b"1234"));
+        assert!(!constant_time_eq(b"1234", b"1235"));
+        assert!(!constant_time_eq(b"1234", b"12345"));
+        assert!(!constant_time_eq(b"1234", b""));
+    }
+}
@@ -0,0 +1,188 @@
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::process::Command;
+use std::sync::OnceLock;
+use tracing::warn;
+
+/// Read to tell the userland, which can be 32-bit on a 64-bit kernel
+const SHELL_PATH: &str = "/bin/sh";
+/// Present on musl systems, such as Alpine or piCore
+const MUSL_LOADER_PREFIX: &str = "ld-musl-";
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const ELF_CLASS_64: u8 = 2;
+const ELF_MACHINE_ARM: u16 = 0x28;
+const ELF_MACHINE_AARCH64: u16 = 0xb7;
+
+static CURRENT: OnceLock<Platform> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    /// Pi 3, 4 and 5 and Zero 2 W, with a 64-bit OS
+    Aarch64,
+    /// The same Pis with a 32-bit OS
+    Armv7,
+    /// Pi 1 and Zero W
+    Armv6,
+}
+
+impl Arch {
+    /// As in the names of release assets.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arch::Aarch64 => "aarch64",
+            Arch::Armv7 => "armv7",
+            Arch::Armv6 => "arm",
+        }
+    }
+
+    /// What release assets for this architecture are named with.
+    fn tokens(&self) -> &'static [&'static str] {
+        match self {
+            Arch::Aarch64 => &["aarch64", "arm64"],
+            Arch::Armv7 => &["armv7", "armhf"],
+            Arch::Armv6 => &["armv6", "arm-unknown"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Libc {
+    Gnu,
+    Musl,
+}
+
+/// The architecture and C library of the running system, which pick the
+/// release assets installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Platform {
+    pub arch: Arch,
+    pub libc: Libc,
+}
+
+impl fmt::Display for Platform {
+    /// The Rust target triple, e.g. `armv7-unknown-linux-gnueabihf`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let abi = match (self.arch, self.libc) {
+            (Arch::Aarch64, Libc::Gnu) => "gnu",
+            (Arch::Aarch64, Libc::Musl) => "musl",
+            (_, Libc::Gnu) => "gnueabihf",
+            (_, Libc::Musl) => "musleabihf",
+        };
+        write!(f, "{}-unknown-linux-{}", self.arch.name(), abi)
+    }
+}
+
+impl Platform {
+    /// Parses a target triple such as `aarch64-unknown-linux-musl`.
+    pub fn parse(triple: &str) -> Option<Self> {
+        let triple = triple.trim().to_lowercase();
+        let (arch, rest) = triple.split_once('-')?;
+        let arch = match arch {
+            "aarch64" | "arm64" => Arch::Aarch64,
+            arch if arch.starts_with("armv7") => Arch::Armv7,
+            "arm" | "armv6" => Arch::Armv6,
+            _ => return None,
+        };
+        let libc = if rest.contains("musl") {
+            Libc::Musl
+        } else {
+            Libc::Gnu
+        };
+        Some(Self { arch, libc })
+    }
+
+    /// Reads `AMARU_PI_PLATFORM`, a target triple, or else looks at the
+    /// system once.
+    pub fn current() -> Self {
+        *CURRENT.get_or_init(|| {
+            if let Ok(triple) = env::var("AMARU_PI_PLATFORM") {
+                match Self::parse(&triple) {
+                    Some(platform) => return platform,
+                    None => warn!("Unknown AMARU_PI_PLATFORM {}", triple),
+                }
+            }
+            Self::detect()
+        })
+    }
+
+    fn detect() -> Self {
+        Self {
+            arch: detect_arch().unwrap_or(Arch::Aarch64),
+            libc: detect_libc(),
+        }
+    }
+
+    /// Whether a release asset, e.g. `amaru-pi-v1.2.0-aarch64-unknown-linux-gnu.tar.gz`,
+    /// is built for this platform. Assets not naming their C library are
+    /// taken for glibc.
+    pub fn matches(&self, asset: &str) -> bool {
+        let asset = asset.to_lowercase();
+        let arch = self.arch.tokens().iter().any(|token| asset.contains(token));
+        let musl = asset.contains("musl");
+        arch && musl == (self.libc == Libc::Musl)
+    }
+
+    /// The asset of `binary` built for this platform, among the names of those
+    /// of a release.
+    pub fn select<'a>(&self, binary: &str, assets: &[&'a str]) -> Option<&'a str> {
+        assets.iter().copied().find(|asset| {
+            // `amaru` isn't `amaru-pi`
+            let versioned = asset
+                .strip_prefix(binary)
+                .and_then(|rest| rest.strip_prefix('-'))
+                .is_some_and(|rest| rest.starts_with(|c: char| c == 'v' || c.is_ascii_digit()));
+            versioned && asset.ends_with(".tar.gz") && self.matches(asset)
+        })
+    }
+}
+
+/// Splits an asset name at its architecture, e.g. `amaru-pi-v1.2.0` and
+/// `aarch64-unknown-linux-gnu.tar.gz`.
+pub fn split_asset(name: &str) -> Option<(&str, &str)> {
+    [Arch::Aarch64, Arch::Armv7, Arch::Armv6]
+        .iter()
+        .flat_map(|arch| arch.tokens())
+        .filter_map(|token| name.find(&format!("-{}", token)))
+        .min()
+        .map(|index| (&name[..index], &name[index + 1..]))
+}
+
+/// From the ELF header of the shell, as the kernel can be 64-bit under a
+/// 32-bit userland.
+fn detect_arch() -> Option<Arch> {
+    let mut header = [0u8; 20];
+    File::open(SHELL_PATH).ok()?.read_exact(&mut header).ok()?;
+    if !header.starts_with(ELF_MAGIC) {
+        return None;
+    }
+    let machine = u16::from_le_bytes([header[18], header[19]]);
+    if header[4] == ELF_CLASS_64 && machine == ELF_MACHINE_AARCH64 {
+        return Some(Arch::Aarch64);
+    }
+    if machine != ELF_MACHINE_ARM {
+        return None;
+    }
+    let output = Command::new("uname").arg("-m").output().ok()?;
+    let machine = String::from_utf8_lossy(&output.stdout);
+    Some(if machine.trim().starts_with("armv6") {
+        Arch::Armv6
+    } else {
+        Arch::Armv7
+    })
+}
+
+fn detect_libc() -> Libc {
+    let musl = ["/lib", "/usr/lib"].iter().any(|dir| {
+        fs::read_dir(dir).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(MUSL_LOADER_PREFIX)
+            })
+        })
+    });
+    if musl { Libc::Musl } else { Libc::Gnu }
+}
@@ -0,0 +1,166 @@
+use crate::chain::koios_url;
+use crate::util::current_timestamp;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const CACHE_FILE_PATH: &str = "/home/pi/.amaru_pools_cache.json";
+const DEFAULT_REFRESH_SECS: u64 = 15 * 60; // 15 minutes
+
+/// Public figures about a stake pool.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolInfo {
+    pub pool_id: String,
+    #[serde(default)]
+    pub ticker: Option<String>,
+    #[serde(default)]
+    pub live_stake: u64,
+    #[serde(default)]
+    pub active_stake: u64,
+    #[serde(default)]
+    pub saturation: f64,
+    #[serde(default)]
+    pub blocks: u64,
+}
+
+impl PoolInfo {
+    /// The ticker if known, a shortened pool id otherwise.
+    pub fn label(&self) -> String {
+        self.ticker.clone().unwrap_or_else(|| {
+            let end = self.pool_id.len().min(12);
+            format!("{}…", &self.pool_id[..end])
+        })
+    }
+}
+
+/// The last successfully fetched pools, persisted to survive restarts and
+/// network outages.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolsSnapshot {
+    #[serde(default)]
+    pub pools: Vec<PoolInfo>,
+    #[serde(default)]
+    pub fetched_at: u64,
+}
+
+impl PoolsSnapshot {
+    pub fn age(&self) -> Option<Duration> {
+        if self.fetched_at == 0 {
+            return None;
+        }
+        let now = current_timestamp().ok()?;
+        Some(Duration::from_secs(now.saturating_sub(self.fetched_at)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolsConfig {
+    /// Bech32 ids of the pools to watch
+    pub pool_ids: Vec<String>,
+    /// Base URL of a Koios compatible API
+    pub api_url: String,
+    pub refresh_interval: Duration,
+}
+
+impl PoolsConfig {
+    /// Reads `AMARU_PI_WATCHED_POOLS` (comma separated pool ids) and
+    /// `AMARU_PI_POOLS_REFRESH_SECS`.
+    pub fn from_env() -> Self {
+        let pool_ids = env::var("AMARU_PI_WATCHED_POOLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let api_url = koios_url();
+        let refresh_interval = env::var("AMARU_PI_POOLS_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_SECS));
+        Self {
+            pool_ids,
+            api_url,
+            refresh_interval,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.pool_ids.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct KoiosMeta {
+    ticker: Option<String>,
+}
+
+/// Subset of the Koios `pool_info` response. Lovelace amounts are strings.
+#[derive(Debug, Deserialize)]
+struct KoiosPoolInfo {
+    pool_id_bech32: String,
+    active_stake: Option<String>,
+    live_stake: Option<String>,
+    live_saturation: Option<f64>,
+    block_count: Option<u64>,
+    meta_json: Option<KoiosMeta>,
+}
+
+impl From<KoiosPoolInfo> for PoolInfo {
+    fn from(info: KoiosPoolInfo) -> Self {
+        let lovelace = |s: Option<String>| s.and_then(|s| s.parse().ok()).unwrap_or(0);
+        Self {
+            pool_id: info.pool_id_bech32,
+            ticker: info.meta_json.and_then(|m| m.ticker),
+            live_stake: lovelace(info.live_stake),
+            active_stake: lovelace(info.active_stake),
+            saturation: info.live_saturation.unwrap_or(0.0),
+            blocks: info.block_count.unwrap_or(0),
+        }
+    }
+}
+
+/// Fetches the watched pools, keeping the configured order.
+pub async fn fetch_pools(config: &PoolsConfig) -> Result<PoolsSnapshot> {
+    let url = format!("{}/pool_info", config.api_url.trim_end_matches('/'));
+    let infos: Vec<KoiosPoolInfo> = reqwest::Client::new()
+        .post(url)
+        .timeout(Duration::from_secs(30))
+        .json(&serde_json::json!({ "_pool_bech32_ids": config.pool_ids }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let mut pools: Vec<PoolInfo> = infos.into_iter().map(PoolInfo::from).collect();
+    pools.sort_by_key(|p| {
+        config
+            .pool_ids
+            .iter()
+            .position(|id| *id == p.pool_id)
+            .unwrap_or(usize::MAX)
+    });
+
+    Ok(PoolsSnapshot {
+        pools,
+        fetched_at: current_timestamp()?,
+    })
+}
+
+pub fn read_cache() -> Result<PoolsSnapshot> {
+    let path = Path::new(CACHE_FILE_PATH);
+    if !path.exists() {
+        return Ok(PoolsSnapshot::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn write_cache(snapshot: &PoolsSnapshot) -> Result<()> {
+    fs::write(CACHE_FILE_PATH, serde_json::to_string_pretty(snapshot)?)?;
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use crate::node_config::{Field, NodeConfig};
+use crate::topology::{self, Reachability};
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, UdpSocket};
+use std::time::Duration;
+
+/// amaru's default, when the node config doesn't set a listen address
+const DEFAULT_PORT: u16 = 3001;
+const DEFAULT_PUBLIC_IP_URL: &str = "https://api.ipify.org";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// How the port is checked from the internet.
+#[derive(Clone, Debug)]
+pub struct PortCheckConfig {
+    /// Answers the public address of the Pi, as plain text
+    pub public_ip_url: String,
+    /// Of a service connecting back, with `{ip}` and `{port}` replaced and
+    /// answering JSON with a boolean `reachable` or `open`. Without one, the
+    /// node is connected to through the public address, which needs the
+    /// router to support NAT loopback.
+    pub service_url: Option<String>,
+}
+
+impl PortCheckConfig {
+    /// Reads `AMARU_PI_PUBLIC_IP_URL` and `AMARU_PI_PORT_CHECK_URL`.
+    pub fn from_env() -> Self {
+        Self {
+            public_ip_url: env::var("AMARU_PI_PUBLIC_IP_URL")
+                .unwrap_or_else(|_| DEFAULT_PUBLIC_IP_URL.to_string()),
+            service_url: env::var("AMARU_PI_PORT_CHECK_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PortVerdict {
+    /// The node doesn't accept connections even from the Pi
+    NotListening,
+    Reachable,
+    /// Listening, but not from the internet: the port isn't forwarded
+    Unreachable,
+    /// The check couldn't tell, e.g. without internet
+    Unknown(String),
+}
+
+/// Outcome of checking the P2P port of the node from the internet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortCheck {
+    pub port: u16,
+    /// To forward the port to
+    pub lan_ip: Option<IpAddr>,
+    pub public_ip: Option<IpAddr>,
+    /// Whether a check service answered, rather than NAT loopback
+    pub by_service: bool,
+    pub verdict: PortVerdict,
+    pub checked_at: u64,
+}
+
+/// The check last run on the reachability screen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PortCheckState {
+    pub checking: bool,
+    pub last: Option<PortCheck>,
+}
+
+/// The port of the node's listen address.
+pub fn listen_port() -> u16 {
+    NodeConfig::load()
+        .ok()
+        .and_then(|config| config.get(Field::ListenAddress))
+        .and_then(|address| address.rsplit_once(':')?.1.parse().ok())
+        .unwrap_or(DEFAULT_PORT)
+}
+
+/// The address the Pi reaches the internet from, on the LAN. Connecting a
+/// UDP socket sends nothing.
+fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(1, 1, 1, 1), 80)).ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+async fn public_ip(config: &PortCheckConfig) -> Result<IpAddr> {
+    let text = reqwest::Client::new()
+        .get(&config.public_ip_url)
+        .timeout(TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    text.trim()
+        .parse()
+        .map_err(|_| anyhow!("Unexpected public address {}", text.trim()))
+}
+
+async fn ask_service(url: &str, ip: IpAddr, port: u16) -> Result<bool> {
+    let url = url
+        .replace("{ip}", &ip.to_string())
+        .replace("{port}", &port.to_string());
+    let response: Value = reqwest::Client::new()
+        .get(url)
+        .timeout(TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    response["reachable"]
+        .as_bool()
+        .or_else(|| response["open"].as_bool())
+        .ok_or_else(|| anyhow!("No reachable nor open in the answer"))
+}
+
+/// Checks that the node listens, then that it can be reached through the
+/// public address of the Pi.
+pub async fn check(config: &PortCheckConfig) -> PortCheck {
+    let port = listen_port();
+    let lan_ip = lan_ip();
+    let mut result = PortCheck {
+        port,
+        lan_ip,
+        public_ip: None,
+        by_service: config.service_url.is_some(),
+        verdict: PortVerdict::NotListening,
+        checked_at: current_timestamp().unwrap_or_default(),
+    };
+
+    let local = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listening = tokio::task::spawn_blocking(move || {
+        TcpStream::connect_timeout(&local, CONNECT_TIMEOUT).is_ok()
+    })
+    .await
+    .unwrap_or(false);
+    if !listening {
+        return result;
+    }
+
+    let public_ip = match public_ip(config).await {
+        Ok(ip) => ip,
+        Err(e) => {
+            result.verdict = PortVerdict::Unknown(format!("No public address: {}", e));
+            return result;
+        }
+    };
+    result.public_ip = Some(public_ip);
+    result.verdict = match &config.service_url {
+        Some(url) => match ask_service(url, public_ip, port).await {
+            Ok(true) => PortVerdict::Reachable,
+            Ok(false) => PortVerdict::Unreachable,
+            Err(e) => PortVerdict::Unknown(e.to_string()),
+        },
+        None => {
+            let address = SocketAddr::new(public_ip, port).to_string();
+            match tokio::task::spawn_blocking(move || topology::test_peer(&address)).await {
+                Ok(Reachability::Reachable { .. } | Reachability::Refused) => {
+                    PortVerdict::Reachable
+                }
+                Ok(Reachability::Unreachable(_)) => PortVerdict::Unreachable,
+                Err(e) => PortVerdict::Unknown(e.to_string()),
+            }
+        }
+    };
+    result
+}
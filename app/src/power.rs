@@ -0,0 +1,225 @@
+use crate::backup::NODE_SERVICE;
+use crate::oplock::{self, Operation};
+use crate::systemd;
+use anyhow::{Result, anyhow};
+use std::cmp::Ordering;
+use std::env;
+use std::process::Command;
+use tracing::{info, warn};
+
+const DEFAULT_SHUTDOWN_PERCENT: u8 = 10;
+const UI_SERVICE: &str = "amaru-pi.service";
+/// Of a single Li-ion cell, empty and full
+#[cfg(feature = "gpio")]
+const CELL_EMPTY_VOLTS: f32 = 3.0;
+#[cfg(feature = "gpio")]
+const CELL_FULL_VOLTS: f32 = 4.2;
+#[cfg(feature = "gpio")]
+const INA219_SHUNT_VOLTAGE: u8 = 0x01;
+#[cfg(feature = "gpio")]
+const INA219_BUS_VOLTAGE: u8 = 0x02;
+#[cfg(feature = "gpio")]
+const MAX17040_VCELL: u8 = 0x02;
+#[cfg(feature = "gpio")]
+const MAX17040_SOC: u8 = 0x04;
+
+/// The chip a UPS HAT reports its battery through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsKind {
+    /// Current and voltage monitor, the charge being guessed from the
+    /// voltage
+    Ina219(u16),
+    /// Fuel gauge, e.g. on the Geekworm X7xx HATs
+    Max17040(u16),
+}
+
+impl UpsKind {
+    /// Parses `ina219:<address>` or `max17040:<address>`, e.g. `ina219:0x42`.
+    fn parse(s: &str) -> Option<Self> {
+        let (kind, address) = s.trim().split_once(':')?;
+        let address = u16::from_str_radix(address.trim_start_matches("0x"), 16).ok()?;
+        match kind {
+            "ina219" => Some(UpsKind::Ina219(address)),
+            "max17040" | "max17043" | "max17048" => Some(UpsKind::Max17040(address)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpsConfig {
+    pub kind: UpsKind,
+    /// Li-ion cells in series, for the INA219
+    pub cells: u8,
+    /// The node is stopped at or below that charge, on battery
+    pub shutdown_percent: u8,
+}
+
+impl UpsConfig {
+    /// Reads `AMARU_PI_UPS` (see [`UpsKind::parse`]), `AMARU_PI_UPS_CELLS`
+    /// and `AMARU_PI_UPS_SHUTDOWN_PERCENT`. `None` without a UPS.
+    pub fn from_env() -> Option<Self> {
+        let var = env::var("AMARU_PI_UPS").ok()?;
+        let Some(kind) = UpsKind::parse(&var) else {
+            warn!("Ignoring invalid AMARU_PI_UPS {}", var);
+            return None;
+        };
+        let parse = |name: &str| env::var(name).ok().and_then(|s| s.trim().parse().ok());
+        Some(Self {
+            kind,
+            cells: parse("AMARU_PI_UPS_CELLS")
+                .filter(|cells| *cells > 0)
+                .unwrap_or(1),
+            shutdown_percent: parse("AMARU_PI_UPS_SHUTDOWN_PERCENT")
+                .unwrap_or(DEFAULT_SHUTDOWN_PERCENT),
+        })
+    }
+}
+
+/// What the Power screen does, once confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    Shutdown,
+    Reboot,
+    /// Restarts amaru-pi alone, the node keeps running
+    RestartUi,
+}
+
+impl PowerAction {
+    pub const ALL: [PowerAction; 3] = [
+        PowerAction::Shutdown,
+        PowerAction::Reboot,
+        PowerAction::RestartUi,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerAction::Shutdown => "Shut down",
+            PowerAction::Reboot => "Reboot",
+            PowerAction::RestartUi => "Restart UI",
+        }
+    }
+
+    /// Shown to confirm, e.g. `Shut down the Pi?`.
+    pub fn question(&self) -> &'static str {
+        match self {
+            PowerAction::Shutdown => "Shut down the Pi?",
+            PowerAction::Reboot => "Reboot the Pi?",
+            PowerAction::RestartUi => "Restart amaru-pi?",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PowerStatus {
+    /// Charge of the battery
+    pub percent: u8,
+    pub volts: f32,
+    /// `None` when the chip can't tell
+    pub charging: Option<bool>,
+}
+
+impl PowerStatus {
+    pub fn on_battery(&self) -> bool {
+        self.charging == Some(false)
+    }
+
+    /// Tells whether the battery charges from how its charge went since the
+    /// previous status, for the chips that can't tell.
+    pub fn following(mut self, previous: Option<PowerStatus>) -> Self {
+        if self.charging.is_none()
+            && let Some(previous) = previous
+        {
+            self.charging = match self.percent.cmp(&previous.percent) {
+                Ordering::Greater => Some(true),
+                Ordering::Less => Some(false),
+                Ordering::Equal => previous.charging,
+            };
+        }
+        self
+    }
+}
+
+/// Charge of a Li-ion cell from its voltage, linear in between, which is
+/// close enough to warn before it runs out.
+#[cfg(feature = "gpio")]
+fn percent_from_cell_volts(volts: f32) -> u8 {
+    let ratio = (volts - CELL_EMPTY_VOLTS) / (CELL_FULL_VOLTS - CELL_EMPTY_VOLTS);
+    (ratio.clamp(0.0, 1.0) * 100.0).round() as u8
+}
+
+#[cfg(feature = "gpio")]
+fn read_word(i2c: &mut rppal::i2c::I2c, register: u8) -> Result<u16> {
+    // Both chips send the most significant byte first
+    Ok(i2c.smbus_read_word(register)?.swap_bytes())
+}
+
+#[cfg(feature = "gpio")]
+pub fn read(config: &UpsConfig) -> Result<PowerStatus> {
+    let mut i2c = rppal::i2c::I2c::new()?;
+    match config.kind {
+        UpsKind::Ina219(address) => {
+            i2c.set_slave_address(address)?;
+            let volts = (read_word(&mut i2c, INA219_BUS_VOLTAGE)? >> 3) as f32 * 0.004;
+            // Across the shunt, positive while the battery charges
+            let shunt = read_word(&mut i2c, INA219_SHUNT_VOLTAGE)? as i16;
+            Ok(PowerStatus {
+                percent: percent_from_cell_volts(volts / config.cells as f32),
+                volts,
+                charging: Some(shunt > 0),
+            })
+        }
+        UpsKind::Max17040(address) => {
+            i2c.set_slave_address(address)?;
+            // 12 bits in 1.25mV
+            let volts = (read_word(&mut i2c, MAX17040_VCELL)? >> 4) as f32 * 0.001_25;
+            let soc = read_word(&mut i2c, MAX17040_SOC)?;
+            Ok(PowerStatus {
+                percent: ((soc >> 8) as u8).min(100),
+                volts,
+                charging: None,
+            })
+        }
+    }
+}
+
+#[cfg(not(feature = "gpio"))]
+pub fn read(_config: &UpsConfig) -> Result<PowerStatus> {
+    Err(anyhow!("UPS HATs can only be read on the device"))
+}
+
+/// Stops the node before the battery runs out, for its databases to be
+/// left consistent.
+pub fn shut_down_node(percent: u8) -> Result<()> {
+    warn!("Battery at {}%, stopping the node", percent);
+    systemd::stop_service(NODE_SERVICE)
+        .map_err(|e| anyhow!("Failed to stop the node on low battery: {}", e))?;
+    info!("Stopped the node on low battery");
+    Ok(())
+}
+
+/// Stops the node for its databases to be left consistent, flushes the
+/// disks, then shuts down or reboots the Pi. Restarting the UI leaves the
+/// node alone.
+pub fn run(action: PowerAction) -> Result<()> {
+    if action == PowerAction::RestartUi {
+        info!("Restarting amaru-pi");
+        return systemd::restart_service(UI_SERVICE);
+    }
+    let _lock = oplock::try_acquire(Operation::PowerOff)?;
+    info!("{}: stopping the node", action.label());
+    systemd::stop_service(NODE_SERVICE).map_err(|e| anyhow!("Failed to stop the node: {}", e))?;
+    match Command::new("sync").status() {
+        Ok(status) if !status.success() => warn!("sync exited with {}", status),
+        Ok(_) => info!("{}: node stopped, disks synced", action.label()),
+        Err(e) => warn!("Failed to sync the disks: {}", e),
+    }
+    let result = systemd::power_off(action == PowerAction::Reboot);
+    // Still up, the node shouldn't stay stopped
+    if result.is_err()
+        && let Err(e) = systemd::start_service(NODE_SERVICE)
+    {
+        warn!("Failed to start the node again: {}", e);
+    }
+    result
+}
@@ -0,0 +1,220 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "display_hat")]
+use crate::logs::extract_json;
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+
+const DEFAULT_PROCESSES: [&str; 4] = ["amaru", "amaru-pi", "NetworkManager", "systemd-journald"];
+/// `/proc/<pid>/comm` is truncated to this many bytes
+const COMM_LEN: usize = 15;
+/// `USER_HZ`, which is 100 on every Linux the Pi runs
+const CLOCK_TICKS: f64 = 100.0;
+/// CPU usage is averaged over this long
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Journal lines shown for a process
+#[cfg(feature = "display_hat")]
+const JOURNAL_LINES: usize = 20;
+
+/// A running process, as `top` shows it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// `R` running, `S` sleeping, `D` waiting on I/O, `Z` zombie...
+    pub state: char,
+    /// Of a single core, so up to 400% on a Pi 4
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    /// systemd service the process belongs to, if any
+    pub unit: Option<String>,
+}
+
+/// From `AMARU_PI_PROCESSES`, a comma separated list of process names.
+pub fn watched() -> Vec<String> {
+    env::var("AMARU_PI_PROCESSES")
+        .ok()
+        .map(|var| {
+            var.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_PROCESSES.iter().map(|s| s.to_string()).collect())
+}
+
+/// The watched name matching a process name as the kernel truncates it.
+fn watched_name<'a>(watched: &'a [String], comm: &str) -> Option<&'a String> {
+    watched.iter().find(|name| {
+        let truncated = &name[..name.len().min(COMM_LEN)];
+        truncated == comm
+    })
+}
+
+/// Fields of `/proc/<pid>/stat`.
+struct Stat {
+    state: char,
+    /// User and system time, in clock ticks
+    cpu_ticks: u64,
+}
+
+/// Parses `/proc/<pid>/stat`, whose second field is the name in parentheses,
+/// which may itself contain spaces or parentheses.
+fn parse_stat(stat: &str) -> Option<Stat> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let state = fields.first()?.chars().next()?;
+    // utime and stime are the 14th and 15th fields, the state the 3rd
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(Stat {
+        state,
+        cpu_ticks: utime + stime,
+    })
+}
+
+/// Resident memory from the `VmRSS:` line of `/proc/<pid>/status`.
+fn parse_rss(status: &str) -> Option<u64> {
+    let kb: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// Service from `/proc/<pid>/cgroup`, e.g. `0::/system.slice/amaru.service`.
+fn parse_unit(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .filter_map(|line| line.rsplit('/').next())
+        .find(|name| name.ends_with(".service"))
+        .map(|name| name.to_string())
+}
+
+/// Watched processes by PID, with their CPU time.
+fn snapshot(watched: &[String]) -> Result<HashMap<u32, (ProcessInfo, u64)>> {
+    let mut processes = HashMap::new();
+    for entry in fs::read_dir("/proc")? {
+        let Some(pid) = entry?
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        // Processes may exit while being read
+        let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", pid)) else {
+            continue;
+        };
+        let Some(name) = watched_name(watched, comm.trim()) else {
+            continue;
+        };
+        let Some(stat) = fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|stat| parse_stat(&stat))
+        else {
+            continue;
+        };
+        let rss_bytes = fs::read_to_string(format!("/proc/{}/status", pid))
+            .ok()
+            .and_then(|status| parse_rss(&status))
+            .unwrap_or_default();
+        let unit = fs::read_to_string(format!("/proc/{}/cgroup", pid))
+            .ok()
+            .and_then(|cgroup| parse_unit(&cgroup));
+        let info = ProcessInfo {
+            pid,
+            name: name.clone(),
+            state: stat.state,
+            cpu_percent: 0.0,
+            rss_bytes,
+            unit,
+        };
+        processes.insert(pid, (info, stat.cpu_ticks));
+    }
+    Ok(processes)
+}
+
+/// Watched processes in the order they are listed, with their CPU usage over
+/// `interval`.
+pub fn sample(interval: Duration) -> Result<Vec<ProcessInfo>> {
+    let watched = watched();
+    let before = snapshot(&watched)?;
+    let start = Instant::now();
+    thread::sleep(interval);
+    let after = snapshot(&watched)?;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let mut processes: Vec<ProcessInfo> = after
+        .into_values()
+        .map(|(mut info, ticks)| {
+            // A process started in between used its CPU time since then
+            let previous = before.get(&info.pid).map(|(_, ticks)| *ticks).unwrap_or(0);
+            let used = ticks.saturating_sub(previous) as f64 / CLOCK_TICKS;
+            info.cpu_percent = used / elapsed * 100.0;
+            info
+        })
+        .collect();
+    processes.sort_by_key(|process| {
+        let rank = watched.iter().position(|name| *name == process.name);
+        (rank, process.pid)
+    });
+    Ok(processes)
+}
+
+/// Last journal lines of a process, of its whole service when it has one.
+/// The node's JSON lines are shortened to their level and message.
+#[cfg(feature = "display_hat")]
+pub fn journal(pid: u32, unit: Option<&str>) -> Result<Vec<String>> {
+    let filter = match unit {
+        Some(unit) => format!("--unit={}", unit),
+        None => format!("_PID={}", pid),
+    };
+    let lines = JOURNAL_LINES.to_string();
+    let output = run_and_capture(
+        "journalctl",
+        [
+            filter.as_str(),
+            "--lines",
+            lines.as_str(),
+            "--output=cat",
+            "--no-pager",
+        ]
+        .to_vec(),
+    )?;
+    Ok(output
+        .lines()
+        .map(|line| {
+            let entry = if line.starts_with('{') {
+                extract_json(line)
+            } else {
+                None
+            };
+            match entry {
+                Some(entry) => format!(
+                    "[{}] {}",
+                    entry.level,
+                    entry.fields.map(|f| f.message).unwrap_or_default()
+                ),
+                None => line.to_string(),
+            }
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn journal(_pid: u32, _unit: Option<&str>) -> Result<Vec<String>> {
+    Err(anyhow::anyhow!(
+        "The journal is only available on the device"
+    ))
+}
@@ -0,0 +1,259 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::kiosk;
+use crate::screen_text::buffer_text;
+use crate::screens::Kind;
+use crate::web_auth::{self, WebAuth};
+use axum::Router;
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use indoc::indoc;
+use ratatui::buffer::Buffer;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+use tracing::{info, warn};
+
+/// The display is sent at most that often
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(250);
+/// Presses waiting for the next frame
+const QUEUE_CAPACITY: usize = 16;
+
+/// A page with the display and the four buttons, served at `/`.
+const PAGE: &str = indoc! {r#"
+    <!DOCTYPE html>
+    <html>
+    <head>
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>Amaru remote</title>
+    <style>
+    body { background: #000; color: #eee; font-family: sans-serif; text-align: center; margin: 0; }
+    pre { font-size: 2.4vw; text-align: left; display: inline-block; margin: 1em 0; }
+    .buttons { display: grid; grid-template-columns: 1fr 1fr; gap: 1em; padding: 1em; }
+    button { font-size: 2em; padding: 0.8em; border-radius: 0.4em; border: 0; }
+    </style>
+    </head>
+    <body>
+    <pre id="screen">Connecting…</pre>
+    <div class="buttons">
+    <button data-press="A">A</button><button data-press="X">X</button>
+    <button data-press="B">B</button><button data-press="Y">Y</button>
+    </div>
+    <script>
+    const ws = new WebSocket(`ws://${location.host}/ws${location.search}`);
+    ws.onmessage = (message) => {
+      const state = JSON.parse(message.data);
+      document.getElementById("screen").textContent = state.text;
+    };
+    ws.onclose = () => document.getElementById("screen").textContent = "Disconnected";
+    let lastPress = {};
+    for (const button of document.querySelectorAll("button")) {
+      button.onclick = () => {
+        const press = button.dataset.press;
+        // A second click within 300 ms makes a double press
+        const type = Date.now() - (lastPress[press] || 0) < 300 ? "Double" : "Short";
+        lastPress[press] = type === "Double" ? 0 : Date.now();
+        ws.send(JSON.stringify({ press, type }));
+      };
+    }
+    </script>
+    </body>
+    </html>
+"#};
+
+/// What the display shows, sent to the clients as it changes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+struct RemoteState {
+    screen: String,
+    text: String,
+}
+
+/// A press sent by a client, e.g. `{"press": "A", "type": "Double"}`.
+#[derive(Debug, Deserialize)]
+struct RemotePress {
+    press: ButtonId,
+    /// A short press when not given
+    #[serde(rename = "type")]
+    press_type: Option<ButtonPress>,
+}
+
+#[derive(Clone)]
+struct Channels {
+    state: watch::Receiver<RemoteState>,
+    inputs: mpsc::Sender<InputEvent>,
+    /// Pages allowed to connect besides the one served, e.g. `https://pi.example`
+    origins: Vec<String>,
+}
+
+/// Lets a phone act as the buttons of a device mounted behind glass. A
+/// WebSocket at `/ws` streams the text of the display and takes presses, and
+/// `/` serves a page with both.
+pub struct RemoteServer {
+    state: watch::Sender<RemoteState>,
+    inputs: mpsc::Receiver<InputEvent>,
+    last_published: Option<Instant>,
+}
+
+impl RemoteServer {
+    /// Listens on `AMARU_PI_REMOTE_PORT`, when set, of the Pi itself unless
+    /// `AMARU_PI_REMOTE_BIND` says otherwise, e.g. `0.0.0.0`. Pressing the
+    /// buttons confirms dialogs, so there is no remote without `WebAuth`
+    /// nor in kiosk mode.
+    pub async fn from_env() -> Option<Self> {
+        let port: u16 = env::var("AMARU_PI_REMOTE_PORT").ok()?.trim().parse().ok()?;
+        if kiosk::is_enabled() {
+            warn!("No remote in kiosk mode");
+            return None;
+        }
+        let auth = WebAuth::from_env();
+        if !auth.is_enabled() {
+            warn!("No remote without AMARU_PI_API_TOKEN nor AMARU_PI_AUTH_PROXY_HEADER");
+            return None;
+        }
+        let ip = env::var("AMARU_PI_REMOTE_BIND")
+            .ok()
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let address = SocketAddr::new(ip, port);
+        let listener = match TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("Failed to listen on {}: {}", address, e);
+                return None;
+            }
+        };
+        let (state_tx, state_rx) = watch::channel(RemoteState::default());
+        let (inputs_tx, inputs_rx) = mpsc::channel(QUEUE_CAPACITY);
+        let router = Router::new()
+            .route("/", get(|| async { Html(PAGE) }))
+            .route("/ws", get(upgrade))
+            .with_state(Channels {
+                state: state_rx,
+                inputs: inputs_tx,
+                origins: env::var("AMARU_PI_REMOTE_ORIGINS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|origin| origin.trim().trim_end_matches('/').to_string())
+                    .filter(|origin| !origin.is_empty())
+                    .collect(),
+            })
+            .layer(middleware::from_fn_with_state(auth, web_auth::require));
+        tokio::spawn(async move {
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(e) = axum::serve(listener, service).await {
+                warn!("The remote stopped: {}", e);
+            }
+        });
+        info!("Remote listening on {}", address);
+        Some(Self {
+            state: state_tx,
+            inputs: inputs_rx,
+            last_published: None,
+        })
+    }
+
+    /// Sends what the display shows to the clients, if any is connected.
+    pub fn publish(&mut self, kind: Kind, buffer: &Buffer) {
+        if self.state.receiver_count() <= 1
+            || self
+                .last_published
+                .is_some_and(|last| last.elapsed() < PUBLISH_INTERVAL)
+        {
+            return;
+        }
+        self.last_published = Some(Instant::now());
+        let state = RemoteState {
+            screen: kind.to_string(),
+            text: buffer_text(buffer),
+        };
+        // Only wakes the clients up when it changed
+        self.state.send_if_modified(|current| {
+            let modified = *current != state;
+            *current = state;
+            modified
+        });
+    }
+
+    /// The presses received since last asked.
+    pub fn inputs(&mut self) -> Vec<InputEvent> {
+        std::iter::from_fn(|| self.inputs.try_recv().ok()).collect()
+    }
+}
+
+/// Whether the page opening the WebSocket is the one served or a listed
+/// one. Browsers send the origin of any page, a malicious one included;
+/// other clients send none.
+fn allows_origin(headers: &HeaderMap, origins: &[String]) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    let served = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .is_some_and(|host| {
+            origin == format!("http://{}", host) || origin == format!("https://{}", host)
+        });
+    served || origins.iter().any(|allowed| allowed == origin)
+}
+
+async fn upgrade(
+    upgrade: WebSocketUpgrade,
+    headers: HeaderMap,
+    State(channels): State<Channels>,
+) -> Response {
+    if !allows_origin(&headers, &channels.origins) {
+        warn!("Refused the remote to {:?}", headers.get(header::ORIGIN));
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    upgrade.on_upgrade(|socket| serve(socket, channels))
+}
+
+/// Streams the display to a client and forwards its presses, until it
+/// disconnects.
+async fn serve(mut socket: WebSocket, mut channels: Channels) {
+    channels.state.mark_changed();
+    loop {
+        tokio::select! {
+            changed = channels.state.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let state = channels.state.borrow_and_update().clone();
+                let Ok(json) = serde_json::to_string(&state) else {
+                    continue;
+                };
+                if socket.send(Message::Text(json.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(_)) => continue,
+                    _ => break,
+                };
+                match serde_json::from_str::<RemotePress>(&text) {
+                    Ok(press) => {
+                        let event = InputEvent {
+                            id: press.press,
+                            press_type: press.press_type.unwrap_or(ButtonPress::Short),
+                        };
+                        if channels.inputs.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => warn!("Ignoring the remote message {}: {}", text.as_str(), e),
+                }
+            }
+        }
+    }
+}
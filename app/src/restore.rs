@@ -0,0 +1,368 @@
+use crate::backup::{self, BACKUP_DIR, BackupProgress, BackupStage, NODE_SERVICE};
+use crate::download::{self, DownloadProgress};
+use crate::integrity;
+use crate::node_config::{Field, NodeConfig};
+use crate::oplock::{self, Operation};
+use crate::storage;
+use crate::systemd;
+use crate::usb;
+use crate::util::format_bytes;
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Downloaded snapshots are kept here until restored
+const DOWNLOAD_DIR: &str = "/home/pi/snapshots";
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+/// How many times bigger a ledger is than its archive, at least
+const EXPANSION_FACTOR: u64 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotSource {
+    /// In the backup directory of the USB drive
+    Usb,
+    Url(String),
+}
+
+/// A ledger archive, as made by a backup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// File name of the archive
+    pub name: String,
+    pub source: SnapshotSource,
+    /// UNIX time the snapshot was made, when known
+    pub timestamp: Option<u64>,
+    /// Of the archive, in bytes
+    pub size: Option<u64>,
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum RestoreStatus {
+    #[default]
+    Idle,
+    Running(BackupProgress),
+    /// Name of the snapshot restored
+    Done(String),
+    Failed(String),
+}
+
+impl RestoreStatus {
+    pub fn is_running(&self) -> bool {
+        matches!(self, RestoreStatus::Running(_))
+    }
+}
+
+/// An entry of the index at `AMARU_PI_SNAPSHOT_URL`.
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    name: String,
+    url: String,
+    network: String,
+    #[serde(default)]
+    timestamp: Option<u64>,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    sha256: Option<String>,
+}
+
+fn network() -> String {
+    NodeConfig::load()
+        .ok()
+        .and_then(|config| config.get(Field::Network))
+        .unwrap_or_else(|| "mainnet".to_string())
+}
+
+/// Timestamp of an archive named by [`backup::archive_name`] for `network`.
+fn archive_timestamp(name: &str, network: &str) -> Option<u64> {
+    let prefix = backup::archive_name(network, 0);
+    let prefix = prefix.strip_suffix("0.tar.gz")?;
+    name.strip_prefix(prefix)?
+        .strip_suffix(".tar.gz")?
+        .parse()
+        .ok()
+}
+
+/// Snapshots of the node's network on the USB drive, newest first.
+pub fn list_usb() -> Result<Vec<Snapshot>> {
+    if !usb::is_present() {
+        return Ok(Vec::new());
+    }
+    let network = network();
+    let drive = usb::open()?;
+    let dir = drive.path().join(BACKUP_DIR);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let timestamp = archive_timestamp(&name, &network)?;
+            // Written by the backup next to the archive
+            let sha256 = fs::read_to_string(dir.join(format!("{}.sha256", name)))
+                .ok()
+                .and_then(|line| line.split_whitespace().next().map(str::to_string));
+            Some(Snapshot {
+                name,
+                source: SnapshotSource::Usb,
+                timestamp: Some(timestamp),
+                size: entry.metadata().ok().map(|metadata| metadata.len()),
+                sha256,
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.timestamp));
+    Ok(snapshots)
+}
+
+/// Snapshots of the node's network listed by the index at
+/// `AMARU_PI_SNAPSHOT_URL`, none without one.
+pub async fn list_remote() -> Result<Vec<Snapshot>> {
+    let Some(url) = env::var("AMARU_PI_SNAPSHOT_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+    else {
+        return Ok(Vec::new());
+    };
+    let entries: Vec<IndexEntry> = reqwest::Client::new()
+        .get(url.trim())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let network = network();
+    Ok(entries
+        .into_iter()
+        .filter(|entry| entry.network == network)
+        .map(|entry| Snapshot {
+            name: entry.name,
+            source: SnapshotSource::Url(entry.url),
+            timestamp: entry.timestamp,
+            size: entry.size,
+            sha256: entry.sha256,
+        })
+        .collect())
+}
+
+fn download_path(snapshot: &Snapshot) -> PathBuf {
+    Path::new(DOWNLOAD_DIR).join(&snapshot.name)
+}
+
+/// Downloads a remote snapshot ahead of [`restore`], resuming a previous
+/// attempt. Snapshots on the USB drive are restored from there.
+pub async fn fetch(snapshot: &Snapshot, progress: impl Fn(BackupProgress)) -> Result<()> {
+    let SnapshotSource::Url(url) = &snapshot.source else {
+        return Ok(());
+    };
+    fs::create_dir_all(DOWNLOAD_DIR)?;
+    // The archive and the ledger extracted from it
+    let (_, free) = storage::filesystem_space(Path::new(DOWNLOAD_DIR))?;
+    if let Some(size) = snapshot.size
+        && free < size * 2
+    {
+        return Err(anyhow!("Not enough space for the snapshot"));
+    }
+
+    let (tx, mut rx) = mpsc::channel::<DownloadProgress>(16);
+    let forward = async {
+        while let Some(downloaded) = rx.recv().await {
+            progress(BackupProgress {
+                stage: BackupStage::Downloading,
+                done: downloaded.downloaded,
+                total: downloaded.total.unwrap_or(0),
+            });
+        }
+    };
+    let dest = download_path(snapshot);
+    let (result, ()) = tokio::join!(
+        download::download(url, &dest, snapshot.sha256.as_deref(), tx),
+        forward
+    );
+    result
+}
+
+/// Extracts the archive into `dir`, reporting the bytes of the archive read.
+fn extract(archive: &Path, dir: &Path, mut on_progress: impl FnMut(u64)) -> Result<()> {
+    let mut file = File::open(archive)?;
+    let mut tar = Command::new("tar")
+        .arg("-xz")
+        .arg("-C")
+        .arg(dir)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let mut tar_in = tar.stdin.take().ok_or_else(|| anyhow!("No tar input"))?;
+    let mut pump = || -> Result<()> {
+        let mut buf = vec![0; 64 * 1024];
+        let mut done = 0;
+        let mut last_progress = Instant::now();
+        loop {
+            let read = file.read(&mut buf)?;
+            if read == 0 {
+                return Ok(());
+            }
+            tar_in.write_all(&buf[..read])?;
+            done += read as u64;
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                on_progress(done);
+            }
+        }
+    };
+    let pumped = pump();
+    drop(tar_in);
+    if pumped.is_err() {
+        tar.kill().ok();
+    }
+    let status = tar.wait()?;
+    pumped?;
+    if !status.success() {
+        return Err(anyhow!("tar exited with {}", status));
+    }
+    Ok(())
+}
+
+/// The directory the archive extracted to, its only entry.
+fn extracted_dir(dir: &Path) -> Result<PathBuf> {
+    let mut entries = fs::read_dir(dir)?.flatten();
+    match (entries.next(), entries.next()) {
+        (Some(entry), None) if entry.path().is_dir() => Ok(entry.path()),
+        _ => Err(anyhow!("Archive doesn't hold a single ledger directory")),
+    }
+}
+
+/// Puts the extracted ledger in place of the current one, which is removed
+/// once the new one is in.
+fn swap(extracted: &Path, ledger_dir: &Path) -> Result<()> {
+    let old = ledger_dir.with_extension("db.old");
+    if old.exists() {
+        fs::remove_dir_all(&old)?;
+    }
+    if ledger_dir.exists() {
+        fs::rename(ledger_dir, &old)?;
+    }
+    if let Err(e) = fs::rename(extracted, ledger_dir) {
+        if old.exists() {
+            fs::rename(&old, ledger_dir)?;
+        }
+        return Err(e.into());
+    }
+    if old.exists()
+        && let Err(e) = fs::remove_dir_all(&old)
+    {
+        warn!("Failed to remove the previous ledger: {}", e);
+    }
+    Ok(())
+}
+
+/// The space extracting the archive takes. Gzip ends with the size of the
+/// data modulo 4 GiB, which ledgers outgrow, so the smallest size matching
+/// it above the archive is taken, and never less than `EXPANSION_FACTOR`
+/// times the archive.
+fn extracted_size(archive: &Path) -> Result<u64> {
+    let mut file = File::open(archive)?;
+    let len = file.metadata()?.len();
+    if len < 4 {
+        return Err(anyhow!("The archive is truncated"));
+    }
+    let mut trailer = [0; 4];
+    file.seek(SeekFrom::End(-4))?;
+    file.read_exact(&mut trailer)?;
+    let modulo = u64::from(u32::from_le_bytes(trailer));
+    let wraps = len.saturating_sub(modulo).div_ceil(1 << 32);
+    Ok((modulo + (wraps << 32)).max(len.saturating_mul(EXPANSION_FACTOR)))
+}
+
+/// Verifies and extracts the archive next to the ledger, then swaps it in,
+/// the node stopped.
+fn restore_archive(
+    archive: &Path,
+    sha256: Option<&str>,
+    progress: &impl Fn(BackupProgress),
+) -> Result<()> {
+    if let Some(expected) = sha256 {
+        progress(BackupProgress::of(BackupStage::Verifying));
+        let actual = integrity::sha256_file(archive)?;
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(anyhow!("Checksum mismatch, the archive is corrupt"));
+        }
+    }
+
+    let config = NodeConfig::load().ok();
+    let ledger_dir = storage::db_dir(config.as_ref(), Field::LedgerDir, "ledger");
+    let parent = ledger_dir
+        .parent()
+        .ok_or_else(|| anyhow!("Unexpected ledger path {}", ledger_dir.display()))?;
+    let staging = ledger_dir.with_extension("db.restore");
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+    let total = fs::metadata(archive)?.len();
+    // Checked before stopping the node, the old ledger stays until swapped
+    let needed = extracted_size(archive)?;
+    let (_, free) = storage::filesystem_space(parent)?;
+    if free < needed {
+        return Err(anyhow!(
+            "Not enough space to extract the snapshot, {} needed and {} free",
+            format_bytes(needed),
+            format_bytes(free)
+        ));
+    }
+
+    progress(BackupProgress::of(BackupStage::StoppingNode));
+    systemd::stop_service(NODE_SERVICE)?;
+    let result = extract(archive, &staging, |done| {
+        progress(BackupProgress {
+            stage: BackupStage::Extracting,
+            done,
+            total,
+        })
+    })
+    .and_then(|()| swap(&extracted_dir(&staging)?, &ledger_dir));
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        warn!("Failed to remove {}: {}", staging.display(), e);
+    }
+    // Started either way, on the restored ledger or on the one it had
+    progress(BackupProgress::of(BackupStage::StartingNode));
+    let started = systemd::start_service(NODE_SERVICE);
+    result?;
+    started.map_err(|e| anyhow!("Restored, but the node didn't start: {}", e))
+}
+
+/// Replaces the node's ledger with the snapshot, fetched beforehand when
+/// remote. Only stops once `cancelled` while waiting for another operation.
+pub fn restore(
+    snapshot: &Snapshot,
+    progress: impl Fn(BackupProgress),
+    cancelled: impl Fn() -> bool,
+) -> Result<()> {
+    progress(BackupProgress::of(BackupStage::Waiting));
+    let _lock = oplock::acquire(Operation::Restore, cancelled)?;
+    match &snapshot.source {
+        SnapshotSource::Usb => {
+            let drive = usb::open()?;
+            let archive = drive.path().join(BACKUP_DIR).join(&snapshot.name);
+            restore_archive(&archive, snapshot.sha256.as_deref(), &progress)?;
+        }
+        SnapshotSource::Url(_) => {
+            // Checked by the download
+            let archive = download_path(snapshot);
+            restore_archive(&archive, None, &progress)?;
+            if let Err(e) = fs::remove_file(&archive) {
+                warn!("Failed to remove {}: {}", archive.display(), e);
+            }
+        }
+    }
+    info!("Restored the ledger from {}", snapshot.name);
+    Ok(())
+}
@@ -0,0 +1,137 @@
+#[cfg(feature = "display_hat")]
+use crate::util::current_timestamp;
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+use anyhow::{Result, anyhow};
+use std::env;
+use std::fs;
+use std::path::Path;
+#[cfg(feature = "display_hat")]
+use tracing::{info, warn};
+
+/// Bookworm moved it under `firmware`
+const BOOT_CONFIG_PATHS: [&str; 2] = ["/boot/firmware/config.txt", "/boot/config.txt"];
+/// Chips of the `i2c-rtc` overlay that amaru-pi configures
+const SUPPORTED_CHIPS: [&str; 5] = ["ds3231", "ds1307", "pcf8523", "pcf85063a", "rv3028"];
+#[cfg(feature = "display_hat")]
+const RTC_TIME_PATH: &str = "/sys/class/rtc/rtc0/since_epoch";
+/// 2026-01-01 UTC, earlier than any amaru-pi build. A clock before that was
+/// reset, e.g. by a power loss without an RTC.
+pub const PLAUSIBLE_SINCE: u64 = 1_767_225_600;
+/// Apart by more than this, the RTC or the system clock is wrong
+pub const MAX_DRIFT_SECS: u64 = 2;
+
+/// Whether a clock at `timestamp` can be right.
+pub fn is_plausible(timestamp: u64) -> bool {
+    timestamp >= PLAUSIBLE_SINCE
+}
+
+/// Reads `AMARU_PI_RTC`, the chip of the RTC module, e.g. `ds3231`. `None`
+/// without one.
+pub fn chip_from_env() -> Result<Option<String>> {
+    let Ok(chip) = env::var("AMARU_PI_RTC") else {
+        return Ok(None);
+    };
+    let chip = chip.trim().to_lowercase();
+    if !SUPPORTED_CHIPS.contains(&chip.as_str()) {
+        return Err(anyhow!(
+            "Unsupported RTC {}, one of {}",
+            chip,
+            SUPPORTED_CHIPS.join(", ")
+        ));
+    }
+    Ok(Some(chip))
+}
+
+/// Adds the `i2c-rtc` overlay of the configured chip to the boot config, for
+/// the kernel to expose the RTC from the next boot. Whether it was added.
+pub fn configure() -> Result<bool> {
+    let Some(chip) = chip_from_env()? else {
+        return Ok(false);
+    };
+    let Some(path) = BOOT_CONFIG_PATHS
+        .iter()
+        .find(|path| Path::new(path).exists())
+    else {
+        return Err(anyhow!("No boot config to add the RTC to"));
+    };
+    let config = fs::read_to_string(path)?;
+    let overlay = format!("dtoverlay=i2c-rtc,{}", chip);
+    if config.lines().any(|line| line.trim() == overlay) {
+        return Ok(false);
+    }
+    let mut config = config;
+    if !config.is_empty() && !config.ends_with('\n') {
+        config.push('\n');
+    }
+    // Sections after a filter such as `[cm4]` only apply to those models
+    let last_filter = config
+        .lines()
+        .rev()
+        .find(|line| line.trim().starts_with('['));
+    if last_filter.is_some_and(|filter| filter.trim() != "[all]") {
+        config.push_str("[all]\n");
+    }
+    config.push_str(&overlay);
+    config.push('\n');
+    fs::write(path, config)?;
+    Ok(true)
+}
+
+/// Seconds since the epoch of the RTC the kernel exposes, `None` without one.
+#[cfg(feature = "display_hat")]
+pub fn read() -> Option<u64> {
+    fs::read_to_string(RTC_TIME_PATH).ok()?.trim().parse().ok()
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn read() -> Option<u64> {
+    None
+}
+
+/// Sets the system clock from the RTC, at boot before NTP synchronized:
+/// the clock is otherwise left where the Pi stopped, and the node judges
+/// slots by it.
+#[cfg(feature = "display_hat")]
+pub fn restore_system_time() {
+    let Some(rtc) = read() else {
+        return;
+    };
+    if !is_plausible(rtc) {
+        warn!("The RTC is not set, its battery may be flat");
+        return;
+    }
+    let synchronized = run_and_capture("timedatectl", vec!["show", "-p", "NTPSynchronized"])
+        .is_ok_and(|output| output.trim() == "NTPSynchronized=yes");
+    let Ok(now) = current_timestamp() else {
+        return;
+    };
+    if synchronized || now.abs_diff(rtc) <= MAX_DRIFT_SECS {
+        return;
+    }
+    match run_and_capture("hwclock", vec!["--hctosys"]) {
+        Ok(_) => info!("Clock set from the RTC, off by {}s", now.abs_diff(rtc)),
+        Err(e) => warn!("Failed to set the clock from the RTC: {}", e),
+    }
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn restore_system_time() {}
+
+/// Sets the RTC from the system clock once synchronized by NTP, when off.
+#[cfg(feature = "display_hat")]
+pub fn save_system_time() -> Result<bool> {
+    let (Some(rtc), Ok(now)) = (read(), current_timestamp()) else {
+        return Ok(false);
+    };
+    if now.abs_diff(rtc) <= MAX_DRIFT_SECS {
+        return Ok(false);
+    }
+    run_and_capture("hwclock", vec!["--systohc"])?;
+    Ok(true)
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn save_system_time() -> Result<bool> {
+    Ok(false)
+}
@@ -1,10 +1,44 @@
 use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::inputs::mapping::Command;
+use crate::kiosk;
+use crate::logging;
+use crate::screens::backup::BackupScreen;
+use crate::screens::blocks::BlocksScreen;
+use crate::screens::boot::BootScreen;
+use crate::screens::buttons::ButtonsScreen;
+use crate::screens::clock::ClockScreen;
+use crate::screens::config::ConfigScreen;
+use crate::screens::coredumps::CoredumpsScreen;
+use crate::screens::events::EventsScreen;
+use crate::screens::faucet::FaucetScreen;
+use crate::screens::files::FilesScreen;
+use crate::screens::firewall::FirewallScreen;
+use crate::screens::fleet::FleetScreen;
 use crate::screens::info::InfoScreen;
+use crate::screens::ip_config::IpConfigScreen;
+use crate::screens::language::LanguageScreen;
+use crate::screens::latency::LatencyScreen;
+use crate::screens::leadership::LeadershipScreen;
 use crate::screens::logo::LogoScreen;
 use crate::screens::logs::LogsScreen;
+use crate::screens::lte::LteScreen;
 use crate::screens::metrics::MetricsScreen;
+use crate::screens::networks::NetworksScreen;
+use crate::screens::peers_map::PeersMapScreen;
+use crate::screens::pools::PoolsScreen;
+use crate::screens::power::PowerScreen;
+use crate::screens::processes::ProcessesScreen;
+use crate::screens::reachability::ReachabilityScreen;
+use crate::screens::restore::RestoreScreen;
 use crate::screens::scan::ScanScreen;
+use crate::screens::stake_pool::StakePoolScreen;
+use crate::screens::tasks::TasksScreen;
+use crate::screens::telemetry::TelemetryScreen;
+use crate::screens::ticker::TickerScreen;
 use crate::screens::tip::TipScreen;
+use crate::screens::topology::TopologyScreen;
+use crate::screens::units::UnitsScreen;
+use crate::screens::updates::UpdatesScreen;
 use crate::screens::wifi_settings::WiFiSettingsScreen;
 use crate::screens::{AppContext, Kind, Screen, ScreenAction};
 use crate::systemd::ActiveState;
@@ -13,15 +47,24 @@ use crate::wifi::Connectivity;
 use ratatui::prelude::*;
 use std::collections::HashSet;
 use std::env;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub struct ScreenFlow {
     screens: Vec<Box<dyn Screen>>,
     order: Vec<Kind>,
     pub current_screen_kind: Kind,
+    /// Use B to move forward and Y to move backward
+    swap_navigation: bool,
+    /// Of the current screen
+    last_update: Option<Instant>,
+    /// Set when the current screen must update on the next frame, whatever
+    /// its interval
+    update_due: bool,
 }
 
+/// Without the screens kiosk mode hides.
 fn get_screen_order() -> Vec<Kind> {
+    let shown = |kind: &Kind| !kiosk::is_enabled() || !kiosk::hides(*kind);
     let default = vec![
         Kind::Logo,
         Kind::Tip,
@@ -36,10 +79,11 @@ fn get_screen_order() -> Vec<Kind> {
         .map(|var| {
             var.split(',')
                 .filter_map(|s| s.trim().parse::<Kind>().ok())
+                .filter(shown)
                 .collect::<Vec<_>>()
         })
         .filter(|v| !v.is_empty())
-        .unwrap_or(default)
+        .unwrap_or_else(|| default.into_iter().filter(shown).collect())
 }
 
 impl Default for ScreenFlow {
@@ -54,9 +98,47 @@ impl Default for ScreenFlow {
             Box::new(LogsScreen::default()),
             Box::new(ScanScreen::default()),
             Box::new(WiFiSettingsScreen::default()),
+            Box::new(IpConfigScreen::default()),
+            Box::new(FirewallScreen::default()),
             Box::new(InfoScreen::default()),
+            Box::new(PoolsScreen::default()),
+            Box::new(StakePoolScreen::default()),
+            Box::new(TickerScreen::default()),
+            Box::new(FleetScreen::default()),
+            Box::new(FaucetScreen::default()),
+            Box::new(TelemetryScreen::default()),
+            Box::new(NetworksScreen::default()),
+            Box::new(LteScreen::default()),
+            Box::new(PeersMapScreen::default()),
+            Box::new(LatencyScreen::default()),
+            Box::new(BootScreen::default()),
+            Box::new(UnitsScreen::default()),
+            Box::new(CoredumpsScreen::default()),
+            Box::new(ProcessesScreen::default()),
+            Box::new(ReachabilityScreen::default()),
+            Box::new(FilesScreen::default()),
+            Box::new(ConfigScreen::default()),
+            Box::new(TopologyScreen::default()),
+            Box::new(LeadershipScreen::default()),
+            Box::new(BlocksScreen::default()),
+            Box::new(BackupScreen::default()),
+            Box::new(RestoreScreen::default()),
+            Box::new(EventsScreen::default()),
+            Box::new(UpdatesScreen::default()),
+            Box::new(ButtonsScreen::default()),
+            Box::new(ClockScreen::default()),
+            Box::new(PowerScreen::default()),
+            Box::new(LanguageScreen::default()),
+            Box::new(TasksScreen::default()),
         ];
-        let order = get_screen_order();
+        Self::new(screens, get_screen_order())
+    }
+}
+
+impl ScreenFlow {
+    /// Goes through `order`, starting with its first screen. Panics when a
+    /// screen is missing from `screens` or is there twice.
+    pub fn new(screens: Vec<Box<dyn Screen>>, order: Vec<Kind>) -> Self {
         let current_screen_kind = order
             .first()
             .copied()
@@ -71,15 +153,25 @@ impl Default for ScreenFlow {
             panic!("No screen found for kind: {:?}", kind);
         }
 
+        let swap_navigation = env::var("AMARU_PI_SWAP_NAVIGATION")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(false);
+
         Self {
             screens,
             order,
             current_screen_kind,
+            swap_navigation,
+            last_update: None,
+            update_due: true,
         }
     }
-}
 
-impl ScreenFlow {
+    pub fn is_enabled(&self, kind: Kind) -> bool {
+        self.order.contains(&kind)
+    }
+
     fn screen(&self, kind: Kind) -> &dyn Screen {
         self.screens
             .iter()
@@ -124,6 +216,15 @@ impl ScreenFlow {
         let new = self.screen_mut(kind);
         new.enter();
         self.current_screen_kind = new.kind();
+        self.update_due = true;
+        logging::set_screen(&self.current_screen_kind.to_string());
+    }
+
+    /// Shows `kind` right away, e.g. the screen shown before a restart.
+    pub fn jump_to(&mut self, kind: Kind) {
+        if kind != self.current_screen_kind {
+            self.update_screen(kind);
+        }
     }
 
     pub fn handle_input(&mut self, event: InputEvent) -> bool {
@@ -131,14 +232,20 @@ impl ScreenFlow {
             let current_screen = self.screen_mut(self.current_screen_kind);
             current_screen.handle_input(event)
         };
+        self.update_due |= handled;
         if !handled {
             // Only deal with input if screen hasn't captured it
+            let (forward, backward) = if self.swap_navigation {
+                (ButtonId::B, ButtonId::Y)
+            } else {
+                (ButtonId::Y, ButtonId::B)
+            };
             match (event.id, event.press_type) {
-                (ButtonId::Y, ButtonPress::Short) => {
-                    self.update_screen(self.next_kind(self.current_screen_kind));
+                (id, ButtonPress::Short) if id == forward => {
+                    self.handle_command(Command::NextScreen);
                 }
-                (ButtonId::B, ButtonPress::Short) => {
-                    self.update_screen(self.previous_kind(self.current_screen_kind));
+                (id, ButtonPress::Short) if id == backward => {
+                    self.handle_command(Command::PreviousScreen);
                 }
                 // Ignore other press types
                 _ => (),
@@ -147,8 +254,57 @@ impl ScreenFlow {
         handled
     }
 
+    pub fn is_typing(&self) -> bool {
+        self.screen(self.current_screen_kind).is_typing()
+    }
+
+    /// See `Screen::cursors`, for the current screen.
+    pub fn cursors(&self) -> Vec<(usize, usize)> {
+        self.screen(self.current_screen_kind).cursors()
+    }
+
+    pub fn handle_command(&mut self, command: Command) {
+        if self
+            .screen_mut(self.current_screen_kind)
+            .handle_command(command)
+        {
+            self.update_due = true;
+            return;
+        }
+        match command {
+            Command::NextScreen => {
+                self.update_screen(self.next_kind(self.current_screen_kind));
+            }
+            Command::PreviousScreen => {
+                self.update_screen(self.previous_kind(self.current_screen_kind));
+            }
+            Command::Home => {
+                if let Some(&first) = self.order.first() {
+                    self.update_screen(first);
+                }
+            }
+            // Handled by the app
+            Command::DevMenu | Command::Screenshot => {}
+            // Only meaningful to screens with a keyboard
+            Command::NextSuggestion
+            | Command::AcceptSuggestion
+            | Command::Type(_)
+            | Command::Erase => {}
+        }
+    }
+
     pub fn update(&mut self, ctx: AppContext) -> ScreenAction {
+        if let Some(interval) = self.screen(self.current_screen_kind).update_interval()
+            && !self.update_due
+            && self
+                .last_update
+                .is_some_and(|last| last.elapsed() < interval)
+        {
+            return ScreenAction::None;
+        }
         let action = self.screen_mut(self.current_screen_kind).update(ctx);
+        self.last_update = Some(Instant::now());
+        self.update_due = false;
         match action {
             ScreenAction::NextScreen => {
                 self.update_screen(self.next_kind(self.current_screen_kind));
@@ -159,8 +315,14 @@ impl ScreenFlow {
     }
 
     pub fn display(&self, ctx: AppContext, frame: &mut Frame) {
-        let [top_area, body] =
-            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(frame.area());
+        let spacing = ctx.frame.density.spacing();
+        let [top_area, _, body] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(spacing),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+        let body = body.inner(Margin::new(spacing, 0));
 
         let amaru_status_color = match ctx.system.amaru_status.active_state {
             ActiveState::Active => Color::Green,
@@ -177,6 +339,11 @@ impl ScreenFlow {
             title: "Amaru",
             amaru_status_color,
             network_status_color,
+            admin_unlocked: ctx.system.admin.is_unlocked(),
+            battery: ctx
+                .system
+                .power
+                .map(|power| (power.percent, power.charging)),
             background: Color::Black,
         };
 
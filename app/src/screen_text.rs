@@ -0,0 +1,120 @@
+use crate::screens::Kind;
+use anyhow::{Result, anyhow};
+use ratatui::buffer::Buffer;
+use std::fs::{self, File, OpenOptions, Permissions};
+use std::io::{self, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::warn;
+
+/// Plain text of the display, for `amaru-pi ctl read` and accessibility
+/// tools. On tmpfs and only readable by root, it may show secrets being typed.
+#[cfg(not(feature = "simulator"))]
+const SCREEN_FILE_PATH: &str = "/run/amaru-pi/screen.txt";
+const SCREEN_FILE_MODE: u32 = 0o600;
+const WRITE_INTERVAL: Duration = Duration::from_secs(1);
+/// Touched that often even if unchanged, so that a dead UI is noticed
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[cfg(not(feature = "simulator"))]
+fn screen_file_path() -> PathBuf {
+    PathBuf::from(SCREEN_FILE_PATH)
+}
+
+/// The simulator doesn't run as root
+#[cfg(feature = "simulator")]
+fn screen_file_path() -> PathBuf {
+    std::env::temp_dir().join("amaru-pi/screen.txt")
+}
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// The rows of `buffer`, without trailing blanks.
+pub fn buffer_text(buffer: &Buffer) -> String {
+    let width = buffer.area.width as usize;
+    let mut text = String::new();
+    for row in buffer.content.chunks(width.max(1)) {
+        let line: String = row.iter().map(|cell| cell.symbol()).collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text.trim_end_matches('\n').to_string() + "\n"
+}
+
+/// Writes what the display shows to the screen file, when it changes.
+#[derive(Debug, Default)]
+pub struct ScreenText {
+    last_write: Option<Instant>,
+    last_refresh: Option<Instant>,
+    last_text: String,
+}
+
+impl ScreenText {
+    pub fn record(&mut self, kind: Kind, buffer: &Buffer) {
+        if self
+            .last_write
+            .is_some_and(|last| last.elapsed() < WRITE_INTERVAL)
+        {
+            return;
+        }
+        self.last_write = Some(Instant::now());
+        let text = format!("[{}]\n{}", kind, buffer_text(buffer));
+        if text != self.last_text {
+            if let Err(e) = write_screen_file(&text) {
+                warn!("Failed to write screen text: {}", e);
+            }
+            self.last_text = text;
+            self.last_refresh = self.last_write;
+        } else if self
+            .last_refresh
+            .is_none_or(|last| last.elapsed() >= REFRESH_INTERVAL)
+        {
+            // Only the modification time, the content is the same
+            if let Err(e) = File::options()
+                .write(true)
+                .open(screen_file_path())
+                .and_then(|file| file.set_modified(SystemTime::now()))
+            {
+                warn!("Failed to refresh screen text: {}", e);
+            }
+            self.last_refresh = self.last_write;
+        }
+    }
+}
+
+fn write_screen_file(text: &str) -> io::Result<()> {
+    let path = screen_file_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Write then rename so that readers never get a half written file
+    let tmp_path = path.with_extension("txt.tmp");
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(SCREEN_FILE_MODE)
+        .open(&tmp_path)?;
+    // The mode only applies to new files
+    file.set_permissions(Permissions::from_mode(SCREEN_FILE_MODE))?;
+    file.write_all(text.as_bytes())?;
+    fs::rename(&tmp_path, &path)
+}
+
+/// What the display of the running UI shows, its screen's name first.
+pub fn read() -> Result<String> {
+    let path = screen_file_path();
+    let modified = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|_| anyhow!("The UI isn't running"))?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    if age > STALE_AFTER {
+        return Err(anyhow!(
+            "The UI stopped updating the display {}s ago",
+            age.as_secs()
+        ));
+    }
+    Ok(fs::read_to_string(&path)?)
+}
@@ -0,0 +1,103 @@
+use crate::backup::{self, BackupRecord, BackupStatus};
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::{format_bytes, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::{Gauge, Paragraph, Wrap};
+
+/// Backs up the ledger to a USB drive, with a double A. The node is stopped
+/// while the ledger is archived.
+#[derive(Debug, Default)]
+pub struct BackupScreen {
+    /// Mirror the system state
+    usb_drive: bool,
+    status: BackupStatus,
+    last: Option<BackupRecord>,
+    action: Option<ScreenAction>,
+}
+
+impl Screen for BackupScreen {
+    fn kind(&self) -> Kind {
+        Kind::Backup
+    }
+
+    fn enter(&mut self) {
+        self.last = backup::last_backup();
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if event.id == ButtonId::A
+            && event.press_type == ButtonPress::Double
+            && self.usb_drive
+            && !self.status.is_running()
+        {
+            self.action = Some(ScreenAction::BackUp);
+            return true;
+        }
+        false
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        self.usb_drive = ac.system.usb_drive;
+        if let BackupStatus::Done(record) = &ac.system.backup {
+            self.last = Some(record.clone());
+        }
+        self.status = ac.system.backup.clone();
+        ScreenAction::None
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+        if let BackupStatus::Running(progress) = &self.status {
+            let [text_area, gauge_area] =
+                Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(area);
+            lines.push(Line::from(progress.stage.label()).centered());
+            frame.render_widget(Paragraph::new(lines), text_area);
+            if let Some(ratio) = progress.ratio() {
+                let gauge = Gauge::default()
+                    .ratio(ratio)
+                    .label(format!(
+                        "{} / {}",
+                        format_bytes(progress.done),
+                        format_bytes(progress.total)
+                    ))
+                    .gauge_style(Style::default().fg(Color::Cyan));
+                frame.render_widget(gauge, gauge_area);
+            }
+            return;
+        }
+
+        let drive = if self.usb_drive {
+            Span::raw("plugged in").green()
+        } else {
+            Span::raw("not found").gray()
+        };
+        lines.push(Line::from(vec![Span::raw("USB drive: "), drive]));
+        let last = match &self.last {
+            Some(record) => Span::raw(format!(
+                "{} ({})",
+                format_local_time(record.timestamp).unwrap_or_default(),
+                format_bytes(record.size)
+            ))
+            .cyan(),
+            None => Span::raw("never").yellow(),
+        };
+        lines.push(Line::from(vec![Span::raw("Last backup: "), last]));
+        if let BackupStatus::Failed(e) = &self.status {
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!("Failed: {}", e)).red());
+        }
+        lines.push(Line::from(""));
+        if self.usb_drive {
+            lines.push(Line::from(tr("A (double): Back up now")).gray().centered());
+            lines.push(Line::from(tr("The node stops meanwhile")).gray().centered());
+        } else {
+            lines.push(Line::from(tr("Plug in a USB drive")).gray().centered());
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+}
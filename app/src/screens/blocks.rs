@@ -0,0 +1,216 @@
+use crate::blocks::{BlockHeader, BlockTracker};
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::{format_bytes, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::{Cell, Paragraph, Row, Table, Wrap};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Last blocks adopted by the node, newest first. X selects the next older
+/// block, holding X goes back to the newest one and A shows the selected
+/// block in full.
+#[derive(Default)]
+pub struct BlocksScreen {
+    tracker: BlockTracker,
+    /// Hash of the selected block, `None` following the newest
+    selected: Option<String>,
+    detail: bool,
+    /// Hash to copy on the next update
+    copy: Option<String>,
+}
+
+fn or_dash(value: Option<u64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+fn format_delay(delay_ms: Option<u64>) -> String {
+    delay_ms.map_or_else(
+        || "-".to_string(),
+        |ms| format!("{:.1}s", ms as f64 / 1000.0),
+    )
+}
+
+/// Slow to arrive blocks are more likely to lose forks.
+fn delay_color(delay_ms: Option<u64>) -> Color {
+    match delay_ms {
+        Some(ms) if ms > 5_000 => Color::Red,
+        Some(ms) if ms > 2_000 => Color::Yellow,
+        Some(_) => Color::Green,
+        None => Color::Gray,
+    }
+}
+
+impl BlocksScreen {
+    fn selected_index(&self) -> usize {
+        self.selected
+            .as_ref()
+            .and_then(|hash| {
+                self.tracker
+                    .blocks()
+                    .iter()
+                    .position(|block| block.hash == *hash)
+            })
+            .unwrap_or(0)
+    }
+
+    fn display_detail(&self, block: &BlockHeader, frame: &mut Frame, area: Rect) {
+        let adopted = block
+            .adopted_at
+            .and_then(|ms| format_local_time(ms / 1_000).ok())
+            .unwrap_or_else(|| "-".to_string());
+        let field = |label: &'static str, value: String| {
+            Line::from(vec![Span::raw(label).gray(), Span::raw(value)])
+        };
+        let lines = vec![
+            Line::from(format!("Block #{}", or_dash(block.height)))
+                .bold()
+                .centered(),
+            field("Slot: ", block.slot.to_string()),
+            field("Hash: ", block.hash.clone()),
+            field(
+                "Size: ",
+                block.size.map_or_else(|| "-".to_string(), format_bytes),
+            ),
+            field("Txs: ", or_dash(block.tx_count)),
+            field("Adopted: ", adopted),
+            Line::from(vec![
+                Span::raw("Delay: ").gray(),
+                Span::raw(format_delay(block.delay_ms)).fg(delay_color(block.delay_ms)),
+            ]),
+            Line::from(""),
+            Line::from(tr("A: Back | Hold A: Copy")).gray().centered(),
+        ];
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+}
+
+impl Screen for BlocksScreen {
+    fn kind(&self) -> Kind {
+        Kind::Blocks
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        let blocks = self.tracker.blocks();
+        if blocks.is_empty() {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::A, ButtonPress::Short) => self.detail = !self.detail,
+            (ButtonId::A, ButtonPress::Long) if self.detail => {
+                self.copy = blocks
+                    .get(self.selected_index())
+                    .map(|block| block.hash.clone());
+            }
+            (ButtonId::X, ButtonPress::Short) if !self.detail => {
+                let next = (self.selected_index() + 1) % blocks.len();
+                self.selected = (next > 0).then(|| blocks[next].hash.clone());
+            }
+            (ButtonId::X, ButtonPress::Long) if !self.detail => self.selected = None,
+            _ => return false,
+        }
+        true
+    }
+
+    fn update_interval(&self) -> Option<Duration> {
+        Some(POLL_INTERVAL)
+    }
+
+    fn update(&mut self, _ac: AppContext) -> ScreenAction {
+        if let Some(hash) = self.copy.take() {
+            return ScreenAction::Copy(hash);
+        }
+        self.tracker.poll();
+        ScreenAction::None
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let blocks = self.tracker.blocks();
+        let selected = self.selected_index();
+        if self.detail
+            && let Some(block) = blocks.get(selected)
+        {
+            self.display_detail(block, frame, area);
+            return;
+        }
+
+        let [table_area, footer_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+        if blocks.is_empty() {
+            let para = Paragraph::new(vec![
+                Line::from(""),
+                Line::from(tr("No block adopted yet")),
+                Line::from(tr("Waiting for the node")).gray(),
+            ])
+            .alignment(Alignment::Center);
+            frame.render_widget(para, table_area);
+            return;
+        }
+
+        // The size only fits on larger displays
+        let comfortable = ac.frame.density.is_comfortable();
+        let mut header = vec!["Height", "Slot", "Hash"];
+        let mut widths = vec![
+            Constraint::Length(8),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+        ];
+        if comfortable {
+            header.push("Size");
+            widths.push(Constraint::Length(8));
+        }
+        header.extend(["Txs", "Delay"]);
+        widths.extend([Constraint::Length(4), Constraint::Length(6)]);
+        let header = Row::new(header).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+        // Header excluded, keeps the selected block in view
+        let row_height = 1 + ac.frame.density.spacing() as usize;
+        let rows_shown = (table_area.height as usize).saturating_sub(1) / row_height;
+        let first = selected.saturating_sub(rows_shown.saturating_sub(1));
+        let rows = blocks
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows_shown)
+            .map(|(i, block)| {
+                let style = if i == selected {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default()
+                };
+                let hash = block.hash.get(..8).unwrap_or(&block.hash).to_string();
+                let mut cells = vec![
+                    Cell::from(or_dash(block.height)),
+                    Cell::from(block.slot.to_string()),
+                    Cell::from(hash),
+                ];
+                if comfortable {
+                    cells.push(Cell::from(
+                        block.size.map_or_else(|| "-".to_string(), format_bytes),
+                    ));
+                }
+                cells.extend([
+                    Cell::from(or_dash(block.tx_count)),
+                    Cell::from(format_delay(block.delay_ms))
+                        .style(Style::default().fg(delay_color(block.delay_ms))),
+                ]);
+                Row::new(cells)
+                    .style(style)
+                    .bottom_margin(ac.frame.density.spacing())
+            });
+        let table = Table::new(rows, widths).header(header);
+        frame.render_widget(table, table_area);
+
+        let footer = if self.selected.is_some() {
+            "X: Older | A: Detail | Hold X: Newest"
+        } else {
+            "X: Older | A: Detail"
+        };
+        frame.render_widget(Line::from(footer).gray().centered(), footer_area);
+    }
+}
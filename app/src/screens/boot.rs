@@ -0,0 +1,88 @@
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen};
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Sparkline};
+
+/// Slowest units listed
+const MAX_UNITS: usize = 4;
+
+/// Breakdown of the last boot time, with the trend over the previous boots
+/// and the units suspected of slowing it down.
+#[derive(Debug, Default)]
+pub struct BootScreen {}
+
+impl Screen for BootScreen {
+    fn kind(&self) -> Kind {
+        Kind::Boot
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let history = &ac.system.boot;
+        let Some(latest) = history.latest() else {
+            let lines = vec![Line::from(""), Line::from(tr("Still booting")).centered()];
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        };
+        let [summary_area, trend_area, units_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+
+        let summary = vec![
+            Line::from(format!("Boot took {:.1}s", latest.total_secs))
+                .bold()
+                .centered(),
+            Line::from(format!(
+                "kernel {:.1}s  userspace {:.1}s",
+                latest.kernel_secs, latest.userspace_secs
+            ))
+            .gray()
+            .centered(),
+        ];
+        frame.render_widget(Paragraph::new(summary), summary_area);
+
+        // In tenths of a second, the last bar being this boot
+        let totals: Vec<u64> = history
+            .boots
+            .iter()
+            .map(|boot| (boot.total_secs * 10.0) as u64)
+            .collect();
+        let regression = history.regression();
+        let color = if regression.is_some() {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        frame.render_widget(
+            Sparkline::default()
+                .data(&totals)
+                .style(Style::default().fg(color)),
+            trend_area,
+        );
+
+        let mut lines = Vec::new();
+        match regression {
+            Some(regression) => {
+                lines.push(
+                    Line::from(format!(
+                        "{:.1}s slower than usual",
+                        regression.slower_by_secs
+                    ))
+                    .red(),
+                );
+                for (unit, secs) in regression.culprits.iter().take(MAX_UNITS) {
+                    lines.push(Line::from(format!("{:>5.1}s {}", secs, unit)).yellow());
+                }
+            }
+            None => {
+                lines.push(Line::from(tr("Slowest units")).cyan());
+                for (unit, secs) in latest.units.iter().take(MAX_UNITS) {
+                    lines.push(Line::from(format!("{:>5.1}s {}", secs, unit)));
+                }
+            }
+        }
+        frame.render_widget(Paragraph::new(lines), units_area);
+    }
+}
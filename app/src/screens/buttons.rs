@@ -0,0 +1,172 @@
+use crate::button::{self, ButtonId, ButtonPress, ButtonTimings, InputEvent};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Presses listed to test the timings with
+const MAX_PRESSES: usize = 4;
+
+/// A timing window adjusted on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    LongPress,
+    DoublePress,
+    Debounce,
+}
+
+impl Field {
+    const ALL: [Field; 3] = [Field::LongPress, Field::DoublePress, Field::Debounce];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Field::LongPress => "Long press:  ",
+            Field::DoublePress => "Double press:",
+            Field::Debounce => "Debounce:    ",
+        }
+    }
+
+    /// Step, minimum and maximum, in milliseconds
+    fn range(&self) -> (u64, u64, u64) {
+        match self {
+            Field::LongPress => (100, 300, 3000),
+            Field::DoublePress => (50, 100, 1000),
+            Field::Debounce => (10, 10, 200),
+        }
+    }
+
+    fn get(&self, timings: &ButtonTimings) -> Duration {
+        match self {
+            Field::LongPress => timings.long_press,
+            Field::DoublePress => timings.double_press,
+            Field::Debounce => timings.debounce,
+        }
+    }
+
+    fn set(&self, timings: &mut ButtonTimings, value: Duration) {
+        match self {
+            Field::LongPress => timings.long_press = value,
+            Field::DoublePress => timings.double_press = value,
+            Field::Debounce => timings.debounce = value,
+        }
+    }
+}
+
+/// Adjusts the windows telling short, double and long presses apart, for
+/// stiffer or softer buttons. Changes apply right away, to try them out on
+/// the presses listed below, and are kept across restarts. X selects the next
+/// window, A lengthens it and holding A shortens it. A double X goes back to
+/// the configured windows.
+#[derive(Debug, Default)]
+pub struct ButtonsScreen {
+    timings: ButtonTimings,
+    selected: usize,
+    /// Newest first
+    presses: VecDeque<InputEvent>,
+    error: Option<String>,
+}
+
+impl ButtonsScreen {
+    fn adjust(&mut self, longer: bool) {
+        let field = Field::ALL[self.selected];
+        let (step, min, max) = field.range();
+        let millis = field.get(&self.timings).as_millis() as u64;
+        let millis = if longer {
+            millis + step
+        } else {
+            millis.saturating_sub(step)
+        };
+        let mut timings = self.timings;
+        field.set(&mut timings, Duration::from_millis(millis.clamp(min, max)));
+        self.error = button::calibrate(timings).err().map(|e| e.to_string());
+        self.timings = timings;
+    }
+}
+
+impl Screen for ButtonsScreen {
+    fn kind(&self) -> Kind {
+        Kind::Buttons
+    }
+
+    fn enter(&mut self) {
+        self.timings = button::timings();
+        self.presses.clear();
+        self.error = None;
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.presses.len() == MAX_PRESSES {
+            self.presses.pop_back();
+        }
+        self.presses.push_front(event);
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % Field::ALL.len();
+            }
+            (ButtonId::X, ButtonPress::Double) => match button::reset_timings() {
+                Ok(timings) => {
+                    self.timings = timings;
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(e.to_string()),
+            },
+            (ButtonId::A, ButtonPress::Short) => self.adjust(true),
+            (ButtonId::A, ButtonPress::Long | ButtonPress::Repeat) => self.adjust(false),
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, _ac: AppContext) -> ScreenAction {
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        vec![(self.selected, Field::ALL.len())]
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+        for (i, field) in Field::ALL.iter().enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(Span::styled(
+                format!(
+                    " {} {:>5} ms ",
+                    field.label(),
+                    field.get(&self.timings).as_millis()
+                ),
+                style,
+            )));
+        }
+
+        lines.push(Line::from(""));
+        if self.presses.is_empty() {
+            lines.push(Line::from(tr("Press a button to test")).gray());
+        } else {
+            let presses: Vec<String> = self
+                .presses
+                .iter()
+                .map(|event| format!("{:?} {:?}", event.id, event.press_type))
+                .collect();
+            lines.push(Line::from(presses.join(", ")).cyan());
+        }
+        if let Some(e) = &self.error {
+            lines.push(Line::from(format!("Not saved: {}", e)).red());
+        }
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(tr("X: Next | A: Longer | Hold A: Shorter"))
+                .gray()
+                .centered(),
+        );
+        lines.push(Line::from(tr("X X: Defaults")).gray().centered());
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::rtc;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::{current_timestamp, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Time zones listed before and after the selected one while picking
+const PICKER_CONTEXT: usize = 2;
+
+/// Region of a time zone, e.g. `Europe` for `Europe/Paris`.
+fn region(timezone: &str) -> &str {
+    timezone.split('/').next().unwrap_or(timezone)
+}
+
+/// Shows whether the clock is synchronized, which slots depend on, and how
+/// far off it is. A double A picks the time zone: X and Y move through the
+/// list, holding them jumps to the next region, A applies and B cancels.
+#[derive(Debug, Default)]
+pub struct ClockScreen {
+    /// As last listed by the system
+    timezones: Vec<String>,
+    current: String,
+    /// Index of the selected time zone while picking one
+    picking: Option<usize>,
+    action: Option<ScreenAction>,
+}
+
+impl ClockScreen {
+    fn start_picking(&mut self) {
+        if self.timezones.is_empty() {
+            return;
+        }
+        let index = self.timezones.iter().position(|tz| *tz == self.current);
+        self.picking = Some(index.unwrap_or(0));
+    }
+
+    /// First time zone of the region after that at `index`, or of the one
+    /// before.
+    fn jump_region(&self, index: usize, forward: bool) -> usize {
+        let len = self.timezones.len();
+        let step = |i: usize| {
+            if forward {
+                (i + 1) % len
+            } else {
+                (i + len - 1) % len
+            }
+        };
+        let current = region(&self.timezones[index]);
+        let mut i = step(index);
+        while i != index && region(&self.timezones[i]) == current {
+            i = step(i);
+        }
+        if !forward {
+            // Back to the start of that region
+            let previous = region(&self.timezones[i]);
+            while i > 0 && region(&self.timezones[i - 1]) == previous {
+                i -= 1;
+            }
+        }
+        i
+    }
+}
+
+impl Screen for ClockScreen {
+    fn kind(&self) -> Kind {
+        Kind::Clock
+    }
+
+    fn enter(&mut self) {
+        self.picking = None;
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        let Some(index) = self.picking else {
+            if (event.id, event.press_type) == (ButtonId::A, ButtonPress::Double) {
+                self.start_picking();
+                return true;
+            }
+            return false;
+        };
+        let len = self.timezones.len();
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short | ButtonPress::Repeat) => {
+                self.picking = Some((index + 1) % len);
+            }
+            (ButtonId::Y, ButtonPress::Short | ButtonPress::Repeat) => {
+                self.picking = Some((index + len - 1) % len);
+            }
+            (ButtonId::X, ButtonPress::Long) => self.picking = Some(self.jump_region(index, true)),
+            (ButtonId::Y, ButtonPress::Long) => {
+                self.picking = Some(self.jump_region(index, false));
+            }
+            (ButtonId::A, ButtonPress::Short) => {
+                let timezone = self.timezones[index].clone();
+                if timezone != self.current {
+                    self.action = Some(ScreenAction::SetTimezone(timezone));
+                }
+                self.picking = None;
+            }
+            (ButtonId::B, ButtonPress::Short) => self.picking = None,
+            _ => {}
+        }
+        true // Picking captures all input
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        // Listed once the screen is entered
+        if self.timezones.len() != ac.system.timezones.len() {
+            self.timezones = ac.system.timezones.clone();
+            self.picking = None;
+        }
+        if let Some(clock) = &ac.system.clock {
+            self.current.clone_from(&clock.timezone);
+        }
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        self.picking
+            .map(|index| (index, self.timezones.len()))
+            .into_iter()
+            .collect()
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+        if let Some(index) = self.picking {
+            lines.push(Line::from(tr("Time zone")).cyan().centered());
+            lines.push(Line::from(""));
+            let first = index.saturating_sub(PICKER_CONTEXT);
+            let last = (index + PICKER_CONTEXT + 1).min(self.timezones.len());
+            for (i, timezone) in self.timezones[first..last].iter().enumerate() {
+                let line = if first + i == index {
+                    Line::from(Span::styled(
+                        format!(" {} ", timezone),
+                        Style::default().fg(Color::Black).bg(Color::Cyan),
+                    ))
+                } else {
+                    Line::from(timezone.as_str()).gray()
+                };
+                lines.push(line.centered());
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(tr("X/Y: Move | Hold: Region")).gray().centered());
+            lines.push(Line::from(tr("A: Apply | B: Cancel")).gray().centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        let Some(clock) = &ac.system.clock else {
+            lines.push(Line::from(tr("Checking the clock...")).centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        };
+        let now = current_timestamp()
+            .ok()
+            .and_then(|now| format_local_time(now).ok())
+            .unwrap_or_default();
+        lines.push(Line::from(now).bold().centered());
+        lines.push(Line::from(clock.timezone.as_str()).gray().centered());
+        lines.push(Line::from(""));
+
+        let sync = if !clock.ntp {
+            Line::from(tr("NTP disabled")).red()
+        } else if clock.synchronized {
+            Line::from(tr("Synchronized")).green()
+        } else {
+            Line::from(tr("Not synchronized")).red()
+        };
+        lines.push(sync.centered());
+        if clock.implausible {
+            lines.push(
+                Line::from(tr("The clock was reset"))
+                    .red()
+                    .bold()
+                    .centered(),
+            );
+            lines.push(Line::from(tr("Blocks can't be validated")).red().centered());
+        }
+        if let Some(offset) = clock.offset_ms {
+            let offset = Line::from(format!("Offset: {:+.1} ms", offset));
+            let offset = if clock.is_drifting() {
+                offset.red()
+            } else {
+                offset.gray()
+            };
+            lines.push(offset.centered());
+        }
+        if let Some(server) = &clock.server {
+            lines.push(Line::from(server.as_str()).gray().centered());
+        }
+        if let Some(offset) = clock.rtc_offset_secs {
+            let rtc = if offset.unsigned_abs() <= rtc::MAX_DRIFT_SECS {
+                Line::from(tr("RTC in sync")).gray()
+            } else {
+                Line::from(format!("RTC off by {:+}s", offset)).yellow()
+            };
+            lines.push(rtc.centered());
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr("A A: Time zone")).gray().centered());
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
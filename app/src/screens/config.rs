@@ -0,0 +1,234 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::inputs::mapping::Command;
+use crate::keyboard::{KeyboardAction, KeyboardWidget};
+use crate::node_config::{ConfigStatus, Field, NETWORKS, NodeConfig};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::widgets::text_input::TextInput;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Edits the node's env file. X selects the next setting, A edits it, a
+/// double A saves the changes and holding A then restarts the node.
+pub struct ConfigScreen {
+    /// As last read, or why it couldn't be
+    config: Result<NodeConfig, String>,
+    /// Edited values not saved yet
+    changes: Vec<(Field, String)>,
+    selected: usize,
+    input: TextInput,
+    keyboard: KeyboardWidget,
+    /// Setting edited with the keyboard
+    editing: Option<Field>,
+    /// Mirrors the system state
+    status: ConfigStatus,
+    action: Option<ScreenAction>,
+}
+
+impl Default for ConfigScreen {
+    fn default() -> Self {
+        Self {
+            config: Err("Loading...".to_string()),
+            changes: Vec::new(),
+            selected: 0,
+            input: TextInput::new(),
+            keyboard: KeyboardWidget::default(),
+            editing: None,
+            status: ConfigStatus::Idle,
+            action: None,
+        }
+    }
+}
+
+impl ConfigScreen {
+    fn reload(&mut self) {
+        self.config = NodeConfig::load().map_err(|e| e.to_string());
+    }
+
+    /// The edited value, else the saved one.
+    fn value(&self, field: Field) -> Option<String> {
+        self.changes
+            .iter()
+            .find(|(changed, _)| *changed == field)
+            .map(|(_, value)| value.clone())
+            .or_else(|| self.config.as_ref().ok()?.get(field))
+    }
+
+    fn change(&mut self, field: Field, value: String) {
+        self.changes.retain(|(changed, _)| *changed != field);
+        let saved = self.config.as_ref().ok().and_then(|c| c.get(field));
+        if saved.as_deref() != Some(value.as_str()) {
+            self.changes.push((field, value));
+        }
+    }
+
+    fn edit(&mut self, field: Field) {
+        if field == Field::Network {
+            let current = self.value(field).unwrap_or_default();
+            let next = NETWORKS
+                .iter()
+                .position(|network| *network == current)
+                .map_or(0, |i| (i + 1) % NETWORKS.len());
+            self.change(field, NETWORKS[next].to_string());
+            return;
+        }
+        self.input = TextInput::new()
+            .max_length(256)
+            .validator(move |value| field.validate(value));
+        self.input.set_value(self.value(field).unwrap_or_default());
+        self.editing = Some(field);
+    }
+
+    fn display_editing(&self, field: Field, frame: &mut Frame, area: Rect) {
+        let [help_area, input_area, keyboard_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        let help = match field {
+            Field::Peers => "Comma separated host:port",
+            Field::ListenAddress => "host:port to accept peers on",
+            _ => "Absolute path",
+        };
+        frame.render_widget(
+            Paragraph::new(help)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Cyan)),
+            help_area,
+        );
+        self.input.render(
+            frame,
+            input_area,
+            field.label(),
+            Style::default().fg(Color::Yellow),
+            true,
+        );
+        self.keyboard.render(frame, keyboard_area);
+    }
+}
+
+impl Screen for ConfigScreen {
+    fn kind(&self) -> Kind {
+        Kind::Config
+    }
+
+    fn enter(&mut self) {
+        // Changes not saved yet are kept
+        if self.changes.is_empty() {
+            self.reload();
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    fn handle_command(&mut self, command: Command) -> bool {
+        if self.editing.is_none() || !command.is_for_keyboard() {
+            return false;
+        }
+        if let Some(action) = self.keyboard.handle_command(command) {
+            self.input.handle_action(&action);
+        }
+        true
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if let Some(field) = self.editing {
+            match self.keyboard.handle_input(event) {
+                Some(KeyboardAction::Exit) => {
+                    self.editing = None;
+                    if self.input.validate().is_ok() {
+                        let value = self.input.value().trim().to_string();
+                        self.change(field, value);
+                    }
+                }
+                Some(action) => {
+                    self.input.handle_action(&action);
+                }
+                None => {}
+            }
+            return true; // Keyboard always captures input
+        }
+        let busy = matches!(self.status, ConfigStatus::Saving | ConfigStatus::Restarting);
+        if self.config.is_err() || busy {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % Field::ALL.len();
+            }
+            (ButtonId::X, ButtonPress::Long) if !self.changes.is_empty() => self.changes.clear(),
+            (ButtonId::A, ButtonPress::Short) => self.edit(Field::ALL[self.selected]),
+            (ButtonId::A, ButtonPress::Double) if !self.changes.is_empty() => {
+                self.action = Some(ScreenAction::SaveNodeConfig(self.changes.clone()));
+            }
+            (ButtonId::A, ButtonPress::Long)
+                if self.status == ConfigStatus::Saved && self.changes.is_empty() =>
+            {
+                self.action = Some(ScreenAction::RestartNode);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        self.keyboard
+            .set_paste_buffer(ac.system.paste_buffer.as_deref());
+        let status = &ac.system.node_config_status;
+        if *status != self.status {
+            if *status == ConfigStatus::Saved {
+                self.changes.clear();
+                self.reload();
+            }
+            self.status = status.clone();
+        }
+        self.action.take().unwrap_or(ScreenAction::None)
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        if let Some(field) = self.editing {
+            self.display_editing(field, frame, area);
+            return;
+        }
+
+        let mut lines = vec![Line::from(tr("Node config")).bold().centered()];
+        if let Err(e) = &self.config {
+            lines.push(Line::from(e.as_str()).red().centered());
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+            return;
+        }
+
+        for (i, field) in Field::ALL.iter().enumerate() {
+            let changed = self.changes.iter().any(|(changed, _)| changed == field);
+            let label_style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            let value = match self.value(*field) {
+                Some(value) if changed => Span::raw(value).yellow(),
+                Some(value) => Span::raw(value).cyan(),
+                None => Span::raw("default").gray(),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {} ", field.label()), label_style),
+                Span::raw(" "),
+                value,
+            ]));
+        }
+
+        lines.push(Line::from(""));
+        let help = match self.status {
+            ConfigStatus::Saving => "Saving...",
+            ConfigStatus::Restarting => "Restarting amaru...",
+            ConfigStatus::Saved if self.changes.is_empty() => "Saved | Hold A: Restart amaru",
+            _ if !self.changes.is_empty() => "A A: Save | Hold X: Discard",
+            _ => "X: Next | A: Edit",
+        };
+        lines.push(Line::from(help).gray().centered());
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
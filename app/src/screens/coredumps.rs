@@ -0,0 +1,115 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::coredump::{self, Coredump};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::{format_bytes, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Dumps listed, newest first
+const MAX_DUMPS: usize = 6;
+
+/// Lists the captured crashes of the node with their size. X selects the
+/// next one, a double A exports it to a USB drive.
+#[derive(Debug, Default)]
+pub struct CoredumpsScreen {
+    /// Mirrors the system state, newest first
+    dumps: Vec<Coredump>,
+    selected: usize,
+    action: Option<ScreenAction>,
+}
+
+impl Screen for CoredumpsScreen {
+    fn kind(&self) -> Kind {
+        Kind::Coredumps
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.dumps.is_empty() {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % self.dumps.len().min(MAX_DUMPS);
+            }
+            (ButtonId::A, ButtonPress::Double) if self.dumps[self.selected].present => {
+                self.action = Some(ScreenAction::ExportCoredump(self.dumps[self.selected].pid));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        self.dumps = ac.system.coredumps.clone();
+        self.selected = self
+            .selected
+            .min(self.dumps.len().min(MAX_DUMPS).saturating_sub(1));
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        vec![(self.selected, self.dumps.len().min(MAX_DUMPS))]
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+
+        if self.dumps.is_empty() {
+            lines.push(Line::from(tr("No crash captured")).centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        for (i, dump) in self.dumps.iter().take(MAX_DUMPS).enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else if dump.present {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let size = match (dump.present, dump.size) {
+                (false, _) => "removed".to_string(),
+                (true, Some(size)) => format_bytes(size),
+                (true, None) => "?".to_string(),
+            };
+            lines.push(Line::from(Span::styled(
+                format!(
+                    " {} {} {} ",
+                    format_local_time(dump.timestamp).unwrap_or_default(),
+                    dump.signal_name(),
+                    size
+                ),
+                style,
+            )));
+        }
+
+        let used: u64 = self
+            .dumps
+            .iter()
+            .filter(|dump| dump.present)
+            .filter_map(|dump| dump.size)
+            .sum();
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(format!(
+                "Using {} of {}",
+                format_bytes(used),
+                coredump::max_use()
+            ))
+            .gray()
+            .centered(),
+        );
+        lines.push(
+            Line::from(tr("X: Next | A A: Export to USB"))
+                .gray()
+                .centered(),
+        );
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::keyboard::{KeyboardAction, KeyboardWidget};
+use crate::notifications::{self, Notification, Severity};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::format_local_time;
+use crate::widgets::text_input::TextInput;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+use std::time::Duration;
+
+/// Events listed, newest first
+const MAX_EVENTS: usize = 5;
+const MAX_NOTE_LENGTH: usize = 80;
+
+/// Lists the latest notifications with the notes left on them, so the
+/// history of the device explains itself. X selects the next one, a double A
+/// notes it with the on-screen keyboard.
+pub struct EventsScreen {
+    /// Newest first
+    events: Vec<Notification>,
+    selected: usize,
+    note: TextInput,
+    keyboard: KeyboardWidget,
+    editing: bool,
+    /// Set once a note is saved, to show it
+    reload: bool,
+    action: Option<ScreenAction>,
+}
+
+impl Default for EventsScreen {
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+            selected: 0,
+            note: TextInput::new().max_length(MAX_NOTE_LENGTH),
+            keyboard: KeyboardWidget::default(),
+            editing: false,
+            reload: false,
+            action: None,
+        }
+    }
+}
+
+impl EventsScreen {
+    fn load(&mut self) {
+        self.events = notifications::read_history().unwrap_or_default();
+        self.events.reverse();
+        self.events.truncate(MAX_EVENTS);
+        self.selected = self.selected.min(self.events.len().saturating_sub(1));
+    }
+}
+
+impl Screen for EventsScreen {
+    fn kind(&self) -> Kind {
+        Kind::Events
+    }
+
+    fn enter(&mut self) {
+        self.load();
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.editing {
+            match self.keyboard.handle_input(event) {
+                Some(KeyboardAction::Exit) => {
+                    self.editing = false;
+                    let timestamp = self.events[self.selected].timestamp;
+                    self.action = Some(ScreenAction::AnnotateEvent(
+                        timestamp,
+                        self.note.value().to_string(),
+                    ));
+                    self.reload = true;
+                }
+                Some(action) => {
+                    self.note.handle_action(&action);
+                }
+                None => {}
+            }
+            return true; // Keyboard always captures input
+        }
+        if self.events.is_empty() {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % self.events.len();
+            }
+            (ButtonId::A, ButtonPress::Double) => {
+                let note = self.events[self.selected].note.clone();
+                self.note.set_value(note.unwrap_or_default());
+                self.editing = true;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(5))
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        self.keyboard
+            .set_paste_buffer(ac.system.paste_buffer.as_deref());
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        // New notifications show up too
+        if std::mem::take(&mut self.reload) || !self.editing {
+            self.load();
+        }
+        ScreenAction::None
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        if self.editing {
+            let [help_area, input_area, keyboard_area] = Layout::vertical([
+                Constraint::Length(2),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .areas(area);
+            frame.render_widget(
+                Paragraph::new(format!("Note on {}", self.events[self.selected].title))
+                    .alignment(Alignment::Center)
+                    .style(Style::default().fg(Color::Cyan)),
+                help_area,
+            );
+            self.note.render(
+                frame,
+                input_area,
+                "Note",
+                Style::default().fg(Color::Yellow),
+                true,
+            );
+            self.keyboard.render(frame, keyboard_area);
+            return;
+        }
+
+        let mut lines = vec![Line::from("")];
+        if self.events.is_empty() {
+            lines.push(Line::from(tr("No event yet")).centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        for (i, event) in self.events.iter().enumerate() {
+            let color = match event.severity {
+                Severity::Info => Color::Cyan,
+                Severity::Warning => Color::Yellow,
+                Severity::Critical => Color::Red,
+            };
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(color)
+            } else {
+                Style::default().fg(color)
+            };
+            let noted = if event.note.is_some() { "*" } else { " " };
+            lines.push(Line::from(Span::styled(
+                format!(
+                    " {} {}{} ",
+                    format_local_time(event.timestamp).unwrap_or_default(),
+                    event.title,
+                    noted
+                ),
+                style,
+            )));
+        }
+
+        let selected = &self.events[self.selected];
+        lines.push(Line::from(""));
+        for line in &selected.lines {
+            lines.push(Line::from(format!("  {}", line)).gray());
+        }
+        if let Some(note) = &selected.note {
+            lines.push(Line::from(format!("  Note: {}", note)).green());
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr("X: Next | A A: Note")).gray().centered());
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
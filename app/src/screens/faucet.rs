@@ -0,0 +1,123 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::chain::Network;
+use crate::faucet::{FaucetConfig, FaucetStatus};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::format_ada;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Requests test ada from the public faucet and follows its arrival.
+pub struct FaucetScreen {
+    config: FaucetConfig,
+    request_pending: bool,
+    copy_requested: bool,
+}
+
+impl Default for FaucetScreen {
+    fn default() -> Self {
+        Self {
+            config: FaucetConfig::from_env(),
+            request_pending: false,
+            copy_requested: false,
+        }
+    }
+}
+
+fn shorten(address: &str) -> String {
+    if address.len() <= 24 {
+        return address.to_string();
+    }
+    format!("{}…{}", &address[..14], &address[address.len() - 8..])
+}
+
+impl Screen for FaucetScreen {
+    fn kind(&self) -> Kind {
+        Kind::Faucet
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.config.is_available()
+            && event.id == ButtonId::A
+            && event.press_type == ButtonPress::Short
+        {
+            self.request_pending = true;
+            return true;
+        }
+        if self.config.address.is_some()
+            && event.id == ButtonId::A
+            && event.press_type == ButtonPress::Long
+        {
+            self.copy_requested = true;
+            return true;
+        }
+        false
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if std::mem::take(&mut self.copy_requested)
+            && let Some(address) = &self.config.address
+        {
+            return ScreenAction::Copy(address.clone());
+        }
+        if std::mem::take(&mut self.request_pending) && !ac.system.faucet.is_in_progress() {
+            return ScreenAction::RequestFaucetFunds;
+        }
+        ScreenAction::None
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+
+        if self.config.network == Network::Mainnet {
+            lines.push(Line::from(tr("Faucet unavailable on mainnet")).centered());
+            lines.push(
+                Line::from("Set AMARU_NETWORK to preprod or preview")
+                    .gray()
+                    .centered(),
+            );
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+            return;
+        }
+        let Some(address) = &self.config.address else {
+            lines.push(Line::from(tr("No faucet address")).centered());
+            lines.push(Line::from("Set AMARU_PI_FAUCET_ADDRESS").gray().centered());
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+            return;
+        };
+
+        lines.push(Line::from(vec![
+            Span::raw("  Network: "),
+            Span::styled(
+                format!("{:?}", self.config.network),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]));
+        lines.push(Line::from(vec![
+            Span::raw("  Address: "),
+            Span::styled(shorten(address), Style::default().fg(Color::Cyan)),
+        ]));
+        lines.push(Line::from(""));
+
+        let status = match &ac.system.faucet {
+            FaucetStatus::Idle => Line::from(tr("Press A to request funds")).gray(),
+            FaucetStatus::Requesting => Line::from(tr("Requesting funds...")).yellow(),
+            FaucetStatus::WaitingForFunds { tx_id } => match tx_id {
+                Some(tx_id) => Line::from(format!("Waiting for tx {}", shorten(tx_id))).yellow(),
+                None => Line::from(tr("Waiting for funds...")).yellow(),
+            },
+            FaucetStatus::Received { lovelace } => {
+                Line::from(format!("Received {} ADA", format_ada(*lovelace))).green()
+            }
+            FaucetStatus::Failed(e) => Line::from(format!("Failed: {}", e)).red(),
+        };
+        lines.push(status.centered());
+        lines.push(
+            Line::from(tr("Hold A to copy the address"))
+                .gray()
+                .centered(),
+        );
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
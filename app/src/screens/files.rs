@@ -0,0 +1,217 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::files::{self, Listing, Root};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::{format_bytes, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use std::path::PathBuf;
+
+/// Read-only browser of the data and config directories. X selects the next
+/// entry, A opens it, holding X goes up. A double A deletes the selected
+/// rotated log.
+#[derive(Debug)]
+pub struct FilesScreen {
+    roots: Vec<Root>,
+    /// `None` while picking a root
+    path: Option<PathBuf>,
+    selected: usize,
+    /// Mirrors the system state, when it is for `path`
+    listing: Option<Listing>,
+    action: Option<ScreenAction>,
+}
+
+impl Default for FilesScreen {
+    fn default() -> Self {
+        Self {
+            roots: files::roots(),
+            path: None,
+            selected: 0,
+            listing: None,
+            action: None,
+        }
+    }
+}
+
+impl FilesScreen {
+    fn open(&mut self, path: PathBuf) {
+        self.action = Some(ScreenAction::Browse(path.clone()));
+        self.path = Some(path);
+        self.listing = None;
+        self.selected = 0;
+    }
+
+    /// Back to the parent directory, or to the roots from a root.
+    fn up(&mut self) {
+        let Some(path) = self.path.take() else {
+            return;
+        };
+        self.listing = None;
+        self.selected = 0;
+        if !self.roots.iter().any(|root| root.path == path)
+            && let Some(parent) = path.parent()
+        {
+            self.open(parent.to_path_buf());
+        }
+    }
+
+    fn entries_len(&self) -> usize {
+        match (&self.path, &self.listing) {
+            (None, _) => self.roots.len(),
+            (Some(_), Some(Listing::Dir(_, entries))) => entries.len(),
+            _ => 0,
+        }
+    }
+
+    fn display_roots(&self, lines: &mut Vec<Line>) {
+        for (i, root) in self.roots.iter().enumerate() {
+            let text = format!(" {}: {} ", root.label, root.path.display());
+            lines.push(selectable(text, i == self.selected, Style::default()));
+        }
+    }
+}
+
+fn selectable(text: String, selected: bool, style: Style) -> Line<'static> {
+    let style = if selected {
+        Style::default().fg(Color::Black).bg(Color::Cyan)
+    } else {
+        style
+    };
+    Line::from(Span::styled(text, style))
+}
+
+impl Screen for FilesScreen {
+    fn kind(&self) -> Kind {
+        Kind::Files
+    }
+
+    fn enter(&mut self) {
+        // Files may have changed since the screen was left
+        if let Some(path) = &self.path {
+            self.action = Some(ScreenAction::Browse(path.clone()));
+        }
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                let len = self.entries_len();
+                if len == 0 {
+                    return false;
+                }
+                self.selected = (self.selected + 1) % len;
+            }
+            (ButtonId::X, ButtonPress::Long) if self.path.is_some() => self.up(),
+            (ButtonId::A, ButtonPress::Short) => match (&self.path, &self.listing) {
+                (None, _) => {
+                    let Some(root) = self.roots.get(self.selected) else {
+                        return false;
+                    };
+                    self.open(root.path.clone());
+                }
+                (Some(path), Some(Listing::Dir(_, entries))) => {
+                    let Some(entry) = entries.get(self.selected) else {
+                        return false;
+                    };
+                    self.open(path.join(&entry.name));
+                }
+                (Some(_), Some(Listing::File(..))) => self.up(),
+                _ => return false,
+            },
+            (ButtonId::A, ButtonPress::Double) => {
+                let (Some(path), Some(Listing::Dir(_, entries))) = (&self.path, &self.listing)
+                else {
+                    return false;
+                };
+                match entries.get(self.selected) {
+                    Some(entry) if entry.deletable => {
+                        self.action = Some(ScreenAction::DeleteFile(path.join(&entry.name)));
+                    }
+                    _ => return false,
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        if let (Some(path), Some(listing)) = (&self.path, &ac.system.browser)
+            && listing.path() == path
+            && self.listing.as_ref() != Some(listing)
+        {
+            self.listing = Some(listing.clone());
+            self.selected = self.selected.min(self.entries_len().saturating_sub(1));
+        }
+        ScreenAction::None
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let title = match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "Files".to_string(),
+        };
+        let mut lines = vec![Line::from(title).bold().centered()];
+        // Title and footer excluded
+        let rows = (area.height as usize).saturating_sub(3);
+
+        let footer = match (&self.path, &self.listing) {
+            (None, _) => {
+                self.display_roots(&mut lines);
+                "X: Next | A: Open"
+            }
+            (Some(_), None) => {
+                lines.push(Line::from(tr("Loading...")).gray().centered());
+                "Hold X: Up"
+            }
+            (Some(_), Some(Listing::File(_, None))) => {
+                lines.push(Line::from(tr("Binary file")).gray().centered());
+                "A: Back"
+            }
+            (Some(_), Some(Listing::File(_, Some(tail)))) => {
+                let skip = tail.len().saturating_sub(rows);
+                lines.extend(tail.iter().skip(skip).map(|line| Line::from(line.clone())));
+                "A: Back"
+            }
+            (Some(_), Some(Listing::Dir(_, entries))) => {
+                if entries.is_empty() {
+                    lines.push(Line::from(tr("Empty")).gray().centered());
+                }
+                // Scrolls to keep the selected entry in view
+                let first = self.selected.saturating_sub(rows.saturating_sub(1));
+                for (i, entry) in entries.iter().enumerate().skip(first).take(rows) {
+                    let text = if entry.is_dir {
+                        format!(" {}/ ", entry.name)
+                    } else {
+                        format!(
+                            " {} {} {} ",
+                            entry.name,
+                            format_bytes(entry.size),
+                            format_local_time(entry.modified).unwrap_or_default()
+                        )
+                    };
+                    let style = if entry.deletable {
+                        Style::default().fg(Color::Yellow)
+                    } else if entry.is_dir {
+                        Style::default().fg(Color::Blue)
+                    } else {
+                        Style::default()
+                    };
+                    lines.push(selectable(text, i == self.selected, style));
+                }
+                match entries.get(self.selected) {
+                    Some(entry) if entry.deletable => "X: Next | A A: Delete | Hold X: Up",
+                    _ => "X: Next | A: Open | Hold X: Up",
+                }
+            }
+        };
+
+        let footer_row = lines.len().max(rows + 1);
+        lines.resize(footer_row, Line::from(""));
+        lines.push(Line::from(footer).gray().centered());
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::firewall::FirewallPreset;
+use crate::i18n::tr;
+use crate::pin::Guarded;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::widgets::dialog::Dialog;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Reports the firewall of the Pi and applies presets of rules through the
+/// firewall helper. X selects the next preset, A asks to confirm it.
+#[derive(Debug, Default)]
+pub struct FirewallScreen {
+    selected: usize,
+    requested: bool,
+}
+
+impl Screen for FirewallScreen {
+    fn kind(&self) -> Kind {
+        Kind::Firewall
+    }
+
+    fn enter(&mut self) {
+        self.selected = 0;
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % FirewallPreset::ALL.len();
+            }
+            (ButtonId::A, ButtonPress::Short) => self.requested = true,
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if std::mem::take(&mut self.requested) && !ac.system.firewall.applying {
+            let preset = FirewallPreset::ALL[self.selected];
+            return ScreenAction::Confirm(Box::new(Dialog::new(
+                tr("Firewall"),
+                vec![
+                    tr(preset.description()).to_string(),
+                    tr("Other rules are kept").to_string(),
+                ],
+                tr(preset.label()),
+                Guarded::ApplyFirewallPreset(preset),
+            )));
+        }
+        ScreenAction::None
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let [status_area, presets_area, hint_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(FirewallPreset::ALL.len() as u16 + 1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let state = &ac.system.firewall;
+        let mut lines = vec![Line::from(tr("Firewall")).bold().centered()];
+        match &state.current {
+            None => lines.push(Line::from(tr("Loading...")).centered()),
+            Some(Err(e)) => lines.push(Line::from(e.clone()).red().centered()),
+            Some(Ok(status)) => {
+                let summary = match (&status.backend, status.active) {
+                    (None, _) => Line::from(tr("No firewall installed")).yellow(),
+                    (Some(backend), true) => {
+                        Line::from(format!("{} {}", backend, tr("active"))).green()
+                    }
+                    (Some(backend), false) => {
+                        Line::from(format!("{} {}", backend, tr("inactive"))).yellow()
+                    }
+                };
+                lines.push(summary.centered());
+                if status.active && status.rules.is_empty() {
+                    lines.push(Line::from(tr("No rule")).gray().centered());
+                }
+                lines.extend(status.rules.iter().map(|rule| Line::from(rule.clone())));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), status_area);
+
+        let mut presets = vec![Line::from("")];
+        for (i, preset) in FirewallPreset::ALL.iter().enumerate() {
+            let line = Line::from(format!("  {}  ", tr(preset.label()))).centered();
+            presets.push(if i == self.selected {
+                line.black().on_cyan()
+            } else {
+                line
+            });
+        }
+        frame.render_widget(Paragraph::new(presets), presets_area);
+
+        let hint = if state.applying {
+            tr("Applying...")
+        } else {
+            tr("X: Next | A: Select")
+        };
+        frame.render_widget(Paragraph::new(hint).gray().centered(), hint_area);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::fleet::FleetNode;
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen};
+use crate::util::format_duration;
+use ratatui::prelude::*;
+use ratatui::widgets::{Cell, Paragraph, Row, Table};
+use std::time::Duration;
+
+/// How long each page shows when the fleet doesn't fit
+const PAGE_DURATION: Duration = Duration::from_secs(5);
+/// Status older than this is flagged as stale
+const STALE_AFTER: Duration = Duration::from_secs(2 * 60);
+
+/// The sync of the remote nodes of `AMARU_PI_FLEET` side by side, a page
+/// at a time when they don't fit.
+#[derive(Debug, Default)]
+pub struct FleetScreen {}
+
+fn node_color(node: &FleetNode) -> Color {
+    let stale = node
+        .updated
+        .is_none_or(|updated| updated.elapsed() > STALE_AFTER);
+    match node
+        .status
+        .as_ref()
+        .and_then(|status| status.node.as_deref())
+    {
+        _ if stale => Color::DarkGray,
+        Some("active") => Color::Green,
+        Some("failed") => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
+impl Screen for FleetScreen {
+    fn kind(&self) -> Kind {
+        Kind::Fleet
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let nodes = &ac.system.fleet;
+        let [table_area, footer_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+
+        if nodes.is_empty() {
+            let para = Paragraph::new(vec![
+                Line::from(""),
+                Line::from(tr("No node in the fleet")),
+                Line::from("Set AMARU_PI_FLEET").gray(),
+            ])
+            .alignment(Alignment::Center);
+            frame.render_widget(para, table_area);
+            return;
+        }
+
+        let row_height = 1 + ac.frame.density.spacing() as usize;
+        let per_page = ((table_area.height as usize).saturating_sub(1) / row_height).max(1);
+        let pages = nodes.len().div_ceil(per_page);
+        let page =
+            (ac.frame.elapsed_since_startup.as_secs() / PAGE_DURATION.as_secs()) as usize % pages;
+
+        let header = Row::new(["Node", "Sync", "Tip", "Seen"]).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Length(6),
+        ];
+        let rows = nodes
+            .iter()
+            .skip(page * per_page)
+            .take(per_page)
+            .map(|node| {
+                let status = node.status.clone().unwrap_or_default();
+                let sync = status
+                    .sync
+                    .map_or_else(|| "-".to_string(), |sync| format!("{:.1}%", sync));
+                let tip = status
+                    .tip
+                    .map_or_else(|| "-".to_string(), |tip| tip.to_string());
+                let seen = match (node.updated, &node.error) {
+                    (_, Some(_)) if node.updated.is_none() => "down".to_string(),
+                    (Some(updated), _) => format_duration(updated.elapsed().as_secs()),
+                    (None, _) => "-".to_string(),
+                };
+                Row::new([
+                    Cell::from(format!("● {}", node.name)).fg(node_color(node)),
+                    Cell::from(sync),
+                    Cell::from(tip),
+                    Cell::from(seen).fg(if node.error.is_some() {
+                        Color::Red
+                    } else {
+                        Color::Gray
+                    }),
+                ])
+                .bottom_margin(ac.frame.density.spacing())
+            });
+        let table = Table::new(rows, widths).header(header);
+        frame.render_widget(table, table_area);
+
+        let down = nodes.iter().filter(|node| node.error.is_some()).count();
+        let mut footer = vec![Span::raw(format!("{} nodes", nodes.len())).gray()];
+        if down > 0 {
+            footer.push(Span::raw(format!(", {} unreachable", down)).red());
+        }
+        if pages > 1 {
+            footer.push(Span::raw(format!(" | {}/{}", page + 1, pages)).gray());
+        }
+        frame.render_widget(Line::from(footer).centered(), footer_area);
+    }
+}
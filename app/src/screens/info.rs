@@ -1,11 +1,34 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::download::{DownloadProgress, read_progress};
+use crate::environment::EnvironmentReading;
+use crate::i18n::tr;
+use crate::oplock::{self, Holder, Operation};
 use crate::screens::{AppContext, Kind, Screen, ScreenAction};
-use crate::update::{UpdateState, read_state_file};
+use crate::storage::StorageHistory;
+use crate::thermal::ThermalStatus;
+use crate::update::{UpdateSchedule, UpdateState, read_state_file};
+use crate::util::{current_timestamp, format_bytes, format_duration};
 use ratatui::prelude::*;
-use ratatui::widgets::Paragraph;
+use ratatui::widgets::{Gauge, Paragraph};
+use std::time::Duration;
 
-/// Displays version information for all managed applications.
+/// Displays version information for all managed applications and the disk
+/// usage and temperature of the node. A double A applies pending updates right away, without
+/// waiting for the maintenance window, holding A exports a support bundle.
+/// X selects an application, holding X holds it at its version or releases
+/// it. Shows the progress of the update being
+/// downloaded and the operation running, with those waiting for it.
 pub struct InfoScreen {
     state: UpdateState,
+    schedule: UpdateSchedule,
+    download: Option<DownloadProgress>,
+    operation: Option<Holder>,
+    queued: Vec<Operation>,
+    apply_requested: bool,
+    export_requested: bool,
+    /// Application selected, in the order shown
+    selected: usize,
+    hold_request: Option<(String, bool)>,
 }
 
 impl Default for InfoScreen {
@@ -13,29 +36,233 @@ impl Default for InfoScreen {
         Self {
             // Load the state once on creation
             state: read_state_file().unwrap_or_default(),
+            schedule: UpdateSchedule::from_env(),
+            download: read_progress(),
+            operation: oplock::holder(),
+            queued: oplock::queue(),
+            apply_requested: false,
+            export_requested: false,
+            selected: 0,
+            hold_request: None,
         }
     }
 }
 
+fn storage_lines(storage: &StorageHistory, lines: &mut Vec<Line>) {
+    let Some(latest) = storage.latest else {
+        return;
+    };
+    let free_color = if latest.is_low() {
+        Color::Red
+    } else {
+        Color::Green
+    };
+    lines.push(Line::from(" STORAGE ").centered());
+    lines.push(Line::from(vec![
+        Span::raw("  Free:     "),
+        Span::styled(
+            format!(
+                "{} ({}%)",
+                format_bytes(latest.free_bytes),
+                latest.free_percent()
+            ),
+            Style::default().fg(free_color),
+        ),
+    ]));
+    let growth = storage
+        .growth_per_day()
+        .map(|growth| {
+            format!(
+                ", {}{}/day",
+                if growth < 0.0 { "-" } else { "+" },
+                format_bytes(growth.abs() as u64)
+            )
+        })
+        .unwrap_or_default();
+    lines.push(Line::from(vec![
+        Span::raw("  Node DB:  "),
+        Span::styled(
+            format!("{}{}", format_bytes(latest.db_bytes()), growth),
+            Style::default().fg(Color::Cyan),
+        ),
+    ]));
+    if let Some(days) = storage.days_until_full() {
+        let color = if days < 30.0 {
+            Color::Yellow
+        } else {
+            Color::DarkGray
+        };
+        lines.push(Line::from(vec![
+            Span::raw("  Full in:  "),
+            Span::styled(format!("~{:.0} days", days), Style::default().fg(color)),
+        ]));
+    }
+}
+
+fn thermal_lines(thermal: &ThermalStatus, lines: &mut Vec<Line>) {
+    let Some(celsius) = thermal.celsius else {
+        return;
+    };
+    let color = if thermal.is_hot() {
+        Color::Red
+    } else {
+        Color::Green
+    };
+    lines.push(Line::from(" THERMAL ").centered());
+    lines.push(Line::from(vec![
+        Span::raw("  SoC:      "),
+        Span::styled(format!("{:.1}°C", celsius), Style::default().fg(color)),
+    ]));
+    if let Some(duty) = thermal.fan_duty {
+        lines.push(Line::from(vec![
+            Span::raw("  Fan:      "),
+            Span::styled(format!("{}%", duty), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    let Some(throttled) = thermal.throttled else {
+        return;
+    };
+    let (now, since_boot) = (throttled.now(), throttled.since_boot());
+    let (text, color) = if !now.is_empty() {
+        (now.join(", "), Color::Red)
+    } else if !since_boot.is_empty() {
+        (format!("earlier: {}", since_boot.join(", ")), Color::Yellow)
+    } else {
+        ("no".to_string(), Color::Green)
+    };
+    lines.push(Line::from(vec![
+        Span::raw("  Limited:  "),
+        Span::styled(text, Style::default().fg(color)),
+    ]));
+}
+
+fn environment_lines(reading: &EnvironmentReading, lines: &mut Vec<Line>) {
+    let color = if reading.is_hot() {
+        Color::Red
+    } else {
+        Color::Green
+    };
+    lines.push(Line::from(" ENCLOSURE ").centered());
+    lines.push(Line::from(vec![
+        Span::raw("  Temp:     "),
+        Span::styled(
+            format!("{:.1}°C", reading.celsius),
+            Style::default().fg(color),
+        ),
+    ]));
+    if let Some(humidity) = reading.humidity {
+        lines.push(Line::from(vec![
+            Span::raw("  Humidity: "),
+            Span::styled(
+                format!("{:.0}%", humidity),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]));
+    }
+    if let Some(hpa) = reading.hpa {
+        lines.push(Line::from(vec![
+            Span::raw("  Pressure: "),
+            Span::styled(format!("{:.0} hPa", hpa), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+}
+
+fn operation_lines(holder: &Holder, queued: &[Operation], lines: &mut Vec<Line>) {
+    let running = current_timestamp()
+        .map(|now| format!(" ({})", format_duration(now.saturating_sub(holder.since))))
+        .unwrap_or_default();
+    lines.push(Line::from(" OPERATION ").centered());
+    lines.push(Line::from(vec![
+        Span::raw("  Running:  "),
+        Span::styled(
+            format!("{}{}", holder.operation, running),
+            Style::default().fg(Color::Yellow),
+        ),
+    ]));
+    for operation in queued {
+        lines.push(Line::from(vec![
+            Span::raw("  Waiting:  "),
+            Span::styled(operation.to_string(), Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+    lines.push(Line::from("")); // spacer
+}
+
+impl InfoScreen {
+    /// Names of the applications, in the order shown.
+    fn app_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.state.applications.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
 impl Screen for InfoScreen {
     fn kind(&self) -> Kind {
         Kind::Info
     }
 
-    /// Re-check the state file periodically.
-    fn update(&mut self, ac: AppContext) -> ScreenAction {
-        // Refresh state every 200 frames (approx 5-10s)
-        if ac.frame.frame_count.is_multiple_of(200)
-            && let Ok(new_state) = read_state_file()
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        let names = self.app_names();
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) if !names.is_empty() => {
+                self.selected = (self.selected + 1) % names.len();
+                return true;
+            }
+            (ButtonId::X, ButtonPress::Long) if !names.is_empty() => {
+                let name = names[self.selected.min(names.len() - 1)].clone();
+                if let Some(app) = self.state.applications.get_mut(&name) {
+                    app.hold = !app.hold;
+                    self.hold_request = Some((name, app.hold));
+                }
+                return true;
+            }
+            _ => {}
+        }
+        if event.id == ButtonId::A && event.press_type == ButtonPress::Long {
+            self.export_requested = true;
+            return true;
+        }
+        if event.id == ButtonId::A
+            && event.press_type == ButtonPress::Double
+            && !self.state.get_pending_app_names().is_empty()
         {
+            self.apply_requested = true;
+            return true;
+        }
+        false
+    }
+
+    fn update_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
+    }
+
+    /// Re-check the state file periodically.
+    fn update(&mut self, _ac: AppContext) -> ScreenAction {
+        if std::mem::take(&mut self.apply_requested) {
+            return ScreenAction::ApplyUpdate;
+        }
+        if std::mem::take(&mut self.export_requested) {
+            return ScreenAction::ExportDiagnostics;
+        }
+        if let Some((name, hold)) = self.hold_request.take() {
+            return ScreenAction::SetUpdateHold(name, hold);
+        }
+        self.download = read_progress();
+        self.operation = oplock::holder();
+        self.queued = oplock::queue();
+        if let Ok(new_state) = read_state_file() {
             self.state = new_state;
         }
         ScreenAction::None
     }
 
     /// Renders the version information.
-    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
         let mut lines = Vec::new();
+        if let Some(holder) = &self.operation {
+            operation_lines(holder, &self.queued, &mut lines);
+        }
 
         if self.state.applications.is_empty() {
             lines.push(Line::from("").centered());
@@ -48,11 +275,26 @@ impl Screen for InfoScreen {
 
             let mut sorted_apps: Vec<_> = self.state.applications.iter().collect();
             sorted_apps.sort();
-            for (app_name, app_state) in sorted_apps {
-                lines.push(Line::from(vec![
-                    Span::raw("  App:      "),
+            let selected = self.selected.min(sorted_apps.len() - 1);
+            for (i, (app_name, app_state)) in sorted_apps.into_iter().enumerate() {
+                let mut name = vec![
+                    Span::raw(if i == selected {
+                        "> App:      "
+                    } else {
+                        "  App:      "
+                    }),
                     Span::styled(app_name, Style::default().fg(Color::Cyan)),
-                ]));
+                ];
+                if self.schedule.is_manual(app_name) {
+                    name.push(Span::styled(
+                        " (manual)",
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                if app_state.hold {
+                    name.push(Span::styled(" (held)", Style::default().fg(Color::Yellow)));
+                }
+                lines.push(Line::from(name));
                 lines.push(Line::from(vec![
                     Span::raw("  Version:  "),
                     Span::styled(
@@ -69,6 +311,30 @@ impl Screen for InfoScreen {
                         ),
                     ]));
                 }
+                if !app_state.slot.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::raw("  Slot:     "),
+                        Span::styled(&app_state.slot, Style::default().fg(Color::DarkGray)),
+                    ]));
+                }
+                if !app_state.pinned_version.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::raw("  Pinned:   "),
+                        Span::styled(
+                            &app_state.pinned_version,
+                            Style::default().fg(Color::Yellow),
+                        ),
+                    ]));
+                }
+                if !app_state.rolled_back_version.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::raw("  Rejected: "),
+                        Span::styled(
+                            &app_state.rolled_back_version,
+                            Style::default().fg(Color::Red),
+                        ),
+                    ]));
+                }
                 if !app_state.pending_version.is_empty() {
                     lines.push(Line::from(vec![
                         Span::raw("  Pending:  "),
@@ -77,13 +343,67 @@ impl Screen for InfoScreen {
                             Style::default().fg(Color::Yellow),
                         ),
                     ]));
+                } else if !app_state.available_version.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::raw("  Released: "),
+                        Span::styled(
+                            &app_state.available_version,
+                            Style::default().fg(Color::Yellow),
+                        ),
+                    ]));
                 }
                 lines.push(Line::from("")); // spacer
             }
+
+            if !self.state.get_pending_app_names().is_empty() {
+                if let Some(window) = self.schedule.window {
+                    lines.push(
+                        Line::from(format!("Updates applied at {}", window))
+                            .gray()
+                            .centered(),
+                    );
+                }
+                lines.push(Line::from(tr("A (double): Update now")).gray().centered());
+            }
+            lines.push(Line::from(tr("X (hold): Hold, release")).gray().centered());
         }
 
+        storage_lines(&ac.system.storage, &mut lines);
+        thermal_lines(&ac.system.thermal, &mut lines);
+        if let Some(reading) = &ac.system.environment {
+            environment_lines(reading, &mut lines);
+        }
         let paragraph = Paragraph::new(lines).alignment(Alignment::Left);
 
-        frame.render_widget(paragraph, area);
+        let Some(download) = &self.download else {
+            frame.render_widget(paragraph, area);
+            return;
+        };
+        let [info_area, name_area, gauge_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        frame.render_widget(paragraph, info_area);
+        frame.render_widget(
+            Line::from(format!("Downloading {}", download.name))
+                .gray()
+                .centered(),
+            name_area,
+        );
+        let downloaded_mb = download.downloaded as f64 / 1_000_000.0;
+        let gauge = match (download.ratio(), download.total) {
+            (Some(ratio), Some(total)) => Gauge::default().ratio(ratio).label(format!(
+                "{:.1} / {:.1} MB",
+                downloaded_mb,
+                total as f64 / 1_000_000.0
+            )),
+            _ => Gauge::default().label(format!("{:.1} MB", downloaded_mb)),
+        };
+        frame.render_widget(
+            gauge.gauge_style(Style::default().fg(Color::Cyan)),
+            gauge_area,
+        );
     }
 }
@@ -0,0 +1,259 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::inputs::mapping::Command;
+use crate::ip_config::{self, IpField, IpMethod, IpSettings};
+use crate::keyboard::{KeyboardAction, KeyboardWidget};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::widgets::text_input::TextInput;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Switches an interface between DHCP and a static address, for relays
+/// needing a stable LAN address to forward ports to. X selects the next
+/// setting, A edits it and a double A applies the changes.
+pub struct IpConfigScreen {
+    interface: String,
+    /// Mirrors the system state
+    current: Option<Result<IpSettings, String>>,
+    /// Edited settings not applied yet
+    draft: Option<IpSettings>,
+    selected: usize,
+    input: TextInput,
+    keyboard: KeyboardWidget,
+    /// Setting edited with the keyboard
+    editing: Option<IpField>,
+    /// Why the draft can't be applied, after a double A
+    error: Option<String>,
+    applying: bool,
+    action: Option<ScreenAction>,
+}
+
+impl Default for IpConfigScreen {
+    fn default() -> Self {
+        Self {
+            interface: ip_config::interface(),
+            current: None,
+            draft: None,
+            selected: 0,
+            input: TextInput::new(),
+            keyboard: KeyboardWidget::default(),
+            editing: None,
+            error: None,
+            applying: false,
+            action: None,
+        }
+    }
+}
+
+impl IpConfigScreen {
+    /// The draft, else the settings read.
+    fn settings(&self) -> Option<&IpSettings> {
+        self.draft
+            .as_ref()
+            .or_else(|| self.current.as_ref()?.as_ref().ok())
+    }
+
+    /// Only the method is set with DHCP.
+    fn fields(&self) -> &'static [IpField] {
+        match self.settings().map(|settings| settings.method) {
+            Some(IpMethod::Static) => &IpField::ALL,
+            _ => &IpField::ALL[..1],
+        }
+    }
+
+    fn edit(&mut self, field: IpField) {
+        let Some(mut settings) = self.settings().cloned() else {
+            return;
+        };
+        self.error = None;
+        if field == IpField::Method {
+            settings.method = match settings.method {
+                IpMethod::Dhcp => IpMethod::Static,
+                IpMethod::Static => IpMethod::Dhcp,
+            };
+            self.draft = Some(settings);
+            return;
+        }
+        self.input = TextInput::new()
+            .max_length(64)
+            .validator(move |value| field.validate(value));
+        self.input.set_value(field.get(&settings));
+        self.editing = Some(field);
+    }
+
+    fn apply(&mut self) {
+        let Some(draft) = &self.draft else {
+            return;
+        };
+        match draft.validate() {
+            Ok(()) => self.action = Some(ScreenAction::ApplyIpConfig(draft.clone())),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn display_editing(&self, field: IpField, frame: &mut Frame, area: Rect) {
+        let [help_area, input_area, keyboard_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        let help = match field {
+            IpField::Address => tr("With its prefix, e.g. 192.168.1.50/24"),
+            IpField::Gateway => tr("The router, e.g. 192.168.1.1"),
+            _ => tr("Comma separated, e.g. 1.1.1.1,9.9.9.9"),
+        };
+        frame.render_widget(
+            Paragraph::new(help)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Cyan)),
+            help_area,
+        );
+        self.input.render(
+            frame,
+            input_area,
+            field.label(),
+            Style::default().fg(Color::Yellow),
+            true,
+        );
+        self.keyboard.render(frame, keyboard_area);
+    }
+}
+
+impl Screen for IpConfigScreen {
+    fn kind(&self) -> Kind {
+        Kind::IpConfig
+    }
+
+    fn is_typing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    fn handle_command(&mut self, command: Command) -> bool {
+        if self.editing.is_none() || !command.is_for_keyboard() {
+            return false;
+        }
+        if let Some(action) = self.keyboard.handle_command(command) {
+            self.input.handle_action(&action);
+        }
+        true
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if let Some(field) = self.editing {
+            match self.keyboard.handle_input(event) {
+                Some(KeyboardAction::Exit) => {
+                    self.editing = None;
+                    if self.input.validate().is_ok()
+                        && let Some(mut settings) = self.settings().cloned()
+                    {
+                        field.set(&mut settings, self.input.value().trim().to_string());
+                        self.draft = Some(settings);
+                    }
+                }
+                Some(action) => {
+                    self.input.handle_action(&action);
+                }
+                None => {}
+            }
+            return true; // Keyboard always captures input
+        }
+        if self.settings().is_none() || self.applying {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % self.fields().len();
+            }
+            (ButtonId::X, ButtonPress::Long) if self.draft.is_some() => {
+                self.draft = None;
+                self.error = None;
+                self.selected = 0;
+            }
+            (ButtonId::A, ButtonPress::Short) => {
+                let field = self.fields()[self.selected.min(self.fields().len() - 1)];
+                self.edit(field);
+            }
+            (ButtonId::A, ButtonPress::Double) => self.apply(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        self.keyboard
+            .set_paste_buffer(ac.system.paste_buffer.as_deref());
+        let state = &ac.system.ip_config;
+        if state.current != self.current {
+            // Applied, or read again
+            if state.current.as_ref().is_some_and(Result::is_ok) {
+                self.draft = None;
+            }
+            self.current = state.current.clone();
+        }
+        self.applying = state.applying;
+        self.selected = self.selected.min(self.fields().len() - 1);
+        self.action.take().unwrap_or(ScreenAction::None)
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        if let Some(field) = self.editing {
+            self.display_editing(field, frame, area);
+            return;
+        }
+
+        let mut lines = vec![
+            Line::from(format!("{} {}", tr("IP address of"), self.interface))
+                .bold()
+                .centered(),
+        ];
+        let settings = match &self.current {
+            None => {
+                lines.push(Line::from(tr("Loading...")).centered());
+                frame.render_widget(Paragraph::new(lines), area);
+                return;
+            }
+            Some(Err(e)) => {
+                lines.push(Line::from(e.as_str()).red().centered());
+                frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+                return;
+            }
+            Some(Ok(current)) => self.draft.as_ref().unwrap_or(current),
+        };
+        let current = self.current.as_ref().and_then(|c| c.as_ref().ok());
+
+        for (i, field) in self.fields().iter().enumerate() {
+            let label_style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            let value = field.get(settings);
+            let changed = current.is_some_and(|current| field.get(current) != value);
+            let value = match value {
+                value if value.is_empty() => Span::raw("none").gray(),
+                value if changed => Span::raw(value).yellow(),
+                value => Span::raw(value).cyan(),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {} ", field.label()), label_style),
+                Span::raw(" "),
+                value,
+            ]));
+        }
+
+        lines.push(Line::from(""));
+        if let Some(error) = &self.error {
+            lines.push(Line::from(error.as_str()).red().centered());
+        }
+        let help = if self.applying {
+            tr("Applying...")
+        } else if self.draft.is_some() {
+            tr("A A: Apply | Hold X: Discard")
+        } else {
+            tr("X: Next | A: Edit")
+        };
+        lines.push(Line::from(help).gray().centered());
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::{self, Language, tr};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+/// Picks the language of the screens: X selects the next one, A applies it.
+#[derive(Debug, Default)]
+pub struct LanguageScreen {
+    selected: usize,
+    requested: bool,
+}
+
+impl Screen for LanguageScreen {
+    fn kind(&self) -> Kind {
+        Kind::Language
+    }
+
+    fn enter(&mut self) {
+        let current = i18n::current();
+        self.selected = Language::ALL
+            .iter()
+            .position(|language| *language == current)
+            .unwrap_or(0);
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % Language::ALL.len();
+            }
+            (ButtonId::A, ButtonPress::Short) => self.requested = true,
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, _ac: AppContext) -> ScreenAction {
+        if std::mem::take(&mut self.requested) {
+            return ScreenAction::SetLanguage(Language::ALL[self.selected]);
+        }
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        vec![(self.selected, Language::ALL.len())]
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let [list_area, hint_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+
+        let current = i18n::current();
+        let mut lines = vec![Line::from(""), Line::from(tr("Language")).bold().centered()];
+        for (i, language) in Language::ALL.iter().enumerate() {
+            let marker = if *language == current { "✓" } else { " " };
+            let line = Line::from(format!(" {} {}  ", marker, language)).centered();
+            lines.push(if i == self.selected {
+                line.black().on_cyan()
+            } else {
+                line
+            });
+        }
+        frame.render_widget(Paragraph::new(lines), list_area);
+        frame.render_widget(
+            Paragraph::new(tr("X: Next | A: Select")).gray().centered(),
+            hint_area,
+        );
+    }
+}
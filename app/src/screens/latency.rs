@@ -0,0 +1,71 @@
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+/// Rows that fit the display
+const MAX_PEERS: usize = 8;
+/// Last characters of the address shown before each row
+const LABEL_WIDTH: usize = 12;
+
+fn latency_color(rtt: Option<f64>) -> Color {
+    match rtt {
+        None => Color::DarkGray,
+        Some(rtt) if rtt < 50.0 => Color::Green,
+        Some(rtt) if rtt < 150.0 => Color::Yellow,
+        Some(rtt) if rtt < 300.0 => Color::LightRed,
+        Some(_) => Color::Red,
+    }
+}
+
+/// Heatmap of the round trip time to each peer by hour of the day, most
+/// recently seen peers first.
+#[derive(Debug, Default)]
+pub struct LatencyScreen {}
+
+impl Screen for LatencyScreen {
+    fn kind(&self) -> Kind {
+        Kind::Latency
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let peers = ac.system.latency.recent_peers();
+        let mut lines = vec![Line::from("")];
+
+        if peers.is_empty() {
+            lines.push(Line::from(tr("No latency samples yet")).centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        let padding = " ".repeat(LABEL_WIDTH + 1);
+        lines.push(Line::from(format!("{}0     6     12    18   ", padding)).gray());
+        for (addr, peer) in peers.into_iter().take(MAX_PEERS) {
+            let label = &addr[addr.len().saturating_sub(LABEL_WIDTH)..];
+            let mut spans = vec![Span::raw(format!(
+                "{:>width$} ",
+                label,
+                width = LABEL_WIDTH
+            ))];
+            spans.extend(
+                peer.hours
+                    .iter()
+                    .map(|rtt| Span::styled("█", Style::default().fg(latency_color(*rtt)))),
+            );
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("█", Style::default().fg(Color::Green)),
+            Span::raw("<50ms "),
+            Span::styled("█", Style::default().fg(Color::Yellow)),
+            Span::raw("<150 "),
+            Span::styled("█", Style::default().fg(Color::LightRed)),
+            Span::raw("<300 "),
+            Span::styled("█", Style::default().fg(Color::Red)),
+            Span::raw("slower"),
+        ]));
+
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
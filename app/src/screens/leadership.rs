@@ -0,0 +1,119 @@
+use crate::i18n::tr;
+use crate::leadership::{LeadershipConfig, SlotOutcome};
+use crate::screens::{AppContext, Kind, Screen};
+use crate::util::{current_timestamp, format_duration, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use std::path::PathBuf;
+
+/// Leadership slots of the current epoch, with a countdown to the next one
+/// and whether the past ones were minted.
+#[derive(Debug)]
+pub struct LeadershipScreen {
+    schedule_path: PathBuf,
+}
+
+impl Default for LeadershipScreen {
+    fn default() -> Self {
+        Self {
+            schedule_path: LeadershipConfig::from_env().schedule_path,
+        }
+    }
+}
+
+fn outcome_span(outcome: SlotOutcome) -> Span<'static> {
+    match outcome {
+        SlotOutcome::Upcoming => Span::raw("upcoming").cyan(),
+        SlotOutcome::Minted => Span::raw("minted").green(),
+        SlotOutcome::NotMinted => Span::raw("not minted").red(),
+        SlotOutcome::Unconfirmed => Span::raw("unconfirmed").gray(),
+    }
+}
+
+impl Screen for LeadershipScreen {
+    fn kind(&self) -> Kind {
+        Kind::Leadership
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let schedule = &ac.system.leadership;
+        let Some(epoch) = schedule.epoch else {
+            let para = Paragraph::new(tr("Loading...")).alignment(Alignment::Center);
+            frame.render_widget(para, area);
+            return;
+        };
+        let mut lines = vec![
+            Line::from(format!("Epoch {} leadership", epoch))
+                .bold()
+                .centered(),
+        ];
+        if schedule.slots.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(tr("No slots scheduled")).centered());
+            lines.push(
+                Line::from(format!(
+                    "Schedule read from {}",
+                    self.schedule_path.display()
+                ))
+                .gray()
+                .centered(),
+            );
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        let now = current_timestamp().unwrap_or_default();
+        match schedule.next_slot(now) {
+            Some(next) => lines.push(
+                Line::from(vec![
+                    Span::raw(tr("Next in ")),
+                    Span::raw(format_duration(next.time - now)).yellow().bold(),
+                    Span::raw(format!(" (#{})", next.slot)),
+                ])
+                .centered(),
+            ),
+            None => lines.push(Line::from(tr("No more slots this epoch")).gray().centered()),
+        }
+        let past = schedule
+            .slots
+            .iter()
+            .filter(|slot| slot.outcome != SlotOutcome::Upcoming)
+            .count();
+        lines.push(
+            Line::from(format!("Minted {}/{}", schedule.minted(), past))
+                .gray()
+                .centered(),
+        );
+
+        // Keeps the next slot in view, after the last few past ones
+        let rows = (area.height as usize).saturating_sub(lines.len());
+        let next_index = schedule
+            .slots
+            .iter()
+            .position(|slot| slot.time >= now)
+            .unwrap_or(schedule.slots.len());
+        let first = next_index
+            .saturating_sub(rows / 3)
+            .min(schedule.slots.len().saturating_sub(rows));
+        for (i, slot) in schedule.slots.iter().enumerate().skip(first).take(rows) {
+            let style = if i == next_index {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(
+                        " {} #{} ",
+                        format_local_time(slot.time).unwrap_or_default(),
+                        slot.slot
+                    ),
+                    style,
+                ),
+                Span::raw(" "),
+                outcome_span(slot.outcome),
+            ]));
+        }
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
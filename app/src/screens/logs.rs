@@ -1,3 +1,6 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::logging;
 use crate::logs::{JournalReader, LogEntry, LogLevel, extract_json};
 use crate::screens::{AppContext, Kind, ScreenAction};
 use ratatui::Frame;
@@ -10,6 +13,7 @@ use std::cell::RefCell;
 use std::env;
 use std::time::{Duration, Instant};
 use tachyonfx::{CellFilter, EffectManager, EffectTimer, Interpolation, Motion, fx};
+use tracing::warn;
 
 impl LogLevel {
     fn color(&self) -> Color {
@@ -23,8 +27,11 @@ impl LogLevel {
     }
 }
 
+/// Logs of the node. X changes what amaru-pi itself logs.
 pub struct LogsScreen {
     level: LogLevel,
+    /// Filter of amaru-pi's own logs
+    own_filter: String,
     reader: JournalReader,
     last_refresh: Instant,
     logs: Vec<LogEntry>,
@@ -42,6 +49,7 @@ impl Default for LogsScreen {
         let reader = JournalReader::new("amaru.service");
         LogsScreen {
             level,
+            own_filter: logging::current_filter(),
             reader,
             last_refresh: Instant::now(),
             effects: RefCell::new(EffectManager::default()),
@@ -117,25 +125,48 @@ impl crate::screens::Screen for LogsScreen {
         Kind::Logs
     }
 
-    fn update(&mut self, ac: AppContext) -> ScreenAction {
-        if ac.frame.frame_count.is_multiple_of(100) {
-            let logs = self
-                .reader
-                .next_lines()
-                .unwrap_or_default()
-                .iter()
-                .flat_map(|str| extract_json(str.as_str()))
-                .filter(|log| log.level >= self.level)
-                .collect::<Vec<_>>();
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if (event.id, event.press_type) != (ButtonId::X, ButtonPress::Short) {
+            return false;
+        }
+        match logging::set_filter(logging::next_level()) {
+            Ok(()) => self.own_filter = logging::current_filter(),
+            Err(e) => warn!("Failed to change the log level: {}", e),
+        }
+        true
+    }
+
+    fn update_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(2))
+    }
+
+    fn update(&mut self, _ac: AppContext) -> ScreenAction {
+        self.own_filter = logging::current_filter();
+        let logs = self
+            .reader
+            .next_lines()
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|str| extract_json(str.as_str()))
+            .filter(|log| log.level >= self.level)
+            .collect::<Vec<_>>();
 
-            if !logs.is_empty() {
-                self.update_logs(logs);
-            }
+        if !logs.is_empty() {
+            self.update_logs(logs);
         }
         ScreenAction::None
     }
 
     fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let [area, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+        frame.render_widget(
+            Paragraph::new(format!("amaru-pi logs {} (X)", self.own_filter))
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+
         if self.logs.is_empty() {
             // Show "no logs" centered
             let chunks = Layout::default()
@@ -147,7 +178,7 @@ impl crate::screens::Screen for LogsScreen {
                 ])
                 .split(area);
 
-            let para = Paragraph::new("No logs")
+            let para = Paragraph::new(tr("No logs"))
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(Color::Gray));
             frame.render_widget(para, chunks[1]);
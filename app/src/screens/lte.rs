@@ -0,0 +1,164 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::inputs::mapping::Command;
+use crate::keyboard::{KeyboardAction, KeyboardWidget};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::format_bytes;
+use crate::widgets::text_input::TextInput;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Shows the USB LTE modem status and data used. A double A edits the APN
+/// with the on-screen keyboard.
+pub struct LteScreen {
+    apn: TextInput,
+    keyboard: KeyboardWidget,
+    editing: bool,
+    /// APN reported by the modem, used to prefill the input
+    current_apn: String,
+    action: Option<ScreenAction>,
+}
+
+impl Default for LteScreen {
+    fn default() -> Self {
+        Self {
+            apn: TextInput::new().max_length(64).validator(|apn| {
+                if apn.trim().is_empty() {
+                    Err("APN required".to_string())
+                } else {
+                    Ok(())
+                }
+            }),
+            keyboard: KeyboardWidget::default(),
+            editing: false,
+            current_apn: String::new(),
+            action: None,
+        }
+    }
+}
+
+impl Screen for LteScreen {
+    fn kind(&self) -> Kind {
+        Kind::Lte
+    }
+
+    fn is_typing(&self) -> bool {
+        self.editing
+    }
+
+    fn handle_command(&mut self, command: Command) -> bool {
+        if !self.editing || !command.is_for_keyboard() {
+            return false;
+        }
+        if let Some(action) = self.keyboard.handle_command(command) {
+            self.apn.handle_action(&action);
+        }
+        true
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.editing {
+            match self.keyboard.handle_input(event) {
+                Some(KeyboardAction::Exit) => {
+                    self.editing = false;
+                    if self.apn.validate().is_ok() {
+                        let apn = self.apn.value().trim().to_string();
+                        self.action = Some(ScreenAction::SetApn(apn));
+                    }
+                }
+                Some(action) => {
+                    self.apn.handle_action(&action);
+                }
+                None => {}
+            }
+            return true; // Keyboard always captures input
+        }
+        if event.id == ButtonId::A && event.press_type == ButtonPress::Double {
+            self.apn.set_value(self.current_apn.clone());
+            self.editing = true;
+            return true;
+        }
+        false
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        self.keyboard
+            .set_paste_buffer(ac.system.paste_buffer.as_deref());
+        self.current_apn.clone_from(&ac.system.modem.apn);
+        self.action.take().unwrap_or(ScreenAction::None)
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        if self.editing {
+            let [help_area, input_area, keyboard_area] = Layout::vertical([
+                Constraint::Length(2),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .areas(area);
+            frame.render_widget(
+                Paragraph::new(tr("Enter the APN of your carrier"))
+                    .alignment(Alignment::Center)
+                    .style(Style::default().fg(Color::Cyan)),
+                help_area,
+            );
+            self.apn.render(
+                frame,
+                input_area,
+                "APN",
+                Style::default().fg(Color::Yellow),
+                true,
+            );
+            self.keyboard.render(frame, keyboard_area);
+            return;
+        }
+
+        let modem = &ac.system.modem;
+        let mut lines = vec![Line::from("")];
+        if !modem.present {
+            lines.push(Line::from(tr("No LTE modem detected")).centered());
+            lines.push(Line::from(tr("Plug in a USB modem")).gray().centered());
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+            return;
+        }
+
+        let signal_color = match modem.signal {
+            60.. => Color::Green,
+            30.. => Color::Yellow,
+            _ => Color::Red,
+        };
+        let state_color = if modem.is_connected() {
+            Color::Green
+        } else {
+            Color::Yellow
+        };
+        let field = |label: &'static str, value: String, color: Color| {
+            Line::from(vec![
+                Span::raw(label),
+                Span::styled(value, Style::default().fg(color)),
+            ])
+        };
+        lines.push(field("  Modem:    ", modem.model.clone(), Color::Cyan));
+        lines.push(field("  State:    ", modem.state.clone(), state_color));
+        lines.push(field("  Operator: ", modem.operator.clone(), Color::Cyan));
+        lines.push(field(
+            "  Signal:   ",
+            format!("{}% {}", modem.signal, modem.access_tech),
+            signal_color,
+        ));
+        lines.push(field("  APN:      ", modem.apn.clone(), Color::Cyan));
+        lines.push(field(
+            "  Data:     ",
+            format!(
+                "↓{} ↑{}",
+                format_bytes(modem.rx_bytes),
+                format_bytes(modem.tx_bytes)
+            ),
+            Color::Cyan,
+        ));
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr("A (double): Set APN")).gray().centered());
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
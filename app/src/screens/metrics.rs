@@ -4,6 +4,7 @@ use crate::{
 };
 use amaru_doctor::{components::Component, metrics::page::MetricsPageComponent};
 use ratatui::{Frame, layout::Rect};
+use std::time::Duration;
 
 pub struct MetricsScreen {
     component: MetricsPageComponent,
@@ -22,6 +23,10 @@ impl Screen for MetricsScreen {
         Kind::Metrics
     }
 
+    fn update_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
+    }
+
     fn update(&mut self, _ac: AppContext) -> ScreenAction {
         self.component.tick();
         ScreenAction::None
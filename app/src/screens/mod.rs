@@ -1,31 +1,135 @@
-use crate::{button::InputEvent, frame::FrameState, systemd::ServiceInfo, wifi::NetworkStatus};
+use crate::{
+    admin::AdminSession,
+    backup::BackupStatus,
+    boot::BootHistory,
+    button::InputEvent,
+    clock::ClockStatus,
+    coredump::Coredump,
+    environment::EnvironmentReading,
+    faucet::FaucetStatus,
+    files::Listing,
+    firewall::FirewallState,
+    fleet::FleetNode,
+    frame::FrameState,
+    i18n::Language,
+    inputs::mapping::Command,
+    ip_config::{IpConfigState, IpSettings},
+    latency::LatencyHistory,
+    leadership::LeadershipSchedule,
+    modem::ModemStatus,
+    node_config::{ConfigStatus, Field},
+    peers::PeersSnapshot,
+    pools::PoolsSnapshot,
+    port_check::PortCheckState,
+    power::PowerStatus,
+    processes::ProcessInfo,
+    restore::{RestoreStatus, Snapshot},
+    stake_pool::PoolSummary,
+    storage::StorageHistory,
+    sync_eta::SyncEta,
+    systemd::{ServiceInfo, units::UnitStatus},
+    tasks::{TaskId, TaskInfo},
+    telemetry::Telemetry,
+    thermal::ThermalStatus,
+    ticker::TickerSnapshot,
+    topology::Reachability,
+    update_history::ReleaseNotes,
+    vpn::{VpnBackend, VpnStatus},
+    widgets::dialog::Dialog,
+    wifi::{NetworkStatus, SavedNetwork},
+};
 use ratatui::{Frame, layout::Rect};
 use std::{
+    collections::BTreeMap,
     fmt::{self, Display},
+    path::PathBuf,
     str::FromStr,
+    time::Duration,
 };
 
+pub mod backup;
+pub mod blocks;
+pub mod boot;
+pub mod buttons;
+pub mod clock;
 pub mod color;
+pub mod config;
+pub mod coredumps;
+pub mod events;
 pub mod exit;
+pub mod faucet;
+pub mod files;
+pub mod firewall;
+pub mod fleet;
 pub mod info;
+pub mod ip_config;
+pub mod language;
+pub mod latency;
+pub mod leadership;
 pub mod logo;
 pub mod logs;
+pub mod lte;
 pub mod metrics;
+pub mod networks;
+pub mod peers_map;
+pub mod pools;
+pub mod power;
+pub mod processes;
+pub mod reachability;
+pub mod restore;
 pub mod scan;
+pub mod stake_pool;
+pub mod tasks;
+pub mod telemetry;
+pub mod ticker;
 pub mod tip;
+pub mod topology;
+pub mod units;
+pub mod updates;
 pub mod wifi_settings;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Kind {
+    Backup,
+    Blocks,
+    Boot,
+    Buttons,
+    Clock,
     Color,
+    Config,
+    Coredumps,
+    Events,
     Exit,
+    Faucet,
+    Files,
+    Firewall,
+    Fleet,
+    Language,
+    Latency,
+    Leadership,
     Logo,
     Logs,
+    Lte,
     Metrics,
+    Networks,
+    PeersMap,
+    Pools,
+    Power,
+    Processes,
+    Reachability,
+    Restore,
     Scan,
+    StakePool,
+    Tasks,
+    Telemetry,
+    Ticker,
     Tip,
+    Topology,
+    Units,
+    Updates,
     WiFiSettings,
     Info,
+    IpConfig,
 }
 
 impl FromStr for Kind {
@@ -39,6 +143,37 @@ impl FromStr for Kind {
             "logs" => Ok(Kind::Logs),
             "scan" => Ok(Kind::Scan),
             "info" => Ok(Kind::Info),
+            "ip" | "ip-config" => Ok(Kind::IpConfig),
+            "pools" => Ok(Kind::Pools),
+            "stake-pool" | "pool" => Ok(Kind::StakePool),
+            "networks" => Ok(Kind::Networks),
+            "peers-map" | "peers" => Ok(Kind::PeersMap),
+            "power" | "shutdown" => Ok(Kind::Power),
+            "lte" => Ok(Kind::Lte),
+            "faucet" => Ok(Kind::Faucet),
+            "config" => Ok(Kind::Config),
+            "topology" => Ok(Kind::Topology),
+            "files" => Ok(Kind::Files),
+            "firewall" | "ufw" => Ok(Kind::Firewall),
+            "fleet" => Ok(Kind::Fleet),
+            "blocks" => Ok(Kind::Blocks),
+            "backup" => Ok(Kind::Backup),
+            "restore" => Ok(Kind::Restore),
+            "latency" => Ok(Kind::Latency),
+            "language" | "lang" => Ok(Kind::Language),
+            "tasks" => Ok(Kind::Tasks),
+            "leadership" | "leader" => Ok(Kind::Leadership),
+            "boot" => Ok(Kind::Boot),
+            "units" => Ok(Kind::Units),
+            "processes" | "top" => Ok(Kind::Processes),
+            "reachability" | "port" => Ok(Kind::Reachability),
+            "coredumps" | "cores" => Ok(Kind::Coredumps),
+            "telemetry" => Ok(Kind::Telemetry),
+            "ticker" | "price" => Ok(Kind::Ticker),
+            "buttons" | "calibration" => Ok(Kind::Buttons),
+            "clock" | "time" => Ok(Kind::Clock),
+            "events" => Ok(Kind::Events),
+            "updates" | "history" => Ok(Kind::Updates),
             "wifi-settings" | "wifi" | "wifi_settings" => Ok(Kind::WiFiSettings),
             _ => Err(()),
         }
@@ -48,15 +183,46 @@ impl FromStr for Kind {
 impl Display for Kind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Kind::Backup => write!(f, "Backup"),
+            Kind::Blocks => write!(f, "Blocks"),
+            Kind::Boot => write!(f, "Boot"),
+            Kind::Buttons => write!(f, "Buttons"),
+            Kind::Clock => write!(f, "Clock"),
             Kind::Color => write!(f, "Color"),
+            Kind::Config => write!(f, "Config"),
+            Kind::Coredumps => write!(f, "Coredumps"),
+            Kind::Events => write!(f, "Events"),
             Kind::Exit => write!(f, "Exit"),
+            Kind::Faucet => write!(f, "Faucet"),
+            Kind::Files => write!(f, "Files"),
+            Kind::Firewall => write!(f, "Firewall"),
+            Kind::Fleet => write!(f, "Fleet"),
+            Kind::Language => write!(f, "Language"),
+            Kind::Latency => write!(f, "Latency"),
+            Kind::Leadership => write!(f, "Leadership"),
             Kind::Logo => write!(f, "Logo"),
             Kind::Logs => write!(f, "Logs"),
+            Kind::Lte => write!(f, "Lte"),
             Kind::Metrics => write!(f, "Metrics"),
+            Kind::Networks => write!(f, "Networks"),
+            Kind::PeersMap => write!(f, "PeersMap"),
+            Kind::Pools => write!(f, "Pools"),
+            Kind::Power => write!(f, "Power"),
+            Kind::Processes => write!(f, "Processes"),
+            Kind::Reachability => write!(f, "Reachability"),
+            Kind::Restore => write!(f, "Restore"),
             Kind::Scan => write!(f, "Scan"),
+            Kind::StakePool => write!(f, "StakePool"),
+            Kind::Tasks => write!(f, "Tasks"),
+            Kind::Telemetry => write!(f, "Telemetry"),
+            Kind::Ticker => write!(f, "Ticker"),
             Kind::Tip => write!(f, "Tip"),
+            Kind::Topology => write!(f, "Topology"),
+            Kind::Units => write!(f, "Units"),
+            Kind::Updates => write!(f, "Updates"),
             Kind::WiFiSettings => write!(f, "WiFiSettings"),
             Kind::Info => write!(f, "Info"),
+            Kind::IpConfig => write!(f, "IpConfig"),
         }
     }
 }
@@ -76,6 +242,57 @@ pub enum ScreenAction {
     NextScreen,
     ConnectToWifi(String, String),
     ResetWifiConnectionStatus,
+    RequestFaucetFunds,
+    SetTelemetry(bool),
+    /// Saved networks by name, first one preferred
+    SetNetworkOrder(Vec<String>),
+    SwitchNetwork(String),
+    /// Brings the VPN up or down
+    SetVpn(VpnBackend, bool),
+    SetApn(String),
+    /// Exports the core dump of the given PID to a USB drive
+    ExportCoredump(u32),
+    /// Collects a support bundle, uploaded or copied to a USB drive
+    ExportDiagnostics,
+    /// Stages the updates found on a USB drive
+    StageOfflineUpdates,
+    /// Shows the journal of the given PID, of its service when it has one
+    ViewProcessLogs(u32, Option<String>),
+    /// Lists a directory, or shows the end of a file
+    Browse(PathBuf),
+    /// Deletes a rotated log
+    DeleteFile(PathBuf),
+    /// Writes settings to the node's env file
+    SaveNodeConfig(Vec<(Field, String)>),
+    /// Connects to a peer and proposes it a handshake
+    TestPeer(String),
+    /// Checks that the node can be reached from the internet
+    CheckPort,
+    /// Restarts the node for it to pick up its new config
+    RestartNode,
+    /// Applies pending updates now, outside of the maintenance window
+    ApplyUpdate,
+    /// Holds the application at its current version, or releases it
+    SetUpdateHold(String, bool),
+    /// Copies the ledger to a USB drive
+    BackUp,
+    /// Lists the snapshots to restore from again
+    RefreshSnapshots,
+    /// Replaces the ledger with a snapshot
+    Restore(Snapshot),
+    /// Notes the notification sent at the given time
+    AnnotateEvent(u64, String),
+    SetTimezone(String),
+    /// Switches the configured interface between DHCP and a static address
+    ApplyIpConfig(IpSettings),
+    /// Switches the screens to the language and saves it
+    SetLanguage(Language),
+    /// Shows a dialog, running what is answered
+    Confirm(Box<Dialog>),
+    /// Asks a running task to stop
+    CancelTask(TaskId),
+    /// Keeps text to be pasted with the keyboard on another screen
+    Copy(String),
 }
 
 #[derive(Debug, Default, Clone)]
@@ -83,6 +300,68 @@ pub struct SystemState {
     pub amaru_status: ServiceInfo,
     pub network_status: NetworkStatus,
     pub wifi_connection_status: WifiConnectionStatus,
+    pub pools: PoolsSnapshot,
+    /// Figures of the operated pool
+    pub stake_pool: PoolSummary,
+    /// ADA price and network figures, when the ticker screen is enabled
+    pub ticker: TickerSnapshot,
+    pub faucet: FaucetStatus,
+    pub telemetry: Telemetry,
+    pub admin: AdminSession,
+    pub saved_networks: Vec<SavedNetwork>,
+    /// `None` without Tailscale nor WireGuard
+    pub vpn: Option<VpnStatus>,
+    pub modem: ModemStatus,
+    /// Sign in page of the captive portal intercepting traffic, if any
+    pub captive_portal: Option<String>,
+    pub peers: PeersSnapshot,
+    pub latency: LatencyHistory,
+    pub boot: BootHistory,
+    /// Installed unit files compared to their templates
+    pub units: Vec<UnitStatus>,
+    /// Crashes of the node, newest first
+    pub coredumps: Vec<Coredump>,
+    /// Watched processes, in the order they are listed
+    pub processes: Vec<ProcessInfo>,
+    /// Last journal lines fetched, with the PID they are for
+    pub process_logs: Option<(u32, Vec<String>)>,
+    /// Last path browsed
+    pub browser: Option<Listing>,
+    pub node_config_status: ConfigStatus,
+    pub ip_config: IpConfigState,
+    pub port_check: PortCheckState,
+    pub firewall: FirewallState,
+    /// Last test of each peer tested, by address
+    pub peer_tests: BTreeMap<String, Reachability>,
+    pub leadership: LeadershipSchedule,
+    pub storage: StorageHistory,
+    /// Whether a USB drive is plugged in, checked on the backup screen
+    pub usb_drive: bool,
+    pub backup: BackupStatus,
+    /// Snapshots to restore from, on the USB drive first
+    pub snapshots: Vec<Snapshot>,
+    pub restore: RestoreStatus,
+    pub thermal: ThermalStatus,
+    /// Around the Pi, when a sensor is configured
+    pub environment: Option<EnvironmentReading>,
+    /// Until the initial sync is done, once the pace is known
+    pub sync_eta: Option<SyncEta>,
+    /// Latest slot of the node, and whether it was synced
+    pub tip: Option<(u64, bool)>,
+    /// Battery of the UPS HAT, when there is one
+    pub power: Option<PowerStatus>,
+    /// Until first checked
+    pub clock: Option<ClockStatus>,
+    /// Listed on the clock screen
+    pub timezones: Vec<String>,
+    /// Of the update shown on the updates screen
+    pub release_notes: Option<ReleaseNotes>,
+    /// Long operations, newest first, with the last few finished
+    pub tasks: Vec<TaskInfo>,
+    /// Last text copied, typed by the paste key of the keyboard
+    pub paste_buffer: Option<String>,
+    /// Remote nodes watched, in the configured order
+    pub fleet: Vec<FleetNode>,
 }
 
 #[derive(Clone, Copy)]
@@ -105,12 +384,36 @@ pub trait Screen {
         false
     }
 
-    /// Update the screen's state. Called once per frame *before* display.
+    /// Give the opportunity to let this screen handle a `Command`.
+    /// If `true` is returned, the command won't be processed further.
+    fn handle_command(&mut self, _command: Command) -> bool {
+        false
+    }
+
+    /// Whether a keyboard taking keyboard commands is shown.
+    fn is_typing(&self) -> bool {
+        false
+    }
+
+    /// The selected index and the length of each list shown, e.g. for tests
+    /// to check the selection never leaves it.
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        Vec::new()
+    }
+
+    /// Update the screen's state. Called once per frame *before* display,
+    /// or at its `update_interval`.
     /// Can return a `ScreenAction` to be processed by the `ScreenFlow`.
     fn update(&mut self, _ctx: AppContext) -> ScreenAction {
         ScreenAction::None
     }
 
+    /// How often `update` needs to be called, every frame when `None`.
+    /// Entering the screen and the input it handles call it right away.
+    fn update_interval(&self) -> Option<Duration> {
+        None
+    }
+
     /// Displays this screen. Takes an immutable reference to `self`.
     fn display(&self, ctx: AppContext, f: &mut Frame, area: Rect);
 
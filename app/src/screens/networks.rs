@@ -0,0 +1,138 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::vpn::VpnStatus;
+use crate::widgets::grid::{self, Edge};
+use crate::wifi::SavedNetwork;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Lists the saved WiFi networks in fallback order, then the VPN. X selects
+/// the next network, a long X moves it up, a double A switches to it and a
+/// long A brings the VPN up or down.
+#[derive(Debug, Default)]
+pub struct NetworksScreen {
+    /// Mirrors the system state, highest priority first
+    networks: Vec<SavedNetwork>,
+    /// Mirrors the system state
+    vpn: Option<VpnStatus>,
+    selected: usize,
+    action: Option<ScreenAction>,
+}
+
+impl NetworksScreen {
+    /// The state and address of the VPN, with how to toggle it.
+    fn vpn_lines(&self) -> Vec<Line<'static>> {
+        let Some(vpn) = &self.vpn else {
+            return Vec::new();
+        };
+        let name = vpn.backend.name();
+        let state = if vpn.connected() {
+            let address = vpn
+                .ip
+                .map_or_else(|| tr("connected").to_string(), |ip| ip.to_string());
+            Line::from(format!("{}: {}", name, address)).green()
+        } else if vpn.up {
+            let issue = vpn.issue.clone().unwrap_or_default();
+            Line::from(format!("{}: {}", name, issue)).yellow()
+        } else {
+            Line::from(format!("{}: {}", name, tr("down"))).gray()
+        };
+        let hint = if vpn.up {
+            tr("Hold A: VPN down")
+        } else {
+            tr("Hold A: VPN up")
+        };
+        vec![
+            Line::from(""),
+            state.centered(),
+            Line::from(hint).gray().centered(),
+        ]
+    }
+}
+
+impl Screen for NetworksScreen {
+    fn kind(&self) -> Kind {
+        Kind::Networks
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if let Some(vpn) = &self.vpn
+            && event.id == ButtonId::A
+            && event.press_type == ButtonPress::Long
+        {
+            self.action = Some(ScreenAction::SetVpn(vpn.backend.clone(), !vpn.up));
+            return true;
+        }
+        if self.networks.is_empty() {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = grid::step(self.selected, self.networks.len(), 1, Edge::Wrap);
+            }
+            (ButtonId::X, ButtonPress::Long) if self.selected > 0 => {
+                self.networks.swap(self.selected, self.selected - 1);
+                self.selected -= 1;
+                let names = self.networks.iter().map(|n| n.name.clone()).collect();
+                self.action = Some(ScreenAction::SetNetworkOrder(names));
+            }
+            (ButtonId::A, ButtonPress::Double) => {
+                let name = self.networks[self.selected].name.clone();
+                self.action = Some(ScreenAction::SwitchNetwork(name));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        self.networks = ac.system.saved_networks.clone();
+        self.vpn = ac.system.vpn.clone();
+        self.selected = self.selected.min(self.networks.len().saturating_sub(1));
+        ScreenAction::None
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+
+        if self.networks.is_empty() {
+            lines.push(Line::from(tr("No saved networks")).centered());
+            lines.push(
+                Line::from("Add with `amaru-pi conf wifi add-network`")
+                    .gray()
+                    .centered(),
+            );
+            lines.extend(self.vpn_lines());
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+            return;
+        }
+
+        for (i, network) in self.networks.iter().enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else if network.active {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default()
+            };
+            let marker = if network.active { "●" } else { " " };
+            lines.push(Line::from(Span::styled(
+                format!(" {} {}. {} ", marker, i + 1, network.name),
+                style,
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(tr("X: Next | Hold X: Move up | A A: Switch"))
+                .gray()
+                .centered(),
+        );
+        lines.extend(self.vpn_lines());
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::button::InputEvent;
+use crate::i18n::tr;
+use crate::peers::PeersSnapshot;
+use crate::screens::{AppContext, Kind, Screen};
+use crate::widgets::pager::Pager;
+use ratatui::prelude::*;
+use ratatui::symbols::Marker;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::canvas::{Canvas, Map, MapResolution, Points};
+
+/// Countries listed under the map
+const MAX_COUNTRIES: usize = 4;
+
+/// Plots the approximate location of connected peers on a world map, to spot
+/// a topology relying on a single region. Further pages list all countries
+/// and all peers.
+#[derive(Debug)]
+pub struct PeersMapScreen {
+    pager: Pager,
+}
+
+impl Default for PeersMapScreen {
+    fn default() -> Self {
+        Self {
+            pager: Pager::new(3),
+        }
+    }
+}
+
+fn display_countries(peers: &PeersSnapshot, frame: &mut Frame, area: Rect) {
+    let mut lines = vec![Line::from(tr("Countries")).bold().centered()];
+    lines.extend(
+        peers
+            .countries()
+            .into_iter()
+            .map(|(country, count)| Line::from(format!(" {:<4}{:>4}", country, count))),
+    );
+    if peers.unlocated > 0 {
+        lines.push(Line::from(format!(" ?   {:>4}", peers.unlocated)).gray());
+    }
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn display_peers(peers: &PeersSnapshot, frame: &mut Frame, area: Rect) {
+    let mut lines = vec![Line::from(tr("Peers")).bold().centered()];
+    lines.extend(peers.located.iter().map(|peer| {
+        Line::from(vec![
+            Span::raw(format!(" {:<4}", peer.country)).cyan(),
+            Span::raw(peer.addr.to_string()),
+        ])
+    }));
+    if peers.unlocated > 0 {
+        lines.push(Line::from(format!(" {} not located", peers.unlocated)).gray());
+    }
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn display_map(peers: &PeersSnapshot, frame: &mut Frame, area: Rect) {
+    let [map_area, summary_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(2)]).areas(area);
+
+    let coords: Vec<(f64, f64)> = peers
+        .located
+        .iter()
+        .map(|peer| (peer.longitude, peer.latitude))
+        .collect();
+    let canvas = Canvas::default()
+        .marker(Marker::Braille)
+        .x_bounds([-180.0, 180.0])
+        .y_bounds([-90.0, 90.0])
+        .paint(|ctx| {
+            ctx.draw(&Map {
+                resolution: MapResolution::Low,
+                color: Color::DarkGray,
+            });
+            ctx.layer();
+            ctx.draw(&Points {
+                coords: &coords,
+                color: Color::Yellow,
+            });
+        });
+    frame.render_widget(canvas, map_area);
+
+    let total = peers.located.len() + peers.unlocated;
+    let countries = peers.countries();
+    let mut summary =
+        vec![Line::from(format!("{} peers in {} countries", total, countries.len())).centered()];
+    if peers.unlocated > 0 && peers.located.is_empty() {
+        summary.push(Line::from(tr("Locations unknown")).gray().centered());
+    } else {
+        let top = countries
+            .iter()
+            .take(MAX_COUNTRIES)
+            .map(|(country, count)| format!("{} {}", country, count))
+            .collect::<Vec<_>>()
+            .join("  ");
+        summary.push(Line::from(top).cyan().centered());
+    }
+    frame.render_widget(Paragraph::new(summary), summary_area);
+}
+
+impl Screen for PeersMapScreen {
+    fn kind(&self) -> Kind {
+        Kind::PeersMap
+    }
+
+    fn enter(&mut self) {
+        self.pager.reset();
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        self.pager.handle_input(event)
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let peers = &ac.system.peers;
+        let area = self.pager.render(frame, area);
+        match self.pager.page() {
+            0 => display_map(peers, frame, area),
+            1 => display_countries(peers, frame, area),
+            _ => display_peers(peers, frame, area),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen};
+use crate::util::format_ada;
+use ratatui::prelude::*;
+use ratatui::widgets::{Cell, Paragraph, Row, Table};
+use std::time::Duration;
+
+/// Data older than this is flagged as stale
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Compares a set of watched pools side by side.
+#[derive(Debug, Default)]
+pub struct PoolsScreen {}
+
+pub fn saturation_color(saturation: f64) -> Color {
+    if saturation >= 100.0 {
+        Color::Red
+    } else if saturation >= 90.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+pub fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    if minutes < 60 {
+        format!("{}m ago", minutes)
+    } else {
+        format!("{}h ago", minutes / 60)
+    }
+}
+
+impl Screen for PoolsScreen {
+    fn kind(&self) -> Kind {
+        Kind::Pools
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let snapshot = &ac.system.pools;
+        let [table_area, footer_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+
+        if snapshot.pools.is_empty() {
+            let para = Paragraph::new(vec![
+                Line::from(""),
+                Line::from(tr("No watched pools")),
+                Line::from("Set AMARU_PI_WATCHED_POOLS").style(Style::default().fg(Color::Gray)),
+            ])
+            .alignment(Alignment::Center);
+            frame.render_widget(para, table_area);
+            return;
+        }
+
+        // The active stake only fits on larger displays
+        let comfortable = ac.frame.density.is_comfortable();
+        let mut header = vec!["Pool", "Stake"];
+        let mut widths = vec![Constraint::Fill(1), Constraint::Length(7)];
+        if comfortable {
+            header.push("Active");
+            widths.push(Constraint::Length(7));
+        }
+        header.extend(["Sat.", "Blocks"]);
+        widths.extend([Constraint::Length(7), Constraint::Length(6)]);
+        let header = Row::new(header).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+        let rows = snapshot.pools.iter().map(|pool| {
+            let mut cells = vec![
+                Cell::from(pool.label()),
+                Cell::from(format_ada(pool.live_stake)),
+            ];
+            if comfortable {
+                cells.push(Cell::from(format_ada(pool.active_stake)));
+            }
+            cells.extend([
+                Cell::from(format!("{:.1}%", pool.saturation))
+                    .style(Style::default().fg(saturation_color(pool.saturation))),
+                Cell::from(pool.blocks.to_string()),
+            ]);
+            Row::new(cells).bottom_margin(ac.frame.density.spacing())
+        });
+        let table = Table::new(rows, widths).header(header);
+        frame.render_widget(table, table_area);
+
+        let footer = match snapshot.age() {
+            Some(age) if age > STALE_AFTER => {
+                Line::from(format!("Stale, updated {}", format_age(age))).yellow()
+            }
+            Some(age) => Line::from(format!("Updated {}", format_age(age))).gray(),
+            None => Line::from(tr("Never updated")).gray(),
+        };
+        frame.render_widget(footer.centered(), footer_area);
+    }
+}
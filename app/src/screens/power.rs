@@ -0,0 +1,97 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::pin::Guarded;
+use crate::power::PowerAction;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::widgets::dialog::Dialog;
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+/// Shuts down or reboots the Pi, the node being stopped first, or restarts
+/// amaru-pi. X selects the next action, A asks to confirm it. Unplugging a
+/// syncing node can corrupt its databases.
+#[derive(Debug, Default)]
+pub struct PowerScreen {
+    selected: usize,
+    requested: bool,
+}
+
+impl Screen for PowerScreen {
+    fn kind(&self) -> Kind {
+        Kind::Power
+    }
+
+    fn enter(&mut self) {
+        self.selected = 0;
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % PowerAction::ALL.len();
+            }
+            (ButtonId::A, ButtonPress::Short) => self.requested = true,
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, _ac: AppContext) -> ScreenAction {
+        if std::mem::take(&mut self.requested) {
+            let action = PowerAction::ALL[self.selected];
+            let detail = match action {
+                PowerAction::RestartUi => "The node keeps running",
+                _ => "The node is stopped first",
+            };
+            return ScreenAction::Confirm(Box::new(Dialog::new(
+                tr("Power"),
+                vec![tr(action.question()).to_string(), tr(detail).to_string()],
+                tr(action.label()),
+                Guarded::Power(action),
+            )));
+        }
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        vec![(self.selected, PowerAction::ALL.len())]
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let [list_area, battery_area, hint_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let mut lines = vec![Line::from(""), Line::from(" POWER ").centered()];
+        for (i, action) in PowerAction::ALL.iter().enumerate() {
+            let line = Line::from(format!("  {}  ", tr(action.label()))).centered();
+            lines.push(if i == self.selected {
+                line.black().on_cyan()
+            } else {
+                line
+            });
+        }
+        frame.render_widget(Paragraph::new(lines), list_area);
+
+        if let Some(power) = &ac.system.power {
+            let source = if power.on_battery() {
+                "on battery"
+            } else {
+                "plugged in"
+            };
+            frame.render_widget(
+                Paragraph::new(format!("Battery {}%, {}", power.percent, source))
+                    .gray()
+                    .centered(),
+                battery_area,
+            );
+        }
+        frame.render_widget(
+            Paragraph::new(tr("X: Next | A: Select")).gray().centered(),
+            hint_area,
+        );
+    }
+}
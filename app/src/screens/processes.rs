@@ -0,0 +1,158 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::processes::{self, ProcessInfo};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::format_bytes;
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+/// Lists the node and related processes with their CPU and memory usage, like
+/// `top`. X selects the next one, A shows its journal.
+#[derive(Debug)]
+pub struct ProcessesScreen {
+    /// Listed even when not running
+    watched: Vec<String>,
+    /// Mirrors the system state
+    processes: Vec<ProcessInfo>,
+    selected: usize,
+    /// Process whose journal is shown instead of the list
+    logs_for: Option<ProcessInfo>,
+    action: Option<ScreenAction>,
+}
+
+impl Default for ProcessesScreen {
+    fn default() -> Self {
+        Self {
+            watched: processes::watched(),
+            processes: Vec::new(),
+            selected: 0,
+            logs_for: None,
+            action: None,
+        }
+    }
+}
+
+impl ProcessesScreen {
+    fn fetch_logs(&mut self, process: ProcessInfo) {
+        self.action = Some(ScreenAction::ViewProcessLogs(
+            process.pid,
+            process.unit.clone(),
+        ));
+        self.logs_for = Some(process);
+    }
+
+    fn display_logs(&self, ac: AppContext, process: &ProcessInfo, frame: &mut Frame, area: Rect) {
+        let title = match &process.unit {
+            Some(unit) => unit.clone(),
+            None => format!("{} ({})", process.name, process.pid),
+        };
+        let mut lines = vec![Line::from(title).bold().centered()];
+        let rows = (area.height as usize).saturating_sub(2);
+        match &ac.system.process_logs {
+            Some((pid, logs)) if *pid == process.pid => {
+                if logs.is_empty() {
+                    lines.push(Line::from(tr("No logs")).gray().centered());
+                }
+                let skip = logs.len().saturating_sub(rows);
+                lines.extend(logs.iter().skip(skip).map(|line| Line::from(line.as_str())));
+            }
+            _ => lines.push(Line::from(tr("Loading...")).gray().centered()),
+        }
+        let footer_row = lines.len().max(rows + 1);
+        lines.resize(footer_row, Line::from(""));
+        lines.push(Line::from(tr("X: Back | A: Refresh")).gray().centered());
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+impl Screen for ProcessesScreen {
+    fn kind(&self) -> Kind {
+        Kind::Processes
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if let Some(process) = &self.logs_for {
+            match (event.id, event.press_type) {
+                (ButtonId::X, ButtonPress::Short) => self.logs_for = None,
+                (ButtonId::A, ButtonPress::Short) => self.fetch_logs(process.clone()),
+                _ => return false,
+            }
+            return true;
+        }
+        if self.processes.is_empty() {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % self.processes.len();
+            }
+            (ButtonId::A, ButtonPress::Short) => {
+                self.fetch_logs(self.processes[self.selected].clone());
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        self.processes = ac.system.processes.clone();
+        self.selected = self.selected.min(self.processes.len().saturating_sub(1));
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        vec![(self.selected, self.processes.len())]
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        if let Some(process) = &self.logs_for {
+            self.display_logs(ac, process, frame, area);
+            return;
+        }
+
+        let mut lines = vec![
+            Line::from(format!(
+                "{:>7} {:<15} {} {:>6} {:>9}",
+                "PID", "NAME", "S", "CPU", "RSS"
+            ))
+            .bold(),
+        ];
+        if self.processes.is_empty() {
+            lines.push(Line::from(tr("Sampling...")).gray().centered());
+        }
+        for (i, process) in self.processes.iter().enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else if process.state == 'Z' || process.state == 'D' {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "{:>7} {:<15} {} {:>5.1}% {:>9}",
+                    process.pid,
+                    process.name,
+                    process.state,
+                    process.cpu_percent,
+                    format_bytes(process.rss_bytes)
+                ),
+                style,
+            )));
+        }
+        if !self.processes.is_empty() {
+            for name in &self.watched {
+                if !self.processes.iter().any(|process| process.name == *name) {
+                    lines.push(Line::from(format!("{:>7} {:<15} not running", "-", name)).red());
+                }
+            }
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr("X: Next | A: Logs")).gray().centered());
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
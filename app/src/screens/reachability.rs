@@ -0,0 +1,112 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::port_check::{PortCheck, PortVerdict};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::format_local_time;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Checks whether the P2P port of the node can be reached from the
+/// internet, the most common misconfiguration of relays, and tells what to
+/// do about it. A runs the check.
+#[derive(Debug, Default)]
+pub struct ReachabilityScreen {
+    action: Option<ScreenAction>,
+}
+
+/// What to do about the outcome, a line each.
+fn guidance(check: &PortCheck) -> Vec<String> {
+    let lan_ip = check
+        .lan_ip
+        .map_or_else(|| "the Pi".to_string(), |ip| ip.to_string());
+    match &check.verdict {
+        PortVerdict::NotListening => vec![
+            format!("Nothing accepts connections on port {}.", check.port),
+            "Check that amaru runs and its listen address.".to_string(),
+        ],
+        PortVerdict::Reachable => vec![
+            "Other relays can connect to this node.".to_string(),
+            format!(
+                "Peers reach it at {}:{}.",
+                check
+                    .public_ip
+                    .map_or_else(|| "?".to_string(), |ip| ip.to_string()),
+                check.port
+            ),
+        ],
+        PortVerdict::Unreachable => {
+            let mut lines = vec![
+                format!(
+                    "Forward TCP port {} on your router to {}.",
+                    check.port, lan_ip
+                ),
+                "Also check the firewall of the Pi.".to_string(),
+            ];
+            if !check.by_service {
+                lines.push(
+                    "Routers without NAT loopback fail this check: set AMARU_PI_PORT_CHECK_URL."
+                        .to_string(),
+                );
+            }
+            lines
+        }
+        PortVerdict::Unknown(reason) => {
+            vec!["The check couldn't tell.".to_string(), reason.clone()]
+        }
+    }
+}
+
+impl Screen for ReachabilityScreen {
+    fn kind(&self) -> Kind {
+        Kind::Reachability
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if event.id == ButtonId::A && event.press_type == ButtonPress::Short {
+            self.action = Some(ScreenAction::CheckPort);
+            return true;
+        }
+        false
+    }
+
+    fn update(&mut self, _ac: AppContext) -> ScreenAction {
+        self.action.take().unwrap_or(ScreenAction::None)
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let state = &ac.system.port_check;
+        let mut lines = vec![Line::from(tr("Relay reachability")).bold().centered()];
+        lines.push(Line::from(""));
+
+        match (&state.last, state.checking) {
+            (_, true) => lines.push(Line::from(tr("Checking...")).yellow().centered()),
+            (None, false) => {
+                lines.push(Line::from(tr("Checks that peers can connect to this node")).centered());
+            }
+            (Some(check), false) => {
+                let verdict = match check.verdict {
+                    PortVerdict::Reachable => Line::from(tr("Your relay is reachable")).green(),
+                    PortVerdict::Unreachable => Line::from(tr("Your relay isn't reachable")).red(),
+                    PortVerdict::NotListening => Line::from(tr("The node isn't listening")).red(),
+                    PortVerdict::Unknown(_) => Line::from(tr("Reachability unknown")).yellow(),
+                };
+                lines.push(verdict.bold().centered());
+                lines.push(Line::from(""));
+                lines.extend(guidance(check).into_iter().map(Line::from));
+                lines.push(Line::from(""));
+                lines.push(
+                    Line::from(format!(
+                        "Checked at {}",
+                        format_local_time(check.checked_at).unwrap_or_default()
+                    ))
+                    .gray()
+                    .centered(),
+                );
+            }
+        }
+        if !state.checking {
+            lines.push(Line::from(tr("A: Check")).gray().centered());
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
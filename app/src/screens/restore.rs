@@ -0,0 +1,140 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::restore::{RestoreStatus, Snapshot, SnapshotSource};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::util::{format_bytes, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::{Gauge, Paragraph, Wrap};
+
+/// Snapshots listed at once
+const MAX_SNAPSHOTS: usize = 5;
+
+/// Lists the ledger snapshots on the USB drive and at `AMARU_PI_SNAPSHOT_URL`.
+/// X selects the next one, a double A replaces the ledger with it and holding
+/// X lists them again, e.g. once a drive is plugged in.
+#[derive(Debug, Default)]
+pub struct RestoreScreen {
+    /// Mirror the system state
+    snapshots: Vec<Snapshot>,
+    status: RestoreStatus,
+    selected: usize,
+    action: Option<ScreenAction>,
+}
+
+impl Screen for RestoreScreen {
+    fn kind(&self) -> Kind {
+        Kind::Restore
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.status.is_running() {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Long) => {
+                self.action = Some(ScreenAction::RefreshSnapshots);
+            }
+            (ButtonId::X, ButtonPress::Short) if !self.snapshots.is_empty() => {
+                self.selected = (self.selected + 1) % self.snapshots.len().min(MAX_SNAPSHOTS);
+            }
+            (ButtonId::A, ButtonPress::Double) if !self.snapshots.is_empty() => {
+                self.action = Some(ScreenAction::Restore(self.snapshots[self.selected].clone()));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        self.snapshots = ac.system.snapshots.clone();
+        self.selected = self
+            .selected
+            .min(self.snapshots.len().min(MAX_SNAPSHOTS).saturating_sub(1));
+        self.status = ac.system.restore.clone();
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        vec![(self.selected, self.snapshots.len().min(MAX_SNAPSHOTS))]
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+        if let RestoreStatus::Running(progress) = &self.status {
+            let [text_area, gauge_area] =
+                Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(area);
+            lines.push(Line::from(progress.stage.label()).centered());
+            frame.render_widget(Paragraph::new(lines), text_area);
+            if let Some(ratio) = progress.ratio() {
+                let gauge = Gauge::default()
+                    .ratio(ratio)
+                    .label(format!(
+                        "{} / {}",
+                        format_bytes(progress.done),
+                        format_bytes(progress.total)
+                    ))
+                    .gauge_style(Style::default().fg(Color::Cyan));
+                frame.render_widget(gauge, gauge_area);
+            }
+            return;
+        }
+
+        if self.snapshots.is_empty() {
+            lines.push(Line::from(tr("No snapshot found")).centered());
+            lines.push(
+                Line::from(tr("Plug in a USB drive with a backup"))
+                    .gray()
+                    .centered(),
+            );
+            lines.push(Line::from(tr("Hold X: Refresh")).gray().centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        for (i, snapshot) in self.snapshots.iter().take(MAX_SNAPSHOTS).enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            let source = match snapshot.source {
+                SnapshotSource::Usb => "USB",
+                SnapshotSource::Url(_) => "Web",
+            };
+            let time = snapshot
+                .timestamp
+                .and_then(|timestamp| format_local_time(timestamp).ok())
+                .unwrap_or_else(|| snapshot.name.clone());
+            let size = snapshot.size.map_or_else(|| "?".to_string(), format_bytes);
+            lines.push(Line::from(Span::styled(
+                format!(" {} {} {} ", source, time, size),
+                style,
+            )));
+        }
+
+        lines.push(Line::from(""));
+        match &self.status {
+            RestoreStatus::Done(name) => {
+                lines.push(Line::from(format!("Restored {}", name)).green());
+            }
+            RestoreStatus::Failed(e) => {
+                lines.push(Line::from(format!("Failed: {}", e)).red());
+            }
+            _ => {}
+        }
+        lines.push(
+            Line::from(tr("X: Next | A (double): Restore"))
+                .gray()
+                .centered(),
+        );
+        lines.push(
+            Line::from(tr("Replaces the node's ledger"))
+                .gray()
+                .centered(),
+        );
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::i18n::tr;
+use crate::screens::pools::{format_age, saturation_color};
+use crate::screens::{AppContext, Kind, Screen};
+use crate::util::{format_ada, format_local_time};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use std::time::Duration;
+
+/// Figures older than this are flagged as stale
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// The operated pool at a glance: pledge, stake, saturation and the last
+/// blocks it minted, as last fetched.
+#[derive(Debug, Default)]
+pub struct StakePoolScreen {}
+
+fn figure(label: &str, value: Span<'static>) -> Line<'static> {
+    Line::from(vec![Span::raw(format!("{:<11}", label)).gray(), value])
+}
+
+impl Screen for StakePoolScreen {
+    fn kind(&self) -> Kind {
+        Kind::StakePool
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let pool = &ac.system.stake_pool;
+        let [body_area, footer_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+
+        if pool.fetched_at == 0 {
+            let para = Paragraph::new(vec![
+                Line::from(""),
+                Line::from(tr("No pool configured")),
+                Line::from("Set AMARU_PI_POOL_ID").gray(),
+            ])
+            .alignment(Alignment::Center);
+            frame.render_widget(para, body_area);
+            return;
+        }
+
+        let end = pool.pool_id.len().min(16);
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw(
+                    pool.ticker
+                        .as_ref()
+                        .map_or(String::new(), |t| format!("{} ", t)),
+                )
+                .bold(),
+                Span::raw(format!("{}…", &pool.pool_id[..end])).gray(),
+            ])
+            .centered(),
+            Line::from(""),
+            figure("Pledge", Span::raw(format_ada(pool.pledge))),
+            figure("Live stake", Span::raw(format_ada(pool.live_stake))),
+            figure(
+                "Saturation",
+                Span::raw(format!("{:.1}%", pool.saturation)).fg(saturation_color(pool.saturation)),
+            ),
+            figure("Blocks", Span::raw(pool.blocks.to_string())),
+            Line::from(""),
+        ];
+        if pool.recent_blocks.is_empty() {
+            lines.push(Line::from(tr("No block minted yet")).gray());
+        } else {
+            lines.push(Line::from(tr("Last minted")).cyan().bold());
+            for block in &pool.recent_blocks {
+                lines.push(Line::from(vec![
+                    Span::raw(format_local_time(block.time).unwrap_or_default()),
+                    Span::raw(format!(" #{}", block.height)).gray(),
+                ]));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines), body_area);
+
+        let footer = match pool.age() {
+            Some(age) if age > STALE_AFTER => {
+                Line::from(format!("Stale, updated {}", format_age(age))).yellow()
+            }
+            Some(age) => Line::from(format!("Updated {}", format_age(age))).gray(),
+            None => Line::from(tr("Never updated")).gray(),
+        };
+        frame.render_widget(footer.centered(), footer_area);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::tasks::{TaskInfo, TaskState};
+use crate::util::format_duration;
+use crate::widgets::grid::{self, Edge};
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Tasks listed, newest first
+const MAX_TASKS: usize = 6;
+
+/// Lists the long operations running in the background, with their
+/// progress, and the last ones finished. X selects the next one, a double A
+/// cancels it.
+#[derive(Debug, Default)]
+pub struct TasksScreen {
+    /// Mirrors the system state, newest first
+    tasks: Vec<TaskInfo>,
+    selected: usize,
+    action: Option<ScreenAction>,
+}
+
+impl TasksScreen {
+    fn selected_task(&self) -> Option<&TaskInfo> {
+        self.tasks.get(self.selected)
+    }
+
+    fn can_cancel(&self) -> bool {
+        self.selected_task()
+            .is_some_and(|task| task.is_running() && task.kind.cancellable())
+    }
+}
+
+impl Screen for TasksScreen {
+    fn kind(&self) -> Kind {
+        Kind::Tasks
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.tasks.is_empty() {
+            return false;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                let len = self.tasks.len().min(MAX_TASKS);
+                self.selected = grid::step(self.selected, len, 1, Edge::Wrap);
+            }
+            (ButtonId::A, ButtonPress::Double) if self.can_cancel() => {
+                self.action = Some(ScreenAction::CancelTask(self.tasks[self.selected].id));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        self.tasks = ac.system.tasks.clone();
+        self.selected = self
+            .selected
+            .min(self.tasks.len().min(MAX_TASKS).saturating_sub(1));
+        ScreenAction::None
+    }
+
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        vec![(self.selected, self.tasks.len().min(MAX_TASKS))]
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("")];
+
+        if self.tasks.is_empty() {
+            lines.push(Line::from(tr("No task yet")).centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        for (i, task) in self.tasks.iter().take(MAX_TASKS).enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                match task.state {
+                    TaskState::Running => Style::default(),
+                    TaskState::Done => Style::default().fg(Color::Green),
+                    TaskState::Failed(_) => Style::default().fg(Color::Red),
+                    TaskState::Cancelled => Style::default().fg(Color::DarkGray),
+                }
+            };
+            let status = match (&task.state, task.progress) {
+                (TaskState::Running, Some(fraction)) => format!(
+                    "{:.0}% {}",
+                    fraction * 100.0,
+                    format_duration(task.started.elapsed().as_secs())
+                ),
+                (TaskState::Running, None) => format_duration(task.started.elapsed().as_secs()),
+                (state, _) => tr(state.label()).to_string(),
+            };
+            lines.push(Line::from(Span::styled(
+                format!(" {} {} ", tr(task.kind.label()), status),
+                style,
+            )));
+        }
+
+        if let Some(TaskState::Failed(e)) = self.selected_task().map(|task| &task.state) {
+            lines.push(Line::from(""));
+            lines.push(Line::from(e.as_str()).gray());
+        }
+        lines.push(Line::from(""));
+        let help = if self.can_cancel() {
+            tr("X: Next | A A: Cancel")
+        } else {
+            tr("X: Next")
+        };
+        lines.push(Line::from(help).gray().centered());
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+}
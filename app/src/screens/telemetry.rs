@@ -0,0 +1,64 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+/// Lets the operator opt in or out of usage statistics, and shows exactly
+/// what would be sent.
+#[derive(Debug, Default)]
+pub struct TelemetryScreen {
+    toggle_requested: bool,
+}
+
+impl Screen for TelemetryScreen {
+    fn kind(&self) -> Kind {
+        Kind::Telemetry
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if event.id == ButtonId::A && event.press_type == ButtonPress::Double {
+            self.toggle_requested = true;
+            return true;
+        }
+        false
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if std::mem::take(&mut self.toggle_requested) {
+            return ScreenAction::SetTelemetry(!ac.system.telemetry.enabled);
+        }
+        ScreenAction::None
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let telemetry = &ac.system.telemetry;
+        let mut lines = vec![Line::from("")];
+
+        if telemetry.endpoint.is_none() {
+            lines.push(Line::from(tr("Usage statistics disabled")).centered());
+            lines.push(Line::from("Set AMARU_PI_TELEMETRY_URL").gray().centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        let status = if telemetry.enabled {
+            Span::styled("On", Style::default().fg(Color::Green))
+        } else {
+            Span::styled("Off", Style::default().fg(Color::Yellow))
+        };
+        lines.push(Line::from(vec![Span::raw("Usage statistics: "), status]).centered());
+        lines.push(Line::from(tr("Double press A to toggle")).gray().centered());
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr("Data sent daily:")).cyan());
+
+        let preview = serde_json::to_string_pretty(&telemetry.report()).unwrap_or_default();
+        lines.extend(
+            preview
+                .lines()
+                .map(|line| Line::from(line.to_string()).gray()),
+        );
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::i18n::tr;
+use crate::screens::pools::format_age;
+use crate::screens::{AppContext, Kind, Screen};
+use crate::ticker;
+use crate::util::format_ada;
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use std::time::Duration;
+
+/// Figures older than this are flagged as stale
+const STALE_AFTER: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// The ADA price and figures of the whole network, as last fetched. Not
+/// shown unless listed in `AMARU_PI_SCREENS`.
+#[derive(Debug, Default)]
+pub struct TickerScreen {}
+
+/// When a figure was fetched, in yellow once stale.
+fn freshness(fetched_at: u64) -> Line<'static> {
+    match ticker::age(fetched_at) {
+        Some(age) if age > STALE_AFTER => {
+            Line::from(format!("Stale, updated {}", format_age(age))).yellow()
+        }
+        Some(age) => Line::from(format!("Updated {}", format_age(age))).gray(),
+        None => Line::from(""),
+    }
+}
+
+fn figure(label: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(format!("{:<13}", label)).gray(),
+        Span::raw(value),
+    ])
+}
+
+impl Screen for TickerScreen {
+    fn kind(&self) -> Kind {
+        Kind::Ticker
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let snapshot = &ac.system.ticker;
+        if snapshot.price.is_none() && snapshot.network.is_none() {
+            let para = Paragraph::new(tr("Loading...")).alignment(Alignment::Center);
+            frame.render_widget(para, area);
+            return;
+        }
+
+        let mut lines = Vec::new();
+        if let Some(price) = &snapshot.price {
+            let mut line = vec![
+                Span::raw("ADA ").gray(),
+                Span::raw(format!(
+                    "{:.4} {}",
+                    price.value,
+                    price.currency.to_uppercase()
+                ))
+                .bold(),
+            ];
+            if let Some(change) = price.change_24h {
+                let color = if change < 0.0 {
+                    Color::Red
+                } else {
+                    Color::Green
+                };
+                line.push(Span::raw(format!(" {:+.1}%", change)).fg(color));
+            }
+            lines.push(Line::from(line).centered());
+            lines.push(freshness(price.fetched_at).centered());
+            lines.push(Line::from(""));
+        }
+        if let Some(network) = &snapshot.network {
+            lines.extend([
+                figure("Epoch", network.epoch.to_string()),
+                figure("Block height", network.block_height.to_string()),
+                figure("Epoch blocks", network.epoch_blocks.to_string()),
+                figure("Transactions", network.epoch_transactions.to_string()),
+                figure("Active stake", format_ada(network.active_stake)),
+                freshness(network.fetched_at),
+            ]);
+        }
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::i18n::tr;
 use crate::logs::{JournalReader, extract_new_tip, extract_tip_changed};
 use crate::screens::{AppContext, Kind, ScreenAction};
 use crate::wifi::Connectivity;
@@ -6,14 +7,17 @@ use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::Stylize;
 use ratatui::text::Line;
-use std::time::{Duration, Instant};
+use ratatui::widgets::Paragraph;
+use std::time::Duration;
 use tracing::debug;
 use tui_big_text::{BigText, PixelSize};
 
+/// Cells taken by a character of big text, in quadrants
+const BIG_CHAR_WIDTH: usize = 4;
+
 pub struct TipScreen {
     reader: JournalReader,
     current_slot: Option<(Slot, bool)>,
-    last_refresh: Instant,
 }
 
 impl TipScreen {
@@ -28,20 +32,34 @@ impl Default for TipScreen {
         TipScreen {
             reader,
             current_slot: None,
-            last_refresh: Instant::now(),
         }
     }
 }
 
 fn create_lines<'a>(ac: AppContext, current_slot: Option<(Slot, bool)>) -> (Vec<Line<'a>>, bool) {
-    if ac.system.network_status.connectivity != Connectivity::Full {
-        (vec![Line::from("Not connected")], false)
+    if ac.system.captive_portal.is_some() {
+        (
+            vec![
+                Line::from(tr("Sign in needed")),
+                Line::from(tr("WiFi portal")).gray(),
+            ],
+            false,
+        )
+    } else if ac.system.network_status.connectivity != Connectivity::Full {
+        (vec![Line::from(tr("Not connected"))], false)
     } else if !ac.system.network_status.resolving {
-        (vec![Line::from("Not resolving")], false)
+        let mut lines = vec![Line::from(tr("Not resolving"))];
+        // An IPv4 only peer can't be reached from an IPv6 only network
+        if ac.system.network_status.ipv6 == Some(true)
+            && ac.system.network_status.ipv4 == Some(false)
+        {
+            lines.push(Line::from(tr("IPv6 only")).gray());
+        }
+        (lines, false)
     } else if let Some((current_slot, synced)) = current_slot {
         (
             vec![
-                Line::from("Slot"),
+                Line::from(tr("Slot")),
                 if synced {
                     format!("#{}", current_slot).green().into()
                 } else {
@@ -51,7 +69,7 @@ fn create_lines<'a>(ac: AppContext, current_slot: Option<(Slot, bool)>) -> (Vec<
             false,
         )
     } else {
-        (vec![Line::from("Bootstrapping")], true)
+        (vec![Line::from(tr("Bootstrapping"))], true)
     }
 }
 
@@ -60,33 +78,33 @@ impl crate::screens::Screen for TipScreen {
         Kind::Tip
     }
 
+    fn update_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
+    }
+
     fn update(&mut self, _ac: AppContext) -> ScreenAction {
-        let now = Instant::now();
-        if now - self.last_refresh > Duration::from_secs(1) {
-            self.last_refresh = now;
-            let lines = self.reader.next_lines().unwrap_or_default();
-            if !lines.is_empty() {
-                debug!("TipScreen::update read {} log lines", lines.len());
-            }
+        let lines = self.reader.next_lines().unwrap_or_default();
+        if !lines.is_empty() {
+            debug!("TipScreen::update read {} log lines", lines.len());
+        }
 
-            let new_tips: Vec<_> = lines
+        let new_tips: Vec<_> = lines
+            .iter()
+            .flat_map(|line| extract_new_tip(line))
+            .collect();
+        if let Some(tip) = new_tips.last() {
+            debug!("Found 'new tip' update: {}", tip);
+            // Set to last tip collected
+            self.update_slot(((*tip).into(), true));
+        } else {
+            let tips: Vec<_> = lines
                 .iter()
-                .flat_map(|line| extract_new_tip(line))
+                .flat_map(|line| extract_tip_changed(line))
                 .collect();
-            if let Some(tip) = new_tips.last() {
-                debug!("Found 'new tip' update: {}", tip);
+            if let Some(tip) = tips.last() {
+                debug!("Found 'tip_changed' update: {}", tip);
                 // Set to last tip collected
-                self.update_slot(((*tip).into(), true));
-            } else {
-                let tips: Vec<_> = lines
-                    .iter()
-                    .flat_map(|line| extract_tip_changed(line))
-                    .collect();
-                if let Some(tip) = tips.last() {
-                    debug!("Found 'tip_changed' update: {}", tip);
-                    // Set to last tip collected
-                    self.update_slot(((*tip).into(), false));
-                }
+                self.update_slot(((*tip).into(), false));
             }
         }
         ScreenAction::None
@@ -104,17 +122,33 @@ impl crate::screens::Screen for TipScreen {
             .split(area);
 
         let (lines, details) = create_lines(ac, self.current_slot);
-        let text = BigText::builder()
-            .pixel_size(PixelSize::Quadrant)
-            .centered()
-            .lines(lines)
-            .build();
-
-        frame.render_widget(text, chunks[1]);
+        let widest = lines.iter().map(Line::width).max().unwrap_or_default();
+        if widest * BIG_CHAR_WIDTH <= chunks[1].width as usize {
+            let text = BigText::builder()
+                .pixel_size(PixelSize::Quadrant)
+                .centered()
+                .lines(lines)
+                .build();
+            frame.render_widget(text, chunks[1]);
+        } else {
+            // Narrow displays, e.g. in portrait
+            let lines: Vec<Line> = lines
+                .into_iter()
+                .map(|line| line.bold().centered())
+                .collect();
+            frame.render_widget(Paragraph::new(lines), chunks[1]);
+        }
 
         if details {
             let details_line = Line::from("this may take a couple minutes").centered();
             frame.render_widget(details_line, chunks[2]);
+        } else if let Some((_, false)) = self.current_slot
+            && let Some(eta) = ac.system.sync_eta
+        {
+            let eta_line = Line::from(format!("Synced in {}", eta.label()))
+                .gray()
+                .centered();
+            frame.render_widget(eta_line, chunks[2]);
         }
     }
 }
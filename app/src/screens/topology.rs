@@ -0,0 +1,305 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::inputs::mapping::Command;
+use crate::keyboard::{KeyboardAction, KeyboardWidget};
+use crate::node_config::{self, ConfigStatus, Field, NodeConfig};
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::topology::{self, Reachability};
+use crate::widgets::text_input::TextInput;
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Edits the upstream peers of the node. X selects the next peer, A tests
+/// it and a double A removes it. Peers added from the last row are tested
+/// before being listed. Holding A saves the changes, then restarts the node.
+pub struct TopologyScreen {
+    /// As last saved, or why they couldn't be read
+    saved: Result<Vec<String>, String>,
+    /// Edited list, last row adds to it
+    peers: Vec<String>,
+    selected: usize,
+    input: TextInput,
+    keyboard: KeyboardWidget,
+    adding: bool,
+    /// Peer being added, until its test succeeds or it is added anyway
+    candidate: Option<String>,
+    /// Tested peers without a result yet
+    testing: BTreeSet<String>,
+    /// Mirror the system state
+    tests: BTreeMap<String, Reachability>,
+    status: ConfigStatus,
+    action: Option<ScreenAction>,
+}
+
+impl Default for TopologyScreen {
+    fn default() -> Self {
+        Self {
+            saved: Err("Loading...".to_string()),
+            peers: Vec::new(),
+            selected: 0,
+            input: TextInput::new(),
+            keyboard: KeyboardWidget::default(),
+            adding: false,
+            candidate: None,
+            testing: BTreeSet::new(),
+            tests: BTreeMap::new(),
+            status: ConfigStatus::Idle,
+            action: None,
+        }
+    }
+}
+
+impl TopologyScreen {
+    fn reload(&mut self) {
+        self.saved = NodeConfig::load()
+            .map(|config| {
+                config
+                    .get(Field::Peers)
+                    .map(|value| topology::parse_peers(&value))
+                    .unwrap_or_default()
+            })
+            .map_err(|e| e.to_string());
+        self.peers = self.saved.clone().unwrap_or_default();
+        self.selected = self.selected.min(self.peers.len());
+    }
+
+    fn changed(&self) -> bool {
+        self.saved.as_ref().is_ok_and(|saved| *saved != self.peers)
+    }
+
+    fn test(&mut self, address: String) {
+        self.testing.insert(address.clone());
+        self.action = Some(ScreenAction::TestPeer(address));
+    }
+
+    fn add(&mut self, address: String) {
+        self.candidate = None;
+        self.selected = self.peers.len();
+        self.peers.push(address);
+    }
+
+    fn start_adding(&mut self) {
+        self.input = TextInput::new()
+            .max_length(256)
+            .validator(|value| node_config::validate_address(value.trim()));
+        self.adding = true;
+    }
+
+    fn display_adding(&self, frame: &mut Frame, area: Rect) {
+        let [help_area, input_area, keyboard_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        frame.render_widget(
+            Paragraph::new("host:port of the peer")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Cyan)),
+            help_area,
+        );
+        self.input.render(
+            frame,
+            input_area,
+            "New peer",
+            Style::default().fg(Color::Yellow),
+            true,
+        );
+        self.keyboard.render(frame, keyboard_area);
+    }
+
+    fn reachability(&self, address: &str) -> Span<'static> {
+        if self.testing.contains(address) {
+            return Span::raw("testing...").gray();
+        }
+        match self.tests.get(address) {
+            Some(Reachability::Reachable {
+                connect_ms,
+                version,
+            }) => Span::raw(format!("ok {}ms v{}", connect_ms, version)).green(),
+            Some(Reachability::Refused) => Span::raw("handshake refused").red(),
+            Some(Reachability::Unreachable(e)) => Span::raw(e.clone()).red(),
+            None => Span::raw("untested").gray(),
+        }
+    }
+}
+
+impl Screen for TopologyScreen {
+    fn kind(&self) -> Kind {
+        Kind::Topology
+    }
+
+    fn enter(&mut self) {
+        // Changes not saved yet are kept
+        if !self.changed() {
+            self.reload();
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        self.adding
+    }
+
+    fn handle_command(&mut self, command: Command) -> bool {
+        if !self.adding || !command.is_for_keyboard() {
+            return false;
+        }
+        if let Some(action) = self.keyboard.handle_command(command) {
+            self.input.handle_action(&action);
+        }
+        true
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.adding {
+            match self.keyboard.handle_input(event) {
+                Some(KeyboardAction::Exit) => {
+                    self.adding = false;
+                    let address = self.input.value().trim().to_string();
+                    if self.input.validate().is_ok() && !self.peers.contains(&address) {
+                        self.candidate = Some(address.clone());
+                        self.test(address);
+                    }
+                }
+                Some(action) => {
+                    self.input.handle_action(&action);
+                }
+                None => {}
+            }
+            return true; // Keyboard always captures input
+        }
+        let busy = matches!(self.status, ConfigStatus::Saving | ConfigStatus::Restarting);
+        if self.saved.is_err() || busy {
+            return false;
+        }
+        if let Some(candidate) = self.candidate.clone() {
+            if self.testing.contains(&candidate) {
+                return false;
+            }
+            match (event.id, event.press_type) {
+                (ButtonId::A, ButtonPress::Double) => self.add(candidate),
+                (ButtonId::X, ButtonPress::Long) => self.candidate = None,
+                _ => return false,
+            }
+            return true;
+        }
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                self.selected = (self.selected + 1) % (self.peers.len() + 1);
+            }
+            (ButtonId::X, ButtonPress::Long) if self.changed() => self.reload(),
+            (ButtonId::A, ButtonPress::Short) => match self.peers.get(self.selected) {
+                Some(address) => self.test(address.clone()),
+                None => self.start_adding(),
+            },
+            // The node needs at least one peer
+            (ButtonId::A, ButtonPress::Double)
+                if self.selected < self.peers.len() && self.peers.len() > 1 =>
+            {
+                self.peers.remove(self.selected);
+            }
+            (ButtonId::A, ButtonPress::Long) if self.changed() => {
+                let peers = self.peers.join(",");
+                self.action = Some(ScreenAction::SaveNodeConfig(vec![(Field::Peers, peers)]));
+            }
+            (ButtonId::A, ButtonPress::Long) if self.status == ConfigStatus::Saved => {
+                self.action = Some(ScreenAction::RestartNode);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        self.keyboard
+            .set_paste_buffer(ac.system.paste_buffer.as_deref());
+        if let Some(action) = self.action.take() {
+            return action;
+        }
+        let status = &ac.system.node_config_status;
+        if *status != self.status {
+            if *status == ConfigStatus::Saved {
+                self.reload();
+            }
+            self.status = status.clone();
+        }
+        if self.tests != ac.system.peer_tests {
+            self.tests = ac.system.peer_tests.clone();
+        }
+        self.testing
+            .retain(|address| !self.tests.contains_key(address));
+        if let Some(candidate) = &self.candidate
+            && let Some(Reachability::Reachable { .. }) = self.tests.get(candidate)
+        {
+            self.add(candidate.clone());
+        }
+        ScreenAction::None
+    }
+
+    fn display(&self, _ac: AppContext, frame: &mut Frame, area: Rect) {
+        if self.adding {
+            self.display_adding(frame, area);
+            return;
+        }
+
+        let mut lines = vec![Line::from(tr("Peers")).bold().centered()];
+        let saved = match &self.saved {
+            Ok(saved) => saved,
+            Err(e) => {
+                lines.push(Line::from(e.as_str()).red().centered());
+                frame.render_widget(Paragraph::new(lines), area);
+                return;
+            }
+        };
+
+        for (i, address) in self.peers.iter().enumerate() {
+            let style = if i == self.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else if !saved.contains(address) {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {} ", address), style),
+                Span::raw(" "),
+                self.reachability(address),
+            ]));
+        }
+        let add_style = if self.selected == self.peers.len() {
+            Style::default().fg(Color::Black).bg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        lines.push(Line::from(Span::styled(" + Add peer ", add_style)));
+
+        if let Some(candidate) = &self.candidate {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::raw(format!("{} ", candidate)).yellow(),
+                self.reachability(candidate),
+            ]));
+        }
+
+        lines.push(Line::from(""));
+        let help = match self.status {
+            ConfigStatus::Saving => "Saving...",
+            ConfigStatus::Restarting => "Restarting amaru...",
+            _ if self
+                .candidate
+                .as_ref()
+                .is_some_and(|c| self.testing.contains(c)) =>
+            {
+                "Testing new peer..."
+            }
+            _ if self.candidate.is_some() => "A A: Add anyway | Hold X: Drop",
+            ConfigStatus::Saved if !self.changed() => "Saved | Hold A: Restart amaru",
+            _ if self.changed() => "Hold A: Save | Hold X: Discard",
+            _ if self.selected == self.peers.len() => "X: Next | A: Add",
+            _ => "X: Next | A: Test | A A: Remove",
+        };
+        lines.push(Line::from(help).gray().centered());
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen};
+use crate::systemd::units::Drift;
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+/// Lines of the first modified unit shown
+const MAX_DIFF_LINES: usize = 6;
+
+/// Whether the installed unit files still match what amaru-pi installs,
+/// listing the changed lines of the first one that doesn't.
+#[derive(Debug, Default)]
+pub struct UnitsScreen {}
+
+impl Screen for UnitsScreen {
+    fn kind(&self) -> Kind {
+        Kind::Units
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let units = &ac.system.units;
+        let mut lines = vec![Line::from(tr("Unit files")).bold().centered()];
+        if units.is_empty() {
+            lines.push(Line::from(tr("Checking...")).gray().centered());
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        for unit in units {
+            lines.push(match &unit.drift {
+                None => Line::from(vec![Span::raw("✓ ").green(), Span::raw(unit.name)]),
+                Some(Drift::Missing) => Line::from(vec![
+                    Span::raw("✗ ").red(),
+                    Span::raw(unit.name),
+                    Span::raw(" missing").red(),
+                ]),
+                Some(Drift::Modified(_)) => Line::from(vec![
+                    Span::raw("~ ").yellow(),
+                    Span::raw(unit.name),
+                    Span::raw(" modified").yellow(),
+                ]),
+            });
+        }
+
+        let modified = units.iter().find_map(|unit| match &unit.drift {
+            Some(Drift::Modified(diff)) => Some(diff),
+            _ => None,
+        });
+        if let Some(diff) = modified {
+            lines.push(Line::from(""));
+            for line in diff.iter().take(MAX_DIFF_LINES) {
+                let color = if line.starts_with('+') {
+                    Color::Green
+                } else {
+                    Color::Red
+                };
+                lines.push(Line::from(line.as_str()).fg(color));
+            }
+        }
+
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
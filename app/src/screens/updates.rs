@@ -0,0 +1,243 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::github::{self, RateLimit};
+use crate::i18n::tr;
+use crate::screens::{AppContext, Kind, Screen, ScreenAction};
+use crate::update::{AppUpdateState, read_state_file};
+use crate::update_history::{self, Outcome, ReleaseNotes, UpdateRecord};
+use crate::util::format_local_time;
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+use std::time::Duration;
+
+/// Updates listed, newest first
+const MAX_RECORDS: usize = 4;
+/// Lines of the notes scrolled by X
+const SCROLL_LINES: u16 = 4;
+
+/// The release notes, Markdown markers left out for the small screen.
+fn plain_lines(markdown: &str) -> Vec<String> {
+    markdown
+        .lines()
+        .map(|line| {
+            let line = line.trim_end();
+            let trimmed = line.trim_start();
+            let line = if let Some(item) = trimmed
+                .strip_prefix("- ")
+                .or_else(|| trimmed.strip_prefix("* "))
+            {
+                format!("• {}", item)
+            } else {
+                trimmed.trim_start_matches('#').trim_start().to_string()
+            };
+            line.replace("**", "").replace('`', "")
+        })
+        .collect()
+}
+
+/// Lists the updates applied and shows the release notes of the pending one,
+/// to read before applying it with a double A. X scrolls the notes, holding A
+/// stages the updates found on a USB drive.
+#[derive(Default)]
+pub struct UpdatesScreen {
+    /// Application with an update waiting
+    pending: Option<(String, AppUpdateState)>,
+    /// Newest first
+    history: Vec<UpdateRecord>,
+    /// Of the GitHub API, as of the last check
+    rate_limit: Option<RateLimit>,
+    scroll: u16,
+    /// Lines of the notes shown, to scroll back to the top past them
+    note_lines: usize,
+    apply_requested: bool,
+    usb_requested: bool,
+}
+
+impl UpdatesScreen {
+    fn load(&mut self) {
+        self.pending = read_state_file()
+            .ok()
+            .and_then(|state| update_history::next_pending(&state));
+        self.history = update_history::read().unwrap_or_default();
+        self.history.reverse();
+        self.history.truncate(MAX_RECORDS);
+        self.rate_limit = github::read_rate_limit();
+    }
+
+    fn display_rate_limit(&self, frame: &mut Frame, area: Rect) {
+        let Some(rate_limit) = &self.rate_limit else {
+            return;
+        };
+        let line = if rate_limit.is_exhausted() {
+            Line::from(format!(
+                "  GitHub: limited until {}",
+                format_local_time(rate_limit.reset).unwrap_or_default()
+            ))
+            .red()
+        } else {
+            let token = if rate_limit.authenticated {
+                ""
+            } else {
+                ", no token"
+            };
+            Line::from(format!(
+                "  GitHub: {}/{} left{}",
+                rate_limit.remaining, rate_limit.limit, token
+            ))
+            .gray()
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+
+    /// The notes fetched for the pending update.
+    fn pending_notes<'a>(&self, ac: &AppContext<'a>) -> Option<&'a ReleaseNotes> {
+        let (name, app) = self.pending.as_ref()?;
+        ac.system
+            .release_notes
+            .as_ref()
+            .filter(|notes| notes.app == *name && notes.version == app.pending_version)
+    }
+
+    fn display_history(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from(" HISTORY ").centered()];
+        if self.history.is_empty() {
+            lines.push(Line::from("  No update applied yet").gray());
+        }
+        for record in &self.history {
+            let (outcome, color) = match record.outcome {
+                Outcome::Applied => ("applied", Color::Green),
+                Outcome::RolledBack => ("rolled back", Color::Red),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!(
+                    "  {} {} {} -> {} ",
+                    format_local_time(record.timestamp).unwrap_or_default(),
+                    record.app,
+                    record.from_version,
+                    record.to_version
+                )),
+                Span::styled(outcome, Style::default().fg(color)),
+            ]));
+        }
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+impl Screen for UpdatesScreen {
+    fn kind(&self) -> Kind {
+        Kind::Updates
+    }
+
+    fn enter(&mut self) {
+        self.scroll = 0;
+        self.load();
+    }
+
+    fn handle_input(&mut self, event: InputEvent) -> bool {
+        match (event.id, event.press_type) {
+            (ButtonId::X, ButtonPress::Short) => {
+                let next = self.scroll.saturating_add(SCROLL_LINES);
+                self.scroll = if usize::from(next) < self.note_lines {
+                    next
+                } else {
+                    0
+                };
+            }
+            (ButtonId::A, ButtonPress::Double) if self.pending.is_some() => {
+                self.apply_requested = true;
+            }
+            (ButtonId::A, ButtonPress::Long) => {
+                self.usb_requested = true;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn update_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(5))
+    }
+
+    fn update(&mut self, ac: AppContext) -> ScreenAction {
+        if std::mem::take(&mut self.apply_requested) {
+            return ScreenAction::ApplyUpdate;
+        }
+        if std::mem::take(&mut self.usb_requested) {
+            return ScreenAction::StageOfflineUpdates;
+        }
+        self.load();
+        self.note_lines = self
+            .pending_notes(&ac)
+            .map_or(0, |notes| notes.body.lines().count());
+        ScreenAction::None
+    }
+
+    fn display(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
+        let history_height = self.history.len().max(1) as u16 + 1;
+        let [
+            pending_area,
+            notes_area,
+            history_area,
+            rate_limit_area,
+            hint_area,
+        ] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(0),
+            Constraint::Length(history_height),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        self.display_rate_limit(frame, rate_limit_area);
+
+        let Some((name, app)) = &self.pending else {
+            frame.render_widget(
+                Paragraph::new(vec![
+                    Line::from(""),
+                    Line::from(tr("Up to date")).centered(),
+                ]),
+                pending_area,
+            );
+            self.display_history(frame, history_area);
+            frame.render_widget(
+                Paragraph::new(tr("Hold A: From USB")).gray().centered(),
+                hint_area,
+            );
+            return;
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(" PENDING ").centered(),
+                Line::from(format!(
+                    "  {}: {} -> {}",
+                    name, app.current_version, app.pending_version
+                ))
+                .yellow(),
+            ]),
+            pending_area,
+        );
+
+        let lines: Vec<Line> = match self.pending_notes(&ac) {
+            Some(notes) if notes.body.trim().is_empty() => {
+                vec![Line::from("  No release notes").gray()]
+            }
+            Some(notes) => plain_lines(&notes.body)
+                .into_iter()
+                .map(|line| Line::from(format!("  {}", line)))
+                .collect(),
+            None => vec![Line::from("  Fetching release notes...").gray()],
+        };
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            notes_area,
+        );
+        self.display_history(frame, history_area);
+        frame.render_widget(
+            Paragraph::new(tr("X: Scroll | A A: Apply | Hold A: USB"))
+                .gray()
+                .centered(),
+            hint_area,
+        );
+    }
+}
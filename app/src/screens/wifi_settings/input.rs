@@ -1,6 +1,7 @@
 use super::{ActiveField, Focus, WiFiSettingsScreen};
 use crate::button::{ButtonId, ButtonPress, InputEvent};
 use crate::keyboard::{KeyboardAction, KeyboardContext};
+use crate::widgets::text_input::TextInput;
 
 impl WiFiSettingsScreen {
     pub fn handle_field_navigation(&mut self, event: InputEvent) -> bool {
@@ -26,20 +27,26 @@ impl WiFiSettingsScreen {
             // A double-press activates the current field
             (ButtonId::A, ButtonPress::Double) => match self.active_field {
                 ActiveField::Ssid => {
-                    self.keyboard.set_context(KeyboardContext::Password);
+                    self.keyboard.set_context(KeyboardContext::Normal);
                     self.focus = Focus::Keyboard;
                 }
                 ActiveField::Password => {
-                    self.keyboard.set_context(KeyboardContext::Normal);
+                    self.keyboard.set_context(KeyboardContext::Password);
                     self.focus = Focus::Keyboard;
                 }
                 ActiveField::PasswordVisibility => {
                     // Toggle password visibility
-                    self.password_visible = !self.password_visible;
+                    self.password.set_masked(!self.password.is_masked());
                 }
                 ActiveField::ConnectButton => {
-                    // Trigger the connection popup
-                    self.focus = Focus::ConnectingPopup;
+                    // Trigger the connection popup, or point at the invalid field
+                    if self.ssid.validate().is_err() {
+                        self.active_field = ActiveField::Ssid;
+                    } else if self.password.validate().is_err() {
+                        self.active_field = ActiveField::Password;
+                    } else {
+                        self.focus = Focus::ConnectingPopup;
+                    }
                 }
             },
             _ => {
@@ -50,15 +57,12 @@ impl WiFiSettingsScreen {
     }
 
     pub fn handle_keyboard_input(&mut self, event: InputEvent) {
-        if let Some(action) = self.keyboard.handle_input(event) {
-            match action {
-                KeyboardAction::KeyPress(chars) => self.get_active_string().push_str(&chars),
-                KeyboardAction::Space => self.get_active_string().push(' '),
-                KeyboardAction::Backspace => {
-                    self.get_active_string().pop();
-                }
-                KeyboardAction::Exit => self.focus = Focus::Fields,
+        match self.keyboard.handle_input(event) {
+            Some(KeyboardAction::Exit) => self.focus = Focus::Fields,
+            Some(action) => {
+                self.get_active_input().handle_action(&action);
             }
+            None => {}
         }
     }
 
@@ -77,13 +81,13 @@ impl WiFiSettingsScreen {
         }
     }
 
-    /// Helper to get a mutable reference to the currently active input string.
-    fn get_active_string(&mut self) -> &mut String {
+    /// Helper to get a mutable reference to the currently active input field.
+    pub(super) fn get_active_input(&mut self) -> &mut TextInput {
         match self.active_field {
             ActiveField::Ssid => &mut self.ssid,
             ActiveField::Password => &mut self.password,
             ActiveField::PasswordVisibility | ActiveField::ConnectButton => {
-                panic!("No active string for this field")
+                panic!("No active input for this field")
             }
         }
     }
@@ -1,6 +1,8 @@
 use crate::button::InputEvent;
+use crate::inputs::mapping::Command;
 use crate::keyboard::KeyboardWidget;
 use crate::screens::{AppContext, Kind, Screen, ScreenAction, WifiConnectionStatus};
+use crate::widgets::text_input::TextInput;
 
 mod input;
 mod render;
@@ -21,11 +23,10 @@ pub(super) enum Focus {
 }
 
 pub struct WiFiSettingsScreen {
-    ssid: String,
-    password: String,
+    ssid: TextInput,
+    password: TextInput,
     active_field: ActiveField,
     focus: Focus,
-    password_visible: bool,
     keyboard: KeyboardWidget,
     popup_dismissed: bool,
 }
@@ -33,11 +34,26 @@ pub struct WiFiSettingsScreen {
 impl Default for WiFiSettingsScreen {
     fn default() -> Self {
         Self {
-            ssid: String::new(),
-            password: String::new(),
+            ssid: TextInput::new().max_length(32).validator(|ssid| {
+                if ssid.trim().is_empty() {
+                    Err("SSID required".to_string())
+                } else {
+                    Ok(())
+                }
+            }),
+            // WPA passphrases are 8 to 63 chars, open networks have none
+            password: TextInput::new()
+                .masked(true)
+                .max_length(63)
+                .validator(|password| {
+                    if password.is_empty() || password.chars().count() >= 8 {
+                        Ok(())
+                    } else {
+                        Err("8 characters minimum".to_string())
+                    }
+                }),
             active_field: ActiveField::Ssid,
             focus: Focus::Fields,
-            password_visible: false,
             keyboard: KeyboardWidget::default(),
             popup_dismissed: false,
         }
@@ -60,7 +76,23 @@ impl Screen for WiFiSettingsScreen {
         }
     }
 
+    fn is_typing(&self) -> bool {
+        self.focus == Focus::Keyboard
+    }
+
+    fn handle_command(&mut self, command: Command) -> bool {
+        if self.focus != Focus::Keyboard || !command.is_for_keyboard() {
+            return false;
+        }
+        if let Some(action) = self.keyboard.handle_command(command) {
+            self.get_active_input().handle_action(&action);
+        }
+        true
+    }
+
     fn update(&mut self, ac: AppContext) -> ScreenAction {
+        self.keyboard
+            .set_paste_buffer(ac.system.paste_buffer.as_deref());
         if self.popup_dismissed {
             self.popup_dismissed = false;
             return ScreenAction::ResetWifiConnectionStatus;
@@ -70,11 +102,22 @@ impl Screen for WiFiSettingsScreen {
             && let WifiConnectionStatus::Idle = ac.system.wifi_connection_status
         {
             // Popup is open, but state is Idle. We need to trigger the connection.
-            return ScreenAction::ConnectToWifi(self.ssid.clone(), self.password.clone());
+            return ScreenAction::ConnectToWifi(
+                self.ssid.value().to_string(),
+                self.password.value().to_string(),
+            );
         }
         ScreenAction::None
     }
 
+    fn cursors(&self) -> Vec<(usize, usize)> {
+        if self.focus == Focus::Keyboard {
+            self.keyboard.cursors()
+        } else {
+            Vec::new()
+        }
+    }
+
     fn display(&self, ac: AppContext, frame: &mut ratatui::Frame, area: ratatui::layout::Rect) {
         self.render_layout(ac, frame, area);
     }
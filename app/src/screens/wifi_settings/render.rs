@@ -1,7 +1,9 @@
 use super::{ActiveField, Focus, WiFiSettingsScreen};
 use crate::{
+    i18n::tr,
     screens::{AppContext, WifiConnectionStatus},
     util::centered_rect,
+    widgets::text_input::TextInput,
 };
 use ratatui::{
     Frame,
@@ -49,7 +51,7 @@ impl WiFiSettingsScreen {
     }
 
     fn render_instructions(&self, frame: &mut Frame, area: Rect) {
-        let instruction = Paragraph::new("Enter Wi-Fi credentials for the Pi to connect.")
+        let instruction = Paragraph::new(tr("Enter Wi-Fi credentials for the Pi to connect."))
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Cyan));
         frame.render_widget(instruction, area);
@@ -59,15 +61,17 @@ impl WiFiSettingsScreen {
         let widget = match self.focus {
             Focus::Fields => {
                 let lines = vec![
-                    Line::from("A/X: Change Field").alignment(Alignment::Center),
-                    Line::from("A (double): Activate/Toggle").alignment(Alignment::Center),
+                    Line::from(tr("A/X: Change Field")).alignment(Alignment::Center),
+                    Line::from(tr("A (double): Activate/Toggle")).alignment(Alignment::Center),
                 ];
                 Paragraph::new(lines).alignment(Alignment::Center)
             }
             Focus::Keyboard => {
                 let lines = vec![
-                    Line::from("A/B/X/Y: Move Cursor").alignment(Alignment::Center),
-                    Line::from("A (double): Type | B (double): Backspace")
+                    Line::from(tr("A/B/X/Y: Move Cursor")).alignment(Alignment::Center),
+                    Line::from(tr("A (double): Type | B (double): Backspace"))
+                        .alignment(Alignment::Center),
+                    Line::from(tr("Hold X: Accents | ←/→: Move in text"))
                         .alignment(Alignment::Center),
                 ];
                 Paragraph::new(lines)
@@ -75,7 +79,7 @@ impl WiFiSettingsScreen {
             Focus::ConnectingPopup => {
                 let lines = vec![
                     Line::from("").alignment(Alignment::Center),
-                    Line::from("Press any button to dismiss.").alignment(Alignment::Center),
+                    Line::from(tr("Press any button to dismiss.")).alignment(Alignment::Center),
                 ];
                 Paragraph::new(lines).alignment(Alignment::Center)
             }
@@ -94,16 +98,11 @@ impl WiFiSettingsScreen {
     }
 
     fn render_password_input(&self, frame: &mut Frame, area: Rect) {
-        let password_display = if self.password_visible {
-            self.password.clone()
-        } else {
-            "*".repeat(self.password.len())
-        };
         self.render_text_input(
             frame,
             area,
             "Password",
-            &password_display,
+            &self.password,
             self.active_field == ActiveField::Password,
         );
     }
@@ -115,10 +114,10 @@ impl WiFiSettingsScreen {
     }
 
     fn render_visibility_button(&self, frame: &mut Frame, area: Rect) {
-        let text = if self.password_visible {
-            "Hide"
-        } else {
+        let text = if self.password.is_masked() {
             "Show"
+        } else {
+            "Hide"
         };
 
         let is_active =
@@ -158,7 +157,7 @@ impl WiFiSettingsScreen {
         frame: &mut Frame,
         area: Rect,
         title: &str,
-        text: &str,
+        input: &TextInput,
         is_active: bool,
     ) {
         let style = if is_active && (self.focus == Focus::Fields || self.focus == Focus::Keyboard) {
@@ -166,12 +165,8 @@ impl WiFiSettingsScreen {
         } else {
             Style::default().fg(Color::White)
         };
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .title(title)
-            .style(style);
-        let paragraph = Paragraph::new(text).block(block);
-        frame.render_widget(paragraph, area);
+        let editing = is_active && self.focus == Focus::Keyboard;
+        input.render(frame, area, title, style, editing);
     }
 
     fn render_connecting_popup(&self, ac: AppContext, frame: &mut Frame, area: Rect) {
@@ -194,7 +189,7 @@ impl WiFiSettingsScreen {
             Line::from(""),
             Line::from(Span::styled(text, style)).alignment(Alignment::Center),
             Line::from(""),
-            Line::from("Press any button to dismiss.").alignment(Alignment::Center),
+            Line::from(tr("Press any button to dismiss.")).alignment(Alignment::Center),
         ];
 
         let block = Block::default()
@@ -0,0 +1,122 @@
+use crate::geometry::Geometry;
+use crate::screens::Kind;
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use mousefood::embedded_graphics::Pixel;
+use mousefood::embedded_graphics::draw_target::DrawTarget;
+use mousefood::embedded_graphics::geometry::{OriginDimensions, Size};
+use mousefood::embedded_graphics::pixelcolor::RgbColor;
+use mousefood::{EmbeddedBackend, EmbeddedBackendConfig, prelude::Rgb565};
+use png::{BitDepth, ColorType, Encoder};
+use ratatui::backend::Backend as _;
+use ratatui::buffer::Buffer;
+use std::convert::Infallible;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const DEFAULT_DIR: &str = "/home/pi/screenshots";
+
+/// Pixels drawn by the backend, in memory.
+struct Canvas {
+    size: Size,
+    pixels: Vec<Rgb565>,
+}
+
+impl Canvas {
+    fn new(size: Size) -> Self {
+        Self {
+            size,
+            pixels: vec![Rgb565::BLACK; (size.width * size.height) as usize],
+        }
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y))
+                && x < self.size.width
+                && y < self.size.height
+            {
+                self.pixels[(y * self.size.width + x) as usize] = color;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draws the buffer as the display does, with the same fonts.
+fn render(buffer: &Buffer) -> Result<Canvas> {
+    let geometry = Geometry::from_env();
+    let mut canvas = Canvas::new(geometry.size());
+    let config = EmbeddedBackendConfig {
+        font_regular: geometry.scale.font(),
+        ..Default::default()
+    };
+    let mut backend: EmbeddedBackend<_, Rgb565> = EmbeddedBackend::new(&mut canvas, config);
+    let cells = buffer.content.iter().enumerate().map(|(i, cell)| {
+        let (x, y) = buffer.pos_of(i);
+        (x, y, cell)
+    });
+    backend
+        .draw(cells)
+        .and_then(|_| backend.flush())
+        .map_err(|e| anyhow!("Failed to render the screenshot: {:?}", e))?;
+    drop(backend);
+    Ok(canvas)
+}
+
+/// Encodes the canvas as an RGB PNG.
+fn encode_png(canvas: &Canvas) -> Result<Vec<u8>> {
+    let rgb: Vec<u8> = canvas
+        .pixels
+        .iter()
+        .flat_map(|pixel| {
+            [
+                pixel.r() << 3 | pixel.r() >> 2,
+                pixel.g() << 2 | pixel.g() >> 4,
+                pixel.b() << 3 | pixel.b() >> 2,
+            ]
+        })
+        .collect();
+    let mut data = Vec::new();
+    let mut encoder = Encoder::new(&mut data, canvas.size.width, canvas.size.height);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&rgb)?;
+    writer.finish()?;
+    Ok(data)
+}
+
+/// From `AMARU_PI_SCREENSHOTS_DIR`.
+fn screenshots_dir() -> PathBuf {
+    env::var("AMARU_PI_SCREENSHOTS_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_DIR))
+}
+
+/// Saves what the display shows as a PNG, named after the time and the
+/// screen. Returns its path.
+pub fn save(buffer: &Buffer, screen: Kind) -> Result<PathBuf> {
+    let png = encode_png(&render(buffer)?)?;
+    let dir = screenshots_dir();
+    fs::create_dir_all(&dir)?;
+    let name = format!("{}-{}.png", current_timestamp()?, screen).to_lowercase();
+    let path = dir.join(name);
+    fs::write(&path, png)?;
+    Ok(path)
+}
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result, anyhow};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, File, OpenOptions, Permissions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use tracing::warn;
+
+/// Sensitive values (API tokens, passphrases...) encrypted at rest with
+/// ChaCha20-Poly1305: a version byte, a nonce and the encrypted JSON object of
+/// all secrets by name.
+const SECRETS_FILE_PATH: &str = "/home/pi/.amaru_secrets";
+/// Random key generated on first use, never leaves the device. The encryption
+/// key is derived from it and the machine id, so the secrets file is useless
+/// on another device. Kept next to it, so anyone reading both on the device,
+/// or a copy of `/home/pi` with the machine id, can decrypt them.
+const DEVICE_KEY_PATH: &str = "/home/pi/.amaru_device_key";
+const MACHINE_ID_PATH: &str = "/etc/machine-id";
+/// Bumped whenever the file layout changes
+const FORMAT_VERSION: u8 = 1;
+const NONCE_LENGTH: usize = 12;
+/// Only readable by the owner
+const FILE_MODE: u32 = 0o600;
+
+pub fn get(name: &str) -> Result<Option<String>> {
+    Ok(read_all()?.remove(name))
+}
+
+pub fn set(name: &str, value: &str) -> Result<()> {
+    let mut secrets = read_all()?;
+    secrets.insert(name.to_string(), value.to_string());
+    write_all(&secrets)
+}
+
+/// Returns whether the secret existed.
+pub fn remove(name: &str) -> Result<bool> {
+    let mut secrets = read_all()?;
+    let existed = secrets.remove(name).is_some();
+    if existed {
+        write_all(&secrets)?;
+    }
+    Ok(existed)
+}
+
+pub fn names() -> Result<Vec<String>> {
+    Ok(read_all()?.into_keys().collect())
+}
+
+/// Looks up a secret stored under the name of an environment variable,
+/// falling back to the variable itself so existing setups keep working.
+pub fn get_or_env(name: &str) -> Option<String> {
+    let stored = get(name).unwrap_or_else(|e| {
+        warn!("Failed to read secret {}: {}", name, e);
+        None
+    });
+    stored
+        .or_else(|| env::var(name).ok())
+        .filter(|s| !s.trim().is_empty())
+}
+
+fn read_all() -> Result<BTreeMap<String, String>> {
+    let path = Path::new(SECRETS_FILE_PATH);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let data = fs::read(path)?;
+    let Some((&version, rest)) = data.split_first() else {
+        return Ok(BTreeMap::new());
+    };
+    if version != FORMAT_VERSION {
+        return Err(anyhow!("Unsupported secrets file version {}", version));
+    }
+    if rest.len() < NONCE_LENGTH {
+        return Err(anyhow!("Truncated secrets file"));
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+    let plaintext = cipher()?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Failed to decrypt secrets, was the device key changed?"))?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+fn write_all(secrets: &BTreeMap<String, String>) -> Result<()> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher()?
+        .encrypt(&nonce, serde_json::to_vec(secrets)?.as_slice())
+        .map_err(|_| anyhow!("Failed to encrypt secrets"))?;
+
+    let mut data = vec![FORMAT_VERSION];
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+
+    write_private(SECRETS_FILE_PATH, &data)
+}
+
+fn cipher() -> Result<ChaCha20Poly1305> {
+    let device_key = device_key()?;
+    // Without a machine id the key only depends on the device key
+    let machine_id = fs::read_to_string(MACHINE_ID_PATH).unwrap_or_default();
+    let hkdf = Hkdf::<Sha256>::new(Some(machine_id.trim().as_bytes()), &device_key);
+    let mut key = Key::default();
+    hkdf.expand(b"amaru-pi secrets", &mut key)
+        .map_err(|_| anyhow!("Failed to derive secrets key"))?;
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+fn device_key() -> Result<Vec<u8>> {
+    let path = Path::new(DEVICE_KEY_PATH);
+    if path.exists() {
+        // Written by older versions without restricting its mode
+        if let Err(e) = fs::set_permissions(path, Permissions::from_mode(FILE_MODE)) {
+            warn!("Failed to restrict the device key: {}", e);
+        }
+        return fs::read(path).context("Failed to read device key");
+    }
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    write_private(DEVICE_KEY_PATH, &key).context("Failed to write device key")?;
+    Ok(key.to_vec())
+}
+
+/// Writes a temporary file next to `path` then renames it, so a power cut
+/// leaves either version but never a half written one.
+fn write_private(path: &str, data: &[u8]) -> Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(FILE_MODE)
+        .open(&tmp_path)?;
+    // The mode only applies to new files
+    file.set_permissions(Permissions::from_mode(FILE_MODE))?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    if let Some(dir) = Path::new(path).parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
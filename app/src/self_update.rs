@@ -0,0 +1,307 @@
+use crate::integrity;
+use crate::screens::Kind;
+use crate::update::{self, UpdateState};
+use crate::update_history::{self, Outcome, UpdateRecord};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::{PermissionsExt, symlink};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub const APP_NAME: &str = "amaru-pi";
+/// Same layout as the activation script, `BIN_DIR/amaru-pi` links to a slot
+const BIN_DIR: &str = "/home/pi/bin";
+const SLOTS_DIR: &str = "/home/pi/slots";
+/// Left by the binary handing off to its update, read by the update
+const HANDOFF_FILE_PATH: &str = "/home/pi/.amaru_pi_handoff.json";
+/// How long the new binary must run before it's kept, as the activation
+/// script does for the other services
+pub const HEALTH_CHECK: Duration = Duration::from_secs(30);
+/// systemd restarts a binary that crashes, this many starts without reaching
+/// the health check switches back to the previous one
+const MAX_STARTS: u32 = 3;
+
+/// An update of amaru-pi being tried out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handoff {
+    pub version: String,
+    #[serde(default)]
+    pub source: String,
+    /// Slot to switch back to if the new binary doesn't stay up
+    pub previous_slot: String,
+    /// Screen shown when handing off, shown again by the new binary
+    #[serde(default)]
+    pub screen: Option<String>,
+    #[serde(default)]
+    pub starts: u32,
+}
+
+impl Handoff {
+    pub fn screen_kind(&self) -> Option<Kind> {
+        self.screen.as_deref()?.parse().ok()
+    }
+}
+
+/// Whether amaru-pi itself has an update staged.
+pub fn is_pending(state: &UpdateState) -> bool {
+    state
+        .applications
+        .get(APP_NAME)
+        .is_some_and(|app| !app.pending_version.is_empty() && Path::new(&app.staged_path).is_file())
+}
+
+fn binary_path() -> PathBuf {
+    Path::new(BIN_DIR).join(APP_NAME)
+}
+
+fn slot_path(slot: &str) -> PathBuf {
+    Path::new(SLOTS_DIR).join(APP_NAME).join(slot)
+}
+
+fn active_slot() -> &'static str {
+    match fs::read_link(binary_path()) {
+        Ok(target) if target == slot_path("b") => "b",
+        _ => "a",
+    }
+}
+
+/// Flips the link to `slot` with a rename, so it always points to a binary.
+fn switch_slot(slot: &str) -> Result<()> {
+    let link = Path::new(BIN_DIR).join(format!("{}.link", APP_NAME));
+    if let Err(e) = fs::remove_file(&link)
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        return Err(e.into());
+    }
+    symlink(slot_path(slot), &link)?;
+    fs::rename(&link, binary_path())?;
+    Ok(())
+}
+
+fn install_to_slot(file: &Path, slot: &str) -> Result<()> {
+    let path = slot_path(slot);
+    let tmp_path = path.with_extension("tmp");
+    fs::create_dir_all(Path::new(SLOTS_DIR).join(APP_NAME))?;
+    fs::copy(file, &tmp_path)?;
+    fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o755))?;
+    File::open(&tmp_path)?.sync_all()?;
+    fs::rename(&tmp_path, &path)?;
+    if let Err(e) = integrity::record(&path) {
+        warn!("Failed to record the checksum of {}: {}", path.display(), e);
+    }
+    Ok(())
+}
+
+/// A binary installed before slots existed moves to slot a.
+fn migrate_to_slots() -> Result<()> {
+    let binary = binary_path();
+    if fs::symlink_metadata(&binary).is_ok_and(|m| m.is_file()) {
+        info!("Moving {} to slot a", APP_NAME);
+        install_to_slot(&binary, "a")?;
+        switch_slot("a")?;
+    }
+    Ok(())
+}
+
+/// A binary built for another board, or truncated, fails to run at all.
+fn verify(staged: &Path) -> Result<()> {
+    fs::set_permissions(staged, fs::Permissions::from_mode(0o755))?;
+    let output = Command::new(staged).arg("--version").output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !stdout.contains(APP_NAME) {
+        return Err(anyhow!(
+            "{} --version failed: {}",
+            staged.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Installs the staged amaru-pi in the inactive slot and switches to it. The
+/// running binary keeps going until it restarts into the new one with
+/// [`restart`].
+pub fn install() -> Result<()> {
+    let mut state = update::read_state_file()?;
+    let app = state
+        .applications
+        .get(APP_NAME)
+        .cloned()
+        .ok_or_else(|| anyhow!("No update state for {}", APP_NAME))?;
+    let staged = Path::new(&app.staged_path);
+    verify(staged)?;
+
+    migrate_to_slots()?;
+    let previous_slot = active_slot();
+    let next_slot = if previous_slot == "a" { "b" } else { "a" };
+    install_to_slot(staged, next_slot)?;
+    // Written before switching, a handoff whose previous slot is still
+    // active is from an install that didn't finish
+    write_handoff(&Handoff {
+        version: app.pending_version.clone(),
+        source: app.pending_source.clone(),
+        previous_slot: previous_slot.to_string(),
+        screen: None,
+        starts: 0,
+    })?;
+    switch_slot(next_slot)?;
+    info!(
+        "Installed {} {} in slot {}",
+        APP_NAME, app.pending_version, next_slot
+    );
+    if let Err(e) = fs::remove_file(staged) {
+        warn!("Failed to remove {}: {}", staged.display(), e);
+    }
+
+    // The activation script leaves amaru-pi alone from now on
+    if let Some(app) = state.applications.get_mut(APP_NAME) {
+        app.pending_version.clear();
+        app.pending_source.clear();
+        app.staged_path.clear();
+    }
+    update::write_state_file(&state)
+}
+
+/// Replaces the running process with the newly installed binary, which shows
+/// `screen` first. Only returns on failure, after switching back to the
+/// previous binary.
+pub fn restart(screen: Kind) -> anyhow::Error {
+    let result = read_handoff().and_then(|handoff| {
+        let mut handoff = handoff.ok_or_else(|| anyhow!("No update to restart into"))?;
+        handoff.screen = Some(screen.to_string());
+        write_handoff(&handoff)?;
+        Ok(handoff)
+    });
+    let handoff = match result {
+        Ok(handoff) => handoff,
+        Err(e) => return e,
+    };
+    info!("Restarting into {} {}", APP_NAME, handoff.version);
+    let e = exec_binary();
+    warn!("Failed to exec {}: {}", binary_path().display(), e);
+    if let Err(e) = roll_back(&handoff) {
+        warn!("Failed to roll back {}: {}", APP_NAME, e);
+    }
+    e.into()
+}
+
+/// Same arguments, so the new binary runs the same command.
+fn exec_binary() -> io::Error {
+    Command::new(binary_path())
+        .args(env::args_os().skip(1))
+        .exec()
+}
+
+/// Called at startup. Returns the update this binary was handed off to, if
+/// it is still being tried out. A binary that keeps getting restarted before
+/// passing the health check is replaced by the previous one.
+pub fn resume() -> Option<Handoff> {
+    let mut handoff = match read_handoff() {
+        Ok(handoff) => handoff?,
+        Err(e) => {
+            warn!("Failed to read update handoff: {}", e);
+            return None;
+        }
+    };
+    if active_slot() == handoff.previous_slot {
+        remove_handoff();
+        return None;
+    }
+
+    handoff.starts += 1;
+    if handoff.starts > MAX_STARTS {
+        warn!(
+            "{} {} failed to stay up, rolling back",
+            APP_NAME, handoff.version
+        );
+        match roll_back(&handoff) {
+            Ok(()) => {
+                let e = exec_binary();
+                warn!("Failed to exec the previous {}: {}", APP_NAME, e);
+            }
+            Err(e) => warn!("Failed to roll back {}: {}", APP_NAME, e),
+        }
+        return None;
+    }
+    if let Err(e) = write_handoff(&handoff) {
+        warn!("Failed to write update handoff: {}", e);
+    }
+    Some(handoff)
+}
+
+/// Keeps the new binary once it passed the health check.
+pub fn promote(handoff: &Handoff) -> Result<()> {
+    let mut state = update::read_state_file()?;
+    let app = state.applications.entry(APP_NAME.to_string()).or_default();
+    let record = UpdateRecord::now(
+        APP_NAME,
+        &app.current_version,
+        &handoff.version,
+        &handoff.source,
+        Outcome::Applied,
+    );
+    app.current_version = handoff.version.clone();
+    app.current_source = handoff.source.clone();
+    app.slot = active_slot().to_string();
+    app.rolled_back_version.clear();
+    update::write_state_file(&state)?;
+    remove_handoff();
+    if let Err(e) = update_history::record(record) {
+        warn!("Failed to record the update: {}", e);
+    }
+    info!("Keeping {} {}", APP_NAME, handoff.version);
+    Ok(())
+}
+
+/// Switches back to the previous slot and records the version so the
+/// updater skips it.
+fn roll_back(handoff: &Handoff) -> Result<()> {
+    switch_slot(&handoff.previous_slot)?;
+    let mut state = update::read_state_file()?;
+    let app = state.applications.entry(APP_NAME.to_string()).or_default();
+    app.rolled_back_version = handoff.version.clone();
+    let record = UpdateRecord::now(
+        APP_NAME,
+        &app.current_version,
+        &handoff.version,
+        &handoff.source,
+        Outcome::RolledBack,
+    );
+    update::write_state_file(&state)?;
+    remove_handoff();
+    if let Err(e) = update_history::record(record) {
+        warn!("Failed to record the update: {}", e);
+    }
+    Ok(())
+}
+
+fn read_handoff() -> Result<Option<Handoff>> {
+    let path = Path::new(HANDOFF_FILE_PATH);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&data)?))
+}
+
+fn write_handoff(handoff: &Handoff) -> Result<()> {
+    let tmp_path = format!("{}.tmp", HANDOFF_FILE_PATH);
+    fs::write(&tmp_path, serde_json::to_string(handoff)?)?;
+    File::open(&tmp_path)?.sync_all()?;
+    fs::rename(&tmp_path, HANDOFF_FILE_PATH)?;
+    Ok(())
+}
+
+fn remove_handoff() {
+    if let Err(e) = fs::remove_file(HANDOFF_FILE_PATH)
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        warn!("Failed to remove update handoff: {}", e);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::frame::FrameState;
+use crate::geometry::Geometry;
+use crate::screen_text::buffer_text;
+use crate::screens::{AppContext, Screen, SystemState};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Size;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Renders the screen as a frame of the app does, updated then displayed,
+/// on the default 320x240 display.
+pub fn render(screen: &mut dyn Screen, system: &SystemState) -> Buffer {
+    render_sized(screen, system, Geometry::default().cells())
+}
+
+pub fn render_sized(screen: &mut dyn Screen, system: &SystemState, size: Size) -> Buffer {
+    let frame = FrameState::default();
+    let ctx = AppContext {
+        frame: &frame,
+        system,
+    };
+    // Actions are left to the app
+    let _ = screen.update(ctx);
+    let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))
+        .expect("The test backend can't fail");
+    terminal
+        .draw(|f| screen.display(ctx, f, f.area()))
+        .expect("The test backend can't fail")
+        .buffer
+        .clone()
+}
+
+/// Snapshots of the crate under test, stored with its sources.
+fn snapshot_path(name: &str) -> PathBuf {
+    let dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(dir)
+        .join("snapshots")
+        .join(format!("{}.txt", name))
+}
+
+/// Compares the text of the buffer to the `snapshots/<name>.txt` of the crate
+/// under test. With `AMARU_PI_UPDATE_SNAPSHOTS=1` they are written instead,
+/// to be reviewed and committed, and missing ones fail otherwise.
+pub fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path = snapshot_path(name);
+    let actual = buffer_text(buffer);
+    if env::var("AMARU_PI_UPDATE_SNAPSHOTS").is_ok_and(|v| v == "1") {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).expect("Failed to create the snapshots directory");
+        }
+        fs::write(&path, &actual).expect("Failed to write the snapshot");
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Snapshot {} can't be read ({}), run with AMARU_PI_UPDATE_SNAPSHOTS=1 to write it",
+            path.display(),
+            e
+        )
+    });
+    if actual == expected {
+        return;
+    }
+
+    let mut diff = String::new();
+    let (expected_lines, actual_lines): (Vec<_>, Vec<_>) =
+        (expected.lines().collect(), actual.lines().collect());
+    for row in 0..expected_lines.len().max(actual_lines.len()) {
+        let (expected, actual) = (
+            expected_lines.get(row).copied().unwrap_or_default(),
+            actual_lines.get(row).copied().unwrap_or_default(),
+        );
+        if expected != actual {
+            diff.push_str(&format!("{:>3} - {}\n    + {}\n", row, expected, actual));
+        }
+    }
+    panic!(
+        "Snapshot {} differs, rerun with AMARU_PI_UPDATE_SNAPSHOTS=1 if expected:\n{}",
+        path.display(),
+        diff
+    );
+}
@@ -0,0 +1,301 @@
+use crate::chain::{Network, koios_url};
+use crate::util::current_timestamp;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const CACHE_FILE_PATH: &str = "/home/pi/.amaru_stake_pool_cache.json";
+const DEFAULT_REFRESH_SECS: u64 = 15 * 60; // 15 minutes
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Minted blocks listed on the screen
+const RECENT_BLOCKS: usize = 5;
+
+/// Where the figures of the pool come from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolApi {
+    /// Base URL of a Koios compatible API
+    Koios(String),
+    /// Base URL of a Blockfrost compatible API and its project id
+    Blockfrost { url: String, project_id: String },
+}
+
+#[derive(Clone, Debug)]
+pub struct StakePoolConfig {
+    /// Bech32 id of the operated pool
+    pub pool_id: Option<String>,
+    pub api: PoolApi,
+    pub refresh_interval: Duration,
+}
+
+impl StakePoolConfig {
+    /// Reads `AMARU_PI_POOL_ID` and `AMARU_PI_STAKE_POOL_REFRESH_SECS`.
+    /// Blockfrost is asked instead of Koios when `AMARU_PI_BLOCKFROST_PROJECT_ID`
+    /// is set, at `AMARU_PI_BLOCKFROST_URL` or its public instance for the
+    /// configured network.
+    pub fn from_env() -> Self {
+        let pool_id = env::var("AMARU_PI_POOL_ID")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let api = match env::var("AMARU_PI_BLOCKFROST_PROJECT_ID") {
+            Ok(project_id) if !project_id.trim().is_empty() => PoolApi::Blockfrost {
+                url: env::var("AMARU_PI_BLOCKFROST_URL")
+                    .unwrap_or_else(|_| blockfrost_url(Network::from_env()).to_string()),
+                project_id: project_id.trim().to_string(),
+            },
+            _ => PoolApi::Koios(koios_url()),
+        };
+        let refresh_interval = env::var("AMARU_PI_STAKE_POOL_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_SECS));
+        Self {
+            pool_id,
+            api,
+            refresh_interval,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.pool_id.is_some()
+    }
+}
+
+fn blockfrost_url(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "https://cardano-mainnet.blockfrost.io/api/v0",
+        Network::Preprod => "https://cardano-preprod.blockfrost.io/api/v0",
+        Network::Preview => "https://cardano-preview.blockfrost.io/api/v0",
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MintedBlock {
+    pub height: u64,
+    pub slot: u64,
+    /// UNIX time
+    pub time: u64,
+}
+
+/// The figures of the operated pool, persisted to survive restarts and
+/// network outages.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolSummary {
+    #[serde(default)]
+    pub pool_id: String,
+    #[serde(default)]
+    pub ticker: Option<String>,
+    /// Declared, in lovelace
+    #[serde(default)]
+    pub pledge: u64,
+    #[serde(default)]
+    pub live_stake: u64,
+    /// In percent
+    #[serde(default)]
+    pub saturation: f64,
+    /// Over the lifetime of the pool
+    #[serde(default)]
+    pub blocks: u64,
+    /// Newest first
+    #[serde(default)]
+    pub recent_blocks: Vec<MintedBlock>,
+    #[serde(default)]
+    pub fetched_at: u64,
+}
+
+impl PoolSummary {
+    pub fn age(&self) -> Option<Duration> {
+        if self.fetched_at == 0 {
+            return None;
+        }
+        let now = current_timestamp().ok()?;
+        Some(Duration::from_secs(now.saturating_sub(self.fetched_at)))
+    }
+}
+
+/// Lovelace amounts are strings in both APIs.
+fn lovelace(amount: Option<String>) -> u64 {
+    amount.and_then(|s| s.parse().ok()).unwrap_or(0)
+}
+
+#[derive(Debug, Deserialize)]
+struct KoiosMeta {
+    ticker: Option<String>,
+}
+
+/// Subset of the Koios `pool_info` response.
+#[derive(Debug, Deserialize)]
+struct KoiosPoolInfo {
+    pledge: Option<String>,
+    live_stake: Option<String>,
+    live_saturation: Option<f64>,
+    block_count: Option<u64>,
+    meta_json: Option<KoiosMeta>,
+}
+
+/// Subset of the Koios `pool_blocks` response.
+#[derive(Debug, Deserialize)]
+struct KoiosBlock {
+    block_height: u64,
+    abs_slot: u64,
+    block_time: u64,
+}
+
+async fn fetch_koios(url: &str, pool_id: &str) -> Result<PoolSummary> {
+    let url = url.trim_end_matches('/');
+    let client = reqwest::Client::new();
+    let infos: Vec<KoiosPoolInfo> = client
+        .post(format!("{}/pool_info", url))
+        .timeout(TIMEOUT)
+        .json(&serde_json::json!({ "_pool_bech32_ids": [pool_id] }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let info = infos
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Unknown pool {}", pool_id))?;
+    let blocks: Vec<KoiosBlock> = client
+        .get(format!("{}/pool_blocks", url))
+        .timeout(TIMEOUT)
+        .query(&[
+            ("_pool_bech32", pool_id),
+            ("order", "block_height.desc"),
+            ("limit", &RECENT_BLOCKS.to_string()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(PoolSummary {
+        pool_id: pool_id.to_string(),
+        ticker: info.meta_json.and_then(|m| m.ticker),
+        pledge: lovelace(info.pledge),
+        live_stake: lovelace(info.live_stake),
+        saturation: info.live_saturation.unwrap_or(0.0),
+        blocks: info.block_count.unwrap_or(0),
+        recent_blocks: blocks
+            .into_iter()
+            .map(|block| MintedBlock {
+                height: block.block_height,
+                slot: block.abs_slot,
+                time: block.block_time,
+            })
+            .collect(),
+        fetched_at: current_timestamp()?,
+    })
+}
+
+/// Subset of the Blockfrost `pools/{id}` response.
+#[derive(Debug, Deserialize)]
+struct BlockfrostPool {
+    declared_pledge: Option<String>,
+    live_stake: Option<String>,
+    /// A ratio, not a percentage
+    live_saturation: Option<f64>,
+    blocks_minted: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockfrostMetadata {
+    ticker: Option<String>,
+}
+
+/// Subset of the Blockfrost `blocks/{hash}` response.
+#[derive(Debug, Deserialize)]
+struct BlockfrostBlock {
+    height: Option<u64>,
+    slot: Option<u64>,
+    time: u64,
+}
+
+async fn blockfrost_get<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    project_id: &str,
+) -> Result<T> {
+    Ok(client
+        .get(url)
+        .timeout(TIMEOUT)
+        .header("project_id", project_id)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+async fn fetch_blockfrost(url: &str, project_id: &str, pool_id: &str) -> Result<PoolSummary> {
+    let base = url.trim_end_matches('/');
+    let url = format!("{}/pools/{}", base, pool_id);
+    let client = reqwest::Client::new();
+    let pool: BlockfrostPool = blockfrost_get(&client, &url, project_id).await?;
+    // Pools without metadata answer 404
+    let metadata: Option<BlockfrostMetadata> =
+        blockfrost_get(&client, &format!("{}/metadata", url), project_id)
+            .await
+            .ok();
+    let hashes: Vec<String> = blockfrost_get(
+        &client,
+        &format!("{}/blocks?order=desc&count={}", url, RECENT_BLOCKS),
+        project_id,
+    )
+    .await?;
+    // The list only has hashes
+    let mut recent_blocks = Vec::with_capacity(hashes.len());
+    for hash in hashes {
+        let block: BlockfrostBlock =
+            blockfrost_get(&client, &format!("{}/blocks/{}", base, hash), project_id).await?;
+        recent_blocks.push(MintedBlock {
+            height: block.height.unwrap_or(0),
+            slot: block.slot.unwrap_or(0),
+            time: block.time,
+        });
+    }
+
+    Ok(PoolSummary {
+        pool_id: pool_id.to_string(),
+        ticker: metadata.and_then(|m| m.ticker),
+        pledge: lovelace(pool.declared_pledge),
+        live_stake: lovelace(pool.live_stake),
+        saturation: pool.live_saturation.unwrap_or(0.0) * 100.0,
+        blocks: pool.blocks_minted.unwrap_or(0),
+        recent_blocks,
+        fetched_at: current_timestamp()?,
+    })
+}
+
+/// Fetches the figures of the operated pool from the configured API.
+pub async fn fetch(config: &StakePoolConfig) -> Result<PoolSummary> {
+    let pool_id = config
+        .pool_id
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("No pool id configured"))?;
+    match &config.api {
+        PoolApi::Koios(url) => fetch_koios(url, pool_id).await,
+        PoolApi::Blockfrost { url, project_id } => fetch_blockfrost(url, project_id, pool_id).await,
+    }
+}
+
+pub fn read_cache() -> Result<PoolSummary> {
+    let path = Path::new(CACHE_FILE_PATH);
+    if !path.exists() {
+        return Ok(PoolSummary::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn write_cache(summary: &PoolSummary) -> Result<()> {
+    fs::write(CACHE_FILE_PATH, serde_json::to_string_pretty(summary)?)?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::chain::Network;
+use crate::screens::SystemState;
+use crate::sync_eta::sync_percent;
+use serde_json::{Value, json};
+use std::fs;
+
+/// Name of the Pi, `amaru-pi` when unknown.
+pub fn hostname() -> String {
+    fs::read_to_string("/etc/hostname")
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "amaru-pi".to_string())
+}
+
+/// The state of the node and the Pi pushed to other services, `null` for
+/// what isn't known yet.
+pub fn status_json(system: &SystemState, network: &Network) -> Value {
+    let disk_free = system
+        .storage
+        .latest
+        .as_ref()
+        .filter(|sample| sample.total_bytes > 0)
+        .map(|sample| sample.free_bytes * 100 / sample.total_bytes);
+    json!({
+        "node": format!("{:?}", system.amaru_status.active_state).to_lowercase(),
+        "sync": system
+            .tip
+            .and_then(|tip| sync_percent(network, tip))
+            .map(|percent| (percent * 10.0).round() / 10.0),
+        "tip": system.tip.map(|(slot, _)| slot),
+        "temperature": system.thermal.celsius,
+        "fan": system.thermal.fan_duty,
+        "enclosure_temperature": system.environment.map(|reading| reading.celsius),
+        "humidity": system.environment.and_then(|reading| reading.humidity),
+        "pressure": system.environment.and_then(|reading| reading.hpa),
+        "disk_free": disk_free,
+        "battery": system.power.as_ref().map(|power| power.percent),
+    })
+}
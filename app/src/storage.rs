@@ -0,0 +1,198 @@
+use crate::node_config::{Field, NodeConfig};
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::warn;
+
+const STATE_FILE_PATH: &str = "/home/pi/.amaru_storage_history.json";
+/// amaru's working directory, its databases are in by default
+const BIN_DIR: &str = "/home/pi/bin";
+const DEFAULT_MIN_FREE_PERCENT: u64 = 10;
+/// Keeps a sample an hour over a month
+const MIN_SAMPLE_GAP_SECS: u64 = 60 * 60;
+const RETENTION_SECS: u64 = 30 * 24 * 60 * 60;
+/// Growth is averaged over that long, to smooth out compactions
+const GROWTH_WINDOW_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageSample {
+    pub timestamp: u64,
+    pub chain_bytes: u64,
+    pub ledger_bytes: u64,
+    /// Of the filesystem holding the ledger
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl StorageSample {
+    pub fn db_bytes(&self) -> u64 {
+        self.chain_bytes + self.ledger_bytes
+    }
+
+    pub fn free_percent(&self) -> u64 {
+        (self.free_bytes * 100)
+            .checked_div(self.total_bytes)
+            .unwrap_or(100)
+    }
+
+    /// Below `AMARU_PI_DISK_MIN_FREE_PERCENT` of free space.
+    pub fn is_low(&self) -> bool {
+        let min_free_percent = env::var("AMARU_PI_DISK_MIN_FREE_PERCENT")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(DEFAULT_MIN_FREE_PERCENT);
+        self.free_percent() < min_free_percent
+    }
+}
+
+/// Size of the node's databases over time, to tell when the disk fills up.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageHistory {
+    /// Oldest first, at most one an hour
+    #[serde(default)]
+    pub samples: Vec<StorageSample>,
+    /// Most recent, possibly not kept in `samples`
+    #[serde(default)]
+    pub latest: Option<StorageSample>,
+}
+
+impl StorageHistory {
+    pub fn load() -> Self {
+        read_state().unwrap_or_else(|e| {
+            warn!("Failed to read storage history: {}", e);
+            StorageHistory::default()
+        })
+    }
+
+    pub fn record(&mut self, sample: StorageSample) {
+        self.latest = Some(sample);
+        if self
+            .samples
+            .last()
+            .is_some_and(|last| sample.timestamp < last.timestamp + MIN_SAMPLE_GAP_SECS)
+        {
+            return;
+        }
+        self.samples.push(sample);
+        self.samples
+            .retain(|kept| sample.timestamp.saturating_sub(kept.timestamp) < RETENTION_SECS);
+        self.save();
+    }
+
+    /// Growth of the databases in bytes a day, over the last week.
+    pub fn growth_per_day(&self) -> Option<f64> {
+        let latest = self.latest?;
+        let oldest = self.samples.iter().find(|sample| {
+            latest.timestamp.saturating_sub(sample.timestamp) <= GROWTH_WINDOW_SECS
+        })?;
+        let days = latest.timestamp.saturating_sub(oldest.timestamp) as f64 / 86_400.0;
+        // Too short to tell growth from noise
+        if days < 1.0 {
+            return None;
+        }
+        Some((latest.db_bytes() as f64 - oldest.db_bytes() as f64) / days)
+    }
+
+    /// Days left until the databases fill the disk at their current growth.
+    pub fn days_until_full(&self) -> Option<f64> {
+        let growth = self.growth_per_day().filter(|growth| *growth > 0.0)?;
+        Some(self.latest?.free_bytes as f64 / growth)
+    }
+
+    fn save(&self) {
+        if let Err(e) = write_state(self) {
+            warn!("Failed to write storage history: {}", e);
+        }
+    }
+}
+
+fn read_state() -> Result<StorageHistory> {
+    let path = Path::new(STATE_FILE_PATH);
+    if !path.exists() {
+        return Ok(StorageHistory::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_state(history: &StorageHistory) -> Result<()> {
+    fs::write(STATE_FILE_PATH, serde_json::to_string(history)?)?;
+    Ok(())
+}
+
+/// The database directory as configured, else amaru's default.
+pub fn db_dir(config: Option<&NodeConfig>, field: Field, prefix: &str) -> PathBuf {
+    let network = config
+        .and_then(|config| config.get(Field::Network))
+        .unwrap_or_else(|| "mainnet".to_string());
+    let dir = config
+        .and_then(|config| config.get(field))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.{}.db", prefix, network)));
+    Path::new(BIN_DIR).join(dir)
+}
+
+/// Apparent size of the files under `path`, not following symlinks.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Size and free space of the filesystem holding `path`, in bytes.
+pub fn filesystem_space(path: &Path) -> Result<(u64, u64)> {
+    let output = Command::new("df").arg("-P").arg("-B1").arg(path).output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "df failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Filesystem 1-blocks Used Available Capacity Mounted on
+    let fields: Vec<&str> = stdout
+        .lines()
+        .nth(1)
+        .ok_or_else(|| anyhow!("No filesystem for {}", path.display()))?
+        .split_whitespace()
+        .collect();
+    let parse = |i: usize| -> Result<u64> {
+        fields
+            .get(i)
+            .and_then(|field| field.parse().ok())
+            .ok_or_else(|| anyhow!("Unexpected df output: {}", stdout.trim()))
+    };
+    Ok((parse(1)?, parse(3)?))
+}
+
+pub fn sample() -> Result<StorageSample> {
+    let config = NodeConfig::load().ok();
+    let chain_dir = db_dir(config.as_ref(), Field::ChainDir, "chain");
+    let ledger_dir = db_dir(config.as_ref(), Field::LedgerDir, "ledger");
+    // The ledger may not exist yet, its parent then tells about the disk
+    let disk_path = if ledger_dir.exists() {
+        ledger_dir.as_path()
+    } else {
+        Path::new(BIN_DIR)
+    };
+    let (total_bytes, free_bytes) = filesystem_space(disk_path)?;
+    Ok(StorageSample {
+        timestamp: current_timestamp()?,
+        chain_bytes: dir_size(&chain_dir),
+        ledger_bytes: dir_size(&ledger_dir),
+        free_bytes,
+        total_bytes,
+    })
+}
@@ -0,0 +1,424 @@
+use crate::systemd::{self, ActiveState, EnabledState, ServiceInfo};
+use anyhow::{Result, anyhow};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Started as by `amaru.service`
+const START_SCRIPT: &str = "/home/pi/scripts/start-amaru.sh";
+const WORKING_DIR: &str = "/home/pi/bin";
+const NODE_USER: &str = "pi";
+const NODE_HOME: &str = "/home/pi";
+/// The output of the node, in place of the journal
+const LOG_PATH: &str = "/home/pi/logs/amaru.log";
+/// Beyond which the log is moved to `amaru.log.1`
+const MAX_LOG_BYTES: u64 = 20 * 1024 * 1024;
+/// Lines kept in memory for the screens reading the output of the node
+const MAX_LINES: usize = 2000;
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// Running this long resets the backoff
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Given to the node to exit on `SIGTERM` before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+static SUPERVISOR: OnceLock<Arc<Supervisor>> = OnceLock::new();
+
+/// Limits the node runs under, unset ones are inherited.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// Of its address space
+    pub memory_mb: Option<u64>,
+    pub open_files: Option<u64>,
+    pub nice: Option<i32>,
+}
+
+impl Limits {
+    /// Reads `AMARU_PI_NODE_MEMORY_MB`, `AMARU_PI_NODE_OPEN_FILES` and
+    /// `AMARU_PI_NODE_NICE`.
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            let value = env::var(name).ok()?;
+            match value.trim().parse() {
+                Ok(parsed) => Some(parsed),
+                Err(_) => {
+                    warn!("Invalid {}: {}", name, value);
+                    None
+                }
+            }
+        }
+        Self {
+            memory_mb: var("AMARU_PI_NODE_MEMORY_MB"),
+            open_files: var("AMARU_PI_NODE_OPEN_FILES"),
+            nice: var("AMARU_PI_NODE_NICE"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Whether the node should run, cleared by `stop`
+    wanted: bool,
+    restart_requested: bool,
+    pid: Option<u32>,
+    started_at: Option<Instant>,
+    /// Set while waiting to start the node again after it exited
+    retry_at: Option<Instant>,
+    backoff: Duration,
+    restarts: u32,
+    last_exit: Option<String>,
+    /// Output of the node, the first line being at index `lines_offset`
+    lines: VecDeque<String>,
+    lines_offset: usize,
+}
+
+/// Runs the node as a child of amaru-pi instead of `amaru.service`, for
+/// systems without systemd or to capture its output. It is restarted with
+/// a backoff when it exits, and stopped with amaru-pi.
+pub struct Supervisor {
+    limits: Limits,
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+/// Reads `AMARU_PI_SUPERVISE`, `1` to supervise the node, and starts it.
+/// Does nothing when already started or off, or when `amaru.service` runs
+/// the node, not to run it twice.
+pub fn start_from_env() {
+    let enabled = env::var("AMARU_PI_SUPERVISE")
+        .is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes"));
+    if !enabled || SUPERVISOR.get().is_some() {
+        return;
+    }
+    // Without systemd, there is no unit to ask
+    if let Ok(service) = systemd::get_systemd_service_info("amaru.service")
+        && matches!(
+            service.active_state,
+            ActiveState::Active | ActiveState::Activating
+        )
+    {
+        warn!(
+            "Not supervising the node, amaru.service runs it: disable it with `systemctl disable --now amaru.service`"
+        );
+        return;
+    }
+    let supervisor = Arc::new(Supervisor {
+        limits: Limits::from_env(),
+        state: Mutex::new(State {
+            wanted: true,
+            backoff: MIN_BACKOFF,
+            ..State::default()
+        }),
+        changed: Condvar::new(),
+    });
+    if SUPERVISOR.set(supervisor.clone()).is_err() {
+        return;
+    }
+    info!("Supervising the node, limits {:?}", supervisor.limits);
+    // The node gets SIGTERM once this thread exits, see `command`
+    let spawned = thread::Builder::new()
+        .name("supervisor".to_string())
+        .spawn(move || supervisor.run());
+    if let Err(e) = spawned {
+        warn!("Failed to start the supervisor: {}", e);
+    }
+}
+
+/// The supervisor, when the node is supervised.
+pub fn get() -> Option<&'static Supervisor> {
+    SUPERVISOR.get().map(Arc::as_ref)
+}
+
+/// The supervisor, when `service` is the node and it is supervised.
+pub fn for_service(service: &str) -> Option<&'static Supervisor> {
+    get().filter(|_| matches!(service, "amaru" | "amaru.service"))
+}
+
+impl Supervisor {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Starts the node if stopped, without waiting for a backoff.
+    pub fn start(&self) {
+        let mut state = self.lock();
+        state.wanted = true;
+        state.retry_at = None;
+        state.backoff = MIN_BACKOFF;
+        self.changed.notify_all();
+    }
+
+    /// Stops the node and waits for it to exit.
+    pub fn stop(&self) -> Result<()> {
+        let mut state = self.lock();
+        state.wanted = false;
+        self.changed.notify_all();
+        let (state, timeout) = self
+            .changed
+            .wait_timeout_while(state, STOP_TIMEOUT + Duration::from_secs(5), |state| {
+                state.pid.is_some()
+            })
+            .unwrap_or_else(|e| e.into_inner());
+        if timeout.timed_out() && state.pid.is_some() {
+            return Err(anyhow!("The node didn't stop"));
+        }
+        Ok(())
+    }
+
+    /// Stops the node and starts it again right away.
+    pub fn restart(&self) {
+        let mut state = self.lock();
+        state.wanted = true;
+        state.restart_requested = state.pid.is_some();
+        state.retry_at = None;
+        state.backoff = MIN_BACKOFF;
+        self.changed.notify_all();
+    }
+
+    /// The node as a service, for the screens showing `amaru.service`.
+    pub fn service_info(&self) -> ServiceInfo {
+        let state = self.lock();
+        let (active_state, sub_state) = match (state.pid, state.wanted, &state.retry_at) {
+            (Some(_), _, _) => (ActiveState::Active, "running".to_string()),
+            (None, true, Some(at)) => (
+                ActiveState::Activating,
+                format!(
+                    "restarting in {}s",
+                    at.saturating_duration_since(Instant::now()).as_secs()
+                ),
+            ),
+            (None, true, None) => (ActiveState::Activating, "starting".to_string()),
+            (None, false, _) => (ActiveState::Inactive, "dead".to_string()),
+        };
+        let mut description = format!("Amaru, supervised, {} restarts", state.restarts);
+        if let Some(exit) = &state.last_exit {
+            description.push_str(&format!(", last {}", exit));
+        }
+        ServiceInfo {
+            name: "amaru.service".to_string(),
+            description,
+            active_state,
+            sub_state,
+            enabled_state: EnabledState::Enabled,
+            main_pid: state.pid,
+        }
+    }
+
+    /// Returns the lines the node output after `cursor` and moves it forward.
+    pub fn lines_since(&self, cursor: &mut usize) -> Vec<String> {
+        let state = self.lock();
+        let start = (*cursor).max(state.lines_offset) - state.lines_offset;
+        *cursor = state.lines_offset + state.lines.len();
+        state.lines.iter().skip(start).cloned().collect()
+    }
+
+    fn push_line(&self, line: String) {
+        let mut state = self.lock();
+        if state.lines.len() == MAX_LINES {
+            state.lines.pop_front();
+            state.lines_offset += 1;
+        }
+        state.lines.push_back(line);
+    }
+
+    /// `setpriv` drops to the user of the node and has it terminated when
+    /// the supervisor thread exits, `prlimit` and `nice` apply the limits.
+    fn command(&self) -> Command {
+        let mut command = Command::new("setpriv");
+        command
+            .arg(format!("--reuid={}", NODE_USER))
+            .arg(format!("--regid={}", NODE_USER))
+            .arg("--init-groups")
+            .arg("--pdeathsig=TERM")
+            .arg("--")
+            .arg("prlimit")
+            // Crashes are captured by systemd-coredump, when there is one
+            .arg("--core=unlimited");
+        if let Some(memory_mb) = self.limits.memory_mb {
+            command.arg(format!("--as={}", memory_mb * 1024 * 1024));
+        }
+        if let Some(open_files) = self.limits.open_files {
+            command.arg(format!("--nofile={}", open_files));
+        }
+        command.arg("--");
+        if let Some(nice) = self.limits.nice {
+            command.arg("nice").arg("-n").arg(nice.to_string());
+        }
+        command
+            .arg(START_SCRIPT)
+            .current_dir(WORKING_DIR)
+            .env("HOME", NODE_HOME)
+            .env("USER", NODE_USER)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+
+    fn run(self: Arc<Self>) {
+        let log = Arc::new(Mutex::new(open_log()));
+        loop {
+            {
+                let mut state = self.lock();
+                loop {
+                    let now = Instant::now();
+                    match state.retry_at {
+                        _ if !state.wanted => {}
+                        Some(at) if at > now => {}
+                        _ => break,
+                    }
+                    let wait = state
+                        .retry_at
+                        .filter(|_| state.wanted)
+                        .map_or(POLL_INTERVAL * 10, |at| at.saturating_duration_since(now));
+                    state = self
+                        .changed
+                        .wait_timeout(state, wait)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0;
+                }
+                state.retry_at = None;
+            }
+
+            let exit = match self.command().spawn() {
+                Ok(child) => self.supervise(child, &log),
+                Err(e) => format!("failed to start: {}", e),
+            };
+            warn!("The node exited: {}", exit);
+
+            let mut state = self.lock();
+            let ran = state.started_at.take().map(|at| at.elapsed());
+            state.pid = None;
+            state.last_exit = Some(exit);
+            if std::mem::take(&mut state.restart_requested) || !state.wanted {
+                // Asked for, no backoff
+            } else {
+                if ran.is_some_and(|ran| ran >= STABLE_AFTER) {
+                    state.backoff = MIN_BACKOFF;
+                }
+                state.retry_at = Some(Instant::now() + state.backoff);
+                state.backoff = (state.backoff * 2).min(MAX_BACKOFF);
+                state.restarts += 1;
+            }
+            self.changed.notify_all();
+        }
+    }
+
+    /// Captures the output of the node until it exits, or is stopped or
+    /// restarted. Returns how it exited.
+    fn supervise(self: &Arc<Self>, mut child: Child, log: &Arc<Mutex<Option<File>>>) -> String {
+        {
+            let mut state = self.lock();
+            state.pid = Some(child.id());
+            state.started_at = Some(Instant::now());
+            self.changed.notify_all();
+        }
+        info!("Node started, pid {}", child.id());
+        if let Some(stdout) = child.stdout.take() {
+            self.capture(stdout, log.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.capture(stderr, log.clone());
+        }
+
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => return status.to_string(),
+                Ok(None) => {}
+                Err(e) => return e.to_string(),
+            }
+            let state = self.lock();
+            if !state.wanted || state.restart_requested {
+                drop(state);
+                return terminate(&mut child);
+            }
+            let _ = self
+                .changed
+                .wait_timeout(state, POLL_INTERVAL)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn capture(
+        self: &Arc<Self>,
+        output: impl Read + Send + 'static,
+        log: Arc<Mutex<Option<File>>>,
+    ) {
+        let supervisor = self.clone();
+        let spawned = thread::Builder::new()
+            .name("node-output".to_string())
+            .spawn(move || {
+                for line in BufReader::new(output).lines().map_while(Result::ok) {
+                    write_log(&log, &line);
+                    supervisor.push_line(line);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to capture the output of the node: {}", e);
+        }
+    }
+}
+
+/// Sends `SIGTERM`, then kills the node if still running after the timeout.
+fn terminate(child: &mut Child) -> String {
+    info!("Stopping the node");
+    if let Err(e) = signal::kill(Pid::from_raw(child.id() as i32), Signal::SIGTERM) {
+        warn!("Failed to send SIGTERM to the node: {}", e);
+    }
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(Some(status)) => return format!("stopped, {}", status),
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return e.to_string(),
+        }
+    }
+    warn!("The node didn't exit on SIGTERM, killing it");
+    let _ = child.kill();
+    match child.wait() {
+        Ok(status) => format!("killed, {}", status),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn open_log() -> Option<File> {
+    let path = Path::new(LOG_PATH);
+    if let Some(dir) = path.parent()
+        && let Err(e) = fs::create_dir_all(dir)
+    {
+        warn!("Failed to create {}: {}", dir.display(), e);
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .inspect_err(|e| warn!("Failed to open {}: {}", LOG_PATH, e))
+        .ok()
+}
+
+fn write_log(log: &Mutex<Option<File>>, line: &str) {
+    let mut log = log.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(file) = log.as_mut() else {
+        return;
+    };
+    if file
+        .metadata()
+        .is_ok_and(|meta| meta.len() >= MAX_LOG_BYTES)
+    {
+        let _ = fs::rename(LOG_PATH, format!("{}.1", LOG_PATH));
+        *log = open_log();
+    }
+    if let Some(file) = log.as_mut() {
+        let _ = writeln!(file, "{}", line);
+    }
+}
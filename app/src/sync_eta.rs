@@ -0,0 +1,263 @@
+use crate::chain::{Era, Network};
+use crate::logs::{JournalReader, extract_new_tip, extract_tip_changed};
+use crate::util::{current_timestamp, format_duration};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Sync rates measured on this device, by era
+const STATE_FILE_PATH: &str = "/home/pi/.amaru_sync_rates.json";
+const MODEL_PATH: &str = "/proc/device-tree/model";
+/// Rates are measured over that long
+const RATE_INTERVAL: Duration = Duration::from_secs(60);
+/// Measures the current rate is estimated from, at most and at least
+const MAX_RATES: usize = 15;
+const MIN_RATES: usize = 3;
+/// Weight of a new measure in the rate of its era on this hardware
+const HISTORY_WEIGHT: f64 = 0.1;
+/// Relative uncertainty on the pace of an era synced before on this
+/// hardware, and of one never synced, guessed from the current pace
+const KNOWN_ERA_SPREAD: f64 = 0.2;
+const UNKNOWN_ERA_SPREAD: f64 = 0.5;
+const SAVE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Slots synced per second in each era, on a given hardware class.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncRates {
+    /// Model of the Pi, e.g. `Raspberry Pi 5 Model B`
+    hardware: String,
+    rates: BTreeMap<Era, f64>,
+}
+
+/// How far the node is through the chain, in percent, from its latest tip.
+/// Only 100 once synced.
+pub fn sync_percent(network: &Network, (slot, synced): (u64, bool)) -> Option<f64> {
+    if synced {
+        return Some(100.0);
+    }
+    let tip = network.slot_at(current_timestamp().ok()?).max(1);
+    Some((slot as f64 / tip as f64 * 100.0).min(99.9))
+}
+
+/// Time left until the node is synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncEta {
+    /// Between that many seconds
+    Range(u64, u64),
+    /// At the slowest recent pace, the node doesn't catch up
+    AtLeast(u64),
+    /// Syncing slower than the chain grows
+    NotCatchingUp,
+}
+
+impl SyncEta {
+    pub fn label(&self) -> String {
+        match self {
+            SyncEta::Range(min, max) if format_duration(*min) == format_duration(*max) => {
+                format!("~{}", format_duration(*min))
+            }
+            SyncEta::Range(min, max) => {
+                format!("{} - {}", format_duration(*min), format_duration(*max))
+            }
+            SyncEta::AtLeast(min) => format!("over {}", format_duration(*min)),
+            SyncEta::NotCatchingUp => "not catching up".to_string(),
+        }
+    }
+}
+
+/// The model without its revision, rates being about the same across them.
+fn hardware_class() -> String {
+    let model = fs::read_to_string(MODEL_PATH).unwrap_or_default();
+    let model = model.trim_end_matches('\0');
+    model.split(" Rev ").next().unwrap_or(model).to_string()
+}
+
+fn read_state() -> Result<SyncRates> {
+    let path = Path::new(STATE_FILE_PATH);
+    if !path.exists() {
+        return Ok(SyncRates::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_state(rates: &SyncRates) -> Result<()> {
+    fs::write(STATE_FILE_PATH, serde_json::to_string(rates)?)?;
+    Ok(())
+}
+
+/// Follows the node tip during the initial sync and estimates when it
+/// catches up, from the recent pace and that of the eras left on this
+/// hardware.
+pub struct SyncEstimator {
+    network: Network,
+    reader: JournalReader,
+    last_check: Instant,
+    interval: Duration,
+    /// Tip when the rate being measured started, and when
+    rate_start: Option<(u64, Instant)>,
+    /// Slots per second over the last intervals, oldest first
+    recent: VecDeque<f64>,
+    /// Latest tip, unless synced
+    syncing_at: Option<u64>,
+    /// Latest tip, and whether it was synced
+    tip: Option<(u64, bool)>,
+    history: SyncRates,
+    last_saved: Instant,
+}
+
+impl SyncEstimator {
+    pub fn new(interval: Duration) -> Self {
+        let hardware = hardware_class();
+        let mut history = read_state().unwrap_or_else(|e| {
+            warn!("Failed to read sync rates: {}", e);
+            SyncRates::default()
+        });
+        if history.hardware != hardware {
+            // E.g. the SD card moved to another Pi
+            if !history.rates.is_empty() {
+                info!("Dropping sync rates measured on {}", history.hardware);
+            }
+            history = SyncRates {
+                hardware,
+                rates: BTreeMap::new(),
+            };
+        }
+        Self {
+            network: Network::from_env(),
+            reader: JournalReader::new("amaru.service"),
+            last_check: Instant::now(),
+            interval,
+            rate_start: None,
+            recent: VecDeque::new(),
+            syncing_at: None,
+            tip: None,
+            history,
+            last_saved: Instant::now(),
+        }
+    }
+
+    /// Reads the latest tips. Returns whether any was found.
+    pub fn check(&mut self) -> bool {
+        if self.last_check.elapsed() < self.interval {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let lines = self.reader.next_lines().unwrap_or_default();
+        let tip = lines.iter().rev().find_map(|line| {
+            extract_new_tip(line)
+                .map(|slot| (slot, true))
+                .or_else(|| extract_tip_changed(line).map(|slot| (slot, false)))
+        });
+        let Some((slot, synced)) = tip else {
+            return false;
+        };
+        self.tip = Some((slot, synced));
+        self.observe(slot, synced);
+        true
+    }
+
+    fn observe(&mut self, slot: u64, synced: bool) {
+        if synced {
+            if self.syncing_at.take().is_some() {
+                self.save();
+            }
+            self.rate_start = None;
+            self.recent.clear();
+            return;
+        }
+        self.syncing_at = Some(slot);
+        let now = Instant::now();
+        let Some((start_slot, start)) = self.rate_start else {
+            self.rate_start = Some((slot, now));
+            return;
+        };
+        let elapsed = now - start;
+        if elapsed < RATE_INTERVAL {
+            return;
+        }
+        self.rate_start = Some((slot, now));
+        // After a rollback or a pause of the node, nothing to measure
+        if slot <= start_slot || elapsed > RATE_INTERVAL * 3 {
+            return;
+        }
+
+        let rate = (slot - start_slot) as f64 / elapsed.as_secs_f64();
+        if self.recent.len() == MAX_RATES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(rate);
+        let era = self.network.era_at(slot);
+        self.history
+            .rates
+            .entry(era)
+            .and_modify(|known| *known += HISTORY_WEIGHT * (rate - *known))
+            .or_insert(rate);
+        if self.last_saved.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    fn save(&mut self) {
+        self.last_saved = Instant::now();
+        if let Err(e) = write_state(&self.history) {
+            warn!("Failed to write sync rates: {}", e);
+        }
+    }
+
+    /// Latest slot seen in the logs of the node, and whether it was synced.
+    pub fn tip(&self) -> Option<(u64, bool)> {
+        self.tip
+    }
+
+    /// `None` once synced, or until the pace is known.
+    pub fn eta(&self) -> Option<SyncEta> {
+        let slot = self.syncing_at?;
+        if self.recent.len() < MIN_RATES {
+            return None;
+        }
+        let tip = self.network.slot_at(current_timestamp().ok()?);
+        let mut rates: Vec<f64> = self.recent.iter().copied().collect();
+        rates.sort_by(f64::total_cmp);
+        // Leaves out the odd burst or stall
+        let (slow, fast) = (rates[rates.len() / 5], rates[rates.len() * 4 / 5]);
+        let min = self.time_to_sync(slot, tip, fast, 1.0);
+        let max = self.time_to_sync(slot, tip, slow, -1.0);
+        Some(match (min, max) {
+            (Some(min), Some(max)) => SyncEta::Range(min, max),
+            (Some(min), None) => SyncEta::AtLeast(min),
+            _ => SyncEta::NotCatchingUp,
+        })
+    }
+
+    /// Seconds to sync from `slot` to `tip`, the current era at `rate` and
+    /// the others at their pace on this hardware, made faster for a positive
+    /// `bias` and slower for a negative one by their uncertainty.
+    fn time_to_sync(&self, slot: u64, tip: u64, rate: f64, bias: f64) -> Option<u64> {
+        let current = self.network.era_at(slot);
+        let rate_of = |era: Era| match self.history.rates.get(&era) {
+            _ if era == current => rate,
+            Some(known) => known * (1.0 + bias * KNOWN_ERA_SPREAD),
+            None => rate * (1.0 + bias * UNKNOWN_ERA_SPREAD),
+        };
+        let eras = self.network.slots_by_era(slot, tip);
+        let Some(((last_era, last_slots), before)) = eras.split_last() else {
+            return Some(0);
+        };
+        let earlier: f64 = before
+            .iter()
+            .map(|(era, slots)| *slots as f64 / rate_of(*era))
+            .sum();
+        // The tip moves a slot a second meanwhile
+        let closing = rate_of(*last_era) - 1.0;
+        if closing <= 0.0 {
+            return None;
+        }
+        Some((earlier + (*last_slots as f64 + earlier) / closing) as u64)
+    }
+}
@@ -1,6 +1,9 @@
+use crate::supervisor;
 use std::collections::HashMap;
 use std::process::Command;
 
+pub mod units;
+
 #[derive(Debug, Default, Clone, Copy)]
 pub enum ActiveState {
     Active,
@@ -67,7 +70,11 @@ pub enum ServiceError {
     ParseError(String),
 }
 
+/// The node is described by the supervisor when it runs it.
 pub fn get_systemd_service_info(service_name: &str) -> Result<ServiceInfo, ServiceError> {
+    if let Some(supervisor) = supervisor::for_service(service_name) {
+        return Ok(supervisor.service_info());
+    }
     let output = Command::new("systemctl")
         .arg("show")
         .arg(service_name)
@@ -125,3 +132,76 @@ pub fn get_systemd_service_info(service_name: &str) -> Result<ServiceInfo, Servi
         main_pid,
     })
 }
+
+#[cfg(feature = "display_hat")]
+fn systemctl(verb: &str, service_name: &str) -> anyhow::Result<()> {
+    let status = Command::new("systemctl")
+        .arg(verb)
+        .arg(service_name)
+        .status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "systemctl {} {} exited with {}",
+            verb,
+            service_name,
+            status
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+fn systemctl(verb: &str, _service_name: &str) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "Services can only be {} on the device",
+        match verb {
+            "stop" => "stopped",
+            "start" => "started",
+            _ => "restarted",
+        }
+    ))
+}
+
+/// Shuts the Pi down, or reboots it.
+#[cfg(feature = "display_hat")]
+pub fn power_off(reboot: bool) -> anyhow::Result<()> {
+    let verb = if reboot { "reboot" } else { "poweroff" };
+    let status = Command::new("systemctl").arg(verb).status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("systemctl {} exited with {}", verb, status));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn power_off(_reboot: bool) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "The Pi can only be shut down on the device"
+    ))
+}
+
+/// Restarts a service, e.g. for the node to pick up its new config. The
+/// node is restarted by the supervisor when it runs it, as with stopping
+/// and starting.
+pub fn restart_service(service_name: &str) -> anyhow::Result<()> {
+    if let Some(supervisor) = supervisor::for_service(service_name) {
+        supervisor.restart();
+        return Ok(());
+    }
+    systemctl("restart", service_name)
+}
+
+pub fn stop_service(service_name: &str) -> anyhow::Result<()> {
+    if let Some(supervisor) = supervisor::for_service(service_name) {
+        return supervisor.stop();
+    }
+    systemctl("stop", service_name)
+}
+
+pub fn start_service(service_name: &str) -> anyhow::Result<()> {
+    if let Some(supervisor) = supervisor::for_service(service_name) {
+        supervisor.start();
+        return Ok(());
+    }
+    systemctl("start", service_name)
+}
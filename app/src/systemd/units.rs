@@ -0,0 +1,282 @@
+use anyhow::{Result, anyhow};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::info;
+
+const UNITS_DIR: &str = "/etc/systemd/system";
+
+/// Substituted for `{{NAME}}` in the templates
+const VARIABLES: &[(&str, &str)] = &[("HOME", "/home/pi"), ("USER", "pi")];
+
+const AMARU_SERVICE: &str = r#"[Unit]
+Description=Amaru
+After=network.target local-fs.target
+
+[Service]
+Type=simple
+EnvironmentFile={{HOME}}/amaru.env
+ExecStart={{HOME}}/scripts/start-amaru.sh
+WorkingDirectory={{HOME}}/bin
+Restart=no
+# Crashes are captured by systemd-coredump
+LimitCORE=infinity
+User={{USER}}
+StandardOutput=journal
+StandardError=journal
+
+[Install]
+WantedBy=multi-user.target
+"#;
+
+const AMARU_PI_SERVICE: &str = r#"[Unit]
+Description=Amaru PI
+Requires=splash.service
+After=splash.service
+
+[Service]
+Type=simple
+EnvironmentFile={{HOME}}/amaru.env
+ExecStart={{HOME}}/bin/amaru-pi
+WorkingDirectory={{HOME}}/bin
+Restart=on-failure
+# The watchdog is enabled by amaru-pi itself
+NotifyAccess=main
+RestartSec=5s
+User=root
+StandardOutput=journal
+StandardError=journal
+
+[Install]
+WantedBy=multi-user.target
+"#;
+
+const UPDATER_SERVICE: &str = r#"[Unit]
+Description=Service to periodically check for application updates
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Type=oneshot
+# For AMARU_PI_MANUAL_UPDATES
+EnvironmentFile=-{{HOME}}/amaru.env
+ExecStart={{HOME}}/bin/updater.sh
+User={{USER}}
+"#;
+
+const UPDATER_TIMER: &str = r#"[Unit]
+Description=Run the application updater periodically
+
+[Timer]
+# Run 10 minutes after boot
+OnBootSec=10min
+# And then run every 60 minutes
+OnUnitActiveSec=60m
+Unit=updater.service
+
+[Install]
+WantedBy=timers.target
+"#;
+
+const ACTIVATE_UPDATE_PATH: &str = r#"[Unit]
+Description=Watch for application update requests
+
+[Path]
+# Trigger when this file is created
+PathExists={{HOME}}/.update_requested
+
+[Install]
+WantedBy=multi-user.target
+"#;
+
+const ACTIVATE_UPDATE_SERVICE: &str = r#"[Unit]
+Description=Handle application update activation
+
+[Service]
+Type=oneshot
+# Run as root to have permission to restart services
+User=root
+ExecStart={{HOME}}/bin/activate-update.sh
+"#;
+
+/// Unit files owned by amaru-pi. Local changes belong in drop-ins, e.g.
+/// `systemctl edit amaru.service`, which are left alone.
+pub const UNITS: &[(&str, &str)] = &[
+    ("amaru.service", AMARU_SERVICE),
+    ("amaru-pi.service", AMARU_PI_SERVICE),
+    ("updater.service", UPDATER_SERVICE),
+    ("updater.timer", UPDATER_TIMER),
+    ("activate-update.path", ACTIVATE_UPDATE_PATH),
+    ("activate-update.service", ACTIVATE_UPDATE_SERVICE),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    Missing,
+    /// Lines only in the installed file, prefixed with `+`, then lines only
+    /// in the template, prefixed with `-`
+    Modified(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitStatus {
+    pub name: &'static str,
+    /// `None` when the installed file matches its template
+    pub drift: Option<Drift>,
+}
+
+fn unit_path(name: &str) -> PathBuf {
+    Path::new(UNITS_DIR).join(name)
+}
+
+fn template(name: &str) -> Result<&'static str> {
+    UNITS
+        .iter()
+        .find(|(unit, _)| *unit == name)
+        .map(|(_, template)| *template)
+        .ok_or_else(|| anyhow!("{} isn't managed by amaru-pi", name))
+}
+
+/// The unit file as it should be installed.
+pub fn render(name: &str) -> Result<String> {
+    Ok(VARIABLES
+        .iter()
+        .fold(template(name)?.to_string(), |content, (variable, value)| {
+            content.replace(&format!("{{{{{}}}}}", variable), value)
+        }))
+}
+
+/// Lines that matter to systemd, comments and blank lines aside.
+fn significant_lines(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with(['#', ';']))
+        .collect()
+}
+
+fn diff(expected: &str, actual: &str) -> Vec<String> {
+    let expected = significant_lines(expected);
+    let actual = significant_lines(actual);
+    let added = actual
+        .iter()
+        .filter(|line| !expected.contains(line))
+        .map(|line| format!("+{}", line));
+    let removed = expected
+        .iter()
+        .filter(|line| !actual.contains(line))
+        .map(|line| format!("-{}", line));
+    added.chain(removed).collect()
+}
+
+/// Compares an installed unit file to its template.
+pub fn check(name: &'static str) -> Result<UnitStatus> {
+    let expected = render(name)?;
+    let drift = match fs::read_to_string(unit_path(name)) {
+        Ok(actual) => {
+            let lines = diff(&expected, &actual);
+            (!lines.is_empty()).then_some(Drift::Modified(lines))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Some(Drift::Missing),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(UnitStatus { name, drift })
+}
+
+/// Every managed unit, a unit that can't be read being reported missing.
+pub fn check_all() -> Vec<UnitStatus> {
+    UNITS
+        .iter()
+        .map(|(name, _)| {
+            check(name).unwrap_or(UnitStatus {
+                name,
+                drift: Some(Drift::Missing),
+            })
+        })
+        .collect()
+}
+
+/// Writes the unit file from its template, without reloading systemd.
+pub fn install(name: &str) -> Result<()> {
+    let tmp_path = unit_path(&format!("{}.tmp", name));
+    fs::write(&tmp_path, render(name)?)?;
+    fs::rename(&tmp_path, unit_path(name))?;
+    Ok(())
+}
+
+/// Reinstalls the units that drifted from their template and reloads
+/// systemd. Running services pick the change up on their next restart.
+/// Returns the names of the units reinstalled.
+pub fn repair() -> Result<Vec<&'static str>> {
+    let mut repaired = Vec::new();
+    for status in check_all() {
+        if status.drift.is_some() {
+            info!("Reinstalling {}", status.name);
+            install(status.name)?;
+            repaired.push(status.name);
+        }
+    }
+    if !repaired.is_empty() {
+        daemon_reload()?;
+    }
+    Ok(repaired)
+}
+
+/// Installs the units that are missing, leaving those edited as they are,
+/// without reloading systemd. Returns the names of the units installed.
+pub fn install_missing() -> Result<Vec<&'static str>> {
+    let mut installed = Vec::new();
+    for status in check_all() {
+        if status.drift == Some(Drift::Missing) {
+            info!("Installing {}", status.name);
+            install(status.name)?;
+            installed.push(status.name);
+        }
+    }
+    Ok(installed)
+}
+
+/// Sets the `ExecStart=` of an installed unit to the one of its template,
+/// keeping its other lines, without reloading systemd. Returns whether it
+/// changed.
+pub fn fix_exec_start(name: &str) -> Result<bool> {
+    let expected = render(name)?;
+    let Some(exec_start) = expected
+        .lines()
+        .find(|line| line.trim().starts_with("ExecStart="))
+    else {
+        return Ok(false);
+    };
+    let actual = match fs::read_to_string(unit_path(name)) {
+        Ok(actual) => actual,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if actual.lines().any(|line| line.trim() == exec_start) {
+        return Ok(false);
+    }
+    info!("Setting {} of {}", exec_start, name);
+    let patched: Vec<&str> = actual
+        .lines()
+        .map(|line| {
+            if line.trim().starts_with("ExecStart=") {
+                exec_start
+            } else {
+                line
+            }
+        })
+        .collect();
+    let tmp_path = unit_path(&format!("{}.tmp", name));
+    fs::write(&tmp_path, patched.join("\n") + "\n")?;
+    fs::rename(&tmp_path, unit_path(name))?;
+    Ok(true)
+}
+
+pub fn daemon_reload() -> Result<()> {
+    let status = Command::new("systemctl").arg("daemon-reload").status()?;
+    if !status.success() {
+        return Err(anyhow!("systemctl daemon-reload exited with {}", status));
+    }
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use crate::app::AppActionComplete;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use tokio::sync::{Notify, mpsc};
+
+/// Finished tasks still listed, the oldest are forgotten
+pub const MAX_FINISHED: usize = 5;
+
+pub type TaskId = u64;
+
+/// The long operations run as tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Backup,
+    Restore,
+    Diagnostics,
+    OfflineUpdate,
+    CoredumpExport,
+}
+
+impl TaskKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskKind::Backup => "Backup",
+            TaskKind::Restore => "Restore",
+            TaskKind::Diagnostics => "Support bundle",
+            TaskKind::OfflineUpdate => "USB update",
+            TaskKind::CoredumpExport => "Crash export",
+        }
+    }
+
+    /// Whether it stops when cancelled. A restore only does while
+    /// downloading or waiting for another operation, the ledger would be
+    /// left half extracted otherwise.
+    pub fn cancellable(&self) -> bool {
+        matches!(self, TaskKind::Backup | TaskKind::Restore)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl TaskState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskState::Running => "Running",
+            TaskState::Done => "Done",
+            TaskState::Failed(_) => "Failed",
+            TaskState::Cancelled => "Cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    pub id: TaskId,
+    pub kind: TaskKind,
+    pub state: TaskState,
+    /// From 0 to 1, once known
+    pub progress: Option<f64>,
+    pub started: Instant,
+}
+
+impl TaskInfo {
+    pub fn is_running(&self) -> bool {
+        self.state == TaskState::Running
+    }
+}
+
+#[derive(Debug, Default)]
+struct Cancellation {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// Handed to a task to report its progress and learn it was cancelled.
+#[derive(Debug, Clone)]
+pub struct TaskHandle {
+    id: TaskId,
+    tx: mpsc::Sender<AppActionComplete>,
+    cancellation: Arc<Cancellation>,
+}
+
+impl TaskHandle {
+    /// `fraction` from 0 to 1. Dropped when the UI lags behind.
+    pub fn progress(&self, fraction: f64) {
+        let _ = self
+            .tx
+            .try_send(AppActionComplete::TaskProgress(self.id, fraction));
+    }
+
+    /// For blocking work to check between steps.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Resolves once cancelled.
+    pub async fn cancelled(&self) {
+        let notified = self.cancellation.notify.notified();
+        tokio::pin!(notified);
+        // Registered before checking, not to miss a cancellation in between
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Runs `future`, dropped when cancelled first.
+    pub async fn unless_cancelled<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        tokio::select! {
+            result = future => result,
+            () = self.cancelled() => Err(anyhow!("Cancelled")),
+        }
+    }
+}
+
+/// Runs long operations in the background, out of the render loop, and
+/// keeps what is needed to cancel them. Their progress and end are sent
+/// back as `AppActionComplete`s.
+#[derive(Debug, Default)]
+pub struct Tasks {
+    last_id: TaskId,
+    running: HashMap<TaskId, Arc<Cancellation>>,
+}
+
+impl Tasks {
+    /// Spawns `task`, then sends what `complete` makes of its result, an
+    /// error once cancelled.
+    pub fn spawn<T, Fut>(
+        &mut self,
+        kind: TaskKind,
+        tx: mpsc::Sender<AppActionComplete>,
+        task: impl FnOnce(TaskHandle) -> Fut,
+        complete: impl FnOnce(Result<T>) -> AppActionComplete + Send + 'static,
+    ) -> TaskInfo
+    where
+        T: Send + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        self.last_id += 1;
+        let id = self.last_id;
+        let cancellation = Arc::new(Cancellation::default());
+        self.running.insert(id, cancellation.clone());
+        let handle = TaskHandle {
+            id,
+            tx,
+            cancellation,
+        };
+        let future = task(handle.clone());
+
+        tokio::spawn(async move {
+            let result = future.await;
+            let state = match &result {
+                Ok(_) => TaskState::Done,
+                Err(_) if handle.is_cancelled() => TaskState::Cancelled,
+                Err(e) => TaskState::Failed(e.to_string()),
+            };
+            let _ = handle
+                .tx
+                .send(AppActionComplete::TaskEnded(id, state))
+                .await;
+            let _ = handle.tx.send(complete(result)).await;
+        });
+        TaskInfo {
+            id,
+            kind,
+            state: TaskState::Running,
+            progress: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Asks the task to stop, which it does at its next check.
+    pub fn cancel(&self, id: TaskId) {
+        if let Some(cancellation) = self.running.get(&id) {
+            cancellation.cancelled.store(true, Ordering::Relaxed);
+            cancellation.notify.notify_waiters();
+        }
+    }
+
+    pub fn finished(&mut self, id: TaskId) {
+        self.running.remove(&id);
+    }
+}
+
+/// Records the end of a task, forgetting the oldest finished ones.
+pub fn record_end(tasks: &mut Vec<TaskInfo>, id: TaskId, state: TaskState) {
+    if let Some(task) = tasks.iter_mut().find(|task| task.id == id) {
+        task.state = state;
+    }
+    let mut finished = 0;
+    // Newest first
+    tasks.retain(|task| {
+        if task.is_running() {
+            return true;
+        }
+        finished += 1;
+        finished <= MAX_FINISHED
+    });
+}
@@ -0,0 +1,140 @@
+use crate::chain::Network;
+use crate::screens::Kind;
+use crate::util::current_timestamp;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tracing::warn;
+
+const STATE_FILE_PATH: &str = "/home/pi/.amaru_telemetry.json";
+const REPORT_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Opt-in usage statistics. Nothing is collected until the operator enables
+/// it on the device, and only aggregate counters are kept: no identifiers,
+/// addresses or free-form messages.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Telemetry {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Number of times each screen was shown
+    #[serde(default)]
+    screen_views: BTreeMap<String, u64>,
+    /// Number of failures per subsystem
+    #[serde(default)]
+    errors: BTreeMap<String, u64>,
+    #[serde(default)]
+    period_start: u64,
+    /// Where reports are sent, from `AMARU_PI_TELEMETRY_URL`
+    #[serde(skip)]
+    pub endpoint: Option<String>,
+}
+
+/// What is sent to the endpoint, also shown on-device before opting in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TelemetryReport {
+    pub version: String,
+    pub network: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub screen_views: BTreeMap<String, u64>,
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl Telemetry {
+    /// Loads the persisted counters and the endpoint configuration.
+    pub fn load() -> Self {
+        let mut telemetry = read_state().unwrap_or_else(|e| {
+            warn!("Failed to read telemetry state: {}", e);
+            Telemetry::default()
+        });
+        telemetry.endpoint = env::var("AMARU_PI_TELEMETRY_URL")
+            .ok()
+            .filter(|s| !s.trim().is_empty());
+        telemetry
+    }
+
+    /// Enabling starts a fresh period, disabling drops everything collected.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled && self.endpoint.is_some();
+        self.reset();
+        self.save();
+    }
+
+    pub fn record_screen_view(&mut self, kind: Kind) {
+        if self.enabled {
+            *self.screen_views.entry(kind.to_string()).or_default() += 1;
+            self.save();
+        }
+    }
+
+    /// Counts a failure of the given subsystem, e.g. `wifi`.
+    pub fn record_error(&mut self, category: &str) {
+        if self.enabled {
+            *self.errors.entry(category.to_string()).or_default() += 1;
+            self.save();
+        }
+    }
+
+    pub fn report(&self) -> TelemetryReport {
+        TelemetryReport {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            network: format!("{:?}", Network::from_env()).to_lowercase(),
+            period_start: self.period_start,
+            period_end: current_timestamp().unwrap_or(0),
+            screen_views: self.screen_views.clone(),
+            errors: self.errors.clone(),
+        }
+    }
+
+    pub fn is_report_due(&self) -> bool {
+        let now = current_timestamp().unwrap_or(0);
+        self.enabled && now.saturating_sub(self.period_start) >= REPORT_INTERVAL.as_secs()
+    }
+
+    /// Starts a new period once a report has been delivered.
+    pub fn mark_reported(&mut self) {
+        self.reset();
+        self.save();
+    }
+
+    fn reset(&mut self) {
+        self.screen_views.clear();
+        self.errors.clear();
+        self.period_start = current_timestamp().unwrap_or(0);
+    }
+
+    fn save(&self) {
+        if let Err(e) = write_state(self) {
+            warn!("Failed to write telemetry state: {}", e);
+        }
+    }
+}
+
+fn read_state() -> Result<Telemetry> {
+    let path = Path::new(STATE_FILE_PATH);
+    if !path.exists() {
+        return Ok(Telemetry::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_state(telemetry: &Telemetry) -> Result<()> {
+    fs::write(STATE_FILE_PATH, serde_json::to_string_pretty(telemetry)?)?;
+    Ok(())
+}
+
+pub async fn send_report(endpoint: &str, report: &TelemetryReport) -> Result<()> {
+    reqwest::Client::new()
+        .post(endpoint)
+        .timeout(Duration::from_secs(30))
+        .json(report)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,314 @@
+use anyhow::{Result, anyhow};
+use std::env;
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+const TEMPERATURE_PATH: &str = "/sys/class/thermal/thermal_zone0/temp";
+/// Fan duty in percent by SoC temperature in °C
+const DEFAULT_CURVE: [(f32, u8); 4] = [(50.0, 0), (60.0, 30), (70.0, 60), (75.0, 100)];
+/// The firmware starts throttling at 80°C
+const DEFAULT_WARN_CELSIUS: f32 = 78.0;
+/// The fan slows down once this far below the point it sped up at
+const HYSTERESIS_CELSIUS: f32 = 3.0;
+#[cfg(feature = "gpio")]
+const PWM_FREQUENCY: f64 = 100.0;
+/// Bits of `vcgencmd get_throttled` for what is happening now, those for what
+/// happened since boot being 16 bits higher
+const THROTTLED_FLAGS: [(u32, &str); 4] = [
+    (0x1, "under-voltage"),
+    (0x2, "frequency capped"),
+    (0x4, "throttled"),
+    (0x8, "soft temp limit"),
+];
+
+/// Duties of the fan at a few temperatures, interpolated in between.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FanCurve(Vec<(f32, u8)>);
+
+impl Default for FanCurve {
+    fn default() -> Self {
+        Self(DEFAULT_CURVE.to_vec())
+    }
+}
+
+impl FanCurve {
+    /// Parses points like `50:0,60:30,75:100`, temperatures in °C to duties
+    /// in percent.
+    fn parse(s: &str) -> Option<Self> {
+        let mut points = s
+            .split(',')
+            .map(|point| {
+                let (celsius, duty) = point.split_once(':')?;
+                let duty: u8 = duty.trim().parse().ok()?;
+                Some((celsius.trim().parse().ok()?, duty.min(100)))
+            })
+            .collect::<Option<Vec<(f32, u8)>>>()?;
+        if points.is_empty() {
+            return None;
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Some(Self(points))
+    }
+
+    pub fn duty_at(&self, celsius: f32) -> u8 {
+        let (first, last) = (self.0[0], self.0[self.0.len() - 1]);
+        if celsius <= first.0 {
+            return first.1;
+        }
+        if celsius >= last.0 {
+            return last.1;
+        }
+        self.0
+            .windows(2)
+            .find(|pair| celsius < pair[1].0)
+            .map_or(last.1, |pair| {
+                let ((t0, d0), (t1, d1)) = (pair[0], pair[1]);
+                let ratio = (celsius - t0) / (t1 - t0);
+                (d0 as f32 + ratio * (d1 as f32 - d0 as f32)).round() as u8
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FanKind {
+    /// Switched on a GPIO pin, driven by software PWM
+    Gpio(u8),
+    /// Taking its duty in percent as a single byte at an I2C address, like
+    /// the Argon cases' fan
+    I2c(u16),
+}
+
+impl FanKind {
+    /// Parses `gpio:<pin>` or `i2c:<address>`, e.g. `gpio:14` or `i2c:0x1a`.
+    fn parse(s: &str) -> Option<Self> {
+        let (kind, value) = s.trim().split_once(':')?;
+        match kind {
+            "gpio" => value.parse().ok().map(FanKind::Gpio),
+            "i2c" => {
+                let value = value.trim_start_matches("0x");
+                u16::from_str_radix(value, 16).ok().map(FanKind::I2c)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalConfig {
+    /// No fan is controlled without one, e.g. the official ones driven by
+    /// the kernel
+    pub fan: Option<FanKind>,
+    pub curve: FanCurve,
+}
+
+impl ThermalConfig {
+    /// Reads `AMARU_PI_FAN`, the fan controlled (see [`FanKind::parse`]), and
+    /// `AMARU_PI_FAN_CURVE`, its curve (see [`FanCurve::parse`]).
+    pub fn from_env() -> Self {
+        let fan = env::var("AMARU_PI_FAN").ok().and_then(|var| {
+            let fan = FanKind::parse(&var);
+            if fan.is_none() {
+                warn!("Ignoring invalid AMARU_PI_FAN {}", var);
+            }
+            fan
+        });
+        let curve = env::var("AMARU_PI_FAN_CURVE")
+            .ok()
+            .and_then(|var| FanCurve::parse(&var))
+            .unwrap_or_default();
+        Self { fan, curve }
+    }
+}
+
+/// As reported by `vcgencmd get_throttled`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Throttled(pub u32);
+
+impl Throttled {
+    /// Parses e.g. `throttled=0x50005`.
+    pub fn parse(output: &str) -> Option<Self> {
+        let hex = output.trim().strip_prefix("throttled=0x")?;
+        u32::from_str_radix(hex, 16).ok().map(Throttled)
+    }
+
+    /// What is limiting the Pi right now.
+    pub fn now(&self) -> Vec<&'static str> {
+        THROTTLED_FLAGS
+            .iter()
+            .filter(|(bit, _)| self.0 & bit != 0)
+            .map(|(_, label)| *label)
+            .collect()
+    }
+
+    /// What limited the Pi since it booted, even if no longer.
+    pub fn since_boot(&self) -> Vec<&'static str> {
+        THROTTLED_FLAGS
+            .iter()
+            .filter(|(bit, _)| self.0 & (bit << 16) != 0)
+            .map(|(_, label)| *label)
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ThermalStatus {
+    /// Of the SoC, in °C
+    pub celsius: Option<f32>,
+    /// In percent, when a fan is controlled
+    pub fan_duty: Option<u8>,
+    /// `None` without `vcgencmd`, e.g. off-device
+    pub throttled: Option<Throttled>,
+}
+
+impl ThermalStatus {
+    /// At or above `AMARU_PI_THERMAL_WARN_CELSIUS`.
+    pub fn is_hot(&self) -> bool {
+        let warn_celsius = env::var("AMARU_PI_THERMAL_WARN_CELSIUS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(DEFAULT_WARN_CELSIUS);
+        self.celsius.is_some_and(|celsius| celsius >= warn_celsius)
+    }
+
+    /// Too hot or limited by the firmware right now.
+    pub fn needs_attention(&self) -> bool {
+        self.is_hot()
+            || self
+                .throttled
+                .is_some_and(|throttled| !throttled.now().is_empty())
+    }
+}
+
+pub fn read_temperature() -> Result<f32> {
+    let millidegrees: i64 = fs::read_to_string(TEMPERATURE_PATH)?.trim().parse()?;
+    Ok(millidegrees as f32 / 1000.0)
+}
+
+pub fn read_throttled() -> Result<Throttled> {
+    let output = Command::new("vcgencmd").arg("get_throttled").output()?;
+    if !output.status.success() {
+        return Err(anyhow!("vcgencmd exited with {}", output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Throttled::parse(&stdout).ok_or_else(|| anyhow!("Unexpected output {}", stdout.trim()))
+}
+
+enum FanDriver {
+    #[cfg(feature = "gpio")]
+    Gpio(rppal::gpio::OutputPin),
+    #[cfg(feature = "gpio")]
+    I2c(rppal::i2c::I2c),
+}
+
+impl FanDriver {
+    #[cfg(feature = "gpio")]
+    fn open(kind: FanKind) -> Result<Self> {
+        match kind {
+            FanKind::Gpio(pin) => {
+                let pin = rppal::gpio::Gpio::new()?.get(pin)?.into_output_high();
+                Ok(FanDriver::Gpio(pin))
+            }
+            FanKind::I2c(address) => {
+                let mut i2c = rppal::i2c::I2c::new()?;
+                i2c.set_slave_address(address)?;
+                Ok(FanDriver::I2c(i2c))
+            }
+        }
+    }
+
+    #[cfg(not(feature = "gpio"))]
+    fn open(_kind: FanKind) -> Result<Self> {
+        Err(anyhow!("Fan control needs the gpio feature"))
+    }
+
+    fn set_duty(&mut self, duty: u8) -> Result<()> {
+        #[cfg(feature = "gpio")]
+        match self {
+            FanDriver::Gpio(pin) => match duty {
+                0 => {
+                    pin.clear_pwm()?;
+                    pin.set_low();
+                }
+                100 => {
+                    pin.clear_pwm()?;
+                    pin.set_high();
+                }
+                _ => pin.set_pwm_frequency(PWM_FREQUENCY, duty as f64 / 100.0)?,
+            },
+            FanDriver::I2c(i2c) => {
+                i2c.write(&[duty])?;
+            }
+        }
+        #[cfg(not(feature = "gpio"))]
+        let _ = duty;
+        Ok(())
+    }
+}
+
+/// Drives the fan along the curve, opened on first use.
+struct FanController {
+    config: ThermalConfig,
+    driver: Option<FanDriver>,
+    duty: Option<u8>,
+}
+
+impl FanController {
+    fn new(config: ThermalConfig) -> Self {
+        let driver = config.fan.and_then(|kind| {
+            FanDriver::open(kind)
+                .inspect(|_| info!("Controlling the {:?} fan", kind))
+                .inspect_err(|e| warn!("Failed to open the {:?} fan: {}", kind, e))
+                .ok()
+        });
+        Self {
+            config,
+            driver,
+            duty: None,
+        }
+    }
+
+    /// Sets the duty for the temperature, unless the fan would only slow down
+    /// a little, which would have it hunt around a curve point.
+    fn adjust(&mut self, celsius: f32) -> Option<u8> {
+        let driver = self.driver.as_mut()?;
+        let mut duty = self.config.curve.duty_at(celsius);
+        if let Some(current) = self.duty
+            && duty < current
+        {
+            duty = self
+                .config
+                .curve
+                .duty_at(celsius + HYSTERESIS_CELSIUS)
+                .min(current);
+        }
+        if self.duty != Some(duty) {
+            if let Err(e) = driver.set_duty(duty) {
+                warn!("Failed to set the fan to {}%: {}", duty, e);
+                return self.duty;
+            }
+            self.duty = Some(duty);
+        }
+        self.duty
+    }
+}
+
+static CONTROLLER: Mutex<Option<FanController>> = Mutex::new(None);
+
+/// Reads the temperature and whether the Pi is throttled, and drives the fan
+/// accordingly.
+pub fn sample() -> ThermalStatus {
+    let celsius = read_temperature().ok();
+    let fan_duty = celsius.and_then(|celsius| {
+        let mut controller = CONTROLLER.lock().unwrap();
+        controller
+            .get_or_insert_with(|| FanController::new(ThermalConfig::from_env()))
+            .adjust(celsius)
+    });
+    ThermalStatus {
+        celsius,
+        fan_duty,
+        throttled: read_throttled().ok(),
+    }
+}
@@ -0,0 +1,203 @@
+use crate::chain::koios_url;
+use crate::util::current_timestamp;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tracing::warn;
+
+const CACHE_FILE_PATH: &str = "/home/pi/.amaru_ticker_cache.json";
+const DEFAULT_PRICE_URL: &str = "https://api.coingecko.com/api/v3/simple/price";
+const DEFAULT_CURRENCY: &str = "usd";
+/// Prices move, but not enough to be worth asking a third party more often
+const DEFAULT_REFRESH_SECS: u64 = 30 * 60; // 30 minutes
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Only fetched while the ticker screen is listed in `AMARU_PI_SCREENS`,
+/// which it isn't by default: it tells third parties the Pi is there.
+#[derive(Clone, Debug)]
+pub struct TickerConfig {
+    /// Of a CoinGecko compatible `simple/price` endpoint
+    pub price_url: String,
+    /// e.g. `usd`, `eur`
+    pub currency: String,
+    /// Base URL of a Koios compatible API, for the network figures
+    pub api_url: String,
+    pub refresh_interval: Duration,
+}
+
+impl TickerConfig {
+    /// Reads `AMARU_PI_TICKER_PRICE_URL`, `AMARU_PI_TICKER_CURRENCY` and
+    /// `AMARU_PI_TICKER_REFRESH_SECS`.
+    pub fn from_env() -> Self {
+        let price_url =
+            env::var("AMARU_PI_TICKER_PRICE_URL").unwrap_or_else(|_| DEFAULT_PRICE_URL.to_string());
+        let currency = env::var("AMARU_PI_TICKER_CURRENCY")
+            .map(|s| s.trim().to_lowercase())
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+        let refresh_interval = env::var("AMARU_PI_TICKER_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(DEFAULT_REFRESH_SECS));
+        Self {
+            price_url,
+            currency,
+            api_url: koios_url(),
+            refresh_interval,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Price {
+    pub currency: String,
+    pub value: f64,
+    /// In percent
+    #[serde(default)]
+    pub change_24h: Option<f64>,
+    pub fetched_at: u64,
+}
+
+/// Figures of the current epoch of the whole network.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkStats {
+    pub epoch: u64,
+    pub block_height: u64,
+    #[serde(default)]
+    pub epoch_blocks: u64,
+    #[serde(default)]
+    pub epoch_transactions: u64,
+    /// In lovelace
+    #[serde(default)]
+    pub active_stake: u64,
+    pub fetched_at: u64,
+}
+
+/// The last price and network figures fetched, each kept when the other
+/// can't be fetched, persisted to survive restarts and network outages.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickerSnapshot {
+    #[serde(default)]
+    pub price: Option<Price>,
+    #[serde(default)]
+    pub network: Option<NetworkStats>,
+}
+
+/// How long ago a figure was fetched at `fetched_at`.
+pub fn age(fetched_at: u64) -> Option<Duration> {
+    let now = current_timestamp().ok()?;
+    Some(Duration::from_secs(now.saturating_sub(fetched_at)))
+}
+
+/// `{"cardano": {"usd": 0.45, "usd_24h_change": -1.2}}`.
+async fn fetch_price(config: &TickerConfig) -> Result<Price> {
+    let response: Value = reqwest::Client::new()
+        .get(&config.price_url)
+        .timeout(TIMEOUT)
+        .query(&[
+            ("ids", "cardano"),
+            ("vs_currencies", config.currency.as_str()),
+            ("include_24hr_change", "true"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let cardano = &response["cardano"];
+    let value = cardano[&config.currency]
+        .as_f64()
+        .ok_or_else(|| anyhow!("No {} price in the response", config.currency))?;
+    Ok(Price {
+        currency: config.currency.clone(),
+        value,
+        change_24h: cardano[format!("{}_24h_change", config.currency)].as_f64(),
+        fetched_at: current_timestamp()?,
+    })
+}
+
+/// Subset of the Koios `tip` response.
+#[derive(Debug, Deserialize)]
+struct KoiosTip {
+    epoch_no: u64,
+    block_no: u64,
+}
+
+/// Subset of the Koios `epoch_info` response.
+#[derive(Debug, Deserialize)]
+struct KoiosEpochInfo {
+    blk_count: Option<u64>,
+    tx_count: Option<u64>,
+    active_stake: Option<String>,
+}
+
+async fn fetch_network(config: &TickerConfig) -> Result<NetworkStats> {
+    let url = config.api_url.trim_end_matches('/');
+    let client = reqwest::Client::new();
+    let tips: Vec<KoiosTip> = client
+        .get(format!("{}/tip", url))
+        .timeout(TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let tip = tips
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Empty tip response"))?;
+    let infos: Vec<KoiosEpochInfo> = client
+        .get(format!("{}/epoch_info", url))
+        .timeout(TIMEOUT)
+        .query(&[("_epoch_no", tip.epoch_no.to_string())])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let info = infos.into_iter().next();
+    Ok(NetworkStats {
+        epoch: tip.epoch_no,
+        block_height: tip.block_no,
+        epoch_blocks: info.as_ref().and_then(|i| i.blk_count).unwrap_or(0),
+        epoch_transactions: info.as_ref().and_then(|i| i.tx_count).unwrap_or(0),
+        active_stake: info
+            .and_then(|i| i.active_stake)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        fetched_at: current_timestamp()?,
+    })
+}
+
+/// Brings what could be fetched of `snapshot` up to date.
+pub async fn refresh(config: &TickerConfig, mut snapshot: TickerSnapshot) -> TickerSnapshot {
+    match fetch_price(config).await {
+        Ok(price) => snapshot.price = Some(price),
+        Err(e) => warn!("Failed to fetch the ADA price: {}", e),
+    }
+    match fetch_network(config).await {
+        Ok(network) => snapshot.network = Some(network),
+        Err(e) => warn!("Failed to fetch the network figures: {}", e),
+    }
+    snapshot
+}
+
+pub fn read_cache() -> Result<TickerSnapshot> {
+    let path = Path::new(CACHE_FILE_PATH);
+    if !path.exists() {
+        return Ok(TickerSnapshot::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn write_cache(snapshot: &TickerSnapshot) -> Result<()> {
+    fs::write(CACHE_FILE_PATH, serde_json::to_string_pretty(snapshot)?)?;
+    Ok(())
+}
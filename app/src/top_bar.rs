@@ -5,14 +5,40 @@ pub struct TopBar<'a> {
     pub title: &'a str,
     pub amaru_status_color: Color,
     pub network_status_color: Color,
+    /// Shows the admin badge while privileged actions are unlocked
+    pub admin_unlocked: bool,
+    /// Charge of the UPS battery, and whether it charges when known
+    pub battery: Option<(u8, Option<bool>)>,
     pub background: Color,
 }
 
 impl<'a> Widget for TopBar<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let [_pad_left, left, before_right, right, _pad_right] = Layout::horizontal([
+        let battery = self.battery.map(|(percent, charging)| {
+            let color = match (percent, charging) {
+                (0..=20, _) => Color::Red,
+                (_, Some(false)) => Color::Yellow,
+                _ => Color::Green,
+            };
+            let charging = if charging == Some(true) { "+" } else { "" };
+            Span::styled(
+                format!("{}{}% ", charging, percent),
+                Style::default().fg(color),
+            )
+        });
+        let [
+            _pad_left,
+            left,
+            admin,
+            battery_area,
+            before_right,
+            right,
+            _pad_right,
+        ] = Layout::horizontal([
             Constraint::Length(1),
             Constraint::Min(1),
+            Constraint::Length(if self.admin_unlocked { 7 } else { 0 }),
+            Constraint::Length(battery.as_ref().map_or(0, |span| span.width() as u16)),
             Constraint::Length(1),
             Constraint::Length(1),
             Constraint::Length(1),
@@ -33,6 +59,21 @@ impl<'a> Widget for TopBar<'a> {
         .block(Block::default().borders(Borders::NONE))
         .render(left, buf);
 
+        if let Some(battery) = battery {
+            Paragraph::new(battery).render(battery_area, buf);
+        }
+
+        if self.admin_unlocked {
+            Paragraph::new(Span::styled(
+                "ADMIN",
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .render(admin, buf);
+        }
+
         Paragraph::new(Span::styled(
             "●",
             Style::default().fg(self.amaru_status_color),
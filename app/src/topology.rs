@@ -0,0 +1,111 @@
+use crate::node_config::{Field, NodeConfig};
+use anyhow::{Result, anyhow};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const MAINNET_MAGIC: u32 = 764824073;
+/// Node-to-node versions proposed, all sharing the same parameters
+const VERSIONS: [u8; 2] = [13, 14];
+
+/// Outcome of connecting to a peer and proposing it a handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reachability {
+    /// Accepted one of the versions proposed
+    Reachable {
+        connect_ms: u64,
+        version: u64,
+    },
+    /// Answered but refused the handshake, e.g. being on another network
+    Refused,
+    Unreachable(String),
+}
+
+/// Peers of a comma separated `AMARU_PEER_ADDRESS`.
+pub fn parse_peers(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|peer| peer.trim().to_string())
+        .filter(|peer| !peer.is_empty())
+        .collect()
+}
+
+fn network_magic(network: &str) -> Result<u32> {
+    match network {
+        "mainnet" => Ok(MAINNET_MAGIC),
+        "preprod" => Ok(1),
+        "preview" => Ok(2),
+        other => other
+            .strip_prefix("testnet:")
+            .and_then(|magic| magic.parse().ok())
+            .ok_or_else(|| anyhow!("Unknown network {}", other)),
+    }
+}
+
+/// CBOR `MsgProposeVersions`, in a multiplexer segment of the handshake
+/// mini-protocol.
+fn propose_versions(magic: u32) -> Vec<u8> {
+    let mut payload = vec![0x82, 0x00, 0xa0 + VERSIONS.len() as u8];
+    for version in VERSIONS {
+        payload.push(version);
+        // [magic, initiator only, no peer sharing, no query]
+        payload.extend([0x84, 0x1a]);
+        payload.extend(magic.to_be_bytes());
+        payload.extend([0xf5, 0x00, 0xf4]);
+    }
+
+    // Timestamp, initiator mode and protocol 0, payload length
+    let mut segment = vec![0, 0, 0, 0, 0, 0];
+    segment.extend((payload.len() as u16).to_be_bytes());
+    segment.extend(payload);
+    segment
+}
+
+/// Accepted version, from the reply to `propose_versions`.
+fn parse_reply(payload: &[u8]) -> Option<u64> {
+    match payload {
+        // [1, version, params]
+        [0x83, 0x01, version, ..] if *version < 0x18 => Some(u64::from(*version)),
+        _ => None,
+    }
+}
+
+fn handshake(stream: &mut TcpStream, magic: u32) -> Result<Option<u64>> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.write_all(&propose_versions(magic))?;
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+    let len = u16::from_be_bytes([header[6], header[7]]);
+    let mut payload = vec![0u8; usize::from(len)];
+    stream.read_exact(&mut payload)?;
+    Ok(parse_reply(&payload))
+}
+
+/// Connects to `address` and proposes a handshake for the node's network.
+pub fn test_peer(address: &str) -> Reachability {
+    let network = NodeConfig::load()
+        .ok()
+        .and_then(|config| config.get(Field::Network))
+        .unwrap_or_else(|| "mainnet".to_string());
+    let result = network_magic(&network).and_then(|magic| {
+        let addr = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("No address for {}", address))?;
+        let start = Instant::now();
+        let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        let connect_ms = start.elapsed().as_millis() as u64;
+        Ok((connect_ms, handshake(&mut stream, magic)?))
+    });
+    match result {
+        Ok((connect_ms, Some(version))) => Reachability::Reachable {
+            connect_ms,
+            version,
+        },
+        Ok((_, None)) => Reachability::Refused,
+        Err(e) => Reachability::Unreachable(e.to_string()),
+    }
+}
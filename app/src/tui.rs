@@ -1,47 +1,240 @@
 use crate::actions::handle_action;
-use crate::app::{App, AppAction, AppEvent};
-use crate::{backends};
-use anyhow::Result;
+use crate::app::{App, AppAction, AppActionComplete, AppEvent};
+use crate::backends;
+use crate::control::ControlSocket;
+use crate::crash::{self, CrashReport};
+use crate::density::Density;
+use crate::inputs::mapping::InputMapper;
+use crate::inputs::recording::{InputRecorder, InputReplay};
+#[cfg(any(feature = "display_hat", feature = "led_matrix"))]
+use crate::outputs;
+#[cfg(feature = "led_matrix")]
+use crate::outputs::matrix::{self, LedMatrix};
+use crate::remote::RemoteServer;
+use crate::rtc;
+use crate::screen_text::ScreenText;
+use crate::screenshot;
+use crate::self_update::{self, Handoff};
+use crate::supervisor;
+use crate::watchdog::Watchdog;
+use anyhow::{Result, anyhow};
 use ratatui::Terminal;
+use ratatui::buffer::Buffer;
+use serde_json::json;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+#[cfg(feature = "led_matrix")]
+use tracing::warn;
 
-pub async fn run() -> Result<()> {
+/// How often the crash screen is redrawn
+const CRASH_SCREEN_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Saves the frame in the background. Answers the control socket when it
+/// asked for it, and notifies otherwise.
+fn save_screenshot(app: &mut App, buffer: Buffer) {
+    let kind = app.current_screen_kind();
+    let tx = app.action_tx.clone();
+    let requests = app.take_screenshot_requests();
+    tokio::spawn(async move {
+        let result = tokio::task::spawn_blocking(move || screenshot::save(&buffer, kind))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+        if !requests.is_empty() {
+            for request in requests {
+                request.answer(match &result {
+                    Ok(path) => Ok(json!(path)),
+                    Err(e) => Err(anyhow!("{}", e)),
+                });
+            }
+            return;
+        }
+        let complete = match result {
+            Ok(path) => AppActionComplete::ScreenshotSaved(path),
+            Err(e) => AppActionComplete::ScreenshotFailed(e.to_string()),
+        };
+        let _ = tx.send(complete).await;
+    });
+}
+
+/// `handoff` is set when started by a previous amaru-pi handing off to its
+/// update.
+pub async fn run(handoff: Option<Handoff>) -> Result<()> {
+    crash::install_hook();
+    rtc::restore_system_time();
+    supervisor::start_from_env();
     #[cfg(feature = "display_hat")]
-    let (backend, input_rx) = backends::display_hat::setup_hardware_and_input()?;
+    let (backend, input_rx, mut led, mut backlight) =
+        backends::display_hat::setup_hardware_and_input()?;
     #[cfg(feature = "simulator")]
     let (backend, input_rx) = backends::simulator::setup_simulator_and_input();
 
+    #[cfg(feature = "led_matrix")]
+    let mut led_matrix = LedMatrix::from_env().unwrap_or_else(|e| {
+        warn!("Failed to set up the LED matrix: {}", e);
+        None
+    });
+
     let mut terminal = Terminal::new(backend)?;
     let mut app = App::default();
+    app.set_density(Density::from_env(terminal.size()?));
+    if let Some(handoff) = handoff {
+        app.resume_after_update(handoff);
+    }
+    let mut input_mapper = InputMapper::from_env();
+    let mut recorder = InputRecorder::from_env();
+    let mut replay = InputReplay::from_env();
+    let mut watchdog = Watchdog::from_env();
+    let mut control = ControlSocket::from_env();
+    let mut remote = RemoteServer::from_env().await;
+    let mut screen_text = ScreenText::default();
     let running = Arc::new(AtomicBool::new(true));
     let mut events: Vec<AppEvent> = Vec::with_capacity(4);
+    let mut restart = false;
+    // Set until the next frame is drawn
+    let mut screenshot = false;
+    // Set once the loop panicked, with when
+    let mut crash: Option<(CrashReport, Instant)> = None;
     while running.load(Ordering::SeqCst) {
+        if let Some((report, since)) = &crash {
+            let restart_in =
+                crash::restart_delay().map(|delay| delay.saturating_sub(since.elapsed()));
+            if input_rx
+                .try_iter()
+                .any(|event| crash::is_restart_request(&event))
+                || restart_in.is_some_and(|remaining| remaining.is_zero())
+            {
+                break;
+            }
+            terminal.draw(|frame| crash::render(frame, report, restart_in))?;
+            #[cfg(feature = "display_hat")]
+            {
+                backlight.set_high();
+                led.show(outputs::LedColor::RED);
+            }
+            #[cfg(feature = "led_matrix")]
+            if let Some(device) = &mut led_matrix {
+                // Left as is when it fails, there is more to worry about
+                let _ = device.fill(outputs::LedColor::RED);
+            }
+            watchdog.ping();
+            tokio::time::sleep(CRASH_SCREEN_INTERVAL).await;
+            continue;
+        }
+
         events.push(AppEvent::Tick);
-        while let Ok(event) = input_rx.try_recv() {
-            events.push(AppEvent::Input(event));
+        events.extend(app.completed_actions());
+        if let Some(control) = &mut control {
+            events.extend(control.requests().into_iter().map(AppEvent::Control));
+        }
+        let replayed = replay.as_mut().map(InputReplay::due).unwrap_or_default();
+        let remote_inputs = remote
+            .as_mut()
+            .map(RemoteServer::inputs)
+            .unwrap_or_default();
+        input_mapper.set_typing(app.is_typing());
+        for event in input_rx.try_iter().chain(replayed).chain(remote_inputs) {
+            if let Some(recorder) = &mut recorder {
+                recorder.record(&event);
+            }
+            events.extend(input_mapper.map(event).into_iter().map(AppEvent::from));
         }
+        events.extend(input_mapper.flush().map(AppEvent::from));
+        #[cfg(feature = "simulator")]
+        events.extend(
+            backends::simulator::take_typed()
+                .into_iter()
+                .map(AppEvent::Command),
+        );
 
         for event in events.drain(..) {
-            let actions = app.update(event);
-            for action in actions {
-                if action == AppAction::Quit {
-                    running.store(false, Ordering::SeqCst);
+            let actions = match panic::catch_unwind(AssertUnwindSafe(|| app.update(event))) {
+                Ok(actions) => actions,
+                Err(_) => {
+                    crash = Some((crash::take_report(), Instant::now()));
                     break;
                 }
-                handle_action(&mut app, action).await
+            };
+            for action in actions {
+                match action {
+                    AppAction::Quit => {
+                        running.store(false, Ordering::SeqCst);
+                        break;
+                    }
+                    AppAction::Restart => {
+                        restart = true;
+                        running.store(false, Ordering::SeqCst);
+                        break;
+                    }
+                    AppAction::Screenshot => screenshot = true,
+                    action => handle_action(&mut app, action),
+                }
             }
         }
 
         if !running.load(Ordering::SeqCst) {
             break;
         }
+        if crash.is_some() {
+            continue;
+        }
 
-        terminal.draw(|frame| {
-            app.draw(frame);
-        })?;
+        let kind = app.current_screen_kind();
+        let drawn = panic::catch_unwind(AssertUnwindSafe(|| {
+            terminal
+                .draw(|frame| {
+                    app.draw(frame);
+                })
+                .map(|frame| {
+                    screen_text.record(kind, frame.buffer);
+                    if let Some(remote) = &mut remote {
+                        remote.publish(kind, frame.buffer);
+                    }
+                    screenshot.then(|| frame.buffer.clone())
+                })
+        }));
+        match drawn {
+            Ok(result) => {
+                if let Some(buffer) = result? {
+                    screenshot = false;
+                    save_screenshot(&mut app, buffer);
+                }
+            }
+            Err(_) => {
+                crash = Some((crash::take_report(), Instant::now()));
+                // The frame was left half drawn
+                terminal.clear()?;
+                continue;
+            }
+        }
+        #[cfg(feature = "display_hat")]
+        if app.is_blanked() == backlight.is_set_high() {
+            if app.is_blanked() {
+                backlight.set_low();
+            } else {
+                backlight.set_high();
+            }
+        }
+        #[cfg(feature = "display_hat")]
+        led.show(outputs::status_color(app.system_state()));
+        #[cfg(feature = "led_matrix")]
+        matrix::show_status(&mut led_matrix, app.system_state());
+        watchdog.ping();
     }
     terminal.clear()?;
 
+    if restart {
+        // The new binary arms the hardware watchdog again
+        drop(watchdog);
+        // Only returns if the new binary couldn't be started
+        return Err(self_update::restart(app.current_screen_kind()));
+    }
+    if crash.is_some() {
+        drop(watchdog);
+        // Only returns if amaru-pi couldn't be started again
+        return Err(crash::restart().into());
+    }
     Ok(())
 }
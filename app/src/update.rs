@@ -1,13 +1,22 @@
-use anyhow::Result;
+use crate::oplock::{self, Operation};
+use crate::self_update;
+use crate::util::{current_timestamp, local_minute_of_day};
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::env;
+use std::fmt;
 use std::fs;
 use std::path::Path;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
 const STATE_FILE_PATH: &str = "/home/pi/.amaru_update_state.json";
 const UPDATE_TRIGGER_PATH: &str = "/home/pi/.update_requested";
 const SNOOZE_DURATION_SECS: u64 = 48 * 60 * 60; // 48 hours
+/// Half an hour of slots
+const DEFAULT_LEADER_GUARD_SLOTS: u64 = 1800;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct AppUpdateState {
@@ -21,6 +30,54 @@ pub struct AppUpdateState {
     pub pending_source: String,
     #[serde(default)]
     pub staged_path: String,
+    /// Install slot (`a` or `b`) the binary runs from
+    #[serde(default)]
+    pub slot: String,
+    /// Last version whose service didn't stay up, skipped by the updater
+    #[serde(default)]
+    pub rolled_back_version: String,
+    /// Latest release, left for the operator to stage when the application
+    /// is updated manually
+    #[serde(default)]
+    pub available_version: String,
+    /// Kept at its current version until released, e.g. a known-good one
+    #[serde(default)]
+    pub hold: bool,
+    /// Never updated past this version, empty for the latest
+    #[serde(default)]
+    pub pinned_version: String,
+}
+
+impl AppUpdateState {
+    /// Whether the hold or the pin keep the application from `version`.
+    pub fn is_held_back(&self, version: &str) -> bool {
+        self.hold
+            || (!self.pinned_version.is_empty()
+                && compare_versions(version, &self.pinned_version) == Ordering::Greater)
+    }
+}
+
+/// Compares versions such as `v1.10.0` and `1.9.2` by their numbers.
+/// Pre-releases (`1.2.0-rc1`) come before their release.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| {
+        let version = version.trim().trim_start_matches('v');
+        let (release, pre) = match version.split_once('-') {
+            Some((release, pre)) => (release, Some(pre.to_string())),
+            None => (version, None),
+        };
+        let numbers: Vec<u64> = release.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+        (numbers, pre)
+    };
+    let ((a_numbers, a_pre), (b_numbers, b_pre)) = (parse(a), parse(b));
+    a_numbers
+        .cmp(&b_numbers)
+        .then_with(|| match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(&b),
+        })
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -46,19 +103,135 @@ impl UpdateState {
         now < self.notify_after
     }
 
-    /// Gets the names of all applications with a pending update.
+    /// Gets the names of all applications with a pending update, those held
+    /// back left out.
     pub fn get_pending_app_names(&self) -> Vec<String> {
         self.applications
             .iter()
             .filter(|(_, app_state)| {
                 // An update is available if a pending version and staged path are set.
-                !app_state.pending_version.is_empty() && !app_state.staged_path.is_empty()
+                !app_state.pending_version.is_empty()
+                    && !app_state.staged_path.is_empty()
+                    && !app_state.is_held_back(&app_state.pending_version)
             })
             .map(|(app_name, _)| app_name.clone())
             .collect()
     }
 }
 
+/// A daily time range, in minutes since local midnight. It may wrap past
+/// midnight, e.g. `23:30-00:30`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    start: u16,
+    end: u16,
+}
+
+impl MaintenanceWindow {
+    /// Parses `HH:MM-HH:MM`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let parse_time = |t: &str| {
+            let (h, m) = t.trim().split_once(':')?;
+            let (h, m): (u16, u16) = (h.parse().ok()?, m.parse().ok()?);
+            (h < 24 && m < 60).then_some(h * 60 + m)
+        };
+        let (start, end) = s.split_once('-')?;
+        Some(Self {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        })
+    }
+
+    pub fn contains(&self, minute_of_day: u16) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+
+    /// Whether the local time is within the window.
+    pub fn is_open(&self) -> bool {
+        match local_minute_of_day() {
+            Ok(minute_of_day) => self.contains(minute_of_day),
+            Err(e) => {
+                warn!("Failed to read local time: {}", e);
+                false
+            }
+        }
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start / 60,
+            self.start % 60,
+            self.end / 60,
+            self.end % 60
+        )
+    }
+}
+
+/// When pending updates are applied without anyone asking.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSchedule {
+    pub window: Option<MaintenanceWindow>,
+    /// Updates wait while a block is to be forged within this many slots
+    pub leader_guard_slots: u64,
+    /// Applications only updated on request, e.g. `amaru` on a block producer
+    pub manual: Vec<String>,
+}
+
+impl UpdateSchedule {
+    /// Reads `AMARU_PI_MAINTENANCE_WINDOW` (e.g. `03:00-04:00`, local time)
+    /// `AMARU_PI_UPDATE_LEADER_GUARD_SLOTS` and `AMARU_PI_MANUAL_UPDATES`
+    /// (e.g. `amaru,amaru-doctor`). Without a window, updates are only applied
+    /// on request.
+    pub fn from_env() -> Self {
+        let window = env::var("AMARU_PI_MAINTENANCE_WINDOW")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .and_then(|s| {
+                let window = MaintenanceWindow::parse(&s);
+                if window.is_none() {
+                    warn!("Ignoring invalid maintenance window: {}", s);
+                }
+                window
+            });
+        let leader_guard_slots = env::var("AMARU_PI_UPDATE_LEADER_GUARD_SLOTS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(DEFAULT_LEADER_GUARD_SLOTS);
+        let manual = env::var("AMARU_PI_MANUAL_UPDATES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self {
+            window,
+            leader_guard_slots,
+            manual,
+        }
+    }
+
+    /// Whether updates of the application are only applied on request.
+    pub fn is_manual(&self, app_name: &str) -> bool {
+        self.manual.iter().any(|name| name == app_name)
+    }
+
+    /// `slots_to_leadership` is the distance to the next slot this node
+    /// leads, when known.
+    pub fn allows_update(&self, slots_to_leadership: Option<u64>) -> bool {
+        let leading_soon = slots_to_leadership.is_some_and(|slots| slots < self.leader_guard_slots);
+        self.window.is_some_and(|window| window.is_open()) && !leading_soon
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum UpdateStatus {
     Idle,
@@ -69,6 +242,11 @@ pub struct UpdateManager {
     last_check: Instant,
     interval: Duration,
     pub current_state: UpdateState,
+    pub schedule: UpdateSchedule,
+    /// Set once the scheduled update was triggered, the app is restarted soon
+    scheduled_update_requested: bool,
+    /// Set once amaru-pi installed its own update, until it restarts into it
+    restart_requested: bool,
 }
 
 impl UpdateManager {
@@ -76,10 +254,37 @@ impl UpdateManager {
         Self {
             last_check: Instant::now() - interval, // Force check on first run
             current_state: read_state_file().unwrap_or_default(),
+            schedule: UpdateSchedule::from_env(),
+            scheduled_update_requested: false,
+            restart_requested: false,
             interval,
         }
     }
 
+    /// Triggers pending updates once the schedule allows it. Returns the
+    /// names of the applications being updated.
+    pub fn apply_if_scheduled(&mut self, slots_to_leadership: Option<u64>) -> Option<Vec<String>> {
+        if self.scheduled_update_requested || !self.schedule.allows_update(slots_to_leadership) {
+            return None;
+        }
+        let mut pending_apps = self.current_state.get_pending_app_names();
+        pending_apps.retain(|name| !self.schedule.is_manual(name));
+        if pending_apps.is_empty() {
+            return None;
+        }
+        match self.request_update_of(&pending_apps) {
+            Ok(()) => {
+                info!("Applying scheduled update for {}", pending_apps.join(", "));
+                self.scheduled_update_requested = true;
+                Some(pending_apps)
+            }
+            Err(e) => {
+                warn!("Failed to request scheduled update: {}", e);
+                None
+            }
+        }
+    }
+
     pub fn check_for_update(&mut self) -> UpdateStatus {
         if self.last_check.elapsed() >= self.interval {
             self.last_check = Instant::now();
@@ -108,23 +313,84 @@ impl UpdateManager {
     pub fn snooze(&mut self) -> Result<()> {
         let now = current_timestamp()?;
         self.current_state.notify_after = now + SNOOZE_DURATION_SECS;
-        Self::write_state_file(&self.current_state)?;
+        write_state_file(&self.current_state)?;
         self.last_check = Instant::now(); // Update cache time
         Ok(())
     }
 
-    /// Triggers the update by creating the trigger file.
-    pub fn request_update() -> Result<()> {
-        fs::File::create(UPDATE_TRIGGER_PATH)?;
+    /// Applies all pending updates, those of manually updated applications
+    /// included.
+    pub fn request_update(&mut self) -> Result<()> {
+        let pending_apps = read_state_file()?.get_pending_app_names();
+        self.request_update_of(&pending_apps)
+    }
+
+    /// Installs a pending update of amaru-pi itself, which then restarts
+    /// into it rather than being stopped by the activation script, and
+    /// creates the trigger file for the other applications, listing them.
+    fn request_update_of(&mut self, app_names: &[String]) -> Result<()> {
+        if app_names.iter().any(|name| name == self_update::APP_NAME)
+            && self_update::is_pending(&read_state_file()?)
+        {
+            // Released before the activation script takes it over
+            let _lock = oplock::try_acquire(Operation::UpdateActivation)?;
+            match self_update::install() {
+                Ok(()) => self.restart_requested = true,
+                Err(e) => warn!(
+                    "Failed to install amaru-pi update, leaving it to the activation script: {}",
+                    e
+                ),
+            }
+        }
+        self.current_state = read_state_file()?;
+        let mut pending_apps = self.current_state.get_pending_app_names();
+        pending_apps.retain(|name| app_names.contains(name));
+        if !pending_apps.is_empty() {
+            pending_apps.sort();
+            fs::write(UPDATE_TRIGGER_PATH, pending_apps.join("\n") + "\n")?;
+        }
         Ok(())
     }
 
-    fn write_state_file(state: &UpdateState) -> Result<()> {
-        let path = Path::new(STATE_FILE_PATH);
-        let data = serde_json::to_string_pretty(state)?;
-        fs::write(path, data)?;
+    /// Holds an application at its current version, or releases it.
+    pub fn set_hold(&mut self, app_name: &str, hold: bool) -> Result<()> {
+        set_policy(app_name, |state| state.hold = hold)?;
+        self.current_state = read_state_file()?;
         Ok(())
     }
+
+    /// Whether amaru-pi should now restart into its installed update.
+    pub fn take_restart_request(&mut self) -> bool {
+        std::mem::take(&mut self.restart_requested)
+    }
+}
+
+pub fn write_state_file(state: &UpdateState) -> Result<()> {
+    let path = Path::new(STATE_FILE_PATH);
+    let data = serde_json::to_string_pretty(state)?;
+    fs::write(path, data)?;
+    Ok(())
+}
+
+/// Changes the hold or the pin of an application in the state file, which
+/// the updater follows too.
+pub fn set_policy(app_name: &str, change: impl FnOnce(&mut AppUpdateState)) -> Result<()> {
+    let mut state = read_state_file()?;
+    let app_state = state
+        .applications
+        .get_mut(app_name)
+        .ok_or_else(|| anyhow!("Unknown application {}", app_name))?;
+    change(app_state);
+    let policy = if app_state.hold {
+        "held".to_string()
+    } else if !app_state.pinned_version.is_empty() {
+        format!("pinned to {}", app_state.pinned_version)
+    } else {
+        "released".to_string()
+    };
+    write_state_file(&state)?;
+    info!("Updates of {} {}", app_name, policy);
+    Ok(())
 }
 
 /// Reads the update state file from disk.
@@ -138,7 +404,3 @@ pub fn read_state_file() -> Result<UpdateState> {
     let state: UpdateState = serde_json::from_str(&data)?;
     Ok(state)
 }
-
-fn current_timestamp() -> Result<u64> {
-    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
-}
@@ -0,0 +1,104 @@
+use crate::github;
+use crate::update::{AppUpdateState, UpdateState};
+use crate::util::current_timestamp;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Also appended to by the activation script
+const HISTORY_FILE_PATH: &str = "/home/pi/.amaru_update_history.json";
+/// Oldest updates are dropped past this many
+const MAX_RECORDS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// The new version stayed up and was kept
+    Applied,
+    /// The new version didn't stay up, the previous one runs again
+    RolledBack,
+}
+
+/// An update applied to one of the managed applications.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateRecord {
+    pub app: String,
+    #[serde(default)]
+    pub from_version: String,
+    pub to_version: String,
+    /// Repository the version comes from
+    #[serde(default)]
+    pub source: String,
+    pub timestamp: u64,
+    pub outcome: Outcome,
+}
+
+impl UpdateRecord {
+    pub fn now(
+        app: &str,
+        from_version: &str,
+        to_version: &str,
+        source: &str,
+        outcome: Outcome,
+    ) -> Self {
+        Self {
+            app: app.to_string(),
+            from_version: from_version.to_string(),
+            to_version: to_version.to_string(),
+            source: source.to_string(),
+            timestamp: current_timestamp().unwrap_or_default(),
+            outcome,
+        }
+    }
+}
+
+/// Updates applied, oldest first.
+pub fn read() -> Result<Vec<UpdateRecord>> {
+    let path = Path::new(HISTORY_FILE_PATH);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn record(record: UpdateRecord) -> Result<()> {
+    let mut history = read()?;
+    history.push(record);
+    let overflow = history.len().saturating_sub(MAX_RECORDS);
+    history.drain(..overflow);
+    fs::write(HISTORY_FILE_PATH, serde_json::to_string_pretty(&history)?)?;
+    Ok(())
+}
+
+/// The notes of the release of a pending update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseNotes {
+    pub app: String,
+    pub version: String,
+    /// Markdown, as written on GitHub
+    pub body: String,
+}
+
+/// The first application, by name, with an update waiting to be applied.
+pub fn next_pending(state: &UpdateState) -> Option<(String, AppUpdateState)> {
+    let mut names = state.get_pending_app_names();
+    names.sort();
+    let name = names.into_iter().next()?;
+    let app = state.applications.get(&name)?.clone();
+    Some((name, app))
+}
+
+#[derive(Deserialize)]
+struct Release {
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// The notes of the GitHub release `tag` of `repo`, as written, in Markdown.
+pub async fn release_notes(repo: &str, tag: &str) -> Result<String> {
+    let body = github::get(&format!("repos/{}/releases/tags/{}", repo, tag)).await?;
+    let release: Release = serde_json::from_str(&body)?;
+    Ok(release.body.unwrap_or_default())
+}
@@ -0,0 +1,313 @@
+use crate::github;
+use crate::offline_update::{self, find_checksum, listed_checksum};
+use crate::platform::Platform;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Listed with the assets of GitHub releases
+const CHECKSUMS_ASSET: &str = "checksums.txt";
+/// Repositories of the applications without a source set
+const DEFAULT_REPOS: &[(&str, &str)] = &[
+    ("amaru-pi", "jeluard/amaru-pi"),
+    ("amaru", "pragma-org/amaru"),
+    ("amaru-doctor", "jeluard/amaru-doctor"),
+];
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A file of a release, downloaded from `url`, or copied when it is a path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub url: String,
+    /// Assets without one aren't installed
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// The files of the latest release of an application.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Listing {
+    pub version: String,
+    pub assets: Vec<Asset>,
+}
+
+/// What the updater installs: the archive for the platform and, when
+/// published, the patch from the version installed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub version: String,
+    /// Recorded as the source of the update
+    pub source: String,
+    pub archive: Asset,
+    pub patch: Option<Asset>,
+}
+
+/// Where the releases of an application come from.
+pub trait UpdateSource {
+    /// Recorded with the updates, e.g. the repository or the URL.
+    fn describe(&self) -> String;
+
+    /// The repository, for the release notes.
+    fn github_repo(&self) -> Option<&str> {
+        None
+    }
+
+    fn latest<'a>(&'a self, app: &'a str) -> BoxFuture<'a, Result<Listing>>;
+}
+
+/// Releases of a GitHub repository, their checksums from `checksums.txt`.
+pub struct GitHubSource {
+    pub repo: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl UpdateSource for GitHubSource {
+    fn describe(&self) -> String {
+        self.repo.clone()
+    }
+
+    fn github_repo(&self) -> Option<&str> {
+        Some(&self.repo)
+    }
+
+    fn latest<'a>(&'a self, _app: &'a str) -> BoxFuture<'a, Result<Listing>> {
+        Box::pin(async move {
+            let release: GitHubRelease =
+                serde_json::from_str(&github::latest_release(&self.repo).await?)?;
+            let checksums = match release
+                .assets
+                .iter()
+                .find(|asset| asset.name == CHECKSUMS_ASSET)
+            {
+                Some(asset) => fetch_text(&asset.browser_download_url).await?,
+                None => String::new(),
+            };
+            let assets = release
+                .assets
+                .into_iter()
+                .map(|asset| Asset {
+                    sha256: find_checksum(&checksums, &asset.name),
+                    name: asset.name,
+                    url: asset.browser_download_url,
+                })
+                .collect();
+            Ok(Listing {
+                version: release.tag_name,
+                assets,
+            })
+        })
+    }
+}
+
+/// A JSON manifest served over HTTPS, such as
+/// `{"version": "v1.2.0", "assets": [{"name": "…", "url": "…", "sha256": "…"}]}`.
+/// URLs of assets are relative to the manifest unless absolute.
+pub struct ManifestSource {
+    pub url: String,
+}
+
+impl UpdateSource for ManifestSource {
+    fn describe(&self) -> String {
+        self.url.clone()
+    }
+
+    fn latest<'a>(&'a self, _app: &'a str) -> BoxFuture<'a, Result<Listing>> {
+        Box::pin(async move {
+            let mut listing: Listing = serde_json::from_str(&fetch_text(&self.url).await?)?;
+            let base = self
+                .url
+                .rsplit_once('/')
+                .map_or(self.url.as_str(), |(base, _)| base);
+            for asset in &mut listing.assets {
+                if !asset.url.contains("://") {
+                    asset.url = format!("{}/{}", base, asset.url.trim_start_matches('/'));
+                }
+            }
+            Ok(listing)
+        })
+    }
+}
+
+/// Release archives in a directory, such as a mounted share, with their
+/// `checksums.txt`. The latest version found is the release.
+pub struct DirectorySource {
+    pub dir: PathBuf,
+}
+
+impl UpdateSource for DirectorySource {
+    fn describe(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn latest<'a>(&'a self, app: &'a str) -> BoxFuture<'a, Result<Listing>> {
+        Box::pin(async move {
+            let archive = offline_update::scan(&self.dir)
+                .into_iter()
+                .find(|archive| archive.app == app)
+                .ok_or_else(|| anyhow!("No {} archive in {}", app, self.dir.display()))?;
+            let version = archive.version;
+            let dir = archive.path.parent().unwrap_or(&self.dir);
+            let assets = fs::read_dir(dir)?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().contains(version.as_str()))
+                })
+                .map(|path| Asset {
+                    name: path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    url: path.display().to_string(),
+                    sha256: listed_checksum(&path),
+                })
+                .collect();
+            Ok(Listing { version, assets })
+        })
+    }
+}
+
+async fn fetch_text(url: &str) -> Result<String> {
+    Ok(reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "amaru-pi")
+        .timeout(TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// Parses a source: `github:<owner>/<repo>` or `<owner>/<repo>`, the URL of
+/// a manifest, or `dir:<path>` or an absolute path.
+pub fn parse(spec: &str) -> Option<Box<dyn UpdateSource + Send + Sync>> {
+    let spec = spec.trim();
+    if spec.starts_with("https://") || spec.starts_with("http://") {
+        return Some(Box::new(ManifestSource {
+            url: spec.to_string(),
+        }));
+    }
+    if let Some(dir) = spec
+        .strip_prefix("dir:")
+        .or(spec.starts_with('/').then_some(spec))
+    {
+        return Some(Box::new(DirectorySource {
+            dir: PathBuf::from(dir),
+        }));
+    }
+    let repo = spec.strip_prefix("github:").unwrap_or(spec);
+    let (owner, name) = repo.split_once('/')?;
+    if owner.is_empty() || name.is_empty() || name.contains('/') {
+        return None;
+    }
+    Some(Box::new(GitHubSource {
+        repo: repo.to_string(),
+    }))
+}
+
+/// Reads `AMARU_PI_UPDATE_SOURCE_<APP>`, e.g. `AMARU_PI_UPDATE_SOURCE_AMARU_PI`,
+/// else the GitHub repository of the application.
+pub fn for_app(app: &str) -> Result<Box<dyn UpdateSource + Send + Sync>> {
+    let name = format!(
+        "AMARU_PI_UPDATE_SOURCE_{}",
+        app.to_uppercase().replace('-', "_")
+    );
+    if let Ok(spec) = env::var(&name)
+        && !spec.trim().is_empty()
+    {
+        return parse(&spec).ok_or_else(|| anyhow!("Invalid {}: {}", name, spec));
+    }
+    DEFAULT_REPOS
+        .iter()
+        .find(|(known, _)| *known == app)
+        .map(|(_, repo)| {
+            Box::new(GitHubSource {
+                repo: repo.to_string(),
+            }) as Box<dyn UpdateSource + Send + Sync>
+        })
+        .ok_or_else(|| anyhow!("No update source for {}", app))
+}
+
+/// The archive of the release for this platform, and the patch from
+/// `from_version`, when listed, e.g. `amaru-pi-v1.1.0-to-v1.2.0-aarch64.zst`.
+pub fn resolve(
+    source: &dyn UpdateSource,
+    app: &str,
+    listing: Listing,
+    from_version: Option<&str>,
+) -> Result<Release> {
+    let platform = Platform::current();
+    let names: Vec<&str> = listing
+        .assets
+        .iter()
+        .map(|asset| asset.name.as_str())
+        .collect();
+    let archive_name = platform
+        .select(app, &names)
+        .ok_or_else(|| anyhow!("No {} {} archive for {}", app, listing.version, platform))?
+        .to_string();
+    let patch_name = from_version.map(|from| {
+        format!(
+            "{}-{}-to-{}-{}.zst",
+            app,
+            from,
+            listing.version,
+            platform.arch.name()
+        )
+    });
+    let find = |name: &str| {
+        listing
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .cloned()
+    };
+    let archive = find(&archive_name).ok_or_else(|| anyhow!("Missing {}", archive_name))?;
+    let patch = patch_name.as_deref().and_then(find);
+    Ok(Release {
+        version: listing.version.clone(),
+        source: source.describe(),
+        archive,
+        patch,
+    })
+}
+
+/// The latest release of an application from its source, with the patch from
+/// `from_version` when there is one.
+pub async fn latest_release(app: &str, from_version: Option<&str>) -> Result<Release> {
+    let source = for_app(app)?;
+    let listing = source.latest(app).await?;
+    resolve(source.as_ref(), app, listing, from_version)
+}
+
+/// The GitHub repository updates of an application staged from `source` come
+/// from, for their release notes.
+pub fn github_repo(app: &str, source: &str) -> Option<String> {
+    let source = if source.is_empty() {
+        for_app(app).ok()?
+    } else {
+        parse(source)?
+    };
+    source.github_repo().map(str::to_string)
+}
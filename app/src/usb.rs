@@ -0,0 +1,122 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+#[cfg(feature = "display_hat")]
+use anyhow::anyhow;
+#[cfg(feature = "display_hat")]
+use serde::Deserialize;
+#[cfg(feature = "display_hat")]
+use std::fs;
+#[cfg(feature = "display_hat")]
+use tracing::warn;
+
+/// Where a drive that isn't mounted yet gets mounted
+#[cfg(feature = "display_hat")]
+const MOUNT_POINT: &str = "/mnt/amaru-usb";
+
+/// A USB drive ready to be written to. Unmounted when dropped if it was
+/// mounted here, so that it can be pulled out right away.
+#[derive(Debug)]
+pub struct UsbDrive {
+    path: PathBuf,
+    #[cfg_attr(not(feature = "display_hat"), allow(dead_code))]
+    mounted_here: bool,
+}
+
+impl UsbDrive {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "display_hat")]
+#[derive(Debug, Deserialize)]
+struct BlockDevices {
+    blockdevices: Vec<BlockDevice>,
+}
+
+#[cfg(feature = "display_hat")]
+#[derive(Debug, Deserialize)]
+struct BlockDevice {
+    name: String,
+    tran: Option<String>,
+    fstype: Option<String>,
+    mountpoint: Option<String>,
+    #[serde(default)]
+    children: Vec<BlockDevice>,
+}
+
+/// First filesystem found on a USB drive, as its device and mount point.
+#[cfg(feature = "display_hat")]
+fn find_filesystem() -> Result<(String, Option<String>)> {
+    let output = run_and_capture(
+        "lsblk",
+        ["-J", "-p", "-o", "NAME,TRAN,FSTYPE,MOUNTPOINT"].to_vec(),
+    )?;
+    let devices: BlockDevices = serde_json::from_str(&output)?;
+    devices
+        .blockdevices
+        .into_iter()
+        .filter(|disk| disk.tran.as_deref() == Some("usb"))
+        .flat_map(|disk| {
+            // A drive formatted without a partition table has no children
+            if disk.children.is_empty() {
+                vec![disk]
+            } else {
+                disk.children
+            }
+        })
+        .find(|fs| fs.fstype.is_some())
+        .map(|fs| (fs.name, fs.mountpoint))
+        .ok_or_else(|| anyhow!("No USB drive found"))
+}
+
+/// Whether a USB drive with a filesystem is plugged in.
+#[cfg(feature = "display_hat")]
+pub fn is_present() -> bool {
+    find_filesystem().is_ok()
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn is_present() -> bool {
+    false
+}
+
+/// The plugged in USB drive, mounted if needed.
+#[cfg(feature = "display_hat")]
+pub fn open() -> Result<UsbDrive> {
+    let (device, mountpoint) = find_filesystem()?;
+    if let Some(mountpoint) = mountpoint {
+        return Ok(UsbDrive {
+            path: PathBuf::from(mountpoint),
+            mounted_here: false,
+        });
+    }
+    fs::create_dir_all(MOUNT_POINT)?;
+    run_and_capture("mount", [device.as_str(), MOUNT_POINT].to_vec())?;
+    Ok(UsbDrive {
+        path: PathBuf::from(MOUNT_POINT),
+        mounted_here: true,
+    })
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn open() -> Result<UsbDrive> {
+    Err(anyhow::anyhow!(
+        "USB drives are only available on the device"
+    ))
+}
+
+#[cfg(feature = "display_hat")]
+impl Drop for UsbDrive {
+    fn drop(&mut self) {
+        if !self.mounted_here {
+            return;
+        }
+        if let Err(e) = run_and_capture("umount", [MOUNT_POINT].to_vec()) {
+            warn!("Failed to unmount the USB drive: {}", e);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use ratatui::layout::{Constraint, Layout, Rect};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::vertical([
@@ -15,3 +16,90 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     ])
     .split(popup_layout[1])[1]
 }
+
+/// Seconds elapsed since the UNIX epoch.
+pub fn current_timestamp() -> anyhow::Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+/// Minutes elapsed since local midnight.
+pub fn local_minute_of_day() -> anyhow::Result<u16> {
+    let tz = tz::TimeZone::local().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    let now = tz::DateTime::now(tz.as_ref()).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    Ok(u16::from(now.hour()) * 60 + u16::from(now.minute()))
+}
+
+/// Formats a UNIX timestamp as a local `MM-DD HH:MM`.
+pub fn format_local_time(timestamp: u64) -> anyhow::Result<String> {
+    let tz = tz::TimeZone::local().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    let time = tz::DateTime::from_timespec(timestamp as i64, 0, tz.as_ref())
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    Ok(format!(
+        "{:02}-{:02} {:02}:{:02}",
+        time.month(),
+        time.month_day(),
+        time.hour(),
+        time.minute()
+    ))
+}
+
+/// Two most significant units, e.g. "1h 05m".
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours >= 24 {
+        format!("{}d {}h", hours / 24, hours % 24)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, secs)
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    let mb = bytes as f64 / 1_000_000.0;
+    if mb >= 1_000.0 {
+        format!("{:.2} GB", mb / 1_000.0)
+    } else if bytes >= 1_000_000 {
+        format!("{:.1} MB", mb)
+    } else if bytes >= 1_000 {
+        format!("{:.1} KB", bytes as f64 / 1_000.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Formats a lovelace amount as a compact ADA value, e.g. `12.3M`.
+pub fn format_ada(lovelace: u64) -> String {
+    let ada = lovelace as f64 / 1_000_000.0;
+    if ada >= 1_000_000.0 {
+        format!("{:.1}M", ada / 1_000_000.0)
+    } else if ada >= 1_000.0 {
+        format!("{:.1}K", ada / 1_000.0)
+    } else {
+        format!("{:.0}", ada)
+    }
+}
+
+/// Parses a UTC RFC 3339 timestamp, as `2025-01-31T12:34:56.789Z`, into
+/// milliseconds since the UNIX epoch.
+pub fn parse_utc_millis(timestamp: &str) -> Option<u64> {
+    let timestamp = timestamp.strip_suffix('Z')?;
+    let (date, time) = timestamp.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    let millis = format!("{:0<3}", fraction).get(..3)?.parse::<i64>().ok()?;
+
+    // Days since the epoch of the proleptic Gregorian calendar date
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    u64::try_from(secs * 1_000 + millis).ok()
+}
@@ -0,0 +1,166 @@
+#[cfg(feature = "display_hat")]
+use crate::systemd;
+#[cfg(feature = "display_hat")]
+use crate::util::current_timestamp;
+#[cfg(feature = "display_hat")]
+use crate::wifi::run_and_capture;
+use anyhow::Result;
+#[cfg(feature = "display_hat")]
+use serde_json::Value;
+use std::env;
+use std::net::IpAddr;
+#[cfg(feature = "display_hat")]
+use std::path::Path;
+
+const DEFAULT_WIREGUARD_INTERFACE: &str = "wg0";
+/// WireGuard renews handshakes every 2 minutes while the peers talk
+#[cfg(feature = "display_hat")]
+const HANDSHAKE_TIMEOUT_SECS: u64 = 180;
+
+/// The WireGuard interface to look for, from `AMARU_PI_WIREGUARD_INTERFACE`.
+pub fn wireguard_interface() -> String {
+    env::var("AMARU_PI_WIREGUARD_INTERFACE")
+        .map(|s| s.trim().to_string())
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_WIREGUARD_INTERFACE.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VpnBackend {
+    Tailscale,
+    /// Brought up by `wg-quick@<interface>`
+    WireGuard(String),
+}
+
+impl VpnBackend {
+    pub fn name(&self) -> String {
+        match self {
+            VpnBackend::Tailscale => "Tailscale".to_string(),
+            VpnBackend::WireGuard(interface) => format!("WireGuard {}", interface),
+        }
+    }
+}
+
+/// The mesh VPN many operators reach their Pi through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VpnStatus {
+    pub backend: VpnBackend,
+    pub up: bool,
+    /// Of the Pi on the VPN
+    pub ip: Option<IpAddr>,
+    /// Why it isn't connected while up, e.g. Tailscale waiting for a login
+    pub issue: Option<String>,
+}
+
+impl VpnStatus {
+    pub fn connected(&self) -> bool {
+        self.up && self.issue.is_none()
+    }
+}
+
+/// Tailscale when installed, else the WireGuard interface when configured,
+/// `None` without either.
+#[cfg(feature = "display_hat")]
+pub fn detect() -> Result<Option<VpnStatus>> {
+    if run_and_capture("tailscale", ["version"].to_vec()).is_ok() {
+        return tailscale_status().map(Some);
+    }
+    let interface = wireguard_interface();
+    let configured = Path::new("/etc/wireguard")
+        .join(format!("{}.conf", interface))
+        .exists();
+    if configured || is_up(&interface) {
+        return wireguard_status(&interface).map(Some);
+    }
+    Ok(None)
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn detect() -> Result<Option<VpnStatus>> {
+    Ok(None)
+}
+
+/// Brings the VPN up or down, which cuts off whoever reaches the Pi through
+/// it.
+#[cfg(feature = "display_hat")]
+pub fn set_up(backend: &VpnBackend, up: bool) -> Result<()> {
+    match backend {
+        VpnBackend::Tailscale => {
+            // Without flags, `up` keeps the settings of the last login
+            run_and_capture("tailscale", [if up { "up" } else { "down" }].to_vec())?;
+            Ok(())
+        }
+        VpnBackend::WireGuard(interface) => {
+            let unit = format!("wg-quick@{}.service", interface);
+            if up {
+                systemd::start_service(&unit)
+            } else {
+                systemd::stop_service(&unit)
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn set_up(_backend: &VpnBackend, _up: bool) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "display_hat")]
+fn tailscale_status() -> Result<VpnStatus> {
+    let status: Value = serde_json::from_str(&run_and_capture(
+        "tailscale",
+        ["status", "--json"].to_vec(),
+    )?)?;
+    // `Running`, `Stopped`, `NeedsLogin`...
+    let state = status["BackendState"].as_str().unwrap_or("Unknown");
+    let ip = status["Self"]["TailscaleIPs"]
+        .as_array()
+        .and_then(|ips| ips.iter().find_map(|ip| ip.as_str()?.parse().ok()));
+    Ok(VpnStatus {
+        backend: VpnBackend::Tailscale,
+        up: state != "Stopped",
+        ip,
+        issue: (state != "Running" && state != "Stopped").then(|| state.to_string()),
+    })
+}
+
+#[cfg(feature = "display_hat")]
+fn is_up(interface: &str) -> bool {
+    Path::new("/sys/class/net").join(interface).exists()
+}
+
+#[cfg(feature = "display_hat")]
+fn wireguard_status(interface: &str) -> Result<VpnStatus> {
+    let mut status = VpnStatus {
+        backend: VpnBackend::WireGuard(interface.to_string()),
+        up: is_up(interface),
+        ip: None,
+        issue: None,
+    };
+    if !status.up {
+        return Ok(status);
+    }
+    // e.g. `5: wg0    inet 10.8.0.2/24 scope global wg0`
+    let addresses = run_and_capture(
+        "ip",
+        ["-o", "-4", "addr", "show", "dev", interface].to_vec(),
+    )?;
+    status.ip = addresses
+        .split_whitespace()
+        .skip_while(|word| *word != "inet")
+        .nth(1)
+        .and_then(|address| address.split('/').next()?.parse().ok());
+    // A public key and the time of its last handshake per peer, 0 if none
+    let handshakes = run_and_capture("wg", ["show", interface, "latest-handshakes"].to_vec())?;
+    let latest = handshakes
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1)?.parse::<u64>().ok())
+        .max()
+        .unwrap_or(0);
+    if current_timestamp()?.saturating_sub(latest) > HANDSHAKE_TIMEOUT_SECS {
+        status.issue = Some("No handshake with the peers".to_string());
+    }
+    Ok(status)
+}
@@ -0,0 +1,174 @@
+use sd_notify::NotifyState;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// The BCM watchdog reboots the board when not written to for about 15s
+const HARDWARE_WATCHDOG_PATH: &str = "/dev/watchdog";
+const HARDWARE_PING_INTERVAL: Duration = Duration::from_secs(1);
+/// A heartbeat not beaten for this long is from a stalled thread
+const STALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Beaten by the thread polling the buttons, whose stalling the main loop
+/// wouldn't notice otherwise
+pub static INPUT_HEARTBEAT: Heartbeat = Heartbeat::new();
+
+static PROCESS_START: OnceLock<Instant> = OnceLock::new();
+
+fn elapsed_millis() -> u64 {
+    PROCESS_START
+        .get_or_init(Instant::now)
+        .elapsed()
+        .as_millis() as u64
+}
+
+/// Last sign of life of a thread.
+#[derive(Debug)]
+pub struct Heartbeat {
+    /// Milliseconds since the process started, 0 when never beaten
+    last: AtomicU64,
+}
+
+impl Heartbeat {
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU64::new(0),
+        }
+    }
+
+    pub fn beat(&self) {
+        self.last.store(elapsed_millis().max(1), Ordering::Relaxed);
+    }
+
+    /// A thread that never beat isn't watched.
+    fn is_stalled(&self) -> bool {
+        let last = self.last.load(Ordering::Relaxed);
+        last != 0 && elapsed_millis().saturating_sub(last) > STALL_TIMEOUT.as_millis() as u64
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tells systemd the UI loop is alive, so that a hung loop gets the service
+/// restarted rather than leaving a frozen display. Optionally also pets the
+/// hardware watchdog, which reboots the board when the whole system hangs.
+pub struct Watchdog {
+    /// systemd restarts the service when not pinged for this long
+    timeout: Option<Duration>,
+    last_ping: Option<Instant>,
+    hardware: Option<File>,
+    last_hardware_ping: Option<Instant>,
+    /// Whether systemd was told the UI is up
+    ready: bool,
+    stall_reported: bool,
+}
+
+impl Watchdog {
+    /// Reads `AMARU_PI_WATCHDOG_SECS`, 0 disabling the systemd watchdog, and
+    /// `AMARU_PI_HARDWARE_WATCHDOG`. The systemd watchdog needs
+    /// `NotifyAccess=main` in the unit.
+    pub fn from_env() -> Self {
+        let timeout_secs = env::var("AMARU_PI_WATCHDOG_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let timeout = (timeout_secs > 0 && env::var_os("NOTIFY_SOCKET").is_some())
+            .then(|| Duration::from_secs(timeout_secs));
+
+        let use_hardware = env::var("AMARU_PI_HARDWARE_WATCHDOG")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(false);
+        let hardware = if use_hardware {
+            OpenOptions::new()
+                .write(true)
+                .open(HARDWARE_WATCHDOG_PATH)
+                .inspect(|_| info!("Hardware watchdog armed"))
+                .inspect_err(|e| warn!("Failed to open {}: {}", HARDWARE_WATCHDOG_PATH, e))
+                .ok()
+        } else {
+            None
+        };
+
+        Self {
+            timeout,
+            last_ping: None,
+            hardware,
+            last_hardware_ping: None,
+            ready: false,
+            stall_reported: false,
+        }
+    }
+
+    /// Enables the systemd watchdog from the binary rather than the unit, so
+    /// that a binary without it can still be rolled back to.
+    fn notify_ready(&mut self) {
+        let mut states = vec![NotifyState::Ready];
+        if let Some(timeout) = self.timeout {
+            states.push(NotifyState::WatchdogUsec(timeout.as_micros() as u32));
+        }
+        if let Err(e) = sd_notify::notify(false, &states) {
+            warn!("Failed to notify systemd: {}", e);
+        }
+        self.ready = true;
+    }
+
+    /// Called after each frame is drawn, skipped while the input thread is
+    /// stalled.
+    pub fn ping(&mut self) {
+        if !self.ready {
+            self.notify_ready();
+        }
+        if INPUT_HEARTBEAT.is_stalled() {
+            if !self.stall_reported {
+                warn!("Input thread stalled, letting the watchdog expire");
+                self.stall_reported = true;
+            }
+            return;
+        }
+        self.stall_reported = false;
+
+        if let Some(timeout) = self.timeout
+            && self
+                .last_ping
+                .is_none_or(|last| last.elapsed() >= timeout / 4)
+        {
+            self.last_ping = Some(Instant::now());
+            if let Err(e) = sd_notify::notify(false, &[NotifyState::Watchdog]) {
+                warn!("Failed to ping the systemd watchdog: {}", e);
+            }
+        }
+
+        if let Some(hardware) = &mut self.hardware
+            && self
+                .last_hardware_ping
+                .is_none_or(|last| last.elapsed() >= HARDWARE_PING_INTERVAL)
+        {
+            self.last_hardware_ping = Some(Instant::now());
+            if let Err(e) = hardware.write_all(b"1").and_then(|_| hardware.flush()) {
+                warn!("Failed to ping the hardware watchdog: {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    /// Disarms the hardware watchdog with the magic character, on exit or
+    /// before exec'ing into an update.
+    fn drop(&mut self) {
+        if let Some(hardware) = &mut self.hardware
+            && let Err(e) = hardware.write_all(b"V")
+        {
+            warn!("Failed to disarm the hardware watchdog: {}", e);
+        }
+    }
+}
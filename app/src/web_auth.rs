@@ -0,0 +1,238 @@
+use crate::secrets;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderMap, HeaderName, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tracing::warn;
+
+/// Who may use the HTTP servers of amaru-pi: holders of the bearer token in
+/// `AMARU_PI_API_TOKEN` and, behind an authenticating reverse proxy (e.g.
+/// oauth2-proxy in front of the team's SSO), the users it forwards.
+#[derive(Clone, Debug, Default)]
+pub struct WebAuth {
+    token: Option<String>,
+    proxy: Option<ProxyAuth>,
+}
+
+#[derive(Clone, Debug)]
+struct ProxyAuth {
+    /// Naming the user, e.g. `X-Forwarded-User`
+    header: HeaderName,
+    /// Only the proxy may set the header
+    trusted: Vec<IpAddr>,
+}
+
+impl WebAuth {
+    /// Reads `AMARU_PI_API_TOKEN`, from the secrets or the environment,
+    /// `AMARU_PI_AUTH_PROXY_HEADER` and `AMARU_PI_AUTH_PROXY_IPS`, the
+    /// addresses of the proxy, local by default.
+    pub fn from_env() -> Self {
+        let token = secrets::get_or_env("AMARU_PI_API_TOKEN").filter(|t| !t.trim().is_empty());
+        let proxy = env::var("AMARU_PI_AUTH_PROXY_HEADER")
+            .ok()
+            .filter(|h| !h.trim().is_empty())
+            .and_then(|h| {
+                HeaderName::try_from(h.trim())
+                    .inspect_err(|e| warn!("Ignoring the proxy header {}: {}", h, e))
+                    .ok()
+            })
+            .map(|header| ProxyAuth {
+                header,
+                trusted: trusted_proxies(),
+            });
+        Self { token, proxy }
+    }
+
+    /// Whether anything is required of the clients. Without, the servers
+    /// only answer the Pi itself.
+    pub fn is_enabled(&self) -> bool {
+        self.token.is_some() || self.proxy.is_some()
+    }
+
+    /// The user allowed, from a bearer token, a `token` in the query for
+    /// browsers opening WebSockets, or the header of a trusted proxy. With
+    /// neither configured, only local clients are, as `anonymous`.
+    pub fn authenticate(
+        &self,
+        peer: IpAddr,
+        headers: &HeaderMap,
+        query: Option<&str>,
+    ) -> Option<String> {
+        if !self.is_enabled() {
+            return canonical(peer)
+                .is_loopback()
+                .then(|| "anonymous".to_string());
+        }
+        if let Some(token) = &self.token {
+            let bearer = headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "));
+            let queried = query
+                .into_iter()
+                .flat_map(|q| q.split('&'))
+                .find_map(|pair| pair.strip_prefix("token="));
+            if bearer
+                .into_iter()
+                .chain(queried)
+                .any(|given| same(given.trim(), token))
+            {
+                return Some("token".to_string());
+            }
+        }
+        let proxy = self.proxy.as_ref()?;
+        if !proxy.trusted.contains(&canonical(peer)) {
+            return None;
+        }
+        headers
+            .get(&proxy.header)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|user| !user.is_empty())
+            .map(str::to_string)
+    }
+}
+
+fn trusted_proxies() -> Vec<IpAddr> {
+    match env::var("AMARU_PI_AUTH_PROXY_IPS") {
+        Ok(ips) => ips
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| {
+                s.parse()
+                    .inspect_err(|_| warn!("Ignoring the proxy address {}", s))
+                    .ok()
+            })
+            .collect(),
+        Err(_) => vec![
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ],
+    }
+}
+
+/// IPv4 peers of a dual-stack socket show as `::ffff:a.b.c.d`.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        ip => ip,
+    }
+}
+
+/// Compares in a time not depending on where they differ.
+fn same(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Middleware refusing the requests `WebAuth::authenticate` doesn't allow.
+/// The router must be served with `ConnectInfo<SocketAddr>`.
+pub async fn require(
+    State(auth): State<WebAuth>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match auth.authenticate(peer.ip(), request.headers(), request.uri().query()) {
+        Some(_) => next.run(request).await,
+        None => {
+            warn!(
+                "Refused {} {} to {}",
+                request.method(),
+                request.uri().path(),
+                peer
+            );
+            (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const LAN: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20));
+    const LOCAL: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    fn with_token() -> WebAuth {
+        WebAuth {
+            token: Some("secret".to_string()),
+            proxy: None,
+        }
+    }
+
+    fn with_proxy() -> WebAuth {
+        WebAuth {
+            token: None,
+            proxy: Some(ProxyAuth {
+                header: HeaderName::from_static("x-forwarded-user"),
+                trusted: vec![LOCAL],
+            }),
+        }
+    }
+
+    fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn anonymous_only_from_the_pi() {
+        let auth = WebAuth::default();
+        let mapped = IpAddr::V6(Ipv4Addr::LOCALHOST.to_ipv6_mapped());
+        for peer in [LOCAL, IpAddr::V6(Ipv6Addr::LOCALHOST), mapped] {
+            assert_eq!(
+                auth.authenticate(peer, &HeaderMap::new(), None).as_deref(),
+                Some("anonymous")
+            );
+        }
+        assert_eq!(auth.authenticate(LAN, &HeaderMap::new(), None), None);
+    }
+
+    #[test]
+    fn token_from_the_header_or_the_query() {
+        let auth = with_token();
+        let bearer = headers("authorization", "Bearer secret");
+        assert_eq!(
+            auth.authenticate(LAN, &bearer, None).as_deref(),
+            Some("token")
+        );
+        assert_eq!(
+            auth.authenticate(LAN, &HeaderMap::new(), Some("a=1&token=secret"))
+                .as_deref(),
+            Some("token")
+        );
+        let wrong = headers("authorization", "Bearer secreT");
+        assert_eq!(auth.authenticate(LAN, &wrong, None), None);
+        assert_eq!(auth.authenticate(LAN, &HeaderMap::new(), None), None);
+        // Configured, local clients need it too
+        assert_eq!(auth.authenticate(LOCAL, &HeaderMap::new(), None), None);
+    }
+
+    #[test]
+    fn proxy_user_only_from_the_proxy() {
+        let auth = with_proxy();
+        let user = headers("x-forwarded-user", "alice");
+        assert_eq!(
+            auth.authenticate(LOCAL, &user, None).as_deref(),
+            Some("alice")
+        );
+        assert_eq!(auth.authenticate(LAN, &user, None), None);
+        assert_eq!(auth.authenticate(LOCAL, &HeaderMap::new(), None), None);
+        let blank = headers("x-forwarded-user", " ");
+        assert_eq!(auth.authenticate(LOCAL, &blank, None), None);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::chain::Network;
+use crate::screens::SystemState;
+use crate::secrets;
+use crate::status::{hostname, status_json};
+use crate::util::current_timestamp;
+use anyhow::Result;
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Replaces the `{{key}}` of the template with the values of the state.
+/// Strings are inserted without their quotes, so that they can be part of
+/// another, and unknown keys as `null`.
+fn render(template: &str, values: &Value) -> String {
+    let mut body = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        body.push_str(&rest[..start]);
+        let key = rest[start + 2..start + end].trim();
+        match values.get(key) {
+            Some(Value::String(s)) => {
+                let quoted = Value::String(s.clone()).to_string();
+                body.push_str(&quoted[1..quoted.len() - 1]);
+            }
+            Some(value) => body.push_str(&value.to_string()),
+            None => body.push_str("null"),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    body.push_str(rest);
+    body
+}
+
+/// Pushes the state of the node to a URL, for services that can't reach the
+/// Pi behind NAT.
+pub struct Webhook {
+    pub url: String,
+    /// Sent as a bearer token
+    pub token: Option<String>,
+    /// The state as is when `None`
+    template: Option<String>,
+    interval: Duration,
+    network: Network,
+    last_push: Option<Instant>,
+}
+
+impl Webhook {
+    /// Reads `AMARU_PI_WEBHOOK_URL`, without which nothing is pushed, and
+    /// `AMARU_PI_WEBHOOK_TOKEN`, from the secrets or the environment,
+    /// `AMARU_PI_WEBHOOK_INTERVAL_SECS` and
+    /// `AMARU_PI_WEBHOOK_TEMPLATE`, the path of the JSON body to send with
+    /// placeholders such as `{{sync}}`.
+    pub fn from_env() -> Option<Self> {
+        let url = env::var("AMARU_PI_WEBHOOK_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())?;
+        let template = env::var("AMARU_PI_WEBHOOK_TEMPLATE").ok().and_then(|path| {
+            fs::read_to_string(&path)
+                .inspect_err(|e| warn!("Failed to read the webhook template {}: {}", path, e))
+                .ok()
+        });
+        Some(Self {
+            url: url.trim().to_string(),
+            token: secrets::get_or_env("AMARU_PI_WEBHOOK_TOKEN"),
+            template,
+            interval: env::var("AMARU_PI_WEBHOOK_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_INTERVAL),
+            network: Network::from_env(),
+            last_push: None,
+        })
+    }
+
+    /// The body to push, once the interval elapsed.
+    pub fn due_body(&mut self, system: &SystemState) -> Option<String> {
+        if self
+            .last_push
+            .is_some_and(|last| last.elapsed() < self.interval)
+        {
+            return None;
+        }
+        self.last_push = Some(Instant::now());
+        let mut values = status_json(system, &self.network);
+        values["host"] = hostname().into();
+        values["timestamp"] = current_timestamp().ok().into();
+        Some(match &self.template {
+            Some(template) => render(template, &values),
+            None => values.to_string(),
+        })
+    }
+}
+
+pub async fn push(url: &str, token: Option<&str>, body: String) -> Result<()> {
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .timeout(TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
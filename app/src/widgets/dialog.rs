@@ -0,0 +1,100 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use crate::i18n::tr;
+use crate::pin::Guarded;
+use crate::util::centered_rect;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    Yes,
+    No,
+    Cancel,
+}
+
+/// Asks before a destructive action, shown over the screen. A answers yes,
+/// B no and Y cancels; without an action for no, B cancels too. What is
+/// answered is run by the app, behind the PIN when there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialog {
+    pub title: String,
+    pub lines: Vec<String>,
+    yes: (String, Guarded),
+    no: Option<(String, Guarded)>,
+    pub answer: Option<Answer>,
+}
+
+impl Dialog {
+    /// `yes` labels the action, e.g. `Restore`.
+    pub fn new(
+        title: impl Into<String>,
+        lines: Vec<String>,
+        yes: impl Into<String>,
+        on_yes: Guarded,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            lines,
+            yes: (yes.into(), on_yes),
+            no: None,
+            answer: None,
+        }
+    }
+
+    /// Runs `on_no` when answered no, instead of cancelling.
+    pub fn with_no(mut self, no: impl Into<String>, on_no: Guarded) -> Self {
+        self.no = Some((no.into(), on_no));
+        self
+    }
+
+    /// The action answered for, once answered.
+    pub fn chosen(&self) -> Option<Guarded> {
+        match self.answer? {
+            Answer::Yes => Some(self.yes.1.clone()),
+            Answer::No => self.no.as_ref().map(|(_, guarded)| guarded.clone()),
+            Answer::Cancel => None,
+        }
+    }
+
+    pub fn handle_input(&mut self, event: InputEvent) {
+        if event.press_type != ButtonPress::Short {
+            return;
+        }
+        self.answer = match event.id {
+            ButtonId::A => Some(Answer::Yes),
+            ButtonId::B if self.no.is_some() => Some(Answer::No),
+            ButtonId::B | ButtonId::Y => Some(Answer::Cancel),
+            _ => None,
+        };
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let mut text: Vec<Line> = vec![Line::from("")];
+        text.extend(self.lines.iter().map(|line| Line::from(line.as_str())));
+        text.push(Line::from(""));
+        text.push(Line::from(format!("[A] {}", self.yes.0)).fg(Color::Red));
+        match &self.no {
+            Some((no, _)) => {
+                text.push(Line::from(format!("[B] {}", no)).fg(Color::Yellow));
+                text.push(Line::from(tr("[Y] Cancel")).fg(Color::Green));
+            }
+            None => text.push(Line::from(tr("[B] Cancel")).fg(Color::Green)),
+        }
+
+        let block = Block::default()
+            .title(format!(" {} ", self.title))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .title_alignment(Alignment::Center);
+
+        let area = centered_rect(80, 60, frame.area());
+
+        let paragraph = Paragraph::new(text)
+            .block(block)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+}
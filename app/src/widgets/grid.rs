@@ -0,0 +1,52 @@
+/// What moving past the first or last item does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Stays on it
+    Stop,
+    /// Goes to the other end
+    Wrap,
+}
+
+/// Moves `index` by `delta` among `len` items, never out of them. Always 0
+/// when there is none.
+pub fn step(index: usize, len: usize, delta: isize, edge: Edge) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let index = index.min(len - 1);
+    match edge {
+        Edge::Stop => index.saturating_add_signed(delta).min(len - 1),
+        Edge::Wrap => {
+            let len = len as isize;
+            (index as isize + delta % len).rem_euclid(len) as usize
+        }
+    }
+}
+
+/// Moves a `(row, column)` cursor through rows of different lengths, such
+/// as the keys of a keyboard, or a list with a single column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridNavigation {
+    pub rows: Edge,
+    pub columns: Edge,
+}
+
+impl GridNavigation {
+    pub const fn new(rows: Edge, columns: Edge) -> Self {
+        Self { rows, columns }
+    }
+
+    /// Moves `cursor` by `rows` then `columns` in `grid`. The column is kept
+    /// within the row landed on.
+    pub fn step<T>(
+        &self,
+        cursor: (usize, usize),
+        grid: &[&[T]],
+        rows: isize,
+        columns: isize,
+    ) -> (usize, usize) {
+        let row = step(cursor.0, grid.len(), rows, self.rows);
+        let len = grid.get(row).map_or(0, |keys| keys.len());
+        (row, step(cursor.1, len, columns, self.columns))
+    }
+}
@@ -0,0 +1,4 @@
+pub mod dialog;
+pub mod grid;
+pub mod pager;
+pub mod text_input;
@@ -0,0 +1,57 @@
+use crate::button::{ButtonId, ButtonPress, InputEvent};
+use ratatui::prelude::*;
+
+/// Splits a screen into pages shown one at a time, with dots telling which.
+/// Y turns to the next page and X back to the previous one. Past the last
+/// page, Y moves on to the next screen as usual.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pager {
+    page: usize,
+    count: usize,
+}
+
+impl Pager {
+    pub fn new(count: usize) -> Self {
+        Self {
+            page: 0,
+            count: count.max(1),
+        }
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Back to the first page, e.g. when entering the screen.
+    pub fn reset(&mut self) {
+        self.page = 0;
+    }
+
+    /// Returns whether the input turned the page.
+    pub fn handle_input(&mut self, event: InputEvent) -> bool {
+        match (event.id, event.press_type) {
+            (ButtonId::Y, ButtonPress::Short) if self.page + 1 < self.count => self.page += 1,
+            (ButtonId::X, ButtonPress::Short) if self.page > 0 => self.page -= 1,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draws the dots at the bottom of `area` and returns what is left for
+    /// the page.
+    pub fn render(&self, frame: &mut Frame, area: Rect) -> Rect {
+        let [page_area, dots_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+        let dots: Vec<Span> = (0..self.count)
+            .map(|page| {
+                if page == self.page {
+                    Span::raw("● ").white()
+                } else {
+                    Span::raw("○ ").dark_gray()
+                }
+            })
+            .collect();
+        frame.render_widget(Line::from(dots).centered(), dots_area);
+        page_area
+    }
+}
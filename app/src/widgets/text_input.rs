@@ -0,0 +1,146 @@
+use crate::keyboard::KeyboardAction;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+const MASK_CHAR: char = '•';
+
+type Validator = Box<dyn Fn(&str) -> Result<(), String>>;
+
+/// A single line text field edited through `KeyboardAction`s, with optional
+/// masking, maximum length and validation.
+#[derive(Default)]
+pub struct TextInput {
+    value: String,
+    /// Cursor position, in chars
+    cursor: usize,
+    masked: bool,
+    max_length: Option<usize>,
+    validator: Option<Validator>,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hides the content, e.g. for passwords.
+    pub fn masked(mut self, masked: bool) -> Self {
+        self.masked = masked;
+        self
+    }
+
+    /// Limits the number of chars that can be entered.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Checks the value, returning the message to show when invalid.
+    pub fn validator(mut self, validator: impl Fn(&str) -> Result<(), String> + 'static) -> Self {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.len();
+    }
+
+    pub fn is_masked(&self) -> bool {
+        self.masked
+    }
+
+    pub fn set_masked(&mut self, masked: bool) {
+        self.masked = masked;
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.validator {
+            Some(validator) => validator(&self.value),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies an edit from the keyboard. Returns `false` for actions that
+    /// don't concern the field, like `Exit`.
+    pub fn handle_action(&mut self, action: &KeyboardAction) -> bool {
+        match action {
+            KeyboardAction::KeyPress(chars) => chars.chars().for_each(|c| self.insert(c)),
+            KeyboardAction::Space => self.insert(' '),
+            KeyboardAction::Backspace => self.backspace(),
+            KeyboardAction::CursorLeft => self.cursor = self.cursor.saturating_sub(1),
+            KeyboardAction::CursorRight => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyboardAction::Exit => return false,
+        }
+        true
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    fn insert(&mut self, c: char) {
+        if self.max_length.is_some_and(|max| self.len() >= max) {
+            return;
+        }
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, c);
+        self.cursor += 1;
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let index = self.byte_index(self.cursor);
+        self.value.remove(index);
+    }
+
+    /// Renders the field in a bordered block. The cursor is shown while
+    /// `editing`, validation errors once something has been entered.
+    pub fn render(&self, frame: &mut Frame, area: Rect, title: &str, style: Style, editing: bool) {
+        let displayed: Vec<char> = if self.masked {
+            vec![MASK_CHAR; self.len()]
+        } else {
+            self.value.chars().collect()
+        };
+
+        let line = if editing {
+            let before: String = displayed[..self.cursor].iter().collect();
+            let at = displayed
+                .get(self.cursor)
+                .map_or(" ".to_string(), char::to_string);
+            let after: String = displayed.iter().skip(self.cursor + 1).collect();
+            Line::from(vec![
+                Span::raw(before),
+                Span::styled(at, Style::default().add_modifier(Modifier::REVERSED)),
+                Span::raw(after),
+            ])
+        } else {
+            Line::from(displayed.into_iter().collect::<String>())
+        };
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string())
+            .style(style);
+        if !self.value.is_empty()
+            && let Err(e) = self.validate()
+        {
+            block = block.title_bottom(Line::from(e).red());
+        }
+        frame.render_widget(Paragraph::new(line).block(block), area);
+    }
+}
@@ -1,9 +1,12 @@
 use anyhow::{Context, anyhow};
 use std::{
-    net::{TcpStream, ToSocketAddrs},
+    env,
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
     process::{Command, Stdio},
     time::Duration,
 };
+#[cfg(feature = "display_hat")]
+use tracing::warn;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Connectivity {
@@ -27,9 +30,54 @@ impl From<&str> for Connectivity {
     }
 }
 
+/// Which address family is tried first when a host has both, from
+/// `AMARU_PI_IP_PREFERENCE`: `ipv4`, `ipv6` or `system` (the resolver order).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IpPreference {
+    #[default]
+    System,
+    Ipv4,
+    Ipv6,
+}
+
+impl IpPreference {
+    pub fn from_env() -> Self {
+        match env::var("AMARU_PI_IP_PREFERENCE")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "ipv4" => IpPreference::Ipv4,
+            "ipv6" => IpPreference::Ipv6,
+            _ => IpPreference::System,
+        }
+    }
+
+    /// Moves the preferred family first, keeping the resolver order within
+    /// each family.
+    pub fn sort(&self, addrs: &mut [SocketAddr]) {
+        match self {
+            IpPreference::System => {}
+            IpPreference::Ipv4 => addrs.sort_by_key(SocketAddr::is_ipv6),
+            IpPreference::Ipv6 => addrs.sort_by_key(SocketAddr::is_ipv4),
+        }
+    }
+}
+
+/// Tries every address the host resolves to, so that a dual-stack host is
+/// still reachable when only one family works. IPv6 literals need brackets,
+/// e.g. `[2001:db8::1]:3001`.
 pub fn is_port_open<A: ToSocketAddrs>(addr: A) -> anyhow::Result<bool> {
     let timeout = Duration::from_secs(2);
-    Ok(TcpStream::connect_timeout(&addr.to_socket_addrs()?.next().unwrap(), timeout).is_ok())
+    let mut addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+    if addrs.is_empty() {
+        return Err(anyhow!("address did not resolve"));
+    }
+    IpPreference::from_env().sort(&mut addrs);
+    Ok(addrs
+        .iter()
+        .any(|addr| TcpStream::connect_timeout(addr, timeout).is_ok()))
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -65,6 +113,10 @@ pub struct NetworkStatus {
     pub state: NetworkState,
     pub connectivity: Connectivity,
     pub resolving: bool,
+    /// Whether the device has a global address of each family, `None` when
+    /// unknown
+    pub ipv4: Option<bool>,
+    pub ipv6: Option<bool>,
 }
 
 pub fn run_and_capture(program: &str, args: Vec<&str>) -> anyhow::Result<String> {
@@ -87,10 +139,20 @@ pub fn run_and_capture(program: &str, args: Vec<&str>) -> anyhow::Result<String>
     }
 }
 
+/// Which families the device has global addresses for, as `(ipv4, ipv6)`.
 #[cfg(feature = "display_hat")]
-pub fn check_network_status() -> anyhow::Result<NetworkStatus> {
-    use std::env;
+fn global_ip_families() -> anyhow::Result<(bool, bool)> {
+    let stdout = run_and_capture("ip", ["-o", "addr", "show", "scope", "global"].to_vec())?;
+    // e.g. `3: wlan0    inet6 2a01:e0a::1/64 scope global dynamic ...`
+    let families: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(2))
+        .collect();
+    Ok((families.contains(&"inet"), families.contains(&"inet6")))
+}
 
+#[cfg(feature = "display_hat")]
+pub fn check_network_status() -> anyhow::Result<NetworkStatus> {
     let stdout = run_and_capture(
         "nmcli",
         ["-t", "-f", "STATE,CONNECTIVITY", "general", "status"].to_vec(),
@@ -102,11 +164,20 @@ pub fn check_network_status() -> anyhow::Result<NetworkStatus> {
     }
 
     let resolving = is_port_open(env::var("AMARU_PEER_ADDRESS").unwrap_or_default())?;
+    let (ipv4, ipv6) = match global_ip_families() {
+        Ok((ipv4, ipv6)) => (Some(ipv4), Some(ipv6)),
+        Err(e) => {
+            warn!("Failed to list the global addresses: {e:#}");
+            (None, None)
+        }
+    };
 
     Ok(NetworkStatus {
         state: parts[0].into(),
         connectivity: parts[1].into(),
         resolving,
+        ipv4,
+        ipv6,
     })
 }
 
@@ -116,6 +187,8 @@ pub fn check_network_status() -> Result<NetworkStatus, Box<dyn std::error::Error
         state: NetworkState::ConnectedGlobal,
         connectivity: Connectivity::Full,
         resolving: true,
+        ipv4: Some(true),
+        ipv6: Some(true),
     })
 }
 
@@ -177,6 +250,130 @@ pub fn scan_ssids() -> anyhow::Result<Vec<String>> {
     Ok(vec![])
 }
 
+/// A WiFi network saved in NetworkManager. The available network with the
+/// highest priority is joined automatically, the others are fallbacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedNetwork {
+    pub name: String,
+    pub priority: i32,
+    pub active: bool,
+}
+
+/// Saved networks, highest priority first.
+#[cfg(feature = "display_hat")]
+pub fn list_saved_networks() -> anyhow::Result<Vec<SavedNetwork>> {
+    let stdout = run_and_capture(
+        "nmcli",
+        [
+            "-t",
+            "-f",
+            "NAME,TYPE,AUTOCONNECT-PRIORITY,ACTIVE",
+            "connection",
+            "show",
+        ]
+        .to_vec(),
+    )?;
+
+    let mut networks: Vec<SavedNetwork> = stdout
+        .lines()
+        .filter_map(|line| {
+            // Only the name can contain (escaped) colons
+            let mut fields = line.rsplitn(4, ':');
+            let active = fields.next()?;
+            let priority = fields.next()?;
+            let kind = fields.next()?;
+            let name = fields.next()?;
+            (kind == "802-11-wireless").then(|| SavedNetwork {
+                name: name.replace("\\:", ":"),
+                priority: priority.parse().unwrap_or(0),
+                active: active == "yes",
+            })
+        })
+        .collect();
+    networks.sort_by_key(|network| std::cmp::Reverse(network.priority));
+
+    Ok(networks)
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn list_saved_networks() -> anyhow::Result<Vec<SavedNetwork>> {
+    Ok(vec![])
+}
+
+/// Saves a network without joining it, replacing any network of the same
+/// name. An empty password saves an open network.
+#[cfg(feature = "display_hat")]
+pub fn add_network(ssid: &str, password: &str, priority: i32) -> anyhow::Result<()> {
+    // Ignore failure, the network may not exist yet
+    let _ = run_and_capture("nmcli", ["con", "delete", ssid].to_vec());
+
+    let priority = priority.to_string();
+    let mut args = vec![
+        "con",
+        "add",
+        "type",
+        "wifi",
+        "ifname",
+        "wlan0",
+        "con-name",
+        ssid,
+        "ssid",
+        ssid,
+        "connection.autoconnect-priority",
+        &priority,
+    ];
+    if !password.is_empty() {
+        args.extend(["wifi-sec.key-mgmt", "wpa-psk", "wifi-sec.psk", password]);
+    }
+    run_and_capture("nmcli", args)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn add_network(_ssid: &str, _password: &str, _priority: i32) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Assigns decreasing priorities to the given networks, first one preferred.
+#[cfg(feature = "display_hat")]
+pub fn set_network_order(names: &[String]) -> anyhow::Result<()> {
+    for (i, name) in names.iter().enumerate() {
+        let priority = (names.len() - i).to_string();
+        run_and_capture(
+            "nmcli",
+            [
+                "con",
+                "modify",
+                name,
+                "connection.autoconnect-priority",
+                &priority,
+            ]
+            .to_vec(),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn set_network_order(_names: &[String]) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Joins a saved network now, regardless of priorities.
+#[cfg(feature = "display_hat")]
+pub fn switch_network(name: &str) -> anyhow::Result<()> {
+    run_and_capture("nmcli", ["con", "up", name].to_vec())?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "display_hat"))]
+pub fn switch_network(_name: &str) -> anyhow::Result<()> {
+    Ok(())
+}
+
 #[cfg(feature = "display_hat")]
 const CONNECTION_NAME: &str = "mobile";
 
@@ -0,0 +1,35 @@
+use amaru_pi::app::{App, AppEvent};
+use amaru_pi::button::{ButtonId, ButtonPress, InputEvent};
+
+fn press(app: &mut App, id: ButtonId, press_type: ButtonPress) {
+    app.update(AppEvent::Input(InputEvent { id, press_type }));
+}
+
+#[test]
+fn blanking_locks_the_admin_session() {
+    // Alone in this binary, nothing else reads the environment meanwhile
+    unsafe {
+        std::env::set_var("AMARU_PI_PIN", "0000");
+        std::env::set_var("AMARU_PI_PIN_LOCK_UI", "true");
+        std::env::set_var("AMARU_PI_BLANK_AFTER_SECS", "0");
+    }
+    let mut app = App::default();
+
+    // Asks for the PIN and blanks right away
+    app.update(AppEvent::Tick);
+    assert!(app.is_blanked());
+    assert!(!app.system_state().admin.is_unlocked());
+
+    // The first press only wakes the screen
+    press(&mut app, ButtonId::A, ButtonPress::Short);
+    assert!(!app.is_blanked());
+    for _ in 0..4 {
+        press(&mut app, ButtonId::A, ButtonPress::Short);
+    }
+    press(&mut app, ButtonId::A, ButtonPress::Long);
+    assert!(app.system_state().admin.is_unlocked());
+
+    app.update(AppEvent::Tick);
+    assert!(app.is_blanked());
+    assert!(!app.system_state().admin.is_unlocked());
+}
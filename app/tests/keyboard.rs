@@ -0,0 +1,64 @@
+use amaru_pi::button::{ButtonId, ButtonPress, InputEvent};
+use amaru_pi::inputs::mapping::{Command, InputMapper, MappedInput};
+use amaru_pi::screen_text::buffer_text;
+use amaru_pi::screens::wifi_settings::WiFiSettingsScreen;
+use amaru_pi::screens::{Screen, SystemState};
+use amaru_pi::snapshot::render;
+
+fn enable_suggestions() {
+    // Every test of this binary sets the same value
+    unsafe {
+        std::env::set_var("AMARU_PI_KEYBOARD_SUGGESTIONS", "true");
+    }
+}
+
+fn press(id: ButtonId, press_type: ButtonPress) -> InputEvent {
+    InputEvent { id, press_type }
+}
+
+/// Opens the keyboard on the WiFi field `fields_down` below the SSID and
+/// types `ma`, the start of a word of the dictionary.
+fn type_in_wifi_field(fields_down: usize) -> String {
+    let system = SystemState::default();
+    let mut screen = WiFiSettingsScreen::default();
+    screen.enter();
+    for _ in 0..fields_down {
+        assert!(screen.handle_input(press(ButtonId::X, ButtonPress::Short)));
+    }
+    assert!(screen.handle_input(press(ButtonId::A, ButtonPress::Double)));
+    for c in ['m', 'a'] {
+        assert!(screen.handle_command(Command::Type(c)));
+    }
+    buffer_text(&render(&mut screen, &system))
+}
+
+#[test]
+fn only_the_password_is_typed_without_suggestions() {
+    enable_suggestions();
+    assert!(type_in_wifi_field(0).contains("mainnet"), "SSID");
+    assert!(!type_in_wifi_field(1).contains("mainnet"), "Password");
+}
+
+#[test]
+fn suggestion_chords_only_apply_while_typing() {
+    enable_suggestions();
+    let mut mapper = InputMapper::from_env();
+
+    // A goes straight through without a keyboard
+    let mapped = mapper.map(press(ButtonId::A, ButtonPress::Short));
+    assert!(matches!(mapped[..], [MappedInput::Event(e)] if e.id == ButtonId::A));
+    let mapped = mapper.map(press(ButtonId::X, ButtonPress::Short));
+    assert!(matches!(mapped[..], [MappedInput::Event(e)] if e.id == ButtonId::X));
+
+    mapper.set_typing(true);
+    assert!(
+        mapper
+            .map(press(ButtonId::A, ButtonPress::Short))
+            .is_empty()
+    );
+    let mapped = mapper.map(press(ButtonId::X, ButtonPress::Short));
+    assert!(matches!(
+        mapped[..],
+        [MappedInput::Command(Command::NextSuggestion)]
+    ));
+}
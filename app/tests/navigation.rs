@@ -0,0 +1,203 @@
+use amaru_pi::button::{ButtonId, ButtonPress, InputEvent};
+use amaru_pi::coredump::Coredump;
+use amaru_pi::frame::FrameState;
+use amaru_pi::geometry::Geometry;
+use amaru_pi::inputs::mapping::Command;
+use amaru_pi::processes::ProcessInfo;
+use amaru_pi::restore::{Snapshot, SnapshotSource};
+use amaru_pi::screen_flow::ScreenFlow;
+use amaru_pi::screens::backup::BackupScreen;
+use amaru_pi::screens::boot::BootScreen;
+use amaru_pi::screens::buttons::ButtonsScreen;
+use amaru_pi::screens::clock::ClockScreen;
+use amaru_pi::screens::coredumps::CoredumpsScreen;
+use amaru_pi::screens::language::LanguageScreen;
+use amaru_pi::screens::latency::LatencyScreen;
+use amaru_pi::screens::pools::PoolsScreen;
+use amaru_pi::screens::power::PowerScreen;
+use amaru_pi::screens::processes::ProcessesScreen;
+use amaru_pi::screens::restore::RestoreScreen;
+use amaru_pi::screens::tasks::TasksScreen;
+use amaru_pi::screens::units::UnitsScreen;
+use amaru_pi::screens::wifi_settings::WiFiSettingsScreen;
+use amaru_pi::screens::{AppContext, Kind, Screen, SystemState};
+use amaru_pi::tasks::{TaskInfo, TaskKind, TaskState};
+use proptest::prelude::*;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use std::time::Instant;
+
+/// Screens reading nothing but the system state, with lists or a keyboard
+/// to move through
+const ORDER: [Kind; 14] = [
+    Kind::Buttons,
+    Kind::Clock,
+    Kind::Coredumps,
+    Kind::Tasks,
+    Kind::Language,
+    Kind::Power,
+    Kind::Backup,
+    Kind::Restore,
+    Kind::Processes,
+    Kind::Units,
+    Kind::Latency,
+    Kind::Boot,
+    Kind::Pools,
+    Kind::WiFiSettings,
+];
+
+fn flow() -> ScreenFlow {
+    let screens: Vec<Box<dyn Screen>> = vec![
+        Box::new(ButtonsScreen::default()),
+        Box::new(ClockScreen::default()),
+        Box::new(CoredumpsScreen::default()),
+        Box::new(TasksScreen::default()),
+        Box::new(LanguageScreen::default()),
+        Box::new(PowerScreen::default()),
+        Box::new(BackupScreen::default()),
+        Box::new(RestoreScreen::default()),
+        Box::new(ProcessesScreen::default()),
+        Box::new(UnitsScreen::default()),
+        Box::new(LatencyScreen::default()),
+        Box::new(BootScreen::default()),
+        Box::new(PoolsScreen::default()),
+        Box::new(WiFiSettingsScreen::default()),
+    ];
+    ScreenFlow::new(screens, ORDER.to_vec())
+}
+
+/// With `len` items in each list, for cursors to be left past the end when
+/// they shrink.
+fn system(len: usize) -> SystemState {
+    let timestamp = 1_767_225_600;
+    SystemState {
+        coredumps: (0..len)
+            .map(|i| Coredump {
+                pid: 1000 + i as u32,
+                timestamp: timestamp + i as u64,
+                signal: 11,
+                size: Some(1 << 20),
+                present: i % 2 == 0,
+            })
+            .collect(),
+        tasks: (0..len)
+            .map(|i| TaskInfo {
+                id: i as u64,
+                kind: TaskKind::Backup,
+                state: if i == 0 {
+                    TaskState::Running
+                } else {
+                    TaskState::Failed("No USB drive".to_string())
+                },
+                progress: Some(0.5),
+                started: Instant::now(),
+            })
+            .collect(),
+        processes: (0..len)
+            .map(|i| ProcessInfo {
+                pid: 2000 + i as u32,
+                name: format!("process-{}", i),
+                state: 'S',
+                cpu_percent: 1.0,
+                rss_bytes: 1 << 20,
+                unit: None,
+            })
+            .collect(),
+        snapshots: (0..len)
+            .map(|i| Snapshot {
+                name: format!("ledger-preprod-{}.tar.gz", timestamp + i as u64),
+                source: SnapshotSource::Usb,
+                timestamp: Some(timestamp + i as u64),
+                size: Some(1 << 30),
+                sha256: None,
+            })
+            .collect(),
+        timezones: (0..len).map(|i| format!("Etc/GMT+{}", i)).collect(),
+        ..SystemState::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Press(InputEvent),
+    Command(Command),
+    /// Changes the length of the lists shown
+    Resize(usize),
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    let id = prop_oneof![
+        Just(ButtonId::A),
+        Just(ButtonId::B),
+        Just(ButtonId::X),
+        Just(ButtonId::Y),
+    ];
+    let press_type = prop_oneof![
+        Just(ButtonPress::Short),
+        Just(ButtonPress::Double),
+        Just(ButtonPress::Long),
+        Just(ButtonPress::Repeat),
+    ];
+    let command = prop_oneof![
+        Just(Command::NextScreen),
+        Just(Command::PreviousScreen),
+        Just(Command::Home),
+        Just(Command::NextSuggestion),
+        Just(Command::AcceptSuggestion),
+        Just(Command::Erase),
+        any::<char>().prop_map(Command::Type),
+    ];
+    prop_oneof![
+        8 => (id, press_type).prop_map(|(id, press_type)| Step::Press(InputEvent { id, press_type })),
+        2 => command.prop_map(Step::Command),
+        1 => (0..8usize).prop_map(Step::Resize),
+    ]
+}
+
+proptest! {
+    /// Whatever is pressed, nothing panics, the screen shown is one of the
+    /// flow, every selection stays in its list and Home goes back to the
+    /// first one, from where every screen is reached in turn.
+    #[test]
+    fn navigation_keeps_invariants(steps in prop::collection::vec(step(), 0..200)) {
+        let mut flow = flow();
+        let mut state = system(3);
+        let frame = FrameState::default();
+        let size = Geometry::default().cells();
+        let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))
+            .expect("The test backend can't fail");
+
+        for step in steps {
+            match step {
+                Step::Press(event) => {
+                    flow.handle_input(event);
+                }
+                Step::Command(command) => flow.handle_command(command),
+                Step::Resize(len) => state = system(len),
+            }
+            let ctx = AppContext { frame: &frame, system: &state };
+            // Actions are left to the app
+            let _ = flow.update(ctx);
+            terminal
+                .draw(|f| flow.display(ctx, f))
+                .expect("The test backend can't fail");
+            prop_assert!(ORDER.contains(&flow.current_screen_kind));
+            for (selected, len) in flow.cursors() {
+                prop_assert!(
+                    selected < len.max(1),
+                    "{} selected out of {} on {}",
+                    selected,
+                    len,
+                    flow.current_screen_kind
+                );
+            }
+        }
+
+        flow.handle_command(Command::Home);
+        prop_assert_eq!(flow.current_screen_kind, ORDER[0]);
+        for kind in ORDER.iter().skip(1).chain([&ORDER[0]]) {
+            flow.handle_command(Command::NextScreen);
+            prop_assert_eq!(flow.current_screen_kind, *kind);
+        }
+    }
+}
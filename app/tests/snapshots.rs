@@ -0,0 +1,241 @@
+use amaru_pi::button::{ButtonId, ButtonPress, InputEvent};
+use amaru_pi::clock::ClockStatus;
+use amaru_pi::firewall::{FirewallState, FirewallStatus};
+use amaru_pi::geometry::{Geometry, Rotation, SUPPORTED_SIZES};
+use amaru_pi::ip_config::{IpConfigState, IpMethod, IpSettings};
+use amaru_pi::processes::ProcessInfo;
+use amaru_pi::screens::buttons::ButtonsScreen;
+use amaru_pi::screens::clock::ClockScreen;
+use amaru_pi::screens::firewall::FirewallScreen;
+use amaru_pi::screens::ip_config::IpConfigScreen;
+use amaru_pi::screens::power::PowerScreen;
+use amaru_pi::screens::processes::ProcessesScreen;
+use amaru_pi::screens::units::UnitsScreen;
+use amaru_pi::screens::{Screen, SystemState};
+use amaru_pi::snapshot::{assert_snapshot, render, render_sized};
+use amaru_pi::systemd::units::{Drift, UnitStatus};
+
+fn press(id: ButtonId, press_type: ButtonPress) -> InputEvent {
+    InputEvent { id, press_type }
+}
+
+#[test]
+fn clock_before_first_check() {
+    let mut screen = ClockScreen::default();
+    screen.enter();
+    assert_snapshot(
+        "clock_before_first_check",
+        &render(&mut screen, &SystemState::default()),
+    );
+}
+
+fn unsynchronized_clock() -> SystemState {
+    SystemState {
+        clock: Some(ClockStatus {
+            ntp: true,
+            synchronized: false,
+            timezone: "Europe/Paris".to_string(),
+            server: None,
+            offset_ms: Some(812.0),
+            ..ClockStatus::default()
+        }),
+        timezones: [
+            "America/New_York",
+            "Asia/Tokyo",
+            "Europe/London",
+            "Europe/Paris",
+            "Europe/Rome",
+            "UTC",
+        ]
+        .map(str::to_string)
+        .to_vec(),
+        ..SystemState::default()
+    }
+}
+
+#[test]
+fn clock_picking_timezone() {
+    let system = unsynchronized_clock();
+    let mut screen = ClockScreen::default();
+    screen.enter();
+    // Picks up the time zones
+    render(&mut screen, &system);
+    assert!(screen.handle_input(press(ButtonId::A, ButtonPress::Double)));
+    assert!(screen.handle_input(press(ButtonId::X, ButtonPress::Short)));
+    assert_snapshot("clock_picking_timezone", &render(&mut screen, &system));
+}
+
+#[test]
+fn clock_picking_timezone_in_portrait() {
+    let system = unsynchronized_clock();
+    let geometry = Geometry {
+        panel: SUPPORTED_SIZES[0],
+        rotation: Rotation::Deg90,
+        ..Geometry::default()
+    };
+    let mut screen = ClockScreen::default();
+    screen.enter();
+    render_sized(&mut screen, &system, geometry.cells());
+    assert!(screen.handle_input(press(ButtonId::A, ButtonPress::Double)));
+    assert_snapshot(
+        "clock_picking_timezone_in_portrait",
+        &render_sized(&mut screen, &system, geometry.cells()),
+    );
+}
+
+#[test]
+fn buttons_default_timings() {
+    let mut screen = ButtonsScreen::default();
+    screen.enter();
+    assert!(screen.handle_input(press(ButtonId::X, ButtonPress::Short)));
+    assert_snapshot(
+        "buttons_default_timings",
+        &render(&mut screen, &SystemState::default()),
+    );
+}
+
+#[test]
+fn firewall_with_rules() {
+    let system = SystemState {
+        firewall: FirewallState {
+            current: Some(Ok(FirewallStatus {
+                backend: Some("ufw".to_string()),
+                active: true,
+                rules: vec![
+                    "22/tcp ALLOW IN 192.168.1.0/24".to_string(),
+                    "3001/tcp ALLOW IN Anywhere".to_string(),
+                ],
+            })),
+            applying: false,
+        },
+        ..SystemState::default()
+    };
+    let mut screen = FirewallScreen::default();
+    screen.enter();
+    assert!(screen.handle_input(press(ButtonId::X, ButtonPress::Short)));
+    assert_snapshot("firewall_with_rules", &render(&mut screen, &system));
+}
+
+#[test]
+fn firewall_not_installed() {
+    let system = SystemState {
+        firewall: FirewallState {
+            current: Some(Ok(FirewallStatus::default())),
+            applying: false,
+        },
+        ..SystemState::default()
+    };
+    let mut screen = FirewallScreen::default();
+    screen.enter();
+    assert_snapshot("firewall_not_installed", &render(&mut screen, &system));
+}
+
+#[test]
+fn firewall_applying() {
+    let system = SystemState {
+        firewall: FirewallState {
+            current: Some(Ok(FirewallStatus {
+                backend: Some("nftables".to_string()),
+                active: false,
+                rules: Vec::new(),
+            })),
+            applying: true,
+        },
+        ..SystemState::default()
+    };
+    let mut screen = FirewallScreen::default();
+    screen.enter();
+    assert_snapshot("firewall_applying", &render(&mut screen, &system));
+}
+
+#[test]
+fn ip_config_static() {
+    let system = SystemState {
+        ip_config: IpConfigState {
+            current: Some(Ok(IpSettings {
+                method: IpMethod::Static,
+                address: "192.168.1.50/24".to_string(),
+                gateway: "192.168.1.1".to_string(),
+                dns: "1.1.1.1,9.9.9.9".to_string(),
+            })),
+            applying: false,
+        },
+        ..SystemState::default()
+    };
+    let mut screen = IpConfigScreen::default();
+    screen.enter();
+    render(&mut screen, &system);
+    assert!(screen.handle_input(press(ButtonId::X, ButtonPress::Short)));
+    assert_snapshot("ip_config_static", &render(&mut screen, &system));
+}
+
+#[test]
+fn units_with_drift() {
+    let system = SystemState {
+        units: vec![
+            UnitStatus {
+                name: "amaru.service",
+                drift: Some(Drift::Modified(vec![
+                    "+Nice=-5".to_string(),
+                    "-Nice=0".to_string(),
+                ])),
+            },
+            UnitStatus {
+                name: "amaru-pi.service",
+                drift: None,
+            },
+            UnitStatus {
+                name: "updater.timer",
+                drift: Some(Drift::Missing),
+            },
+        ],
+        ..SystemState::default()
+    };
+    let mut screen = UnitsScreen::default();
+    screen.enter();
+    assert_snapshot("units_with_drift", &render(&mut screen, &system));
+}
+
+#[test]
+fn processes_by_cpu() {
+    let system = SystemState {
+        processes: vec![
+            ProcessInfo {
+                pid: 812,
+                name: "amaru".to_string(),
+                state: 'R',
+                cpu_percent: 87.5,
+                rss_bytes: 1_288_490_188,
+                unit: Some("amaru.service".to_string()),
+            },
+            ProcessInfo {
+                pid: 640,
+                name: "amaru-pi".to_string(),
+                state: 'S',
+                cpu_percent: 4.2,
+                rss_bytes: 25_165_824,
+                unit: Some("amaru-pi.service".to_string()),
+            },
+            ProcessInfo {
+                pid: 1,
+                name: "systemd".to_string(),
+                state: 'S',
+                cpu_percent: 0.0,
+                rss_bytes: 12_582_912,
+                unit: None,
+            },
+        ],
+        ..SystemState::default()
+    };
+    let mut screen = ProcessesScreen::default();
+    screen.enter();
+    assert_snapshot("processes_by_cpu", &render(&mut screen, &system));
+}
+
+#[test]
+fn power_menu() {
+    let mut screen = PowerScreen::default();
+    screen.enter();
+    assert!(screen.handle_input(press(ButtonId::X, ButtonPress::Short)));
+    assert_snapshot("power_menu", &render(&mut screen, &SystemState::default()));
+}
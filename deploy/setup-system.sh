@@ -36,7 +36,9 @@ install_dependencies() {
         libgpiod-dev \
         liblgpio-dev \
         git \
-        python3-venv
+        python3-venv \
+        systemd-coredump \
+        zstd
     echo "Dependency installation complete"
 }
 
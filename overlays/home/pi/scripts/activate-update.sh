@@ -3,16 +3,40 @@ set -euo pipefail
 
 STATE_FILE="/home/pi/.amaru_update_state.json"
 BIN_DIR="/home/pi/bin"
+SLOTS_DIR="/home/pi/slots"
 TRIGGER_FILE="/home/pi/.update_requested"
-LOCK_FILE="/tmp/amaru_update.lock"
+# Only root can write there
+RUN_DIR="/run/amaru-pi"
+LOCK_FILE="${RUN_DIR}/update.lock"
+# Checksums of the installed binaries, in sha256sum format, verified by amaru-pi
+CHECKSUMS_FILE="/home/pi/.amaru_checksums"
+# How long a service must stay up on its new binary to keep it
+HEALTH_CHECK_SECS=30
+# Updates applied, shown by amaru-pi
+HISTORY_FILE="/home/pi/.amaru_update_history.json"
+MAX_HISTORY=100
 
-declare -a MANAGED_SERVICES=("amaru-pi.service")
+declare -A APP_SERVICES
+APP_SERVICES["amaru-pi"]="amaru-pi.service"
+APP_SERVICES["amaru"]="amaru.service"
 
+mkdir -p "$RUN_DIR"
 exec 200>"$LOCK_FILE"
 flock -n 200 || { echo "ERROR: Another update is in progress."; exit 1; }
 
 log() { logger -t amaru-update "$1"; echo "$1"; }
 
+# Shared with amaru-pi, so that no other operation runs during the swap
+OPERATION_LOCK_FILE="${RUN_DIR}/operation.lock"
+OPERATION_HOLDER_FILE="${RUN_DIR}/operation.holder"
+exec 201>"$OPERATION_LOCK_FILE"
+if ! flock -n 201; then
+    log "INFO: Waiting for $(jq -r '.operation // "another operation"' "$OPERATION_HOLDER_FILE" 2>/dev/null || echo "another operation") to finish..."
+    flock 201
+fi
+printf '{"operation":"update_activation","pid":%d,"since":%d}\n' $$ "$(date +%s)" > "$OPERATION_HOLDER_FILE"
+trap 'rm -f "$OPERATION_HOLDER_FILE"' EXIT
+
 abort() {
     log "ERROR: $1"
     rm -f "$TRIGGER_FILE"
@@ -28,35 +52,155 @@ validate_state_file() {
     fi
 }
 
+# Listed in the trigger file, one per line. Without any, all staged updates
+# are applied.
+declare -a REQUESTED_APPS=()
+
+read_requested_apps() {
+    [ -f "$TRIGGER_FILE" ] || return 0
+    mapfile -t REQUESTED_APPS < <(grep -v '^[[:space:]]*$' "$TRIGGER_FILE" || true)
+    if [ "${#REQUESTED_APPS[@]}" -gt 0 ]; then
+        log "INFO: Updating ${REQUESTED_APPS[*]} only."
+    fi
+}
+
+is_requested() {
+    local app_name="$1"
+    [ "${#REQUESTED_APPS[@]}" -eq 0 ] && return 0
+    local requested
+    for requested in "${REQUESTED_APPS[@]}"; do
+        [ "$requested" == "$app_name" ] && return 0
+    done
+    return 1
+}
+
+# Applications with a staged update, whose services are restarted
+declare -a UPDATING_APPS=()
+
+find_updating_apps() {
+    local state_json="$1"
+
+    for app_name in $(echo "$state_json" | jq -r '.applications | keys[]'); do
+        is_requested "$app_name" || continue
+        local pending_version
+        pending_version=$(echo "$state_json" | jq -r ".applications[\"${app_name}\"].pending_version")
+        if [ -n "$pending_version" ]; then
+            UPDATING_APPS+=("$app_name")
+        fi
+    done
+}
+
+# amaru-pi installs its own updates, so it is usually not stopped here
 stop_services() {
-    log "INFO: Stopping all managed services..."
-    for service in "${MANAGED_SERVICES[@]}"; do
+    log "INFO: Stopping the services being updated..."
+    for app_name in "${UPDATING_APPS[@]}"; do
+        local service="${APP_SERVICES[$app_name]:-}"
+        [ -n "$service" ] || continue
         if ! systemctl stop "$service"; then
             log "WARN: Failed to stop $service. Continuing..."
         fi
     done
 }
 
-backup_and_swap_binary() {
+# Each binary in BIN_DIR is a symlink to one of two slots, a or b. Updates are
+# written to the inactive slot and the symlink is flipped with a rename, so a
+# power loss at any point leaves a complete binary in place.
+active_slot() {
     local app_name="$1"
-    local staged_path="$2"
+    if [[ "$(readlink "${BIN_DIR}/${app_name}" 2>/dev/null)" == "${SLOTS_DIR}/${app_name}/b" ]]; then
+        echo "b"
+    else
+        echo "a"
+    fi
+}
+
+switch_slot() {
+    local app_name="$1"
+    local slot="$2"
+
+    log "INFO: Switching ${app_name} to slot ${slot}..."
+    ln -sfn "${SLOTS_DIR}/${app_name}/${slot}" "${BIN_DIR}/${app_name}.link"
+    mv -T "${BIN_DIR}/${app_name}.link" "${BIN_DIR}/${app_name}"
+}
+
+install_to_slot() {
+    local app_name="$1"
+    local file="$2"
+    local slot="$3"
+    local slot_path="${SLOTS_DIR}/${app_name}/${slot}"
+
+    mkdir -p "${SLOTS_DIR}/${app_name}"
+    cp "$file" "${slot_path}.tmp"
+    chmod +x "${slot_path}.tmp"
+    sync
+    mv "${slot_path}.tmp" "$slot_path"
+    record_checksum "$slot_path"
+}
+
+# Recorded before switching to the slot, amaru-pi only checks active ones
+record_checksum() {
+    local path="$1"
+    local entry
+    entry=$(sha256sum "$path")
+    { grep -vF "  ${path}" "$CHECKSUMS_FILE" 2>/dev/null || true; echo "$entry"; } > "${CHECKSUMS_FILE}.tmp"
+    mv "${CHECKSUMS_FILE}.tmp" "$CHECKSUMS_FILE"
+}
+
+# Binaries installed before slots existed move to slot a
+migrate_to_slots() {
+    local app_name="$1"
+    local binary="${BIN_DIR}/${app_name}"
 
-    local backup_path="${BIN_DIR}/${app_name}.bak.$(date +%s)"
-    if [ -f "${BIN_DIR}/${app_name}" ]; then
-        mv "${BIN_DIR}/${app_name}" "$backup_path"
-        log "INFO: Backed up ${app_name} to ${backup_path}"
+    if [ -f "$binary" ] && [ ! -L "$binary" ]; then
+        log "INFO: Moving ${app_name} to slot a..."
+        install_to_slot "$app_name" "$binary" "a"
+        switch_slot "$app_name" "a"
     fi
+}
+
+# Installs the staged binary in the inactive slot and switches to it. Prints
+# the slot switched from.
+install_and_switch() {
+    local app_name="$1"
+    local staged_path="$2"
 
-    log "INFO: Swapping binary for ${app_name}..."
-    mv "$staged_path" "${BIN_DIR}/${app_name}"
-    chmod +x "${BIN_DIR}/${app_name}"
+    migrate_to_slots "$app_name" >&2
+    local previous_slot
+    previous_slot=$(active_slot "$app_name")
+    local next_slot="a"
+    [ "$previous_slot" == "a" ] && next_slot="b"
+
+    install_to_slot "$app_name" "$staged_path" "$next_slot"
+    rm -f "$staged_path"
+    switch_slot "$app_name" "$next_slot" >&2
+    echo "$previous_slot"
 }
 
-apply_updates_and_promote_versions() {
+# A service restarting or failing within the check means the binary is bad
+is_healthy() {
+    local app_name="$1"
+    local service="${APP_SERVICES[$app_name]:-}"
+    [ -n "$service" ] || return 0
+
+    local waited=0
+    while [ "$waited" -lt "$HEALTH_CHECK_SECS" ]; do
+        systemctl is-active --quiet "$service" || return 1
+        sleep 5
+        waited=$((waited + 5))
+    done
+}
+
+# Slot each updated application ran from before, to roll back to
+declare -A PREVIOUS_SLOTS
+NEW_STATE_JSON=""
+
+apply_updates() {
     local state_json="$1"
-    local new_state_json="$state_json" # Start with the original state
+    NEW_STATE_JSON="$state_json" # Start with the original state
 
     for app_name in $(echo "$state_json" | jq -r '.applications | keys[]'); do
+        # Left staged for a later request, e.g. updated manually
+        is_requested "$app_name" || continue
         local pending_version
         pending_version=$(echo "$state_json" | jq -r ".applications[\"${app_name}\"].pending_version")
         local staged_path
@@ -66,37 +210,91 @@ apply_updates_and_promote_versions() {
         if [ -n "$pending_version" ] && [ -n "$staged_path" ]; then
             if [ -f "$staged_path" ]; then
                 log "INFO: Applying update for ${app_name} version ${pending_version}..."
-                backup_and_swap_binary "$app_name" "$staged_path"
-                
-                # Update the JSON state for this app (promotion)
-                new_state_json=$(echo "$new_state_json" | jq \
-                    ".applications[\"${app_name}\"].current_version = \"${pending_version}\" |
-                     .applications[\"${app_name}\"].pending_version = \"\" |
-                     .applications[\"${app_name}\"].staged_path = \"\"")
+                PREVIOUS_SLOTS["$app_name"]=$(install_and_switch "$app_name" "$staged_path")
             else
                 log "WARN: Staged file ${staged_path} for ${app_name} not found. Skipping."
                 # Clear the pending state so we don't try again
-                new_state_json=$(echo "$new_state_json" | jq \
+                NEW_STATE_JSON=$(echo "$NEW_STATE_JSON" | jq \
                     ".applications[\"${app_name}\"].pending_version = \"\" |
                      .applications[\"${app_name}\"].staged_path = \"\"")
             fi
         fi
     done
-    
+}
+
+# Keeps the new binaries whose service stays up, switches the others back
+promote_or_roll_back() {
+    local state_json="$1"
+    local new_state_json="$NEW_STATE_JSON"
+
+    for app_name in "${!PREVIOUS_SLOTS[@]}"; do
+        local pending_version
+        pending_version=$(echo "$state_json" | jq -r ".applications[\"${app_name}\"].pending_version")
+        local current_version
+        current_version=$(echo "$state_json" | jq -r ".applications[\"${app_name}\"].current_version // \"\"")
+        local pending_source
+        pending_source=$(echo "$state_json" | jq -r ".applications[\"${app_name}\"].pending_source // \"\"")
+        local previous_slot="${PREVIOUS_SLOTS[$app_name]}"
+
+        # Without a previous binary there is nothing to roll back to
+        if is_healthy "$app_name" || [ ! -f "${SLOTS_DIR}/${app_name}/${previous_slot}" ]; then
+            log "INFO: Promoting ${app_name} version ${pending_version}..."
+            new_state_json=$(echo "$new_state_json" | jq \
+                ".applications[\"${app_name}\"].current_version = \"${pending_version}\" |
+                 .applications[\"${app_name}\"].slot = \"$(active_slot "$app_name")\" |
+                 .applications[\"${app_name}\"].rolled_back_version = \"\"")
+            record_history "$app_name" "$current_version" "$pending_version" "$pending_source" "applied"
+        else
+            log "ERROR: ${app_name} version ${pending_version} is unhealthy. Rolling back..."
+            switch_slot "$app_name" "$previous_slot"
+            if ! systemctl restart "${APP_SERVICES[$app_name]}"; then
+                log "ERROR: Failed to restart ${APP_SERVICES[$app_name]}. Manual intervention may be required."
+            fi
+            new_state_json=$(echo "$new_state_json" | jq \
+                ".applications[\"${app_name}\"].rolled_back_version = \"${pending_version}\"")
+            record_history "$app_name" "$current_version" "$pending_version" "$pending_source" "rolled_back"
+        fi
+
+        new_state_json=$(echo "$new_state_json" | jq \
+            ".applications[\"${app_name}\"].pending_version = \"\" |
+             .applications[\"${app_name}\"].staged_path = \"\"")
+    done
+
     # Reset notify_after and write the new state all at once
     log "INFO: Resetting update state in JSON..."
     local temp_state_file
     temp_state_file=$(mktemp)
-    
+
     echo "$new_state_json" | jq '.notify_after = 0' > "$temp_state_file"
-    
+
     mv "$temp_state_file" "$STATE_FILE"
     chown pi:pi "$STATE_FILE"
 }
 
+record_history() {
+    local app_name="$1"
+    local from_version="$2"
+    local to_version="$3"
+    local source="$4"
+    local outcome="$5"
+
+    [ -f "$HISTORY_FILE" ] && jq empty "$HISTORY_FILE" 2>/dev/null || echo "[]" > "$HISTORY_FILE"
+    local tmp_history
+    tmp_history=$(mktemp)
+    jq --arg app "$app_name" --arg from "$from_version" --arg to "$to_version" \
+       --arg source "$source" --arg outcome "$outcome" --argjson max "$MAX_HISTORY" \
+       '. + [{app: $app, from_version: $from, to_version: $to, source: $source,
+              timestamp: now | floor, outcome: $outcome}] | .[-$max:]' \
+       "$HISTORY_FILE" > "$tmp_history"
+    mv "$tmp_history" "$HISTORY_FILE"
+    chown pi:pi "$HISTORY_FILE"
+}
+
 start_services() {
-    log "INFO: Starting all managed services..."
-    for service in "${MANAGED_SERVICES[@]}"; do
+    log "INFO: Starting the services being updated..."
+    for app_name in "${UPDATING_APPS[@]}"; do
+        local service="${APP_SERVICES[$app_name]:-}"
+        [ -n "$service" ] || continue
         if ! systemctl start "$service"; then
             log "ERROR: Failed to start $service. Manual intervention may be required."
         fi
@@ -108,12 +306,15 @@ main() {
     validate_state_file
     local state_json
     state_json=$(cat "$STATE_FILE")
-    
+
+    read_requested_apps
+    find_updating_apps "$state_json"
     stop_services
-    apply_updates_and_promote_versions "$state_json"
+    apply_updates "$state_json"
     rm -f "$TRIGGER_FILE"
     start_services
-    
+    promote_or_roll_back "$state_json"
+
     log "INFO: Update activation complete."
 }
 
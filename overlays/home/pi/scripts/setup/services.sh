@@ -30,6 +30,8 @@ enable_service() {
 }
 
 reload_systemd
+# Creates /run/amaru-pi without waiting for a reboot
+run_cmd systemd-tmpfiles --create /etc/tmpfiles.d/amaru-pi.conf
 enable_service first-boot
 enable_service amaru
 enable_service amaru-pi
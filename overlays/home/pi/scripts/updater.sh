@@ -1,16 +1,33 @@
 #!/bin/bash
 set -euo pipefail
 
+# Also run by amaru-pi, not only by updater.service
+if [ -f /home/pi/amaru.env ]; then
+    set -a
+    source /home/pi/amaru.env
+    set +a
+fi
+
 STATE_FILE="/home/pi/.amaru_update_state.json"
 STAGING_DIR="/tmp"
-LOCK_FILE="/tmp/amaru_check_update.lock"
-
-declare -a BINARIES_TO_UPDATE=("amaru-pi")
-
+BIN_DIR="/home/pi/bin"
+AMARU_PI_BIN="${BIN_DIR}/amaru-pi"
+# Created by /etc/tmpfiles.d/amaru-pi.conf, writable by pi
+LOCK_FILE="/run/amaru-pi/update-check.lock"
+METERED_FLAG="/run/amaru-pi/metered"
+# Comma separated, from amaru.env: checked and reported, but only staged with
+# `updater.sh --stage <app>` and only applied on request
+MANUAL_UPDATES="${AMARU_PI_MANUAL_UPDATES:-}"
+# Application to stage even though updated manually
+STAGE_APP=""
+
+declare -a BINARIES_TO_UPDATE=("amaru-pi" "amaru" "amaru-doctor")
+
+# Only used by amaru-pi versions without `updates latest`
 declare -A GITHUB_REPOS
-# GITHUB_REPOS["amaru"]="pragma-org/amaru"
-GITHUB_REPOS["amaru-pi"]="jeluard/amaru-pi"
-# GITHUB_REPOS["amaru-doctor"]="jeluard/amaru-doctor"
+GITHUB_REPOS["amaru"]="${AMARU_REPO_OVERRIDE:-pragma-org/amaru}"
+GITHUB_REPOS["amaru-pi"]="${AMARU_PI_REPO_OVERRIDE:-jeluard/amaru-pi}"
+GITHUB_REPOS["amaru-doctor"]="${AMARU_DOCTOR_REPO_OVERRIDE:-jeluard/amaru-doctor}"
 
 exec 200>"$LOCK_FILE"
 flock -n 200 || { echo "ERROR: Another update process is running."; exit 1; }
@@ -41,10 +58,38 @@ init_state_file() {
     fi
 }
 
+# amaru-pi authenticates with AMARU_PI_GITHUB_TOKEN, from its secrets too,
+# and sends the ETag of the last response so that unchanged releases don't
+# count against the rate limit.
 fetch_latest_release_json() {
     local repo="$1"
+    if "$AMARU_PI_BIN" latest-release --help > /dev/null 2>&1; then
+        "$AMARU_PI_BIN" latest-release "$repo" 2>/dev/null || return 1
+        return
+    fi
     local api_url="https://api.github.com/repos/${repo}/releases/latest"
-    curl -s --fail --retry 3 --max-time 15 "$api_url" || return 1
+    local -a auth=()
+    [ -n "${AMARU_PI_GITHUB_TOKEN:-}" ] && auth=(-H "Authorization: Bearer ${AMARU_PI_GITHUB_TOKEN}")
+    curl -s --fail --retry 3 --max-time 15 "${auth[@]}" "$api_url" || return 1
+}
+
+# Architecture and C library of the system, e.g. armv7 with musl, picked by
+# amaru-pi. Older versions only know aarch64 with glibc.
+platform_arch() {
+    "$AMARU_PI_BIN" platform --arch 2>/dev/null || echo "aarch64"
+}
+
+select_asset() {
+    local release_json="$1"
+    local binary_name="$2"
+
+    local names
+    names=$(echo "$release_json" | jq -r '.assets[].name')
+    if "$AMARU_PI_BIN" platform --help > /dev/null 2>&1; then
+        echo "$names" | "$AMARU_PI_BIN" platform --select "$binary_name" 2>/dev/null
+    else
+        echo "$names" | grep -F "$binary_name" | grep -F "aarch64" | grep -v "musl" | grep '\.tar\.gz$' | head -n 1
+    fi
 }
 
 extract_release_info() {
@@ -53,8 +98,10 @@ extract_release_info() {
 
     local latest_version
     latest_version=$(echo "$release_json" | jq -r '.tag_name')
+    local asset_name
+    asset_name=$(select_asset "$release_json" "$binary_name") || return 1
     local download_url
-    download_url=$(echo "$release_json" | jq -r ".assets[] | select(.name | contains(\"${binary_name}\") and contains(\"aarch64\")) | .browser_download_url")
+    download_url=$(echo "$release_json" | jq -r --arg name "$asset_name" '.assets[] | select(.name == $name) | .browser_download_url')
     local checksum_url
     checksum_url=$(echo "$release_json" | jq -r ".assets[] | select(.name | endswith(\"checksums.txt\")) | .browser_download_url")
 
@@ -62,17 +109,110 @@ extract_release_info() {
     echo "${latest_version}|${download_url}|${checksum_url}"
 }
 
-verify_checksum() {
-    local binary_name="$1"
-    local archive="$2"
-    local checksum_file="$3"
+# Patch from the installed version, built with
+# `zstd -19 --long=31 --patch-from=<old binary> <new binary> -o <asset name>`
+extract_patch_url() {
+    local release_json="$1"
+    local binary_name="$2"
+    local from_version="$3"
+    local to_version="$4"
+
+    local asset_name="${binary_name}-${from_version}-to-${to_version}-$(platform_arch).zst"
+    echo "$release_json" | jq -r --arg name "$asset_name" '.assets[] | select(.name == $name) | .browser_download_url'
+}
+
+expected_checksum() {
+    local asset_name="$1"
+    local checksum_file="$2"
+
+    awk -v name="$asset_name" '{ sub(/^\*/, "", $2) } $2 == name { print $1 }' "$checksum_file"
+}
+
+# Files without a checksum aren't trusted
+verify_sha256() {
+    local file="$1"
+    local expected="$2"
 
-    local expected
-    expected=$(grep "${binary_name}" "$checksum_file" | awk '{print $1}')
     local actual
-    actual=$(sha256sum "$archive" | awk '{print $1}')
+    actual=$(sha256sum "$file" | awk '{print $1}')
 
-    [[ "$expected" == "$actual" ]]
+    [[ -n "$expected" && "$expected" == "$actual" ]]
+}
+
+# The latest release of an application as JSON: its version and source, the
+# archive and the patch from the installed version, each with its name, url
+# and sha256. amaru-pi reads the source set with AMARU_PI_UPDATE_SOURCE_<APP>,
+# a GitHub repository, a manifest URL or a directory. Older versions only
+# know GitHub.
+resolve_release() {
+    local binary_name="$1"
+    local current_version="$2"
+
+    if "$AMARU_PI_BIN" updates latest --help > /dev/null 2>&1; then
+        "$AMARU_PI_BIN" updates latest "$binary_name" --from "$current_version" 2>/dev/null
+        return
+    fi
+
+    local repo="${GITHUB_REPOS[$binary_name]}"
+    local release_json
+    release_json=$(fetch_latest_release_json "$repo") || return 1
+    local info
+    info=$(extract_release_info "$release_json" "$binary_name") || return 1
+    local latest_version download_url checksum_url
+    IFS='|' read -r latest_version download_url checksum_url <<< "$info"
+
+    local checksum_file
+    checksum_file=$(mktemp)
+    curl -sL -o "$checksum_file" "$checksum_url"
+    local archive_name
+    archive_name=$(basename "$download_url")
+    local patch_url patch_name="" patch_sha256=""
+    patch_url=$(extract_patch_url "$release_json" "$binary_name" "$current_version" "$latest_version")
+    if [ -n "$patch_url" ]; then
+        patch_name=$(basename "$patch_url")
+        patch_sha256=$(expected_checksum "$patch_name" "$checksum_file")
+    fi
+    jq -n \
+      --arg version "$latest_version" \
+      --arg source "$repo" \
+      --arg archive_name "$archive_name" \
+      --arg archive_url "$download_url" \
+      --arg archive_sha256 "$(expected_checksum "$archive_name" "$checksum_file")" \
+      --arg patch_name "$patch_name" \
+      --arg patch_url "$patch_url" \
+      --arg patch_sha256 "$patch_sha256" \
+      '{version: $version, source: $source,
+        archive: {name: $archive_name, url: $archive_url, sha256: $archive_sha256},
+        patch: (if $patch_url == "" then null else {name: $patch_name, url: $patch_url, sha256: $patch_sha256} end)}'
+    rm -f "$checksum_file"
+}
+
+# Resumable, retried and verified when amaru-pi supports it, which also
+# shows the progress on its Info screen. An interrupted download is kept in
+# "<dest>.part" and resumed by the next run.
+download() {
+    local url="$1"
+    local dest="$2"
+    local sha256="${3:-}"
+
+    if "$AMARU_PI_BIN" download --help > /dev/null 2>&1; then
+        "$AMARU_PI_BIN" download "$url" "$dest" ${sha256:+--sha256 "$sha256"}
+    else
+        curl -sL --fail -o "$dest" "$url"
+    fi
+}
+
+# Assets of a directory source are paths
+fetch_asset() {
+    local url="$1"
+    local dest="$2"
+    local sha256="$3"
+
+    if [[ "$url" == /* ]]; then
+        cp "$url" "$dest"
+    else
+        download "$url" "$dest" "$sha256"
+    fi
 }
 
 stage_binary() {
@@ -90,16 +230,89 @@ stage_binary() {
     echo "$staging_path"
 }
 
+# zstd checks the content checksum embedded in the patch, so a patch applied
+# to the wrong base fails instead of staging a corrupt binary.
+stage_binary_from_patch() {
+    local binary_name="$1"
+    local patch="$2"
+    local current_binary="${BIN_DIR}/${binary_name}"
+    local staging_path="${STAGING_DIR}/${binary_name}.new"
+
+    [ -f "$current_binary" ] || return 1
+    if ! zstd -q -d -f --long=31 --patch-from="$current_binary" "$patch" -o "$staging_path"; then
+        rm -f "$staging_path"
+        return 1
+    fi
+
+    chmod +x "$staging_path"
+    echo "$staging_path"
+}
+
+# Downloads and applies the patch from the installed version, if the release
+# has one. Returns non-zero so that the full archive is used instead.
+try_stage_from_patch() {
+    local binary_name="$1"
+    local release="$2"
+    local current_version="$3"
+
+    command -v zstd > /dev/null || return 1
+    local patch_url
+    patch_url=$(echo "$release" | jq -r '.patch.url // ""')
+    [ -n "$patch_url" ] || return 1
+
+    log "INFO: Downloading patch from ${current_version}..." >&2
+    local patch_name patch_sha256
+    patch_name=$(echo "$release" | jq -r '.patch.name')
+    patch_sha256=$(echo "$release" | jq -r '.patch.sha256 // ""')
+    local patch="/tmp/${patch_name}"
+    local staged=""
+    if fetch_asset "$patch_url" "$patch" "$patch_sha256" >&2 \
+        && verify_sha256 "$patch" "$patch_sha256"; then
+        staged=$(stage_binary_from_patch "$binary_name" "$patch") || staged=""
+    fi
+    rm -f "$patch"
+
+    [ -n "$staged" ] || { log "WARN: Patch unusable, falling back to the full archive." >&2; return 1; }
+    echo "$staged"
+}
+
+# Whether the first version is later than the second, e.g. v1.10.0 > v1.9.2
+version_gt() {
+    local a="${1#v}"
+    local b="${2#v}"
+    [[ "$a" != "$b" && "$(printf '%s\n%s\n' "$a" "$b" | sort -V | tail -n 1)" == "$a" ]]
+}
+
+is_manual() {
+    local binary_name="$1"
+    [[ ",${MANUAL_UPDATES// /}," == *",${binary_name},"* ]]
+}
+
+set_available_version() {
+    local binary_name="$1"
+    local version="$2"
+
+    local tmp_state
+    tmp_state=$(mktemp)
+    jq ".applications[\"${binary_name}\"].available_version = \"${version}\"" \
+      "$STATE_FILE" > "$tmp_state"
+    mv "$tmp_state" "$STATE_FILE"
+    chown pi:pi "$STATE_FILE"
+}
+
 update_state_file_with_pending() {
     local binary_name="$1"
     local version="$2"
     local staged_path="$3"
+    local source="$4"
 
     local tmp_state
     tmp_state=$(mktemp)
     jq \
       ".applications[\"${binary_name}\"].pending_version = \"${version}\" |
-       .applications[\"${binary_name}\"].staged_path = \"${staged_path}\"" \
+       .applications[\"${binary_name}\"].pending_source = \"${source}\" |
+       .applications[\"${binary_name}\"].staged_path = \"${staged_path}\" |
+       .applications[\"${binary_name}\"].available_version = \"\"" \
       "$STATE_FILE" > "$tmp_state"
     mv "$tmp_state" "$STATE_FILE"
     chown pi:pi "$STATE_FILE"
@@ -107,7 +320,6 @@ update_state_file_with_pending() {
 
 check_one_binary() {
     local binary_name="$1"
-    local repo="${GITHUB_REPOS[$binary_name]}"
 
     log "INFO: Checking ${binary_name}..."
 
@@ -115,35 +327,92 @@ check_one_binary() {
     current_version=$(jq -r ".applications[\"${binary_name}\"].current_version" "$STATE_FILE" 2>/dev/null || echo "v0.0.0")
     log "INFO: Current version: ${current_version}"
 
-    local release_json
-    release_json=$(fetch_latest_release_json "$repo") || { log "WARN: Failed to fetch release info."; return; }
+    local release
+    release=$(resolve_release "$binary_name" "$current_version") || { log "WARN: Failed to fetch release info for ${binary_name}."; return; }
 
-    local info
-    info=$(extract_release_info "$release_json" "$binary_name") || { log "WARN: Missing assets for ${binary_name}."; return; }
+    local latest_version source
+    latest_version=$(echo "$release" | jq -r '.version')
+    source=$(echo "$release" | jq -r '.source')
+    log "INFO: Latest version: ${latest_version} from ${source}"
 
-    IFS='|' read -r latest_version download_url checksum_url <<< "$info"
-    log "INFO: Latest version: ${latest_version}"
+    local rolled_back_version
+    rolled_back_version=$(jq -r ".applications[\"${binary_name}\"].rolled_back_version // \"\"" "$STATE_FILE")
+    [[ "$latest_version" == "$rolled_back_version" ]] && { log "WARN: ${binary_name} ${latest_version} was rolled back, waiting for a newer release."; return; }
 
-    [[ "$current_version" == "$latest_version" ]] && { log "INFO: ${binary_name} is up to date."; return; }
+    if [[ "$current_version" == "$latest_version" ]]; then
+        set_available_version "$binary_name" ""
+        log "INFO: ${binary_name} is up to date."
+        return
+    fi
 
-    log "INFO: Downloading new assets..."
-    local archive="/tmp/${binary_name}_latest.tar.gz"
-    local checksum_file="/tmp/${binary_name}_checksums.txt"
-    curl -sL -o "$archive" "$download_url"
-    curl -sL -o "$checksum_file" "$checksum_url"
+    # Staged from a USB drive with `amaru-pi updates install`, kept unless
+    # the release is newer
+    local pending_source pending_version
+    pending_source=$(jq -r ".applications[\"${binary_name}\"].pending_source // \"\"" "$STATE_FILE")
+    pending_version=$(jq -r ".applications[\"${binary_name}\"].pending_version // \"\"" "$STATE_FILE")
+    if [[ "$pending_source" == "usb" ]] && ! version_gt "$latest_version" "$pending_version"; then
+        log "INFO: ${binary_name} ${pending_version} is staged from USB."
+        return
+    fi
 
-    verify_checksum "$binary_name" "$archive" "$checksum_file" || { abort "Checksum mismatch for ${binary_name}."; }
+    # Set with `amaru-pi updates hold|pin`
+    local hold
+    hold=$(jq -r ".applications[\"${binary_name}\"].hold // false" "$STATE_FILE")
+    if [[ "$hold" == "true" ]]; then
+        set_available_version "$binary_name" "$latest_version"
+        log "INFO: ${binary_name} is held at ${current_version}, ${latest_version} not staged."
+        return
+    fi
+    local pinned_version
+    pinned_version=$(jq -r ".applications[\"${binary_name}\"].pinned_version // \"\"" "$STATE_FILE")
+    if [[ -n "$pinned_version" ]] && version_gt "$latest_version" "$pinned_version"; then
+        set_available_version "$binary_name" "$latest_version"
+        log "INFO: ${binary_name} is pinned to ${pinned_version}, ${latest_version} not staged."
+        return
+    fi
+
+    if is_manual "$binary_name" && [[ "$STAGE_APP" != "$binary_name" ]]; then
+        set_available_version "$binary_name" "$latest_version"
+        log "INFO: ${binary_name} ${latest_version} is available, stage it with: updater.sh --stage ${binary_name}"
+        return
+    fi
 
-    log "INFO: Checksum verified."
+    # Patches are much smaller than full archives on slow or metered links
     local staged
-    staged=$(stage_binary "$binary_name" "$archive")
-    rm -f "$archive" "$checksum_file"
+    if ! staged=$(try_stage_from_patch "$binary_name" "$release" "$current_version"); then
+        log "INFO: Downloading new assets..."
+        local archive_name archive_url archive_sha256
+        archive_name=$(echo "$release" | jq -r '.archive.name')
+        archive_url=$(echo "$release" | jq -r '.archive.url')
+        archive_sha256=$(echo "$release" | jq -r '.archive.sha256 // ""')
+        # Named after the release asset, so only the same version is resumed
+        local archive="/tmp/${archive_name}"
+        fetch_asset "$archive_url" "$archive" "$archive_sha256" \
+            || abort "Download failed for ${binary_name}."
+
+        verify_sha256 "$archive" "$archive_sha256" || { abort "Checksum mismatch for ${binary_name}."; }
+
+        log "INFO: Checksum verified."
+        staged=$(stage_binary "$binary_name" "$archive")
+        rm -f "$archive"
+    fi
 
-    update_state_file_with_pending "$binary_name" "$latest_version" "$staged"
+    update_state_file_with_pending "$binary_name" "$latest_version" "$staged" "$source"
     log "SUCCESS: ${binary_name} staged at ${staged} (version ${latest_version})."
 }
 
 main() {
+    if [[ "${1:-}" == "--stage" ]]; then
+        STAGE_APP="${2:-}"
+        [[ -n "${GITHUB_REPOS[$STAGE_APP]:-}" ]] || abort "Unknown application: ${STAGE_APP}"
+    fi
+
+    # Written by amaru-pi while traffic goes through a metered (LTE) link
+    if [ -f "$METERED_FLAG" ]; then
+        log "INFO: On a metered link, skipping update checks"
+        exit 0
+    fi
+
     init_state_file
     for binary in "${BINARIES_TO_UPDATE[@]}"; do
         echo "---"